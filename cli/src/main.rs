@@ -0,0 +1,184 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Command line tools for SGX Enclave Attestation
+//!
+//! ```console
+//! attestation reverify <archive>
+//! attestation capabilities
+//! ```
+
+#![deny(missing_docs, missing_debug_implementations)]
+
+use mc_attestation_verifier::{
+    capabilities, to_canonical_json, ArchiveRecord, MbedTlsCertificateChainVerifier, TrustAnchor,
+};
+use std::{env, fs, process::ExitCode};
+
+const USAGE: &str = "usage: attestation reverify <archive>\n       attestation capabilities";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["reverify", path] => match reverify(path) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(message) => {
+                eprintln!("error: {message}");
+                ExitCode::FAILURE
+            }
+        },
+        ["capabilities"] => match to_canonical_json(&capabilities()) {
+            Ok(json) => {
+                println!("{json}");
+                ExitCode::SUCCESS
+            }
+            Err(error) => {
+                eprintln!("error: {error}");
+                ExitCode::FAILURE
+            }
+        },
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Re-verify the archive at `path`, printing the transcript.
+///
+/// Returns whether the outcome and transcript match the archive.
+fn reverify(path: &str) -> Result<bool, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let record = ArchiveRecord::from_json(&json).map_err(|e| e.to_string())?;
+    let trust_anchor = TrustAnchor::try_from_der(record.trust_anchor())
+        .map_err(|e| format!("Invalid trust anchor: {e}"))?;
+    let certificate_verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+    let collateral = record
+        .collateral()
+        .to_collateral()
+        .map_err(|e| e.to_string())?;
+
+    let reverification = record
+        .reverify(certificate_verifier, &collateral)
+        .map_err(|e| e.to_string())?;
+    println!("{}", reverification.transcript());
+
+    if reverification.matches_archive() {
+        println!("Verification matches the archive");
+    } else {
+        println!(
+            "Verification DOES NOT match the archive, archived outcome: {}",
+            outcome(record.succeeded())
+        );
+        println!("{}", record.transcript());
+    }
+    Ok(reverification.matches_archive())
+}
+
+fn outcome(succeeded: bool) -> &'static str {
+    if succeeded {
+        "success"
+    } else {
+        "failure"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use der::{DateTime, DecodePem, Encode};
+    use mc_attestation_verifier::{test_utils, MrEnclave, TrustedMrEnclaveIdentity};
+    use std::path::PathBuf;
+    use x509_cert::Certificate;
+
+    const QUOTE: &[u8] = include_bytes!("../../verifier/data/tests/hw_quote.dat");
+    const ROOT_CA: &str = include_str!("../../verifier/data/tests/root_ca.pem");
+    const PROCESSOR_CA: &str = include_str!("../../verifier/data/tests/processor_ca.pem");
+    const TCB_SIGNER: &str = include_str!("../../verifier/data/tests/tcb_signer.pem");
+    const ROOT_CRL: &[u8] = include_bytes!("../../verifier/data/tests/root_crl.der");
+    const PROCESSOR_CRL: &[u8] = include_bytes!("../../verifier/data/tests/processor_crl.der");
+    const TCB_INFO_JSON: &str =
+        include_str!("../../verifier/data/tests/fmspc_00906ED50000_2023_07_12.json");
+    const QE_IDENTITY_JSON: &str = include_str!("../../verifier/data/tests/qe_identity.json");
+
+    /// Record the verification of the test quote, at the time it was valid
+    fn record() -> ArchiveRecord {
+        let tcb_chain = [TCB_SIGNER, ROOT_CA].join("\n");
+        let collateral = test_utils::collateral(
+            &[PROCESSOR_CA, ROOT_CA].join("\n"),
+            ROOT_CRL,
+            PROCESSOR_CRL,
+            &tcb_chain,
+            TCB_INFO_JSON,
+            &tcb_chain,
+            QE_IDENTITY_JSON,
+        )
+        .expect("Failed to create collateral");
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("Failed to parse root CA");
+        let root_ca = Certificate::from_pem(ROOT_CA)
+            .and_then(|cert| cert.to_der())
+            .expect("Failed to encode root CA");
+        let mr_enclave = MrEnclave::from([
+            0x84, 0x0d, 0x61, 0xb0, 0x58, 0x5d, 0xc8, 0xb4, 0xdc, 0x90, 0xf5, 0x3a, 0xf2, 0x93,
+            0xc7, 0x60, 0xfd, 0xa0, 0x6b, 0xee, 0x75, 0x97, 0x8a, 0x6a, 0x86, 0x26, 0x3f, 0xfb,
+            0x29, 0x64, 0x23, 0xf4,
+        ]);
+        let identity = TrustedMrEnclaveIdentity::new(
+            mr_enclave,
+            [] as [&str; 0],
+            ["INTEL-SA-00334", "INTEL-SA-00615"],
+        );
+        let time = "2023-07-12T20:48:25Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+
+        ArchiveRecord::record(
+            MbedTlsCertificateChainVerifier::new(trust_anchor),
+            root_ca,
+            QUOTE,
+            &collateral,
+            [identity],
+            time,
+        )
+        .expect("Failed to record")
+    }
+
+    /// Write the archive `json` to a file, unique to the test `name`
+    fn write_archive(name: &str, json: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("attestation-{name}-{}.car", std::process::id()));
+        fs::write(&path, json).expect("Failed to write archive");
+        path
+    }
+
+    #[test]
+    fn missing_archive_is_an_error() {
+        let error = reverify("does/not/exist.car").unwrap_err();
+        assert!(error.starts_with("Failed to read does/not/exist.car"));
+    }
+
+    #[test]
+    fn matching_archive_reverifies() {
+        let record = record();
+        assert!(record.succeeded());
+        let json = record.to_json().expect("Failed to serialize");
+        let path = write_archive("matching", &json);
+
+        let matches = reverify(path.to_str().expect("Non UTF-8 path"));
+
+        fs::remove_file(&path).expect("Failed to remove archive");
+        assert_eq!(matches, Ok(true));
+    }
+
+    #[test]
+    fn tampered_archive_does_not_match() {
+        let json = record().to_json().expect("Failed to serialize");
+        let tampered = json.replace(r#""succeeded":true"#, r#""succeeded":false"#);
+        assert_ne!(json, tampered);
+        let path = write_archive("tampered", &tampered);
+
+        let matches = reverify(path.to_str().expect("Non UTF-8 path"));
+
+        fs::remove_file(&path).expect("Failed to remove archive");
+        assert_eq!(matches, Ok(false));
+    }
+}
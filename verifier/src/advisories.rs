@@ -1,9 +1,13 @@
 // Copyright (c) 2023-2024 The MobileCoin Foundation
 
-use crate::{Accessor, SpacedStructName, VerificationOutput, Verifier};
+use crate::{
+    choice_to_status_message, Accessor, Error, SpacedStructName, VerificationMessage,
+    VerificationOutput, Verifier,
+};
 use alloc::{
     collections::BTreeSet,
     string::{String, ToString},
+    vec::Vec,
 };
 use core::fmt::{Display, Formatter};
 use serde::{Deserialize, Serialize};
@@ -46,6 +50,19 @@ pub enum AdvisoryStatus {
     UpToDate,
 }
 
+impl AdvisoryStatus {
+    /// Returns `true` if the platform may need additional configuration to be
+    /// trustworthy.
+    pub fn is_configuration_needed(&self) -> bool {
+        matches!(
+            self,
+            Self::ConfigurationNeeded
+                | Self::ConfigurationAndSWHardeningNeeded
+                | Self::OutOfDateConfigurationNeeded
+        )
+    }
+}
+
 /// The advisories pertaining to a TCB(Trusted Computing Base).
 #[derive(Debug, Default, Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Advisories {
@@ -69,6 +86,45 @@ impl Advisories {
         Self { ids, status }
     }
 
+    /// The status of the advisories
+    pub fn status(&self) -> AdvisoryStatus {
+        self.status
+    }
+
+    /// The IDs of the advisories, in sorted order
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.ids.iter().map(String::as_str)
+    }
+
+    /// Converge the advisories of the platform, `self`, with those of the
+    /// QE(quoting enclave) which produced the quote.
+    ///
+    /// A stale QE downgrades the platform, as the Intel QVL does:
+    /// - An `OutOfDate` QE makes an up to date platform `OutOfDate`, or
+    ///   `OutOfDateConfigurationNeeded` if the platform needs configuration.
+    /// - A `Revoked` QE makes the platform `Revoked`.
+    ///
+    /// The advisory IDs of both apply. The advisories of a TDX module, see
+    /// [`TcbInfo::tdx_module_advisories()`](crate::TcbInfo::tdx_module_advisories),
+    /// converge with the platform the same way.
+    pub fn converge_with_qe(&self, qe_advisories: &Advisories) -> Self {
+        let status = match (qe_advisories.status, self.status) {
+            (AdvisoryStatus::Revoked, _) => AdvisoryStatus::Revoked,
+            (
+                AdvisoryStatus::OutOfDate,
+                AdvisoryStatus::UpToDate | AdvisoryStatus::SWHardeningNeeded,
+            ) => AdvisoryStatus::OutOfDate,
+            (
+                AdvisoryStatus::OutOfDate,
+                AdvisoryStatus::ConfigurationNeeded
+                | AdvisoryStatus::ConfigurationAndSWHardeningNeeded,
+            ) => AdvisoryStatus::OutOfDateConfigurationNeeded,
+            (_, status) => status,
+        };
+        let ids = self.ids.union(&qe_advisories.ids).cloned().collect();
+        Self { ids, status }
+    }
+
     /// Returns `true` if `self` is a superset of `other`.
     ///
     /// This means that `self` contains at all the advisories in `other`
@@ -141,11 +197,107 @@ impl Display for AdvisoriesVerifier {
     }
 }
 
+/// Policy for the advisories a deployment tolerates.
+///
+/// An `UpToDate` TCB is always accepted. A TCB which is up to date, but needs
+/// SW hardening or configuration, is only accepted when each of its advisory
+/// IDs is approved. Any other status is rejected.
+///
+/// ```
+/// use mc_attestation_verifier::{Advisories, AdvisoryPolicy, AdvisoryStatus, Verifier};
+///
+/// let policy = AdvisoryPolicy::new(["INTEL-SA-00615"]);
+/// let advisories = Advisories::new(
+///     ["INTEL-SA-00334", "INTEL-SA-00615"],
+///     AdvisoryStatus::SWHardeningNeeded,
+/// );
+/// assert_eq!(policy.unapproved_ids(&advisories), ["INTEL-SA-00334"]);
+/// assert_eq!(policy.verify(&advisories).is_failure().unwrap_u8(), 1);
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AdvisoryPolicy {
+    approved_ids: BTreeSet<String>,
+}
+
+impl AdvisoryPolicy {
+    /// Create a new instance.
+    ///
+    /// # Arguments:
+    /// * approved_ids - The IDs of the advisories which are tolerated, these
+    ///   are of the form "INTEL-SA-12345".
+    pub fn new<'a, I, E>(approved_ids: I) -> Self
+    where
+        I: IntoIterator<Item = &'a E>,
+        E: ToString + 'a + ?Sized,
+    {
+        let approved_ids = approved_ids.into_iter().map(ToString::to_string).collect();
+        Self { approved_ids }
+    }
+
+    /// The IDs of the approved advisories, in sorted order
+    pub fn approved_ids(&self) -> impl Iterator<Item = &str> {
+        self.approved_ids.iter().map(String::as_str)
+    }
+
+    /// The IDs of the `advisories` which are not approved, in sorted order
+    pub fn unapproved_ids<'a>(&self, advisories: &'a Advisories) -> Vec<&'a str> {
+        advisories
+            .ids()
+            .filter(|id| !self.approved_ids.contains(*id))
+            .collect()
+    }
+
+    /// Why `advisories` are rejected, `None` when they are accepted
+    fn rejection(&self, advisories: &Advisories) -> Option<Error> {
+        match advisories.status() {
+            AdvisoryStatus::UpToDate => None,
+            AdvisoryStatus::SWHardeningNeeded
+            | AdvisoryStatus::ConfigurationNeeded
+            | AdvisoryStatus::ConfigurationAndSWHardeningNeeded => {
+                let unapproved = self.unapproved_ids(advisories);
+                (!unapproved.is_empty()).then(|| Error::UnapprovedAdvisories(unapproved.join(", ")))
+            }
+            status => Some(Error::TcbStatusNotAccepted(status)),
+        }
+    }
+}
+
+impl<E: Accessor<Advisories>> Verifier<E> for AdvisoryPolicy {
+    type Value = Option<Error>;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let error = self.rejection(&evidence.get());
+        let is_success = error.is_none() as u8;
+        VerificationOutput::new(error, is_success.into())
+    }
+}
+
+impl VerificationMessage<Option<Error>> for AdvisoryPolicy {
+    fn fmt_padded(
+        &self,
+        f: &mut Formatter<'_>,
+        pad: usize,
+        result: &VerificationOutput<Option<Error>>,
+    ) -> core::fmt::Result {
+        let is_success = result.is_success();
+        let status = choice_to_status_message(is_success);
+        write!(f, "{:pad$}{status} ", "")?;
+
+        match result.value() {
+            None => write!(
+                f,
+                "The advisories are approved by the advisory policy: {:?}",
+                self.approved_ids
+            ),
+            Some(error) => write!(f, "{error}"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::VerificationTreeDisplay;
-    use alloc::format;
+    use alloc::{format, vec::Vec};
 
     #[test]
     fn verifying_advisories_the_same() {
@@ -163,6 +315,93 @@ mod test {
         assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
     }
 
+    #[yare::parameterized(
+        up_to_date = { AdvisoryStatus::UpToDate, &["INTEL-SA-00334"] },
+        sw_hardening = { AdvisoryStatus::SWHardeningNeeded, &["INTEL-SA-00615"] },
+        configuration = { AdvisoryStatus::ConfigurationNeeded, &["INTEL-SA-00615"] },
+        configuration_sw_hardening = { AdvisoryStatus::ConfigurationAndSWHardeningNeeded, &["INTEL-SA-00615"] },
+        no_advisories = { AdvisoryStatus::SWHardeningNeeded, &[] },
+    )]
+    fn advisory_policy_accepts(status: AdvisoryStatus, ids: &[&str]) {
+        let policy = AdvisoryPolicy::new(["INTEL-SA-00615", "INTEL-SA-00657"]);
+        let advisories = Advisories::new(ids, status);
+        let verification = policy.verify(&advisories);
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&policy, verification);
+        let expected = r#"
+            - [x] The advisories are approved by the advisory policy: {"INTEL-SA-00615", "INTEL-SA-00657"}"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn advisory_policy_rejects_unapproved_advisory() {
+        let policy = AdvisoryPolicy::new(["INTEL-SA-00615"]);
+        let advisories = Advisories::new(
+            ["INTEL-SA-00615", "INTEL-SA-00334", "INTEL-SA-00219"],
+            AdvisoryStatus::ConfigurationAndSWHardeningNeeded,
+        );
+        assert_eq!(
+            policy.unapproved_ids(&advisories),
+            ["INTEL-SA-00219", "INTEL-SA-00334"]
+        );
+
+        let verification = policy.verify(&advisories);
+        assert_eq!(verification.is_failure().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&policy, verification);
+        let expected = r#"
+            - [ ] The advisories INTEL-SA-00219, INTEL-SA-00334 are not approved by the advisory policy"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[yare::parameterized(
+        out_of_date = { AdvisoryStatus::OutOfDate },
+        out_of_date_configuration = { AdvisoryStatus::OutOfDateConfigurationNeeded },
+        revoked = { AdvisoryStatus::Revoked },
+    )]
+    fn advisory_policy_rejects_status(status: AdvisoryStatus) {
+        let policy = AdvisoryPolicy::new(["INTEL-SA-00615"]);
+        let advisories = Advisories::new(["INTEL-SA-00615"], status);
+        let verification = policy.verify(&advisories);
+        assert_eq!(verification.is_failure().unwrap_u8(), 1);
+        assert_eq!(
+            verification.value(),
+            &Some(Error::TcbStatusNotAccepted(status))
+        );
+    }
+
+    #[test]
+    fn advisory_ids_are_sorted() {
+        let advisories = Advisories::new(
+            ["INTEL-SA-00615", "INTEL-SA-00334"],
+            AdvisoryStatus::OutOfDate,
+        );
+        assert_eq!(
+            advisories.ids().collect::<Vec<_>>(),
+            ["INTEL-SA-00334", "INTEL-SA-00615"]
+        );
+    }
+
+    #[yare::parameterized(
+        up_to_date = { AdvisoryStatus::UpToDate, AdvisoryStatus::UpToDate, AdvisoryStatus::UpToDate },
+        qe_out_of_date = { AdvisoryStatus::UpToDate, AdvisoryStatus::OutOfDate, AdvisoryStatus::OutOfDate },
+        qe_out_of_date_sw_hardening = { AdvisoryStatus::SWHardeningNeeded, AdvisoryStatus::OutOfDate, AdvisoryStatus::OutOfDate },
+        qe_out_of_date_configuration = { AdvisoryStatus::ConfigurationNeeded, AdvisoryStatus::OutOfDate, AdvisoryStatus::OutOfDateConfigurationNeeded },
+        qe_out_of_date_configuration_sw_hardening = { AdvisoryStatus::ConfigurationAndSWHardeningNeeded, AdvisoryStatus::OutOfDate, AdvisoryStatus::OutOfDateConfigurationNeeded },
+        qe_out_of_date_platform_revoked = { AdvisoryStatus::Revoked, AdvisoryStatus::OutOfDate, AdvisoryStatus::Revoked },
+        qe_revoked = { AdvisoryStatus::UpToDate, AdvisoryStatus::Revoked, AdvisoryStatus::Revoked },
+        platform_out_of_date = { AdvisoryStatus::OutOfDate, AdvisoryStatus::UpToDate, AdvisoryStatus::OutOfDate },
+    )]
+    fn converge_with_qe(platform: AdvisoryStatus, qe: AdvisoryStatus, expected: AdvisoryStatus) {
+        let platform = Advisories::new(["INTEL-SA-00334"], platform);
+        let qe = Advisories::new(["INTEL-SA-00615"], qe);
+        assert_eq!(
+            platform.converge_with_qe(&qe),
+            Advisories::new(["INTEL-SA-00334", "INTEL-SA-00615"], expected)
+        );
+    }
+
     #[test]
     fn advisories_have_one_more_when_verifying() {
         let mut advisories = Advisories::new(
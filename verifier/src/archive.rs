@@ -0,0 +1,521 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Long term archival of attestations.
+//!
+//! An [`ArchiveRecord`] contains everything that went into verifying a quote,
+//! the quote, a snapshot of the collateral, the trust anchor, and the policy,
+//! as well as what came out of it, the outcome and the transcript. This allows
+//! re-running the verification at a later time to prove the record is self
+//! contained.
+
+use crate::{
+    ffi::dcap_collateral, to_canonical_json, CertificateChainVerifier, Error, Evidence,
+    EvidenceVerifier, TrustedIdentity, VerificationTreeDisplay, Verifier,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::time::Duration;
+use der::{pem::LineEnding, DateTime, Encode, EncodePem};
+use mc_sgx_dcap_types::Collateral;
+use serde::{Deserialize, Serialize};
+use x509_cert::Certificate;
+
+/// The current version of the [`ArchiveRecord`] format.
+pub const ARCHIVE_RECORD_VERSION: u32 = 1;
+
+/// A snapshot of the [`Collateral`] used to verify a quote.
+///
+/// Certificate chains are PEM encoded, leaf first. CRLs are hex encoded DER.
+/// The TCB info and QE identity are the JSON as provided by the PCS.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CollateralSnapshot {
+    pck_crl_issuer_chain: Vec<String>,
+    #[serde(with = "hex")]
+    root_ca_crl: Vec<u8>,
+    #[serde(with = "hex")]
+    pck_crl: Vec<u8>,
+    tcb_issuer_chain: Vec<String>,
+    tcb_info: String,
+    qe_identity_issuer_chain: Vec<String>,
+    qe_identity: String,
+}
+
+fn chain_to_pem(chain: &[Certificate]) -> Result<Vec<String>, Error> {
+    Ok(chain
+        .iter()
+        .map(|cert| cert.to_pem(LineEnding::LF))
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+impl TryFrom<&Collateral> for CollateralSnapshot {
+    type Error = Error;
+
+    fn try_from(collateral: &Collateral) -> Result<Self, Self::Error> {
+        Ok(Self {
+            pck_crl_issuer_chain: chain_to_pem(collateral.pck_crl_issuer_chain())?,
+            root_ca_crl: collateral.root_ca_crl().to_der()?,
+            pck_crl: collateral.pck_crl().to_der()?,
+            tcb_issuer_chain: chain_to_pem(collateral.tcb_issuer_chain())?,
+            tcb_info: collateral.tcb_info().to_string(),
+            qe_identity_issuer_chain: chain_to_pem(collateral.qe_identity_issuer_chain())?,
+            qe_identity: collateral.qe_identity().to_string(),
+        })
+    }
+}
+
+impl CollateralSnapshot {
+    /// The PEM encoded certificate chain for the PCK CRL
+    pub fn pck_crl_issuer_chain(&self) -> &[String] {
+        &self.pck_crl_issuer_chain
+    }
+
+    /// The DER encoded root CA CRL
+    pub fn root_ca_crl(&self) -> &[u8] {
+        &self.root_ca_crl
+    }
+
+    /// The DER encoded PCK CRL
+    pub fn pck_crl(&self) -> &[u8] {
+        &self.pck_crl
+    }
+
+    /// The PEM encoded certificate chain for the TCB info
+    pub fn tcb_issuer_chain(&self) -> &[String] {
+        &self.tcb_issuer_chain
+    }
+
+    /// The TCB info JSON
+    pub fn tcb_info(&self) -> &str {
+        &self.tcb_info
+    }
+
+    /// The PEM encoded certificate chain for the QE identity
+    pub fn qe_identity_issuer_chain(&self) -> &[String] {
+        &self.qe_identity_issuer_chain
+    }
+
+    /// The QE identity JSON
+    pub fn qe_identity(&self) -> &str {
+        &self.qe_identity
+    }
+
+    /// Rebuild the [`Collateral`] from the snapshot, for
+    /// [`ArchiveRecord::reverify()`].
+    ///
+    /// # Errors
+    /// `Error::Collateral` if a part of the snapshot can not be decoded.
+    pub fn to_collateral(&self) -> Result<Collateral, Error> {
+        dcap_collateral(
+            &self.pck_crl_issuer_chain.join("\n"),
+            &self.root_ca_crl,
+            &self.pck_crl,
+            &self.tcb_issuer_chain.join("\n"),
+            &self.tcb_info,
+            &self.qe_identity_issuer_chain.join("\n"),
+            &self.qe_identity,
+        )
+    }
+}
+
+/// An archived attestation.
+///
+/// Supports de/serialization to/from JSON. Unknown JSON fields are flagged as
+/// an error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArchiveRecord {
+    version: u32,
+    #[serde(with = "hex")]
+    quote: Vec<u8>,
+    collateral: CollateralSnapshot,
+    #[serde(with = "hex")]
+    trust_anchor: Vec<u8>,
+    trusted_identities: Vec<TrustedIdentity>,
+    /// Seconds since the Unix epoch, `None` when time validation was skipped.
+    time: Option<u64>,
+    succeeded: bool,
+    transcript: String,
+}
+
+/// The result of re-running the verification of an [`ArchiveRecord`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reverification {
+    succeeded: bool,
+    transcript: String,
+    matches_archive: bool,
+}
+
+impl Reverification {
+    /// Whether the verification succeeded
+    pub fn succeeded(&self) -> bool {
+        self.succeeded
+    }
+
+    /// The transcript of the verification, see [`VerificationTreeDisplay`]
+    pub fn transcript(&self) -> &str {
+        &self.transcript
+    }
+
+    /// Whether the outcome and transcript are the same as in the archive
+    pub fn matches_archive(&self) -> bool {
+        self.matches_archive
+    }
+}
+
+impl ArchiveRecord {
+    /// Verify the `quote` and record the inputs and outcome.
+    ///
+    /// # Arguments
+    /// * `certificate_verifier` - The verifier to use for the certificate
+    ///   chains. It must use the `trust_anchor`.
+    /// * `trust_anchor` - The DER encoded trust anchor certificate.
+    /// * `quote` - The quote bytes.
+    /// * `collateral` - The collateral for the `quote`.
+    /// * `trusted_identities` - The policy of identities to trust.
+    /// * `time` - The time to verify at, see [`EvidenceVerifier::new()`].
+    ///
+    /// # Errors
+    /// When the `quote` or `collateral` can not be parsed or encoded. A failed
+    /// verification is recorded in the archive and is not an error.
+    pub fn record<C, I, ID>(
+        certificate_verifier: C,
+        trust_anchor: impl Into<Vec<u8>>,
+        quote: impl Into<Vec<u8>>,
+        collateral: &Collateral,
+        trusted_identities: I,
+        time: impl Into<Option<DateTime>>,
+    ) -> Result<Self, Error>
+    where
+        C: CertificateChainVerifier,
+        I: IntoIterator<Item = ID>,
+        ID: Into<TrustedIdentity>,
+    {
+        let quote = quote.into();
+        let time = time.into();
+        let trusted_identities = trusted_identities
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<_>>();
+        let (succeeded, transcript) = verify(
+            certificate_verifier,
+            &quote,
+            collateral,
+            &trusted_identities,
+            time,
+        )?;
+        Ok(Self {
+            version: ARCHIVE_RECORD_VERSION,
+            quote,
+            collateral: collateral.try_into()?,
+            trust_anchor: trust_anchor.into(),
+            trusted_identities,
+            time: time.map(|time| time.unix_duration().as_secs()),
+            succeeded,
+            transcript,
+        })
+    }
+
+    /// Parse an archive record from JSON
+    ///
+    /// # Errors
+    /// `Error::Serde` if the JSON is not a valid archive record.
+    /// `Error::ArchiveRecordVersion` if the record is from an unsupported
+    /// version.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let record: Self = serde_json::from_str(json)?;
+        if record.version != ARCHIVE_RECORD_VERSION {
+            return Err(Error::ArchiveRecordVersion {
+                expected: ARCHIVE_RECORD_VERSION,
+                actual: record.version,
+            });
+        }
+        Ok(record)
+    }
+
+    /// The JSON representation of the archive record
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// The canonical JSON representation of the archive record, for signing
+    /// the outcome, see [`to_canonical_json()`](crate::to_canonical_json)
+    pub fn to_canonical_json(&self) -> Result<String, Error> {
+        to_canonical_json(self)
+    }
+
+    /// Re-run the verification with the archived inputs.
+    ///
+    /// # Arguments
+    /// * `certificate_verifier` - The verifier to use for the certificate
+    ///   chains. It must use the archived [`ArchiveRecord::trust_anchor()`].
+    /// * `collateral` - The collateral rebuilt from the archived
+    ///   [`ArchiveRecord::collateral()`], see
+    ///   [`CollateralSnapshot::to_collateral()`].
+    ///
+    /// # Errors
+    /// `Error::ArchiveCollateralMismatch` if `collateral` is not the archived
+    /// collateral.
+    /// When the archived quote or `collateral` can no longer be parsed.
+    pub fn reverify<C: CertificateChainVerifier>(
+        &self,
+        certificate_verifier: C,
+        collateral: &Collateral,
+    ) -> Result<Reverification, Error> {
+        if CollateralSnapshot::try_from(collateral)? != self.collateral {
+            return Err(Error::ArchiveCollateralMismatch);
+        }
+        let time = self
+            .time
+            .map(|secs| DateTime::from_unix_duration(Duration::from_secs(secs)))
+            .transpose()?;
+        let (succeeded, transcript) = verify(
+            certificate_verifier,
+            &self.quote,
+            collateral,
+            &self.trusted_identities,
+            time,
+        )?;
+        let matches_archive = succeeded == self.succeeded && transcript == self.transcript;
+        Ok(Reverification {
+            succeeded,
+            transcript,
+            matches_archive,
+        })
+    }
+
+    /// The archived quote bytes
+    pub fn quote(&self) -> &[u8] {
+        &self.quote
+    }
+
+    /// The archived collateral
+    pub fn collateral(&self) -> &CollateralSnapshot {
+        &self.collateral
+    }
+
+    /// The archived DER encoded trust anchor certificate
+    pub fn trust_anchor(&self) -> &[u8] {
+        &self.trust_anchor
+    }
+
+    /// The archived policy of identities to trust
+    pub fn trusted_identities(&self) -> &[TrustedIdentity] {
+        &self.trusted_identities
+    }
+
+    /// The archived verification time in seconds since the Unix epoch
+    pub fn time(&self) -> Option<u64> {
+        self.time
+    }
+
+    /// Whether the archived verification succeeded
+    pub fn succeeded(&self) -> bool {
+        self.succeeded
+    }
+
+    /// The archived transcript of the verification
+    pub fn transcript(&self) -> &str {
+        &self.transcript
+    }
+}
+
+fn verify<C: CertificateChainVerifier>(
+    certificate_verifier: C,
+    quote: &[u8],
+    collateral: &Collateral,
+    trusted_identities: &[TrustedIdentity],
+    time: Option<DateTime>,
+) -> Result<(bool, String), Error> {
+    let evidence = Evidence::try_from_quote_bytes(quote, crate::Collateral::try_from(collateral)?)?;
+    let verifier = EvidenceVerifier::new(certificate_verifier, trusted_identities, time);
+    let output = verifier.verify(&evidence);
+    let succeeded = bool::from(output.is_success());
+    let transcript = VerificationTreeDisplay::new(&verifier, output).to_string();
+    Ok((succeeded, transcript))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        evidence::{
+            self,
+            test::{QE_IDENTITY_JSON, TCB_INFO_JSON},
+        },
+        verify_quote::test::AcceptingChainVerifier,
+        TrustedMrEnclaveIdentity,
+    };
+    use alloc::format;
+    use assert_matches::assert_matches;
+    use der::DecodePem;
+    use mc_sgx_core_types::MrEnclave;
+
+    const QUOTE: &[u8] = include_bytes!("../data/tests/hw_quote.dat");
+    const ROOT_CA: &str = include_str!("../data/tests/root_ca.pem");
+
+    fn collateral(tcb_info: &str) -> Collateral {
+        evidence::test::dcap_collateral(tcb_info, QE_IDENTITY_JSON)
+    }
+
+    fn trust_anchor() -> Vec<u8> {
+        Certificate::from_pem(ROOT_CA)
+            .and_then(|cert| cert.to_der())
+            .expect("Failed to encode root CA")
+    }
+
+    fn identity() -> TrustedIdentity {
+        let mr_enclave = MrEnclave::from([
+            0x84, 0x0d, 0x61, 0xb0, 0x58, 0x5d, 0xc8, 0xb4, 0xdc, 0x90, 0xf5, 0x3a, 0xf2, 0x93,
+            0xc7, 0x60, 0xfd, 0xa0, 0x6b, 0xee, 0x75, 0x97, 0x8a, 0x6a, 0x86, 0x26, 0x3f, 0xfb,
+            0x29, 0x64, 0x23, 0xf4,
+        ]);
+        TrustedMrEnclaveIdentity::new(
+            mr_enclave,
+            [] as [&str; 0],
+            ["INTEL-SA-00334", "INTEL-SA-00615"],
+        )
+        .into()
+    }
+
+    fn time() -> DateTime {
+        "2023-07-12T20:48:25Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time")
+    }
+
+    #[test]
+    fn record_and_reverify() {
+        let collateral = collateral(TCB_INFO_JSON);
+        let record = ArchiveRecord::record(
+            AcceptingChainVerifier,
+            trust_anchor(),
+            QUOTE,
+            &collateral,
+            [identity()],
+            time(),
+        )
+        .expect("Failed to record");
+        assert!(record.succeeded());
+        assert!(record
+            .transcript()
+            .starts_with("- [x] all of the following"));
+
+        let json = record.to_json().expect("Failed to serialize");
+        let record = ArchiveRecord::from_json(&json).expect("Failed to deserialize");
+
+        let reverification = record
+            .reverify(AcceptingChainVerifier, &collateral)
+            .expect("Failed to reverify");
+        assert!(reverification.succeeded());
+        assert!(reverification.matches_archive());
+        assert_eq!(reverification.transcript(), record.transcript());
+    }
+
+    #[test]
+    fn record_failed_verification() {
+        let collateral = collateral(TCB_INFO_JSON);
+        let record = ArchiveRecord::record(
+            AcceptingChainVerifier,
+            trust_anchor(),
+            QUOTE,
+            &collateral,
+            [] as [TrustedIdentity; 0],
+            time(),
+        )
+        .expect("Failed to record");
+        assert!(!record.succeeded());
+
+        let reverification = record
+            .reverify(AcceptingChainVerifier, &collateral)
+            .expect("Failed to reverify");
+        assert!(!reverification.succeeded());
+        assert!(reverification.matches_archive());
+    }
+
+    #[test]
+    fn reverify_with_other_collateral_fails() {
+        let archived = collateral(TCB_INFO_JSON);
+        let record = ArchiveRecord::record(
+            AcceptingChainVerifier,
+            trust_anchor(),
+            QUOTE,
+            &archived,
+            [identity()],
+            None,
+        )
+        .expect("Failed to record");
+        assert_eq!(record.time(), None);
+
+        let other_tcb_info = TCB_INFO_JSON.replace("2023-07-12", "2023-07-13");
+        let other = collateral(&other_tcb_info);
+        assert_eq!(
+            record.reverify(AcceptingChainVerifier, &other),
+            Err(Error::ArchiveCollateralMismatch)
+        );
+    }
+
+    #[test]
+    fn tampered_archive_does_not_match() {
+        let collateral = collateral(TCB_INFO_JSON);
+        let mut record = ArchiveRecord::record(
+            AcceptingChainVerifier,
+            trust_anchor(),
+            QUOTE,
+            &collateral,
+            [identity()],
+            time(),
+        )
+        .expect("Failed to record");
+        record.succeeded = false;
+
+        let reverification = record
+            .reverify(AcceptingChainVerifier, &collateral)
+            .expect("Failed to reverify");
+        assert!(reverification.succeeded());
+        assert!(!reverification.matches_archive());
+    }
+
+    #[test]
+    fn collateral_from_snapshot() {
+        let collateral = collateral(TCB_INFO_JSON);
+        let snapshot = CollateralSnapshot::try_from(&collateral).expect("Failed to snapshot");
+
+        assert_eq!(snapshot.to_collateral(), Ok(collateral));
+    }
+
+    #[test]
+    fn collateral_from_damaged_snapshot_fails() {
+        let mut snapshot =
+            CollateralSnapshot::try_from(&collateral(TCB_INFO_JSON)).expect("Failed to snapshot");
+        snapshot.pck_crl.truncate(10);
+
+        assert_matches!(snapshot.to_collateral(), Err(Error::Collateral(_)));
+    }
+
+    #[test]
+    fn unsupported_archive_version() {
+        let json = format!(
+            r#"{{"version":2,"quote":"","collateral":{{"pck_crl_issuer_chain":[],"root_ca_crl":"","pck_crl":"","tcb_issuer_chain":[],"tcb_info":"","qe_identity_issuer_chain":[],"qe_identity":""}},"trust_anchor":"{}","trusted_identities":[],"time":null,"succeeded":true,"transcript":""}}"#,
+            hex::encode(trust_anchor())
+        );
+        assert_eq!(
+            ArchiveRecord::from_json(&json),
+            Err(Error::ArchiveRecordVersion {
+                expected: 1,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn archive_with_unknown_field_fails() {
+        let json = r#"{"version":1,"unknown":true}"#;
+        assert!(matches!(
+            ArchiveRecord::from_json(json),
+            Err(Error::Serde(_))
+        ));
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Asynchronous client for the Intel Provisioning Certification Service (PCS).
+//!
+//! The requests and the verification of the responses are those of the
+//! [`PcsClient`], made with `reqwest` so services running on tokio don't need
+//! to move the fetches to a blocking thread.
+
+use crate::{
+    mbedtls::{Error, Result},
+    pcs::{
+        fetch_error, invalid_utf8, missing_issuer_chain, pem_chain, status_error,
+        verify_qe_identity, verify_tcb_info, ENCLAVE_IDENTITY_ISSUER_CHAIN_HEADER,
+        PCK_CRL_ISSUER_CHAIN_HEADER,
+    },
+    AsyncCollateralProvider, CertificateChainVerifier, Collateral, Fmspc, PckCaType, PckCrl,
+    PcsClient, QeIdentity, TcbInfo, TeeType,
+};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use der::{DateTime, Decode};
+use x509_cert::{crl::CertificateList, Certificate};
+
+/// An asynchronous HTTP client for the Intel PCS, or a PCCS.
+///
+/// It is configured through a [`PcsClient`], its base URL, API version,
+/// headers, and retry policy are used for the requests.
+///
+/// ```no_run
+/// use mc_attestation_verifier::{AsyncPcsClient, PckCaType, PcsClient};
+///
+/// # async fn fetch() {
+/// let client = AsyncPcsClient::from(PcsClient::with_base_url("https://pccs.internal:8081"));
+/// let pck_crl = client.fetch_pck_crl(PckCaType::Processor).await.unwrap();
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct AsyncPcsClient {
+    pcs: PcsClient,
+    client: reqwest::Client,
+}
+
+impl AsyncPcsClient {
+    /// Create a new instance for the Intel PCS
+    pub fn new() -> Self {
+        Self::from(PcsClient::new())
+    }
+
+    /// The configuration of the requests
+    pub fn pcs_client(&self) -> &PcsClient {
+        &self.pcs
+    }
+
+    /// See [`PcsClient::fetch_pck_crl()`].
+    pub async fn fetch_pck_crl(&self, ca: PckCaType) -> Result<PckCrl> {
+        let (crl, issuer_chain) = self
+            .get_with_issuer_chain(&self.pcs.pck_crl_url(ca), PCK_CRL_ISSUER_CHAIN_HEADER)
+            .await?;
+        PckCrl::from_response(&crl, &issuer_chain)
+    }
+
+    /// See [`PcsClient::fetch_tcb_info()`].
+    pub async fn fetch_tcb_info<C: CertificateChainVerifier>(
+        &self,
+        fmspc: &Fmspc,
+        tee_type: TeeType,
+        certificate_verifier: &C,
+        crls: &[CertificateList],
+        time: impl Into<Option<DateTime>>,
+    ) -> Result<TcbInfo> {
+        let (tcb_info, issuer_chain) = self
+            .get_with_issuer_chain(
+                &self.pcs.tcb_info_url(fmspc, tee_type),
+                self.pcs.tcb_info_issuer_chain_header(),
+            )
+            .await?;
+        verify_tcb_info(
+            &tcb_info,
+            &issuer_chain,
+            certificate_verifier,
+            crls,
+            time.into(),
+        )
+    }
+
+    /// See [`PcsClient::fetch_qe_identity()`].
+    pub async fn fetch_qe_identity<C: CertificateChainVerifier>(
+        &self,
+        tee_type: TeeType,
+        certificate_verifier: &C,
+        crls: &[CertificateList],
+        time: impl Into<Option<DateTime>>,
+    ) -> Result<QeIdentity> {
+        let (qe_identity, issuer_chain) = self
+            .get_with_issuer_chain(
+                &self.pcs.qe_identity_url(tee_type),
+                ENCLAVE_IDENTITY_ISSUER_CHAIN_HEADER,
+            )
+            .await?;
+        verify_qe_identity(
+            &qe_identity,
+            &issuer_chain,
+            certificate_verifier,
+            crls,
+            time.into(),
+        )
+    }
+
+    /// See [`PcsClient::fetch_qve_identity()`].
+    pub async fn fetch_qve_identity<C: CertificateChainVerifier>(
+        &self,
+        certificate_verifier: &C,
+        crls: &[CertificateList],
+        time: impl Into<Option<DateTime>>,
+    ) -> Result<QeIdentity> {
+        let (qve_identity, issuer_chain) = self
+            .get_with_issuer_chain(
+                &self.pcs.url("sgx", "qve/identity"),
+                ENCLAVE_IDENTITY_ISSUER_CHAIN_HEADER,
+            )
+            .await?;
+        verify_qe_identity(
+            &qve_identity,
+            &issuer_chain,
+            certificate_verifier,
+            crls,
+            time.into(),
+        )
+    }
+
+    /// See [`PcsClient::fetch_collateral()`].
+    pub async fn fetch_collateral(
+        &self,
+        ca: PckCaType,
+        fmspc: &Fmspc,
+        tee_type: TeeType,
+    ) -> Result<Collateral> {
+        self.collateral(ca, fmspc, tee_type).await
+    }
+
+    async fn call(&self, url: &str) -> Result<reqwest::Response> {
+        let mut retry = 0;
+        loop {
+            let error = match self
+                .pcs
+                .headers()
+                .iter()
+                .fold(self.client.get(url), |request, (name, value)| {
+                    request.header(name.as_str(), value.as_str())
+                })
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+            {
+                Ok(response) => return Ok(response),
+                Err(e) => match e.status() {
+                    Some(status) => status_error(url, status.as_u16()),
+                    None => fetch_error(url, e),
+                },
+            };
+            match self.pcs.retry_policy().delay(retry) {
+                Some(delay) if error.is_transient() => tokio::time::sleep(delay).await,
+                _ => return Err(error),
+            }
+            retry += 1;
+        }
+    }
+
+    async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self.call(url).await?;
+        read_body(url, response).await
+    }
+
+    async fn get_with_issuer_chain(&self, url: &str, header: &str) -> Result<(Vec<u8>, String)> {
+        let response = self.call(url).await?;
+        let issuer_chain = response
+            .headers()
+            .get(header)
+            .ok_or_else(|| missing_issuer_chain(url, header))?
+            .to_str()
+            .map_err(|_| missing_issuer_chain(url, header))?
+            .into();
+        Ok((read_body(url, response).await?, issuer_chain))
+    }
+}
+
+impl Default for AsyncPcsClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<PcsClient> for AsyncPcsClient {
+    fn from(pcs: PcsClient) -> Self {
+        Self {
+            pcs,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncCollateralProvider for AsyncPcsClient {
+    type Error = Error;
+
+    async fn pck_crl(&self, ca: PckCaType) -> Result<(CertificateList, Vec<Certificate>)> {
+        let (crl, issuer_chain) = self
+            .get_with_issuer_chain(&self.pcs.pck_crl_url(ca), PCK_CRL_ISSUER_CHAIN_HEADER)
+            .await?;
+        Ok((CertificateList::from_der(&crl)?, pem_chain(&issuer_chain)?))
+    }
+
+    async fn root_ca_crl(&self) -> Result<CertificateList> {
+        let crl = self.get(&self.pcs.root_ca_crl_url()?).await?;
+        Ok(CertificateList::from_der(&crl)?)
+    }
+
+    async fn tcb_info(
+        &self,
+        fmspc: &Fmspc,
+        tee_type: TeeType,
+    ) -> Result<(String, Vec<Certificate>)> {
+        let (tcb_info, issuer_chain) = self
+            .get_with_issuer_chain(
+                &self.pcs.tcb_info_url(fmspc, tee_type),
+                self.pcs.tcb_info_issuer_chain_header(),
+            )
+            .await?;
+        Ok((
+            String::from_utf8(tcb_info).map_err(|e| invalid_utf8("TCB info", e))?,
+            pem_chain(&issuer_chain)?,
+        ))
+    }
+
+    async fn qe_identity(&self, tee_type: TeeType) -> Result<(String, Vec<Certificate>)> {
+        let (qe_identity, issuer_chain) = self
+            .get_with_issuer_chain(
+                &self.pcs.qe_identity_url(tee_type),
+                ENCLAVE_IDENTITY_ISSUER_CHAIN_HEADER,
+            )
+            .await?;
+        Ok((
+            String::from_utf8(qe_identity).map_err(|e| invalid_utf8("QE identity", e))?,
+            pem_chain(&issuer_chain)?,
+        ))
+    }
+}
+
+async fn read_body(url: &str, response: reqwest::Response) -> Result<Vec<u8>> {
+    let body = response.bytes().await.map_err(|e| fetch_error(url, e))?;
+    Ok(body.to_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::PCS_API_VERSION;
+    use alloc::string::ToString;
+
+    #[test]
+    fn configured_from_pcs_client() {
+        let client = AsyncPcsClient::from(
+            PcsClient::with_base_url("https://pccs.example.com")
+                .with_api_version(3)
+                .with_header("Ocp-Apim-Subscription-Key", "key"),
+        );
+
+        let pcs = client.pcs_client();
+        assert_eq!(pcs.base_url(), "https://pccs.example.com");
+        assert_eq!(pcs.api_version(), 3);
+        assert_eq!(
+            pcs.headers(),
+            [("Ocp-Apim-Subscription-Key".to_string(), "key".to_string())]
+        );
+    }
+
+    #[test]
+    fn default_is_intel_pcs() {
+        let client = AsyncPcsClient::default();
+
+        assert_eq!(client.pcs_client().base_url(), crate::INTEL_PCS_URL);
+        assert_eq!(client.pcs_client().api_version(), PCS_API_VERSION);
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Attestation of keys held by an enclave.
+//!
+//! An enclave, or an HSM fronted by an enclave, can attest that it holds a
+//! key under specific conditions. The enclave describes the key and the
+//! conditions as [`KeyClaims`] and binds them to its quote through the
+//! REPORT_DATA. A relying party, like a certificate authority issuing a
+//! certificate for the key, verifies the evidence and the binding to get an
+//! [`AttestedKey`].
+//!
+//! The REPORT_DATA binding is the SHA-256 digest of the canonical JSON, see
+//! [`to_canonical_json()`](crate::to_canonical_json), of the claims in the
+//! first 32 bytes, followed by 32 zero bytes.
+
+use crate::{to_canonical_json, Accessor, Advisories, Error, ReportDataVerifier, Verifier};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use mc_sgx_core_types::{IsvProductId, IsvSvn, MrEnclave, MrSigner, ReportData};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The size of the digest of the claims in the REPORT_DATA
+const CLAIMS_DIGEST_SIZE: usize = 32;
+
+/// A public key and the claims an enclave makes about how it holds the
+/// private key.
+///
+/// The claims are free form names and values, for instance
+/// `"non_exportable": "true"` or the ID of the HSM slot holding the key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyClaims {
+    #[serde(with = "hex")]
+    public_key: Vec<u8>,
+    claims: BTreeMap<String, String>,
+}
+
+impl KeyClaims {
+    /// Create a new instance for the `public_key` without any claims.
+    ///
+    /// The `public_key` is expected to be a DER encoded
+    /// SubjectPublicKeyInfo, as used in certificate signing requests.
+    pub fn new(public_key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            public_key: public_key.into(),
+            claims: BTreeMap::new(),
+        }
+    }
+
+    /// Add the claim `name` with `value`, replacing an existing claim with
+    /// the same `name`
+    pub fn with_claim(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.claims.insert(name.into(), value.into());
+        self
+    }
+
+    /// The public key
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// The value of the claim `name`
+    pub fn claim(&self, name: &str) -> Option<&str> {
+        self.claims.get(name).map(String::as_str)
+    }
+
+    /// The claims, ordered by name
+    pub fn claims(&self) -> &BTreeMap<String, String> {
+        &self.claims
+    }
+
+    /// The REPORT_DATA binding the claims to a quote.
+    ///
+    /// The enclave provides this when creating its report.
+    ///
+    /// # Errors
+    /// `Error::Serde` if the claims can not be serialized.
+    pub fn report_data(&self) -> Result<ReportData, Error> {
+        let json = to_canonical_json(self)?;
+        let mut report_data = [0u8; ReportData::SIZE];
+        report_data[..CLAIMS_DIGEST_SIZE].copy_from_slice(&Sha256::digest(json.as_bytes()));
+        Ok(ReportData::from(report_data))
+    }
+}
+
+/// A key whose [`KeyClaims`] have been verified to be bound to attested
+/// evidence.
+///
+/// The identity of the enclave which made the claims is provided so the
+/// relying party can decide whether it trusts the enclave to make them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttestedKey {
+    claims: KeyClaims,
+    mr_enclave: MrEnclave,
+    mr_signer: MrSigner,
+    isv_product_id: IsvProductId,
+    isv_svn: IsvSvn,
+    advisories: Advisories,
+}
+
+impl AttestedKey {
+    /// Verify the `evidence` and that its REPORT_DATA binds the `claims`.
+    ///
+    /// # Arguments
+    /// * `evidence` - The evidence from the enclave holding the key.
+    /// * `verifier` - The verifier for the `evidence`, for instance an
+    ///   [`EvidenceVerifier`](crate::EvidenceVerifier) combined with the
+    ///   trusted identities.
+    /// * `claims` - The claims the enclave made about the key.
+    ///
+    /// # Errors
+    /// `Error::KeyAttestationEvidence` if the `verifier` fails.
+    /// `Error::KeyClaimsNotBound` if the REPORT_DATA does not bind the
+    /// `claims`.
+    /// `Error::Serde` if the `claims` can not be serialized.
+    pub fn verify<E, V>(evidence: &E, verifier: &V, claims: KeyClaims) -> Result<Self, Error>
+    where
+        E: Accessor<ReportData>
+            + Accessor<MrEnclave>
+            + Accessor<MrSigner>
+            + Accessor<IsvProductId>
+            + Accessor<IsvSvn>
+            + Accessor<Advisories>,
+        V: Verifier<E>,
+    {
+        if !bool::from(verifier.verify(evidence).is_success()) {
+            return Err(Error::KeyAttestationEvidence);
+        }
+        let binding =
+            ReportDataVerifier::new(claims.report_data()?, [0xFF; ReportData::SIZE].into());
+        if !bool::from(binding.verify(evidence).is_success()) {
+            return Err(Error::KeyClaimsNotBound);
+        }
+        Ok(Self {
+            claims,
+            mr_enclave: evidence.get(),
+            mr_signer: evidence.get(),
+            isv_product_id: evidence.get(),
+            isv_svn: evidence.get(),
+            advisories: evidence.get(),
+        })
+    }
+
+    /// The verified claims
+    pub fn claims(&self) -> &KeyClaims {
+        &self.claims
+    }
+
+    /// The attested public key
+    pub fn public_key(&self) -> &[u8] {
+        self.claims.public_key()
+    }
+
+    /// The MRENCLAVE of the enclave which made the claims
+    pub fn mr_enclave(&self) -> &MrEnclave {
+        &self.mr_enclave
+    }
+
+    /// The MRSIGNER of the enclave which made the claims
+    pub fn mr_signer(&self) -> &MrSigner {
+        &self.mr_signer
+    }
+
+    /// The ISV product ID of the enclave which made the claims
+    pub fn isv_product_id(&self) -> IsvProductId {
+        self.isv_product_id
+    }
+
+    /// The ISV SVN of the enclave which made the claims
+    pub fn isv_svn(&self) -> IsvSvn {
+        self.isv_svn
+    }
+
+    /// The advisories of the platform the enclave ran on
+    pub fn advisories(&self) -> &Advisories {
+        &self.advisories
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AdvisoryStatus, MrEnclaveVerifier};
+    use assert_matches::assert_matches;
+
+    const PUBLIC_KEY: &[u8] = &[0x30, 0x59, 0x30, 0x13];
+
+    struct TestEvidence {
+        report_data: ReportData,
+    }
+
+    impl Accessor<ReportData> for TestEvidence {
+        fn get(&self) -> ReportData {
+            self.report_data.clone()
+        }
+    }
+
+    impl Accessor<MrEnclave> for TestEvidence {
+        fn get(&self) -> MrEnclave {
+            MrEnclave::from([0xAB; 32])
+        }
+    }
+
+    impl Accessor<MrSigner> for TestEvidence {
+        fn get(&self) -> MrSigner {
+            MrSigner::from([0x01; 32])
+        }
+    }
+
+    impl Accessor<IsvProductId> for TestEvidence {
+        fn get(&self) -> IsvProductId {
+            IsvProductId::from(2)
+        }
+    }
+
+    impl Accessor<IsvSvn> for TestEvidence {
+        fn get(&self) -> IsvSvn {
+            IsvSvn::from(3)
+        }
+    }
+
+    impl Accessor<Advisories> for TestEvidence {
+        fn get(&self) -> Advisories {
+            Advisories::new(["INTEL-SA-00334"], AdvisoryStatus::SWHardeningNeeded)
+        }
+    }
+
+    fn claims() -> KeyClaims {
+        KeyClaims::new(PUBLIC_KEY)
+            .with_claim("non_exportable", "true")
+            .with_claim("hsm_slot", "7")
+    }
+
+    #[test]
+    fn report_data_is_digest_of_canonical_claims() {
+        let json = r#"{"claims":{"hsm_slot":"7","non_exportable":"true"},"public_key":"30593013"}"#;
+        let mut expected = [0u8; ReportData::SIZE];
+        expected[..CLAIMS_DIGEST_SIZE].copy_from_slice(&Sha256::digest(json.as_bytes()));
+
+        assert_eq!(claims().report_data(), Ok(ReportData::from(expected)));
+    }
+
+    #[test]
+    fn claims_accessors() {
+        let claims = claims();
+        assert_eq!(claims.public_key(), PUBLIC_KEY);
+        assert_eq!(claims.claim("hsm_slot"), Some("7"));
+        assert_eq!(claims.claim("missing"), None);
+        assert_eq!(claims.claims().len(), 2);
+    }
+
+    #[test]
+    fn verify_attested_key() {
+        let evidence = TestEvidence {
+            report_data: claims().report_data().expect("Failed to bind claims"),
+        };
+        let verifier = MrEnclaveVerifier::new(MrEnclave::from([0xAB; 32]));
+
+        let key = AttestedKey::verify(&evidence, &verifier, claims()).expect("Failed to verify");
+
+        assert_eq!(key.public_key(), PUBLIC_KEY);
+        assert_eq!(key.claims().claim("non_exportable"), Some("true"));
+        assert_eq!(key.mr_enclave(), &MrEnclave::from([0xAB; 32]));
+        assert_eq!(key.mr_signer(), &MrSigner::from([0x01; 32]));
+        assert_eq!(key.isv_product_id(), IsvProductId::from(2));
+        assert_eq!(key.isv_svn(), IsvSvn::from(3));
+        assert_eq!(key.advisories().status(), AdvisoryStatus::SWHardeningNeeded);
+    }
+
+    #[test]
+    fn failed_evidence_is_not_attested() {
+        let evidence = TestEvidence {
+            report_data: claims().report_data().expect("Failed to bind claims"),
+        };
+        let verifier = MrEnclaveVerifier::new(MrEnclave::from([0xCD; 32]));
+
+        assert_matches!(
+            AttestedKey::verify(&evidence, &verifier, claims()),
+            Err(Error::KeyAttestationEvidence)
+        );
+    }
+
+    #[test]
+    fn other_claims_are_not_bound() {
+        let evidence = TestEvidence {
+            report_data: claims().report_data().expect("Failed to bind claims"),
+        };
+        let verifier = MrEnclaveVerifier::new(MrEnclave::from([0xAB; 32]));
+        let exportable = claims().with_claim("non_exportable", "false");
+
+        assert_matches!(
+            AttestedKey::verify(&evidence, &verifier, exportable),
+            Err(Error::KeyClaimsNotBound)
+        );
+    }
+}
@@ -0,0 +1,291 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! JSON Canonicalization Scheme (JCS), as specified in
+//! [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785).
+//!
+//! Signatures over JSON payloads, like policies or verification outcomes,
+//! need the exact bytes to be reproducible. The output of a JSON serializer
+//! can differ between versions and languages, in the order of the object
+//! members, the escaping of strings, and the formatting of numbers. The
+//! canonical form removes these differences so the signer and the verifier
+//! produce the same bytes from the same data.
+
+use crate::Error;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write;
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+/// The largest integer which can be represented exactly by an IEEE 754
+/// double, which is how JCS represents numbers
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Serialize the `value` to canonical JSON, see [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785).
+///
+/// # Errors
+/// `Error::Serde` if the `value` can not be serialized to JSON.
+/// `Error::CanonicalJson` if the `value` has an integer which can not be
+/// represented exactly as a double.
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    let value = serde_json::to_value(value)?;
+    let mut json = String::new();
+    write_value(&mut json, &value)?;
+    Ok(json)
+}
+
+/// Canonicalize the `json` text, see [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785).
+///
+/// Useful to verify a signature over a payload which was received in a
+/// non-canonical form. When an object has duplicate member names, which JCS
+/// does not allow, the last one is kept.
+///
+/// # Errors
+/// `Error::Serde` if the `json` is not valid JSON.
+/// `Error::CanonicalJson` if the `json` has an integer which can not be
+/// represented exactly as a double.
+pub fn canonicalize_json(json: &str) -> Result<String, Error> {
+    let value: Value = serde_json::from_str(json)?;
+    to_canonical_json(&value)
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<(), Error> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+        Value::Number(number) => write_number(out, number)?,
+        Value::String(string) => write_string(out, string),
+        Value::Array(values) => {
+            out.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index != 0 {
+                    out.push(',');
+                }
+                write_value(out, value)?;
+            }
+            out.push(']');
+        }
+        Value::Object(members) => {
+            // Members are sorted by the UTF-16 code units of their names
+            let mut members = members.iter().collect::<Vec<_>>();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (index, (name, value)) in members.into_iter().enumerate() {
+                if index != 0 {
+                    out.push(',');
+                }
+                write_string(out, name);
+                out.push(':');
+                write_value(out, value)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn write_number(out: &mut String, number: &Number) -> Result<(), Error> {
+    let unsafe_integer = || Error::CanonicalJson(format!("{number} is not a safe integer"));
+    if let Some(integer) = number.as_u64() {
+        if integer > MAX_SAFE_INTEGER {
+            return Err(unsafe_integer());
+        }
+        out.push_str(&integer.to_string());
+    } else if let Some(integer) = number.as_i64() {
+        if integer.unsigned_abs() > MAX_SAFE_INTEGER {
+            return Err(unsafe_integer());
+        }
+        out.push_str(&integer.to_string());
+    } else {
+        let double = number
+            .as_f64()
+            .ok_or_else(|| Error::CanonicalJson(format!("{number} is not a number")))?;
+        write_double(out, double)?;
+    }
+    Ok(())
+}
+
+/// Write the `double` like ECMAScript's `Number.prototype.toString()`, see
+/// section 3.2.2.3 of RFC 8785.
+fn write_double(out: &mut String, double: f64) -> Result<(), Error> {
+    if !double.is_finite() {
+        return Err(Error::CanonicalJson(format!("{double} is not finite")));
+    }
+    // Covers negative zero too
+    if double == 0.0 {
+        out.push('0');
+        return Ok(());
+    }
+    if double.is_sign_negative() {
+        out.push('-');
+    }
+
+    // The shortest digits which round trip, `d.ddde<exponent>`
+    let scientific = format!("{:e}", double.abs());
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("Scientific notation always has an exponent");
+    let digits = mantissa.replace('.', "");
+    let exponent = exponent
+        .parse::<i32>()
+        .expect("Scientific notation exponent is an integer");
+
+    // With `digits` as an integer, the value is `digits * 10^(point - k)`
+    let k = digits.len() as i32;
+    let point = exponent + 1;
+    if k <= point && point <= 21 {
+        out.push_str(&digits);
+        out.extend((0..point - k).map(|_| '0'));
+    } else if 0 < point && point <= 21 {
+        let (integer, fraction) = digits.split_at(point as usize);
+        let _ = write!(out, "{integer}.{fraction}");
+    } else if -6 < point && point <= 0 {
+        out.push_str("0.");
+        out.extend((0..-point).map(|_| '0'));
+        out.push_str(&digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        out.push_str(first);
+        if !rest.is_empty() {
+            let _ = write!(out, ".{rest}");
+        }
+        let sign = if point > 0 { '+' } else { '-' };
+        let _ = write!(out, "e{sign}{}", (point - 1).abs());
+    }
+    Ok(())
+}
+
+fn write_string(out: &mut String, string: &str) {
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use serde::Serialize;
+    use yare::parameterized;
+
+    fn double(double: f64) -> String {
+        let mut out = String::new();
+        write_double(&mut out, double).expect("Failed to write double");
+        out
+    }
+
+    // From Appendix B of RFC 8785
+    #[parameterized(
+        zero = { 0.0, "0" },
+        negative_zero = { -0.0, "0" },
+        min_positive = { 5e-324, "5e-324" },
+        max = { 1.7976931348623157e308, "1.7976931348623157e+308" },
+        negative_max = { -1.7976931348623157e308, "-1.7976931348623157e+308" },
+        two_to_the_53 = { 9007199254740992.0, "9007199254740992" },
+        large_integer = { 295147905179352830000.0, "295147905179352830000" },
+        exponent_threshold = { 1e21, "1e+21" },
+        below_exponent_threshold = { 9.999999999999997e22, "9.999999999999997e+22" },
+        fraction = { 333333333.3333333, "333333333.3333333" },
+        small_fraction = { 0.000001, "0.000001" },
+        small_exponent = { 1e-7, "1e-7" },
+        small_negative_exponent = { -1.5e-7, "-1.5e-7" },
+        one_and_a_half = { 1.5, "1.5" },
+    )]
+    fn double_is_formatted(value: f64, expected: &str) {
+        assert_eq!(double(value), expected);
+    }
+
+    #[test]
+    fn non_finite_double_fails() {
+        let mut out = String::new();
+        assert_matches!(
+            write_double(&mut out, f64::NAN),
+            Err(Error::CanonicalJson(_))
+        );
+        assert_matches!(
+            write_double(&mut out, f64::INFINITY),
+            Err(Error::CanonicalJson(_))
+        );
+    }
+
+    #[test]
+    fn members_are_sorted_by_utf16() {
+        // From section 3.2.3 of RFC 8785. The emoji sorts before U+FB33 in
+        // UTF-16, but after it in UTF-8.
+        let json = r#"{
+            "\u20ac": "Euro Sign",
+            "\r": "Carriage Return",
+            "\ufb33": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\ud83d\ude00": "Emoji: Grinning Face",
+            "\u0080": "Control",
+            "\u00f6": "Latin Small Letter O With Diaeresis"
+        }"#;
+        let expected = "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\
+            \"\u{f6}\":\"Latin Small Letter O With Diaeresis\",\"\u{20ac}\":\"Euro Sign\",\
+            \"\u{1f600}\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}";
+        assert_eq!(canonicalize_json(json), Ok(expected.into()));
+    }
+
+    #[test]
+    fn rfc_example() {
+        // From section 3.2.2 of RFC 8785
+        let json = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        let expected = "{\"literals\":[null,true,false],\
+            \"numbers\":[333333333.3333333,1e+30,4.5,0.002,1e-27],\
+            \"string\":\"\u{20ac}$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\"}";
+        assert_eq!(canonicalize_json(json), Ok(expected.into()));
+    }
+
+    #[test]
+    fn serialized_value_is_canonical() {
+        #[derive(Serialize)]
+        struct Payload {
+            zebra: u32,
+            apple: Vec<i32>,
+        }
+        let payload = Payload {
+            zebra: 1,
+            apple: vec![-2, 3],
+        };
+        assert_eq!(
+            to_canonical_json(&payload),
+            Ok(r#"{"apple":[-2,3],"zebra":1}"#.into())
+        );
+    }
+
+    #[parameterized(
+        positive = { "9007199254740992" },
+        negative = { "-9007199254740992" },
+    )]
+    fn unsafe_integer_fails(json: &str) {
+        assert_matches!(canonicalize_json(json), Err(Error::CanonicalJson(_)));
+    }
+
+    #[test]
+    fn invalid_json_fails() {
+        assert_matches!(canonicalize_json("{"), Err(Error::Serde(_)));
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Introspection of what this build of the verifier supports.
+//!
+//! The supported platforms, backends, and formats depend on the features the
+//! crate was built with. Orchestrators can check a deployment at runtime,
+//! for instance with `attestation capabilities`, instead of trusting the
+//! build configuration.
+
+use crate::{
+    qe_identity::QE_IDENTITY_VERSION, tcb::SUPPORTED_TCB_INFO_VERSIONS,
+    verify_quote::VERIFIABLE_QUOTE_VERSION, ARCHIVE_RECORD_VERSION,
+};
+use alloc::{vec, vec::Vec};
+use serde::Serialize;
+
+/// What this build of the verifier supports, see [`capabilities()`].
+///
+/// Serializes to JSON, via [`to_canonical_json()`](crate::to_canonical_json),
+/// with the field names as keys. New fields may be added in minor releases.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// The version of the verifier crate
+    pub version: &'static str,
+    /// The crate features which were enabled
+    pub features: Vec<&'static str>,
+    /// The TEE platforms whose evidence can be verified
+    pub platforms: Vec<&'static str>,
+    /// The certificate chain verification backends
+    pub backends: Vec<&'static str>,
+    /// The quote versions which can be verified
+    pub quote_versions: Vec<u16>,
+    /// The TCB info versions which can be verified
+    pub tcb_info_versions: Vec<u32>,
+    /// The QE identity versions which can be verified
+    pub qe_identity_versions: Vec<u32>,
+    /// The archive record versions which can be re-verified
+    pub archive_record_versions: Vec<u32>,
+    /// The formats certificate chains and CRLs can be read from
+    pub certificate_formats: Vec<&'static str>,
+}
+
+impl Capabilities {
+    /// Whether the crate `feature` was enabled
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.contains(&feature)
+    }
+
+    /// Whether evidence from the TEE `platform` can be verified
+    pub fn supports_platform(&self, platform: &str) -> bool {
+        self.platforms.contains(&platform)
+    }
+
+    /// Whether the certificate chain verification `backend` is available
+    pub fn has_backend(&self, backend: &str) -> bool {
+        self.backends.contains(&backend)
+    }
+}
+
+/// The capabilities of this build of the verifier
+pub fn capabilities() -> Capabilities {
+    let mut features = Vec::new();
+    let mut platforms = vec!["sgx"];
+    let mut backends = Vec::new();
+    let mut certificate_formats = vec!["der", "pem"];
+
+    if cfg!(feature = "std") {
+        features.push("std");
+    }
+    if cfg!(feature = "mbedtls") {
+        features.push("mbedtls");
+        backends.push("mbedtls");
+        certificate_formats.extend(["base64", "pem-bundle", "pkcs7"]);
+    }
+    if cfg!(feature = "native-roots") {
+        features.push("native-roots");
+    }
+    if cfg!(feature = "pcs") {
+        features.push("pcs");
+    }
+    if cfg!(feature = "serde") {
+        features.push("serde");
+    }
+    if cfg!(feature = "tokio") {
+        features.push("tokio");
+    }
+    if cfg!(feature = "unstable") {
+        features.push("unstable");
+        platforms.push("cove");
+    }
+
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        features,
+        platforms,
+        backends,
+        quote_versions: vec![VERIFIABLE_QUOTE_VERSION],
+        tcb_info_versions: SUPPORTED_TCB_INFO_VERSIONS.to_vec(),
+        qe_identity_versions: vec![QE_IDENTITY_VERSION],
+        archive_record_versions: vec![ARCHIVE_RECORD_VERSION],
+        certificate_formats,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::to_canonical_json;
+
+    #[test]
+    fn sgx_is_always_supported() {
+        let capabilities = capabilities();
+        assert!(capabilities.supports_platform("sgx"));
+        assert!(!capabilities.supports_platform("sev-snp"));
+        assert_eq!(capabilities.quote_versions, [3]);
+        assert_eq!(capabilities.tcb_info_versions, [2, 3]);
+        assert_eq!(capabilities.qe_identity_versions, [2]);
+        assert_eq!(capabilities.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn features_match_the_build() {
+        let capabilities = capabilities();
+        assert_eq!(capabilities.has_feature("std"), cfg!(feature = "std"));
+        assert_eq!(
+            capabilities.has_feature("mbedtls"),
+            cfg!(feature = "mbedtls")
+        );
+        assert_eq!(capabilities.has_feature("pcs"), cfg!(feature = "pcs"));
+        assert_eq!(capabilities.has_feature("tokio"), cfg!(feature = "tokio"));
+        assert_eq!(
+            capabilities.has_backend("mbedtls"),
+            cfg!(feature = "mbedtls")
+        );
+        assert_eq!(
+            capabilities.supports_platform("cove"),
+            cfg!(feature = "unstable")
+        );
+    }
+
+    #[test]
+    fn serializes_to_json() {
+        let json = to_canonical_json(&capabilities()).expect("Failed to serialize");
+        assert!(json.contains(r#""platforms":["sgx""#));
+        assert!(json.contains(r#""quote_versions":[3]"#));
+    }
+}
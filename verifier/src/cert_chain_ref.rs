@@ -0,0 +1,279 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Certificate chains borrowed from their DER encoding.
+//!
+//! Decoding a [`Certificate`] allocates for its names and extensions, and
+//! handing the chain to a backend, like MbedTls, copies each certificate.
+//! [`CertChainRef`] only walks the DER structure, so a chain can be checked,
+//! iterated, and have its leaf key extracted without any allocation. The
+//! certificates are copied only when a backend needs to verify them.
+
+use crate::Error;
+use der::{asn1::AnyRef, Decode, Reader, SliceReader, Tag, TagNumber, Tagged};
+use p256::ecdsa::VerifyingKey;
+use x509_cert::{spki::SubjectPublicKeyInfoRef, Certificate};
+
+/// The number of `TBSCertificate` fields preceding the
+/// `subjectPublicKeyInfo`, after the optional version: serial number,
+/// signature, issuer, validity, and subject.
+const FIELDS_BEFORE_SPKI: usize = 5;
+
+/// A certificate chain borrowed from concatenated DER encoded certificates.
+///
+/// The first certificate is the leaf. The DER is only checked for the
+/// outer structure of each certificate, the certificates are fully decoded
+/// when verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CertChainRef<'a> {
+    der: &'a [u8],
+}
+
+impl<'a> CertChainRef<'a> {
+    /// Borrow a certificate chain from the concatenated DER encoded
+    /// certificates in `der`.
+    ///
+    /// # Errors
+    /// `Error::Der` if `der` is not a sequence of complete DER `SEQUENCE`s.
+    pub fn try_from_der(der: &'a [u8]) -> Result<Self, Error> {
+        let mut rest = der;
+        while !rest.is_empty() {
+            rest = split_certificate(rest)?.1;
+        }
+        Ok(Self { der })
+    }
+
+    /// The certificates, starting with the leaf
+    pub fn iter(&self) -> CertChainRefIter<'a> {
+        CertChainRefIter { rest: self.der }
+    }
+
+    /// The leaf certificate, if the chain is not empty
+    pub fn leaf(&self) -> Option<CertificateRef<'a>> {
+        self.iter().next()
+    }
+
+    /// The number of certificates in the chain
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Whether the chain has no certificates
+    pub fn is_empty(&self) -> bool {
+        self.der.is_empty()
+    }
+
+    /// The concatenated DER encoded certificates
+    pub fn as_der(&self) -> &'a [u8] {
+        self.der
+    }
+}
+
+impl<'a> IntoIterator for CertChainRef<'a> {
+    type Item = CertificateRef<'a>;
+    type IntoIter = CertChainRefIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the certificates of a [`CertChainRef`]
+#[derive(Debug, Clone)]
+pub struct CertChainRefIter<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for CertChainRefIter<'a> {
+    type Item = CertificateRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        // The chain was checked when created, so this only fails for chains
+        // which were not
+        let (der, rest) = split_certificate(self.rest).ok()?;
+        self.rest = rest;
+        Some(CertificateRef { der })
+    }
+}
+
+/// A certificate borrowed from its DER encoding, see [`CertChainRef`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CertificateRef<'a> {
+    der: &'a [u8],
+}
+
+impl<'a> CertificateRef<'a> {
+    /// The DER encoded certificate
+    pub fn as_der(&self) -> &'a [u8] {
+        self.der
+    }
+
+    /// Fully decode the certificate.
+    ///
+    /// # Errors
+    /// `Error::Der` if the certificate can not be decoded.
+    pub fn decode(&self) -> Result<Certificate, Error> {
+        Ok(Certificate::from_der(self.der)?)
+    }
+
+    /// The subject public key info, borrowed from the certificate.
+    ///
+    /// # Errors
+    /// `Error::Der` if the certificate can not be decoded up to its subject
+    /// public key info.
+    pub fn subject_public_key_info(&self) -> Result<SubjectPublicKeyInfoRef<'a>, Error> {
+        Ok(subject_public_key_info(self.der)?)
+    }
+
+    /// The P-256 public key of the certificate.
+    ///
+    /// # Errors
+    /// `Error::Der` if the certificate can not be decoded up to its subject
+    /// public key info.
+    /// `Error::UnsupportedPublicKey` if the key is not a P-256 key.
+    pub fn public_key(&self) -> Result<VerifyingKey, Error> {
+        let spki = self.subject_public_key_info()?;
+        let key = spki
+            .subject_public_key
+            .as_bytes()
+            .ok_or(Error::UnsupportedPublicKey)?;
+        VerifyingKey::from_sec1_bytes(key).map_err(|_| Error::UnsupportedPublicKey)
+    }
+}
+
+/// Split the first DER encoded certificate from `der`, returning it and the
+/// remaining bytes.
+fn split_certificate(der: &[u8]) -> der::Result<(&[u8], &[u8])> {
+    let mut reader = SliceReader::new(der)?;
+    AnyRef::decode(&mut reader)?
+        .tag()
+        .assert_eq(Tag::Sequence)?;
+    let len = usize::try_from(reader.position())?;
+    Ok(der.split_at(len))
+}
+
+/// The subject public key info of the DER encoded `certificate`, decoding
+/// only the fields up to it.
+pub(crate) fn subject_public_key_info(
+    certificate: &[u8],
+) -> der::Result<SubjectPublicKeyInfoRef<'_>> {
+    let certificate = AnyRef::from_der(certificate)?;
+    certificate.tag().assert_eq(Tag::Sequence)?;
+    let mut reader = SliceReader::new(certificate.value())?;
+    let tbs_certificate = AnyRef::decode(&mut reader)?;
+    tbs_certificate.tag().assert_eq(Tag::Sequence)?;
+
+    let mut reader = SliceReader::new(tbs_certificate.value())?;
+    let version = Tag::ContextSpecific {
+        constructed: true,
+        number: TagNumber::N0,
+    };
+    if reader.peek_tag()? == version {
+        AnyRef::decode(&mut reader)?;
+    }
+    for _ in 0..FIELDS_BEFORE_SPKI {
+        AnyRef::decode(&mut reader)?;
+    }
+    SubjectPublicKeyInfoRef::decode(&mut reader)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+    use der::{DecodePem, Encode};
+
+    fn pem_to_der(pem: &str) -> Vec<u8> {
+        Certificate::from_pem(pem)
+            .and_then(|cert| cert.to_der())
+            .expect("Failed to encode certificate")
+    }
+
+    fn chain_der() -> Vec<u8> {
+        [
+            pem_to_der(include_str!("../data/tests/leaf_cert.pem")),
+            pem_to_der(include_str!("../data/tests/processor_ca.pem")),
+            pem_to_der(include_str!("../data/tests/root_ca.pem")),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn iterate_chain() {
+        let der = chain_der();
+        let chain = CertChainRef::try_from_der(&der).expect("Failed to borrow chain");
+        assert_eq!(chain.len(), 3);
+        assert!(!chain.is_empty());
+
+        let root = chain.iter().last().expect("Missing root");
+        assert_eq!(
+            root.as_der(),
+            pem_to_der(include_str!("../data/tests/root_ca.pem"))
+        );
+        let decoded = root.decode().expect("Failed to decode root");
+        assert_eq!(
+            decoded.tbs_certificate.subject,
+            decoded.tbs_certificate.issuer
+        );
+    }
+
+    #[test]
+    fn empty_chain() {
+        let chain = CertChainRef::try_from_der(&[]).expect("Failed to borrow chain");
+        assert!(chain.is_empty());
+        assert_eq!(chain.leaf(), None);
+    }
+
+    #[test]
+    fn truncated_chain_fails() {
+        let der = chain_der();
+        assert_matches!(
+            CertChainRef::try_from_der(&der[..der.len() - 1]),
+            Err(Error::Der(_))
+        );
+    }
+
+    #[test]
+    fn non_sequence_fails() {
+        let der = [
+            pem_to_der(include_str!("../data/tests/leaf_cert.pem")),
+            Vec::from([0x02, 0x01, 0x00]),
+        ]
+        .concat();
+        assert_matches!(CertChainRef::try_from_der(&der), Err(Error::Der(_)));
+    }
+
+    #[test]
+    fn leaf_spki_matches_decoded_certificate() {
+        let der = chain_der();
+        let chain = CertChainRef::try_from_der(&der).expect("Failed to borrow chain");
+        let leaf = chain.leaf().expect("Missing leaf");
+
+        let spki = leaf
+            .subject_public_key_info()
+            .expect("Failed to get public key info");
+        let decoded = leaf.decode().expect("Failed to decode leaf");
+        let expected = decoded.tbs_certificate.subject_public_key_info;
+        assert_eq!(
+            spki.to_der().expect("Failed to encode"),
+            expected.to_der().expect("Failed to encode")
+        );
+
+        let key = leaf.public_key().expect("Failed to get public key");
+        assert_eq!(
+            key.to_encoded_point(false).as_bytes(),
+            expected.subject_public_key.raw_bytes()
+        );
+    }
+
+    #[test]
+    fn non_p256_key_is_unsupported() {
+        let der = include_bytes!("../data/tests/pkits/certs/GoodCACert.crt");
+        let chain = CertChainRef::try_from_der(der).expect("Failed to borrow chain");
+        let leaf = chain.leaf().expect("Missing leaf");
+        assert_matches!(leaf.public_key(), Err(Error::UnsupportedPublicKey));
+    }
+}
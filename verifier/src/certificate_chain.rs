@@ -2,8 +2,33 @@
 
 //! Trait and Error for verifying certificate chains
 
-use der::DateTime;
-use x509_cert::{crl::CertificateList, Certificate};
+use alloc::{string::String, vec, vec::Vec};
+use core::mem::discriminant;
+use der::{
+    oid::{
+        db::{
+            rfc4519::COMMON_NAME,
+            rfc5280::{
+                ANY_EXTENDED_KEY_USAGE, ID_CE_AUTHORITY_KEY_IDENTIFIER, ID_CE_BASIC_CONSTRAINTS,
+                ID_CE_CRL_DISTRIBUTION_POINTS, ID_CE_EXT_KEY_USAGE, ID_CE_KEY_USAGE,
+                ID_CE_NAME_CONSTRAINTS, ID_CE_SUBJECT_ALT_NAME, ID_CE_SUBJECT_KEY_IDENTIFIER,
+            },
+        },
+        ObjectIdentifier,
+    },
+    DateTime, Decode, Encode,
+};
+use x509_cert::{
+    crl::CertificateList,
+    ext::pkix::{
+        constraints::name::GeneralSubtrees,
+        crl::dp::DistributionPoint,
+        name::{DirectoryString, DistributionPointName, GeneralName},
+        CrlDistributionPoints, ExtendedKeyUsage, NameConstraints, SubjectAltName,
+    },
+    name::Name,
+    Certificate,
+};
 
 /// Error verifying a certificate chain
 #[derive(displaydoc::Display, Debug, Clone, PartialEq)]
@@ -14,12 +39,39 @@ pub enum CertificateChainVerifierError {
     CertificateExpired,
     /// X509 certificate has been revoked
     CertificateRevoked,
+    /// Certificate revocation list not yet valid
+    CrlNotYetValid,
+    /// Certificate revocation list has expired
+    CrlExpired,
     /// General error trying to verify a certificate chain
     GeneralCertificateError,
     /// Error verifying the signature
     SignatureVerification,
+    /// A certificate is missing the required extended key usage
+    ExtendedKeyUsage,
+    /// A certificate violates the name constraints of an issuing CA
+    NameConstraints,
+    /// A certificate has the unrecognized critical extension {0}
+    UnknownCriticalExtension(ObjectIdentifier),
+    /// The leaf certificate does not match the expected names
+    LeafMismatch,
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for CertificateChainVerifierError {}
+
+/// The extensions recognized when rejecting unknown critical extensions,
+/// these are processed by the verifier backends
+const RECOGNIZED_EXTENSIONS: &[ObjectIdentifier] = &[
+    ID_CE_AUTHORITY_KEY_IDENTIFIER,
+    ID_CE_BASIC_CONSTRAINTS,
+    ID_CE_CRL_DISTRIBUTION_POINTS,
+    ID_CE_EXT_KEY_USAGE,
+    ID_CE_KEY_USAGE,
+    ID_CE_SUBJECT_ALT_NAME,
+    ID_CE_SUBJECT_KEY_IDENTIFIER,
+];
+
 /// A trait whose implementation will verify multiple certificate chains which all use the same
 /// trust anchor.
 pub trait CertificateChainVerifier {
@@ -43,3 +95,768 @@ pub trait CertificateChainVerifier {
         time: impl Into<Option<DateTime>>,
     ) -> Result<(), CertificateChainVerifierError>;
 }
+
+impl<C: CertificateChainVerifier> CertificateChainVerifier for &C {
+    fn verify_certificate_chain<'a, 'b>(
+        &self,
+        certificate_chain: impl IntoIterator<Item = &'a Certificate>,
+        crls: impl IntoIterator<Item = &'b CertificateList>,
+        time: impl Into<Option<DateTime>>,
+    ) -> Result<(), CertificateChainVerifierError> {
+        (**self).verify_certificate_chain(certificate_chain, crls, time)
+    }
+}
+
+/// Policy checks for a certificate chain, in addition to the path validation
+/// of a [`CertificateChainVerifier`].
+///
+/// No checks are enabled by default. They are meant for deployments reusing
+/// the verifier with non-Intel PKIs, where the expected key usage or the
+/// namespace of the CAs needs to be enforced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainPolicy {
+    leaf_extended_key_usage: Option<ObjectIdentifier>,
+    leaf_matcher: Option<LeafMatcher>,
+    name_constraints: bool,
+    unknown_critical_extensions: bool,
+    recognized_extensions: Vec<ObjectIdentifier>,
+}
+
+impl ChainPolicy {
+    /// Create a new instance without any checks enabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the leaf certificate to carry the extended key `usage`.
+    ///
+    /// The CAs in the chain are allowed to restrict the extended key usage as
+    /// well. When a CA has the extended key usage extension it must include
+    /// `usage` or `anyExtendedKeyUsage`.
+    pub fn require_leaf_extended_key_usage(mut self, usage: ObjectIdentifier) -> Self {
+        self.leaf_extended_key_usage = Some(usage);
+        self
+    }
+
+    /// Require the leaf certificate to match the expected names, see
+    /// [`LeafMatcher`].
+    pub fn match_leaf(mut self, matcher: LeafMatcher) -> Self {
+        self.leaf_matcher = Some(matcher);
+        self
+    }
+
+    /// Enforce the name constraints of the CAs in the chain.
+    ///
+    /// The subject and the subject alternative names of the certificates
+    /// issued below a CA with the name constraints extension are checked
+    /// against its permitted and excluded subtrees, as described in
+    /// [RFC 5280 Section 4.2.1.10](https://datatracker.ietf.org/doc/html/rfc5280#section-4.2.1.10).
+    /// Directory names and DNS names are supported. A certificate with a name
+    /// of another form, which is constrained by a CA, is rejected.
+    pub fn enforce_name_constraints(mut self) -> Self {
+        self.name_constraints = true;
+        self
+    }
+
+    /// Reject certificates with an unrecognized critical extension.
+    ///
+    /// [RFC 5280 Section 4.2](https://datatracker.ietf.org/doc/html/rfc5280#section-4.2)
+    /// requires a certificate to be rejected when it has a critical extension
+    /// the verifier does not process. Without this check, it's left to the
+    /// verifier backend to decide. The standard extensions processed by the
+    /// backends are recognized, as is the name constraints extension when
+    /// [`ChainPolicy::enforce_name_constraints`] is used. Other extensions
+    /// processed by the caller can be added with
+    /// [`ChainPolicy::recognize_extension`].
+    pub fn reject_unknown_critical_extensions(mut self) -> Self {
+        self.unknown_critical_extensions = true;
+        self
+    }
+
+    /// Recognize the extension `oid` when rejecting unknown critical
+    /// extensions.
+    ///
+    /// The caller is responsible for processing the extension, for example
+    /// the DICE `TcbInfo` of a CoVE attestation certificate.
+    pub fn recognize_extension(mut self, oid: ObjectIdentifier) -> Self {
+        self.recognized_extensions.push(oid);
+        self
+    }
+
+    /// Whether no checks are enabled
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Check the `chain` against the policy.
+    ///
+    /// The `chain` must be ordered from the leaf up to the root. The trust
+    /// anchor may be omitted.
+    ///
+    /// # Errors
+    /// `CertificateChainVerifierError::ExtendedKeyUsage` if the leaf, or a CA,
+    /// does not allow the required extended key usage.
+    /// `CertificateChainVerifierError::NameConstraints` if a certificate
+    /// violates the name constraints of a CA.
+    /// `CertificateChainVerifierError::UnknownCriticalExtension` if a
+    /// certificate has an unrecognized critical extension.
+    /// `CertificateChainVerifierError::LeafMismatch` if the leaf does not
+    /// match the [`LeafMatcher`].
+    /// `CertificateChainVerifierError::GeneralCertificateError` if an
+    /// extension can not be decoded.
+    pub fn check<'a>(
+        &self,
+        chain: impl IntoIterator<Item = &'a Certificate>,
+    ) -> Result<(), CertificateChainVerifierError> {
+        let chain = chain.into_iter().collect::<Vec<_>>();
+        self.check_critical_extensions(chain.iter().copied())?;
+        if let Some(usage) = &self.leaf_extended_key_usage {
+            check_extended_key_usage(&chain, usage)?;
+        }
+        if let Some(matcher) = &self.leaf_matcher {
+            let leaf = chain
+                .first()
+                .ok_or(CertificateChainVerifierError::LeafMismatch)?;
+            matcher.check(leaf)?;
+        }
+        if self.name_constraints {
+            check_name_constraints(&chain)?;
+        }
+        Ok(())
+    }
+
+    /// Check the certificates of `chain`, in any order, for unrecognized
+    /// critical extensions.
+    ///
+    /// This is part of [`ChainPolicy::check`]. It's provided separately so it
+    /// can be done before the chain is given to a verifier backend, which may
+    /// fail on its own for an unknown critical extension.
+    ///
+    /// # Errors
+    /// `CertificateChainVerifierError::UnknownCriticalExtension` with the
+    /// first unrecognized critical extension found.
+    pub fn check_critical_extensions<'a>(
+        &self,
+        chain: impl IntoIterator<Item = &'a Certificate>,
+    ) -> Result<(), CertificateChainVerifierError> {
+        if !self.unknown_critical_extensions {
+            return Ok(());
+        }
+        let extensions = chain
+            .into_iter()
+            .flat_map(|cert| cert.tbs_certificate.extensions.iter().flatten());
+        for extension in extensions {
+            let oid = &extension.extn_id;
+            let recognized = RECOGNIZED_EXTENSIONS.contains(oid)
+                || self.recognized_extensions.contains(oid)
+                || (self.name_constraints && oid == &ID_CE_NAME_CONSTRAINTS);
+            if extension.critical && !recognized {
+                return Err(CertificateChainVerifierError::UnknownCriticalExtension(
+                    *oid,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks the names of a leaf certificate against the expected values.
+///
+/// A valid certificate from a shared PKI may still be for another party. The
+/// matcher rejects such a leaf by its subject common name, its subject
+/// alternative names, or its full subject distinguished name. Each kind of
+/// name which is configured must match, and several patterns of the same
+/// kind match if any of them does.
+///
+/// Patterns are compared exactly, except for `*` which matches any sequence
+/// of characters, for example `"Intel SGX PCK *"`. DNS names are compared
+/// case insensitively.
+///
+/// ```
+/// use mc_attestation_verifier::LeafMatcher;
+///
+/// let matcher = LeafMatcher::new().common_name("Intel SGX PCK Certificate");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LeafMatcher {
+    common_names: Vec<String>,
+    alt_names: Vec<String>,
+    subject: Option<Name>,
+}
+
+impl LeafMatcher {
+    /// Create a new instance which matches any leaf
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow a subject common name matching `pattern`
+    pub fn common_name(mut self, pattern: impl Into<String>) -> Self {
+        self.common_names.push(pattern.into());
+        self
+    }
+
+    /// Allow a DNS name, email address, or URI subject alternative name
+    /// matching `pattern`
+    pub fn alt_name(mut self, pattern: impl Into<String>) -> Self {
+        self.alt_names.push(pattern.into());
+        self
+    }
+
+    /// Require the subject distinguished name to be `subject`.
+    ///
+    /// The name can be parsed from its RFC 4514 string, like
+    /// `"CN=Intel SGX PCK Certificate,O=Intel Corporation"`, with
+    /// `x509_cert::name::Name::from_str()`.
+    pub fn subject(mut self, subject: Name) -> Self {
+        self.subject = Some(subject);
+        self
+    }
+
+    /// Check the `leaf` certificate against the expected names.
+    ///
+    /// # Errors
+    /// `CertificateChainVerifierError::LeafMismatch` if a name does not
+    /// match.
+    /// `CertificateChainVerifierError::GeneralCertificateError` if the
+    /// subject alternative names can not be decoded.
+    pub fn check(&self, leaf: &Certificate) -> Result<(), CertificateChainVerifierError> {
+        let tbs = &leaf.tbs_certificate;
+        if let Some(subject) = &self.subject {
+            if subject != &tbs.subject {
+                return Err(CertificateChainVerifierError::LeafMismatch);
+            }
+        }
+
+        if !self.common_names.is_empty() {
+            let matched = common_names(&tbs.subject).iter().any(|name| {
+                self.common_names
+                    .iter()
+                    .any(|pattern| wildcard_match(pattern, name))
+            });
+            if !matched {
+                return Err(CertificateChainVerifierError::LeafMismatch);
+            }
+        }
+
+        if !self.alt_names.is_empty() {
+            let alt_names = tbs
+                .get::<SubjectAltName>()
+                .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?
+                .map(|(_, alt_names)| alt_names.0)
+                .unwrap_or_default();
+            let matched = alt_names.iter().any(|name| {
+                self.alt_names.iter().any(|pattern| match name {
+                    GeneralName::DnsName(name) => wildcard_match(
+                        &pattern.to_ascii_lowercase(),
+                        &name.as_str().to_ascii_lowercase(),
+                    ),
+                    GeneralName::Rfc822Name(name)
+                    | GeneralName::UniformResourceIdentifier(name) => {
+                        wildcard_match(pattern, name.as_str())
+                    }
+                    _ => false,
+                })
+            });
+            if !matched {
+                return Err(CertificateChainVerifierError::LeafMismatch);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The common names of `name`, skipping values which are not strings
+pub(crate) fn common_names(name: &Name) -> Vec<String> {
+    name.0
+        .iter()
+        .flat_map(|rdn| rdn.0.iter())
+        .filter(|attribute| attribute.oid == COMMON_NAME)
+        .filter_map(|attribute| {
+            let value = attribute.value.to_der().ok()?;
+            DirectoryString::from_der(&value).ok()
+        })
+        .map(|value| match value {
+            DirectoryString::PrintableString(value) => String::from(value.as_str()),
+            DirectoryString::TeletexString(value) => String::from(value.as_str()),
+            DirectoryString::Utf8String(value) => value,
+        })
+        .collect()
+}
+
+/// Whether `value` matches `pattern`, where `*` matches any sequence of
+/// characters
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let value = value.as_bytes();
+    let (mut p, mut v) = (0, 0);
+    // The position after the last `*` and the value position it's matched up to
+    let mut backtrack = None;
+    while v < value.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            p += 1;
+            backtrack = Some((p, v));
+        } else if p < pattern.len() && pattern[p] == value[v] {
+            p += 1;
+            v += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star;
+            v = matched + 1;
+            backtrack = Some((star, v));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+fn check_extended_key_usage(
+    chain: &[&Certificate],
+    usage: &ObjectIdentifier,
+) -> Result<(), CertificateChainVerifierError> {
+    for (index, cert) in chain.iter().enumerate() {
+        let usages = cert
+            .tbs_certificate
+            .get::<ExtendedKeyUsage>()
+            .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?;
+        let allowed = match usages {
+            Some((_, usages)) => {
+                usages.0.contains(usage) || usages.0.contains(&ANY_EXTENDED_KEY_USAGE)
+            }
+            // Only CAs may omit the extension, meaning any usage
+            None => index != 0,
+        };
+        if !allowed {
+            return Err(CertificateChainVerifierError::ExtendedKeyUsage);
+        }
+    }
+    Ok(())
+}
+
+fn check_name_constraints(chain: &[&Certificate]) -> Result<(), CertificateChainVerifierError> {
+    for (index, ca) in chain.iter().enumerate().skip(1) {
+        let Some((_, constraints)) = ca
+            .tbs_certificate
+            .get::<NameConstraints>()
+            .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?
+        else {
+            continue;
+        };
+        for (position, cert) in chain[..index].iter().enumerate() {
+            // Self-issued intermediate CAs are exempt, see RFC 5280 Section 6.1.3
+            let tbs = &cert.tbs_certificate;
+            if position != 0 && tbs.subject == tbs.issuer {
+                continue;
+            }
+            for name in names(cert)? {
+                if !is_permitted(&constraints, &name) {
+                    return Err(CertificateChainVerifierError::NameConstraints);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The subject, when not empty, and the subject alternative names of `cert`
+fn names(cert: &Certificate) -> Result<Vec<GeneralName>, CertificateChainVerifierError> {
+    let tbs = &cert.tbs_certificate;
+    let mut names = vec![];
+    if !tbs.subject.0.is_empty() {
+        names.push(GeneralName::DirectoryName(tbs.subject.clone()));
+    }
+    let alt_names = tbs
+        .get::<SubjectAltName>()
+        .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?;
+    if let Some((_, alt_names)) = alt_names {
+        names.extend(alt_names.0);
+    }
+    Ok(names)
+}
+
+fn is_permitted(constraints: &NameConstraints, name: &GeneralName) -> bool {
+    let same_form = |subtrees: &Option<GeneralSubtrees>| {
+        subtrees
+            .iter()
+            .flatten()
+            .filter(|subtree| discriminant(&subtree.base) == discriminant(name))
+            .map(|subtree| within_subtree(&subtree.base, name))
+            .collect::<Vec<_>>()
+    };
+
+    // An unsupported form is treated as matching the excluded subtree and not
+    // matching the permitted subtree, rejecting the name either way
+    let excluded = same_form(&constraints.excluded_subtrees);
+    if excluded.iter().any(|within| within != &Some(false)) {
+        return false;
+    }
+    let permitted = same_form(&constraints.permitted_subtrees);
+    permitted.is_empty() || permitted.contains(&Some(true))
+}
+
+/// Whether `name` is within the subtree of `base`.
+///
+/// Returns `None` if the form of the names is not supported.
+fn within_subtree(base: &GeneralName, name: &GeneralName) -> Option<bool> {
+    match (base, name) {
+        (GeneralName::DirectoryName(base), GeneralName::DirectoryName(name)) => {
+            Some(name.0.starts_with(&base.0))
+        }
+        (GeneralName::DnsName(base), GeneralName::DnsName(name)) => {
+            let base = base.as_str().to_ascii_lowercase();
+            let name = name.as_str().to_ascii_lowercase();
+            Some(if base.is_empty() {
+                true
+            } else if base.starts_with('.') {
+                name.ends_with(&base)
+            } else {
+                name == base || name.ends_with(&alloc::format!(".{base}"))
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Get the URIs from the CRL distribution points extension of `certificate`.
+///
+/// Only distribution points with a full name are supported. An empty list is
+/// returned when the certificate has no CRL distribution points.
+///
+/// # Errors
+/// `der::Error` if the CRL distribution points extension can not be decoded.
+pub fn crl_distribution_points(certificate: &Certificate) -> Result<Vec<String>, der::Error> {
+    let uris = certificate
+        .tbs_certificate
+        .get::<CrlDistributionPoints>()?
+        .map(|(_, points)| distribution_point_uris(&points.0))
+        .unwrap_or_default();
+    Ok(uris)
+}
+
+/// The URIs of the full names in `points`.
+pub(crate) fn distribution_point_uris(points: &[DistributionPoint]) -> Vec<String> {
+    points
+        .iter()
+        .filter_map(|point| match &point.distribution_point {
+            Some(DistributionPointName::FullName(names)) => Some(names),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|name| match name {
+            GeneralName::UniformResourceIdentifier(uri) => Some(uri.as_str().into()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use der::{
+        asn1::Ia5String,
+        oid::db::rfc5280::{ID_KP_CLIENT_AUTH, ID_KP_SERVER_AUTH},
+        Decode, DecodePem,
+    };
+    use yare::parameterized;
+
+    #[test]
+    fn crl_distribution_points_of_leaf() {
+        let leaf = Certificate::from_pem(include_str!("../data/tests/leaf_cert.pem"))
+            .expect("Failed to parse certificate");
+        assert_eq!(
+            crl_distribution_points(&leaf),
+            Ok(vec![String::from(
+                "https://api.trustedservices.intel.com/sgx/certification/v3/pckcrl?ca=processor&encoding=der"
+            )])
+        );
+    }
+
+    #[test]
+    fn crl_distribution_points_of_ca() {
+        let ca = Certificate::from_pem(include_str!("../data/tests/processor_ca.pem"))
+            .expect("Failed to parse certificate");
+        assert_eq!(
+            crl_distribution_points(&ca),
+            Ok(vec![String::from(
+                "https://certificates.trustedservices.intel.com/IntelSGXRootCA.der"
+            )])
+        );
+    }
+
+    const NAME_CONSTRAINTS_ROOT: &[u8] = include_bytes!("../data/tests/name_constraints/root.der");
+    const NAME_CONSTRAINTS_CA: &[u8] = include_bytes!("../data/tests/name_constraints/ca.der");
+
+    fn name_constraints_chain(leaf: &[u8]) -> Vec<Certificate> {
+        [leaf, NAME_CONSTRAINTS_CA, NAME_CONSTRAINTS_ROOT]
+            .iter()
+            .map(|der| Certificate::from_der(der).expect("Failed to parse certificate"))
+            .collect()
+    }
+
+    #[test]
+    fn default_policy_has_no_checks() {
+        let chain = name_constraints_chain(include_bytes!(
+            "../data/tests/name_constraints/other_dn_leaf.der"
+        ));
+        let policy = ChainPolicy::new();
+        assert!(policy.is_empty());
+        assert_eq!(policy.check(&chain), Ok(()));
+    }
+
+    #[parameterized(
+        client_auth = { include_bytes!("../data/tests/name_constraints/good_leaf.der"), Ok(()) },
+        no_usage = { include_bytes!("../data/tests/name_constraints/no_eku_leaf.der"), Err(CertificateChainVerifierError::ExtendedKeyUsage) },
+        server_auth = { include_bytes!("../data/tests/name_constraints/server_leaf.der"), Err(CertificateChainVerifierError::ExtendedKeyUsage) },
+    )]
+    fn leaf_extended_key_usage(leaf: &[u8], expected: Result<(), CertificateChainVerifierError>) {
+        let chain = name_constraints_chain(leaf);
+        let policy = ChainPolicy::new().require_leaf_extended_key_usage(ID_KP_CLIENT_AUTH);
+        assert_eq!(policy.check(&chain), expected);
+    }
+
+    #[test]
+    fn ca_restricts_extended_key_usage() {
+        // The leaf allows server authentication, but the CA only allows client
+        // authentication
+        let chain = name_constraints_chain(include_bytes!(
+            "../data/tests/name_constraints/server_leaf.der"
+        ));
+        let policy = ChainPolicy::new().require_leaf_extended_key_usage(ID_KP_SERVER_AUTH);
+        assert_eq!(
+            policy.check(&chain),
+            Err(CertificateChainVerifierError::ExtendedKeyUsage)
+        );
+    }
+
+    #[parameterized(
+        permitted = { include_bytes!("../data/tests/name_constraints/good_leaf.der"), Ok(()) },
+        other_subject = { include_bytes!("../data/tests/name_constraints/other_dn_leaf.der"), Err(CertificateChainVerifierError::NameConstraints) },
+        other_dns_name = { include_bytes!("../data/tests/name_constraints/other_dns_leaf.der"), Err(CertificateChainVerifierError::NameConstraints) },
+        excluded_dns_name = { include_bytes!("../data/tests/name_constraints/excluded_dns_leaf.der"), Err(CertificateChainVerifierError::NameConstraints) },
+    )]
+    fn name_constraints(leaf: &[u8], expected: Result<(), CertificateChainVerifierError>) {
+        let chain = name_constraints_chain(leaf);
+        let policy = ChainPolicy::new().enforce_name_constraints();
+        assert_eq!(policy.check(&chain), expected);
+    }
+
+    #[test]
+    fn name_constraints_of_intel_chain() {
+        let chain = [
+            include_str!("../data/tests/leaf_cert.pem"),
+            include_str!("../data/tests/processor_ca.pem"),
+            include_str!("../data/tests/root_ca.pem"),
+        ]
+        .iter()
+        .map(|pem| Certificate::from_pem(pem).expect("Failed to parse certificate"))
+        .collect::<Vec<_>>();
+        let policy = ChainPolicy::new().enforce_name_constraints();
+        assert_eq!(policy.check(&chain), Ok(()));
+    }
+
+    #[parameterized(
+        exact = { "example.com", "example.com", true },
+        subdomain = { "example.com", "host.example.com", true },
+        case_insensitive = { "Example.COM", "HOST.example.com", true },
+        suffix_is_not_subdomain = { "example.com", "badexample.com", false },
+        leading_dot_excludes_exact = { ".example.com", "example.com", false },
+        leading_dot_subdomain = { ".example.com", "host.example.com", true },
+        empty_base = { "", "anything.org", true },
+    )]
+    fn dns_name_within_subtree(base: &str, name: &str, expected: bool) {
+        let base = GeneralName::DnsName(Ia5String::new(base).expect("Invalid DNS name"));
+        let name = GeneralName::DnsName(Ia5String::new(name).expect("Invalid DNS name"));
+        assert_eq!(within_subtree(&base, &name), Some(expected));
+    }
+
+    #[test]
+    fn unsupported_name_form_is_rejected() {
+        let uri = GeneralName::UniformResourceIdentifier(
+            Ia5String::new("https://example.com").expect("Invalid URI"),
+        );
+        assert_eq!(within_subtree(&uri, &uri), None);
+    }
+
+    const DICE_TCB_INFO: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.23.133.5.4.1");
+    const DICE_MULTI_TCB_INFO: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.23.133.5.4.5");
+
+    fn dice_chain() -> Vec<Certificate> {
+        vec![
+            Certificate::from_der(include_bytes!("../data/tests/cove/evidence.der"))
+                .expect("Failed to parse certificate"),
+        ]
+    }
+
+    #[test]
+    fn unknown_critical_extension_allowed_by_default() {
+        assert_eq!(ChainPolicy::new().check(&dice_chain()), Ok(()));
+    }
+
+    #[test]
+    fn unknown_critical_extension_is_rejected() {
+        let policy = ChainPolicy::new().reject_unknown_critical_extensions();
+        assert!(!policy.is_empty());
+        assert_eq!(
+            policy.check(&dice_chain()),
+            Err(CertificateChainVerifierError::UnknownCriticalExtension(
+                DICE_TCB_INFO
+            ))
+        );
+    }
+
+    #[test]
+    fn recognized_critical_extensions_are_allowed() {
+        let policy = ChainPolicy::new()
+            .reject_unknown_critical_extensions()
+            .recognize_extension(DICE_TCB_INFO)
+            .recognize_extension(DICE_MULTI_TCB_INFO);
+        assert_eq!(policy.check(&dice_chain()), Ok(()));
+    }
+
+    #[test]
+    fn critical_name_constraints_need_enforcing() {
+        let chain = name_constraints_chain(include_bytes!(
+            "../data/tests/name_constraints/good_leaf.der"
+        ));
+        let policy = ChainPolicy::new().reject_unknown_critical_extensions();
+        assert_eq!(
+            policy.check(&chain),
+            Err(CertificateChainVerifierError::UnknownCriticalExtension(
+                ID_CE_NAME_CONSTRAINTS
+            ))
+        );
+        assert_eq!(policy.enforce_name_constraints().check(&chain), Ok(()));
+    }
+
+    #[test]
+    fn unknown_critical_extension_error_names_oid() {
+        assert_eq!(
+            CertificateChainVerifierError::UnknownCriticalExtension(DICE_TCB_INFO).to_string(),
+            "A certificate has the unrecognized critical extension 2.23.133.5.4.1"
+        );
+    }
+
+    #[test]
+    fn intel_chain_has_no_unknown_critical_extensions() {
+        let chain = [
+            include_str!("../data/tests/leaf_cert.pem"),
+            include_str!("../data/tests/processor_ca.pem"),
+            include_str!("../data/tests/root_ca.pem"),
+        ]
+        .iter()
+        .map(|pem| Certificate::from_pem(pem).expect("Failed to parse certificate"))
+        .collect::<Vec<_>>();
+        let policy = ChainPolicy::new().reject_unknown_critical_extensions();
+        assert_eq!(policy.check(&chain), Ok(()));
+    }
+
+    #[test]
+    fn no_crl_distribution_points() {
+        let leaf = Certificate::from_der(include_bytes!("../data/tests/delta_crl/good_leaf.der"))
+            .expect("Failed to parse certificate");
+        assert_eq!(crl_distribution_points(&leaf), Ok(vec![]));
+    }
+
+    fn pck_leaf() -> Certificate {
+        Certificate::from_pem(include_str!("../data/tests/leaf_cert.pem"))
+            .expect("Failed to parse certificate")
+    }
+
+    #[parameterized(
+        exact = { "Intel SGX PCK Certificate", Ok(()) },
+        wildcard_suffix = { "Intel SGX PCK *", Ok(()) },
+        wildcard_middle = { "Intel * Certificate", Ok(()) },
+        only_wildcard = { "*", Ok(()) },
+        other_name = { "Intel SGX PCK Processor CA", Err(CertificateChainVerifierError::LeafMismatch) },
+        case_sensitive = { "intel sgx pck certificate", Err(CertificateChainVerifierError::LeafMismatch) },
+        prefix_only = { "Intel SGX PCK", Err(CertificateChainVerifierError::LeafMismatch) },
+    )]
+    fn leaf_common_name(pattern: &str, expected: Result<(), CertificateChainVerifierError>) {
+        let matcher = LeafMatcher::new().common_name(pattern);
+        assert_eq!(matcher.check(&pck_leaf()), expected);
+    }
+
+    #[test]
+    fn any_common_name_pattern_matches() {
+        let matcher = LeafMatcher::new()
+            .common_name("Intel SGX PCK Platform CA")
+            .common_name("Intel SGX PCK Certificate");
+        assert_eq!(matcher.check(&pck_leaf()), Ok(()));
+    }
+
+    #[test]
+    fn leaf_subject() {
+        let subject = pck_leaf().tbs_certificate.subject;
+        let matcher = LeafMatcher::new().subject(subject);
+        assert_eq!(matcher.check(&pck_leaf()), Ok(()));
+
+        let processor_ca = Certificate::from_pem(include_str!("../data/tests/processor_ca.pem"))
+            .expect("Failed to parse certificate");
+        assert_eq!(
+            matcher.check(&processor_ca),
+            Err(CertificateChainVerifierError::LeafMismatch)
+        );
+    }
+
+    #[parameterized(
+        exact = { include_bytes!("../data/tests/name_constraints/good_leaf.der"), "service.example.com", Ok(()) },
+        wildcard = { include_bytes!("../data/tests/name_constraints/good_leaf.der"), "*.example.com", Ok(()) },
+        case_insensitive = { include_bytes!("../data/tests/name_constraints/good_leaf.der"), "SERVICE.Example.com", Ok(()) },
+        other_domain = { include_bytes!("../data/tests/name_constraints/other_dns_leaf.der"), "*.example.com", Err(CertificateChainVerifierError::LeafMismatch) },
+    )]
+    fn leaf_alt_name(
+        leaf: &[u8],
+        pattern: &str,
+        expected: Result<(), CertificateChainVerifierError>,
+    ) {
+        let leaf = Certificate::from_der(leaf).expect("Failed to parse certificate");
+        let matcher = LeafMatcher::new().alt_name(pattern);
+        assert_eq!(matcher.check(&leaf), expected);
+    }
+
+    #[test]
+    fn leaf_without_alt_names_mismatches() {
+        let matcher = LeafMatcher::new().alt_name("*");
+        assert_eq!(
+            matcher.check(&pck_leaf()),
+            Err(CertificateChainVerifierError::LeafMismatch)
+        );
+    }
+
+    #[test]
+    fn all_configured_names_must_match() {
+        let leaf = Certificate::from_der(include_bytes!(
+            "../data/tests/name_constraints/good_leaf.der"
+        ))
+        .expect("Failed to parse certificate");
+        let matcher = LeafMatcher::new()
+            .common_name("Good Leaf")
+            .alt_name("service.example.org");
+        assert_eq!(
+            matcher.check(&leaf),
+            Err(CertificateChainVerifierError::LeafMismatch)
+        );
+    }
+
+    #[test]
+    fn policy_matches_leaf() {
+        let chain = [
+            include_str!("../data/tests/leaf_cert.pem"),
+            include_str!("../data/tests/processor_ca.pem"),
+            include_str!("../data/tests/root_ca.pem"),
+        ]
+        .iter()
+        .map(|pem| Certificate::from_pem(pem).expect("Failed to parse certificate"))
+        .collect::<Vec<_>>();
+        let policy = ChainPolicy::new()
+            .match_leaf(LeafMatcher::new().common_name("Intel SGX PCK Certificate"));
+        assert_eq!(policy.check(&chain), Ok(()));
+
+        // The processor CA is not the expected leaf
+        assert_eq!(
+            policy.check(&chain[1..]),
+            Err(CertificateChainVerifierError::LeafMismatch)
+        );
+    }
+}
@@ -0,0 +1,617 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! The certification data of a quote, which identifies the PCK of the
+//! platform.
+//!
+//! Table 9 of appendix A of the
+//! [DCAP quote library reference](https://download.01.org/intel-sgx/latest/dcap-latest/linux/docs/Intel_SGX_ECDSA_QuoteLibReference_DCAP_API.pdf)
+//! defines the certification data types:
+//! 1. The PPID in plain text, the CPUSVN, PCESVN, and PCE ID.
+//! 2. The PPID encrypted with RSA-2048-OAEP, the CPUSVN, PCESVN, and PCE ID.
+//! 3. The PPID encrypted with RSA-3072-OAEP, the CPUSVN, PCESVN, and PCE ID.
+//! 4. The PCK leaf certificate.
+//! 5. The PCK certificate chain.
+//!
+//! Only type 5 carries everything needed to verify the quote. The other types
+//! need a [`PckResolver`] to exchange them for the PCK certificate chain,
+//! usually by querying a provisioning service such as the Intel PCS or a
+//! PCCS, see [`PccsPckResolver`].
+
+use crate::{
+    quote4::Reader, url_encoding::percent_decode, CpuSvn, Error, PceId, QuoteError, QuoteField,
+    QuoteHeader, PCE_ID_SIZE, PPID_SIZE,
+};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Debug, Display, Formatter};
+use der::DecodePem;
+use x509_cert::Certificate;
+
+/// Type of the certification data with the PPID in plain text
+pub const PPID_CLEARTEXT_TYPE: u16 = 1;
+
+/// Type of the certification data with the PPID encrypted with RSA-2048-OAEP
+pub const PPID_ENCRYPTED_RSA_2048_TYPE: u16 = 2;
+
+/// Type of the certification data with the PPID encrypted with RSA-3072-OAEP
+pub const PPID_ENCRYPTED_RSA_3072_TYPE: u16 = 3;
+
+/// Type of the certification data with the PCK leaf certificate
+pub const PCK_LEAF_CERTIFICATE_TYPE: u16 = 4;
+
+/// Type of the certification data with the PCK certificate chain
+pub const PCK_CERTIFICATE_CHAIN_TYPE: u16 = 5;
+
+/// The path of the PCS and PCCS request for a PCK certificate
+const PCK_CERTIFICATE_PATH: &str = "/sgx/certification/v4/pckcert";
+
+/// Size of the PPID encrypted with RSA-2048-OAEP
+const PPID_RSA_2048_SIZE: usize = 256;
+
+/// Size of the PPID encrypted with RSA-3072-OAEP
+const PPID_RSA_3072_SIZE: usize = 384;
+
+/// Size of the CPUSVN
+const CPU_SVN_SIZE: usize = 16;
+
+/// Size of the PCESVN
+const PCE_SVN_SIZE: usize = 2;
+
+/// Size of the ECDSA signature, attestation key, and QE report signature
+const ECDSA_P256_SIZE: usize = 64;
+
+/// Size of the application enclave report body of a version 3 quote
+const REPORT_BODY_SIZE: usize = 384;
+
+/// Size of the QE report
+const QE_REPORT_SIZE: usize = 384;
+
+/// The PCK identifier of certification data types 1 to 3.
+///
+/// This is what a provisioning service needs to look up the PCK certificate
+/// of the platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PckIdentifier<'a> {
+    ppid: &'a [u8],
+    cpu_svn: CpuSvn,
+    pce_svn: u16,
+    pce_id: PceId,
+}
+
+impl<'a> PckIdentifier<'a> {
+    /// The PPID, in plain text or encrypted depending on the certification
+    /// data type
+    pub fn ppid(&self) -> &'a [u8] {
+        self.ppid
+    }
+
+    /// The CPUSVN of the platform
+    pub fn cpu_svn(&self) -> &CpuSvn {
+        &self.cpu_svn
+    }
+
+    /// The SVN of the PCE
+    pub fn pce_svn(&self) -> u16 {
+        self.pce_svn
+    }
+
+    /// The ID of the PCE
+    pub fn pce_id(&self) -> PceId {
+        self.pce_id
+    }
+
+    fn read(data: &'a [u8], ppid_size: usize, data_type: u16) -> Result<Self, Error> {
+        let expected = ppid_size + CPU_SVN_SIZE + PCE_SVN_SIZE + PCE_ID_SIZE;
+        if data.len() != expected {
+            return Err(QuoteError::CertificationDataSize {
+                data_type,
+                expected,
+                actual: data.len(),
+            }
+            .into());
+        }
+        let mut reader = Reader::new(data);
+        let ppid = reader.take(QuoteField::Ppid, ppid_size)?;
+        let cpu_svn = CpuSvn::from(reader.array::<CPU_SVN_SIZE>(QuoteField::CpuSvn)?);
+        let pce_svn = reader.u16(QuoteField::PceSvn)?;
+        let pce_id = PceId::new(reader.array(QuoteField::PceId)?);
+        Ok(Self {
+            ppid,
+            cpu_svn,
+            pce_svn,
+            pce_id,
+        })
+    }
+}
+
+/// The certification data of a quote
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PckCertificationData<'a> {
+    /// Type 1, the PPID is in plain text
+    PpidCleartext(PckIdentifier<'a>),
+    /// Type 2, the PPID is encrypted with RSA-2048-OAEP
+    PpidEncryptedRsa2048(PckIdentifier<'a>),
+    /// Type 3, the PPID is encrypted with RSA-3072-OAEP
+    PpidEncryptedRsa3072(PckIdentifier<'a>),
+    /// Type 4, the PCK leaf certificate without its issuer chain
+    PckLeafCertificate(Box<Certificate>),
+    /// Type 5, the PCK certificate chain, leaf first
+    PckCertificateChain(Vec<Certificate>),
+}
+
+impl<'a> PckCertificationData<'a> {
+    /// Parse the certification data of `data_type` from its raw `data`.
+    ///
+    /// # Errors
+    /// * `Error::Quote` if the `data_type` is not one of 1 to 5, or the
+    ///   `data` is the wrong size for the PCK identifier of types 1 to 3.
+    /// * `Error::Der` if the PEM certificates of types 4 and 5 can not be
+    ///   decoded.
+    pub fn try_from_raw(data_type: u16, data: &'a [u8]) -> Result<Self, Error> {
+        Ok(match data_type {
+            PPID_CLEARTEXT_TYPE => {
+                Self::PpidCleartext(PckIdentifier::read(data, PPID_SIZE, data_type)?)
+            }
+            PPID_ENCRYPTED_RSA_2048_TYPE => Self::PpidEncryptedRsa2048(PckIdentifier::read(
+                data,
+                PPID_RSA_2048_SIZE,
+                data_type,
+            )?),
+            PPID_ENCRYPTED_RSA_3072_TYPE => Self::PpidEncryptedRsa3072(PckIdentifier::read(
+                data,
+                PPID_RSA_3072_SIZE,
+                data_type,
+            )?),
+            PCK_LEAF_CERTIFICATE_TYPE => {
+                Self::PckLeafCertificate(Box::new(Certificate::from_pem(trim_nul(data))?))
+            }
+            PCK_CERTIFICATE_CHAIN_TYPE => {
+                Self::PckCertificateChain(Certificate::load_pem_chain(trim_nul(data))?)
+            }
+            _ => return Err(QuoteError::CertificationDataType(data_type).into()),
+        })
+    }
+
+    /// Parse the certification data from the bytes of a version 3 quote.
+    ///
+    /// # Errors
+    /// * `Error::Quote` if the quote is too short for its signature data, or
+    ///   the certification data is not supported.
+    /// * `Error::Der` if the PEM certificates of types 4 and 5 can not be
+    ///   decoded.
+    pub fn try_from_quote3_bytes(quote: &'a [u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(quote);
+        reader.take(QuoteField::Header, QuoteHeader::SIZE)?;
+        reader.take(QuoteField::ReportBody, REPORT_BODY_SIZE)?;
+        let size = reader.u32(QuoteField::SignatureDataSize)? as usize;
+        let mut reader = reader.section(QuoteField::SignatureData, size)?;
+        reader.take(QuoteField::QuoteSignature, ECDSA_P256_SIZE)?;
+        reader.take(QuoteField::AttestationKey, ECDSA_P256_SIZE)?;
+        reader.take(QuoteField::QeReport, QE_REPORT_SIZE)?;
+        reader.take(QuoteField::QeReportSignature, ECDSA_P256_SIZE)?;
+        let authentication_data_size = reader.u16(QuoteField::QeAuthenticationDataSize)? as usize;
+        reader.take(QuoteField::QeAuthenticationData, authentication_data_size)?;
+        let data_type = reader.u16(QuoteField::CertificationDataType)?;
+        let size = reader.u32(QuoteField::CertificationDataSize)? as usize;
+        Self::try_from_raw(data_type, reader.take(QuoteField::CertificationData, size)?)
+    }
+
+    /// The certification data type, 1 to 5
+    pub fn data_type(&self) -> u16 {
+        match self {
+            Self::PpidCleartext(_) => PPID_CLEARTEXT_TYPE,
+            Self::PpidEncryptedRsa2048(_) => PPID_ENCRYPTED_RSA_2048_TYPE,
+            Self::PpidEncryptedRsa3072(_) => PPID_ENCRYPTED_RSA_3072_TYPE,
+            Self::PckLeafCertificate(_) => PCK_LEAF_CERTIFICATE_TYPE,
+            Self::PckCertificateChain(_) => PCK_CERTIFICATE_CHAIN_TYPE,
+        }
+    }
+
+    /// The PCK identifier, for types 1 to 3
+    pub fn pck_identifier(&self) -> Option<&PckIdentifier<'a>> {
+        match self {
+            Self::PpidCleartext(identifier)
+            | Self::PpidEncryptedRsa2048(identifier)
+            | Self::PpidEncryptedRsa3072(identifier) => Some(identifier),
+            _ => None,
+        }
+    }
+
+    /// The path and query of the PCS or PCCS request for the PCK certificate
+    /// of the platform, relative to the base URL of the service.
+    ///
+    /// Only the PPID encrypted with RSA-3072-OAEP, type 3, is accepted by the
+    /// service, `None` is returned for the other types.
+    pub fn pck_certificate_request(&self) -> Option<String> {
+        let Self::PpidEncryptedRsa3072(identifier) = self else {
+            return None;
+        };
+        Some(format!(
+            "{PCK_CERTIFICATE_PATH}?encrypted_ppid={}&cpusvn={}&pcesvn={}&pceid={}",
+            hex::encode_upper(identifier.ppid),
+            hex::encode_upper(identifier.cpu_svn.as_ref()),
+            hex::encode_upper(identifier.pce_svn.to_le_bytes()),
+            identifier.pce_id,
+        ))
+    }
+
+    /// The PCK certificate chain, resolving it with `resolver` unless it's
+    /// embedded in the certification data.
+    ///
+    /// # Errors
+    /// The errors of the `resolver`.
+    pub fn pck_certificate_chain(
+        &self,
+        resolver: &impl PckResolver,
+    ) -> Result<Vec<Certificate>, Error> {
+        match self {
+            Self::PckCertificateChain(chain) => Ok(chain.clone()),
+            _ => resolver.resolve(self),
+        }
+    }
+}
+
+/// Resolves the PCK certificate chain of certification data which doesn't
+/// embed it, types 1 to 4.
+///
+/// Implementations will usually query a provisioning service, the PCK
+/// identifier of types 1 to 3 has the parameters of the PCS
+/// `GET /sgx/certification/v4/pckcert` request.
+pub trait PckResolver {
+    /// Resolve the PCK certificate chain, leaf first, for the
+    /// `certification_data`.
+    ///
+    /// # Errors
+    /// `Error::UnsupportedQuoteCertificationData` if the `certification_data`
+    /// can not be resolved. Implementations may return other errors for
+    /// failures to reach the provisioning service.
+    fn resolve(
+        &self,
+        certification_data: &PckCertificationData<'_>,
+    ) -> Result<Vec<Certificate>, Error>;
+}
+
+impl<R: PckResolver> PckResolver for &R {
+    fn resolve(
+        &self,
+        certification_data: &PckCertificationData<'_>,
+    ) -> Result<Vec<Certificate>, Error> {
+        (*self).resolve(certification_data)
+    }
+}
+
+/// A [`PckResolver`] which resolves nothing, only certification data with an
+/// embedded PCK certificate chain, type 5, is supported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmbeddedPckChainOnly;
+
+impl PckResolver for EmbeddedPckChainOnly {
+    fn resolve(
+        &self,
+        _certification_data: &PckCertificationData<'_>,
+    ) -> Result<Vec<Certificate>, Error> {
+        Err(Error::UnsupportedQuoteCertificationData)
+    }
+}
+
+/// The response to a PCS or PCCS request for a PCK certificate, see
+/// [`PckCertificationData::pck_certificate_request()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PckCertificateResponse {
+    certificate: String,
+    issuer_chain: String,
+}
+
+impl PckCertificateResponse {
+    /// Create a new instance
+    ///
+    /// # Arguments
+    /// * `certificate` - The body of the response, the PEM PCK certificate.
+    /// * `issuer_chain` - The `SGX-PCK-Certificate-Issuer-Chain` header of
+    ///   the response, the URL encoded PEM issuer chain.
+    pub fn new(certificate: impl Into<String>, issuer_chain: impl Into<String>) -> Self {
+        Self {
+            certificate: certificate.into(),
+            issuer_chain: issuer_chain.into(),
+        }
+    }
+
+    /// The PCK certificate chain, leaf first
+    ///
+    /// # Errors
+    /// * `Error::UrlEncoding` if the issuer chain is not validly URL encoded.
+    /// * `Error::Der` if a certificate can not be decoded.
+    pub fn certificate_chain(&self) -> Result<Vec<Certificate>, Error> {
+        let mut chain = Vec::from([Certificate::from_pem(&self.certificate)?]);
+        chain.extend(Certificate::load_pem_chain(&percent_decode(
+            &self.issuer_chain,
+        )?)?);
+        Ok(chain)
+    }
+}
+
+/// A [`PckResolver`] which requests the PCK certificate from a PCCS, or the
+/// Intel PCS.
+///
+/// The request is made by `fetch`, with the path and query of
+/// [`PckCertificationData::pck_certificate_request()`]. `fetch` is
+/// responsible for prepending the base URL of the service and performing the
+/// request, usually via HTTP.
+///
+/// ```
+/// use mc_attestation_verifier::{
+///     EmbeddedPckChainOnly, PccsPckResolver, PckCertificateResponse, PckCertificationData,
+///     PckResolver,
+/// };
+///
+/// let resolver = PccsPckResolver::new(|path: &str| {
+///     Err::<PckCertificateResponse, _>(format!("no network for https://pccs.example.com{path}"))
+/// });
+/// # let bytes = [0u8; 404];
+/// let data = PckCertificationData::try_from_raw(3, &bytes).unwrap();
+/// assert!(data.pck_certificate_chain(&resolver).is_err());
+/// ```
+pub struct PccsPckResolver<F> {
+    fetch: F,
+}
+
+impl<F> PccsPckResolver<F> {
+    /// Create a new instance which requests with `fetch`
+    pub fn new(fetch: F) -> Self {
+        Self { fetch }
+    }
+}
+
+impl<F> Debug for PccsPckResolver<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PccsPckResolver").finish_non_exhaustive()
+    }
+}
+
+impl<F, E> PckResolver for PccsPckResolver<F>
+where
+    F: Fn(&str) -> Result<PckCertificateResponse, E>,
+    E: Display,
+{
+    fn resolve(
+        &self,
+        certification_data: &PckCertificationData<'_>,
+    ) -> Result<Vec<Certificate>, Error> {
+        let request = certification_data
+            .pck_certificate_request()
+            .ok_or(Error::UnsupportedQuoteCertificationData)?;
+        let response = (self.fetch)(&request).map_err(|e| Error::PckResolution(e.to_string()))?;
+        response.certificate_chain()
+    }
+}
+
+fn trim_nul(bytes: &[u8]) -> &[u8] {
+    let end = bytes
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map_or(0, |index| index + 1);
+    &bytes[..end]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    const QUOTE: &[u8] = include_bytes!("../data/tests/hw_quote.dat");
+    const PROCESSOR_CA: &str = include_str!("../data/tests/processor_ca.pem");
+    const ROOT_CA: &str = include_str!("../data/tests/root_ca.pem");
+
+    fn pck_identifier_bytes(ppid_size: usize) -> Vec<u8> {
+        let mut bytes = vec![1; ppid_size];
+        bytes.extend([2; CPU_SVN_SIZE]);
+        bytes.extend(3u16.to_le_bytes());
+        bytes.extend([4, 5]);
+        bytes
+    }
+
+    // A resolver which answers with a fixed chain
+    struct FixedResolver(Vec<Certificate>);
+
+    impl PckResolver for FixedResolver {
+        fn resolve(
+            &self,
+            _certification_data: &PckCertificationData<'_>,
+        ) -> Result<Vec<Certificate>, Error> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn pck_certificate_chain_from_quote() {
+        let data = PckCertificationData::try_from_quote3_bytes(QUOTE)
+            .expect("Failed to parse certification data");
+        assert_eq!(data.data_type(), PCK_CERTIFICATE_CHAIN_TYPE);
+        assert_eq!(data.pck_identifier(), None);
+        let chain = data
+            .pck_certificate_chain(&EmbeddedPckChainOnly)
+            .expect("Failed to get chain");
+        assert_eq!(chain.len(), 3);
+    }
+
+    #[test]
+    fn truncated_quote() {
+        assert_matches!(
+            PckCertificationData::try_from_quote3_bytes(&QUOTE[..QUOTE.len() - 10]),
+            Err(Error::Quote(QuoteError::Truncated { .. }))
+        );
+    }
+
+    #[yare::parameterized(
+        cleartext = { PPID_CLEARTEXT_TYPE, 16 },
+        rsa_2048 = { PPID_ENCRYPTED_RSA_2048_TYPE, 256 },
+        rsa_3072 = { PPID_ENCRYPTED_RSA_3072_TYPE, 384 },
+    )]
+    fn pck_identifier(data_type: u16, ppid_size: usize) {
+        let bytes = pck_identifier_bytes(ppid_size);
+        let data = PckCertificationData::try_from_raw(data_type, &bytes)
+            .expect("Failed to parse certification data");
+        assert_eq!(data.data_type(), data_type);
+
+        let identifier = data.pck_identifier().expect("Missing PCK identifier");
+        assert_eq!(identifier.ppid(), vec![1; ppid_size]);
+        assert_eq!(identifier.cpu_svn(), &CpuSvn::from([2; CPU_SVN_SIZE]));
+        assert_eq!(identifier.pce_svn(), 3);
+        assert_eq!(identifier.pce_id(), PceId::new([4, 5]));
+    }
+
+    #[test]
+    fn pck_identifier_wrong_size() {
+        let bytes = pck_identifier_bytes(17);
+        assert_eq!(
+            PckCertificationData::try_from_raw(PPID_CLEARTEXT_TYPE, &bytes),
+            Err(QuoteError::CertificationDataSize {
+                data_type: PPID_CLEARTEXT_TYPE,
+                expected: 36,
+                actual: 37,
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn pck_leaf_certificate() {
+        let data =
+            PckCertificationData::try_from_raw(PCK_LEAF_CERTIFICATE_TYPE, PROCESSOR_CA.as_bytes())
+                .expect("Failed to parse certification data");
+        let expected = Certificate::from_pem(PROCESSOR_CA).expect("Failed to parse certificate");
+        assert_eq!(
+            data,
+            PckCertificationData::PckLeafCertificate(Box::new(expected))
+        );
+    }
+
+    #[test]
+    fn unsupported_data_type() {
+        assert_eq!(
+            PckCertificationData::try_from_raw(6, b"data"),
+            Err(QuoteError::CertificationDataType(6).into())
+        );
+    }
+
+    #[test]
+    fn ppid_needs_resolver() {
+        let bytes = pck_identifier_bytes(16);
+        let data = PckCertificationData::try_from_raw(PPID_CLEARTEXT_TYPE, &bytes)
+            .expect("Failed to parse certification data");
+        assert_eq!(
+            data.pck_certificate_chain(&EmbeddedPckChainOnly),
+            Err(Error::UnsupportedQuoteCertificationData)
+        );
+
+        let chain = [PROCESSOR_CA, ROOT_CA]
+            .iter()
+            .map(|pem| Certificate::from_pem(pem).expect("Failed to parse certificate"))
+            .collect::<Vec<_>>();
+        let resolver = FixedResolver(chain.clone());
+        assert_eq!(data.pck_certificate_chain(&resolver), Ok(chain));
+    }
+
+    fn url_encode(pem: &str) -> String {
+        pem.replace('\n', "%0A")
+            .replace(' ', "%20")
+            .replace('+', "%2B")
+            .replace('/', "%2F")
+            .replace('=', "%3D")
+    }
+
+    fn pck_response() -> PckCertificateResponse {
+        let issuer_chain = [PROCESSOR_CA, ROOT_CA].concat();
+        PckCertificateResponse::new(
+            include_str!("../data/tests/leaf_cert.pem"),
+            url_encode(&issuer_chain),
+        )
+    }
+
+    #[test]
+    fn pck_certificate_request() {
+        let mut bytes = vec![0xAB; PPID_RSA_3072_SIZE];
+        bytes.extend([0x0C; CPU_SVN_SIZE]);
+        bytes.extend(0x0102u16.to_le_bytes());
+        bytes.extend([0x00, 0x01]);
+        let data = PckCertificationData::try_from_raw(PPID_ENCRYPTED_RSA_3072_TYPE, &bytes)
+            .expect("Failed to parse certification data");
+
+        let expected = format!(
+            "/sgx/certification/v4/pckcert?encrypted_ppid={}&cpusvn={}&pcesvn=0201&pceid=0001",
+            "AB".repeat(PPID_RSA_3072_SIZE),
+            "0C".repeat(CPU_SVN_SIZE),
+        );
+        assert_eq!(data.pck_certificate_request(), Some(expected));
+    }
+
+    #[yare::parameterized(
+        cleartext = { PPID_CLEARTEXT_TYPE, 16 },
+        rsa_2048 = { PPID_ENCRYPTED_RSA_2048_TYPE, 256 },
+    )]
+    fn no_pck_certificate_request(data_type: u16, ppid_size: usize) {
+        let bytes = pck_identifier_bytes(ppid_size);
+        let data = PckCertificationData::try_from_raw(data_type, &bytes)
+            .expect("Failed to parse certification data");
+
+        assert_eq!(data.pck_certificate_request(), None);
+        let resolver = PccsPckResolver::new(|_: &str| Ok::<_, String>(pck_response()));
+        assert_eq!(
+            data.pck_certificate_chain(&resolver),
+            Err(Error::UnsupportedQuoteCertificationData)
+        );
+    }
+
+    #[test]
+    fn pccs_resolver() {
+        let bytes = pck_identifier_bytes(PPID_RSA_3072_SIZE);
+        let data = PckCertificationData::try_from_raw(PPID_ENCRYPTED_RSA_3072_TYPE, &bytes)
+            .expect("Failed to parse certification data");
+        let resolver = PccsPckResolver::new(|path: &str| {
+            assert!(path.starts_with("/sgx/certification/v4/pckcert?encrypted_ppid=0101"));
+            Ok::<_, String>(pck_response())
+        });
+
+        let chain = data
+            .pck_certificate_chain(&resolver)
+            .expect("Failed to resolve chain");
+        let expected = [
+            include_str!("../data/tests/leaf_cert.pem"),
+            PROCESSOR_CA,
+            ROOT_CA,
+        ]
+        .iter()
+        .map(|pem| Certificate::from_pem(pem).expect("Failed to parse certificate"))
+        .collect::<Vec<_>>();
+        assert_eq!(chain, expected);
+    }
+
+    #[test]
+    fn pccs_resolver_fetch_fails() {
+        let bytes = pck_identifier_bytes(PPID_RSA_3072_SIZE);
+        let data = PckCertificationData::try_from_raw(PPID_ENCRYPTED_RSA_3072_TYPE, &bytes)
+            .expect("Failed to parse certification data");
+        let resolver =
+            PccsPckResolver::new(|_: &str| Err::<PckCertificateResponse, _>("404 Not Found"));
+
+        assert_eq!(
+            data.pck_certificate_chain(&resolver),
+            Err(Error::PckResolution("404 Not Found".into()))
+        );
+    }
+
+    #[test]
+    fn pck_response_with_bad_issuer_chain() {
+        let response = PckCertificateResponse::new(
+            include_str!("../data/tests/leaf_cert.pem"),
+            "-----BEGIN%2",
+        );
+
+        assert_eq!(response.certificate_chain(), Err(Error::UrlEncoding));
+    }
+}
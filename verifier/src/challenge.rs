@@ -0,0 +1,257 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Challenge-response freshness for quotes.
+//!
+//! A quote says nothing about when it was created, a quote captured from an
+//! earlier session verifies just as well as a new one. To prove a quote is
+//! fresh, the relying party generates a [`Challenge`] and sends its nonce to
+//! the enclave. The enclave puts the nonce in the first 32 bytes of its
+//! REPORT_DATA, leaving the remaining 32 bytes for application data like the
+//! digest of a key. The relying party then verifies the quote with a
+//! [`ChallengeVerifier`], which checks that the REPORT_DATA commits to the
+//! nonce before the challenge expires.
+
+use crate::{
+    choice_to_status_message, Accessor, Error, VerificationMessage, VerificationOutput, Verifier,
+};
+use core::{fmt::Formatter, time::Duration};
+use der::DateTime;
+use mc_sgx_core_types::ReportData;
+use rand_core::{CryptoRng, RngCore};
+use subtle::{Choice, ConstantTimeEq};
+
+/// The size of the nonce of a [`Challenge`]
+pub const NONCE_SIZE: usize = 32;
+
+/// A random nonce and when it stops being accepted.
+///
+/// ```
+/// use core::time::Duration;
+/// use mc_attestation_verifier::{Challenge, ChallengeVerifier, Verifier};
+/// # use der::DateTime;
+/// # let now = DateTime::new(2024, 1, 1, 0, 0, 0).unwrap();
+/// # let mut rng = rand::thread_rng();
+///
+/// let challenge = Challenge::generate(&mut rng, now, Duration::from_secs(60)).unwrap();
+///
+/// // The enclave puts `challenge.nonce()` in its REPORT_DATA
+/// let report_data = challenge.report_data();
+///
+/// let verifier = ChallengeVerifier::new(challenge, now);
+/// let verification = verifier.verify(&report_data);
+/// assert_eq!(verification.is_success().unwrap_u8(), 1);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Challenge {
+    nonce: [u8; NONCE_SIZE],
+    expiration: DateTime,
+}
+
+impl Challenge {
+    /// Create a new instance with a `nonce` which expires at `expiration`.
+    ///
+    /// The `nonce` must be unpredictable and never reused, prefer
+    /// [`Challenge::generate()`] unless the nonce comes from elsewhere.
+    pub fn new(nonce: [u8; NONCE_SIZE], expiration: DateTime) -> Self {
+        Self { nonce, expiration }
+    }
+
+    /// Generate a challenge with a random nonce.
+    ///
+    /// # Arguments
+    /// * `rng` - The cryptographically secure source of the nonce.
+    /// * `time` - The time the challenge is issued.
+    /// * `lifetime` - How long after `time` the challenge is accepted.
+    ///
+    /// # Errors
+    /// `Error::Der` if the expiration is not representable as a
+    /// [`DateTime`].
+    pub fn generate(
+        rng: &mut (impl CryptoRng + RngCore),
+        time: DateTime,
+        lifetime: Duration,
+    ) -> Result<Self, Error> {
+        let mut nonce = [0u8; NONCE_SIZE];
+        rng.fill_bytes(&mut nonce);
+        let expiration =
+            DateTime::from_unix_duration(time.unix_duration().saturating_add(lifetime))?;
+        Ok(Self::new(nonce, expiration))
+    }
+
+    /// The nonce for the enclave to commit to
+    pub fn nonce(&self) -> &[u8; NONCE_SIZE] {
+        &self.nonce
+    }
+
+    /// The time at which the challenge is no longer accepted
+    pub fn expiration(&self) -> DateTime {
+        self.expiration
+    }
+
+    /// The REPORT_DATA committing to the nonce, with the application data
+    /// zeroed.
+    pub fn report_data(&self) -> ReportData {
+        let mut report_data = [0u8; ReportData::SIZE];
+        report_data[..NONCE_SIZE].copy_from_slice(&self.nonce);
+        ReportData::from(report_data)
+    }
+}
+
+/// Verifier for ensuring the [`ReportData`] commits to the nonce of a
+/// [`Challenge`] which hasn't expired.
+///
+/// Only the first 32 bytes of the report data are compared, the remaining
+/// bytes are for the application to verify.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChallengeVerifier {
+    challenge: Challenge,
+    time: DateTime,
+}
+
+impl ChallengeVerifier {
+    /// Create a new instance
+    ///
+    /// # Arguments
+    /// * `challenge` - The challenge which was sent to the enclave.
+    /// * `time` - The time the quote is being verified, the challenge must
+    ///   expire after this.
+    pub fn new(challenge: Challenge, time: DateTime) -> Self {
+        Self { challenge, time }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.time >= self.challenge.expiration
+    }
+}
+
+impl<E: Accessor<ReportData>> Verifier<E> for ChallengeVerifier {
+    type Value = ReportData;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let actual = evidence.get();
+        let bytes: &[u8] = actual.as_ref();
+        let committed = bytes[..NONCE_SIZE].ct_eq(&self.challenge.nonce);
+        let fresh = Choice::from(!self.is_expired() as u8);
+        VerificationOutput::new(actual, committed & fresh)
+    }
+}
+
+impl VerificationMessage<ReportData> for ChallengeVerifier {
+    fn fmt_padded(
+        &self,
+        f: &mut Formatter<'_>,
+        pad: usize,
+        output: &VerificationOutput<ReportData>,
+    ) -> core::fmt::Result {
+        let is_success = output.is_success();
+        let status = choice_to_status_message(is_success);
+        write!(
+            f,
+            "{:pad$}{status} The report data should commit to the nonce {} before {}",
+            "",
+            hex::encode_upper(self.challenge.nonce),
+            self.challenge.expiration
+        )?;
+        if (!is_success).into() {
+            let bytes: &[u8] = output.value.as_ref();
+            if bytes[..NONCE_SIZE] != self.challenge.nonce {
+                write!(
+                    f,
+                    ", but the report data committed to {}",
+                    hex::encode_upper(&bytes[..NONCE_SIZE])
+                )?;
+            } else {
+                write!(f, ", but it was verified at {}", self.time)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::VerificationTreeDisplay;
+    use alloc::format;
+
+    fn time() -> DateTime {
+        DateTime::new(2024, 3, 1, 12, 0, 0).expect("Failed to create time")
+    }
+
+    fn challenge() -> Challenge {
+        let expiration = DateTime::new(2024, 3, 1, 12, 5, 0).expect("Failed to create time");
+        Challenge::new([0xAB; NONCE_SIZE], expiration)
+    }
+
+    #[test]
+    fn generate_challenge() {
+        let mut rng = rand::thread_rng();
+        let lifetime = Duration::from_secs(300);
+
+        let first = Challenge::generate(&mut rng, time(), lifetime).expect("Failed to generate");
+        let second = Challenge::generate(&mut rng, time(), lifetime).expect("Failed to generate");
+
+        assert_eq!(first.expiration(), challenge().expiration());
+        assert_ne!(first.nonce(), second.nonce());
+    }
+
+    #[test]
+    fn report_data_leads_with_nonce() {
+        let report_data = challenge().report_data();
+        let bytes: &[u8] = report_data.as_ref();
+
+        assert_eq!(&bytes[..NONCE_SIZE], &[0xAB; NONCE_SIZE]);
+        assert_eq!(&bytes[NONCE_SIZE..], &[0; ReportData::SIZE - NONCE_SIZE]);
+    }
+
+    #[test]
+    fn fresh_commitment_succeeds() {
+        let mut report_data = [0x11; ReportData::SIZE];
+        report_data[..NONCE_SIZE].copy_from_slice(&[0xAB; NONCE_SIZE]);
+        let verifier = ChallengeVerifier::new(challenge(), time());
+        let verification = verifier.verify(&ReportData::from(report_data));
+
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        assert_eq!(
+            format!("{displayable}"),
+            format!(
+                "- [x] The report data should commit to the nonce {} before 2024-03-01T12:05:00Z",
+                "AB".repeat(NONCE_SIZE)
+            )
+        );
+    }
+
+    #[test]
+    fn other_nonce_fails() {
+        let verifier = ChallengeVerifier::new(challenge(), time());
+        let verification = verifier.verify(&ReportData::from([0xCD; ReportData::SIZE]));
+
+        assert_eq!(verification.is_success().unwrap_u8(), 0);
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        assert_eq!(
+            format!("{displayable}"),
+            format!(
+                "- [ ] The report data should commit to the nonce {} before 2024-03-01T12:05:00Z, but the report data committed to {}",
+                "AB".repeat(NONCE_SIZE),
+                "CD".repeat(NONCE_SIZE)
+            )
+        );
+    }
+
+    #[test]
+    fn expired_challenge_fails() {
+        let expiration = challenge().expiration();
+        let verifier = ChallengeVerifier::new(challenge(), expiration);
+        let verification = verifier.verify(&challenge().report_data());
+
+        assert_eq!(verification.is_success().unwrap_u8(), 0);
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        assert_eq!(
+            format!("{displayable}"),
+            format!(
+                "- [ ] The report data should commit to the nonce {} before 2024-03-01T12:05:00Z, but it was verified at 2024-03-01T12:05:00Z",
+                "AB".repeat(NONCE_SIZE)
+            )
+        );
+    }
+}
@@ -0,0 +1,569 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! The collateral needed to verify a quote, as one consistent bundle.
+//!
+//! The collateral is retrieved from different PCS, or PCCS, endpoints:
+//! - The PCK CRL, with its issuer chain in the `SGX-PCK-CRL-Issuer-Chain`
+//!   header.
+//! - The root CA CRL.
+//! - The TCB info, with its issuer chain in the `TCB-Info-Issuer-Chain`
+//!   header.
+//! - The QE identity, with its issuer chain in the
+//!   `SGX-Enclave-Identity-Issuer-Chain` header.
+//!
+//! Mixing up the parts, for instance using the CRL of the platform CA with the
+//! issuer chain of the processor CA, would only surface as an obscure failure
+//! deep in the verification. [`Collateral`] checks the parts fit together when
+//! it is created.
+
+use crate::{Error, QeIdentity, SignedQeIdentity, SignedTcbInfo, TcbInfo};
+use alloc::{format, string::ToString, vec::Vec};
+use core::fmt::{Display, Formatter};
+use der::{DateTime, Decode};
+use mc_sgx_dcap_types::Collateral as DcapCollateral;
+use x509_cert::{crl::CertificateList, Certificate};
+
+/// The collateral for verifying a quote.
+///
+/// All of the parts have been parsed and checked to be consistent with each
+/// other:
+/// - The PCK CRL is issued by the leaf of the PCK CRL issuer chain.
+/// - The issuer chains all end in the same root CA certificate.
+/// - The root CA CRL is issued by the root CA.
+/// - The TCB info and QE identity are well formed.
+///
+/// The signatures are *not* verified, that is done when verifying a quote.
+#[derive(Debug, Clone)]
+pub struct Collateral {
+    pck_crl_issuer_chain: Vec<Certificate>,
+    root_ca_crl: CertificateList,
+    pck_crl: CertificateList,
+    tcb_info_issuer_chain: Vec<Certificate>,
+    signed_tcb_info: SignedTcbInfo,
+    tcb_info: TcbInfo,
+    qe_identity_issuer_chain: Vec<Certificate>,
+    signed_qe_identity: SignedQeIdentity,
+    qe_identity: QeIdentity,
+}
+
+impl Collateral {
+    /// Create a new instance from the decoded parts.
+    ///
+    /// The issuer chains are leaf first, root last, as provided by the PCS.
+    ///
+    /// # Errors
+    /// - `Error::Serde` if the `tcb_info` or `qe_identity` JSON is malformed.
+    /// - `Error::InconsistentCollateral` if the parts do not fit together.
+    pub fn new(
+        pck_crl_issuer_chain: Vec<Certificate>,
+        root_ca_crl: CertificateList,
+        pck_crl: CertificateList,
+        tcb_info_issuer_chain: Vec<Certificate>,
+        tcb_info: &str,
+        qe_identity_issuer_chain: Vec<Certificate>,
+        qe_identity: &str,
+    ) -> Result<Self, Error> {
+        let signed_tcb_info = SignedTcbInfo::try_from(tcb_info)?;
+        let signed_qe_identity = SignedQeIdentity::try_from(qe_identity)?;
+        let collateral = Self {
+            tcb_info: TcbInfo::try_from(&signed_tcb_info)?,
+            qe_identity: QeIdentity::try_from(&signed_qe_identity)?,
+            pck_crl_issuer_chain,
+            root_ca_crl,
+            pck_crl,
+            tcb_info_issuer_chain,
+            signed_tcb_info,
+            qe_identity_issuer_chain,
+            signed_qe_identity,
+        };
+        collateral.validate()?;
+        Ok(collateral)
+    }
+
+    /// Create a new instance from the raw parts, as they are provided by the
+    /// PCS.
+    ///
+    /// The issuer chains are concatenated PEM certificates, leaf first. The
+    /// CRLs are DER encoded. Trailing NUL terminators, as in the collateral
+    /// from the DCAP libraries, are ignored.
+    ///
+    /// # Errors
+    /// - `Error::Der` if a certificate or CRL can not be decoded.
+    /// - The errors of [`Collateral::new()`].
+    pub fn try_from_raw_parts(
+        pck_crl_issuer_chain: &str,
+        root_ca_crl: &[u8],
+        pck_crl: &[u8],
+        tcb_info_issuer_chain: &str,
+        tcb_info: &str,
+        qe_identity_issuer_chain: &str,
+        qe_identity: &str,
+    ) -> Result<Self, Error> {
+        Self::new(
+            pem_chain(pck_crl_issuer_chain)?,
+            CertificateList::from_der(trim_nul(root_ca_crl))?,
+            CertificateList::from_der(trim_nul(pck_crl))?,
+            pem_chain(tcb_info_issuer_chain)?,
+            tcb_info.trim_end_matches('\0'),
+            pem_chain(qe_identity_issuer_chain)?,
+            qe_identity.trim_end_matches('\0'),
+        )
+    }
+
+    /// The issuer chain of the PCK CRL, leaf first
+    pub fn pck_crl_issuer_chain(&self) -> &[Certificate] {
+        &self.pck_crl_issuer_chain
+    }
+
+    /// The CRL of the root CA
+    pub fn root_ca_crl(&self) -> &CertificateList {
+        &self.root_ca_crl
+    }
+
+    /// The CRL of the CA which issued the PCK certificates
+    pub fn pck_crl(&self) -> &CertificateList {
+        &self.pck_crl
+    }
+
+    /// The signing chain of the TCB info, leaf first
+    pub fn tcb_info_issuer_chain(&self) -> &[Certificate] {
+        &self.tcb_info_issuer_chain
+    }
+
+    /// The signed TCB info
+    pub fn signed_tcb_info(&self) -> &SignedTcbInfo {
+        &self.signed_tcb_info
+    }
+
+    /// The TCB info
+    pub fn tcb_info(&self) -> &TcbInfo {
+        &self.tcb_info
+    }
+
+    /// The signing chain of the QE identity, leaf first
+    pub fn qe_identity_issuer_chain(&self) -> &[Certificate] {
+        &self.qe_identity_issuer_chain
+    }
+
+    /// The signed QE identity
+    pub fn signed_qe_identity(&self) -> &SignedQeIdentity {
+        &self.signed_qe_identity
+    }
+
+    /// The QE identity
+    pub fn qe_identity(&self) -> &QeIdentity {
+        &self.qe_identity
+    }
+
+    /// The time the collateral expires, and the item which expires first.
+    ///
+    /// The collateral expires at the earliest of the `nextUpdate` of the TCB
+    /// info, QE identity, and CRLs, and the `notAfter` of the certificates in
+    /// the issuer chains. A verification result based on the collateral
+    /// should not be relied on after this time.
+    ///
+    /// # Errors
+    /// `Error::Der` if the `nextUpdate` of the TCB info or QE identity is not
+    /// a valid time.
+    pub fn expiration(&self) -> Result<(DateTime, CollateralItem), Error> {
+        self.expiration_with_pck_certificate_chain(&[])
+    }
+
+    /// The time the collateral, or the PCK certificate chain of a quote,
+    /// expires, and the item which expires first.
+    ///
+    /// This is [`Collateral::expiration()`] with the `notAfter` of the
+    /// certificates in the `pck_certificate_chain` included, the unified
+    /// expiration of a verified quote.
+    ///
+    /// # Arguments
+    /// - `pck_certificate_chain` - The PCK certificate chain of the quote,
+    ///   leaf first.
+    ///
+    /// # Errors
+    /// `Error::Der` if the `nextUpdate` of the TCB info or QE identity is not
+    /// a valid time.
+    pub fn expiration_with_pck_certificate_chain(
+        &self,
+        pck_certificate_chain: &[Certificate],
+    ) -> Result<(DateTime, CollateralItem), Error> {
+        let tcb_info = (self.tcb_info.next_update()?, CollateralItem::TcbInfo);
+        let mut items = Vec::from([(self.qe_identity.next_update()?, CollateralItem::QeIdentity)]);
+        for (crl, item) in [
+            (&self.root_ca_crl, CollateralItem::RootCaCrl),
+            (&self.pck_crl, CollateralItem::PckCrl),
+        ] {
+            if let Some(next_update) = crl.tbs_cert_list.next_update {
+                items.push((next_update.to_date_time(), item));
+            }
+        }
+        items.extend(chain_expirations(
+            &self.pck_crl_issuer_chain,
+            CollateralItem::PckCrlIssuerChain,
+        ));
+        items.extend(chain_expirations(
+            &self.tcb_info_issuer_chain,
+            CollateralItem::TcbInfoIssuerChain,
+        ));
+        items.extend(chain_expirations(
+            &self.qe_identity_issuer_chain,
+            CollateralItem::QeIdentityIssuerChain,
+        ));
+        items.extend(chain_expirations(
+            pck_certificate_chain,
+            CollateralItem::PckCertificateChain,
+        ));
+
+        Ok(items.into_iter().fold(
+            tcb_info,
+            |earliest, item| {
+                if item.0 < earliest.0 {
+                    item
+                } else {
+                    earliest
+                }
+            },
+        ))
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let pck_crl_issuer = self
+            .pck_crl_issuer_chain
+            .first()
+            .ok_or_else(|| inconsistent("the PCK CRL issuer chain is empty"))?;
+        if pck_crl_issuer.tbs_certificate.subject != self.pck_crl.tbs_cert_list.issuer {
+            return Err(inconsistent(
+                "the PCK CRL is not issued by the PCK CRL issuer chain",
+            ));
+        }
+
+        let root = root_of(&self.pck_crl_issuer_chain, "PCK CRL")?;
+        if root_of(&self.tcb_info_issuer_chain, "TCB info")? != root {
+            return Err(inconsistent(
+                "the TCB info issuer chain has a different root CA",
+            ));
+        }
+        if root_of(&self.qe_identity_issuer_chain, "QE identity")? != root {
+            return Err(inconsistent(
+                "the QE identity issuer chain has a different root CA",
+            ));
+        }
+        if root.tbs_certificate.subject != self.root_ca_crl.tbs_cert_list.issuer {
+            return Err(inconsistent("the root CA CRL is not issued by the root CA"));
+        }
+
+        Ok(())
+    }
+}
+
+/// An individual item of the [`Collateral`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CollateralItem {
+    /// The TCB info
+    TcbInfo,
+    /// The QE identity
+    QeIdentity,
+    /// The root CA CRL
+    RootCaCrl,
+    /// The PCK CRL
+    PckCrl,
+    /// The certificate at the index, leaf first, of the PCK CRL issuer chain
+    PckCrlIssuerChain(usize),
+    /// The certificate at the index, leaf first, of the TCB info issuer chain
+    TcbInfoIssuerChain(usize),
+    /// The certificate at the index, leaf first, of the QE identity issuer
+    /// chain
+    QeIdentityIssuerChain(usize),
+    /// The certificate at the index, leaf first, of the PCK certificate chain
+    /// of the quote
+    PckCertificateChain(usize),
+}
+
+impl Display for CollateralItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TcbInfo => write!(f, "TCB info"),
+            Self::QeIdentity => write!(f, "QE identity"),
+            Self::RootCaCrl => write!(f, "root CA CRL"),
+            Self::PckCrl => write!(f, "PCK CRL"),
+            Self::PckCrlIssuerChain(index) => {
+                write!(f, "certificate {index} of the PCK CRL issuer chain")
+            }
+            Self::TcbInfoIssuerChain(index) => {
+                write!(f, "certificate {index} of the TCB info issuer chain")
+            }
+            Self::QeIdentityIssuerChain(index) => {
+                write!(f, "certificate {index} of the QE identity issuer chain")
+            }
+            Self::PckCertificateChain(index) => {
+                write!(f, "certificate {index} of the PCK certificate chain")
+            }
+        }
+    }
+}
+
+impl TryFrom<&DcapCollateral> for Collateral {
+    type Error = Error;
+
+    fn try_from(collateral: &DcapCollateral) -> Result<Self, Self::Error> {
+        Self::new(
+            collateral.pck_crl_issuer_chain().to_vec(),
+            collateral.root_ca_crl().clone(),
+            collateral.pck_crl().clone(),
+            collateral.tcb_issuer_chain().to_vec(),
+            collateral.tcb_info(),
+            collateral.qe_identity_issuer_chain().to_vec(),
+            collateral.qe_identity(),
+        )
+    }
+}
+
+fn inconsistent(reason: &str) -> Error {
+    Error::InconsistentCollateral(reason.to_string())
+}
+
+fn root_of<'a>(chain: &'a [Certificate], name: &str) -> Result<&'a Certificate, Error> {
+    chain
+        .last()
+        .ok_or_else(|| Error::InconsistentCollateral(format!("the {name} issuer chain is empty")))
+}
+
+/// The `notAfter` of each certificate in the `chain`, tagged with its `item`
+fn chain_expirations(
+    chain: &[Certificate],
+    item: fn(usize) -> CollateralItem,
+) -> impl Iterator<Item = (DateTime, CollateralItem)> + '_ {
+    chain.iter().enumerate().map(move |(index, certificate)| {
+        let not_after = certificate.tbs_certificate.validity.not_after;
+        (not_after.to_date_time(), item(index))
+    })
+}
+
+fn trim_nul(bytes: &[u8]) -> &[u8] {
+    let end = bytes
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map_or(0, |index| index + 1);
+    &bytes[..end]
+}
+
+fn pem_chain(pem: &str) -> Result<Vec<Certificate>, Error> {
+    Ok(Certificate::load_pem_chain(
+        pem.trim_end_matches('\0').as_bytes(),
+    )?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{string::String, vec};
+    use assert_matches::assert_matches;
+    use der::{asn1::GeneralizedTime, DecodePem};
+    use x509_cert::time::Time;
+
+    const ROOT_CA: &str = include_str!("../data/tests/root_ca.pem");
+    const PROCESSOR_CA: &str = include_str!("../data/tests/processor_ca.pem");
+    const LEAF_CERT: &str = include_str!("../data/tests/leaf_cert.pem");
+    const TCB_SIGNER: &str = include_str!("../data/tests/tcb_signer.pem");
+    const ROOT_CRL: &[u8] = include_bytes!("../data/tests/root_crl.der");
+    const PROCESSOR_CRL: &[u8] = include_bytes!("../data/tests/processor_crl.der");
+    const TCB_INFO_JSON: &str = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+    const QE_IDENTITY_JSON: &str = include_str!("../data/tests/qe_identity.json");
+
+    fn chain(pems: &[&str]) -> String {
+        pems.join("\n")
+    }
+
+    /// The raw parts of the test collateral, for tests to alter
+    struct RawParts {
+        pck_crl_issuer_chain: String,
+        root_ca_crl: Vec<u8>,
+        pck_crl: Vec<u8>,
+        tcb_info_issuer_chain: String,
+        tcb_info: String,
+        qe_identity_issuer_chain: String,
+        qe_identity: String,
+    }
+
+    impl Default for RawParts {
+        fn default() -> Self {
+            Self {
+                pck_crl_issuer_chain: chain(&[PROCESSOR_CA, ROOT_CA]),
+                root_ca_crl: ROOT_CRL.to_vec(),
+                pck_crl: PROCESSOR_CRL.to_vec(),
+                tcb_info_issuer_chain: chain(&[TCB_SIGNER, ROOT_CA]),
+                tcb_info: TCB_INFO_JSON.into(),
+                qe_identity_issuer_chain: chain(&[TCB_SIGNER, ROOT_CA]),
+                qe_identity: QE_IDENTITY_JSON.into(),
+            }
+        }
+    }
+
+    impl RawParts {
+        fn collateral(&self) -> Result<Collateral, Error> {
+            Collateral::try_from_raw_parts(
+                &self.pck_crl_issuer_chain,
+                &self.root_ca_crl,
+                &self.pck_crl,
+                &self.tcb_info_issuer_chain,
+                &self.tcb_info,
+                &self.qe_identity_issuer_chain,
+                &self.qe_identity,
+            )
+        }
+    }
+
+    fn collateral() -> Collateral {
+        RawParts::default()
+            .collateral()
+            .expect("Failed to create collateral")
+    }
+
+    #[test]
+    fn collateral_from_raw_parts() {
+        let collateral = collateral();
+
+        assert_eq!(collateral.pck_crl_issuer_chain().len(), 2);
+        assert_eq!(collateral.tcb_info_issuer_chain().len(), 2);
+        assert_eq!(collateral.qe_identity_issuer_chain().len(), 2);
+        assert_eq!(collateral.tcb_info().fmspc().to_string(), "00906ED50000");
+        assert_eq!(collateral.qe_identity().id(), "QE");
+        assert_eq!(
+            collateral.root_ca_crl(),
+            &CertificateList::from_der(ROOT_CRL).expect("Failed to decode CRL")
+        );
+    }
+
+    #[test]
+    fn collateral_expiration_is_earliest_item() {
+        let collateral = collateral();
+
+        let expected = "2023-05-21T22:00:36Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+        assert_eq!(
+            collateral.expiration(),
+            Ok((expected, CollateralItem::PckCrl))
+        );
+    }
+
+    #[test]
+    fn expiration_with_pck_certificate_chain() {
+        let collateral = collateral();
+        let mut pck_certificate_chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
+            .iter()
+            .map(|pem| Certificate::from_pem(pem).expect("Failed to parse certificate"))
+            .collect::<Vec<_>>();
+
+        let pck_crl_expiration = "2023-05-21T22:00:36Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+        assert_eq!(
+            collateral.expiration_with_pck_certificate_chain(&pck_certificate_chain),
+            Ok((pck_crl_expiration, CollateralItem::PckCrl))
+        );
+
+        let expected = "2023-05-01T00:00:00Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+        pck_certificate_chain[0].tbs_certificate.validity.not_after =
+            Time::GeneralTime(GeneralizedTime::from_date_time(expected));
+        assert_eq!(
+            collateral.expiration_with_pck_certificate_chain(&pck_certificate_chain),
+            Ok((expected, CollateralItem::PckCertificateChain(0)))
+        );
+    }
+
+    #[test]
+    fn collateral_item_display() {
+        assert_eq!(CollateralItem::PckCrl.to_string(), "PCK CRL");
+        assert_eq!(
+            CollateralItem::TcbInfoIssuerChain(1).to_string(),
+            "certificate 1 of the TCB info issuer chain"
+        );
+        assert_eq!(
+            CollateralItem::PckCertificateChain(0).to_string(),
+            "certificate 0 of the PCK certificate chain"
+        );
+    }
+
+    #[test]
+    fn collateral_from_nul_terminated_raw_parts() {
+        let mut parts = RawParts::default();
+        parts.pck_crl_issuer_chain.push('\0');
+        parts.root_ca_crl.push(0);
+        parts.tcb_info_issuer_chain.push('\0');
+        parts.tcb_info.push('\0');
+        parts.qe_identity_issuer_chain.push('\0');
+        parts.qe_identity.push('\0');
+
+        assert!(parts.collateral().is_ok());
+    }
+
+    #[test]
+    fn collateral_with_swapped_crls_is_inconsistent() {
+        let collateral = RawParts {
+            root_ca_crl: PROCESSOR_CRL.to_vec(),
+            pck_crl: ROOT_CRL.to_vec(),
+            ..Default::default()
+        }
+        .collateral();
+        assert_matches!(
+            collateral,
+            Err(Error::InconsistentCollateral(reason)) if reason.contains("PCK CRL")
+        );
+    }
+
+    #[test]
+    fn collateral_with_different_roots_is_inconsistent() {
+        let collateral = RawParts {
+            qe_identity_issuer_chain: chain(&[TCB_SIGNER]),
+            ..Default::default()
+        }
+        .collateral();
+        assert_eq!(
+            collateral.unwrap_err(),
+            Error::InconsistentCollateral(
+                "the QE identity issuer chain has a different root CA".into()
+            )
+        );
+    }
+
+    #[test]
+    fn collateral_with_empty_chain_is_inconsistent() {
+        let root = Certificate::from_pem(ROOT_CA).expect("Failed to decode root");
+        let collateral = Collateral::new(
+            vec![],
+            CertificateList::from_der(ROOT_CRL).expect("Failed to decode CRL"),
+            CertificateList::from_der(PROCESSOR_CRL).expect("Failed to decode CRL"),
+            vec![root.clone()],
+            TCB_INFO_JSON,
+            vec![root],
+            QE_IDENTITY_JSON,
+        );
+        assert_eq!(
+            collateral.unwrap_err(),
+            Error::InconsistentCollateral("the PCK CRL issuer chain is empty".into())
+        );
+    }
+
+    #[test]
+    fn collateral_with_malformed_tcb_info() {
+        let collateral = RawParts {
+            tcb_info: "{}".into(),
+            ..Default::default()
+        }
+        .collateral();
+        assert_matches!(collateral, Err(Error::Serde(_)));
+    }
+
+    #[test]
+    fn collateral_with_malformed_crl() {
+        let collateral = RawParts {
+            root_ca_crl: ROOT_CRL[1..].to_vec(),
+            ..Default::default()
+        }
+        .collateral();
+        assert_matches!(collateral, Err(Error::Der(_)));
+    }
+}
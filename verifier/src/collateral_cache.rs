@@ -0,0 +1,382 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! In-memory caching of the collateral from a [`CollateralProvider`].
+//!
+//! The collateral changes rarely, but a verifier fetching it for every quote
+//! makes a round trip to the PCS for each one. The [`CollateralCache`] keeps
+//! each part of the collateral until it expires, at the `nextUpdate` of the
+//! part or the `notAfter` of its issuer chain, so only the first quote after
+//! an expiry pays for the fetch.
+
+use crate::{
+    CollateralProvider, Error, Fmspc, PckCaType, QeIdentity, SignedQeIdentity, SignedTcbInfo,
+    TcbInfo, TeeType,
+};
+use alloc::{string::String, vec::Vec};
+use core::{hash::Hash, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::SystemTime,
+};
+use x509_cert::{crl::CertificateList, Certificate};
+
+type IssuedCrl = (CertificateList, Vec<Certificate>);
+type IssuedJson = (String, Vec<Certificate>);
+
+#[derive(Debug)]
+struct Cached<T> {
+    value: T,
+    expiration: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    pck_crls: HashMap<PckCaType, Cached<IssuedCrl>>,
+    root_ca_crl: HashMap<(), Cached<CertificateList>>,
+    tcb_infos: HashMap<(Fmspc, TeeType), Cached<IssuedJson>>,
+    qe_identities: HashMap<TeeType, Cached<IssuedJson>>,
+}
+
+/// Caches the collateral of a [`CollateralProvider`] until it expires.
+///
+/// The cache is itself a [`CollateralProvider`], so it can be used anywhere
+/// the wrapped provider is.
+///
+/// ```no_run
+/// use mc_attestation_verifier::{
+///     CollateralCache, CollateralProvider, Fmspc, PckCaType, PcsClient, TeeType,
+/// };
+///
+/// let cache = CollateralCache::new(PcsClient::new());
+/// let fmspc = "00906ED50000".parse::<Fmspc>().unwrap();
+///
+/// // Fetched from the PCS
+/// let collateral = cache.collateral(PckCaType::Processor, &fmspc, TeeType::Sgx).unwrap();
+/// // Served from the cache
+/// let collateral = cache.collateral(PckCaType::Processor, &fmspc, TeeType::Sgx).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct CollateralCache<P> {
+    provider: P,
+    cache: Mutex<Cache>,
+    clock: fn() -> Duration,
+}
+
+impl<P: CollateralProvider> CollateralCache<P> {
+    /// Create a new, empty, cache of the collateral from `provider`
+    pub fn new(provider: P) -> Self {
+        Self::with_clock(provider, system_time)
+    }
+
+    fn with_clock(provider: P, clock: fn() -> Duration) -> Self {
+        Self {
+            provider,
+            cache: Mutex::default(),
+            clock,
+        }
+    }
+
+    /// The provider of the collateral
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Remove all of the cached collateral, the next requests go to the
+    /// provider.
+    ///
+    /// For when the collateral is known to have changed before its
+    /// `nextUpdate`, for instance after a TCB recovery.
+    pub fn clear(&self) {
+        *self.lock() = Cache::default();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cache> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The cached value of `key`, or the value from `fetch` if there is none
+    /// or it has expired.
+    fn get<K, V>(
+        &self,
+        entries: fn(&mut Cache) -> &mut HashMap<K, Cached<V>>,
+        key: K,
+        fetch: impl FnOnce() -> Result<V, P::Error>,
+        expiration: fn(&V) -> Result<Duration, Error>,
+    ) -> Result<V, P::Error>
+    where
+        K: Eq + Hash,
+        V: Clone,
+    {
+        let now = (self.clock)();
+        if let Some(cached) = entries(&mut self.lock()).get(&key) {
+            if now < cached.expiration {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        // The provider is called without holding the lock, so concurrent
+        // misses for the same key may each fetch, the last insert wins.
+        let value = fetch()?;
+        let expiration = expiration(&value)?;
+        entries(&mut self.lock()).insert(
+            key,
+            Cached {
+                value: value.clone(),
+                expiration,
+            },
+        );
+        Ok(value)
+    }
+}
+
+impl<P: CollateralProvider> CollateralProvider for CollateralCache<P> {
+    type Error = P::Error;
+
+    fn pck_crl(&self, ca: PckCaType) -> Result<IssuedCrl, Self::Error> {
+        self.get(
+            |cache| &mut cache.pck_crls,
+            ca,
+            || self.provider.pck_crl(ca),
+            |(crl, issuer_chain)| Ok(crl_expiration(crl).min(chain_expiration(issuer_chain))),
+        )
+    }
+
+    fn root_ca_crl(&self) -> Result<CertificateList, Self::Error> {
+        self.get(
+            |cache| &mut cache.root_ca_crl,
+            (),
+            || self.provider.root_ca_crl(),
+            |crl| Ok(crl_expiration(crl)),
+        )
+    }
+
+    fn tcb_info(&self, fmspc: &Fmspc, tee_type: TeeType) -> Result<IssuedJson, Self::Error> {
+        self.get(
+            |cache| &mut cache.tcb_infos,
+            (*fmspc, tee_type),
+            || self.provider.tcb_info(fmspc, tee_type),
+            |(tcb_info, issuer_chain)| {
+                let tcb_info = TcbInfo::try_from(&SignedTcbInfo::try_from(tcb_info.as_str())?)?;
+                Ok(tcb_info
+                    .next_update()?
+                    .unix_duration()
+                    .min(chain_expiration(issuer_chain)))
+            },
+        )
+    }
+
+    fn qe_identity(&self, tee_type: TeeType) -> Result<IssuedJson, Self::Error> {
+        self.get(
+            |cache| &mut cache.qe_identities,
+            tee_type,
+            || self.provider.qe_identity(tee_type),
+            |(qe_identity, issuer_chain)| {
+                let qe_identity =
+                    QeIdentity::try_from(&SignedQeIdentity::try_from(qe_identity.as_str())?)?;
+                Ok(qe_identity
+                    .next_update()?
+                    .unix_duration()
+                    .min(chain_expiration(issuer_chain)))
+            },
+        )
+    }
+}
+
+/// The time since the Unix epoch
+fn system_time() -> Duration {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// The `nextUpdate` of the `crl`, a CRL without one is always expired so it
+/// isn't served from the cache
+fn crl_expiration(crl: &CertificateList) -> Duration {
+    crl.tbs_cert_list
+        .next_update
+        .map_or(Duration::ZERO, |next_update| {
+            next_update.to_date_time().unix_duration()
+        })
+}
+
+/// The earliest `notAfter` of the certificates in the `chain`
+fn chain_expiration(chain: &[Certificate]) -> Duration {
+    chain
+        .iter()
+        .map(|certificate| {
+            certificate
+                .tbs_certificate
+                .validity
+                .not_after
+                .to_date_time()
+                .unix_duration()
+        })
+        .min()
+        .unwrap_or(Duration::MAX)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use der::{DateTime, Decode, DecodePem};
+
+    const FMSPC: Fmspc = Fmspc::new([0x00, 0x90, 0x6E, 0xD5, 0x00, 0x00]);
+
+    /// Provides the test collateral, counting the calls
+    #[derive(Debug, Default)]
+    struct CountingProvider {
+        calls: AtomicUsize,
+    }
+
+    impl CountingProvider {
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+
+        fn count(&self) {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn tcb_signing_chain() -> Vec<Certificate> {
+        Vec::from([
+            Certificate::from_pem(include_str!("../data/tests/tcb_signer.pem"))
+                .expect("Failed to parse certificate"),
+            Certificate::from_pem(include_str!("../data/tests/root_ca.pem"))
+                .expect("Failed to parse certificate"),
+        ])
+    }
+
+    impl CollateralProvider for CountingProvider {
+        type Error = Error;
+
+        fn pck_crl(&self, _ca: PckCaType) -> Result<IssuedCrl, Error> {
+            self.count();
+            let crl = CertificateList::from_der(include_bytes!("../data/tests/processor_crl.der"))?;
+            let issuer_chain = Vec::from([
+                Certificate::from_pem(include_str!("../data/tests/processor_ca.pem"))?,
+                Certificate::from_pem(include_str!("../data/tests/root_ca.pem"))?,
+            ]);
+            Ok((crl, issuer_chain))
+        }
+
+        fn root_ca_crl(&self) -> Result<CertificateList, Error> {
+            self.count();
+            Ok(CertificateList::from_der(include_bytes!(
+                "../data/tests/root_crl.der"
+            ))?)
+        }
+
+        fn tcb_info(&self, _fmspc: &Fmspc, _tee_type: TeeType) -> Result<IssuedJson, Error> {
+            self.count();
+            Ok((
+                include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json").to_string(),
+                tcb_signing_chain(),
+            ))
+        }
+
+        fn qe_identity(&self, _tee_type: TeeType) -> Result<IssuedJson, Error> {
+            self.count();
+            Ok((
+                include_str!("../data/tests/qe_identity.json").to_string(),
+                tcb_signing_chain(),
+            ))
+        }
+    }
+
+    fn at(time: &str) -> Duration {
+        time.parse::<DateTime>()
+            .expect("Failed to parse time")
+            .unix_duration()
+    }
+
+    /// Before any of the test collateral expires
+    fn before_expiration() -> Duration {
+        at("2023-05-01T00:00:00Z")
+    }
+
+    /// After the processor CRL expires, on 2023-05-21, but before the TCB
+    /// info and QE identity expire
+    fn after_pck_crl_expiration() -> Duration {
+        at("2023-07-20T00:00:00Z")
+    }
+
+    #[test]
+    fn collateral_is_served_from_the_cache() {
+        let cache = CollateralCache::with_clock(CountingProvider::default(), before_expiration);
+
+        let first = cache
+            .collateral(PckCaType::Processor, &FMSPC, TeeType::Sgx)
+            .expect("Failed to get collateral");
+        let second = cache
+            .collateral(PckCaType::Processor, &FMSPC, TeeType::Sgx)
+            .expect("Failed to get collateral");
+
+        assert_eq!(cache.provider().calls(), 4);
+        assert_eq!(first.tcb_info().fmspc(), second.tcb_info().fmspc());
+    }
+
+    #[test]
+    fn entries_are_keyed() {
+        let cache = CollateralCache::with_clock(CountingProvider::default(), before_expiration);
+
+        for _ in 0..2 {
+            cache
+                .tcb_info(&FMSPC, TeeType::Sgx)
+                .expect("Failed to get TCB info");
+            cache
+                .tcb_info(&FMSPC, TeeType::Tdx)
+                .expect("Failed to get TCB info");
+            cache
+                .tcb_info(&Fmspc::new([0; 6]), TeeType::Sgx)
+                .expect("Failed to get TCB info");
+            cache
+                .pck_crl(PckCaType::Platform)
+                .expect("Failed to get PCK CRL");
+        }
+
+        assert_eq!(cache.provider().calls(), 4);
+    }
+
+    #[test]
+    fn expired_parts_are_fetched_again() {
+        let cache =
+            CollateralCache::with_clock(CountingProvider::default(), after_pck_crl_expiration);
+
+        for _ in 0..2 {
+            cache
+                .pck_crl(PckCaType::Processor)
+                .expect("Failed to get PCK CRL");
+            cache
+                .qe_identity(TeeType::Sgx)
+                .expect("Failed to get QE identity");
+        }
+
+        // The PCK CRL is fetched both times, the QE identity once
+        assert_eq!(cache.provider().calls(), 3);
+    }
+
+    #[test]
+    fn clear_empties_the_cache() {
+        let cache = CollateralCache::with_clock(CountingProvider::default(), before_expiration);
+
+        cache.root_ca_crl().expect("Failed to get root CA CRL");
+        cache.clear();
+        cache.root_ca_crl().expect("Failed to get root CA CRL");
+
+        assert_eq!(cache.provider().calls(), 2);
+    }
+
+    #[test]
+    fn signing_chain_expires_with_its_first_certificate() {
+        // The `notAfter` of the TCB signer, the root CA is valid until 2049
+        assert_eq!(
+            chain_expiration(&tcb_signing_chain()),
+            at("2025-05-21T10:50:10Z")
+        );
+        assert_eq!(chain_expiration(&[]), Duration::MAX);
+    }
+}
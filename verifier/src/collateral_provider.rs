@@ -0,0 +1,316 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Sources of the collateral for verifying quotes.
+//!
+//! A [`CollateralProvider`] supplies each part of the [`Collateral`], where
+//! it comes from is up to the implementation. The
+//! [`PcsClient`](crate::PcsClient) fetches it from the Intel PCS, or a PCCS,
+//! while an application may keep it on the filesystem, in a database, or in
+//! some proprietary store. The parts are assembled into the same
+//! [`Collateral`] whatever the source, so the verification of quotes doesn't
+//! change.
+//!
+//! With the `tokio` feature, [`AsyncCollateralProvider`] is the equivalent for
+//! sources which are fetched asynchronously.
+
+use crate::{Collateral, Error, Fmspc, PckCaType, TeeType};
+#[cfg(feature = "tokio")]
+use alloc::boxed::Box;
+use alloc::{string::String, vec::Vec};
+use x509_cert::{crl::CertificateList, Certificate};
+
+/// A source of the collateral for verifying quotes.
+///
+/// The issuer chains are leaf first, root last, as provided by the PCS. The
+/// parts are not trusted, their signatures are verified when verifying a
+/// quote.
+pub trait CollateralProvider {
+    /// The error when a part of the collateral can not be provided
+    type Error: From<Error>;
+
+    /// The CRL of the PCK `ca` and its issuer chain
+    fn pck_crl(&self, ca: PckCaType) -> Result<(CertificateList, Vec<Certificate>), Self::Error>;
+
+    /// The CRL of the Intel SGX root CA
+    fn root_ca_crl(&self) -> Result<CertificateList, Self::Error>;
+
+    /// The signed TCB info JSON of the platforms with `fmspc` and its
+    /// signing chain
+    ///
+    /// # Arguments
+    /// * `fmspc` - The FMSPC of the platform, from its PCK certificate.
+    /// * `tee_type` - Whether to provide the SGX, or the TDX, TCB info.
+    fn tcb_info(
+        &self,
+        fmspc: &Fmspc,
+        tee_type: TeeType,
+    ) -> Result<(String, Vec<Certificate>), Self::Error>;
+
+    /// The signed QE identity JSON and its signing chain
+    ///
+    /// # Arguments
+    /// * `tee_type` - Whether to provide the identity of the SGX QE, or the
+    ///   TD QE.
+    fn qe_identity(&self, tee_type: TeeType) -> Result<(String, Vec<Certificate>), Self::Error>;
+
+    /// All of the collateral for verifying the quotes of the platforms with
+    /// `fmspc`.
+    ///
+    /// # Arguments
+    /// * `ca` - The CA which issued the PCK certificate of the platform.
+    /// * `fmspc` - The FMSPC of the platform, from its PCK certificate.
+    /// * `tee_type` - Whether to provide the SGX, or the TDX, TCB info and QE
+    ///   identity.
+    ///
+    /// # Errors
+    /// The errors of the individual parts, or of [`Collateral::new()`] when
+    /// the parts do not fit together.
+    fn collateral(
+        &self,
+        ca: PckCaType,
+        fmspc: &Fmspc,
+        tee_type: TeeType,
+    ) -> Result<Collateral, Self::Error> {
+        let (pck_crl, pck_crl_issuer_chain) = self.pck_crl(ca)?;
+        let root_ca_crl = self.root_ca_crl()?;
+        let (tcb_info, tcb_info_issuer_chain) = self.tcb_info(fmspc, tee_type)?;
+        let (qe_identity, qe_identity_issuer_chain) = self.qe_identity(tee_type)?;
+        Ok(Collateral::new(
+            pck_crl_issuer_chain,
+            root_ca_crl,
+            pck_crl,
+            tcb_info_issuer_chain,
+            &tcb_info,
+            qe_identity_issuer_chain,
+            &qe_identity,
+        )?)
+    }
+}
+
+/// An asynchronous source of the collateral for verifying quotes.
+///
+/// See [`CollateralProvider`], this is the same with `async` methods.
+#[cfg(feature = "tokio")]
+#[async_trait::async_trait]
+pub trait AsyncCollateralProvider: Sync {
+    /// The error when a part of the collateral can not be provided
+    type Error: From<Error>;
+
+    /// The CRL of the PCK `ca` and its issuer chain
+    async fn pck_crl(
+        &self,
+        ca: PckCaType,
+    ) -> Result<(CertificateList, Vec<Certificate>), Self::Error>;
+
+    /// The CRL of the Intel SGX root CA
+    async fn root_ca_crl(&self) -> Result<CertificateList, Self::Error>;
+
+    /// The signed TCB info JSON of the platforms with `fmspc` and its
+    /// signing chain
+    async fn tcb_info(
+        &self,
+        fmspc: &Fmspc,
+        tee_type: TeeType,
+    ) -> Result<(String, Vec<Certificate>), Self::Error>;
+
+    /// The signed QE identity JSON and its signing chain
+    async fn qe_identity(
+        &self,
+        tee_type: TeeType,
+    ) -> Result<(String, Vec<Certificate>), Self::Error>;
+
+    /// All of the collateral for verifying the quotes of the platforms with
+    /// `fmspc`.
+    ///
+    /// See [`CollateralProvider::collateral()`].
+    async fn collateral(
+        &self,
+        ca: PckCaType,
+        fmspc: &Fmspc,
+        tee_type: TeeType,
+    ) -> Result<Collateral, Self::Error> {
+        let (pck_crl, pck_crl_issuer_chain) = self.pck_crl(ca).await?;
+        let root_ca_crl = self.root_ca_crl().await?;
+        let (tcb_info, tcb_info_issuer_chain) = self.tcb_info(fmspc, tee_type).await?;
+        let (qe_identity, qe_identity_issuer_chain) = self.qe_identity(tee_type).await?;
+        Ok(Collateral::new(
+            pck_crl_issuer_chain,
+            root_ca_crl,
+            pck_crl,
+            tcb_info_issuer_chain,
+            &tcb_info,
+            qe_identity_issuer_chain,
+            &qe_identity,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use assert_matches::assert_matches;
+    use der::{Decode, DecodePem};
+
+    /// Provides the collateral from the test data, as an application would
+    /// from its own store
+    struct TestProvider;
+
+    fn tcb_signing_chain() -> Vec<Certificate> {
+        Certificate::load_pem_chain(
+            include_bytes!("../data/tests/tcb_signer.pem")
+                .iter()
+                .chain(include_bytes!("../data/tests/root_ca.pem"))
+                .copied()
+                .collect::<Vec<_>>()
+                .as_slice(),
+        )
+        .expect("Failed to load chain")
+    }
+
+    impl CollateralProvider for TestProvider {
+        type Error = Error;
+
+        fn pck_crl(&self, ca: PckCaType) -> Result<(CertificateList, Vec<Certificate>), Error> {
+            assert_eq!(ca, PckCaType::Processor);
+            let crl = CertificateList::from_der(include_bytes!("../data/tests/processor_crl.der"))?;
+            let issuer_chain = Vec::from([
+                Certificate::from_pem(include_str!("../data/tests/processor_ca.pem"))?,
+                Certificate::from_pem(include_str!("../data/tests/root_ca.pem"))?,
+            ]);
+            Ok((crl, issuer_chain))
+        }
+
+        fn root_ca_crl(&self) -> Result<CertificateList, Error> {
+            Ok(CertificateList::from_der(include_bytes!(
+                "../data/tests/root_crl.der"
+            ))?)
+        }
+
+        fn tcb_info(
+            &self,
+            fmspc: &Fmspc,
+            tee_type: TeeType,
+        ) -> Result<(String, Vec<Certificate>), Error> {
+            assert_eq!(fmspc.to_string(), "00906ED50000");
+            assert_eq!(tee_type, TeeType::Sgx);
+            Ok((
+                include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json").to_string(),
+                tcb_signing_chain(),
+            ))
+        }
+
+        fn qe_identity(&self, tee_type: TeeType) -> Result<(String, Vec<Certificate>), Error> {
+            assert_eq!(tee_type, TeeType::Sgx);
+            Ok((
+                include_str!("../data/tests/qe_identity.json").to_string(),
+                tcb_signing_chain(),
+            ))
+        }
+    }
+
+    #[test]
+    fn collateral_from_provider() {
+        let fmspc = Fmspc::new([0x00, 0x90, 0x6E, 0xD5, 0x00, 0x00]);
+
+        let collateral = CollateralProvider::collateral(
+            &TestProvider,
+            PckCaType::Processor,
+            &fmspc,
+            TeeType::Sgx,
+        )
+        .expect("Failed to assemble collateral");
+
+        assert_eq!(collateral.tcb_info().fmspc(), *fmspc.as_bytes());
+        assert_eq!(collateral.pck_crl_issuer_chain().len(), 2);
+    }
+
+    /// Provides the collateral of the wrong TCB info
+    struct MismatchedProvider;
+
+    impl CollateralProvider for MismatchedProvider {
+        type Error = Error;
+
+        fn pck_crl(&self, ca: PckCaType) -> Result<(CertificateList, Vec<Certificate>), Error> {
+            CollateralProvider::pck_crl(&TestProvider, ca)
+        }
+
+        fn root_ca_crl(&self) -> Result<CertificateList, Error> {
+            CollateralProvider::root_ca_crl(&TestProvider)
+        }
+
+        fn tcb_info(
+            &self,
+            fmspc: &Fmspc,
+            tee_type: TeeType,
+        ) -> Result<(String, Vec<Certificate>), Error> {
+            let (tcb_info, _) = CollateralProvider::tcb_info(&TestProvider, fmspc, tee_type)?;
+            let issuer_chain = Vec::from([Certificate::from_pem(include_str!(
+                "../data/tests/leaf_cert.pem"
+            ))?]);
+            Ok((tcb_info, issuer_chain))
+        }
+
+        fn qe_identity(&self, tee_type: TeeType) -> Result<(String, Vec<Certificate>), Error> {
+            CollateralProvider::qe_identity(&TestProvider, tee_type)
+        }
+    }
+
+    #[test]
+    fn inconsistent_collateral_from_provider_fails() {
+        let fmspc = Fmspc::new([0x00, 0x90, 0x6E, 0xD5, 0x00, 0x00]);
+
+        let result = MismatchedProvider.collateral(PckCaType::Processor, &fmspc, TeeType::Sgx);
+
+        assert_matches!(result, Err(Error::InconsistentCollateral(_)));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[async_trait::async_trait]
+    impl AsyncCollateralProvider for TestProvider {
+        type Error = Error;
+
+        async fn pck_crl(
+            &self,
+            ca: PckCaType,
+        ) -> Result<(CertificateList, Vec<Certificate>), Error> {
+            CollateralProvider::pck_crl(self, ca)
+        }
+
+        async fn root_ca_crl(&self) -> Result<CertificateList, Error> {
+            CollateralProvider::root_ca_crl(self)
+        }
+
+        async fn tcb_info(
+            &self,
+            fmspc: &Fmspc,
+            tee_type: TeeType,
+        ) -> Result<(String, Vec<Certificate>), Error> {
+            CollateralProvider::tcb_info(self, fmspc, tee_type)
+        }
+
+        async fn qe_identity(
+            &self,
+            tee_type: TeeType,
+        ) -> Result<(String, Vec<Certificate>), Error> {
+            CollateralProvider::qe_identity(self, tee_type)
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn collateral_from_async_provider() {
+        let fmspc = Fmspc::new([0x00, 0x90, 0x6E, 0xD5, 0x00, 0x00]);
+
+        let collateral = AsyncCollateralProvider::collateral(
+            &TestProvider,
+            PckCaType::Processor,
+            &fmspc,
+            TeeType::Sgx,
+        )
+        .await
+        .expect("Failed to assemble collateral");
+
+        assert_eq!(collateral.tcb_info().fmspc(), *fmspc.as_bytes());
+    }
+}
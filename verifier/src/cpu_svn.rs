@@ -0,0 +1,210 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! The CPU SVN broken down into its SGX TCB components
+//!
+//! The [`CpuSvn`] of a report body is 16 bytes. For TCB type 0, the only type
+//! Intel has defined, each byte is the SVN of one SGX TCB component,
+//! `sgxtcbcomp01svn` through `sgxtcbcomp16svn`. A platform is at a TCB level
+//! when every component is greater than or equal to the level's component,
+//! so two CPU SVNs may be incomparable.
+
+use crate::Tcb;
+use core::{
+    cmp::Ordering,
+    fmt::{Display, Formatter},
+};
+use mc_sgx_core_types::CpuSvn;
+use mc_sgx_dcap_types::COMPONENT_SVN_COUNT;
+
+/// The SGX TCB component SVNs of a [`CpuSvn`]
+///
+/// Ordered component-wise, one CPU SVN is only greater than or equal to
+/// another if every one of its components is.
+///
+/// ```
+/// use mc_attestation_verifier::{CpuSvn, CpuSvnComponents};
+///
+/// let lower = CpuSvnComponents::from(&CpuSvn::from([1; 16]));
+/// let mut svns = [1; 16];
+/// svns[0] = 2;
+/// let higher = CpuSvnComponents::from(svns);
+/// assert!(higher >= lower);
+///
+/// svns[1] = 0;
+/// let mixed = CpuSvnComponents::from(svns);
+/// assert_eq!(mixed.component(1), Some(0));
+/// assert_eq!(mixed.partial_cmp(&lower), None);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CpuSvnComponents([u8; COMPONENT_SVN_COUNT]);
+
+impl CpuSvnComponents {
+    /// The SVN of the component at `index`
+    ///
+    /// The index is 0 based, `sgxtcbcomp01svn` is at index 0. Returns `None`
+    /// if `index` is past the last component.
+    pub fn component(&self, index: usize) -> Option<u8> {
+        self.0.get(index).copied()
+    }
+
+    /// The SVNs of the components, `sgxtcbcomp01svn` through
+    /// `sgxtcbcomp16svn`
+    pub fn components(&self) -> &[u8; COMPONENT_SVN_COUNT] {
+        &self.0
+    }
+
+    /// The SVNs of the components widened to match the SVNs of a [`Tcb`]
+    pub fn svns(&self) -> [u32; COMPONENT_SVN_COUNT] {
+        let mut svns = [0; COMPONENT_SVN_COUNT];
+        for (svn, component) in svns.iter_mut().zip(self.0) {
+            *svn = u32::from(component);
+        }
+        svns
+    }
+
+    /// Whether every component is greater than or equal to the corresponding
+    /// SGX TCB component of `tcb`
+    ///
+    /// This is step 3.a of the TCB level matching in
+    /// <https://api.portal.trustedservices.intel.com/documentation#pcs-tcb-info-v4>,
+    /// the PCE SVN of `tcb` is not considered.
+    pub fn is_at_least(&self, tcb: &Tcb) -> bool {
+        self.svns()
+            .iter()
+            .zip(tcb.sgx_tcb_component_svns())
+            .all(|(actual, expected)| *actual >= expected)
+    }
+}
+
+impl From<[u8; COMPONENT_SVN_COUNT]> for CpuSvnComponents {
+    fn from(components: [u8; COMPONENT_SVN_COUNT]) -> Self {
+        Self(components)
+    }
+}
+
+impl From<&CpuSvn> for CpuSvnComponents {
+    fn from(cpu_svn: &CpuSvn) -> Self {
+        let mut components = [0; COMPONENT_SVN_COUNT];
+        components.copy_from_slice(cpu_svn.as_ref());
+        Self(components)
+    }
+}
+
+impl From<CpuSvn> for CpuSvnComponents {
+    fn from(cpu_svn: CpuSvn) -> Self {
+        Self::from(&cpu_svn)
+    }
+}
+
+impl From<CpuSvnComponents> for CpuSvn {
+    fn from(components: CpuSvnComponents) -> Self {
+        CpuSvn::from(components.0)
+    }
+}
+
+impl PartialOrd for CpuSvnComponents {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let mut ordering = Ordering::Equal;
+        for (ours, theirs) in self.0.iter().zip(&other.0) {
+            match (ordering, ours.cmp(theirs)) {
+                (_, Ordering::Equal) => {}
+                (Ordering::Equal, component) => ordering = component,
+                (overall, component) if overall != component => return None,
+                _ => {}
+            }
+        }
+        Some(ordering)
+    }
+}
+
+impl Display for CpuSvnComponents {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut separator = "";
+        for component in self.0 {
+            write!(f, "{separator}{component}")?;
+            separator = ".";
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::format;
+    use yare::parameterized;
+
+    fn components(first: u8, second: u8) -> CpuSvnComponents {
+        let mut components = [3; COMPONENT_SVN_COUNT];
+        components[0] = first;
+        components[1] = second;
+        components.into()
+    }
+
+    #[test]
+    fn from_cpu_svn() {
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+        let components = CpuSvnComponents::from(&CpuSvn::from(bytes));
+
+        assert_eq!(components.components(), &bytes);
+        assert_eq!(components.component(0), Some(1));
+        assert_eq!(components.component(15), Some(16));
+        assert_eq!(components.component(16), None);
+        assert_eq!(CpuSvn::from(components), CpuSvn::from(bytes));
+    }
+
+    #[test]
+    fn svns_are_widened() {
+        let components = components(255, 0);
+        let svns = components.svns();
+
+        assert_eq!(svns[0], 255);
+        assert_eq!(svns[1], 0);
+        assert_eq!(svns[2], 3);
+    }
+
+    #[parameterized(
+        equal = { components(2, 2), components(2, 2), Some(Ordering::Equal) },
+        one_greater = { components(3, 2), components(2, 2), Some(Ordering::Greater) },
+        all_greater = { components(3, 3), components(2, 2), Some(Ordering::Greater) },
+        one_less = { components(2, 1), components(2, 2), Some(Ordering::Less) },
+        greater_and_less = { components(3, 1), components(2, 2), None },
+        less_and_greater = { components(1, 3), components(2, 2), None },
+    )]
+    fn component_wise_ordering(
+        left: CpuSvnComponents,
+        right: CpuSvnComponents,
+        expected: Option<Ordering>,
+    ) {
+        assert_eq!(left.partial_cmp(&right), expected);
+    }
+
+    fn tcb(first: u32) -> Tcb {
+        let json = format!(
+            r#"{{"sgxtcbcomponents": [
+                {{"svn": {first}}}, {{"svn": 3}}, {{"svn": 3}}, {{"svn": 3}},
+                {{"svn": 3}}, {{"svn": 3}}, {{"svn": 3}}, {{"svn": 3}},
+                {{"svn": 3}}, {{"svn": 3}}, {{"svn": 3}}, {{"svn": 3}},
+                {{"svn": 3}}, {{"svn": 3}}, {{"svn": 3}}, {{"svn": 3}}
+            ], "pcesvn": 200}}"#
+        );
+        Tcb::try_from(json.as_str()).expect("Failed to parse TCB")
+    }
+
+    #[parameterized(
+        equal = { 4, true },
+        below = { 3, true },
+        above = { 5, false },
+    )]
+    fn is_at_least_tcb(first: u32, expected: bool) {
+        assert_eq!(components(4, 3).is_at_least(&tcb(first)), expected);
+    }
+
+    #[test]
+    fn display_is_dotted_components() {
+        assert_eq!(
+            format!("{}", components(16, 2)),
+            "16.2.3.3.3.3.3.3.3.3.3.3.3.3.3.3"
+        );
+    }
+}
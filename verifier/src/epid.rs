@@ -0,0 +1,389 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Legacy EPID quotes, as verified by the Intel Attestation Service (IAS).
+//!
+//! The layout is the `sgx_quote_t` of
+//! <https://download.01.org/intel-sgx/latest/linux-latest/docs/Intel_SGX_Developer_Reference_Linux_2.20_Open_Source.pdf>.
+//! The EPID signature can only be verified by IAS, so the quote is only
+//! trusted once it is in a verified attestation verification report. The
+//! report has the quote without its signature, see [`EpidQuote::BODY_SIZE`].
+
+use crate::{
+    quote4::{Reader, QE_REPORT_SIZE},
+    QuoteError, QuoteField,
+};
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::{Display, Formatter};
+use mc_sgx_core_types::ReportBody;
+use serde::{Deserialize, Serialize};
+
+/// The path of the IAS endpoint which verifies a quote, relative to the IAS
+/// base URL
+pub const IAS_REPORT_PATH: &str = "/attestation/v4/report";
+
+/// Size of the fields preceding the report body of an [`EpidQuote`]
+const HEADER_SIZE: usize = 48;
+
+/// The type of EPID signature in an [`EpidQuote`]
+#[derive(displaydoc::Display, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EpidSignatureType {
+    /// Unlinkable
+    Unlinkable,
+    /// Linkable
+    Linkable,
+}
+
+impl EpidSignatureType {
+    /// The raw value of the signature type in a quote
+    pub fn value(&self) -> u16 {
+        match self {
+            EpidSignatureType::Unlinkable => 0,
+            EpidSignatureType::Linkable => 1,
+        }
+    }
+}
+
+impl TryFrom<u16> for EpidSignatureType {
+    type Error = QuoteError;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        [EpidSignatureType::Unlinkable, EpidSignatureType::Linkable]
+            .into_iter()
+            .find(|signature_type| signature_type.value() == value)
+            .ok_or(QuoteError::EpidSignatureType(value))
+    }
+}
+
+/// The EPID group of the platform which produced a quote
+///
+/// Displayed as the big endian hex used by the IAS API, the quote has it
+/// little endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EpidGroupId([u8; 4]);
+
+impl EpidGroupId {
+    /// The raw, little endian, bytes
+    pub fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+
+    /// The path of the IAS endpoint for the signature revocation list of the
+    /// group, relative to the IAS base URL
+    ///
+    /// The SigRL must be given to the quoting enclave when creating the
+    /// quote.
+    pub fn sigrl_path(&self) -> String {
+        format!("/attestation/v4/sigrl/{self}")
+    }
+}
+
+impl From<[u8; 4]> for EpidGroupId {
+    fn from(bytes: [u8; 4]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl Display for EpidGroupId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for byte in self.0.iter().rev() {
+            write!(f, "{byte:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A legacy EPID quote, an `sgx_quote_t`
+///
+/// Either the full quote from the quoting enclave, with the EPID signature,
+/// or the quote body from an IAS report, without it.
+///
+/// ```
+/// use mc_attestation_verifier::{EpidQuote, QuoteError, QuoteField};
+///
+/// let error = EpidQuote::try_from([2u8, 0].as_slice()).unwrap_err();
+/// assert_eq!(
+///     error,
+///     QuoteError::Truncated { field: QuoteField::Header, offset: 0, size: 48, available: 2 }
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpidQuote<T> {
+    bytes: T,
+    version: u16,
+    signature_type: EpidSignatureType,
+    group_id: EpidGroupId,
+    qe_svn: u16,
+    pce_svn: u16,
+    extended_epid_group_id: u32,
+    basename: [u8; 32],
+    report_body: ReportBody,
+    signature: Option<(usize, usize)>,
+}
+
+impl<T: AsRef<[u8]>> EpidQuote<T> {
+    /// The EPID quote versions
+    pub const SUPPORTED_VERSIONS: [u16; 2] = [1, 2];
+
+    /// Size of the quote without the signature length and signature
+    pub const BODY_SIZE: usize = HEADER_SIZE + QE_REPORT_SIZE;
+
+    fn parse(bytes: T) -> Result<Self, QuoteError> {
+        let mut reader = Reader::new(bytes.as_ref());
+        let mut header = reader.section(QuoteField::Header, HEADER_SIZE)?;
+        let field = QuoteField::Header;
+        let version = header.u16(field)?;
+        if !Self::SUPPORTED_VERSIONS.contains(&version) {
+            return Err(QuoteError::Version(version));
+        }
+        let signature_type = EpidSignatureType::try_from(header.u16(field)?)?;
+        let group_id = EpidGroupId(header.array(field)?);
+        let qe_svn = header.u16(field)?;
+        let pce_svn = header.u16(field)?;
+        let extended_epid_group_id = header.u32(field)?;
+        let basename = header.array(field)?;
+        let report_body =
+            ReportBody::try_from(reader.take(QuoteField::ReportBody, QE_REPORT_SIZE)?)
+                .expect("`take()` returns a report body's worth of bytes");
+
+        let signature = if reader.offset() == bytes.as_ref().len() {
+            None
+        } else {
+            let size = reader.u32(QuoteField::EpidSignatureSize)? as usize;
+            let range = reader.range(QuoteField::EpidSignature, size)?;
+            Some((range.start, range.end))
+        };
+
+        Ok(Self {
+            bytes,
+            version,
+            signature_type,
+            group_id,
+            qe_svn,
+            pce_svn,
+            extended_epid_group_id,
+            basename,
+            report_body,
+            signature,
+        })
+    }
+
+    /// The quote version
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// The type of EPID signature
+    pub fn signature_type(&self) -> EpidSignatureType {
+        self.signature_type
+    }
+
+    /// The EPID group of the platform
+    pub fn group_id(&self) -> EpidGroupId {
+        self.group_id
+    }
+
+    /// The security version of the QE
+    pub fn qe_svn(&self) -> u16 {
+        self.qe_svn
+    }
+
+    /// The security version of the PCE
+    pub fn pce_svn(&self) -> u16 {
+        self.pce_svn
+    }
+
+    /// The extended EPID group ID, `0` for IAS
+    pub fn extended_epid_group_id(&self) -> u32 {
+        self.extended_epid_group_id
+    }
+
+    /// The basename of a linkable quote
+    pub fn basename(&self) -> &[u8; 32] {
+        &self.basename
+    }
+
+    /// The report body of the enclave which was quoted
+    pub fn report_body(&self) -> &ReportBody {
+        &self.report_body
+    }
+
+    /// The quote without the signature length and signature, as it is in an
+    /// IAS report
+    pub fn body(&self) -> &[u8] {
+        &self.bytes.as_ref()[..Self::BODY_SIZE]
+    }
+
+    /// The EPID signature, `None` for the quote body from an IAS report
+    ///
+    /// The signature may be encrypted for IAS, it can not be verified
+    /// locally.
+    pub fn signature(&self) -> Option<&[u8]> {
+        self.signature
+            .map(|(start, end)| &self.bytes.as_ref()[start..end])
+    }
+
+    /// The quote as it was parsed
+    ///
+    /// Any bytes the input had after the signature are not included.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let end = self.signature.map_or(Self::BODY_SIZE, |(_, end)| end);
+        self.bytes.as_ref()[..end].to_vec()
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for EpidQuote<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes.as_ref()
+    }
+}
+
+impl<T> crate::Accessor<ReportBody> for EpidQuote<T> {
+    fn get(&self) -> ReportBody {
+        self.report_body.clone()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for EpidQuote<&'a [u8]> {
+    type Error = QuoteError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::parse(bytes)
+    }
+}
+
+impl TryFrom<Vec<u8>> for EpidQuote<Vec<u8>> {
+    type Error = QuoteError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::parse(bytes)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use alloc::{string::ToString, vec};
+    use yare::parameterized;
+
+    /// An EPID quote body, each field is filled with a distinct value to
+    /// catch offset errors
+    pub(crate) fn epid_quote_body() -> Vec<u8> {
+        let mut quote = vec![];
+        quote.extend(2u16.to_le_bytes());
+        quote.extend(1u16.to_le_bytes());
+        quote.extend([0x12, 0x34, 0x56, 0x78]);
+        quote.extend(3u16.to_le_bytes());
+        quote.extend(4u16.to_le_bytes());
+        quote.extend(5u32.to_le_bytes());
+        quote.extend([6u8; 32]);
+        quote.extend([7u8; QE_REPORT_SIZE]);
+        quote
+    }
+
+    fn epid_quote() -> Vec<u8> {
+        let mut quote = epid_quote_body();
+        quote.extend(3u32.to_le_bytes());
+        quote.extend([8u8; 3]);
+        quote
+    }
+
+    #[test]
+    fn parse_epid_quote() {
+        let bytes = epid_quote();
+        let quote = EpidQuote::try_from(bytes.as_slice()).expect("Failed to parse quote");
+
+        assert_eq!(quote.version(), 2);
+        assert_eq!(quote.signature_type(), EpidSignatureType::Linkable);
+        assert_eq!(quote.group_id().as_bytes(), &[0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(quote.qe_svn(), 3);
+        assert_eq!(quote.pce_svn(), 4);
+        assert_eq!(quote.extended_epid_group_id(), 5);
+        assert_eq!(quote.basename(), &[6; 32]);
+        assert_eq!(quote.body(), &bytes[..EpidQuote::<&[u8]>::BODY_SIZE]);
+        assert_eq!(quote.signature(), Some([8u8; 3].as_slice()));
+        assert_eq!(quote.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn parse_epid_quote_body() {
+        let bytes = epid_quote_body();
+        let quote = EpidQuote::try_from(bytes.clone()).expect("Failed to parse quote");
+
+        assert_eq!(quote.signature(), None);
+        assert_eq!(quote.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn epid_quote_trailing_bytes_are_dropped() {
+        let mut bytes = epid_quote();
+        let expected = bytes.clone();
+        bytes.extend([9u8; 4]);
+        let quote = EpidQuote::try_from(bytes.as_slice()).expect("Failed to parse quote");
+
+        assert_eq!(quote.to_bytes(), expected);
+    }
+
+    #[parameterized(
+        version_0 = { 0 },
+        version_3 = { 3 },
+    )]
+    fn unsupported_version(version: u16) {
+        let mut bytes = epid_quote();
+        bytes[..2].copy_from_slice(&version.to_le_bytes());
+
+        assert_eq!(
+            EpidQuote::try_from(bytes.as_slice()),
+            Err(QuoteError::Version(version))
+        );
+    }
+
+    #[test]
+    fn unknown_signature_type() {
+        let mut bytes = epid_quote();
+        bytes[2..4].copy_from_slice(&2u16.to_le_bytes());
+
+        assert_eq!(
+            EpidQuote::try_from(bytes.as_slice()),
+            Err(QuoteError::EpidSignatureType(2))
+        );
+    }
+
+    #[test]
+    fn signature_past_end() {
+        let mut bytes = epid_quote();
+        bytes.pop();
+
+        assert_eq!(
+            EpidQuote::try_from(bytes.as_slice()),
+            Err(QuoteError::Truncated {
+                field: QuoteField::EpidSignature,
+                offset: 436,
+                size: 3,
+                available: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn truncated_report_body() {
+        let bytes = epid_quote_body();
+
+        assert_eq!(
+            EpidQuote::try_from(&bytes[..bytes.len() - 1]),
+            Err(QuoteError::Truncated {
+                field: QuoteField::ReportBody,
+                offset: 48,
+                size: QE_REPORT_SIZE,
+                available: QE_REPORT_SIZE - 1,
+            })
+        );
+    }
+
+    #[test]
+    fn group_id_is_displayed_big_endian() {
+        let group_id = EpidGroupId::from([0x12, 0x34, 0x56, 0x78]);
+
+        assert_eq!(group_id.to_string(), "78563412");
+        assert_eq!(group_id.sigrl_path(), "/attestation/v4/sigrl/78563412");
+    }
+}
@@ -2,8 +2,12 @@
 
 //! Errors that can occur during verification
 
-use alloc::string::{String, ToString};
-use mc_sgx_dcap_types::TcbError;
+use crate::{AdvisoryStatus, Fmspc, QuoteError, TeeType};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use mc_sgx_dcap_types::{CollateralError, Quote3Error, TcbError};
 use serde::{Deserialize, Serialize};
 
 /// Error working with quote evidence
@@ -23,13 +27,16 @@ pub enum Error {
     TcbInfoNotYetValid,
     /// TCB info expired
     TcbInfoExpired,
-    /// TCB info version mismatch, expecting {expected} got {actual}
+    /// TCB info version mismatch, expecting one of {supported:?} got {actual}
     #[allow(missing_docs)]
-    TcbInfoVersion { expected: u32, actual: u32 },
-    /// Asking for TCB levels for a different FMSPC
-    FmspcMismatch,
+    TcbInfoVersion { supported: Vec<u32>, actual: u32 },
+    /// The FMSPC of the PCK certificate, {pck}, does not match the FMSPC of the TCB info, {tcb_info}
+    #[allow(missing_docs)]
+    FmspcMismatch { pck: Fmspc, tcb_info: Fmspc },
     /// The TCB level reported does not match an entry in the TCB info data.
     UnsupportedTcbLevel,
+    /// Unsupported TCB type {0}, only type 0 is defined
+    TcbType(u32),
     /// Failure to get the TCB info from a quote {0}
     Quote3TcbInfo(TcbError),
     /// Unsupported quote certification data, should be `PckCertificateChain`
@@ -41,8 +48,89 @@ pub enum Error {
     /// QE identity version mismatch, expecting {expected} got {actual}
     #[allow(missing_docs)]
     QeIdentityVersion { expected: u32, actual: u32 },
+    /// Error parsing the quote: {0}
+    Quote3(String),
+    /// TEE type mismatch, expecting {expected} got {actual}
+    #[allow(missing_docs)]
+    TeeTypeMismatch { expected: TeeType, actual: TeeType },
+    /// The policy engine failed: {0}
+    PolicyEngine(String),
+    /// Archive record version mismatch, expecting {expected} got {actual}
+    #[allow(missing_docs)]
+    ArchiveRecordVersion { expected: u32, actual: u32 },
+    /// The collateral does not match the archived collateral
+    ArchiveCollateralMismatch,
+    /// Invalid collateral: {0}
+    Collateral(String),
+    /// Invalid {kind}: {error}
+    #[allow(missing_docs)]
+    PlatformId { kind: String, error: String },
+    /// Unable to canonicalize the JSON: {0}
+    CanonicalJson(String),
+    /// The evidence attesting to the key failed verification
+    KeyAttestationEvidence,
+    /// The key claims are not bound to the evidence's report data
+    KeyClaimsNotBound,
+    /// The public key is not a supported P-256 key
+    UnsupportedPublicKey,
+    /// Invalid quote: {0}
+    Quote(QuoteError),
+    /// Invalid {kind}: {error}
+    #[allow(missing_docs)]
+    TdMeasurement { kind: String, error: String },
+    /// The signing certificate chain failed verification: {0}
+    SigningCertificateChain(String),
+    /// The collateral is inconsistent: {0}
+    InconsistentCollateral(String),
+    /// The quote failed verification:\n{0}
+    QuoteVerification(String),
+    /// The report data prefix is {0} bytes, longer than the 64 byte report data
+    ReportDataPrefixLength(usize),
+    /// Invalid hex for the {kind} of a report body: {error}
+    #[allow(missing_docs)]
+    ReportBodyHex { kind: String, error: String },
+    /// Unable to derive the supplemental data: {0}
+    SupplementalData(String),
+    /// No TDX module identity {0} in the TCB info
+    TdxModuleIdentityNotFound(String),
+    /// The {0} of the TDX module does not match the TCB info
+    TdxModuleMismatch(String),
+    /// The TDX module SVN {0} is below all of the TCB levels of its identity
+    UnsupportedTdxModuleTcbLevel(u8),
+    /// The TDX module SVN {0} is at a revoked TCB level
+    TdxModuleRevoked(u8),
+    /// The TCB status {0:?} is not accepted by the advisory policy
+    TcbStatusNotAccepted(AdvisoryStatus),
+    /// The advisories {0} are not approved by the advisory policy
+    UnapprovedAdvisories(String),
+    /// The TCB info evaluation data number, {actual}, is below the minimum of {minimum}
+    #[allow(missing_docs)]
+    TcbInfoEvaluationDataNumber { minimum: u32, actual: u32 },
+    /// The QE identity evaluation data number, {actual}, is below the minimum of {minimum}
+    #[allow(missing_docs)]
+    QeIdentityEvaluationDataNumber { minimum: u32, actual: u32 },
+    /// The local report is {0} bytes, expected 432 bytes
+    LocalReportSize(usize),
+    /// The report key for the local report is not available
+    ReportKeyUnavailable,
+    /// The MAC of the local report does not match its report body
+    LocalReportMac,
+    /// IAS error: {0}
+    Ias(String),
+    /// Invalid URL encoding
+    UrlEncoding,
+    /// Failed to resolve the PCK certificate chain: {0}
+    PckResolution(String),
+    /// The PCK certificate is not issued by a PCK CA, its issuer is {0}
+    PckCaType(String),
+    /// The quote has {expected} bytes of signature data, but {actual} bytes were provided
+    #[allow(missing_docs)]
+    SignatureDataLength { expected: u32, actual: usize },
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
 impl From<der::Error> for Error {
     fn from(e: der::Error) -> Self {
         Error::Der(e.to_string())
@@ -55,6 +143,18 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<Quote3Error> for Error {
+    fn from(e: Quote3Error) -> Self {
+        Error::Quote3(e.to_string())
+    }
+}
+
+impl From<CollateralError> for Error {
+    fn from(e: CollateralError) -> Self {
+        Error::Collateral(e.to_string())
+    }
+}
+
 impl From<TcbError> for Error {
     fn from(e: TcbError) -> Self {
         Error::Quote3TcbInfo(e)
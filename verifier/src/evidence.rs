@@ -4,22 +4,28 @@
 
 use crate::{
     choice_to_status_message, identity::TrustedIdentityValue, qe_report_body::QeReportBodyValue,
-    Accessor, Advisories, CertificateChainVerifier, CertificateChainVerifierError, Error,
-    QeIdentity, QeReportBody, QeReportBodyVerifier, Quote3Verifier, SignedQeIdentity,
-    SignedQeIdentityVerifier, SignedTcbInfo, SignedTcbInfoVerifier, TcbInfo,
-    TrustedIdentitiesVerifier, TrustedIdentity, VerificationMessage, VerificationOutput, Verifier,
+    Accessor, Advisories, CertificateChainVerifier, CertificateChainVerifierError, Collateral,
+    CollateralItem, EmbeddedPckChainOnly, Error, Fmspc, FmspcVerifier, PckCaType,
+    PckCertificationData, PckResolver, PlatformConfiguration, QeIdentity, QeReportBody,
+    QeReportBodyVerifier, Quote3Verifier, SignedQeIdentity, SignedQeIdentityVerifier,
+    SignedTcbInfo, SignedTcbInfoVerifier, TcbInfo, TeeType, TrustedIdentitiesVerifier,
+    TrustedIdentity, VerificationMessage, VerificationOptions, VerificationOutput, Verifier,
     MESSAGE_INDENT,
 };
-use alloc::vec::Vec;
-use core::fmt::Formatter;
-use der::{DateTime, DecodePem};
+use alloc::{format, vec::Vec};
+use core::{
+    fmt::{Display, Formatter},
+    mem,
+};
+use der::DateTime;
+use mc_sgx_core_sys_types::sgx_report_body_t;
 use mc_sgx_core_types::{
     Attributes, ConfigId, ConfigSvn, CpuSvn, ExtendedProductId, FamilyId, IsvProductId, IsvSvn,
-    MiscellaneousSelect, MrEnclave, MrSigner, ReportData,
+    MiscellaneousSelect, MrEnclave, MrSigner, ReportBody, ReportData,
 };
-use mc_sgx_dcap_types::{CertificationData, Collateral, Quote3, TcbInfo as QuoteTcbInfo};
+use mc_sgx_dcap_types::{Collateral as DcapCollateral, Quote3, TcbInfo as QuoteTcbInfo};
 use p256::ecdsa::VerifyingKey;
-use x509_cert::{crl::CertificateList, Certificate};
+use x509_cert::Certificate;
 
 /// The full set of evidence needed for verifying a quote
 ///
@@ -33,44 +39,136 @@ use x509_cert::{crl::CertificateList, Certificate};
 #[derive(Debug, Clone)]
 pub struct Evidence<Q> {
     quote: Quote3<Q>,
-    signed_tcb_info: SignedTcbInfo,
-    signed_qe_identity: SignedQeIdentity,
-    qe_identity: QeIdentity,
     advisories: Advisories,
+    platform_configuration: PlatformConfiguration,
+    pck_ca_type: PckCaType,
+    pck_fmspc: Fmspc,
+    tcb_info_fmspc: Fmspc,
+    pck_certificate_chain: Vec<Certificate>,
     collateral: Collateral,
 }
 
 impl<Q: AsRef<[u8]>> Evidence<Q> {
     /// Create a new instance
+    ///
+    /// The PCK certificate chain must be embedded in the `quote`, type 5
+    /// certification data. Use [`Evidence::new_with_pck_resolver()`] for
+    /// the other certification data types.
+    ///
+    /// # Errors
+    /// `Error::TeeTypeMismatch` if the `quote` is not from an SGX TEE.
+    /// `Error::FmspcMismatch` if the FMSPC of the PCK certificate does not
+    /// match the FMSPC of the TCB info.
+    /// `Error::UnsupportedQuoteCertificationData` if the PCK certificate
+    /// chain is not embedded in the `quote`.
+    /// `Error::PckCaType` if the PCK certificate is not issued by a PCK CA.
+    /// `Error::InconsistentCollateral` if the PCK CRL is not from the CA
+    /// which issued the PCK certificate, for instance the Processor CA CRL
+    /// for a multi-package platform.
     pub fn new(quote: Quote3<Q>, collateral: Collateral) -> Result<Self, Error> {
+        Self::new_with_pck_resolver(quote, collateral, &EmbeddedPckChainOnly)
+    }
+
+    /// Create a new instance, using `resolver` to get the PCK certificate
+    /// chain when it is not embedded in the `quote`.
+    ///
+    /// # Errors
+    /// The errors of [`Evidence::new()`], and those of the `resolver`.
+    pub fn new_with_pck_resolver(
+        quote: Quote3<Q>,
+        collateral: Collateral,
+        resolver: &impl PckResolver,
+    ) -> Result<Self, Error> {
+        TeeType::Sgx.verify_quote(quote.as_ref())?;
+
         // We perform any fallible conversions now to keep the verification focused on the values
         // and not the types/format.
-        let signed_qe_identity = SignedQeIdentity::try_from(collateral.qe_identity())?;
-        let qe_identity = QeIdentity::try_from(&signed_qe_identity)?;
-
-        let signed_tcb_info = SignedTcbInfo::try_from(collateral.tcb_info())?;
-        let quote_tcb_info = tcb_info_try_from_quote(&quote)?;
-        let tcb_info = TcbInfo::try_from(&signed_tcb_info)?;
-        let advisories = tcb_info.advisories(&quote_tcb_info)?;
+        let pck_certificate_chain = certificate_chain_try_from_quote(&quote, resolver)?;
+        let leaf_cert = pck_certificate_chain
+            .first()
+            .ok_or(Error::UnsupportedQuoteCertificationData)?;
+        let quote_tcb_info = QuoteTcbInfo::try_from(leaf_cert)?;
+        let platform_configuration = PlatformConfiguration::try_from(leaf_cert)?;
+        let pck_ca_type = PckCaType::try_from(leaf_cert)?;
+        if collateral.pck_crl().tbs_cert_list.issuer != leaf_cert.tbs_certificate.issuer {
+            return Err(Error::InconsistentCollateral(format!(
+                "the PCK CRL is not from the {pck_ca_type} CA which issued the PCK certificate"
+            )));
+        }
+        let tcb_info = collateral.tcb_info();
+        let mut advisories = tcb_info.advisories(&quote_tcb_info)?;
+        // A stale QE downgrades the platform. A QE below all of the levels
+        // fails the QE report body verification, so it's ignored here.
+        let qe_isv_svn = quote.signature_data().qe_report_body().isv_svn();
+        if let Ok(qe_advisories) = collateral.qe_identity().advisories(&qe_isv_svn) {
+            advisories = advisories.converge_with_qe(&qe_advisories);
+        }
         Ok(Self {
             quote,
-            signed_tcb_info,
-            signed_qe_identity,
-            qe_identity,
             advisories,
+            platform_configuration,
+            pck_ca_type,
+            pck_fmspc: Fmspc::new(*quote_tcb_info.fmspc()),
+            tcb_info_fmspc: tcb_info.fmspc(),
+            pck_certificate_chain,
             collateral,
         })
     }
 }
 
+impl<Q> Evidence<Q> {
+    /// The collateral the evidence was created with
+    pub(crate) fn collateral(&self) -> &Collateral {
+        &self.collateral
+    }
+
+    /// The PCK certificate chain of the quote, leaf first
+    pub(crate) fn pck_certificate_chain(&self) -> &[Certificate] {
+        &self.pck_certificate_chain
+    }
+
+    /// The time the evidence expires, and the item which expires first.
+    ///
+    /// This is the earliest of the `nextUpdate` of the TCB info, QE identity,
+    /// and CRLs, and the `notAfter` of the certificates in the issuer chains
+    /// and the PCK certificate chain. A verification result based on the
+    /// evidence should not be relied on after this time.
+    ///
+    /// # Errors
+    /// `Error::Der` if the `nextUpdate` of the TCB info or QE identity is not
+    /// a valid time.
+    pub fn expiration(&self) -> Result<(DateTime, CollateralItem), Error> {
+        self.collateral
+            .expiration_with_pck_certificate_chain(&self.pck_certificate_chain)
+    }
+}
+
+impl Evidence<Vec<u8>> {
+    /// Create a new instance from the raw `quote` bytes
+    ///
+    /// # Errors
+    /// `Error::TeeTypeMismatch` if the `quote` is not from an SGX TEE.
+    /// `Error::Quote3` if the `quote` can not be parsed.
+    pub fn try_from_quote_bytes(
+        quote: impl Into<Vec<u8>>,
+        collateral: Collateral,
+    ) -> Result<Self, Error> {
+        let quote = quote.into();
+        TeeType::Sgx.verify_quote(&quote)?;
+        Self::new(Quote3::try_from(quote)?, collateral)
+    }
+}
+
 impl From<Evidence<&[u8]>> for Evidence<Vec<u8>> {
     fn from(value: Evidence<&[u8]>) -> Self {
         Self {
             quote: value.quote.into(),
-            signed_tcb_info: value.signed_tcb_info,
-            signed_qe_identity: value.signed_qe_identity,
-            qe_identity: value.qe_identity,
             advisories: value.advisories,
+            platform_configuration: value.platform_configuration,
+            pck_ca_type: value.pck_ca_type,
+            pck_fmspc: value.pck_fmspc,
+            tcb_info_fmspc: value.tcb_info_fmspc,
+            pck_certificate_chain: value.pck_certificate_chain,
             collateral: value.collateral,
         }
     }
@@ -78,13 +176,13 @@ impl From<Evidence<&[u8]>> for Evidence<Vec<u8>> {
 
 impl<Q> Accessor<SignedQeIdentity> for Evidence<Q> {
     fn get(&self) -> SignedQeIdentity {
-        self.signed_qe_identity.clone()
+        self.collateral.signed_qe_identity().clone()
     }
 }
 
 impl<Q> Accessor<SignedTcbInfo> for Evidence<Q> {
     fn get(&self) -> SignedTcbInfo {
-        self.signed_tcb_info.clone()
+        self.collateral.signed_tcb_info().clone()
     }
 }
 
@@ -100,35 +198,202 @@ impl<Q> Accessor<Advisories> for Evidence<Q> {
     }
 }
 
+/// The FMSPC of the PCK certificate
+impl<Q> Accessor<Fmspc> for Evidence<Q> {
+    fn get(&self) -> Fmspc {
+        self.pck_fmspc
+    }
+}
+
+/// The CA which issued the PCK certificate
+impl<Q> Accessor<PckCaType> for Evidence<Q> {
+    fn get(&self) -> PckCaType {
+        self.pck_ca_type
+    }
+}
+
+impl<Q> Accessor<PlatformConfiguration> for Evidence<Q> {
+    fn get(&self) -> PlatformConfiguration {
+        self.platform_configuration
+    }
+}
+
+/// The application enclave report body of the quote
+impl<Q: AsRef<[u8]>> Accessor<ReportBody> for Evidence<Q> {
+    fn get(&self) -> ReportBody {
+        self.quote.app_report_body().clone()
+    }
+}
+
 impl<Q: AsRef<[u8]>> Accessor<QeReportBody> for Evidence<Q> {
     fn get(&self) -> QeReportBody {
         (&self.quote).into()
     }
 }
 
+/// Size of the quote header which precedes the application enclave report body.
+/// See Table 3 of
+/// <https://download.01.org/intel-sgx/latest/dcap-latest/linux/docs/Intel_SGX_ECDSA_QuoteLibReference_DCAP_API.pdf>
+const QUOTE_HEADER_SIZE: usize = 48;
+
+/// Evidence salvaged from a quote and collateral that may be damaged.
+///
+/// **Nothing in here has been verified.** This is only meant for diagnostics
+/// and logging of malformed submissions, for example to see which enclave
+/// produced a quote that can no longer be parsed. Use [`Evidence::new()`] and
+/// an [`EvidenceVerifier`] to get trustworthy results, they will continue to
+/// reject the damaged evidence.
+#[derive(Debug)]
+pub struct UnverifiedEvidence {
+    app_report_body: Option<ReportBody>,
+    qe_report_body: Option<ReportBody>,
+    tcb_info: Option<TcbInfo>,
+    qe_identity: Option<QeIdentity>,
+    advisories: Option<Advisories>,
+    errors: Vec<Error>,
+}
+
+impl UnverifiedEvidence {
+    /// Extract whatever can be parsed from the `quote` bytes and `collateral`.
+    ///
+    /// This never fails, the problems encountered are available from
+    /// [`UnverifiedEvidence::errors()`]. When the quote itself can not be
+    /// parsed, the application enclave report body is still read from its
+    /// fixed location in the quote bytes.
+    pub fn parse_lossy(quote: &[u8], collateral: Option<&DcapCollateral>) -> Self {
+        let mut errors = Vec::new();
+        let mut qe_report_body = None;
+        let mut quote_tcb_info = None;
+
+        let parsed = TeeType::Sgx
+            .verify_quote(quote)
+            .and_then(|_| Ok(Quote3::try_from(quote)?));
+        let app_report_body = match parsed {
+            Ok(quote) => {
+                qe_report_body = Some(quote.signature_data().qe_report_body().clone());
+                quote_tcb_info = pck_certificate_try_from_quote(&quote)
+                    .and_then(|leaf_cert| Ok(QuoteTcbInfo::try_from(&leaf_cert)?))
+                    .map_err(|e| errors.push(e))
+                    .ok();
+                Some(quote.app_report_body().clone())
+            }
+            Err(e @ Error::TeeTypeMismatch { .. }) => {
+                // The report body is at a different location in other quote types
+                errors.push(e);
+                None
+            }
+            Err(e) => {
+                errors.push(e);
+                quote
+                    .get(QUOTE_HEADER_SIZE..QUOTE_HEADER_SIZE + mem::size_of::<sgx_report_body_t>())
+                    .and_then(|bytes| ReportBody::try_from(bytes).ok())
+            }
+        };
+
+        let mut tcb_info = None;
+        let mut qe_identity = None;
+        if let Some(collateral) = collateral {
+            tcb_info = SignedTcbInfo::try_from(collateral.tcb_info())
+                .and_then(|signed| TcbInfo::try_from(&signed))
+                .map_err(|e| errors.push(e))
+                .ok();
+            qe_identity = SignedQeIdentity::try_from(collateral.qe_identity())
+                .and_then(|signed| QeIdentity::try_from(&signed))
+                .map_err(|e| errors.push(e))
+                .ok();
+        }
+
+        let advisories = match (&tcb_info, &quote_tcb_info) {
+            (Some(tcb_info), Some(quote_tcb_info)) => tcb_info
+                .advisories(quote_tcb_info)
+                .map_err(|e| errors.push(e))
+                .ok(),
+            _ => None,
+        };
+
+        Self {
+            app_report_body,
+            qe_report_body,
+            tcb_info,
+            qe_identity,
+            advisories,
+            errors,
+        }
+    }
+
+    /// The unverified application enclave report body, if it could be parsed.
+    pub fn app_report_body(&self) -> Option<&ReportBody> {
+        self.app_report_body.as_ref()
+    }
+
+    /// The unverified quoting enclave report body, if it could be parsed.
+    pub fn qe_report_body(&self) -> Option<&ReportBody> {
+        self.qe_report_body.as_ref()
+    }
+
+    /// The unverified TCB info from the collateral, if it could be parsed.
+    pub fn tcb_info(&self) -> Option<&TcbInfo> {
+        self.tcb_info.as_ref()
+    }
+
+    /// The unverified QE identity from the collateral, if it could be parsed.
+    pub fn qe_identity(&self) -> Option<&QeIdentity> {
+        self.qe_identity.as_ref()
+    }
+
+    /// The unverified advisories, if they could be derived.
+    pub fn advisories(&self) -> Option<&Advisories> {
+        self.advisories.as_ref()
+    }
+
+    /// The errors encountered while parsing.
+    ///
+    /// If this is empty the same inputs are likely to be accepted by
+    /// [`Evidence::new()`].
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+}
+
+impl Display for UnverifiedEvidence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "UNVERIFIED evidence, do not trust for attestation")?;
+        match &self.app_report_body {
+            Some(report_body) => writeln!(f, "Application enclave report body: {report_body}")?,
+            None => writeln!(f, "Application enclave report body: unavailable")?,
+        }
+        match &self.qe_report_body {
+            Some(report_body) => writeln!(f, "QE report body: {report_body}")?,
+            None => writeln!(f, "QE report body: unavailable")?,
+        }
+        write!(f, "Errors:")?;
+        if self.errors.is_empty() {
+            write!(f, " none")?;
+        }
+        for error in &self.errors {
+            writeln!(f)?;
+            write!(f, "{:MESSAGE_INDENT$}- {error}", "")?;
+        }
+        Ok(())
+    }
+}
+
 // Get the certificate chain from the quote's certification data. Table 9 in appendix A of
 // <https://download.01.org/intel-sgx/latest/dcap-latest/linux/docs/Intel_SGX_ECDSA_QuoteLibReference_DCAP_API.pdf#%5B%7B%22num%22%3A77%2C%22gen%22%3A0%7D%2C%7B%22name%22%3A%22XYZ%22%7D%2C120%2C282%2C0%5D>
 fn certificate_chain_try_from_quote<Q: AsRef<[u8]>>(
     quote: &Quote3<Q>,
+    resolver: &impl PckResolver,
 ) -> Result<Vec<Certificate>, Error> {
-    let signature_data = quote.signature_data();
-    let certification_data = signature_data.certification_data();
-    let CertificationData::PckCertificateChain(pem_chain) = certification_data else {
-        return Err(Error::UnsupportedQuoteCertificationData);
-    };
-    Ok(pem_chain
-        .into_iter()
-        .map(Certificate::from_pem)
-        .collect::<Result<Vec<_>, _>>()?)
+    PckCertificationData::try_from_quote3_bytes(quote.as_ref())?.pck_certificate_chain(resolver)
 }
 
-// TODO think this should go in tcb.rs of `mc-sgx-dcap-types`
-fn tcb_info_try_from_quote<Q: AsRef<[u8]>>(quote: &Quote3<Q>) -> Result<QuoteTcbInfo, Error> {
-    let chain = certificate_chain_try_from_quote(quote)?;
-    let leaf_cert = chain
-        .first()
-        .ok_or(Error::UnsupportedQuoteCertificationData)?;
-    Ok(QuoteTcbInfo::try_from(leaf_cert)?)
+// The PCK certificate is the leaf of the quote's certificate chain
+fn pck_certificate_try_from_quote<Q: AsRef<[u8]>>(quote: &Quote3<Q>) -> Result<Certificate, Error> {
+    let chain = certificate_chain_try_from_quote(quote, &EmbeddedPckChainOnly)?;
+    chain
+        .into_iter()
+        .next()
+        .ok_or(Error::UnsupportedQuoteCertificationData)
 }
 
 /// Macro to generate boilerplate for implementing [`Accessor`] for a field of
@@ -166,6 +431,7 @@ quote_application_report_body_field_accessor! {
 ///
 /// This will perform most of the verification to be done on [`Evidence`] this includes:
 /// - verifying the certificate chains
+/// - verifying the FMSPC of the PCK certificate matches the TCB info
 /// - verifying the QE identity
 /// - verifying the TCB info
 /// - verifying the signature of the Quote
@@ -175,6 +441,7 @@ pub struct EvidenceVerifier<C> {
     certificate_verifier: C,
     trusted_identities: Vec<TrustedIdentity>,
     time: Option<DateTime>,
+    options: VerificationOptions,
 }
 
 impl<C> EvidenceVerifier<C>
@@ -206,10 +473,89 @@ where
             certificate_verifier,
             trusted_identities: trusted_identities.into_iter().map(Into::into).collect(),
             time: time.into(),
+            options: VerificationOptions::default(),
+        }
+    }
+
+    /// Verify the times of the TCB info and QE identity with the tolerances
+    /// of `options`.
+    ///
+    /// The `certificate_verifier` is responsible for the times of the
+    /// certificates and CRLs, for example with
+    /// `MbedTlsCertificateChainVerifier::with_options()`.
+    pub fn with_options(mut self, options: VerificationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn verify_issuer_chain<Q>(
+        &self,
+        chain: IssuerChain,
+        evidence: &Evidence<Q>,
+    ) -> (
+        Option<VerifyingKey>,
+        VerificationOutput<Option<CertificateChainVerifierError>>,
+    ) {
+        IssuerChainVerifier::new(&self.certificate_verifier, chain, self.time)
+            .verify_chain(evidence)
+    }
+}
+
+/// A certificate chain of the [`Evidence`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum IssuerChain {
+    /// The signing chain of the TCB info, checked against the root CA CRL
+    TcbInfo,
+    /// The signing chain of the QE identity, checked against the root CA CRL
+    QeIdentity,
+    /// The PCK certificate chain of the quote, checked against the root CA
+    /// and PCK CRLs
+    Quote,
+}
+
+impl IssuerChain {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::TcbInfo => "TCB",
+            Self::QeIdentity => "QE identity",
+            Self::Quote => "Quote",
+        }
+    }
+}
+
+/// Verifier for one of the certificate chains of the [`Evidence`].
+///
+/// The [`EvidenceVerifier`] verifies all of the chains. This allows composing
+/// a policy from the individual checks instead.
+#[derive(Debug)]
+pub struct IssuerChainVerifier<C> {
+    certificate_verifier: C,
+    chain: IssuerChain,
+    time: Option<DateTime>,
+}
+
+impl<C: CertificateChainVerifier> IssuerChainVerifier<C> {
+    /// Create a new instance
+    ///
+    /// # Arguments
+    /// * `certificate_verifier` - The verifier to use for verifying the
+    ///   `chain`.
+    /// * `chain` - The certificate chain of the evidence to verify.
+    /// * `time` - The time to verify the `chain` at, see
+    ///   [`EvidenceVerifier::new()`].
+    pub fn new(
+        certificate_verifier: C,
+        chain: IssuerChain,
+        time: impl Into<Option<DateTime>>,
+    ) -> Self {
+        Self {
+            certificate_verifier,
+            chain,
+            time: time.into(),
         }
     }
 
-    // Assumes that `chain` is ordered such that the leaf is the first element and root is the last.
+    // The chains are ordered such that the leaf is the first element and root is the last.
     //
     // This order matches that documented at
     // <https://api.portal.trustedservices.intel.com/documentation#pcs-tcb-info-v4>
@@ -223,14 +569,27 @@ where
     //      5: Concatenated PCK Cert Chain (PEM formatted).
     //      PCK Leaf Cert||Intermediate CA Cert||Root CA Cert
     //
-    fn verify_certificate_chain<'c>(
+    fn verify_chain<Q>(
         &self,
-        chain: &[Certificate],
-        crls: impl IntoIterator<Item = &'c CertificateList>,
+        evidence: &Evidence<Q>,
     ) -> (
         Option<VerifyingKey>,
         VerificationOutput<Option<CertificateChainVerifierError>>,
     ) {
+        let collateral = &evidence.collateral;
+        let root_ca_crl = collateral.root_ca_crl();
+        let (chain, pck_crl) = match self.chain {
+            IssuerChain::TcbInfo => (collateral.tcb_info_issuer_chain(), None),
+            IssuerChain::QeIdentity => (collateral.qe_identity_issuer_chain(), None),
+            // The Quote's chain is not in the collateral. It was taken from the
+            // quote's certification data, or resolved from it, when the evidence
+            // was created.
+            IssuerChain::Quote => (
+                &evidence.pck_certificate_chain[..],
+                Some(collateral.pck_crl()),
+            ),
+        };
+        let crls = core::iter::once(root_ca_crl).chain(pck_crl);
         let result = self
             .certificate_verifier
             .verify_certificate_chain(chain, crls, self.time);
@@ -247,62 +606,30 @@ where
             VerificationOutput::new(result.err(), is_success.into()),
         )
     }
+}
 
-    fn verify_tcb_signing_chain(
-        &self,
-        collateral: &Collateral,
-    ) -> (
-        Option<VerifyingKey>,
-        VerificationOutput<Option<CertificateChainVerifierError>>,
-    ) {
-        let chain = collateral.tcb_issuer_chain();
-        let crls = [collateral.root_ca_crl()];
-        self.verify_certificate_chain(chain, crls)
-    }
+impl<C: CertificateChainVerifier, E: Accessor<Evidence<Vec<u8>>>> Verifier<E>
+    for IssuerChainVerifier<C>
+{
+    type Value = Option<CertificateChainVerifierError>;
 
-    fn verify_qe_identity_signing_chain(
-        &self,
-        collateral: &Collateral,
-    ) -> (
-        Option<VerifyingKey>,
-        VerificationOutput<Option<CertificateChainVerifierError>>,
-    ) {
-        let chain = collateral.qe_identity_issuer_chain();
-        let crls = [collateral.root_ca_crl()];
-        self.verify_certificate_chain(chain, crls)
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        self.verify_chain(&evidence.get()).1
     }
+}
 
-    fn verify_quote_signing_chain<Q: AsRef<[u8]>>(
+impl<C> VerificationMessage<Option<CertificateChainVerifierError>> for IssuerChainVerifier<C> {
+    fn fmt_padded(
         &self,
-        quote: &Quote3<Q>,
-        collateral: &Collateral,
-    ) -> (
-        Option<VerifyingKey>,
-        VerificationOutput<Option<CertificateChainVerifierError>>,
-    ) {
-        let crls = [collateral.root_ca_crl(), collateral.pck_crl()];
-        // The Quote's chain is not in the collateral. It is in the quote itself.
-        // As documented in table 9 of appendix A,
-        // <https://download.01.org/intel-sgx/latest/dcap-latest/linux/docs/Intel_SGX_ECDSA_QuoteLibReference_DCAP_API.pdf#%5B%7B%22num%22%3A77%2C%22gen%22%3A0%7D%2C%7B%22name%22%3A%22XYZ%22%7D%2C120%2C282%2C0%5D>
-        // the certificate chain is at the end of the quote bytes for quotes with type `5`
-        // certification data.
-        match certificate_chain_try_from_quote(quote) {
-            Ok(chain) => self.verify_certificate_chain(&chain, crls),
-            Err(_) => {
-                let is_success = 0u8;
-                (
-                    None,
-                    VerificationOutput::new(
-                        Some(CertificateChainVerifierError::GeneralCertificateError),
-                        is_success.into(),
-                    ),
-                )
-            }
-        }
+        f: &mut Formatter<'_>,
+        pad: usize,
+        output: &VerificationOutput<Option<CertificateChainVerifierError>>,
+    ) -> core::fmt::Result {
+        fmt_chain_verification_result_padded(f, pad, self.chain.name(), output)
     }
 }
 
-fn key_from_certificate(cert: &Certificate) -> Option<VerifyingKey> {
+pub(crate) fn key_from_certificate(cert: &Certificate) -> Option<VerifyingKey> {
     let key_bytes = cert
         .tbs_certificate
         .subject_public_key_info
@@ -318,21 +645,27 @@ impl<C: CertificateChainVerifier, E: Accessor<Evidence<Vec<u8>>>> Verifier<E>
 
     fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
         let evidence = evidence.get();
-        let collateral = &evidence.collateral;
-        let quote = &evidence.quote;
 
-        let (tcb_key, tcb_chain_verification) = self.verify_tcb_signing_chain(collateral);
-        let (qe_key, qe_chain_verification) = self.verify_qe_identity_signing_chain(collateral);
+        let (tcb_key, tcb_chain_verification) =
+            self.verify_issuer_chain(IssuerChain::TcbInfo, &evidence);
+        let (qe_key, qe_chain_verification) =
+            self.verify_issuer_chain(IssuerChain::QeIdentity, &evidence);
         let (quote_key, quote_chain_verification) =
-            self.verify_quote_signing_chain(quote, collateral);
+            self.verify_issuer_chain(IssuerChain::Quote, &evidence);
+
+        let fmspc_verifier = FmspcVerifier::new(evidence.tcb_info_fmspc);
+        let fmspc_verification = fmspc_verifier.verify(&evidence);
 
-        let tcb_info_verifier = SignedTcbInfoVerifier::new(tcb_key, self.time);
+        let tcb_info_verifier =
+            SignedTcbInfoVerifier::new(tcb_key, self.time).with_options(self.options);
         let tcb_info_verification = tcb_info_verifier.verify(&evidence);
 
-        let qe_identity_verifier = SignedQeIdentityVerifier::new(qe_key, self.time);
+        let qe_identity_verifier =
+            SignedQeIdentityVerifier::new(qe_key, self.time).with_options(self.options);
         let qe_identity_verification = qe_identity_verifier.verify(&evidence);
 
-        let qe_report_body_verifier = QeReportBodyVerifier::new(evidence.qe_identity.clone());
+        let qe_report_body_verifier =
+            QeReportBodyVerifier::new(evidence.collateral.qe_identity().clone());
         let qe_report_body_verification = qe_report_body_verifier.verify(&evidence);
 
         let quote_verifier = Quote3Verifier::new(quote_key);
@@ -345,6 +678,7 @@ impl<C: CertificateChainVerifier, E: Accessor<Evidence<Vec<u8>>>> Verifier<E>
             tcb_signing_key: tcb_chain_verification,
             qe_identity_signing_key: qe_chain_verification,
             quote_signing_key: quote_chain_verification,
+            fmspc: (fmspc_verifier, fmspc_verification),
             tcb_info: (tcb_info_verifier, tcb_info_verification),
             qe_identity: (qe_identity_verifier, qe_identity_verification),
             qe_report_body: (qe_report_body_verifier, qe_report_body_verification),
@@ -355,6 +689,7 @@ impl<C: CertificateChainVerifier, E: Accessor<Evidence<Vec<u8>>>> Verifier<E>
         let is_success = evidence_value.tcb_signing_key.is_success()
             & evidence_value.qe_identity_signing_key.is_success()
             & evidence_value.quote_signing_key.is_success()
+            & evidence_value.fmspc.1.is_success()
             & evidence_value.tcb_info.1.is_success()
             & evidence_value.qe_identity.1.is_success()
             & evidence_value.qe_report_body.1.is_success()
@@ -374,6 +709,7 @@ pub struct EvidenceValue {
     tcb_signing_key: VerificationOutput<Option<CertificateChainVerifierError>>,
     qe_identity_signing_key: VerificationOutput<Option<CertificateChainVerifierError>>,
     quote_signing_key: VerificationOutput<Option<CertificateChainVerifierError>>,
+    fmspc: (FmspcVerifier, VerificationOutput<Option<Error>>),
     tcb_info: (SignedTcbInfoVerifier, VerificationOutput<Option<Error>>),
     qe_identity: (SignedQeIdentityVerifier, VerificationOutput<Option<Error>>),
     qe_report_body: (QeReportBodyVerifier, VerificationOutput<QeReportBodyValue>),
@@ -431,6 +767,9 @@ where
         writeln!(f)?;
         fmt_chain_verification_result_padded(f, pad, "Quote", &output.value.quote_signing_key)?;
         writeln!(f)?;
+        let (fmspc_verifier, fmspc_verification) = &output.value.fmspc;
+        fmspc_verifier.fmt_padded(f, pad, fmspc_verification)?;
+        writeln!(f)?;
         let (tcb_info_verifier, tcb_info_verification) = &output.value.tcb_info;
         tcb_info_verifier.fmt_padded(f, pad, tcb_info_verification)?;
         writeln!(f)?;
@@ -450,65 +789,66 @@ where
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     use super::*;
+    use crate::{And, QuoteError, TrustedMrEnclaveIdentity, VerificationTreeDisplay};
     #[cfg(feature = "mbedtls")]
     use crate::{MbedTlsCertificateChainVerifier, TrustAnchor};
-    use crate::{TrustedMrEnclaveIdentity, VerificationTreeDisplay};
     use alloc::{
         format,
         string::{String, ToString},
     };
     use assert_matches::assert_matches;
     use core::mem;
-    use mc_sgx_dcap_sys_types::{sgx_ql_ecdsa_sig_data_t, sgx_ql_qve_collateral_t, sgx_quote3_t};
-
-    const TCB_INFO_JSON: &str = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
-    const QE_IDENTITY_JSON: &str = include_str!("../data/tests/qe_identity.json");
-
-    fn collateral(tcb_info: &str, qe_identity: &str) -> Collateral {
-        let mut sgx_collateral = sgx_ql_qve_collateral_t::default();
-
-        // SAFETY: Version is a union which is inherently unsafe
-        #[allow(unsafe_code)]
-        let version = unsafe { sgx_collateral.__bindgen_anon_1.__bindgen_anon_1.as_mut() };
-        version.major_version = 3;
-        version.minor_version = 1;
+    use mc_sgx_dcap_sys_types::{sgx_ql_ecdsa_sig_data_t, sgx_quote3_t};
+    use x509_cert::crl::CertificateList;
 
-        let pck_issuer_cert = include_str!("../data/tests/processor_ca.pem");
-        let root_cert = include_str!("../data/tests/root_ca.pem");
-        let mut pck_crl_chain = [pck_issuer_cert, root_cert].join("\n").as_bytes().to_vec();
-        pck_crl_chain.push(0);
-        sgx_collateral.pck_crl_issuer_chain = pck_crl_chain.as_ptr() as _;
-        sgx_collateral.pck_crl_issuer_chain_size = pck_crl_chain.len() as u32;
+    pub(crate) const TCB_INFO_JSON: &str =
+        include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+    pub(crate) const QE_IDENTITY_JSON: &str = include_str!("../data/tests/qe_identity.json");
 
-        let mut root_crl = include_bytes!("../data/tests/root_crl.der").to_vec();
-        root_crl.push(0);
-        sgx_collateral.root_ca_crl = root_crl.as_ptr() as _;
-        sgx_collateral.root_ca_crl_size = root_crl.len() as u32;
+    const ROOT_CA: &str = include_str!("../data/tests/root_ca.pem");
+    const ROOT_CRL: &[u8] = include_bytes!("../data/tests/root_crl.der");
+    const PROCESSOR_CRL: &[u8] = include_bytes!("../data/tests/processor_crl.der");
 
-        let mut pck_crl = include_bytes!("../data/tests/processor_crl.der").to_vec();
-        pck_crl.push(0);
-        sgx_collateral.pck_crl = pck_crl.as_ptr() as _;
-        sgx_collateral.pck_crl_size = pck_crl.len() as u32;
-
-        let tcb_cert = include_str!("../data/tests/tcb_signer.pem");
-        let mut tcb_chain = [tcb_cert, root_cert].join("\n").as_bytes().to_vec();
-        tcb_chain.push(0);
-        sgx_collateral.tcb_info_issuer_chain = tcb_chain.as_ptr() as _;
-        sgx_collateral.tcb_info_issuer_chain_size = tcb_chain.len() as u32;
+    fn pck_crl_chain() -> String {
+        [include_str!("../data/tests/processor_ca.pem"), ROOT_CA].join("\n")
+    }
 
-        sgx_collateral.tcb_info = tcb_info.as_ptr() as _;
-        sgx_collateral.tcb_info_size = tcb_info.len() as u32;
+    // For live data the QE identity uses the same chain as the TCB info
+    fn tcb_chain() -> String {
+        [include_str!("../data/tests/tcb_signer.pem"), ROOT_CA].join("\n")
+    }
 
-        // For live data the QE identity uses the same chain as the TCB info
-        sgx_collateral.qe_identity_issuer_chain = tcb_chain.as_ptr() as _;
-        sgx_collateral.qe_identity_issuer_chain_size = tcb_chain.len() as u32;
+    pub(crate) fn try_collateral(tcb_info: &str, qe_identity: &str) -> Result<Collateral, Error> {
+        Collateral::try_from_raw_parts(
+            &pck_crl_chain(),
+            ROOT_CRL,
+            PROCESSOR_CRL,
+            &tcb_chain(),
+            tcb_info,
+            &tcb_chain(),
+            qe_identity,
+        )
+    }
 
-        sgx_collateral.qe_identity = qe_identity.as_ptr() as _;
-        sgx_collateral.qe_identity_size = qe_identity.len() as u32;
+    pub(crate) fn collateral(tcb_info: &str, qe_identity: &str) -> Collateral {
+        try_collateral(tcb_info, qe_identity).expect("Failed to parse collateral")
+    }
 
-        Collateral::try_from(&sgx_collateral).expect("Failed to parse collateral")
+    /// The collateral as it comes from the DCAP libraries, which may be
+    /// damaged
+    pub(crate) fn dcap_collateral(tcb_info: &str, qe_identity: &str) -> DcapCollateral {
+        crate::ffi::dcap_collateral(
+            &pck_crl_chain(),
+            ROOT_CRL,
+            PROCESSOR_CRL,
+            &tcb_chain(),
+            tcb_info,
+            &tcb_chain(),
+            qe_identity,
+        )
+        .expect("Failed to parse collateral")
     }
 
     // Valid time for the TCB_INFO_JSON and QE_IDENTITY_JSON
@@ -533,6 +873,38 @@ mod test {
         identity.into()
     }
 
+    #[test]
+    fn evidence_fails_for_pck_crl_of_another_ca() {
+        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
+        let quote = Quote3::try_from(quote_bytes.as_slice()).expect("Failed to parse quote");
+        // The root CA, and its CRL, stand in for the other PCK CA
+        let collateral = Collateral::try_from_raw_parts(
+            ROOT_CA,
+            ROOT_CRL,
+            ROOT_CRL,
+            &tcb_chain(),
+            TCB_INFO_JSON,
+            &tcb_chain(),
+            QE_IDENTITY_JSON,
+        )
+        .expect("Failed to parse collateral");
+
+        assert_matches!(
+            Evidence::new(quote, collateral),
+            Err(Error::InconsistentCollateral(message)) if message.contains("processor CA")
+        );
+    }
+
+    #[test]
+    fn evidence_has_pck_ca_type() {
+        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
+        let quote = Quote3::try_from(quote_bytes.as_slice()).expect("Failed to parse quote");
+        let evidence = Evidence::new(quote, collateral(TCB_INFO_JSON, QE_IDENTITY_JSON))
+            .expect("Failed to create evidence");
+
+        assert_eq!(Accessor::<PckCaType>::get(&evidence), PckCaType::Processor);
+    }
+
     #[test]
     fn evidence_fails_due_to_wrong_quote_certification_data() {
         let mut quote_bytes = include_bytes!("../data/tests/hw_quote.dat").to_vec();
@@ -549,9 +921,8 @@ mod test {
         // "2" is for the u16 for reading in the auth data size
         let cert_data_type_offset = auth_data_offset + auth_data_size + 2;
 
-        // Not all types are supported so we set to 1
-        // (PPID in plain text, CPUSVN and PCESVN)
-        quote_bytes[cert_data_type_offset] = 1;
+        // Type 7, the platform manifest, doesn't identify the PCK
+        quote_bytes[cert_data_type_offset] = 7;
 
         let quote = Quote3::try_from(quote_bytes).expect("Failed to parse quote");
 
@@ -559,7 +930,7 @@ mod test {
 
         assert_matches!(
             Evidence::new(quote, collateral),
-            Err(Error::UnsupportedQuoteCertificationData)
+            Err(Error::Quote(QuoteError::CertificationDataType(7)))
         );
     }
 
@@ -571,49 +942,181 @@ mod test {
         let tcb_json = include_str!("../data/tests/example_tcb.json");
         let collateral = collateral(tcb_json, QE_IDENTITY_JSON);
 
-        assert_matches!(Evidence::new(quote, collateral), Err(Error::FmspcMismatch));
+        assert_matches!(
+            Evidence::new(quote, collateral),
+            Err(Error::FmspcMismatch { pck, tcb_info })
+                if pck.to_string() == "00906ED50000" && tcb_info.to_string() == "50806F000000"
+        );
     }
 
     #[test]
-    fn evidence_fails_due_inability_get_underlying_tcb_info() {
-        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
-        let quote = Quote3::try_from(quote_bytes.as_ref()).expect("Failed to parse quote");
-
+    fn collateral_fails_due_inability_get_underlying_tcb_info() {
         let tcb_json = TCB_INFO_JSON;
         let bad_tcb_json = tcb_json.replace("SWHardeningNeeded", "NotGonnaHappen");
-        let collateral = collateral(bad_tcb_json.as_str(), QE_IDENTITY_JSON);
 
-        assert_matches!(Evidence::new(quote, collateral), Err(Error::Serde(_)));
+        assert_matches!(
+            try_collateral(bad_tcb_json.as_str(), QE_IDENTITY_JSON),
+            Err(Error::Serde(_))
+        );
     }
 
     #[test]
-    fn evidence_fails_due_inability_get_underlying_signed_qe_identity() {
-        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
-        let quote = Quote3::try_from(quote_bytes.as_ref()).expect("Failed to parse quote");
-
+    fn collateral_fails_due_inability_get_underlying_signed_qe_identity() {
         let qe_json = QE_IDENTITY_JSON;
 
         // Modifies an outer JSON field, i.e. only the Signed QE Identity
         let bad_qe_json = qe_json.replace("enclaveIdentity", "NotGonnaHappen");
 
-        let collateral = collateral(TCB_INFO_JSON, bad_qe_json.as_str());
-
-        assert_matches!(Evidence::new(quote, collateral), Err(Error::Serde(_)));
+        assert_matches!(
+            try_collateral(TCB_INFO_JSON, bad_qe_json.as_str()),
+            Err(Error::Serde(_))
+        );
     }
 
     #[test]
-    fn evidence_fails_due_inability_get_underlying_qe_identity() {
-        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
-        let quote = Quote3::try_from(quote_bytes.as_ref()).expect("Failed to parse quote");
-
+    fn collateral_fails_due_inability_get_underlying_qe_identity() {
         let qe_json = QE_IDENTITY_JSON;
 
         // Modifies a JSON field inside of the `enclaveIdentity`
         let bad_qe_json = qe_json.replace("UpToDate", "NotGonnaHappen");
 
-        let collateral = collateral(TCB_INFO_JSON, bad_qe_json.as_str());
+        assert_matches!(
+            try_collateral(TCB_INFO_JSON, bad_qe_json.as_str()),
+            Err(Error::Serde(_))
+        );
+    }
+
+    #[test]
+    fn parse_lossy_intact_evidence() {
+        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
+        let collateral = dcap_collateral(TCB_INFO_JSON, QE_IDENTITY_JSON);
+
+        let evidence = UnverifiedEvidence::parse_lossy(quote_bytes, Some(&collateral));
 
-        assert_matches!(Evidence::new(quote, collateral), Err(Error::Serde(_)));
+        assert_eq!(evidence.errors(), &[]);
+        let report_body = evidence
+            .app_report_body()
+            .expect("Should have an application report body");
+        let TrustedIdentity::MrEnclave(identity) = valid_test_trusted_identity() else {
+            panic!("Expected an MRENCLAVE identity");
+        };
+        assert_eq!(report_body.mr_enclave(), identity.mr_enclave());
+        assert!(evidence.qe_report_body().is_some());
+        assert!(evidence.tcb_info().is_some());
+        assert!(evidence.qe_identity().is_some());
+        assert!(evidence.advisories().is_some());
+    }
+
+    #[test]
+    fn parse_lossy_damaged_quote_keeps_report_body() {
+        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
+        let intact = Quote3::try_from(quote_bytes.as_ref()).expect("Failed to parse quote");
+
+        // Only the header, report body, and part of the signature data
+        let damaged = &quote_bytes[..mem::size_of::<sgx_quote3_t>() + 10];
+        assert!(Quote3::try_from(damaged).is_err());
+
+        let collateral = dcap_collateral(TCB_INFO_JSON, QE_IDENTITY_JSON);
+        let evidence = UnverifiedEvidence::parse_lossy(damaged, Some(&collateral));
+
+        assert_matches!(evidence.errors(), [Error::Quote3(_)]);
+        assert_eq!(evidence.app_report_body(), Some(intact.app_report_body()));
+        assert_eq!(evidence.qe_report_body(), None);
+        assert!(evidence.tcb_info().is_some());
+        assert!(evidence.qe_identity().is_some());
+        // Needs the TCB info from the quote's certificate chain
+        assert!(evidence.advisories().is_none());
+    }
+
+    #[test]
+    fn parse_lossy_quote_missing_report_body() {
+        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
+        let damaged = &quote_bytes[..QUOTE_HEADER_SIZE + 10];
+
+        let evidence = UnverifiedEvidence::parse_lossy(damaged, None);
+
+        assert_matches!(evidence.errors(), [Error::Quote3(_)]);
+        assert_eq!(evidence.app_report_body(), None);
+        assert!(evidence.tcb_info().is_none());
+
+        let display = evidence.to_string();
+        assert!(display.starts_with(
+            "UNVERIFIED evidence, do not trust for attestation\n\
+            Application enclave report body: unavailable\n\
+            QE report body: unavailable\n\
+            Errors:\n  - Error parsing the quote: "
+        ));
+    }
+
+    #[test]
+    fn parse_lossy_damaged_collateral() {
+        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
+        let bad_tcb_json = TCB_INFO_JSON.replace("SWHardeningNeeded", "NotGonnaHappen");
+        let collateral = dcap_collateral(bad_tcb_json.as_str(), QE_IDENTITY_JSON);
+
+        let evidence = UnverifiedEvidence::parse_lossy(quote_bytes, Some(&collateral));
+
+        assert_matches!(evidence.errors(), [Error::Serde(_)]);
+        assert!(evidence.app_report_body().is_some());
+        assert!(evidence.tcb_info().is_none());
+        assert!(evidence.qe_identity().is_some());
+        assert!(evidence.advisories().is_none());
+    }
+
+    #[test]
+    fn parse_lossy_without_collateral() {
+        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
+
+        let evidence = UnverifiedEvidence::parse_lossy(quote_bytes, None);
+
+        assert_eq!(evidence.errors(), &[]);
+        assert!(evidence.app_report_body().is_some());
+        assert!(evidence.qe_report_body().is_some());
+        assert!(evidence.tcb_info().is_none());
+        assert!(evidence.qe_identity().is_none());
+        assert!(evidence.advisories().is_none());
+    }
+
+    fn tdx_quote_bytes() -> Vec<u8> {
+        let mut quote_bytes = include_bytes!("../data/tests/hw_quote.dat").to_vec();
+        // The TEE type is the u32 after the version and attestation key type
+        quote_bytes[4] = 0x81;
+        quote_bytes
+    }
+
+    #[test]
+    fn evidence_fails_due_to_tee_type_mismatch() {
+        let collateral = collateral(TCB_INFO_JSON, QE_IDENTITY_JSON);
+
+        assert_eq!(
+            Evidence::try_from_quote_bytes(tdx_quote_bytes(), collateral).unwrap_err(),
+            Error::TeeTypeMismatch {
+                expected: TeeType::Sgx,
+                actual: TeeType::Tdx
+            }
+        );
+    }
+
+    #[test]
+    fn evidence_from_quote_bytes() {
+        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
+        let collateral = collateral(TCB_INFO_JSON, QE_IDENTITY_JSON);
+
+        assert!(Evidence::try_from_quote_bytes(quote_bytes.as_ref(), collateral).is_ok());
+    }
+
+    #[test]
+    fn parse_lossy_tee_type_mismatch() {
+        let evidence = UnverifiedEvidence::parse_lossy(&tdx_quote_bytes(), None);
+
+        assert_eq!(
+            evidence.errors(),
+            &[Error::TeeTypeMismatch {
+                expected: TeeType::Sgx,
+                actual: TeeType::Tdx
+            }]
+        );
+        assert_eq!(evidence.app_report_body(), None);
     }
 
     struct TestDoubleChainVerifier {
@@ -749,6 +1252,7 @@ mod test {
               - [x] The TCB issuer chain was verified.
               - [x] The QE identity issuer chain was verified.
               - [x] The Quote issuer chain was verified.
+              - [x] The FMSPC of the PCK certificate matches the TCB info: 00906ED50000
               - [x] The TCB info was verified for the provided key
               - [x] The QE identity was verified for the provided key
               - [x] QE Report Body all of the following must be true:
@@ -756,7 +1260,7 @@ mod test {
                 - [x] The ISV product ID should be 1
                 - [x] The expected miscellaneous select is 0x0000_0000 with mask 0xFFFF_FFFF
                 - [x] The expected attributes is Flags: INITTED | PROVISION_KEY Xfrm: (none) with mask Flags: 0xFFFF_FFFF_FFFF_FFFB Xfrm: (none)
-                - [x] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
+                - [x] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
               - [x] The quote was signed with the provided key
               - [x] Both of the following must be true:
                 - [x] The MRENCLAVE should be 840d61b0585dc8b4dc90f53af293c760fda06bee75978a6a86263ffb296423f4
@@ -764,6 +1268,36 @@ mod test {
         assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
     }
 
+    #[test]
+    fn issuer_chain_verifier_composes_with_other_verifiers() {
+        let time = "2023-07-12T20:48:25Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+        let certificate_verifier = TestDoubleChainVerifier::fail_at_certificate(
+            "Intel SGX PCK Certificate",
+            CertificateChainVerifierError::CertificateExpired,
+        );
+        let verifier = And::new(
+            IssuerChainVerifier::new(&certificate_verifier, IssuerChain::TcbInfo, time),
+            IssuerChainVerifier::new(&certificate_verifier, IssuerChain::Quote, time),
+        );
+        let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
+        let quote = Quote3::try_from(quote_bytes.to_vec()).expect("Failed to parse quote");
+        let collateral = collateral(TCB_INFO_JSON, QE_IDENTITY_JSON);
+        let evidence = Evidence::new(quote, collateral).expect("Failed to create evidence");
+
+        let verification = verifier.verify(&evidence);
+
+        assert_eq!(verification.is_failure().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [ ] Both of the following must be true:
+              - [x] The TCB issuer chain was verified.
+              - [ ] The Quote issuer chain could not be verified: X509 certificate has expired"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
     #[test]
     fn evidence_verifier_fails_for_expired_quote_certificate() {
         let time = "2023-07-12T20:48:25Z"
@@ -790,6 +1324,7 @@ mod test {
               - [x] The TCB issuer chain was verified.
               - [x] The QE identity issuer chain was verified.
               - [ ] The Quote issuer chain could not be verified: X509 certificate has expired
+              - [x] The FMSPC of the PCK certificate matches the TCB info: 00906ED50000
               - [x] The TCB info was verified for the provided key
               - [x] The QE identity was verified for the provided key
               - [x] QE Report Body all of the following must be true:
@@ -797,7 +1332,7 @@ mod test {
                 - [x] The ISV product ID should be 1
                 - [x] The expected miscellaneous select is 0x0000_0000 with mask 0xFFFF_FFFF
                 - [x] The expected attributes is Flags: INITTED | PROVISION_KEY Xfrm: (none) with mask Flags: 0xFFFF_FFFF_FFFF_FFFB Xfrm: (none)
-                - [x] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
+                - [x] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
               - [x] The quote was signed with the provided key
               - [x] Both of the following must be true:
                 - [x] The MRENCLAVE should be 840d61b0585dc8b4dc90f53af293c760fda06bee75978a6a86263ffb296423f4
@@ -831,6 +1366,7 @@ mod test {
               - [ ] The TCB issuer chain could not be verified: X509 certificate has been revoked
               - [ ] The QE identity issuer chain could not be verified: X509 certificate has been revoked
               - [x] The Quote issuer chain was verified.
+              - [x] The FMSPC of the PCK certificate matches the TCB info: 00906ED50000
               - [x] The TCB info was verified for the provided key
               - [x] The QE identity was verified for the provided key
               - [x] QE Report Body all of the following must be true:
@@ -838,7 +1374,7 @@ mod test {
                 - [x] The ISV product ID should be 1
                 - [x] The expected miscellaneous select is 0x0000_0000 with mask 0xFFFF_FFFF
                 - [x] The expected attributes is Flags: INITTED | PROVISION_KEY Xfrm: (none) with mask Flags: 0xFFFF_FFFF_FFFF_FFFB Xfrm: (none)
-                - [x] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
+                - [x] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
               - [x] The quote was signed with the provided key
               - [x] Both of the following must be true:
                 - [x] The MRENCLAVE should be 840d61b0585dc8b4dc90f53af293c760fda06bee75978a6a86263ffb296423f4
@@ -869,6 +1405,7 @@ mod test {
               - [x] The TCB issuer chain was verified.
               - [x] The QE identity issuer chain was verified.
               - [x] The Quote issuer chain was verified.
+              - [x] The FMSPC of the PCK certificate matches the TCB info: 00906ED50000
               - [ ] The TCB info could not be verified: TCB info expired
               - [x] The QE identity was verified for the provided key
               - [x] QE Report Body all of the following must be true:
@@ -876,7 +1413,7 @@ mod test {
                 - [x] The ISV product ID should be 1
                 - [x] The expected miscellaneous select is 0x0000_0000 with mask 0xFFFF_FFFF
                 - [x] The expected attributes is Flags: INITTED | PROVISION_KEY Xfrm: (none) with mask Flags: 0xFFFF_FFFF_FFFF_FFFB Xfrm: (none)
-                - [x] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
+                - [x] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
               - [x] The quote was signed with the provided key
               - [x] Both of the following must be true:
                 - [x] The MRENCLAVE should be 840d61b0585dc8b4dc90f53af293c760fda06bee75978a6a86263ffb296423f4
@@ -907,6 +1444,7 @@ mod test {
               - [x] The TCB issuer chain was verified.
               - [x] The QE identity issuer chain was verified.
               - [x] The Quote issuer chain was verified.
+              - [x] The FMSPC of the PCK certificate matches the TCB info: 00906ED50000
               - [x] The TCB info was verified for the provided key
               - [ ] The QE identity signature could not be verified: QE identity not yet valid
               - [x] QE Report Body all of the following must be true:
@@ -914,7 +1452,7 @@ mod test {
                 - [x] The ISV product ID should be 1
                 - [x] The expected miscellaneous select is 0x0000_0000 with mask 0xFFFF_FFFF
                 - [x] The expected attributes is Flags: INITTED | PROVISION_KEY Xfrm: (none) with mask Flags: 0xFFFF_FFFF_FFFF_FFFB Xfrm: (none)
-                - [x] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
+                - [x] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
               - [x] The quote was signed with the provided key
               - [x] Both of the following must be true:
                 - [x] The MRENCLAVE should be 840d61b0585dc8b4dc90f53af293c760fda06bee75978a6a86263ffb296423f4
@@ -943,6 +1481,7 @@ mod test {
               - [x] The TCB issuer chain was verified.
               - [x] The QE identity issuer chain was verified.
               - [x] The Quote issuer chain was verified.
+              - [x] The FMSPC of the PCK certificate matches the TCB info: 00906ED50000
               - [x] The TCB info was verified for the provided key
               - [x] The QE identity was verified for the provided key
               - [x] QE Report Body all of the following must be true:
@@ -950,7 +1489,7 @@ mod test {
                 - [x] The ISV product ID should be 1
                 - [x] The expected miscellaneous select is 0x0000_0000 with mask 0xFFFF_FFFF
                 - [x] The expected attributes is Flags: INITTED | PROVISION_KEY Xfrm: (none) with mask Flags: 0xFFFF_FFFF_FFFF_FFFB Xfrm: (none)
-                - [x] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
+                - [x] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
               - [x] The quote was signed with the provided key
               - [ ] No enclave identity matched for:
                 - MRENCLAVE: 840d61b0585dc8b4dc90f53af293c760fda06bee75978a6a86263ffb296423f4
@@ -988,6 +1527,7 @@ mod test {
               - [x] The TCB issuer chain was verified.
               - [x] The QE identity issuer chain was verified.
               - [x] The Quote issuer chain was verified.
+              - [x] The FMSPC of the PCK certificate matches the TCB info: 00906ED50000
               - [x] The TCB info was verified for the provided key
               - [x] The QE identity was verified for the provided key
               - [x] QE Report Body all of the following must be true:
@@ -995,7 +1535,7 @@ mod test {
                 - [x] The ISV product ID should be 1
                 - [x] The expected miscellaneous select is 0x0000_0000 with mask 0xFFFF_FFFF
                 - [x] The expected attributes is Flags: INITTED | PROVISION_KEY Xfrm: (none) with mask Flags: 0xFFFF_FFFF_FFFF_FFFB Xfrm: (none)
-                - [x] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
+                - [x] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
               - [ ] The quote signature did not match provided key
               - [x] Both of the following must be true:
                 - [x] The MRENCLAVE should be 840d61b0585dc8b4dc90f53af293c760fda06bee75978a6a86263ffb296423f4
@@ -1029,6 +1569,7 @@ mod test {
               - [x] The TCB issuer chain was verified.
               - [x] The QE identity issuer chain was verified.
               - [x] The Quote issuer chain was verified.
+              - [x] The FMSPC of the PCK certificate matches the TCB info: 00906ED50000
               - [x] The TCB info was verified for the provided key
               - [x] The QE identity was verified for the provided key
               - [x] QE Report Body all of the following must be true:
@@ -1036,7 +1577,7 @@ mod test {
                 - [x] The ISV product ID should be 1
                 - [x] The expected miscellaneous select is 0x0000_0000 with mask 0xFFFF_FFFF
                 - [x] The expected attributes is Flags: INITTED | PROVISION_KEY Xfrm: (none) with mask Flags: 0xFFFF_FFFF_FFFF_FFFB Xfrm: (none)
-                - [x] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
+                - [x] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]
               - [x] The quote was signed with the provided key
               - [x] Both of the following must be true:
                 - [x] The MRENCLAVE should be 840d61b0585dc8b4dc90f53af293c760fda06bee75978a6a86263ffb296423f4
@@ -0,0 +1,298 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Conversions from the `repr(C)` types of the Intel SGX SDK and DCAP QL.
+//!
+//! Code which gets reports and quotes through FFI has them as the structs of
+//! `mc-sgx-core-sys-types` and `mc-sgx-dcap-sys-types`. These conversions
+//! copy the fields out one at a time, so no unsafe transmutes of the structs
+//! to bytes are needed. The `mc-sgx-core-types` report types already convert
+//! from their structs, for instance
+//! [`ReportBody`](mc_sgx_core_types::ReportBody) from an
+//! `sgx_report_body_t`.
+//!
+//! The DCAP [`Collateral`](DcapCollateral) can only be created from an
+//! `sgx_ql_qve_collateral_t`, so collateral from other sources is passed
+//! through one.
+
+use crate::{Error, LocalReport, QuoteHeader, REPORT_SIZE};
+use alloc::vec::Vec;
+use mc_sgx_core_sys_types::{sgx_report_body_t, sgx_report_t};
+use mc_sgx_dcap_sys_types::{sgx_ql_qve_collateral_t, sgx_quote3_t, sgx_quote_header_t};
+use mc_sgx_dcap_types::Collateral as DcapCollateral;
+
+/// Size of an `sgx_report_body_t`
+const REPORT_BODY_SIZE: usize = 384;
+
+/// The bytes of a report body, in the layout the hardware produces
+fn report_body_bytes(body: &sgx_report_body_t) -> [u8; REPORT_BODY_SIZE] {
+    let mut bytes = [0u8; REPORT_BODY_SIZE];
+    bytes[..16].copy_from_slice(&body.cpu_svn.svn);
+    bytes[16..20].copy_from_slice(&body.misc_select.to_le_bytes());
+    bytes[20..32].copy_from_slice(&body.reserved1);
+    bytes[32..48].copy_from_slice(&body.isv_ext_prod_id);
+    bytes[48..56].copy_from_slice(&body.attributes.flags.to_le_bytes());
+    bytes[56..64].copy_from_slice(&body.attributes.xfrm.to_le_bytes());
+    bytes[64..96].copy_from_slice(&body.mr_enclave.m);
+    bytes[96..128].copy_from_slice(&body.reserved2);
+    bytes[128..160].copy_from_slice(&body.mr_signer.m);
+    bytes[160..192].copy_from_slice(&body.reserved3);
+    bytes[192..256].copy_from_slice(&body.config_id);
+    bytes[256..258].copy_from_slice(&body.isv_prod_id.to_le_bytes());
+    bytes[258..260].copy_from_slice(&body.isv_svn.to_le_bytes());
+    bytes[260..262].copy_from_slice(&body.config_svn.to_le_bytes());
+    bytes[262..304].copy_from_slice(&body.reserved4);
+    bytes[304..320].copy_from_slice(&body.isv_family_id);
+    bytes[320..].copy_from_slice(&body.report_data.d);
+    bytes
+}
+
+/// The bytes of a quote header, in the layout of the quote
+fn quote_header_bytes(header: &sgx_quote_header_t) -> [u8; QuoteHeader::SIZE] {
+    let mut bytes = [0u8; QuoteHeader::SIZE];
+    bytes[..2].copy_from_slice(&header.version.to_le_bytes());
+    bytes[2..4].copy_from_slice(&header.att_key_type.to_le_bytes());
+    bytes[4..8].copy_from_slice(&header.att_key_data_0.to_le_bytes());
+    bytes[8..10].copy_from_slice(&header.qe_svn.to_le_bytes());
+    bytes[10..12].copy_from_slice(&header.pce_svn.to_le_bytes());
+    bytes[12..28].copy_from_slice(&header.vendor_id);
+    bytes[28..].copy_from_slice(&header.user_data);
+    bytes
+}
+
+impl From<&sgx_quote_header_t> for QuoteHeader {
+    fn from(header: &sgx_quote_header_t) -> Self {
+        Self::try_from(quote_header_bytes(header).as_slice())
+            .expect("Quote header should be the correct size")
+    }
+}
+
+impl From<&QuoteHeader> for sgx_quote_header_t {
+    fn from(header: &QuoteHeader) -> Self {
+        sgx_quote_header_t {
+            version: header.version(),
+            att_key_type: header.attestation_key_type(),
+            att_key_data_0: header.tee_type_value(),
+            qe_svn: header.qe_svn(),
+            pce_svn: header.pce_svn(),
+            vendor_id: *header.qe_vendor_id(),
+            user_data: *header.user_data(),
+        }
+    }
+}
+
+impl From<&sgx_report_t> for LocalReport {
+    fn from(report: &sgx_report_t) -> Self {
+        let mut bytes = [0u8; REPORT_SIZE];
+        bytes[..REPORT_BODY_SIZE].copy_from_slice(&report_body_bytes(&report.body));
+        bytes[REPORT_BODY_SIZE..REPORT_SIZE - report.mac.len()].copy_from_slice(&report.key_id.id);
+        bytes[REPORT_SIZE - report.mac.len()..].copy_from_slice(&report.mac);
+        Self::try_from(bytes.as_slice()).expect("Report should be the correct size")
+    }
+}
+
+impl From<&LocalReport> for sgx_report_t {
+    fn from(report: &LocalReport) -> Self {
+        mc_sgx_core_types::Report::from(report).into()
+    }
+}
+
+/// The bytes of a version 3 quote from the DCAP QL.
+///
+/// The QL fills a buffer with an `sgx_quote3_t` followed by its
+/// `signature_data_len` bytes of signature data. The struct can't reach the
+/// signature data without unsafe code, so it's provided separately. The
+/// returned bytes can be parsed as a [`Quote3`](mc_sgx_dcap_types::Quote3).
+///
+/// # Errors
+/// `Error::SignatureDataLength` if `signature_data` isn't the
+/// `signature_data_len` of the `quote`.
+pub fn sgx_quote3_bytes(quote: &sgx_quote3_t, signature_data: &[u8]) -> Result<Vec<u8>, Error> {
+    let expected = quote.signature_data_len;
+    if usize::try_from(expected).ok() != Some(signature_data.len()) {
+        return Err(Error::SignatureDataLength {
+            expected,
+            actual: signature_data.len(),
+        });
+    }
+    let mut bytes =
+        Vec::with_capacity(QuoteHeader::SIZE + REPORT_BODY_SIZE + 4 + signature_data.len());
+    bytes.extend_from_slice(&quote_header_bytes(&quote.header));
+    bytes.extend_from_slice(&report_body_bytes(&quote.report_body));
+    bytes.extend_from_slice(&expected.to_le_bytes());
+    bytes.extend_from_slice(signature_data);
+    Ok(bytes)
+}
+
+/// The DCAP collateral from the raw parts, as they are provided by the PCS.
+///
+/// The issuer chains are concatenated PEM certificates, leaf first. The CRLs
+/// are DER encoded. The parts are NUL terminated, as the DCAP QL provides
+/// them, before being passed through an `sgx_ql_qve_collateral_t`.
+///
+/// # Errors
+/// `Error::Collateral` if a part can not be decoded.
+pub(crate) fn dcap_collateral(
+    pck_crl_issuer_chain: &str,
+    root_ca_crl: &[u8],
+    pck_crl: &[u8],
+    tcb_info_issuer_chain: &str,
+    tcb_info: &str,
+    qe_identity_issuer_chain: &str,
+    qe_identity: &str,
+) -> Result<DcapCollateral, Error> {
+    let pck_crl_issuer_chain = nul_terminated(pck_crl_issuer_chain.as_bytes());
+    let root_ca_crl = nul_terminated(root_ca_crl);
+    let pck_crl = nul_terminated(pck_crl);
+    let tcb_info_issuer_chain = nul_terminated(tcb_info_issuer_chain.as_bytes());
+    let tcb_info = nul_terminated(tcb_info.as_bytes());
+    let qe_identity_issuer_chain = nul_terminated(qe_identity_issuer_chain.as_bytes());
+    let qe_identity = nul_terminated(qe_identity.as_bytes());
+
+    let mut collateral = sgx_ql_qve_collateral_t::default();
+
+    // SAFETY: The version is a union of the legacy 32 bit version and the
+    // major and minor versions, both are plain integers so writing through
+    // either is sound.
+    #[allow(unsafe_code)]
+    let version = unsafe { collateral.__bindgen_anon_1.__bindgen_anon_1.as_mut() };
+    version.major_version = 3;
+    version.minor_version = 1;
+
+    collateral.pck_crl_issuer_chain = pck_crl_issuer_chain.as_ptr() as _;
+    collateral.pck_crl_issuer_chain_size = pck_crl_issuer_chain.len() as u32;
+    collateral.root_ca_crl = root_ca_crl.as_ptr() as _;
+    collateral.root_ca_crl_size = root_ca_crl.len() as u32;
+    collateral.pck_crl = pck_crl.as_ptr() as _;
+    collateral.pck_crl_size = pck_crl.len() as u32;
+    collateral.tcb_info_issuer_chain = tcb_info_issuer_chain.as_ptr() as _;
+    collateral.tcb_info_issuer_chain_size = tcb_info_issuer_chain.len() as u32;
+    collateral.tcb_info = tcb_info.as_ptr() as _;
+    collateral.tcb_info_size = tcb_info.len() as u32;
+    collateral.qe_identity_issuer_chain = qe_identity_issuer_chain.as_ptr() as _;
+    collateral.qe_identity_issuer_chain_size = qe_identity_issuer_chain.len() as u32;
+    collateral.qe_identity = qe_identity.as_ptr() as _;
+    collateral.qe_identity_size = qe_identity.len() as u32;
+
+    Ok(DcapCollateral::try_from(&collateral)?)
+}
+
+fn nul_terminated(bytes: &[u8]) -> Vec<u8> {
+    let mut bytes = bytes.to_vec();
+    bytes.push(0);
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{quote4::test::tdx_quote, TeeType};
+    use mc_sgx_core_sys_types::{
+        sgx_attributes_t, sgx_cpu_svn_t, sgx_key_id_t, sgx_measurement_t, sgx_report_data_t,
+    };
+    use mc_sgx_core_types::ReportBody;
+
+    fn report_body() -> sgx_report_body_t {
+        sgx_report_body_t {
+            cpu_svn: sgx_cpu_svn_t { svn: [1; 16] },
+            misc_select: 0x0203_0405,
+            reserved1: [0; 12],
+            isv_ext_prod_id: [6; 16],
+            attributes: sgx_attributes_t {
+                flags: 0x0707_0707_0707_0707,
+                xfrm: 0x0808_0808_0808_0808,
+            },
+            mr_enclave: sgx_measurement_t { m: [9; 32] },
+            reserved2: [0; 32],
+            mr_signer: sgx_measurement_t { m: [10; 32] },
+            reserved3: [0; 32],
+            config_id: [11; 64],
+            isv_prod_id: 0x0C0D,
+            isv_svn: 0x0E0F,
+            config_svn: 0x1011,
+            reserved4: [0; 42],
+            isv_family_id: [18; 16],
+            report_data: sgx_report_data_t { d: [19; 64] },
+        }
+    }
+
+    #[test]
+    fn report_body_bytes_match_hardware_layout() {
+        let body = report_body();
+        let bytes = report_body_bytes(&body);
+
+        let parsed = ReportBody::try_from(bytes.as_slice()).expect("Failed to parse report body");
+        assert_eq!(parsed, ReportBody::from(&body));
+        assert_eq!(&bytes[16..20], &[5, 4, 3, 2]);
+        assert_eq!(&bytes[256..262], &[0x0D, 0x0C, 0x0F, 0x0E, 0x11, 0x10]);
+    }
+
+    #[test]
+    fn quote_header_round_trips() {
+        let bytes = tdx_quote();
+        let header = QuoteHeader::try_from(bytes.as_slice()).expect("Failed to parse header");
+
+        let raw = sgx_quote_header_t::from(&header);
+        assert_eq!(raw.version, 4);
+        assert_eq!(raw.att_key_data_0, TeeType::Tdx.value());
+        assert_eq!(QuoteHeader::from(&raw), header);
+        assert_eq!(quote_header_bytes(&raw), bytes[..QuoteHeader::SIZE]);
+    }
+
+    #[test]
+    fn local_report_from_sgx_report() {
+        let report = sgx_report_t {
+            body: report_body(),
+            key_id: sgx_key_id_t { id: [20; 32] },
+            mac: [21; 16],
+        };
+
+        let local_report = LocalReport::from(&report);
+        assert_eq!(local_report.body(), ReportBody::from(&report.body));
+        assert_eq!(local_report.key_id().as_ref(), &[20; 32]);
+        assert_eq!(local_report.mac().as_ref(), &[21; 16]);
+    }
+
+    #[test]
+    fn sgx_quote3_to_bytes() {
+        let mut header_bytes = tdx_quote();
+        header_bytes.truncate(QuoteHeader::SIZE);
+        let header = QuoteHeader::try_from(header_bytes.as_slice()).expect("Failed to parse");
+        let quote = sgx_quote3_t {
+            header: (&header).into(),
+            report_body: report_body(),
+            signature_data_len: 3,
+            ..Default::default()
+        };
+
+        let bytes = sgx_quote3_bytes(&quote, &[22, 23, 24]).expect("Failed to convert quote");
+        assert_eq!(&bytes[..QuoteHeader::SIZE], header_bytes.as_slice());
+        assert_eq!(
+            &bytes[QuoteHeader::SIZE..QuoteHeader::SIZE + REPORT_BODY_SIZE],
+            report_body_bytes(&report_body()).as_slice()
+        );
+        assert_eq!(
+            &bytes[QuoteHeader::SIZE + REPORT_BODY_SIZE..],
+            &[3, 0, 0, 0, 22, 23, 24]
+        );
+    }
+
+    #[test]
+    fn sgx_quote3_with_wrong_signature_data_length_fails() {
+        let quote = sgx_quote3_t {
+            header: sgx_quote_header_t::from(
+                &QuoteHeader::try_from(tdx_quote().as_slice()).expect("Failed to parse"),
+            ),
+            report_body: report_body(),
+            signature_data_len: 4,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sgx_quote3_bytes(&quote, &[22, 23, 24]),
+            Err(Error::SignatureDataLength {
+                expected: 4,
+                actual: 3
+            })
+        );
+    }
+}
@@ -0,0 +1,725 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! The requests and responses of the Intel Attestation Service (IAS), for
+//! attesting legacy EPID quotes.
+//!
+//! The flow is described in
+//! <https://api.trustedservices.intel.com/documents/sgx-attestation-api-spec.pdf>:
+//! 1. Get the signature revocation list for the platform's EPID group, from
+//!    [`EpidGroupId::sigrl_path()`](crate::EpidGroupId::sigrl_path).
+//! 2. Have the quoting enclave create an [`EpidQuote`] with the SigRL.
+//! 3. Post an [`IasEvidence`] with the quote to
+//!    [`IAS_REPORT_PATH`](crate::IAS_REPORT_PATH).
+//! 4. Verify the response as a [`SignedIasReport`], which gives the
+//!    [`IasReport`].
+//!
+//! The report is signed by the IAS report signing certificate, whose chain is
+//! rooted in the Intel SGX Attestation Report Signing CA, published at
+//! <https://certificates.trustedservices.intel.com/Intel_SGX_Attestation_RootCA.pem>.
+
+use crate::{
+    url_encoding::percent_decode, Advisories, CertificateChainVerifier, EpidQuote, Error, TcbStatus,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use base64ct::{Base64, Encoding};
+use der::{DateTime, Encode};
+use rsa::{
+    pkcs1v15::{Signature, VerifyingKey},
+    pkcs8::DecodePublicKey,
+    signature::Verifier as _,
+    RsaPublicKey,
+};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use x509_cert::{crl::CertificateList, Certificate};
+
+/// Maximum length of the nonce of an [`IasEvidence`]
+pub const IAS_NONCE_MAX_LENGTH: usize = 32;
+
+/// The attestation evidence payload posted to IAS
+///
+/// Serialize it as JSON for the body of the request.
+///
+/// ```
+/// use mc_attestation_verifier::IasEvidence;
+///
+/// let evidence = IasEvidence::new(&[1, 2, 3]).nonce("abc").unwrap();
+/// assert_eq!(
+///     serde_json::to_string(&evidence).unwrap(),
+///     r#"{"isvEnclaveQuote":"AQID","nonce":"abc"}"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IasEvidence {
+    isv_enclave_quote: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pse_manifest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nonce: Option<String>,
+}
+
+impl IasEvidence {
+    /// Evidence for the `quote` bytes, as produced by the quoting enclave
+    pub fn new(quote: &[u8]) -> Self {
+        Self {
+            isv_enclave_quote: Base64::encode_string(quote),
+            pse_manifest: None,
+            nonce: None,
+        }
+    }
+
+    /// Set the Platform Service Enclave manifest
+    pub fn pse_manifest(mut self, pse_manifest: &[u8]) -> Self {
+        self.pse_manifest = Some(Base64::encode_string(pse_manifest));
+        self
+    }
+
+    /// Set the nonce, IAS echoes it back in the [`IasReport`]
+    ///
+    /// # Errors
+    /// `Error::Ias` if the nonce is longer than [`IAS_NONCE_MAX_LENGTH`].
+    pub fn nonce(mut self, nonce: &str) -> Result<Self, Error> {
+        if nonce.len() > IAS_NONCE_MAX_LENGTH {
+            return Err(Error::Ias(format!(
+                "the nonce is {} characters, the maximum is {IAS_NONCE_MAX_LENGTH}",
+                nonce.len()
+            )));
+        }
+        self.nonce = Some(nonce.to_string());
+        Ok(self)
+    }
+}
+
+/// The status IAS gives to a quote
+#[derive(displaydoc::Display, Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IasQuoteStatus {
+    /// The EPID signature was verified and the platform is up to date
+    Ok,
+    /// The EPID signature is invalid
+    SignatureInvalid,
+    /// The EPID group has been revoked
+    GroupRevoked,
+    /// The EPID private key used for the signature has been revoked
+    SignatureRevoked,
+    /// The EPID private key has been revoked by the private key revocation list
+    KeyRevoked,
+    /// The SigRL used for the quote is not the latest
+    SigrlVersionMismatch,
+    /// The EPID group is out of date, the platform needs a TCB recovery
+    GroupOutOfDate,
+    /// The platform needs additional configuration
+    ConfigurationNeeded,
+    /// The enclave needs software hardening
+    SwHardeningNeeded,
+    /// The platform needs additional configuration and the enclave needs
+    /// software hardening
+    ConfigurationAndSwHardeningNeeded,
+}
+
+impl IasQuoteStatus {
+    /// The equivalent DCAP TCB status of the platform
+    ///
+    /// Returns `None` when the status is about the quote rather than the
+    /// platform, [`IasQuoteStatus::SignatureInvalid`] and
+    /// [`IasQuoteStatus::SigrlVersionMismatch`]. Those quotes are not valid.
+    /// The EPID revocation statuses are all [`TcbStatus::Revoked`].
+    pub fn tcb_status(&self) -> Option<TcbStatus> {
+        match self {
+            Self::Ok => Some(TcbStatus::UpToDate),
+            Self::GroupRevoked | Self::SignatureRevoked | Self::KeyRevoked => {
+                Some(TcbStatus::Revoked)
+            }
+            Self::GroupOutOfDate => Some(TcbStatus::OutOfDate),
+            Self::ConfigurationNeeded => Some(TcbStatus::ConfigurationNeeded),
+            Self::SwHardeningNeeded => Some(TcbStatus::SWHardeningNeeded),
+            Self::ConfigurationAndSwHardeningNeeded => {
+                Some(TcbStatus::ConfigurationAndSWHardeningNeeded)
+            }
+            Self::SignatureInvalid | Self::SigrlVersionMismatch => None,
+        }
+    }
+}
+
+/// The body of an attestation verification report from IAS
+///
+/// Nothing in the report is trusted until the signature of the report has
+/// been verified.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IasReport {
+    id: String,
+    timestamp: String,
+    version: u32,
+    isv_enclave_quote_status: IasQuoteStatus,
+    isv_enclave_quote_body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    revocation_reason: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pse_manifest_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pse_manifest_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    platform_info_blob: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    nonce: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    epid_pseudonym: Option<String>,
+    #[serde(
+        rename = "advisoryURL",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    advisory_url: Option<String>,
+    #[serde(rename = "advisoryIDs", default, skip_serializing_if = "Vec::is_empty")]
+    advisory_ids: Vec<String>,
+}
+
+impl IasReport {
+    /// The ID IAS assigned to the report
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// When the report was created, as an ISO 8601 UTC time without the
+    /// time zone
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    /// The version of the IAS API which created the report
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The status of the quote
+    pub fn quote_status(&self) -> IasQuoteStatus {
+        self.isv_enclave_quote_status
+    }
+
+    /// The quote, without its EPID signature
+    ///
+    /// # Errors
+    /// * `Error::Ias` if the quote body is not valid base64.
+    /// * `Error::Quote` if the quote body can not be parsed.
+    pub fn quote(&self) -> Result<EpidQuote<Vec<u8>>, Error> {
+        let bytes = Base64::decode_vec(&self.isv_enclave_quote_body)
+            .map_err(|e| Error::Ias(format!("invalid quote body: {e}")))?;
+        Ok(EpidQuote::try_from(bytes)?)
+    }
+
+    /// The reason the EPID group was revoked, for
+    /// [`IasQuoteStatus::GroupRevoked`]
+    ///
+    /// This is a CRL reason code, see RFC 5280 section 5.3.1.
+    pub fn revocation_reason(&self) -> Option<u32> {
+        self.revocation_reason
+    }
+
+    /// The status of the Platform Service Enclave manifest, when the evidence
+    /// had one
+    pub fn pse_manifest_status(&self) -> Option<&str> {
+        self.pse_manifest_status.as_deref()
+    }
+
+    /// The SHA-256 of the Platform Service Enclave manifest, as hex
+    pub fn pse_manifest_hash(&self) -> Option<&str> {
+        self.pse_manifest_hash.as_deref()
+    }
+
+    /// The platform info blob, as hex, for the platform software to resolve
+    /// the quote status
+    pub fn platform_info_blob(&self) -> Option<&str> {
+        self.platform_info_blob.as_deref()
+    }
+
+    /// The nonce from the [`IasEvidence`]
+    pub fn nonce(&self) -> Option<&str> {
+        self.nonce.as_deref()
+    }
+
+    /// The pseudonym of the platform, for linkable quotes
+    pub fn epid_pseudonym(&self) -> Option<&str> {
+        self.epid_pseudonym.as_deref()
+    }
+
+    /// Where to find more information about the advisories
+    pub fn advisory_url(&self) -> Option<&str> {
+        self.advisory_url.as_deref()
+    }
+
+    /// The IDs of the security advisories affecting the platform, of the form
+    /// "INTEL-SA-12345"
+    pub fn advisory_ids(&self) -> &[String] {
+        &self.advisory_ids
+    }
+
+    /// The advisories of the platform, with the TCB status of the quote
+    ///
+    /// Returns `None` if the quote status is not a TCB status, see
+    /// [`IasQuoteStatus::tcb_status()`].
+    pub fn advisories(&self) -> Option<Advisories> {
+        let status = self.isv_enclave_quote_status.tcb_status()?;
+        Some(Advisories::new(&self.advisory_ids, status))
+    }
+}
+
+impl TryFrom<&str> for IasReport {
+    type Error = Error;
+
+    fn try_from(json: &str) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// An attestation verification report, as returned by IAS, with its
+/// signature
+///
+/// The signature is over the exact bytes of the response body, so the body
+/// must not be re-encoded before verifying.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedIasReport {
+    body: String,
+    signature: Vec<u8>,
+}
+
+impl SignedIasReport {
+    /// Create a new instance
+    ///
+    /// # Arguments
+    /// * `body` - The body of the IAS response.
+    /// * `signature` - The decoded `X-IASReport-Signature` header of the IAS
+    ///   response.
+    pub fn new(body: impl Into<String>, signature: impl Into<Vec<u8>>) -> Self {
+        Self {
+            body: body.into(),
+            signature: signature.into(),
+        }
+    }
+
+    /// Create a new instance from the `X-IASReport-Signature` header, which
+    /// is base64
+    ///
+    /// # Errors
+    /// `Error::SignatureDecodeError` if the header is not valid base64.
+    pub fn from_signature_header(
+        body: impl Into<String>,
+        signature_header: &str,
+    ) -> Result<Self, Error> {
+        let signature =
+            Base64::decode_vec(signature_header).map_err(|_| Error::SignatureDecodeError)?;
+        Ok(Self::new(body, signature))
+    }
+
+    /// The body of the report, as it was signed
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// The RSA signature of the body
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Verify the report was signed with `key`, the public key of the IAS
+    /// report signing certificate.
+    ///
+    /// # Returns
+    /// The verified report.
+    ///
+    /// # Errors
+    /// - `Error::SignatureDecodeError` if the signature is malformed.
+    /// - `Error::SignatureVerification` if the signature does not match.
+    /// - `Error::Serde` if the verified body is not an IAS report.
+    pub fn verify(&self, key: &RsaPublicKey) -> Result<IasReport, Error> {
+        let signature = Signature::try_from(self.signature.as_slice())
+            .map_err(|_| Error::SignatureDecodeError)?;
+        VerifyingKey::<Sha256>::new(key.clone())
+            .verify(self.body.as_bytes(), &signature)
+            .map_err(|_| Error::SignatureVerification)?;
+        IasReport::try_from(self.body.as_str())
+    }
+
+    /// Verify the report was signed by the leaf of the IAS report signing
+    /// `certificate_chain`.
+    ///
+    /// # Arguments
+    /// - `certificate_verifier` - The verifier for the `certificate_chain`,
+    ///   trusting the Intel SGX Attestation Report Signing CA.
+    /// - `certificate_chain` - The report signing chain, leaf first. This is
+    ///   the `X-IASReport-Signing-Certificate` header of the IAS response, see
+    ///   [`ias_signing_chain_from_header()`].
+    /// - `crls` - The CRLs to verify the `certificate_chain` against.
+    /// - `time` - The time to verify the `certificate_chain` at.
+    ///
+    /// # Returns
+    /// The verified report.
+    ///
+    /// # Errors
+    /// - `Error::SigningCertificateChain` if the `certificate_chain` fails
+    ///   verification.
+    /// - `Error::MissingPublicKey` if the leaf RSA key can not be retrieved.
+    /// - The errors of [`SignedIasReport::verify()`].
+    pub fn verify_with_certificate_chain<C: CertificateChainVerifier>(
+        &self,
+        certificate_verifier: &C,
+        certificate_chain: &[Certificate],
+        crls: &[CertificateList],
+        time: Option<DateTime>,
+    ) -> Result<IasReport, Error> {
+        certificate_verifier
+            .verify_certificate_chain(certificate_chain, crls, time)
+            .map_err(|e| Error::SigningCertificateChain(e.to_string()))?;
+        let key = certificate_chain
+            .first()
+            .and_then(rsa_key_from_certificate)
+            .ok_or(Error::MissingPublicKey)?;
+        self.verify(&key)
+    }
+}
+
+fn rsa_key_from_certificate(certificate: &Certificate) -> Option<RsaPublicKey> {
+    let spki = certificate
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .ok()?;
+    RsaPublicKey::from_public_key_der(&spki).ok()
+}
+
+/// The IAS report signing chain from the `X-IASReport-Signing-Certificate`
+/// header of the IAS response.
+///
+/// The header is the URL encoded PEM of the chain, leaf first.
+///
+/// # Errors
+/// - `Error::UrlEncoding` if the header is not validly URL encoded.
+/// - `Error::Der` if the certificates can not be parsed.
+pub fn ias_signing_chain_from_header(header: &str) -> Result<Vec<Certificate>, Error> {
+    let pem = percent_decode(header)?;
+    Ok(Certificate::load_pem_chain(&pem)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{epid::test::epid_quote_body, CertificateChainVerifierError, QuoteError};
+    use assert_matches::assert_matches;
+    use der::{DecodePem, EncodePem};
+    use rsa::{
+        pkcs1v15::SigningKey,
+        pkcs8::DecodePrivateKey,
+        signature::{SignatureEncoding, Signer},
+        traits::PublicKeyParts,
+        RsaPrivateKey,
+    };
+    use yare::parameterized;
+
+    struct TestChainVerifier(Result<(), CertificateChainVerifierError>);
+
+    impl CertificateChainVerifier for TestChainVerifier {
+        fn verify_certificate_chain<'a, 'b>(
+            &self,
+            _certificate_chain: impl IntoIterator<Item = &'a Certificate>,
+            _crls: impl IntoIterator<Item = &'b CertificateList>,
+            _time: impl Into<Option<DateTime>>,
+        ) -> Result<(), CertificateChainVerifierError> {
+            self.0.clone()
+        }
+    }
+
+    fn private_key() -> RsaPrivateKey {
+        RsaPrivateKey::from_pkcs8_der(include_bytes!("../data/tests/ias_signing_key.der"))
+            .expect("Failed to decode private key")
+    }
+
+    fn sign(key: &RsaPrivateKey, body: &str) -> SignedIasReport {
+        let signature = SigningKey::<Sha256>::new(key.clone()).sign(body.as_bytes());
+        SignedIasReport::new(body, signature.to_vec())
+    }
+
+    fn signing_certificate() -> Certificate {
+        Certificate::from_pem(include_str!("../data/tests/ias_signing_cert.pem"))
+            .expect("Failed to parse certificate")
+    }
+
+    fn report_json(status: &str, quote_body: &str) -> String {
+        format!(
+            r#"{{
+                "id": "165171271757108173876306223827987629752",
+                "timestamp": "2023-07-12T16:01:02.123456",
+                "version": 4,
+                "isvEnclaveQuoteStatus": "{status}",
+                "isvEnclaveQuoteBody": "{quote_body}",
+                "platformInfoBlob": "1502006504000F00000F0F",
+                "nonce": "abc",
+                "advisoryURL": "https://security-center.intel.com",
+                "advisoryIDs": ["INTEL-SA-00161", "INTEL-SA-00289"]
+            }}"#
+        )
+    }
+
+    #[test]
+    fn evidence_with_pse_manifest() {
+        let evidence = IasEvidence::new(&[1, 2, 3]).pse_manifest(&[4, 5, 6]);
+
+        assert_eq!(
+            serde_json::to_string(&evidence).expect("Failed to serialize"),
+            r#"{"isvEnclaveQuote":"AQID","pseManifest":"BAUG"}"#
+        );
+    }
+
+    #[test]
+    fn evidence_nonce_too_long() {
+        let nonce = "a".repeat(IAS_NONCE_MAX_LENGTH + 1);
+
+        assert_matches!(IasEvidence::new(&[]).nonce(&nonce), Err(Error::Ias(_)));
+        assert!(IasEvidence::new(&[]).nonce(&nonce[1..]).is_ok());
+    }
+
+    #[test]
+    fn parse_report() {
+        let quote_body = Base64::encode_string(&epid_quote_body());
+        let json = report_json("GROUP_OUT_OF_DATE", &quote_body);
+        let report = IasReport::try_from(json.as_str()).expect("Failed to parse report");
+
+        assert_eq!(report.id(), "165171271757108173876306223827987629752");
+        assert_eq!(report.timestamp(), "2023-07-12T16:01:02.123456");
+        assert_eq!(report.version(), 4);
+        assert_eq!(report.quote_status(), IasQuoteStatus::GroupOutOfDate);
+        assert_eq!(report.platform_info_blob(), Some("1502006504000F00000F0F"));
+        assert_eq!(report.nonce(), Some("abc"));
+        assert_eq!(report.epid_pseudonym(), None);
+        assert_eq!(report.revocation_reason(), None);
+        assert_eq!(
+            report.advisory_url(),
+            Some("https://security-center.intel.com")
+        );
+        assert_eq!(report.advisory_ids(), ["INTEL-SA-00161", "INTEL-SA-00289"]);
+
+        let quote = report.quote().expect("Failed to parse quote");
+        assert_eq!(quote.body(), epid_quote_body().as_slice());
+        assert_eq!(quote.signature(), None);
+    }
+
+    #[parameterized(
+        ok = { "OK", IasQuoteStatus::Ok },
+        signature_invalid = { "SIGNATURE_INVALID", IasQuoteStatus::SignatureInvalid },
+        group_revoked = { "GROUP_REVOKED", IasQuoteStatus::GroupRevoked },
+        signature_revoked = { "SIGNATURE_REVOKED", IasQuoteStatus::SignatureRevoked },
+        key_revoked = { "KEY_REVOKED", IasQuoteStatus::KeyRevoked },
+        sigrl_version_mismatch = { "SIGRL_VERSION_MISMATCH", IasQuoteStatus::SigrlVersionMismatch },
+        configuration_needed = { "CONFIGURATION_NEEDED", IasQuoteStatus::ConfigurationNeeded },
+        sw_hardening_needed = { "SW_HARDENING_NEEDED", IasQuoteStatus::SwHardeningNeeded },
+        configuration_and_sw_hardening_needed = { "CONFIGURATION_AND_SW_HARDENING_NEEDED", IasQuoteStatus::ConfigurationAndSwHardeningNeeded },
+    )]
+    fn quote_status(json: &str, expected: IasQuoteStatus) {
+        let json = report_json(json, "");
+        let report = IasReport::try_from(json.as_str()).expect("Failed to parse report");
+
+        assert_eq!(report.quote_status(), expected);
+    }
+
+    #[test]
+    fn unknown_quote_status() {
+        let json = report_json("NOT_A_STATUS", "");
+
+        assert_matches!(IasReport::try_from(json.as_str()), Err(Error::Serde(_)));
+    }
+
+    #[test]
+    fn invalid_quote_body() {
+        let json = report_json("OK", "not base64!");
+        let report = IasReport::try_from(json.as_str()).expect("Failed to parse report");
+
+        assert_matches!(report.quote(), Err(Error::Ias(_)));
+    }
+
+    #[test]
+    fn truncated_quote_body() {
+        let quote_body = Base64::encode_string(&epid_quote_body()[..100]);
+        let json = report_json("OK", &quote_body);
+        let report = IasReport::try_from(json.as_str()).expect("Failed to parse report");
+
+        assert_matches!(
+            report.quote(),
+            Err(Error::Quote(QuoteError::Truncated { .. }))
+        );
+    }
+
+    #[parameterized(
+        ok = { IasQuoteStatus::Ok, Some(TcbStatus::UpToDate) },
+        signature_invalid = { IasQuoteStatus::SignatureInvalid, None },
+        group_revoked = { IasQuoteStatus::GroupRevoked, Some(TcbStatus::Revoked) },
+        signature_revoked = { IasQuoteStatus::SignatureRevoked, Some(TcbStatus::Revoked) },
+        key_revoked = { IasQuoteStatus::KeyRevoked, Some(TcbStatus::Revoked) },
+        sigrl_version_mismatch = { IasQuoteStatus::SigrlVersionMismatch, None },
+        group_out_of_date = { IasQuoteStatus::GroupOutOfDate, Some(TcbStatus::OutOfDate) },
+        configuration_needed = { IasQuoteStatus::ConfigurationNeeded, Some(TcbStatus::ConfigurationNeeded) },
+        sw_hardening_needed = { IasQuoteStatus::SwHardeningNeeded, Some(TcbStatus::SWHardeningNeeded) },
+        configuration_and_sw_hardening_needed = { IasQuoteStatus::ConfigurationAndSwHardeningNeeded, Some(TcbStatus::ConfigurationAndSWHardeningNeeded) },
+    )]
+    fn tcb_status(status: IasQuoteStatus, expected: Option<TcbStatus>) {
+        assert_eq!(status.tcb_status(), expected);
+    }
+
+    #[test]
+    fn report_advisories() {
+        let json = report_json("SW_HARDENING_NEEDED", "");
+        let report = IasReport::try_from(json.as_str()).expect("Failed to parse report");
+
+        assert_eq!(
+            report.advisories(),
+            Some(Advisories::new(
+                ["INTEL-SA-00161", "INTEL-SA-00289"],
+                TcbStatus::SWHardeningNeeded
+            ))
+        );
+    }
+
+    #[test]
+    fn no_advisories_for_invalid_signature() {
+        let json = report_json("SIGNATURE_INVALID", "");
+        let report = IasReport::try_from(json.as_str()).expect("Failed to parse report");
+
+        assert_eq!(report.advisories(), None);
+    }
+
+    #[test]
+    fn verify_signed_report() {
+        let key = private_key();
+        let json = report_json("OK", "");
+        let signed = sign(&key, &json);
+
+        let report = signed
+            .verify(&key.to_public_key())
+            .expect("Failed to verify report");
+        assert_eq!(report.quote_status(), IasQuoteStatus::Ok);
+    }
+
+    #[test]
+    fn verify_modified_report_fails() {
+        let key = private_key();
+        let json = report_json("GROUP_REVOKED", "");
+        let signed = sign(&key, &json);
+        let modified =
+            SignedIasReport::new(json.replace("GROUP_REVOKED", "OK"), signed.signature());
+
+        assert_eq!(
+            modified.verify(&key.to_public_key()),
+            Err(Error::SignatureVerification)
+        );
+    }
+
+    #[test]
+    fn verify_with_wrong_key_fails() {
+        let key = private_key();
+        let signed = sign(&key, &report_json("OK", ""));
+        let wrong_key = RsaPublicKey::new(key.n() + 2u32, key.e().clone())
+            .expect("Failed to create public key");
+
+        assert_eq!(signed.verify(&wrong_key), Err(Error::SignatureVerification));
+    }
+
+    #[test]
+    fn signature_header_must_be_base64() {
+        assert_eq!(
+            SignedIasReport::from_signature_header("{}", "not base64!"),
+            Err(Error::SignatureDecodeError)
+        );
+    }
+
+    #[test]
+    fn verify_with_certificate_chain() {
+        let key = private_key();
+        let json = report_json("OK", "");
+        let signature = Base64::encode_string(sign(&key, &json).signature());
+        let signed = SignedIasReport::from_signature_header(json.as_str(), &signature)
+            .expect("Failed to decode signature");
+
+        let report = signed
+            .verify_with_certificate_chain(
+                &TestChainVerifier(Ok(())),
+                &[signing_certificate()],
+                &[],
+                None,
+            )
+            .expect("Failed to verify report");
+        assert_eq!(report.id(), "165171271757108173876306223827987629752");
+    }
+
+    #[test]
+    fn verify_with_certificate_chain_fails_for_bad_chain() {
+        let key = private_key();
+        let signed = sign(&key, &report_json("OK", ""));
+
+        assert_matches!(
+            signed.verify_with_certificate_chain(
+                &TestChainVerifier(Err(CertificateChainVerifierError::CertificateRevoked)),
+                &[signing_certificate()],
+                &[],
+                None,
+            ),
+            Err(Error::SigningCertificateChain(_))
+        );
+    }
+
+    #[test]
+    fn verify_with_certificate_chain_needs_rsa_leaf() {
+        let key = private_key();
+        let signed = sign(&key, &report_json("OK", ""));
+        let p256_certificate = Certificate::from_pem(include_str!("../data/tests/root_ca.pem"))
+            .expect("Failed to parse certificate");
+
+        assert_eq!(
+            signed.verify_with_certificate_chain(
+                &TestChainVerifier(Ok(())),
+                &[p256_certificate],
+                &[],
+                None,
+            ),
+            Err(Error::MissingPublicKey)
+        );
+    }
+
+    #[test]
+    fn signing_chain_from_url_encoded_header() {
+        let chain = [
+            Certificate::from_pem(include_str!("../data/tests/tcb_signer.pem"))
+                .expect("Failed to parse certificate"),
+            Certificate::from_pem(include_str!("../data/tests/root_ca.pem"))
+                .expect("Failed to parse certificate"),
+        ];
+        let header = chain
+            .iter()
+            .map(|certificate| {
+                certificate
+                    .to_pem(der::pem::LineEnding::LF)
+                    .expect("Failed to encode certificate")
+            })
+            .collect::<String>()
+            .replace('\n', "%0A")
+            .replace(' ', "%20")
+            .replace('+', "%2B")
+            .replace('/', "%2F")
+            .replace('=', "%3D");
+
+        assert_eq!(
+            ias_signing_chain_from_header(&header).expect("Failed to parse chain"),
+            chain
+        );
+    }
+
+    #[test]
+    fn signing_chain_with_truncated_escape() {
+        assert_matches!(
+            ias_signing_chain_from_header("-----BEGIN%2"),
+            Err(Error::UrlEncoding)
+        );
+    }
+}
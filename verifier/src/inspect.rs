@@ -0,0 +1,458 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Human readable dumps of quotes.
+//!
+//! [`QuoteInspector`] renders every field of a quote, so an operator can
+//! paste the dump into a ticket when verification fails. The bytes are walked
+//! without validating them, a quote which fails to parse is rendered up to
+//! the point of failure followed by the error.
+
+use crate::{
+    quote4::{Reader, ECDSA_P256_KEY_TYPE, QE_REPORT_SIZE},
+    QuoteError, QuoteField, QuoteHeader, TdReportBody, TeeType, INTEL_QE_VENDOR_ID,
+};
+use core::fmt::{Display, Formatter};
+use mc_sgx_core_sys_types::sgx_attributes_t;
+use mc_sgx_core_types::Attributes;
+
+/// The certification data type of a PCK certificate chain
+const PCK_CERTIFICATE_CHAIN_TYPE: u16 = 5;
+
+/// The certification data type of the QE report certification data
+const QE_REPORT_CERTIFICATION_DATA_TYPE: u16 = 6;
+
+/// Size of an ECDSA P-256 signature or raw public key
+const ECDSA_P256_SIZE: usize = 64;
+
+/// The names of the TD attributes bits, from the TDX module ABI
+const TD_ATTRIBUTES_FLAGS: [(u64, &str); 5] = [
+    (1 << 0, "DEBUG"),
+    (1 << 28, "SEPT_VE_DISABLE"),
+    (1 << 30, "PKS"),
+    (1 << 31, "KL"),
+    (1 << 63, "PERFMON"),
+];
+
+/// Indentation of the fields in a section
+const FIELD_INDENT: usize = 2;
+
+/// Renders every header, report body, and signature data field of a quote
+///
+/// Version 3, 4, and 5 quotes are supported. Byte fields are displayed as
+/// upper case hex, and the SGX attributes and TD attributes are decoded into
+/// their flag names.
+///
+/// ```
+/// use mc_attestation_verifier::QuoteInspector;
+///
+/// let mut bytes = [0u8; 48];
+/// bytes[0] = 3;
+/// let dump = QuoteInspector::new(&bytes).to_string();
+/// assert!(dump.starts_with("Quote header:\n  Version: 3\n"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteInspector<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> QuoteInspector<'a> {
+    /// Create a new instance for the raw quote `bytes`
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn dump(&self, f: &mut Formatter<'_>) -> Result<(), DumpError> {
+        let mut reader = Reader::new(self.bytes);
+        let header = QuoteHeader::read(&mut reader)?;
+        write_header(f, &header)?;
+
+        match header.version() {
+            3 => {
+                writeln!(f, "SGX report body:")?;
+                let mut body = reader.section(QuoteField::ReportBody, QE_REPORT_SIZE)?;
+                write_sgx_report_body(f, &mut body, QuoteField::ReportBody, FIELD_INDENT)?;
+            }
+            4 => {
+                writeln!(f, "TD report body:")?;
+                let body = TdReportBody::read(&mut reader, false)?;
+                write_td_report_body(f, &body)?;
+            }
+            5 => {
+                let body_type = reader.u16(QuoteField::BodyType)?;
+                let size = reader.u32(QuoteField::BodySize)? as usize;
+                match body_type {
+                    1 => {
+                        writeln!(f, "SGX report body:")?;
+                        let mut body = reader.section(QuoteField::ReportBody, size)?;
+                        write_sgx_report_body(f, &mut body, QuoteField::ReportBody, FIELD_INDENT)?;
+                    }
+                    2 | 3 => {
+                        let tdx = if body_type == 2 { "1.0" } else { "1.5" };
+                        writeln!(f, "TD report body, TDX {tdx}:")?;
+                        let body =
+                            TdReportBody::try_from(reader.take(QuoteField::ReportBody, size)?)?;
+                        write_td_report_body(f, &body)?;
+                    }
+                    _ => return Err(QuoteError::BodyType(body_type).into()),
+                }
+            }
+            version => return Err(QuoteError::Version(version).into()),
+        }
+
+        let size = reader.u32(QuoteField::SignatureDataSize)? as usize;
+        writeln!(f, "Signature data: {size} bytes")?;
+        let mut signature_reader = reader.section(QuoteField::SignatureData, size)?;
+        write_hex(
+            f,
+            "Signature",
+            signature_reader.take(QuoteField::QuoteSignature, ECDSA_P256_SIZE)?,
+        )?;
+        write_hex(
+            f,
+            "Attestation key",
+            signature_reader.take(QuoteField::AttestationKey, ECDSA_P256_SIZE)?,
+        )?;
+        if header.version() == 3 {
+            write_qe_report(f, &mut signature_reader)?;
+        } else {
+            let data_type = signature_reader.u16(QuoteField::QeCertificationDataType)?;
+            let size = signature_reader.u32(QuoteField::QeCertificationDataSize)? as usize;
+            write_field(
+                f,
+                "Certification data",
+                format_args!("type {data_type}, {size} bytes"),
+            )?;
+            if data_type != QE_REPORT_CERTIFICATION_DATA_TYPE {
+                return Err(QuoteError::CertificationDataType(data_type).into());
+            }
+            write_qe_report(
+                f,
+                &mut signature_reader.section(QuoteField::QeCertificationData, size)?,
+            )?;
+        }
+
+        let trailing = self.bytes.len() - reader.offset();
+        if trailing != 0 {
+            writeln!(f, "Trailing bytes: {trailing}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for QuoteInspector<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.dump(f) {
+            Ok(()) => Ok(()),
+            Err(DumpError::Format(e)) => Err(e),
+            Err(DumpError::Quote(e)) => write!(f, "Unable to parse further: {e}"),
+        }
+    }
+}
+
+/// Stops the dump, either because the quote is malformed or the formatter
+/// failed
+enum DumpError {
+    Format(core::fmt::Error),
+    Quote(QuoteError),
+}
+
+impl From<core::fmt::Error> for DumpError {
+    fn from(e: core::fmt::Error) -> Self {
+        DumpError::Format(e)
+    }
+}
+
+impl From<QuoteError> for DumpError {
+    fn from(e: QuoteError) -> Self {
+        DumpError::Quote(e)
+    }
+}
+
+fn write_field(f: &mut Formatter<'_>, name: &str, value: impl Display) -> core::fmt::Result {
+    write_indented_field(f, FIELD_INDENT, name, value)
+}
+
+fn write_indented_field(
+    f: &mut Formatter<'_>,
+    indent: usize,
+    name: &str,
+    value: impl Display,
+) -> core::fmt::Result {
+    writeln!(f, "{:indent$}{name}: {value}", "")
+}
+
+fn write_hex(f: &mut Formatter<'_>, name: &str, bytes: &[u8]) -> core::fmt::Result {
+    write_indented_field(f, FIELD_INDENT, name, hex::encode_upper(bytes))
+}
+
+fn write_header(f: &mut Formatter<'_>, header: &QuoteHeader) -> core::fmt::Result {
+    writeln!(f, "Quote header:")?;
+    write_field(f, "Version", header.version())?;
+    let key_type = header.attestation_key_type();
+    let key_name = if key_type == ECDSA_P256_KEY_TYPE {
+        " (ECDSA-256 with P-256)"
+    } else {
+        " (unknown)"
+    };
+    write_field(
+        f,
+        "Attestation key type",
+        format_args!("{key_type}{key_name}"),
+    )?;
+    let tee_name = match header.tee_type() {
+        Some(TeeType::Sgx) => "SGX",
+        Some(TeeType::Tdx) => "TDX",
+        None => "unknown",
+    };
+    write_field(
+        f,
+        "TEE type",
+        format_args!("0x{:08X} ({tee_name})", header.tee_type_value()),
+    )?;
+    write_field(f, "QE SVN", header.qe_svn())?;
+    write_field(f, "PCE SVN", header.pce_svn())?;
+    let vendor = if header.qe_vendor_id() == &INTEL_QE_VENDOR_ID {
+        "Intel"
+    } else {
+        "unknown"
+    };
+    write_field(
+        f,
+        "QE vendor ID",
+        format_args!("{} ({vendor})", hex::encode_upper(header.qe_vendor_id())),
+    )?;
+    write_hex(f, "User data", header.user_data())
+}
+
+/// The fields of a `sgx_report_body_t`, read in place so that malformed
+/// bodies still render up to where they end
+fn write_sgx_report_body(
+    f: &mut Formatter<'_>,
+    reader: &mut Reader,
+    field: QuoteField,
+    indent: usize,
+) -> Result<(), DumpError> {
+    read_hex(f, reader, field, indent, "CPUSVN", 16)?;
+    let misc_select = reader.u32(field)?;
+    write_indented_field(f, indent, "MISCSELECT", format_args!("0x{misc_select:08X}"))?;
+    reader.take(field, 12)?;
+    read_hex(f, reader, field, indent, "ISV extended product ID", 16)?;
+    let flags = reader.u64(field)?;
+    let xfrm = reader.u64(field)?;
+    let attributes = Attributes::from(sgx_attributes_t { flags, xfrm });
+    write_indented_field(
+        f,
+        indent,
+        "Attributes",
+        format_args!("0x{flags:016X} 0x{xfrm:016X} ({attributes})"),
+    )?;
+    read_hex(f, reader, field, indent, "MRENCLAVE", 32)?;
+    reader.take(field, 32)?;
+    read_hex(f, reader, field, indent, "MRSIGNER", 32)?;
+    reader.take(field, 32)?;
+    read_hex(f, reader, field, indent, "CONFIGID", 64)?;
+    write_indented_field(f, indent, "ISV product ID", reader.u16(field)?)?;
+    write_indented_field(f, indent, "ISV SVN", reader.u16(field)?)?;
+    write_indented_field(f, indent, "CONFIGSVN", reader.u16(field)?)?;
+    reader.take(field, 42)?;
+    read_hex(f, reader, field, indent, "ISV family ID", 16)?;
+    read_hex(f, reader, field, indent, "Report data", 64)
+}
+
+fn read_hex(
+    f: &mut Formatter<'_>,
+    reader: &mut Reader,
+    field: QuoteField,
+    indent: usize,
+    name: &str,
+    size: usize,
+) -> Result<(), DumpError> {
+    let bytes = reader.take(field, size)?;
+    write_indented_field(f, indent, name, hex::encode_upper(bytes))?;
+    Ok(())
+}
+
+fn write_td_report_body(f: &mut Formatter<'_>, body: &TdReportBody) -> core::fmt::Result {
+    write_hex(f, "TEE TCB SVN", body.tee_tcb_svn())?;
+    write_hex(f, "MRSEAM", body.mr_seam())?;
+    write_hex(f, "MRSIGNERSEAM", body.mr_signer_seam())?;
+    write_hex(f, "SEAM attributes", body.seam_attributes())?;
+    let td_attributes = u64::from_le_bytes(*body.td_attributes());
+    write_field(
+        f,
+        "TD attributes",
+        format_args!("0x{td_attributes:016X} ({})", FlagNames(td_attributes)),
+    )?;
+    write_field(
+        f,
+        "XFAM",
+        format_args!("0x{:016X}", u64::from_le_bytes(*body.xfam())),
+    )?;
+    write_hex(f, "MRTD", body.mr_td())?;
+    write_hex(f, "MRCONFIGID", body.mr_config_id())?;
+    write_hex(f, "MROWNER", body.mr_owner())?;
+    write_hex(f, "MROWNERCONFIG", body.mr_owner_config())?;
+    for (index, rtmr) in body.rtmrs().iter().enumerate() {
+        write_field(
+            f,
+            "RTMR",
+            format_args!("{index} {}", hex::encode_upper(rtmr)),
+        )?;
+    }
+    write_hex(f, "Report data", body.report_data())?;
+    if let Some(tee_tcb_svn_2) = body.tee_tcb_svn_2() {
+        write_hex(f, "TEE TCB SVN 2", tee_tcb_svn_2)?;
+    }
+    if let Some(mr_service_td) = body.mr_service_td() {
+        write_hex(f, "MRSERVICETD", mr_service_td)?;
+    }
+    Ok(())
+}
+
+/// The QE report certification data, shared by all of the quote versions
+fn write_qe_report(f: &mut Formatter<'_>, reader: &mut Reader) -> Result<(), DumpError> {
+    writeln!(f, "{:FIELD_INDENT$}QE report body:", "")?;
+    let mut qe_report = reader.section(QuoteField::QeReport, QE_REPORT_SIZE)?;
+    write_sgx_report_body(f, &mut qe_report, QuoteField::QeReport, FIELD_INDENT * 2)?;
+    write_hex(
+        f,
+        "QE report signature",
+        reader.take(QuoteField::QeReportSignature, ECDSA_P256_SIZE)?,
+    )?;
+    let size = reader.u16(QuoteField::QeAuthenticationDataSize)? as usize;
+    write_hex(
+        f,
+        "QE authentication data",
+        reader.take(QuoteField::QeAuthenticationData, size)?,
+    )?;
+    let data_type = reader.u16(QuoteField::CertificationDataType)?;
+    let size = reader.u32(QuoteField::CertificationDataSize)? as usize;
+    let data = reader.take(QuoteField::CertificationData, size)?;
+    if data_type == PCK_CERTIFICATE_CHAIN_TYPE {
+        let certificates = data
+            .windows(b"-----BEGIN CERTIFICATE-----".len())
+            .filter(|window| window == b"-----BEGIN CERTIFICATE-----")
+            .count();
+        write_field(
+            f,
+            "Certification data",
+            format_args!("type {data_type}, {size} bytes, {certificates} PEM certificates"),
+        )?;
+    } else {
+        write_field(
+            f,
+            "Certification data",
+            format_args!("type {data_type}, {size} bytes"),
+        )?;
+    }
+    Ok(())
+}
+
+/// The names of the set TD attributes bits
+struct FlagNames(u64);
+
+impl Display for FlagNames {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut names = TD_ATTRIBUTES_FLAGS
+            .iter()
+            .filter(|(bit, _)| self.0 & bit != 0)
+            .map(|(_, name)| name)
+            .peekable();
+        if names.peek().is_none() {
+            return write!(f, "none");
+        }
+        for (index, name) in names.enumerate() {
+            let separator = if index == 0 { "" } else { " | " };
+            write!(f, "{separator}{name}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::quote4::test::tdx_quote;
+    use alloc::{format, string::ToString};
+
+    const QUOTE_BYTES: &[u8] = include_bytes!("../data/tests/hw_quote.dat");
+
+    #[test]
+    fn sgx_quote_dump() {
+        let dump = QuoteInspector::new(QUOTE_BYTES).to_string();
+
+        let header = textwrap::dedent(
+            "
+            Quote header:
+              Version: 3
+              Attestation key type: 2 (ECDSA-256 with P-256)
+              TEE type: 0x00000000 (SGX)
+            ",
+        );
+        assert!(format!("\n{dump}").starts_with(&header));
+        assert!(dump.contains("\nSGX report body:\n  CPUSVN: "));
+        assert!(dump.contains("\n  QE report body:\n    CPUSVN: "));
+        assert!(dump.contains("\n    Attributes: 0x"));
+        assert!(dump.contains("  Certification data: type 5, "));
+        assert!(dump.ends_with(" bytes, 3 PEM certificates\n"));
+    }
+
+    #[test]
+    fn tdx_quote_dump() {
+        let mut quote = tdx_quote();
+        // Set the DEBUG and SEPT_VE_DISABLE bits of the TD attributes
+        quote[QuoteHeader::SIZE + 120..QuoteHeader::SIZE + 128]
+            .copy_from_slice(&0x1000_0001u64.to_le_bytes());
+        let dump = QuoteInspector::new(&quote).to_string();
+
+        assert!(dump.contains("  TEE type: 0x00000081 (TDX)\n"));
+        assert!(dump.contains("\nTD report body:\n  TEE TCB SVN: 00000000"));
+        assert!(dump.contains("  TD attributes: 0x0000000010000001 (DEBUG | SEPT_VE_DISABLE)\n"));
+        assert!(dump.contains("  XFAM: 0x0505050505050505\n"));
+        assert!(dump.contains(&format!("  RTMR: 3 {}\n", "0D".repeat(48))));
+        assert!(dump.contains("  QE authentication data: BBBBBB\n"));
+        assert!(dump.ends_with("  Certification data: type 5, 5 bytes, 0 PEM certificates\n"));
+    }
+
+    #[test]
+    fn trailing_bytes_are_counted() {
+        let mut quote = tdx_quote();
+        quote.extend([0; 3]);
+        let dump = QuoteInspector::new(&quote).to_string();
+
+        assert!(dump.ends_with("\nTrailing bytes: 3\n"));
+    }
+
+    #[test]
+    fn truncated_quote_dumps_up_to_the_error() {
+        let quote = tdx_quote();
+        let dump = QuoteInspector::new(&quote[..QuoteHeader::SIZE + 20]).to_string();
+
+        assert!(dump.contains("  User data: "));
+        assert!(dump.ends_with(&format!(
+            "TD report body:\nUnable to parse further: {}",
+            QuoteError::Truncated {
+                field: QuoteField::ReportBody,
+                offset: QuoteHeader::SIZE,
+                size: TdReportBody::SIZE,
+                available: 20,
+            }
+        )));
+    }
+
+    #[test]
+    fn unsupported_version_stops_after_header() {
+        let mut quote = tdx_quote();
+        quote[0] = 6;
+        let dump = QuoteInspector::new(&quote).to_string();
+
+        assert!(dump.contains("  Version: 6\n"));
+        assert!(dump.ends_with("\nUnable to parse further: Unsupported quote version 6"));
+    }
+
+    #[test]
+    fn no_td_attributes() {
+        assert_eq!(FlagNames(0).to_string(), "none");
+        assert_eq!(FlagNames(1 << 63 | 1 << 30).to_string(), "PKS | PERFMON");
+    }
+}
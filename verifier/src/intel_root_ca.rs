@@ -0,0 +1,57 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! The Intel SGX Root CA, the trust anchor of all DCAP collateral.
+//!
+//! The certificate is published at
+//! <https://certificates.trustedservices.intel.com/Intel_SGX_Provisioning_Certification_RootCA.cer>.
+//! Its SHA-256 fingerprint is
+//! `44:A0:19:6B:2B:99:F8:89:B8:E1:49:E9:5B:80:7A:35:0E:74:24:96:43:99:E8:85:A7:CB:B8:CC:FA:B6:74:D3`.
+
+/// The PEM encoded Intel SGX Root CA certificate
+pub const INTEL_SGX_ROOT_CA_PEM: &str = include_str!("../data/intel_sgx_root_ca.pem");
+
+/// The SHA-256 hash of the DER encoded SubjectPublicKeyInfo of the Intel SGX
+/// Root CA, for pinning the root key.
+pub const INTEL_SGX_ROOT_CA_SPKI_SHA256: [u8; 32] = [
+    0xa0, 0xaf, 0x03, 0x12, 0x89, 0xf5, 0xd5, 0xd4, 0x13, 0x2f, 0x91, 0x86, 0x06, 0x8a, 0x7f, 0xc1,
+    0x36, 0x28, 0x63, 0x3b, 0xa2, 0x35, 0x77, 0x74, 0x72, 0xe2, 0x9b, 0x6b, 0x6c, 0x67, 0xa4, 0x9e,
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use der::{DecodePem, Encode};
+    use sha2::{Digest, Sha256};
+    use x509_cert::Certificate;
+
+    fn root_ca() -> Certificate {
+        Certificate::from_pem(INTEL_SGX_ROOT_CA_PEM).expect("Failed to parse root CA")
+    }
+
+    #[test]
+    fn spki_hash_matches_certificate() {
+        let spki = root_ca()
+            .tbs_certificate
+            .subject_public_key_info
+            .to_der()
+            .expect("Failed to encode public key info");
+        assert_eq!(Sha256::digest(spki)[..], INTEL_SGX_ROOT_CA_SPKI_SHA256);
+    }
+
+    #[test]
+    fn fingerprint_matches_published_certificate() {
+        let der = root_ca().to_der().expect("Failed to encode root CA");
+        assert_eq!(
+            hex::encode(Sha256::digest(der)),
+            "44a0196b2b99f889b8e149e95b807a350e7424964399e885a7cbb8ccfab674d3"
+        );
+    }
+
+    #[test]
+    fn is_self_signed_intel_root() {
+        let tbs = root_ca().tbs_certificate;
+        assert_eq!(tbs.subject, tbs.issuer);
+        assert!(tbs.subject.to_string().contains("CN=Intel SGX Root CA"));
+    }
+}
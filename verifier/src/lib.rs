@@ -5,44 +5,184 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 mod advisories;
+mod archive;
+#[cfg(feature = "tokio")]
+mod async_pcs;
+mod attested_key;
+mod canonical_json;
+mod capabilities;
+mod cert_chain_ref;
 mod certificate_chain;
+mod certification_data;
+mod challenge;
+mod collateral;
+#[cfg(feature = "std")]
+mod collateral_cache;
+mod collateral_provider;
+mod cpu_svn;
+mod epid;
 mod error;
 mod evidence;
+mod ffi;
+mod ias;
 mod identity;
+mod inspect;
+mod intel_root_ca;
+mod local_report;
 #[cfg(feature = "mbedtls")]
 mod mbedtls;
+mod options;
+mod pck_ca;
+#[cfg(feature = "pcs")]
+mod pcs;
+#[cfg(feature = "mbedtls")]
+mod pkcs7;
+mod platform_configuration;
+mod platform_id;
+mod policy;
+#[cfg(feature = "std")]
+mod prefetch;
 mod qe_identity;
 mod qe_report_body;
 mod quote;
+mod quote4;
+mod quote5;
 mod report_body;
+#[cfg(feature = "pcs")]
+mod retry;
+#[cfg(feature = "std")]
+mod reverification;
 mod struct_name;
+mod supplemental;
+mod target_info;
 mod tcb;
-
-pub use advisories::{Advisories, AdvisoriesVerifier, AdvisoryStatus};
-pub use certificate_chain::{CertificateChainVerifier, CertificateChainVerifierError};
+mod td_policy;
+mod td_report;
+mod tdx_module;
+mod tee_type;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+#[cfg(feature = "pcs")]
+mod thim;
+#[cfg(feature = "unstable")]
+pub mod unstable;
+mod url_encoding;
+mod verify_quote;
+#[cfg(feature = "std")]
+mod worker;
+
+pub use advisories::{Advisories, AdvisoriesVerifier, AdvisoryPolicy, AdvisoryStatus};
+pub use archive::{ArchiveRecord, CollateralSnapshot, Reverification, ARCHIVE_RECORD_VERSION};
+#[cfg(feature = "tokio")]
+pub use async_pcs::AsyncPcsClient;
+pub use attested_key::{AttestedKey, KeyClaims};
+pub use canonical_json::{canonicalize_json, to_canonical_json};
+pub use capabilities::{capabilities, Capabilities};
+pub use cert_chain_ref::{CertChainRef, CertChainRefIter, CertificateRef};
+pub use certificate_chain::{
+    crl_distribution_points, CertificateChainVerifier, CertificateChainVerifierError, ChainPolicy,
+    LeafMatcher,
+};
+pub use certification_data::{
+    EmbeddedPckChainOnly, PccsPckResolver, PckCertificateResponse, PckCertificationData,
+    PckIdentifier, PckResolver, PCK_CERTIFICATE_CHAIN_TYPE, PCK_LEAF_CERTIFICATE_TYPE,
+    PPID_CLEARTEXT_TYPE, PPID_ENCRYPTED_RSA_2048_TYPE, PPID_ENCRYPTED_RSA_3072_TYPE,
+};
+pub use challenge::{Challenge, ChallengeVerifier, NONCE_SIZE};
+pub use collateral::{Collateral, CollateralItem};
+#[cfg(feature = "std")]
+pub use collateral_cache::CollateralCache;
+#[cfg(feature = "tokio")]
+pub use collateral_provider::AsyncCollateralProvider;
+pub use collateral_provider::CollateralProvider;
+pub use cpu_svn::CpuSvnComponents;
+pub use epid::{EpidGroupId, EpidQuote, EpidSignatureType, IAS_REPORT_PATH};
 pub use error::Error;
-pub use evidence::{Evidence, EvidenceValue, EvidenceVerifier};
+pub use evidence::{
+    Evidence, EvidenceValue, EvidenceVerifier, IssuerChain, IssuerChainVerifier, UnverifiedEvidence,
+};
 
+pub use ffi::sgx_quote3_bytes;
+pub use ias::{
+    ias_signing_chain_from_header, IasEvidence, IasQuoteStatus, IasReport, SignedIasReport,
+    IAS_NONCE_MAX_LENGTH,
+};
 pub use identity::{
     TrustedIdentitiesVerifier, TrustedIdentity, TrustedMrEnclaveIdentity, TrustedMrSignerIdentity,
 };
 
+pub use inspect::QuoteInspector;
+pub use intel_root_ca::{INTEL_SGX_ROOT_CA_PEM, INTEL_SGX_ROOT_CA_SPKI_SHA256};
+pub use local_report::{LocalReport, ReportKeyProvider, REPORT_KEY_SIZE, REPORT_SIZE};
+pub use options::VerificationOptions;
+pub use pck_ca::{PckCaType, PckCaTypeVerifier};
+#[cfg(feature = "pcs")]
+pub use pcs::{PckCrl, PcsClient, INTEL_PCS_URL, PCS_API_VERSION};
+pub use platform_configuration::{PlatformConfiguration, PlatformConfigurationVerifier};
+pub use platform_id::{Fmspc, PceId, Ppid, PCE_ID_SIZE, PPID_SIZE};
+pub use policy::{PolicyDecision, PolicyEngine, PolicyEngineVerifier, PolicyInput};
+
+#[cfg(feature = "std")]
+pub use prefetch::CollateralPrefetcher;
+
 pub use qe_identity::{QeIdentity, SignedQeIdentity, SignedQeIdentityVerifier};
 pub use qe_report_body::{QeReportBody, QeReportBodyVerifier};
-pub use quote::Quote3Verifier;
+pub use quote::{
+    AuthenticationData, CertificationData, PckCertificateChain, Quote3, Quote3Bytes, Quote3Error,
+    Quote3Ref, Quote3Verifier, SignatureData,
+};
+pub use quote4::{
+    Quote4, Quote4Ref, Quote4SignatureData, QuoteError, QuoteField, QuoteHeader, TdReportBody,
+    VerifiedQuote, INTEL_QE_VENDOR_ID,
+};
+pub use quote5::{Quote5, Quote5Ref, QuoteBody};
+
+#[cfg(feature = "pcs")]
+pub use retry::RetryPolicy;
+#[cfg(feature = "std")]
+pub use reverification::{ReverificationTask, SessionState, StateChange};
 
 pub use report_body::{
-    AttributesVerifier, ConfigIdVerifier, ConfigSvnVerifier, CpuSvnVerifier,
-    ExtendedProductIdVerifier, FamilyIdVerifier, IsvProductIdVerifier, IsvSvnVerifier,
-    MiscellaneousSelectVerifier, MrEnclaveVerifier, MrSignerVerifier, ReportDataVerifier,
+    Attributes, AttributesVerifier, ConfigId, ConfigIdVerifier, ConfigSvn, ConfigSvnVerifier,
+    CpuSvn, CpuSvnVerifier, ExtendedProductId, ExtendedProductIdVerifier, FamilyId,
+    FamilyIdVerifier, IsvProductId, IsvProductIdVerifier, IsvSvn, IsvSvnVerifier, KssPolicy,
+    MiscellaneousSelect, MiscellaneousSelectVerifier, MrEnclave, MrEnclavePolicy,
+    MrEnclaveVerifier, MrSigner, MrSignerKeyVerifier, MrSignerPolicy, MrSignerVerifier,
+    RejectDebug, ReportBody, ReportBodyFnVerifier, ReportData, ReportDataFnVerifier,
+    ReportDataVerifier,
 };
 
-pub use tcb::{SignedTcbInfo, SignedTcbInfoVerifier, TcbInfo};
+pub use supplemental::{
+    SgxType, SupplementalData, PLATFORM_INSTANCE_ID_SIZE, ROOT_KEY_ID_SIZE,
+    SUPPLEMENTAL_DATA_VERSION,
+};
+pub use target_info::{TargetInfo, TargetInfoBuilder};
+pub use tcb::{
+    FmspcVerifier, SignedTcbInfo, SignedTcbInfoVerifier, Tcb, TcbComponent, TcbInfo, TcbLevel,
+    TcbStatus,
+};
+pub use td_policy::{TdPolicy, TdPolicyValue};
+pub use td_report::{
+    MrConfigId, MrOwner, MrOwnerConfig, MrTd, Rtmr, TdAttributes, TdReport, TdReportData, Xfam,
+};
+pub use tdx_module::{TdxModule, TdxModuleIdentity, TdxModuleTcbLevel, TdxModuleVerifier};
+pub use tee_type::TeeType;
+#[cfg(feature = "pcs")]
+pub use thim::{ThimClient, AZURE_INSTANCE_METADATA_THIM_URL, AZURE_THIM_URL, QE_ID_SIZE};
+pub use verify_quote::{verify_quote, QuotePolicy, QuoteVerificationResult};
 
 #[cfg(feature = "mbedtls")]
-pub use crate::mbedtls::{Error as MbedTlsError, MbedTlsCertificateChainVerifier, TrustAnchor};
+pub use crate::mbedtls::{
+    fetch_crls, CertificateCheck, CertificateReport, CertificateRevocationList,
+    ChainVerificationReport, Error as MbedTlsError, FailureReason, FailureReasons,
+    MbedTlsCertificateChainVerifier, PinnedTrustAnchor, Quote3PckCertChain, RevocationStatus,
+    SignatureScheme, TrustAnchor, UnverifiedCertChain, VerifiedCertChain,
+    VerifyError as MbedTlsVerifyError,
+};
 
 use crate::struct_name::SpacedStructName;
 use core::{
@@ -230,6 +370,17 @@ impl<V: Display, O: SpacedStructName + Display> VerificationMessage<O> for V {
 
 /// A verifier. These can composed using the [`Or`] and [`And`]
 /// types.
+///
+/// Each individual check is a verifier, so a policy can be assembled from
+/// only the checks an application needs, for example:
+/// - [`IssuerChainVerifier`] for the certificate chains
+/// - [`SignedTcbInfoVerifier`] and [`AdvisoriesVerifier`] for the TCB
+/// - [`MrEnclaveVerifier`] and [`MrSignerVerifier`] for the enclave identity
+/// - [`AttributesVerifier`] for the attributes
+/// - [`ReportDataVerifier`] for the report data
+///
+/// The [`EvidenceVerifier`] composes all of the checks needed to verify a
+/// quote.
 pub trait Verifier<E> {
     /// The value that was attempted to be verified.
     type Value;
@@ -485,7 +636,55 @@ impl<L, R> OrOutput<L, R> {
 ///
 /// This is will be a long operation. If the `left` side succeeds
 /// the `right` side will *still* be exercised.
-#[derive(Debug)]
+///
+/// Combined with [`And`] and [`Not`] this expresses policies like "the
+/// MRENCLAVE is one of an allowlist, or the MRSIGNER is trusted and the ISV
+/// SVN is at least 3":
+///
+/// ```
+/// use mc_attestation_verifier::{
+///     Accessor, And, IsvSvn, IsvSvnVerifier, MrEnclave, MrEnclavePolicy, MrSigner,
+///     MrSignerKeyVerifier, Or, Verifier,
+/// };
+///
+/// struct Enclave {
+///     mr_enclave: MrEnclave,
+///     mr_signer: MrSigner,
+///     isv_svn: IsvSvn,
+/// }
+/// # impl Accessor<MrEnclave> for Enclave {
+/// #     fn get(&self) -> MrEnclave {
+/// #         self.mr_enclave
+/// #     }
+/// # }
+/// # impl Accessor<MrSigner> for Enclave {
+/// #     fn get(&self) -> MrSigner {
+/// #         self.mr_signer
+/// #     }
+/// # }
+/// # impl Accessor<IsvSvn> for Enclave {
+/// #     fn get(&self) -> IsvSvn {
+/// #         self.isv_svn
+/// #     }
+/// # }
+///
+/// let trusted_signer = MrSigner::from([2; 32]);
+/// let policy = Or::new(
+///     MrEnclavePolicy::new([MrEnclave::from([1; 32])]),
+///     And::new(
+///         MrSignerKeyVerifier::new(trusted_signer),
+///         IsvSvnVerifier::new(IsvSvn::from(3)),
+///     ),
+/// );
+///
+/// let enclave = Enclave {
+///     mr_enclave: MrEnclave::from([9; 32]),
+///     mr_signer: trusted_signer,
+///     isv_svn: IsvSvn::from(4),
+/// };
+/// assert_eq!(policy.verify(&enclave).is_success().unwrap_u8(), 1);
+/// ```
+#[derive(Debug, Clone)]
 pub struct Or<L, R> {
     left: L,
     right: R,
@@ -558,7 +757,7 @@ impl<O> NotOutput<O> {
 }
 
 /// Negated due to `Not`
-#[derive(displaydoc::Display, Debug)]
+#[derive(displaydoc::Display, Debug, Clone)]
 pub struct Not<V> {
     verifier: V,
 }
@@ -568,6 +767,11 @@ impl<V> Not<V> {
     pub fn new(verifier: V) -> Self {
         Self { verifier }
     }
+
+    /// The verifier which is negated.
+    pub fn verifier(&self) -> &V {
+        &self.verifier
+    }
 }
 
 impl<E, V: Verifier<E>> Verifier<E> for Not<V> {
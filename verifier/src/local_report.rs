@@ -0,0 +1,226 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Reports for local attestation between enclaves on the same platform
+//!
+//! An enclave creates a report for a target enclave with `EREPORT`. The
+//! report is MACed with the target enclave's report key, so only the target
+//! enclave, which can derive the key with `EGETKEY`, can verify it. See
+//! section 2.16 of
+//! <https://www.intel.com/content/dam/develop/external/us/en/documents/sgx-developer-reference-linux-2-20.pdf>
+
+use crate::Error;
+use aes::Aes128;
+use cmac::{Cmac, Mac as _};
+use mc_sgx_core_sys_types::{sgx_key_id_t, sgx_report_t};
+use mc_sgx_core_types::{KeyId, Mac, Report, ReportBody};
+
+/// Size of a report body, the portion of the report which is MACed
+const REPORT_BODY_SIZE: usize = 384;
+
+/// Size of the key ID of a report
+const KEY_ID_SIZE: usize = 32;
+
+/// Size of the MAC of a report
+const MAC_SIZE: usize = 16;
+
+/// Size of a report, an `sgx_report_t`
+pub const REPORT_SIZE: usize = REPORT_BODY_SIZE + KEY_ID_SIZE + MAC_SIZE;
+
+/// Size of a report key
+pub const REPORT_KEY_SIZE: usize = 16;
+
+/// Provides the report key used to verify the MAC of a [`LocalReport`]
+///
+/// Within the target enclave this would call `sgx_get_key()` with
+/// `SGX_KEYSELECT_REPORT` and the report's key ID. A known key, like one
+/// provided by a test harness, can be used directly as a
+/// `[u8; REPORT_KEY_SIZE]`.
+pub trait ReportKeyProvider {
+    /// The report key for the report with `key_id`
+    ///
+    /// Returns `None` if the key can not be derived.
+    fn report_key(&self, key_id: &KeyId) -> Option<[u8; REPORT_KEY_SIZE]>;
+}
+
+impl ReportKeyProvider for [u8; REPORT_KEY_SIZE] {
+    fn report_key(&self, _key_id: &KeyId) -> Option<[u8; REPORT_KEY_SIZE]> {
+        Some(*self)
+    }
+}
+
+/// A report for local attestation, an `sgx_report_t`
+///
+/// Nothing in the report is trusted until [`LocalReport::verify()`]
+/// succeeds.
+///
+/// ```
+/// use mc_attestation_verifier::{LocalReport, REPORT_KEY_SIZE};
+///
+/// # fn report_key_of_this_enclave() -> [u8; REPORT_KEY_SIZE] { [0; REPORT_KEY_SIZE] }
+/// fn verify_peer(bytes: &[u8]) -> Result<(), mc_attestation_verifier::Error> {
+///     let report = LocalReport::try_from(bytes)?;
+///     let report_body = report.verify(&report_key_of_this_enclave())?;
+///     // Check the peer's identity with the verifiers for `ReportBody`
+///     Ok(())
+/// }
+///
+/// assert!(verify_peer(&[0; 10]).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalReport {
+    bytes: [u8; REPORT_SIZE],
+}
+
+impl LocalReport {
+    /// The body of the report
+    ///
+    /// This has *not* been verified, see [`LocalReport::verify()`].
+    pub fn body(&self) -> ReportBody {
+        ReportBody::try_from(&self.bytes[..REPORT_BODY_SIZE])
+            .expect("Report body should be the correct size")
+    }
+
+    /// The ID of the key used to MAC the report
+    pub fn key_id(&self) -> KeyId {
+        KeyId::try_from(&self.bytes[REPORT_BODY_SIZE..REPORT_BODY_SIZE + KEY_ID_SIZE])
+            .expect("Key ID should be the correct size")
+    }
+
+    /// The MAC of the report body
+    pub fn mac(&self) -> Mac {
+        Mac::try_from(&self.bytes[REPORT_BODY_SIZE + KEY_ID_SIZE..])
+            .expect("MAC should be the correct size")
+    }
+
+    /// Verify the MAC of the report, returning the report body on success
+    ///
+    /// The MAC is an AES-128-CMAC of the report body with the report key of
+    /// the target enclave. The comparison is constant time.
+    ///
+    /// # Errors
+    /// * `Error::ReportKeyUnavailable` if `keys` can not provide the report
+    ///   key.
+    /// * `Error::LocalReportMac` if the MAC does not match the report body.
+    pub fn verify(&self, keys: &impl ReportKeyProvider) -> Result<ReportBody, Error> {
+        let key = keys
+            .report_key(&self.key_id())
+            .ok_or(Error::ReportKeyUnavailable)?;
+        let mut cmac =
+            <Cmac<Aes128>>::new_from_slice(&key).expect("Report key should be a valid AES-128 key");
+        cmac.update(&self.bytes[..REPORT_BODY_SIZE]);
+        cmac.verify_slice(self.mac().as_ref())
+            .map_err(|_| Error::LocalReportMac)?;
+        Ok(self.body())
+    }
+}
+
+impl TryFrom<&[u8]> for LocalReport {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| Error::LocalReportSize(bytes.len()))?;
+        Ok(Self { bytes })
+    }
+}
+
+impl From<&LocalReport> for Report {
+    fn from(report: &LocalReport) -> Self {
+        let mut key_id = [0; KEY_ID_SIZE];
+        key_id.copy_from_slice(report.key_id().as_ref());
+        let mut mac = [0; MAC_SIZE];
+        mac.copy_from_slice(report.mac().as_ref());
+        Report::from(sgx_report_t {
+            body: report.body().into(),
+            key_id: sgx_key_id_t { id: key_id },
+            mac,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec::Vec;
+    use assert_matches::assert_matches;
+
+    const REPORT_KEY: [u8; REPORT_KEY_SIZE] = [
+        0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f,
+        0x3c,
+    ];
+
+    fn report_bytes(key: &[u8; REPORT_KEY_SIZE]) -> Vec<u8> {
+        let mut bytes = (0..REPORT_BODY_SIZE + KEY_ID_SIZE)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        let mut cmac = <Cmac<Aes128>>::new_from_slice(key).expect("Invalid key");
+        cmac.update(&bytes[..REPORT_BODY_SIZE]);
+        bytes.extend_from_slice(&cmac.finalize().into_bytes());
+        bytes
+    }
+
+    struct NoKey;
+
+    impl ReportKeyProvider for NoKey {
+        fn report_key(&self, _key_id: &KeyId) -> Option<[u8; REPORT_KEY_SIZE]> {
+            None
+        }
+    }
+
+    #[test]
+    fn verify_local_report() {
+        let bytes = report_bytes(&REPORT_KEY);
+        let report = LocalReport::try_from(bytes.as_slice()).expect("Failed to parse report");
+
+        let report_body = report.verify(&REPORT_KEY).expect("Failed to verify report");
+        assert_eq!(
+            report_body,
+            ReportBody::try_from(&bytes[..REPORT_BODY_SIZE]).expect("Invalid report body")
+        );
+    }
+
+    #[test]
+    fn local_report_fields() {
+        let bytes = report_bytes(&REPORT_KEY);
+        let report = LocalReport::try_from(bytes.as_slice()).expect("Failed to parse report");
+
+        assert_eq!(report.key_id().as_ref(), &bytes[384..416]);
+        assert_eq!(report.mac().as_ref(), &bytes[416..]);
+    }
+
+    #[test]
+    fn local_report_with_modified_body_fails() {
+        let mut bytes = report_bytes(&REPORT_KEY);
+        bytes[0] ^= 1;
+        let report = LocalReport::try_from(bytes.as_slice()).expect("Failed to parse report");
+
+        assert_eq!(report.verify(&REPORT_KEY), Err(Error::LocalReportMac));
+    }
+
+    #[test]
+    fn local_report_with_wrong_key_fails() {
+        let bytes = report_bytes(&[1; REPORT_KEY_SIZE]);
+        let report = LocalReport::try_from(bytes.as_slice()).expect("Failed to parse report");
+
+        assert_eq!(report.verify(&REPORT_KEY), Err(Error::LocalReportMac));
+    }
+
+    #[test]
+    fn local_report_without_key_fails() {
+        let bytes = report_bytes(&REPORT_KEY);
+        let report = LocalReport::try_from(bytes.as_slice()).expect("Failed to parse report");
+
+        assert_eq!(report.verify(&NoKey), Err(Error::ReportKeyUnavailable));
+    }
+
+    #[test]
+    fn local_report_wrong_size() {
+        let bytes = report_bytes(&REPORT_KEY);
+
+        assert_matches!(
+            LocalReport::try_from(&bytes[1..]),
+            Err(Error::LocalReportSize(431))
+        );
+    }
+}
@@ -3,29 +3,167 @@
 //! Implementation of certificate chain verification using MbedTls.
 
 extern crate alloc;
-use alloc::{string::String, vec, vec::Vec};
-use core::fmt::{Debug, Formatter};
-use der::{DateTime, Encode};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use core::{
+    borrow::BorrowMut,
+    fmt::{Debug, Display, Formatter},
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+use der::{
+    asn1::Uint,
+    oid::{
+        db::rfc5912::{
+            ECDSA_WITH_SHA_256, ECDSA_WITH_SHA_384, ECDSA_WITH_SHA_512,
+            SHA_256_WITH_RSA_ENCRYPTION, SHA_384_WITH_RSA_ENCRYPTION, SHA_512_WITH_RSA_ENCRYPTION,
+        },
+        AssociatedOid, ObjectIdentifier,
+    },
+    DateTime, Decode, Encode,
+};
 
+pub use ::mbedtls::x509::VerifyError;
 use ::mbedtls::{
     alloc::List as MbedtlsList,
-    hash::Type as HashType,
-    pk::{EcGroupId, Type as PkType},
+    hash::{Md, Type as HashType},
+    pk::{EcGroupId, Pk, Type as PkType},
     x509::{Certificate as MbedTlsCertificate, Crl, Profile},
 };
 
-use crate::{CertificateChainVerifier, CertificateChainVerifierError};
-use x509_cert::{crl::CertificateList, Certificate};
+use base64ct::{Base64, Encoding};
+use p256::ecdsa::{signature::Verifier as _, Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+
+#[cfg(doc)]
+use crate::Quote4SignatureData;
+use crate::{
+    cert_chain_ref, certificate_chain::distribution_point_uris, crl_distribution_points, pkcs7,
+    CertChainRef, CertificateChainVerifier, CertificateChainVerifierError, CertificationData,
+    ChainPolicy, LeafMatcher, Quote3, VerificationOptions, INTEL_SGX_ROOT_CA_PEM,
+    INTEL_SGX_ROOT_CA_SPKI_SHA256,
+};
+use x509_cert::{
+    crl::CertificateList,
+    ext::pkix::{BaseCrlNumber, CrlNumber, CrlReason, FreshestCrl, KeyUsage},
+    name::Name,
+    serial_number::SerialNumber,
+    spki::SubjectPublicKeyInfoRef,
+    Certificate,
+};
 
 pub type Result<T> = core::result::Result<T, Error>;
 
+/// A callback for each certificate of the path being verified, see
+/// [`UnverifiedCertChain::verify_with()`].
+type VerifyCallback = Box<dyn Fn(&Certificate, usize, &mut VerifyError) + Send + Sync>;
+
 /// Error type for decoding and verifying certificates.
 #[derive(Debug, displaydoc::Display, PartialEq, Eq)]
 pub enum Error {
     /// An error occurred working with MbedTls: {0}
     MbedTls(mbedtls::Error),
+    /// The certificate chain failed verification: {0}
+    CertificateVerification(FailureReasons),
     /// An error occurred encoding to DER: {0}
     Der(der::Error),
+    /// An error occurred decoding base64: {0}
+    Base64(base64ct::Error),
+    /// A certificate in the chain was revoked by a delta CRL
+    CertificateRevoked,
+    /// A delta CRL did not have a corresponding base CRL
+    DeltaCrlWithoutBase,
+    /// The signature of a delta CRL could not be verified
+    DeltaCrlSignature,
+    /// A delta CRL has expired, it is past its next update
+    DeltaCrlExpired,
+    /// A delta CRL is not yet valid, it is before its this update
+    DeltaCrlNotYetValid,
+    /// The public key is not supported for the operation
+    UnsupportedPublicKey,
+    /// The signature could not be verified
+    SignatureVerification,
+    /// The trust anchor's public key does not match the pin
+    TrustAnchorPinMismatch,
+    /// Failed to fetch the CRL from {uri}: {error}
+    #[allow(missing_docs)]
+    CrlFetch { uri: String, error: String },
+    /// The certificate "{subject}" is in the chain more than once
+    #[allow(missing_docs)]
+    DuplicateCertificate { subject: String },
+    /// The issuer "{issuer}" of the certificate "{subject}" is not in the chain
+    #[allow(missing_docs)]
+    CertificateChainGap { subject: String, issuer: String },
+    /// The certificate "{subject}" is not part of the certificate path
+    #[allow(missing_docs)]
+    CertificateNotInPath { subject: String },
+    /// The certificate chain has {depth} certificates, more than the maximum of {max_depth}
+    #[allow(missing_docs)]
+    ChainTooDeep { depth: usize, max_depth: usize },
+    /// No trust anchor was provided to verify the certificate chain
+    NoTrustAnchor,
+    /// The leaf certificate does not match the expected names
+    LeafMismatch,
+    /// The leaf certificate does not assert the required key usage
+    LeafKeyUsage,
+    /// The self-signed certificate "{subject}" in the chain is not the trust anchor
+    #[allow(missing_docs)]
+    EmbeddedRootMismatch { subject: String },
+    /// Failed to load the system root certificates: {0}
+    SystemRoots(String),
+    /// No PEM encoded "{label}" was found
+    #[allow(missing_docs)]
+    MissingPem { label: String },
+    /// The QE report was not signed by the leaf certificate
+    QeReportSignature,
+    /// The quote certification data does not embed the PCK certificate chain
+    MissingPckCertChain,
+    /// Failed to fetch {url}: {error}
+    #[allow(missing_docs)]
+    PcsFetch { url: String, error: String },
+    /// Invalid URL encoding
+    UrlEncoding,
+    /// The fetched collateral is not valid: {0}
+    Collateral(String),
+    /// The service is unavailable to fetch {url}, HTTP status {status}
+    #[allow(missing_docs)]
+    PcsUnavailable { url: String, status: u16 },
+    /// The service rejected the request for {url}, HTTP status {status}
+    #[allow(missing_docs)]
+    PcsRequest { url: String, status: u16 },
+    /// The response from {url} has no valid {header} header
+    #[allow(missing_docs)]
+    MissingIssuerChain { url: String, header: String },
+}
+
+impl Error {
+    /// Whether the error is transient, a request which failed with it may
+    /// succeed when retried
+    ///
+    /// Only failures of the HTTP request itself, and statuses for an
+    /// unavailable service, are transient. A response which is missing its
+    /// issuer chain is not.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Error::PcsFetch { .. } | Error::PcsUnavailable { .. })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::MbedTls(error) => Some(error),
+            Error::Der(error) => Some(error),
+            Error::Base64(error) => Some(error),
+            _ => None,
+        }
+    }
 }
 
 impl From<mbedtls::Error> for Error {
@@ -40,14 +178,34 @@ impl From<der::Error> for Error {
     }
 }
 
+impl From<base64ct::Error> for Error {
+    fn from(src: base64ct::Error) -> Self {
+        Error::Base64(src)
+    }
+}
+
+impl From<crate::Error> for Error {
+    fn from(src: crate::Error) -> Self {
+        Error::Collateral(src.to_string())
+    }
+}
+
 impl From<Error> for CertificateChainVerifierError {
     fn from(error: Error) -> Self {
         match error {
             // Any error (expired, revoked, etc) in certificate chain verification comes back as
             // `X509CertVerifyFailed`
-            Error::MbedTls(mbedtls::Error::X509CertVerifyFailed) => {
+            Error::MbedTls(mbedtls::Error::X509CertVerifyFailed)
+            | Error::CertificateVerification(_) => {
+                CertificateChainVerifierError::SignatureVerification
+            }
+            Error::CertificateRevoked => CertificateChainVerifierError::CertificateRevoked,
+            Error::LeafMismatch => CertificateChainVerifierError::LeafMismatch,
+            Error::DeltaCrlSignature | Error::SignatureVerification => {
                 CertificateChainVerifierError::SignatureVerification
             }
+            Error::DeltaCrlExpired => CertificateChainVerifierError::CrlExpired,
+            Error::DeltaCrlNotYetValid => CertificateChainVerifierError::CrlNotYetValid,
             _ => CertificateChainVerifierError::GeneralCertificateError,
         }
     }
@@ -57,28 +215,110 @@ impl From<Error> for CertificateChainVerifierError {
 #[derive(Debug)]
 pub struct MbedTlsCertificateChainVerifier {
     trust_anchor: TrustAnchor,
+    policy: ChainPolicy,
+    options: Option<VerificationOptions>,
+    max_chain_depth: Option<usize>,
 }
 
 impl MbedTlsCertificateChainVerifier {
     /// Create a new instance
-    pub fn new(trust_anchor: TrustAnchor) -> Self {
-        Self { trust_anchor }
+    ///
+    /// A [`PinnedTrustAnchor`] can be used to further restrict the trust
+    /// anchor to a known public key.
+    pub fn new(trust_anchor: impl Into<TrustAnchor>) -> Self {
+        Self {
+            trust_anchor: trust_anchor.into(),
+            policy: ChainPolicy::default(),
+            options: None,
+            max_chain_depth: None,
+        }
+    }
+
+    /// Enforce the `policy` on the certificate chains, after they have been
+    /// verified.
+    ///
+    /// MbedTls does not support the name constraints extension, it rejects
+    /// certificates where it's marked critical. So name constraints can only
+    /// be enforced for CAs which mark the extension non-critical.
+    pub fn with_policy(mut self, policy: ChainPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Check the validity periods of the certificates and CRLs at the
+    /// verification time, with the tolerances of `options`.
+    ///
+    /// MbedTls is commonly built without time support, in which case it
+    /// ignores the validity periods. The check is skipped when no time is
+    /// provided.
+    pub fn with_options(mut self, options: VerificationOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    /// Reject certificate chains with more than `max_chain_depth`
+    /// certificates, before they are parsed by MbedTls.
+    ///
+    /// See [`UnverifiedCertChain::with_max_depth()`].
+    pub fn with_max_chain_depth(mut self, max_chain_depth: usize) -> Self {
+        self.max_chain_depth = Some(max_chain_depth);
+        self
     }
 }
 
 impl CertificateChainVerifier for MbedTlsCertificateChainVerifier {
-    // Note: `_time` is ignored because MbedTls will either, call out to a system timer or ignore
+    // Note: MbedTls does not use `time`, it will either, call out to a system timer or ignore
     // time checks depending on how it's built. The common build we use, ignores time checks.
+    // `time` is only used for the validity checks enabled with `with_options()`, and otherwise for
+    // the delta CRLs, which MbedTls never sees.
     fn verify_certificate_chain<'a, 'b>(
         &self,
         certificate_chain: impl IntoIterator<Item = &'a Certificate>,
         crls: impl IntoIterator<Item = &'b CertificateList>,
-        _time: impl Into<Option<DateTime>>,
+        time: impl Into<Option<DateTime>>,
     ) -> core::result::Result<(), CertificateChainVerifierError> {
+        let certificate_chain = certificate_chain.into_iter().collect::<Vec<_>>();
+        if let Some(max_depth) = self.max_chain_depth {
+            if certificate_chain.len() > max_depth {
+                return Err(Error::ChainTooDeep {
+                    depth: certificate_chain.len(),
+                    max_depth,
+                }
+                .into());
+            }
+        }
+        let crls = crls.into_iter().collect::<Vec<_>>();
+        let time = time.into();
+        if let (Some(options), Some(time)) = (&self.options, time) {
+            options.check_validity(
+                certificate_chain.iter().copied(),
+                crls.iter().copied(),
+                time,
+            )?;
+        }
+        // MbedTls fails to parse certificates with unknown critical
+        // extensions, so check first to report which extension it is
+        self.policy
+            .check_critical_extensions(certificate_chain.iter().copied())?;
         let unverified = UnverifiedCertChain::try_from_certificates(certificate_chain)
             .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?;
+        let unverified = match (&self.options, time) {
+            // The options already checked the validity of every CRL,
+            // including the delta CRLs, with their clock skew
+            (None, Some(time)) => unverified.with_time(time),
+            _ => unverified,
+        };
         let crls = CertificateRevocationList::try_from_crls(crls)?;
-        Ok(unverified.verify(&self.trust_anchor, crls)?)
+        if self.policy.is_empty() {
+            unverified.verify(&self.trust_anchor, crls)?;
+        } else {
+            // The policy needs the chain ordered from the leaf
+            let ordered = unverified.into_ordered()?;
+            let certificates = ordered.certificates()?;
+            ordered.verify(&self.trust_anchor, crls)?;
+            self.policy.check(&certificates)?;
+        }
+        Ok(())
     }
 }
 
@@ -92,6 +332,28 @@ impl Debug for TrustAnchor {
     }
 }
 
+impl Display for TrustAnchor {
+    /// The subject of the anchor's certificate and its SHA-256 fingerprint
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match Certificate::from_der(self.der()) {
+            Ok(cert) => write!(f, "{}", cert.tbs_certificate.subject)?,
+            Err(_) => write!(f, "<undecodable certificate>")?,
+        }
+        write!(f, " (SHA-256: {})", hex::encode(self.sha256_fingerprint()))
+    }
+}
+
+impl PartialEq for TrustAnchor {
+    fn eq(&self, other: &Self) -> bool {
+        self.0
+            .iter()
+            .map(|cert| cert.as_der())
+            .eq(other.0.iter().map(|cert| cert.as_der()))
+    }
+}
+
+impl Eq for TrustAnchor {}
+
 impl TrustAnchor {
     /// Try to get a trust anchor from a PEM encoded string.
     ///
@@ -108,6 +370,15 @@ impl TrustAnchor {
         Ok(Self(certs))
     }
 
+    /// The Intel SGX Root CA, [`INTEL_SGX_ROOT_CA_PEM`].
+    ///
+    /// Prefer this to a copy of the certificate. The returned anchor is
+    /// checked against [`INTEL_SGX_ROOT_CA_SPKI_SHA256`], see
+    /// [`PinnedTrustAnchor::intel_sgx_root()`] to keep the pin.
+    pub fn intel_sgx_root() -> Self {
+        PinnedTrustAnchor::intel_sgx_root().into()
+    }
+
     /// Try to get a trust anchor from DER encoded bytes.
     ///
     /// # Errors
@@ -118,6 +389,130 @@ impl TrustAnchor {
         certs.push(cert);
         Ok(Self(certs))
     }
+
+    /// Try to get a trust anchor from a base64 encoded DER certificate,
+    /// without PEM armor.
+    ///
+    /// # Errors
+    /// `Error::Base64` if the string is not valid base64.
+    /// `Error::MbedTls` if the bytes are not a valid DER certificate.
+    pub fn try_from_base64(base64: &str) -> Result<Self> {
+        Self::try_from_der(decode_base64(base64)?)
+    }
+
+    /// Load the trust anchors from the operating system's root certificate
+    /// store.
+    ///
+    /// This is for attestation PKIs anchored in the platform trust store, like
+    /// a private CA hierarchy for TPM endorsement key certificates. DCAP
+    /// collateral should be verified against
+    /// [`TrustAnchor::intel_sgx_root()`] instead. Certificates MbedTls can not
+    /// parse are skipped.
+    ///
+    /// # Errors
+    /// `Error::SystemRoots` if the store can not be read, or has no
+    /// certificate MbedTls can parse.
+    #[cfg(feature = "native-roots")]
+    pub fn from_system_roots() -> Result<Self> {
+        let roots = rustls_native_certs::load_native_certs()
+            .map_err(|error| Error::SystemRoots(error.to_string()))?;
+        let mut certs = MbedtlsList::<MbedTlsCertificate>::new();
+        for root in roots {
+            if let Ok(cert) = MbedTlsCertificate::from_der(root.as_ref()) {
+                certs.push(cert);
+            }
+        }
+        if certs.is_empty() {
+            return Err(Error::SystemRoots(String::from(
+                "no usable root certificate",
+            )));
+        }
+        Ok(Self(certs))
+    }
+
+    /// The SHA-256 fingerprint of the anchor's certificate, the hash of its
+    /// DER encoding. For an anchor with several certificates, like the
+    /// system roots, it's the fingerprint of the first one.
+    ///
+    /// This is the fingerprint shown by most tools, like
+    /// `openssl x509 -fingerprint -sha256`, to identify which root was used.
+    pub fn sha256_fingerprint(&self) -> [u8; 32] {
+        Sha256::digest(self.der()).into()
+    }
+
+    /// The DER encoding of the anchor's certificate
+    fn der(&self) -> &[u8] {
+        self.0.iter().next().map_or(&[], |cert| cert.as_der())
+    }
+}
+
+/// A trust anchor pinned to the SHA-256 hash of its SubjectPublicKeyInfo.
+///
+/// In addition to the normal path validation, only the certificates of the
+/// trust anchor whose public key matches the pin are trusted. This protects
+/// against a misconfigured trust store when the root key is known a priori,
+/// like the Intel SGX root CA.
+#[derive(Clone, Debug)]
+pub struct PinnedTrustAnchor {
+    trust_anchor: TrustAnchor,
+    pin: [u8; 32],
+}
+
+impl PinnedTrustAnchor {
+    /// Create a new instance
+    ///
+    /// # Arguments
+    /// * `trust_anchor` - The trust anchor to pin.
+    /// * `pin` - The SHA-256 hash of the DER encoded SubjectPublicKeyInfo of
+    ///   the root certificate.
+    ///
+    /// # Errors
+    /// `Error::TrustAnchorPinMismatch` if no certificate in `trust_anchor`
+    /// matches the `pin`.
+    /// `Error::Der` if a certificate in `trust_anchor` can not be decoded.
+    pub fn try_new(trust_anchor: TrustAnchor, pin: [u8; 32]) -> Result<Self> {
+        let mut certs = MbedtlsList::<MbedTlsCertificate>::new();
+        for cert in trust_anchor.0.iter() {
+            if spki_sha256(cert.as_der())? == pin {
+                certs.push(MbedTlsCertificate::from_der(cert.as_der())?);
+            }
+        }
+        if certs.iter().next().is_none() {
+            return Err(Error::TrustAnchorPinMismatch);
+        }
+        Ok(Self {
+            trust_anchor: TrustAnchor(certs),
+            pin,
+        })
+    }
+
+    /// The Intel SGX Root CA pinned to [`INTEL_SGX_ROOT_CA_SPKI_SHA256`]
+    pub fn intel_sgx_root() -> Self {
+        let trust_anchor = TrustAnchor::try_from_pem(INTEL_SGX_ROOT_CA_PEM)
+            .expect("The Intel SGX Root CA should be a valid certificate");
+        Self::try_new(trust_anchor, INTEL_SGX_ROOT_CA_SPKI_SHA256)
+            .expect("The Intel SGX Root CA should match its pin")
+    }
+
+    /// The SHA-256 hash of the pinned SubjectPublicKeyInfo
+    pub fn pin(&self) -> &[u8; 32] {
+        &self.pin
+    }
+}
+
+impl From<PinnedTrustAnchor> for TrustAnchor {
+    fn from(pinned: PinnedTrustAnchor) -> Self {
+        pinned.trust_anchor
+    }
+}
+
+/// The SHA-256 hash of the SubjectPublicKeyInfo of the DER encoded `cert`
+fn spki_sha256(cert: &[u8]) -> Result<[u8; 32]> {
+    let cert = Certificate::from_der(cert)?;
+    let spki = cert.tbs_certificate.subject_public_key_info.to_der()?;
+    let mut hash = [0u8; 32];
+    Md::hash(HashType::Sha256, &spki, &mut hash)?;
+    Ok(hash)
 }
 
 /// An unverified certificate chain.
@@ -125,7 +520,11 @@ impl TrustAnchor {
 /// This is mostly opaque meant to be used to verify and create a
 /// [`VerifiedCertChain`].
 #[derive(Clone)]
-struct UnverifiedCertChain(MbedtlsList<MbedTlsCertificate>);
+pub struct UnverifiedCertChain {
+    certs: MbedtlsList<MbedTlsCertificate>,
+    max_depth: Option<usize>,
+    time: Option<DateTime>,
+}
 
 impl Debug for UnverifiedCertChain {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
@@ -136,9 +535,209 @@ impl Debug for UnverifiedCertChain {
 impl UnverifiedCertChain {
     /// Verify the certificate chain is valid for the given `trust_anchor`.
     ///
+    /// The `crl` can be passed as `&mut CertificateRevocationList` to reuse
+    /// it for other verifications.
+    ///
+    /// # Errors
+    /// `Error::CertificateVerification` with the reasons if the certificate
+    /// chain is not valid.
+    /// `Error::MbedTls` if MbedTls fails otherwise.
+    /// `Error::CertificateRevoked` if a certificate is revoked by a delta CRL.
+    /// `Error::DeltaCrlSignature` if a delta CRL signature is invalid.
+    /// `Error::DeltaCrlExpired` or `Error::DeltaCrlNotYetValid` if a delta
+    /// CRL is not valid at the time set with
+    /// [`UnverifiedCertChain::with_time()`].
+    /// `Error::ChainTooDeep` if the chain has more certificates than allowed
+    /// by [`UnverifiedCertChain::with_max_depth()`].
+    pub fn verify(
+        self,
+        trust_anchor: &TrustAnchor,
+        mut crl: impl BorrowMut<CertificateRevocationList>,
+    ) -> Result<VerifiedCertChain> {
+        self.check_path(trust_anchor, crl.borrow_mut(), None, None)?;
+        Ok(VerifiedCertChain(self.certs))
+    }
+
+    /// Check the certificate chain is valid for the given `trust_anchor`,
+    /// like [`UnverifiedCertChain::verify()`], without consuming the chain.
+    ///
+    /// This allows trying other trust anchors without copying the chain, for
+    /// instance both the old and the new root during a root rotation. See
+    /// [`UnverifiedCertChain::verify_with_any()`] to get the
+    /// [`VerifiedCertChain`].
+    ///
+    /// # Errors
+    /// The same errors as [`UnverifiedCertChain::verify()`].
+    pub fn check(
+        &self,
+        trust_anchor: &TrustAnchor,
+        mut crl: impl BorrowMut<CertificateRevocationList>,
+    ) -> Result<()> {
+        self.check_path(trust_anchor, crl.borrow_mut(), None, None)
+    }
+
+    /// Verify the certificate chain against each of the `trust_anchors`, in
+    /// order, until it is valid for one of them.
+    ///
+    /// # Errors
+    /// The error of the last trust anchor tried, see
+    /// [`UnverifiedCertChain::verify()`].
+    /// `Error::NoTrustAnchor` if `trust_anchors` is empty.
+    pub fn verify_with_any<'a>(
+        self,
+        trust_anchors: impl IntoIterator<Item = &'a TrustAnchor>,
+        mut crl: impl BorrowMut<CertificateRevocationList>,
+    ) -> Result<VerifiedCertChain> {
+        let crl = crl.borrow_mut();
+        let mut result = Err(Error::NoTrustAnchor);
+        for trust_anchor in trust_anchors {
+            result = self.check_path(trust_anchor, crl, None, None);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result.map(|_| VerifiedCertChain(self.certs))
+    }
+
+    /// Verify the certificate chain, like [`UnverifiedCertChain::verify()`],
+    /// with a `callback` adding bespoke constraints.
+    ///
+    /// The `callback` is invoked by MbedTls for each certificate of the path,
+    /// with the certificate, its depth, and the verification flags MbedTls
+    /// set for it. The depth of the leaf is 0 and the trust anchor has the
+    /// greatest depth. Inserting any flag, like [`VerifyError::CERT_OTHER`],
+    /// fails the verification, while removing flags accepts what MbedTls
+    /// rejected, so the latter should be done with care.
+    ///
+    /// ```no_run
+    /// # use mc_attestation_verifier::{CertificateRevocationList, MbedTlsVerifyError, TrustAnchor, UnverifiedCertChain};
+    /// # fn verify(chain: UnverifiedCertChain, trust_anchor: TrustAnchor, crl: CertificateRevocationList) {
+    /// // Reject certificates issued before 2020
+    /// let cutoff = der::DateTime::new(2020, 1, 1, 0, 0, 0).unwrap();
+    /// let verified = chain.verify_with(&trust_anchor, crl, move |cert, _depth, flags| {
+    ///     if cert.tbs_certificate.validity.not_before.to_date_time() < cutoff {
+    ///         flags.insert(MbedTlsVerifyError::CERT_OTHER);
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// The same errors as [`UnverifiedCertChain::verify()`].
+    /// `Error::MbedTls` if a certificate can not be decoded for the
+    /// `callback`.
+    pub fn verify_with<F>(
+        self,
+        trust_anchor: &TrustAnchor,
+        mut crl: impl BorrowMut<CertificateRevocationList>,
+        callback: F,
+    ) -> Result<VerifiedCertChain>
+    where
+        F: Fn(&Certificate, usize, &mut VerifyError) + Send + Sync + 'static,
+    {
+        self.check_path(
+            trust_anchor,
+            crl.borrow_mut(),
+            None,
+            Some(Box::new(callback)),
+        )?;
+        Ok(VerifiedCertChain(self.certs))
+    }
+
+    /// Limit the number of certificates in the chain, including the leaf, to
+    /// `max_depth`.
+    ///
+    /// The limit is checked before any other work is done by
+    /// [`UnverifiedCertChain::verify()`], [`UnverifiedCertChain::verify_with_report()`]
+    /// and [`UnverifiedCertChain::into_ordered()`]. It bounds the work spent
+    /// on a chain from an untrusted source, independent of the path length
+    /// constraints in the CA certificates, which are only known once the
+    /// chain has been processed.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// The number of certificates in the chain
+    pub fn len(&self) -> usize {
+        self.certs.iter().count()
+    }
+
+    /// Whether the chain has no certificates
+    pub fn is_empty(&self) -> bool {
+        self.certs.iter().next().is_none()
+    }
+
+    fn check_depth(&self) -> Result<()> {
+        match self.max_depth {
+            Some(max_depth) if self.len() > max_depth => Err(Error::ChainTooDeep {
+                depth: self.len(),
+                max_depth,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Verify the certificate chain, like [`UnverifiedCertChain::verify()`],
+    /// and report what was checked for each certificate.
+    ///
+    /// The report is provided whether or not the verification succeeds, so it
+    /// can be used to audit both outcomes. The validity window of each
+    /// certificate is reported, but it is only checked when MbedTls is built
+    /// with time support.
+    ///
     /// # Errors
-    /// `Error::MbedTls` if the certificate chain is not valid.
-    fn verify(self, trust_anchor: &TrustAnchor, mut crl: CertificateRevocationList) -> Result<()> {
+    /// The same errors as [`UnverifiedCertChain::verify()`].
+    /// `Error::Der` if a certificate can not be decoded for the report.
+    pub fn verify_with_report(
+        self,
+        trust_anchor: &TrustAnchor,
+        mut crl: impl BorrowMut<CertificateRevocationList>,
+    ) -> (Result<VerifiedCertChain>, ChainVerificationReport) {
+        let crl = crl.borrow_mut();
+        let certificates = match self
+            .check_depth()
+            .and_then(|_| self.certificate_reports(crl))
+        {
+            Ok(certificates) => certificates,
+            Err(error) => {
+                let report = ChainVerificationReport {
+                    certificates: Vec::new(),
+                    failure: Some(error.to_string()),
+                };
+                return (Err(error), report);
+            }
+        };
+
+        let mut details = String::new();
+        let result = self
+            .check_path(trust_anchor, crl, Some(&mut details), None)
+            .map(|_| VerifiedCertChain(self.certs));
+        let failure = result.as_ref().err().map(|error| {
+            let details = details.trim();
+            if details.is_empty() {
+                error.to_string()
+            } else {
+                alloc::format!("{error}: {details}")
+            }
+        });
+        (
+            result,
+            ChainVerificationReport {
+                certificates,
+                failure,
+            },
+        )
+    }
+
+    fn check_path(
+        &self,
+        trust_anchor: &TrustAnchor,
+        crl: &mut CertificateRevocationList,
+        details: Option<&mut String>,
+        callback: Option<VerifyCallback>,
+    ) -> Result<()> {
+        self.check_depth()?;
         let profile = Profile::new(
             vec![HashType::Sha256, HashType::Sha384, HashType::Sha512],
             // The note on `PkType::Ecdsa` is a lie:
@@ -156,13 +755,196 @@ impl UnverifiedCertChain {
             ],
             2048,
         );
-        Ok(MbedTlsCertificate::verify_with_profile(
-            &self.0,
+        // MbedTls only reports the flags of each certificate to the callback
+        let reasons = Arc::new(AtomicU32::new(0));
+        let path_reasons = reasons.clone();
+        let result = MbedTlsCertificate::verify_with_callback_and_profile(
+            &self.certs,
             &trust_anchor.0,
-            Some(&mut crl.0),
+            Some(&mut crl.crl),
             Some(&profile),
-            None,
-        )?)
+            details,
+            move |cert: &MbedTlsCertificate, depth: i32, flags: &mut VerifyError| {
+                if let Some(callback) = &callback {
+                    let cert = Certificate::from_der(cert.as_der())
+                        .map_err(|_| mbedtls::Error::X509InvalidFormat)?;
+                    callback(&cert, depth as usize, flags);
+                }
+                path_reasons.fetch_or(flags.bits(), Ordering::Relaxed);
+                Ok(())
+            },
+        );
+        match result {
+            Err(mbedtls::Error::X509CertVerifyFailed) => {
+                let flags = VerifyError::from_bits_truncate(reasons.load(Ordering::Relaxed));
+                return Err(if flags.is_empty() {
+                    Error::MbedTls(mbedtls::Error::X509CertVerifyFailed)
+                } else {
+                    Error::CertificateVerification(flags.into())
+                });
+            }
+            result => result?,
+        }
+        crl.check_deltas(&self.certs, trust_anchor, self.time)
+    }
+
+    /// Check the delta CRLs are valid at the verification `time`.
+    ///
+    /// MbedTls checks the `thisUpdate` and `nextUpdate` of the base CRLs,
+    /// when it is built with time support, but it never sees the delta CRLs.
+    /// Without a time the validity of the delta CRLs is not checked, and an
+    /// outdated delta CRL could hide newer revocations.
+    pub fn with_time(mut self, time: DateTime) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    fn certificate_reports(
+        &self,
+        crl: &CertificateRevocationList,
+    ) -> Result<Vec<CertificateReport>> {
+        Ok(self
+            .certificates()?
+            .iter()
+            .map(|cert| CertificateReport::new(cert, crl))
+            .collect())
+    }
+
+    fn certificates(&self) -> Result<Vec<Certificate>> {
+        Ok(self
+            .certs
+            .iter()
+            .map(|cert| Certificate::from_der(cert.as_der()))
+            .collect::<core::result::Result<Vec<_>, _>>()?)
+    }
+
+    /// Order the certificate chain from the leaf up to the root.
+    ///
+    /// The path is built by matching each certificate's issuer to the subject
+    /// of another certificate in the chain. The leaf is the certificate which
+    /// did not issue any of the others. The path ends at a self-signed
+    /// certificate or at a certificate whose issuer is not in the chain, which
+    /// is expected to be in the trust anchor.
+    ///
+    /// # Errors
+    /// `Error::Der` if a certificate can not be decoded.
+    /// `Error::DuplicateCertificate` if a certificate is in the chain more
+    /// than once.
+    /// `Error::CertificateChainGap` if the issuer of a certificate in the path
+    /// is missing while other certificates remain.
+    /// `Error::CertificateNotInPath` if a certificate is not part of the path
+    /// from the leaf.
+    /// `Error::ChainTooDeep` if the chain has more certificates than allowed
+    /// by [`UnverifiedCertChain::with_max_depth()`].
+    pub fn into_ordered(self) -> Result<Self> {
+        self.check_depth()?;
+        let certs = self.certificates()?;
+
+        for (index, cert) in certs.iter().enumerate() {
+            if certs[..index].contains(cert) {
+                return Err(Error::DuplicateCertificate {
+                    subject: subject(cert),
+                });
+            }
+        }
+
+        let issues = |issuer: &Certificate, cert: &Certificate| {
+            !is_self_signed(cert) && issuer.tbs_certificate.subject == cert.tbs_certificate.issuer
+        };
+        let is_leaf = |cert: &Certificate| !certs.iter().any(|other| issues(cert, other));
+        let mut remaining = certs.iter().collect::<Vec<_>>();
+        // A self-signed root which issued none of the others indicates a gap,
+        // so it's only the leaf when nothing else is
+        let Some(leaf) = certs
+            .iter()
+            .position(|cert| is_leaf(cert) && !is_self_signed(cert))
+            .or_else(|| certs.iter().position(is_leaf))
+        else {
+            return match certs.first() {
+                Some(cert) => Err(Error::CertificateNotInPath {
+                    subject: subject(cert),
+                }),
+                None => Ok(self),
+            };
+        };
+
+        let mut end = remaining.remove(leaf);
+        let mut path = vec![end];
+        while let Some(index) = remaining.iter().position(|cert| issues(cert, end)) {
+            end = remaining.remove(index);
+            path.push(end);
+        }
+
+        if let Some(unused) = remaining.first() {
+            return Err(if is_self_signed(end) {
+                Error::CertificateNotInPath {
+                    subject: subject(unused),
+                }
+            } else {
+                Error::CertificateChainGap {
+                    subject: subject(end),
+                    issuer: end.tbs_certificate.issuer.to_string(),
+                }
+            });
+        }
+
+        let ders = path
+            .into_iter()
+            .map(|cert| cert.to_der())
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+        let ordered = Self::try_from_der(ders)?;
+        Ok(Self {
+            max_depth: self.max_depth,
+            time: self.time,
+            ..ordered
+        })
+    }
+
+    /// Whether the chain contains a self-signed certificate, usually the root.
+    ///
+    /// # Errors
+    /// `Error::Der` if a certificate can not be decoded.
+    pub fn contains_self_signed(&self) -> Result<bool> {
+        Ok(self.certificates()?.iter().any(is_self_signed))
+    }
+
+    /// Remove the self-signed root from the chain, checking it is the
+    /// `trust_anchor`.
+    ///
+    /// Quotes often embed the root CA in their PCK certificate chain. The
+    /// chain is verified against the configured trust anchor either way, but
+    /// an embedded root which differs from it is likely a misconfiguration,
+    /// or an attempt to have the chain trusted, and is reported explicitly
+    /// instead of as a failed path validation.
+    ///
+    /// # Errors
+    /// `Error::Der` if a certificate can not be decoded.
+    /// `Error::EmbeddedRootMismatch` if a self-signed certificate in the chain
+    /// is not the `trust_anchor`.
+    pub fn strip_self_signed_root(self, trust_anchor: &TrustAnchor) -> Result<Self> {
+        if !self.contains_self_signed()? {
+            return Ok(self);
+        }
+        let mut certs = MbedtlsList::<MbedTlsCertificate>::new();
+        for cert in self.certs.iter() {
+            let decoded = Certificate::from_der(cert.as_der())?;
+            if !is_self_signed(&decoded) {
+                certs.push(MbedTlsCertificate::from_der(cert.as_der())?);
+            } else if !trust_anchor
+                .0
+                .iter()
+                .any(|anchor| anchor.as_der() == cert.as_der())
+            {
+                return Err(Error::EmbeddedRootMismatch {
+                    subject: subject(&decoded),
+                });
+            }
+        }
+        Ok(Self {
+            certs,
+            max_depth: self.max_depth,
+            time: self.time,
+        })
     }
 
     /// Try to get a certificate chain from an iterator of DER encoded byte
@@ -180,7 +962,29 @@ impl UnverifiedCertChain {
             let cert = MbedTlsCertificate::from_der(der.as_ref())?;
             certs.push(cert);
         }
-        Ok(Self(certs))
+        Ok(Self {
+            certs,
+            max_depth: None,
+            time: None,
+        })
+    }
+
+    /// Try to get a certificate chain from an iterator of base64 encoded DER
+    /// certificates, without PEM armor.
+    ///
+    /// # Errors
+    /// `Error::Base64` if one of the strings is not valid base64.
+    /// `Error::MbedTls` if one of the certificates is not valid DER.
+    pub fn try_from_base64<S, I>(certificates: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let ders = certificates
+            .into_iter()
+            .map(|base64| decode_base64(base64.as_ref()))
+            .collect::<Result<Vec<_>>>()?;
+        Self::try_from_der(ders)
     }
 
     /// Try to get a certificate chain from an iterator of X509Certificates
@@ -198,288 +1002,2847 @@ impl UnverifiedCertChain {
             .collect::<core::result::Result<Vec<_>, _>>()?;
         Self::try_from_der(certs)
     }
+
+    /// Try to get a certificate chain from a borrowed [`CertChainRef`].
+    ///
+    /// This is where the certificates are copied into MbedTls, so the chain
+    /// can be inspected beforehand without allocating.
+    ///
+    /// # Errors
+    /// `Error::MbedTls` if a certificate could not be decoded by mbedtls.
+    pub fn try_from_chain_ref(chain: CertChainRef<'_>) -> Result<Self> {
+        Self::try_from_der(chain.iter().map(|cert| cert.as_der()))
+    }
+
+    /// Try to get a certificate chain from a bundle of concatenated PEM
+    /// certificates.
+    ///
+    /// Quotes and the Intel PCS deliver a whole chain as one string. Text
+    /// outside of the `CERTIFICATE` PEM blocks is ignored.
+    ///
+    /// # Errors
+    /// `Error::MissingPem` if the bundle has no certificates.
+    /// `Error::Der` if a PEM block is malformed.
+    /// `Error::MbedTls` if a certificate could not be decoded by mbedtls.
+    pub fn try_from_pem_bundle(bundle: &str) -> Result<Self> {
+        Self::try_from_der(pem_blocks(bundle, CERTIFICATE_PEM_LABEL)?)
+    }
+
+    /// Try to get a certificate chain from a DER encoded degenerate PKCS#7,
+    /// CMS, SignedData bundle, as emitted by `openssl crl2pkcs7` and many CA
+    /// tools.
+    ///
+    /// The certificates are kept in the order of the bundle, use
+    /// [`UnverifiedCertChain::into_ordered()`] if the order is not known.
+    ///
+    /// # Errors
+    /// `Error::Der` if the bytes are not a SignedData or one of its
+    /// certificates can not be decoded.
+    /// `Error::MbedTls` if a certificate could not be decoded by mbedtls.
+    pub fn try_from_pkcs7(der: impl AsRef<[u8]>) -> Result<Self> {
+        Self::try_from_certificates(&pkcs7::certificates(der.as_ref())?)
+    }
+}
+
+/// Extracts the PCK certificate chain embedded in a [`Quote3`].
+///
+/// This connects a quote to [`UnverifiedCertChain`] without slicing the PEM
+/// chain out of the quote bytes by hand.
+///
+/// ```no_run
+/// # use mc_attestation_verifier::{CertificateRevocationList, Quote3, Quote3PckCertChain, TrustAnchor};
+/// # fn verify(quote: Quote3<&[u8]>, crl: CertificateRevocationList) {
+/// let chain = quote
+///     .pck_cert_chain()
+///     .and_then(|chain| chain.verify(&TrustAnchor::intel_sgx_root(), crl))
+///     .expect("The PCK certificate chain should be valid");
+/// # }
+/// ```
+pub trait Quote3PckCertChain {
+    /// The PCK certificate chain, leaf first, of certification data type 5.
+    ///
+    /// # Errors
+    /// `Error::MissingPckCertChain` if the certification data is not the
+    /// PCK certificate chain.
+    /// The errors of [`UnverifiedCertChain::try_from_pem_bundle()`] if the
+    /// embedded chain can not be decoded.
+    fn pck_cert_chain(&self) -> Result<UnverifiedCertChain>;
+}
+
+impl<T: AsRef<[u8]>> Quote3PckCertChain for Quote3<T> {
+    fn pck_cert_chain(&self) -> Result<UnverifiedCertChain> {
+        let signature_data = self.signature_data();
+        let CertificationData::PckCertificateChain(chain) = signature_data.certification_data()
+        else {
+            return Err(Error::MissingPckCertChain);
+        };
+        let bundle = chain.into_iter().collect::<String>();
+        UnverifiedCertChain::try_from_pem_bundle(&bundle)
+    }
+}
+
+const CERTIFICATE_PEM_LABEL: &str = "CERTIFICATE";
+const CRL_PEM_LABEL: &str = "X509 CRL";
+
+/// The DER contents of the PEM blocks with `label` in `bundle`, in order.
+fn pem_blocks(bundle: &str, label: &str) -> Result<Vec<Vec<u8>>> {
+    let begin = format!("-----BEGIN {label}-----");
+    let end = format!("-----END {label}-----");
+    let mut ders = Vec::new();
+    let mut rest = bundle;
+    while let Some(start) = rest.find(&begin) {
+        let block = &rest[start..];
+        // A block missing its end boundary is left to the decoder to reject
+        let block_len = block
+            .find(&end)
+            .map_or(block.len(), |offset| offset + end.len());
+        let (_, der) =
+            der::pem::decode_vec(&block.as_bytes()[..block_len]).map_err(der::Error::from)?;
+        ders.push(der);
+        rest = &block[block_len..];
+    }
+    if ders.is_empty() {
+        return Err(Error::MissingPem {
+            label: label.to_string(),
+        });
+    }
+    Ok(ders)
+}
+
+/// Decode standard, padded, base64 ignoring whitespace, which config systems
+/// often add to wrap long values
+fn decode_base64(base64: &str) -> Result<Vec<u8>> {
+    let base64 = base64
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<String>();
+    Ok(Base64::decode_vec(&base64)?)
+}
+
+fn subject(cert: &Certificate) -> String {
+    cert.tbs_certificate.subject.to_string()
 }
 
-/// Certificate revocation list.
+fn is_self_signed(cert: &Certificate) -> bool {
+    cert.tbs_certificate.subject == cert.tbs_certificate.issuer
+}
+
+/// Certificate revocation lists for verifying a certificate chain.
+///
+/// Both complete (base) CRLs and delta CRLs, as described in
+/// [RFC 5280 Section 5.2.4](https://datatracker.ietf.org/doc/html/rfc5280#section-5.2.4),
+/// are supported. Base CRLs are handed to MbedTls, while delta CRLs are
+/// applied after MbedTls has verified the certificate chain. This allows
+/// refreshing revocation information via the small delta CRLs, advertised in
+/// the Freshest CRL extension of the base CRLs, instead of re-downloading the
+/// base CRLs.
+///
+/// Entries of a delta CRL with the `removeFromCRL` reason can not un-revoke
+/// certificates listed in the base CRL. A new base CRL is needed for that.
+///
+/// A set can be reused across verifications, by passing it as
+/// `&mut CertificateRevocationList`, and kept current with
+/// [`CertificateRevocationList::try_extend_from_der()`] or
+/// [`CertificateRevocationList::merge()`]. Cloning re-parses the base CRLs
+/// into MbedTls, so it is better avoided for large sets.
 #[derive(Debug)]
-struct CertificateRevocationList(Crl);
+pub struct CertificateRevocationList {
+    crl: Crl,
+    /// The DER of the base CRLs, as MbedTls does not provide it back
+    base_ders: Vec<Vec<u8>>,
+    bases: Vec<BaseCrl>,
+    deltas: Vec<DeltaCrl>,
+}
+
+impl Clone for CertificateRevocationList {
+    fn clone(&self) -> Self {
+        let mut crl = Crl::new();
+        for der in &self.base_ders {
+            crl.push_from_der(der)
+                .expect("The base CRLs were already parsed by MbedTls");
+        }
+        Self {
+            crl,
+            base_ders: self.base_ders.clone(),
+            bases: self.bases.clone(),
+            deltas: self.deltas.clone(),
+        }
+    }
+}
 
 impl CertificateRevocationList {
     /// Try to get a set of certificate revocation lists from an iterator of
     /// DER encoded byte slices.
     ///
+    /// The slices may contain base and delta CRLs in any order. When there are
+    /// multiple delta CRLs from the same issuer, only the most recent one is
+    /// kept.
+    ///
     /// # Errors
     /// `Error::MbedTls` if one of the slices is not a valid DER CRL.
-    fn try_from_der<E, I>(ders: I) -> Result<Self>
+    /// `Error::DeltaCrlWithoutBase` if a delta CRL does not have a
+    /// corresponding base CRL.
+    pub fn try_from_der<E, I>(ders: I) -> Result<Self>
     where
         I: IntoIterator<Item = E>,
         E: AsRef<[u8]>,
     {
-        let mut crl = Crl::new();
-        for der in ders {
-            crl.push_from_der(der.as_ref())?;
-        }
-        Ok(Self(crl))
+        Self::parse(ders, &[])
     }
 
-    /// Try to get a set of certificate revocation lists from an iterator of `CertificateList`
+    /// Try to get a set of certificate revocation lists from an iterator of
+    /// base64 encoded DER CRLs, without PEM armor, like the CRLs in the PCS
+    /// collateral responses.
     ///
     /// # Errors
-    /// `Error::MbedTls` if there is a problem decoding a CRL by mbedtls
-    /// `Error::Der` if there is an error converting a CRL to DER
-    fn try_from_crls<'a, I>(crls: I) -> Result<Self>
+    /// `Error::Base64` if one of the strings is not valid base64.
+    /// The same errors as [`CertificateRevocationList::try_from_der()`].
+    pub fn try_from_base64<S, I>(crls: I) -> Result<Self>
     where
-        I: IntoIterator<Item = &'a CertificateList>,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
     {
-        let crls = crls
+        let ders = crls
             .into_iter()
-            .map(|crl| crl.to_der())
-            .collect::<core::result::Result<Vec<_>, _>>()?;
-        Self::try_from_der(crls)
+            .map(|base64| decode_base64(base64.as_ref()))
+            .collect::<Result<Vec<_>>>()?;
+        Self::try_from_der(ders)
     }
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-    use der::{Decode, DecodePem};
 
-    const LEAF_CERT: &str = include_str!("../data/tests/leaf_cert.pem");
+    /// Add the DER encoded base and delta CRLs in `ders` to the set.
+    ///
+    /// This is meant for keeping a long lived set current, for instance by
+    /// adding a newer delta CRL for one of the base CRLs already in the set.
+    /// A delta CRL replaces an older one from the same issuer.
+    ///
+    /// # Errors
+    /// The same errors as [`CertificateRevocationList::try_from_der()`], a
+    /// delta CRL may correspond to a base CRL already in the set. On error
+    /// the set is unchanged.
+    pub fn try_extend_from_der<E, I>(&mut self, ders: I) -> Result<()>
+    where
+        I: IntoIterator<Item = E>,
+        E: AsRef<[u8]>,
+    {
+        let other = Self::parse(ders, &self.bases)?;
+        self.merge(other)
+    }
+
+    /// Merge the CRLs of `other` into this set.
+    ///
+    /// A delta CRL replaces an older one from the same issuer.
+    ///
+    /// # Errors
+    /// `Error::MbedTls` if MbedTls fails to take one of the base CRLs of
+    /// `other`.
+    pub fn merge(&mut self, other: Self) -> Result<()> {
+        for der in &other.base_ders {
+            self.crl.push_from_der(der)?;
+        }
+        self.base_ders.extend(other.base_ders);
+        self.bases.extend(other.bases);
+        for delta in other.deltas {
+            add_delta(&mut self.deltas, delta);
+        }
+        Ok(())
+    }
+
+    /// Parse the CRLs in `ders`, whose delta CRLs may apply to one of the
+    /// `existing` base CRLs.
+    fn parse<E, I>(ders: I, existing: &[BaseCrl]) -> Result<Self>
+    where
+        I: IntoIterator<Item = E>,
+        E: AsRef<[u8]>,
+    {
+        let mut crl = Crl::new();
+        let mut base_ders = Vec::new();
+        let mut bases = Vec::new();
+        let mut deltas = Vec::new();
+        for der in ders {
+            let der = der.as_ref();
+            match crl.push_from_der(der) {
+                Ok(()) => {
+                    base_ders.push(der.to_vec());
+                    bases.extend(BaseCrl::try_from_der(der));
+                }
+                Err(err) => {
+                    // MbedTls rejects the critical delta CRL indicator extension, so
+                    // only try delta CRLs once MbedTls has failed.
+                    let delta = DeltaCrl::try_from_der(der).ok_or(err)?;
+                    add_delta(&mut deltas, delta);
+                }
+            }
+        }
+
+        for delta in &deltas {
+            if !bases
+                .iter()
+                .chain(existing)
+                .any(|base| delta.applies_to(base))
+            {
+                return Err(Error::DeltaCrlWithoutBase);
+            }
+        }
+
+        Ok(Self {
+            crl,
+            base_ders,
+            bases,
+            deltas,
+        })
+    }
+
+    /// Try to get a set of certificate revocation lists from an iterator of `CertificateList`
+    ///
+    /// # Errors
+    /// `Error::MbedTls` if there is a problem decoding a CRL by mbedtls
+    /// `Error::Der` if there is an error converting a CRL to DER
+    /// `Error::DeltaCrlWithoutBase` if a delta CRL does not have a
+    /// corresponding base CRL.
+    pub fn try_from_crls<'a, I>(crls: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = &'a CertificateList>,
+    {
+        let crls = crls
+            .into_iter()
+            .map(|crl| crl.to_der())
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+        Self::try_from_der(crls)
+    }
+
+    /// Try to get a set of certificate revocation lists from a bundle of
+    /// concatenated PEM CRLs.
+    ///
+    /// Text outside of the `X509 CRL` PEM blocks is ignored.
+    ///
+    /// # Errors
+    /// `Error::MissingPem` if the bundle has no CRLs.
+    /// `Error::Der` if a PEM block is malformed.
+    /// `Error::MbedTls` if a CRL could not be decoded by mbedtls.
+    /// `Error::DeltaCrlWithoutBase` if a delta CRL does not have a
+    /// corresponding base CRL.
+    pub fn try_from_pem_bundle(bundle: &str) -> Result<Self> {
+        Self::try_from_der(pem_blocks(bundle, CRL_PEM_LABEL)?)
+    }
+
+    /// The URIs, from the Freshest CRL extension of the base CRLs, where
+    /// delta CRLs can be retrieved.
+    pub fn delta_crl_uris(&self) -> Vec<String> {
+        self.bases
+            .iter()
+            .flat_map(|base| base.delta_uris.iter().cloned())
+            .collect()
+    }
+
+    /// Check the already verified `chain` against the delta CRLs.
+    ///
+    /// The delta CRL signatures are verified with the matching issuer from the
+    /// `chain` or the `trust_anchor`. When there is a verification `time`, it
+    /// must be within the validity of the delta CRLs.
+    ///
+    /// # Errors
+    /// `Error::DeltaCrlSignature` if the signature of a delta CRL is invalid.
+    /// `Error::DeltaCrlExpired` or `Error::DeltaCrlNotYetValid` if a delta CRL
+    /// is not valid at `time`.
+    /// `Error::CertificateRevoked` if a certificate in the chain is revoked by
+    /// a delta CRL.
+    fn check_deltas(
+        &self,
+        chain: &MbedtlsList<MbedTlsCertificate>,
+        trust_anchor: &TrustAnchor,
+        time: Option<DateTime>,
+    ) -> Result<()> {
+        if self.deltas.is_empty() {
+            return Ok(());
+        }
+
+        let chain = chain
+            .iter()
+            .map(|cert| Certificate::from_der(cert.as_der()))
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+        let anchors = trust_anchor
+            .0
+            .iter()
+            .map(|cert| Certificate::from_der(cert.as_der()))
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+
+        for delta in &self.deltas {
+            let Some(issuer) = chain
+                .iter()
+                .chain(anchors.iter())
+                .find(|cert| &cert.tbs_certificate.subject == delta.issuer())
+            else {
+                // Nothing in the chain was issued by this CRL issuer
+                continue;
+            };
+            delta.verify_signature(issuer)?;
+            if let Some(time) = time {
+                delta.check_validity(time)?;
+            }
+
+            if chain.iter().any(|cert| delta.revokes(cert)) {
+                return Err(Error::CertificateRevoked);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A verified certificate chain.
+///
+/// Obtained from [`UnverifiedCertChain::verify()`].
+#[derive(Clone)]
+pub struct VerifiedCertChain(MbedtlsList<MbedTlsCertificate>);
+
+impl Debug for VerifiedCertChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "VerifiedCertChain{{...}}")
+    }
+}
+
+impl VerifiedCertChain {
+    /// The public key of the leaf certificate.
+    ///
+    /// The leaf is the first certificate of the chain, the one which was
+    /// verified to chain up to the trust anchor. For DCAP this is the PCK
+    /// certificate, or the signing certificate of the TCB info and QE identity.
+    ///
+    /// # Errors
+    /// `Error::Der` if the leaf certificate can not be decoded.
+    /// `Error::UnsupportedPublicKey` if the leaf key is not a P-256 key.
+    pub fn leaf_public_key(&self) -> Result<VerifyingKey> {
+        let spki = self.leaf_spki()?;
+        let key = spki
+            .subject_public_key
+            .as_bytes()
+            .ok_or(Error::UnsupportedPublicKey)?;
+        VerifyingKey::from_sec1_bytes(key).map_err(|_| Error::UnsupportedPublicKey)
+    }
+
+    /// Verify a detached `signature` over `data` with the leaf's public key.
+    ///
+    /// ECDSA signatures are expected to be ASN.1 DER encoded, as they are in
+    /// certificates. The raw `r || s` signatures used in DCAP collateral need
+    /// to be converted first, for example with
+    /// `p256::ecdsa::Signature::to_der()`.
+    ///
+    /// # Errors
+    /// `Error::Der` if the leaf certificate can not be decoded.
+    /// `Error::UnsupportedPublicKey` if the leaf key can not be used with the
+    /// `scheme`.
+    /// `Error::SignatureVerification` if the signature is invalid.
+    pub fn verify_signature(
+        &self,
+        data: &[u8],
+        signature: &[u8],
+        scheme: SignatureScheme,
+    ) -> Result<()> {
+        let key = self.leaf_spki()?.to_der()?;
+        let mut key = Pk::from_public_key(&key).map_err(|_| Error::UnsupportedPublicKey)?;
+        if !scheme.supports(key.pk_type()) {
+            return Err(Error::UnsupportedPublicKey);
+        }
+
+        let hash_type = scheme.hash_type();
+        let mut hash = [0u8; 64];
+        let size = Md::hash(hash_type, data, &mut hash)?;
+        key.verify(hash_type, &hash[..size], signature)
+            .map_err(|_| Error::SignatureVerification)
+    }
+
+    /// Verify the QE report of a quote was signed by the leaf, the PCK
+    /// certificate.
+    ///
+    /// This binds the QE, and through it the quote, to the platform whose PCK
+    /// chain was verified. For version 4 and 5 quotes the arguments are
+    /// [`Quote4SignatureData::qe_report_bytes()`] and
+    /// [`Quote4SignatureData::qe_report_signature()`].
+    ///
+    /// # Errors
+    /// `Error::Der` if the leaf certificate can not be decoded.
+    /// `Error::UnsupportedPublicKey` if the leaf key is not a P-256 key.
+    /// `Error::QeReportSignature` if the QE report was not signed by the
+    /// leaf.
+    pub fn verify_qe_report(
+        &self,
+        qe_report: &[u8],
+        qe_report_signature: &Signature,
+    ) -> Result<()> {
+        self.leaf_public_key()?
+            .verify(qe_report, qe_report_signature)
+            .map_err(|_| Error::QeReportSignature)
+    }
+
+    /// The leaf certificate, requiring it to assert the key `usage`.
+    ///
+    /// For instance the PCK certificate must assert `digitalSignature` for
+    /// its key to sign the quoting enclave report. All the bits of `usage`
+    /// must be asserted, the leaf may assert more.
+    ///
+    /// # Errors
+    /// `Error::Der` if the leaf certificate can not be decoded.
+    /// `Error::LeafKeyUsage` if the leaf has no key usage extension or does
+    /// not assert all of `usage`.
+    pub fn leaf_with_usage(&self, usage: KeyUsage) -> Result<Certificate> {
+        let leaf = self.0.iter().next().ok_or(Error::LeafKeyUsage)?;
+        let leaf = Certificate::from_der(leaf.as_der())?;
+        match leaf.tbs_certificate.get::<KeyUsage>()? {
+            Some((_, asserted)) if asserted.0.contains(usage.0) => Ok(leaf),
+            _ => Err(Error::LeafKeyUsage),
+        }
+    }
+
+    /// The DER encoding of each certificate, in the order of the chain
+    /// starting with the leaf.
+    ///
+    /// This allows forwarding the verified chain, for example into an RA-TLS
+    /// certificate or an evidence archive. The chain is in path order when it
+    /// was ordered with [`UnverifiedCertChain::into_ordered()`] before being
+    /// verified.
+    pub fn to_der_vec(&self) -> Vec<Vec<u8>> {
+        self.0.iter().map(|cert| cert.as_der().to_vec()).collect()
+    }
+
+    /// Check the leaf certificate against the expected names of `matcher`.
+    ///
+    /// # Errors
+    /// `Error::Der` if the leaf certificate can not be decoded.
+    /// `Error::LeafMismatch` if the leaf does not match.
+    pub fn check_leaf(&self, matcher: &LeafMatcher) -> Result<()> {
+        let leaf = self.0.iter().next().ok_or(Error::LeafMismatch)?;
+        let leaf = Certificate::from_der(leaf.as_der())?;
+        matcher.check(&leaf).map_err(|_| Error::LeafMismatch)
+    }
+
+    /// The start of the chain's validity window, the latest `notBefore` of
+    /// its certificates.
+    ///
+    /// # Errors
+    /// `Error::Der` if a certificate can not be decoded.
+    pub fn not_before(&self) -> Result<DateTime> {
+        Ok(self.validity()?.0)
+    }
+
+    /// The end of the chain's validity window, the earliest `notAfter` of its
+    /// certificates.
+    ///
+    /// A cached verification of the chain should be redone by then.
+    ///
+    /// # Errors
+    /// `Error::Der` if a certificate can not be decoded.
+    pub fn not_after(&self) -> Result<DateTime> {
+        Ok(self.validity()?.1)
+    }
+
+    /// The intersection of the validity periods of the certificates
+    fn validity(&self) -> Result<(DateTime, DateTime)> {
+        let mut not_before = DateTime::from_unix_duration(Duration::ZERO)?;
+        let mut not_after = DateTime::INFINITY;
+        for cert in self.0.iter() {
+            let validity = Certificate::from_der(cert.as_der())?
+                .tbs_certificate
+                .validity;
+            not_before = not_before.max(validity.not_before.to_date_time());
+            not_after = not_after.min(validity.not_after.to_date_time());
+        }
+        Ok((not_before, not_after))
+    }
+
+    /// The subject public key info of the leaf, borrowed from its DER
+    /// encoding instead of decoding the whole certificate
+    fn leaf_spki(&self) -> Result<SubjectPublicKeyInfoRef<'_>> {
+        let leaf = self.0.iter().next().ok_or(Error::UnsupportedPublicKey)?;
+        Ok(cert_chain_ref::subject_public_key_info(leaf.as_der())?)
+    }
+}
+
+/// The signature scheme for [`VerifiedCertChain::verify_signature()`].
+///
+/// Limited to the schemes allowed when verifying the certificate chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignatureScheme {
+    /// ECDSA with SHA-256, as used by DCAP
+    EcdsaSha256,
+    /// ECDSA with SHA-384
+    EcdsaSha384,
+    /// ECDSA with SHA-512
+    EcdsaSha512,
+    /// RSASSA-PKCS1-v1_5 with SHA-256
+    RsaPkcs1Sha256,
+    /// RSASSA-PKCS1-v1_5 with SHA-384
+    RsaPkcs1Sha384,
+    /// RSASSA-PKCS1-v1_5 with SHA-512
+    RsaPkcs1Sha512,
+}
+
+impl SignatureScheme {
+    fn hash_type(&self) -> HashType {
+        match self {
+            Self::EcdsaSha256 | Self::RsaPkcs1Sha256 => HashType::Sha256,
+            Self::EcdsaSha384 | Self::RsaPkcs1Sha384 => HashType::Sha384,
+            Self::EcdsaSha512 | Self::RsaPkcs1Sha512 => HashType::Sha512,
+        }
+    }
+
+    fn supports(&self, key_type: PkType) -> bool {
+        match self {
+            Self::EcdsaSha256 | Self::EcdsaSha384 | Self::EcdsaSha512 => {
+                matches!(key_type, PkType::Eckey | PkType::Ecdsa)
+            }
+            Self::RsaPkcs1Sha256 | Self::RsaPkcs1Sha384 | Self::RsaPkcs1Sha512 => {
+                matches!(key_type, PkType::Rsa)
+            }
+        }
+    }
+}
+
+/// What was determined for each certificate of a chain, obtained from
+/// [`UnverifiedCertChain::verify_with_report()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainVerificationReport {
+    certificates: Vec<CertificateReport>,
+    failure: Option<String>,
+}
+
+impl ChainVerificationReport {
+    /// The reports for the certificates, in the order of the chain
+    pub fn certificates(&self) -> &[CertificateReport] {
+        &self.certificates
+    }
+
+    /// Why the verification failed, `None` if it succeeded
+    pub fn failure(&self) -> Option<&str> {
+        self.failure.as_deref()
+    }
+}
+
+impl Display for ChainVerificationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for certificate in &self.certificates {
+            writeln!(f, "{certificate}")?;
+        }
+        match &self.failure {
+            Some(failure) => write!(f, "Verification failed: {failure}"),
+            None => write!(f, "Verification succeeded"),
+        }
+    }
+}
+
+/// The details of a single certificate in a [`ChainVerificationReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateReport {
+    subject: String,
+    issuer: String,
+    serial_number: String,
+    not_before: DateTime,
+    not_after: DateTime,
+    revocation_status: RevocationStatus,
+    checks: Vec<CertificateCheck>,
+}
+
+impl CertificateReport {
+    fn new(cert: &Certificate, crl: &CertificateRevocationList) -> Self {
+        let tbs = &cert.tbs_certificate;
+        let bases = crl
+            .bases
+            .iter()
+            .filter(|base| base.issuer == tbs.issuer)
+            .collect::<Vec<_>>();
+        let deltas = crl
+            .deltas
+            .iter()
+            .filter(|delta| delta.issuer() == &tbs.issuer)
+            .collect::<Vec<_>>();
+
+        let mut checks = vec![CertificateCheck::IssuerSignature];
+        if !bases.is_empty() {
+            checks.push(CertificateCheck::Revocation);
+        }
+        if !deltas.is_empty() {
+            checks.push(CertificateCheck::DeltaRevocation);
+        }
+
+        let revoked = bases
+            .iter()
+            .any(|base| base.revoked.contains(&tbs.serial_number))
+            || deltas.iter().any(|delta| delta.revokes(cert));
+        let revocation_status = match (revoked, bases.is_empty()) {
+            (true, _) => RevocationStatus::Revoked,
+            (false, false) => RevocationStatus::NotRevoked,
+            (false, true) => RevocationStatus::NoCrl,
+        };
+
+        Self {
+            subject: tbs.subject.to_string(),
+            issuer: tbs.issuer.to_string(),
+            serial_number: hex::encode(tbs.serial_number.as_bytes()),
+            not_before: tbs.validity.not_before.to_date_time(),
+            not_after: tbs.validity.not_after.to_date_time(),
+            revocation_status,
+            checks,
+        }
+    }
+
+    /// The subject of the certificate
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// The issuer of the certificate
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// The hex encoded serial number of the certificate
+    pub fn serial_number(&self) -> &str {
+        &self.serial_number
+    }
+
+    /// The start of the certificate's validity window
+    pub fn not_before(&self) -> DateTime {
+        self.not_before
+    }
+
+    /// The end of the certificate's validity window
+    pub fn not_after(&self) -> DateTime {
+        self.not_after
+    }
+
+    /// The revocation status according to the provided CRLs
+    pub fn revocation_status(&self) -> RevocationStatus {
+        self.revocation_status
+    }
+
+    /// The checks performed on the certificate
+    pub fn checks(&self) -> &[CertificateCheck] {
+        &self.checks
+    }
+}
+
+impl Display for CertificateReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "Certificate: {}", self.subject)?;
+        writeln!(f, "  Issuer: {}", self.issuer)?;
+        writeln!(f, "  Serial number: {}", self.serial_number)?;
+        writeln!(f, "  Validity: {} to {}", self.not_before, self.not_after)?;
+        writeln!(f, "  Revocation status: {}", self.revocation_status)?;
+        write!(f, "  Checks:")?;
+        for (index, check) in self.checks.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{separator}{check}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The revocation status of a certificate in a [`CertificateReport`].
+#[derive(Debug, displaydoc::Display, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RevocationStatus {
+    /// not revoked
+    NotRevoked,
+    /// revoked
+    Revoked,
+    /// no CRL from the issuer
+    NoCrl,
+}
+
+/// A check performed on a certificate in a [`CertificateReport`].
+#[derive(Debug, displaydoc::Display, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CertificateCheck {
+    /// issuer signature
+    IssuerSignature,
+    /// base CRL revocation
+    Revocation,
+    /// delta CRL revocation
+    DeltaRevocation,
+}
+
+/// A reason MbedTls gave for rejecting a certificate in the path, see
+/// [`FailureReasons`].
+#[derive(Debug, displaydoc::Display, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FailureReason {
+    /// certificate expired
+    Expired,
+    /// certificate not yet valid
+    NotYetValid,
+    /// certificate revoked
+    Revoked,
+    /// common name mismatch
+    CnMismatch,
+    /// certificate not trusted
+    NotTrusted,
+    /// certificate missing
+    Missing,
+    /// verification skipped
+    SkipVerify,
+    /// rejected by a callback
+    Other,
+    /// key usage not allowed
+    KeyUsage,
+    /// extended key usage not allowed
+    ExtendedKeyUsage,
+    /// Netscape certificate type not allowed
+    NsCertType,
+    /// certificate signed with a disallowed hash
+    BadMd,
+    /// certificate signed with a disallowed key type
+    BadPk,
+    /// certificate signed with a disallowed key
+    BadKey,
+    /// CRL not trusted
+    CrlNotTrusted,
+    /// CRL expired
+    CrlExpired,
+    /// CRL not yet valid
+    CrlNotYetValid,
+    /// CRL signed with a disallowed hash
+    CrlBadMd,
+    /// CRL signed with a disallowed key type
+    CrlBadPk,
+    /// CRL signed with a disallowed key
+    CrlBadKey,
+}
+
+impl FailureReason {
+    /// All the reasons with their MbedTls verification flag
+    const FLAGS: [(FailureReason, VerifyError); 20] = [
+        (FailureReason::Expired, VerifyError::CERT_EXPIRED),
+        (FailureReason::NotYetValid, VerifyError::CERT_FUTURE),
+        (FailureReason::Revoked, VerifyError::CERT_REVOKED),
+        (FailureReason::CnMismatch, VerifyError::CERT_CN_MISMATCH),
+        (FailureReason::NotTrusted, VerifyError::CERT_NOT_TRUSTED),
+        (FailureReason::Missing, VerifyError::CERT_MISSING),
+        (FailureReason::SkipVerify, VerifyError::CERT_SKIP_VERIFY),
+        (FailureReason::Other, VerifyError::CERT_OTHER),
+        (FailureReason::KeyUsage, VerifyError::CERT_KEY_USAGE),
+        (
+            FailureReason::ExtendedKeyUsage,
+            VerifyError::CERT_EXT_KEY_USAGE,
+        ),
+        (FailureReason::NsCertType, VerifyError::CERT_NS_CERT_TYPE),
+        (FailureReason::BadMd, VerifyError::CERT_BAD_MD),
+        (FailureReason::BadPk, VerifyError::CERT_BAD_PK),
+        (FailureReason::BadKey, VerifyError::CERT_BAD_KEY),
+        (FailureReason::CrlNotTrusted, VerifyError::CRL_NOT_TRUSTED),
+        (FailureReason::CrlExpired, VerifyError::CRL_EXPIRED),
+        (FailureReason::CrlNotYetValid, VerifyError::CRL_FUTURE),
+        (FailureReason::CrlBadMd, VerifyError::CRL_BAD_MD),
+        (FailureReason::CrlBadPk, VerifyError::CRL_BAD_PK),
+        (FailureReason::CrlBadKey, VerifyError::CRL_BAD_KEY),
+    ];
+}
+
+/// Why MbedTls rejected a certificate chain.
+///
+/// The union of the verification flags MbedTls set for the certificates in
+/// the path, see [`Error::CertificateVerification`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FailureReasons(VerifyError);
+
+impl FailureReasons {
+    /// Whether MbedTls gave the `reason`
+    pub fn contains(&self, reason: FailureReason) -> bool {
+        self.iter().any(|r| r == reason)
+    }
+
+    /// The reasons MbedTls gave
+    pub fn iter(&self) -> impl Iterator<Item = FailureReason> + '_ {
+        FailureReason::FLAGS
+            .iter()
+            .filter(|(_, flag)| self.0.contains(*flag))
+            .map(|(reason, _)| *reason)
+    }
+
+    /// Whether no reason was given
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The raw MbedTls verification flags, including any without a
+    /// [`FailureReason`]
+    pub fn flags(&self) -> VerifyError {
+        self.0
+    }
+}
+
+impl From<VerifyError> for FailureReasons {
+    fn from(flags: VerifyError) -> Self {
+        Self(flags)
+    }
+}
+
+impl Display for FailureReasons {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut separator = "";
+        for reason in self.iter() {
+            write!(f, "{separator}{reason}")?;
+            separator = ", ";
+        }
+        if separator.is_empty() {
+            write!(f, "unknown reason")?;
+        }
+        Ok(())
+    }
+}
+
+/// Fetch the CRLs for the `certificates`.
+///
+/// Each URI from the certificates' CRL distribution points, see
+/// [`crl_distribution_points()`], is passed to `fetch` once. `fetch` is
+/// responsible for retrieving the CRL, usually via HTTP, and may return it DER
+/// or PEM encoded.
+///
+/// # Errors
+/// `Error::CrlFetch` if `fetch` fails for one of the URIs.
+/// `Error::Der` if the CRL distribution points or a PEM CRL can not be decoded.
+/// `Error::MbedTls` if a fetched CRL is not valid.
+pub fn fetch_crls<'a, I, F, E>(certificates: I, mut fetch: F) -> Result<CertificateRevocationList>
+where
+    I: IntoIterator<Item = &'a Certificate>,
+    F: FnMut(&str) -> core::result::Result<Vec<u8>, E>,
+    E: Display,
+{
+    let mut uris = Vec::new();
+    for certificate in certificates {
+        for uri in crl_distribution_points(certificate)? {
+            if !uris.contains(&uri) {
+                uris.push(uri);
+            }
+        }
+    }
+
+    let mut crls = Vec::with_capacity(uris.len());
+    for uri in uris {
+        let crl = fetch(&uri).map_err(|error| Error::CrlFetch {
+            error: error.to_string(),
+            uri,
+        })?;
+        let crl = if crl.starts_with(b"-----BEGIN") {
+            der::pem::decode_vec(&crl).map_err(der::Error::from)?.1
+        } else {
+            crl
+        };
+        crls.push(crl);
+    }
+    CertificateRevocationList::try_from_der(crls)
+}
+
+/// Add the `delta` CRL to `deltas`, keeping only the most recent delta CRL
+/// of each issuer.
+fn add_delta(deltas: &mut Vec<DeltaCrl>, delta: DeltaCrl) {
+    match deltas
+        .iter_mut()
+        .find(|existing| existing.issuer() == delta.issuer())
+    {
+        Some(existing) if existing.number < delta.number => *existing = delta,
+        Some(_) => {}
+        None => deltas.push(delta),
+    }
+}
+
+/// The information from a base CRL needed to apply delta CRLs and report
+/// revocations.
+#[derive(Clone, Debug)]
+struct BaseCrl {
+    issuer: Name,
+    number: Option<Uint>,
+    delta_uris: Vec<String>,
+    revoked: Vec<SerialNumber>,
+}
+
+impl BaseCrl {
+    /// Get the base CRL information from a DER encoded CRL.
+    ///
+    /// Returns `None` if the CRL can not be decoded.
+    fn try_from_der(der: &[u8]) -> Option<Self> {
+        let crl = CertificateList::from_der(der).ok()?;
+        let mut number = None;
+        let mut delta_uris = Vec::new();
+        for extension in crl.tbs_cert_list.crl_extensions.iter().flatten() {
+            let value = extension.extn_value.as_bytes();
+            if extension.extn_id == CrlNumber::OID {
+                number = CrlNumber::from_der(value).ok().map(|number| number.0);
+            } else if extension.extn_id == FreshestCrl::OID {
+                if let Ok(freshest) = FreshestCrl::from_der(value) {
+                    delta_uris.extend(distribution_point_uris(&freshest.0));
+                }
+            }
+        }
+        let revoked = crl
+            .tbs_cert_list
+            .revoked_certificates
+            .iter()
+            .flatten()
+            .map(|revoked| revoked.serial_number.clone())
+            .collect();
+        Some(Self {
+            issuer: crl.tbs_cert_list.issuer,
+            number,
+            delta_uris,
+            revoked,
+        })
+    }
+}
+
+/// A delta CRL, containing the changes since a base CRL.
+#[derive(Clone, Debug)]
+struct DeltaCrl {
+    crl: CertificateList,
+    number: Uint,
+    base_number: Uint,
+    this_update: DateTime,
+    next_update: Option<DateTime>,
+}
+
+impl DeltaCrl {
+    /// Get a delta CRL from a DER encoded CRL.
+    ///
+    /// Returns `None` if the CRL can not be decoded, is not a delta CRL, or has
+    /// other critical extensions.
+    fn try_from_der(der: &[u8]) -> Option<Self> {
+        let crl = CertificateList::from_der(der).ok()?;
+        let mut number = None;
+        let mut base_number = None;
+        for extension in crl.tbs_cert_list.crl_extensions.iter().flatten() {
+            let value = extension.extn_value.as_bytes();
+            if extension.extn_id == BaseCrlNumber::OID {
+                base_number = Some(BaseCrlNumber::from_der(value).ok()?.0);
+            } else if extension.extn_id == CrlNumber::OID {
+                number = Some(CrlNumber::from_der(value).ok()?.0);
+            } else if extension.critical {
+                return None;
+            }
+        }
+        Some(Self {
+            number: number?,
+            base_number: base_number?,
+            this_update: crl.tbs_cert_list.this_update.to_date_time(),
+            next_update: crl
+                .tbs_cert_list
+                .next_update
+                .map(|next_update| next_update.to_date_time()),
+            crl,
+        })
+    }
+
+    fn issuer(&self) -> &Name {
+        &self.crl.tbs_cert_list.issuer
+    }
+
+    /// Whether this delta CRL can be applied to the `base` CRL.
+    fn applies_to(&self, base: &BaseCrl) -> bool {
+        &base.issuer == self.issuer()
+            && matches!(
+                &base.number,
+                Some(number) if number >= &self.base_number && number < &self.number
+            )
+    }
+
+    /// Whether `cert` is revoked by this delta CRL.
+    fn revokes(&self, cert: &Certificate) -> bool {
+        if &cert.tbs_certificate.issuer != self.issuer() {
+            return false;
+        }
+        self.crl
+            .tbs_cert_list
+            .revoked_certificates
+            .iter()
+            .flatten()
+            .filter(|revoked| revoked.serial_number == cert.tbs_certificate.serial_number)
+            .any(|revoked| {
+                let reason = revoked
+                    .crl_entry_extensions
+                    .iter()
+                    .flatten()
+                    .find(|extension| extension.extn_id == CrlReason::OID)
+                    .and_then(|extension| {
+                        CrlReason::from_der(extension.extn_value.as_bytes()).ok()
+                    });
+                reason != Some(CrlReason::RemoveFromCRL)
+            })
+    }
+
+    /// Check `time` is within the validity of this delta CRL, a delta CRL
+    /// without a `nextUpdate` does not expire.
+    ///
+    /// # Errors
+    /// `Error::DeltaCrlNotYetValid` if `time` is before `thisUpdate`.
+    /// `Error::DeltaCrlExpired` if `time` is at, or after, `nextUpdate`.
+    fn check_validity(&self, time: DateTime) -> Result<()> {
+        if time < self.this_update {
+            return Err(Error::DeltaCrlNotYetValid);
+        }
+        match self.next_update {
+            Some(next_update) if time >= next_update => Err(Error::DeltaCrlExpired),
+            _ => Ok(()),
+        }
+    }
+
+    /// Verify the signature of this delta CRL with the public key of `issuer`.
+    ///
+    /// # Errors
+    /// `Error::DeltaCrlSignature` if the signature is invalid or uses an
+    /// unsupported algorithm.
+    fn verify_signature(&self, issuer: &Certificate) -> Result<()> {
+        let hash_type = signature_hash_type(&self.crl.signature_algorithm.oid)
+            .ok_or(Error::DeltaCrlSignature)?;
+        let signature = self
+            .crl
+            .signature
+            .as_bytes()
+            .ok_or(Error::DeltaCrlSignature)?;
+        let tbs = self.crl.tbs_cert_list.to_der()?;
+        let mut hash = [0u8; 64];
+        let size = Md::hash(hash_type, &tbs, &mut hash)?;
+
+        let key = issuer.tbs_certificate.subject_public_key_info.to_der()?;
+        let mut key = Pk::from_public_key(&key)?;
+        key.verify(hash_type, &hash[..size], signature)
+            .map_err(|_| Error::DeltaCrlSignature)
+    }
+}
+
+/// The hash used by a CRL signature algorithm, limited to those allowed when
+/// verifying the certificate chain.
+fn signature_hash_type(algorithm: &ObjectIdentifier) -> Option<HashType> {
+    match *algorithm {
+        ECDSA_WITH_SHA_256 | SHA_256_WITH_RSA_ENCRYPTION => Some(HashType::Sha256),
+        ECDSA_WITH_SHA_384 | SHA_384_WITH_RSA_ENCRYPTION => Some(HashType::Sha384),
+        ECDSA_WITH_SHA_512 | SHA_512_WITH_RSA_ENCRYPTION => Some(HashType::Sha512),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_der {
+    //! Serialization of the MbedTls backed types as their DER encodings, in
+    //! hex, so they can be embedded in configuration files and caches.
+
+    use super::{
+        CertificateRevocationList, Error, MbedTlsCertificate, MbedtlsList, TrustAnchor,
+        UnverifiedCertChain,
+    };
+    use alloc::{string::String, vec::Vec};
+    use der::Encode;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    fn serialize_ders<'a, S: Serializer>(
+        ders: impl IntoIterator<Item = &'a [u8]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(ders.into_iter().map(hex::encode))
+    }
+
+    fn deserialize_ders<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<u8>>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(hex::decode)
+            .collect::<Result<_, _>>()
+            .map_err(de::Error::custom)
+    }
+
+    fn certificate_ders(certs: &MbedtlsList<MbedTlsCertificate>) -> impl Iterator<Item = &[u8]> {
+        certs.iter().map(|cert| cert.as_der())
+    }
+
+    /// Serialized as the list of its certificates
+    impl Serialize for TrustAnchor {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize_ders(certificate_ders(&self.0), serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for TrustAnchor {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let mut certs = MbedtlsList::<MbedTlsCertificate>::new();
+            for der in deserialize_ders(deserializer)? {
+                let cert = MbedTlsCertificate::from_der(&der)
+                    .map_err(|e| de::Error::custom(Error::from(e)))?;
+                certs.push(cert);
+            }
+            Ok(Self(certs))
+        }
+    }
+
+    /// Serialized as the list of its certificates, the limit from
+    /// [`UnverifiedCertChain::with_max_depth()`] is not kept.
+    impl Serialize for UnverifiedCertChain {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize_ders(certificate_ders(&self.certs), serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for UnverifiedCertChain {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Self::try_from_der(deserialize_ders(deserializer)?).map_err(de::Error::custom)
+        }
+    }
+
+    /// Serialized as the list of its base and delta CRLs
+    impl Serialize for CertificateRevocationList {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut ders = self.base_ders.clone();
+            for delta in &self.deltas {
+                ders.push(delta.crl.to_der().map_err(serde::ser::Error::custom)?);
+            }
+            serialize_ders(ders.iter().map(Vec::as_slice), serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for CertificateRevocationList {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Self::try_from_der(deserialize_ders(deserializer)?).map_err(de::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_matches::assert_matches;
+    use der::{flagset::FlagSet, DecodePem};
+    use x509_cert::ext::pkix::KeyUsages;
+    use yare::parameterized;
+
+    const LEAF_CERT: &str = include_str!("../data/tests/leaf_cert.pem");
     const PROCESSOR_CA: &str = include_str!("../data/tests/processor_ca.pem");
     const ROOT_CA: &str = include_str!("../data/tests/root_ca.pem");
+    const TCB_SIGNER: &str = include_str!("../data/tests/tcb_signer.pem");
+    const TCB_INFO_JSON: &str = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
     const PROCESSOR_CRL: &[u8] = include_bytes!("../data/tests/processor_crl.der");
     const ROOT_CRL: &[u8] = include_bytes!("../data/tests/root_crl.der");
+    const QUOTE_BYTES: &[u8] = include_bytes!("../data/tests/hw_quote.dat");
+
+    /// Offset of the QE report in a version 3 quote
+    const QE_REPORT_OFFSET: usize = 48 + 384 + 4 + 64 + 64;
+
+    // common PKITs tests data
+    const TRUST_ANCHOR_ROOT_CERTIFICATE: &[u8] =
+        include_bytes!("../data/tests/pkits/certs/TrustAnchorRootCertificate.crt");
+    const TRUST_ANCHOR_ROOT_CRL: &[u8] =
+        include_bytes!("../data/tests/pkits/crls/TrustAnchorRootCRL.crl");
+    const GOOD_CA_CERT: &[u8] = include_bytes!("../data/tests/pkits/certs/GoodCACert.crt");
+    const GOOD_CA_CRL: &[u8] = include_bytes!("../data/tests/pkits/crls/GoodCACRL.crl");
+
+    // delta CRL tests data
+    const DELTA_CA: &[u8] = include_bytes!("../data/tests/delta_crl/ca.der");
+    const DELTA_GOOD_LEAF: &[u8] = include_bytes!("../data/tests/delta_crl/good_leaf.der");
+    const DELTA_BASE_REVOKED_LEAF: &[u8] =
+        include_bytes!("../data/tests/delta_crl/base_revoked_leaf.der");
+    const DELTA_REVOKED_LEAF: &[u8] =
+        include_bytes!("../data/tests/delta_crl/delta_revoked_leaf.der");
+    const BASE_CRL: &[u8] = include_bytes!("../data/tests/delta_crl/base.crl");
+    const DELTA_CRL: &[u8] = include_bytes!("../data/tests/delta_crl/delta.crl");
+    const DELTA_UNKNOWN_BASE_CRL: &[u8] =
+        include_bytes!("../data/tests/delta_crl/delta_unknown_base.crl");
+
+    #[test]
+    fn intel_sgx_root_verifies_intel_chain() {
+        let trust_anchor = TrustAnchor::intel_sgx_root();
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        assert!(pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA])
+            .verify(&trust_anchor, crl)
+            .is_ok());
+    }
+
+    #[test]
+    fn check_does_not_consume_chain() {
+        let old_root = TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE)
+            .expect("failed to parse root cert");
+        let new_root = TrustAnchor::intel_sgx_root();
+        let mut crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let chain = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]);
+
+        assert!(chain.check(&old_root, &mut crl).is_err());
+        assert!(chain.check(&new_root, &mut crl).is_ok());
+        assert!(chain.verify(&new_root, crl).is_ok());
+    }
+
+    #[test]
+    fn verify_with_any_tries_each_trust_anchor() {
+        let old_root = TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE)
+            .expect("failed to parse root cert");
+        let new_root = TrustAnchor::intel_sgx_root();
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let chain = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]);
+        assert!(chain.verify_with_any([&old_root, &new_root], crl).is_ok());
+    }
+
+    #[test]
+    fn verify_with_any_reports_last_failure() {
+        let old_root = TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE)
+            .expect("failed to parse root cert");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let chain = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]);
+        assert_matches!(
+            chain.verify_with_any([&old_root], crl),
+            Err(Error::CertificateVerification(reasons)) if reasons.contains(FailureReason::NotTrusted)
+        );
+    }
+
+    #[test]
+    fn verify_with_any_without_trust_anchors_fails() {
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let chain = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]);
+        assert!(matches!(
+            chain.verify_with_any([], crl),
+            Err(Error::NoTrustAnchor)
+        ));
+    }
+
+    #[test]
+    fn pinned_intel_sgx_root() {
+        assert_eq!(
+            PinnedTrustAnchor::intel_sgx_root().pin(),
+            &INTEL_SGX_ROOT_CA_SPKI_SHA256
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn error_source() {
+        use std::error::Error as _;
+
+        let der_error = der::Error::incomplete(1u8.into());
+        let error = Error::from(der_error);
+        let source = error.source().expect("missing source");
+        assert_eq!(source.to_string(), der_error.to_string());
+        assert!(Error::LeafMismatch.source().is_none());
+    }
+
+    #[test]
+    fn trust_anchor_fingerprint() {
+        assert_eq!(
+            hex::encode(TrustAnchor::intel_sgx_root().sha256_fingerprint()),
+            "44a0196b2b99f889b8e149e95b807a350e7424964399e885a7cbb8ccfab674d3"
+        );
+    }
+
+    #[test]
+    fn trust_anchor_equality() {
+        let intel_root = TrustAnchor::intel_sgx_root();
+        let root_ca = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let pkits_root = TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE)
+            .expect("failed to parse root cert");
+        assert_eq!(intel_root, root_ca);
+        assert_ne!(intel_root, pkits_root);
+    }
+
+    #[test]
+    fn trust_anchor_display() {
+        let display = TrustAnchor::intel_sgx_root().to_string();
+        assert!(display.contains("CN=Intel SGX Root CA"));
+        assert!(display.ends_with(
+            "(SHA-256: 44a0196b2b99f889b8e149e95b807a350e7424964399e885a7cbb8ccfab674d3)"
+        ));
+    }
+
+    #[test]
+    fn trust_anchor_from_pem() {
+        assert!(TrustAnchor::try_from_pem(ROOT_CA).is_ok());
+    }
+
+    #[test]
+    fn trust_anchor_from_bad_pem_fails() {
+        assert!(matches!(
+            TrustAnchor::try_from_pem(&ROOT_CA[1..]),
+            Err(Error::MbedTls(_))
+        ));
+    }
+
+    #[test]
+    fn trust_anchor_from_base64() {
+        let base64 = Base64::encode_string(TRUST_ANCHOR_ROOT_CERTIFICATE);
+        let trust_anchor = TrustAnchor::try_from_base64(&base64).expect("failed to parse base64");
+        let expected =
+            TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE).expect("failed to parse DER");
+        assert_eq!(trust_anchor, expected);
+    }
+
+    #[test]
+    fn trust_anchor_from_wrapped_base64() {
+        let base64 = Base64::encode_string(TRUST_ANCHOR_ROOT_CERTIFICATE);
+        let wrapped = base64
+            .as_bytes()
+            .chunks(64)
+            .map(|line| core::str::from_utf8(line).expect("base64 is ASCII"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(TrustAnchor::try_from_base64(&wrapped).is_ok());
+    }
+
+    #[test]
+    fn trust_anchor_from_bad_base64_fails() {
+        assert_eq!(
+            TrustAnchor::try_from_base64("not base64!").unwrap_err(),
+            Error::Base64(base64ct::Error::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn cert_chain_from_base64() {
+        let base64 = [
+            Base64::encode_string(GOOD_CA_CERT),
+            Base64::encode_string(TRUST_ANCHOR_ROOT_CERTIFICATE),
+        ];
+        let cert_chain =
+            UnverifiedCertChain::try_from_base64(&base64).expect("failed to parse cert chain");
+        let expected =
+            UnverifiedCertChain::try_from_der([GOOD_CA_CERT, TRUST_ANCHOR_ROOT_CERTIFICATE])
+                .expect("failed to parse cert chain");
+        assert_eq!(chain_ders(&cert_chain), chain_ders(&expected));
+    }
+
+    #[test]
+    fn crl_from_base64() {
+        let base64 = [
+            Base64::encode_string(TRUST_ANCHOR_ROOT_CRL),
+            Base64::encode_string(GOOD_CA_CRL),
+        ];
+        let crl = CertificateRevocationList::try_from_base64(base64).expect("failed to parse CRLs");
+        assert_eq!(crl.bases.len(), 2);
+    }
+
+    #[test]
+    fn trust_anchor_from_der() {
+        assert!(TrustAnchor::try_from_der(&TRUST_ANCHOR_ROOT_CERTIFICATE).is_ok());
+    }
+
+    #[test]
+    fn trust_anchor_from_bad_der_fails() {
+        assert!(matches!(
+            TrustAnchor::try_from_der(&TRUST_ANCHOR_ROOT_CERTIFICATE[1..]),
+            Err(Error::MbedTls(_))
+        ));
+    }
+
+    #[test]
+    fn cert_chain_from_one_der_cert() {
+        let cert_chain = UnverifiedCertChain::try_from_der([TRUST_ANCHOR_ROOT_CERTIFICATE])
+            .expect("failed to parse cert chain");
+        let count = cert_chain.len();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn cert_chain_from_multiple_der_certs() {
+        let cert_chain =
+            UnverifiedCertChain::try_from_der([GOOD_CA_CERT, TRUST_ANCHOR_ROOT_CERTIFICATE])
+                .expect("failed to parse cert chain");
+        let count = cert_chain.len();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn cert_chain_from_invalid_der_cert() {
+        assert!(matches!(
+            UnverifiedCertChain::try_from_der([&TRUST_ANCHOR_ROOT_CERTIFICATE[1..]]),
+            Err(Error::MbedTls(_))
+        ));
+    }
+
+    #[test]
+    fn cert_chain_from_pem_bundle() {
+        let bundle = [LEAF_CERT, PROCESSOR_CA, ROOT_CA].concat();
+        let cert_chain =
+            UnverifiedCertChain::try_from_pem_bundle(&bundle).expect("failed to parse bundle");
+        assert_eq!(
+            chain_ders(&cert_chain),
+            chain_ders(&pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]))
+        );
+    }
+
+    #[test]
+    fn cert_chain_from_pem_bundle_ignores_other_text() {
+        let bundle = [
+            "leaf\n",
+            LEAF_CERT,
+            include_str!("../data/tests/processor_crl.pem"),
+            ROOT_CA,
+            "\0",
+        ]
+        .concat();
+        let cert_chain =
+            UnverifiedCertChain::try_from_pem_bundle(&bundle).expect("failed to parse bundle");
+        assert_eq!(
+            chain_ders(&cert_chain),
+            chain_ders(&pem_chain(&[LEAF_CERT, ROOT_CA]))
+        );
+    }
+
+    #[test]
+    fn cert_chain_from_chain_ref() {
+        let der = [GOOD_CA_CERT, TRUST_ANCHOR_ROOT_CERTIFICATE].concat();
+        let chain_ref = CertChainRef::try_from_der(&der).expect("failed to borrow chain");
+        let cert_chain =
+            UnverifiedCertChain::try_from_chain_ref(chain_ref).expect("failed to parse cert chain");
+        assert_eq!(
+            chain_ders(&cert_chain),
+            [
+                GOOD_CA_CERT.to_vec(),
+                TRUST_ANCHOR_ROOT_CERTIFICATE.to_vec()
+            ]
+        );
+    }
+
+    #[test]
+    fn cert_chain_from_pkcs7() {
+        let cert_chain =
+            UnverifiedCertChain::try_from_pkcs7(include_bytes!("../data/tests/chain.p7b"))
+                .expect("failed to parse bundle");
+        assert_eq!(
+            chain_ders(&cert_chain),
+            chain_ders(&pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]))
+        );
+    }
+
+    #[test]
+    fn cert_chain_from_bad_pkcs7_fails() {
+        assert!(matches!(
+            UnverifiedCertChain::try_from_pkcs7(GOOD_CA_CERT),
+            Err(Error::Der(_))
+        ));
+    }
+
+    #[test]
+    fn cert_chain_from_empty_pem_bundle_fails() {
+        assert_eq!(
+            UnverifiedCertChain::try_from_pem_bundle("no certificates").unwrap_err(),
+            Error::MissingPem {
+                label: "CERTIFICATE".into()
+            }
+        );
+    }
+
+    #[test]
+    fn cert_chain_from_truncated_pem_bundle_fails() {
+        let bundle = [LEAF_CERT, &PROCESSOR_CA[..PROCESSOR_CA.len() / 2]].concat();
+        assert!(matches!(
+            UnverifiedCertChain::try_from_pem_bundle(&bundle),
+            Err(Error::Der(_))
+        ));
+    }
+
+    #[test]
+    fn crl_from_pem_bundle() {
+        let bundle = [
+            include_str!("../data/tests/root_crl.pem"),
+            include_str!("../data/tests/processor_crl.pem"),
+        ]
+        .concat();
+        let crl = CertificateRevocationList::try_from_pem_bundle(&bundle)
+            .expect("failed to parse bundle");
+        assert_eq!(crl.bases.len(), 2);
+    }
+
+    #[test]
+    fn crl_from_empty_pem_bundle_fails() {
+        assert_eq!(
+            CertificateRevocationList::try_from_pem_bundle(LEAF_CERT).unwrap_err(),
+            Error::MissingPem {
+                label: "X509 CRL".into()
+            }
+        );
+    }
+
+    #[test]
+    fn verify_with_callback_for_each_certificate() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let depths = alloc::sync::Arc::new(core::sync::atomic::AtomicUsize::new(0));
+        let seen = depths.clone();
+
+        let result = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]).verify_with(
+            &trust_anchor,
+            crl,
+            move |cert, depth, _flags| {
+                if depth == 0 {
+                    assert_eq!(
+                        cert.tbs_certificate.subject.to_string(),
+                        pem_subject(LEAF_CERT)
+                    );
+                }
+                seen.fetch_or(1 << depth, core::sync::atomic::Ordering::SeqCst);
+            },
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(depths.load(core::sync::atomic::Ordering::SeqCst), 0b111);
+    }
+
+    #[test]
+    fn verify_with_callback_rejecting_leaf_fails() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+
+        let result = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]).verify_with(
+            &trust_anchor,
+            crl,
+            |_cert, depth, flags| {
+                if depth == 0 {
+                    flags.insert(VerifyError::CERT_OTHER);
+                }
+            },
+        );
+
+        assert_matches!(
+            result.unwrap_err(),
+            Error::CertificateVerification(reasons) if reasons.contains(FailureReason::Other)
+        );
+    }
+
+    #[test]
+    fn failure_reasons_from_flags() {
+        let reasons = FailureReasons::from(VerifyError::CERT_REVOKED | VerifyError::CRL_EXPIRED);
+        assert!(reasons.contains(FailureReason::Revoked));
+        assert!(reasons.contains(FailureReason::CrlExpired));
+        assert!(!reasons.contains(FailureReason::Expired));
+        assert_eq!(
+            reasons.iter().collect::<Vec<_>>(),
+            [FailureReason::Revoked, FailureReason::CrlExpired]
+        );
+        assert_eq!(reasons.to_string(), "certificate revoked, CRL expired");
+    }
+
+    #[test]
+    fn failure_reasons_display_in_error() {
+        let error = Error::CertificateVerification(
+            (VerifyError::CERT_EXPIRED | VerifyError::CERT_NOT_TRUSTED).into(),
+        );
+        assert_eq!(
+            error.to_string(),
+            "The certificate chain failed verification: certificate expired, certificate not trusted"
+        );
+        assert_eq!(
+            CertificateChainVerifierError::from(error),
+            CertificateChainVerifierError::SignatureVerification
+        );
+    }
+
+    #[test]
+    fn empty_failure_reasons() {
+        let reasons = FailureReasons::from(VerifyError::empty());
+        assert!(reasons.is_empty());
+        assert_eq!(reasons.iter().count(), 0);
+        assert_eq!(reasons.to_string(), "unknown reason");
+    }
+
+    #[test]
+    fn verify_valid_cert_chain() {
+        let chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crls = [ROOT_CRL, PROCESSOR_CRL]
+            .iter()
+            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .collect::<Vec<_>>();
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+        assert!(verifier
+            .verify_certificate_chain(chain.iter(), crls.iter(), None)
+            .is_ok());
+    }
+
+    #[test]
+    fn invalid_cert_chain() {
+        let chain = [LEAF_CERT, ROOT_CA]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crls = [ROOT_CRL, PROCESSOR_CRL]
+            .iter()
+            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .collect::<Vec<_>>();
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), crls.iter(), None),
+            Err(CertificateChainVerifierError::SignatureVerification)
+        );
+    }
+
+    #[test]
+    fn unordered_cert_chain_succeeds() {
+        let chain = [PROCESSOR_CA, ROOT_CA, LEAF_CERT]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crls = [ROOT_CRL, PROCESSOR_CRL]
+            .iter()
+            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .collect::<Vec<_>>();
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+        assert!(verifier
+            .verify_certificate_chain(chain.iter(), crls.iter(), None)
+            .is_ok());
+    }
+
+    #[test]
+    fn policy_checked_after_verification() {
+        let chain = [PROCESSOR_CA, ROOT_CA, LEAF_CERT]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crls = [ROOT_CRL, PROCESSOR_CRL]
+            .iter()
+            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .collect::<Vec<_>>();
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor.clone())
+            .with_policy(ChainPolicy::new().enforce_name_constraints());
+        assert!(verifier
+            .verify_certificate_chain(chain.iter(), crls.iter(), None)
+            .is_ok());
+
+        // The Intel PCK certificates don't have an extended key usage
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor).with_policy(
+            ChainPolicy::new()
+                .require_leaf_extended_key_usage(der::oid::db::rfc5280::ID_KP_CLIENT_AUTH),
+        );
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), crls.iter(), None),
+            Err(CertificateChainVerifierError::ExtendedKeyUsage)
+        );
+    }
+
+    #[test]
+    fn validity_checked_with_options() {
+        let chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crls = [ROOT_CRL, PROCESSOR_CRL]
+            .iter()
+            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .collect::<Vec<_>>();
+        // The processor CRL's next update is 2023-05-21T22:00:36Z
+        let time = "2023-05-21T22:00:40Z"
+            .parse::<DateTime>()
+            .expect("failed to parse time");
+
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor.clone());
+        assert!(verifier
+            .verify_certificate_chain(chain.iter(), crls.iter(), time)
+            .is_ok());
+
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor.clone())
+            .with_options(VerificationOptions::default());
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), crls.iter(), time),
+            Err(CertificateChainVerifierError::CrlExpired)
+        );
+        assert!(verifier
+            .verify_certificate_chain(chain.iter(), crls.iter(), None)
+            .is_ok());
+
+        let verifier =
+            MbedTlsCertificateChainVerifier::new(trust_anchor).with_options(VerificationOptions {
+                max_clock_skew: core::time::Duration::from_secs(60),
+                ..Default::default()
+            });
+        assert!(verifier
+            .verify_certificate_chain(chain.iter(), crls.iter(), time)
+            .is_ok());
+    }
+
+    #[test]
+    fn unknown_critical_extension_reported_before_parsing() {
+        let chain = [
+            Certificate::from_der(include_bytes!("../data/tests/cove/evidence.der"))
+                .expect("failed to parse cert"),
+        ];
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor.clone());
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), [], None),
+            Err(CertificateChainVerifierError::GeneralCertificateError)
+        );
+
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor)
+            .with_policy(ChainPolicy::new().reject_unknown_critical_extensions());
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), [], None),
+            Err(CertificateChainVerifierError::UnknownCriticalExtension(
+                der::oid::ObjectIdentifier::new_unwrap("2.23.133.5.4.1")
+            ))
+        );
+    }
+
+    fn pem_chain(pems: &[&str]) -> UnverifiedCertChain {
+        let chain = pems
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        UnverifiedCertChain::try_from_certificates(&chain).expect("failed to parse cert chain")
+    }
+
+    fn chain_ders(chain: &UnverifiedCertChain) -> Vec<Vec<u8>> {
+        chain
+            .certs
+            .iter()
+            .map(|cert| cert.as_der().to_vec())
+            .collect()
+    }
+
+    fn pem_subject(pem: &str) -> String {
+        let cert = Certificate::from_pem(pem).expect("failed to parse cert");
+        cert.tbs_certificate.subject.to_string()
+    }
+
+    #[parameterized(
+        unordered = { &[PROCESSOR_CA, ROOT_CA, LEAF_CERT] },
+        reversed = { &[ROOT_CA, PROCESSOR_CA, LEAF_CERT] },
+        ordered = { &[LEAF_CERT, PROCESSOR_CA, ROOT_CA] },
+    )]
+    fn into_ordered_chain(pems: &[&str]) {
+        let ordered = pem_chain(pems)
+            .into_ordered()
+            .expect("failed to order chain");
+        let expected = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]);
+        assert_eq!(chain_ders(&ordered), chain_ders(&expected));
+    }
+
+    #[test]
+    fn contains_self_signed() {
+        let chain = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]);
+        assert_eq!(chain.contains_self_signed(), Ok(true));
+        let chain = pem_chain(&[LEAF_CERT, PROCESSOR_CA]);
+        assert_eq!(chain.contains_self_signed(), Ok(false));
+    }
+
+    #[test]
+    fn strip_self_signed_root_matching_trust_anchor() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let stripped = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA])
+            .strip_self_signed_root(&trust_anchor)
+            .expect("failed to strip root");
+        let expected = pem_chain(&[LEAF_CERT, PROCESSOR_CA]);
+        assert_eq!(chain_ders(&stripped), chain_ders(&expected));
+    }
+
+    #[test]
+    fn strip_self_signed_root_without_root() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let stripped = pem_chain(&[LEAF_CERT, PROCESSOR_CA])
+            .strip_self_signed_root(&trust_anchor)
+            .expect("failed to strip root");
+        let expected = pem_chain(&[LEAF_CERT, PROCESSOR_CA]);
+        assert_eq!(chain_ders(&stripped), chain_ders(&expected));
+    }
+
+    #[test]
+    fn strip_self_signed_root_other_than_trust_anchor_fails() {
+        let trust_anchor = TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE)
+            .expect("failed to parse root cert");
+        assert_eq!(
+            pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA])
+                .strip_self_signed_root(&trust_anchor)
+                .unwrap_err(),
+            Error::EmbeddedRootMismatch {
+                subject: pem_subject(ROOT_CA)
+            }
+        );
+    }
+
+    #[test]
+    fn into_ordered_without_root() {
+        let ordered = pem_chain(&[PROCESSOR_CA, LEAF_CERT])
+            .into_ordered()
+            .expect("failed to order chain");
+        let expected = pem_chain(&[LEAF_CERT, PROCESSOR_CA]);
+        assert_eq!(chain_ders(&ordered), chain_ders(&expected));
+    }
 
-    // common PKITs tests data
-    const TRUST_ANCHOR_ROOT_CERTIFICATE: &[u8] =
-        include_bytes!("../data/tests/pkits/certs/TrustAnchorRootCertificate.crt");
-    const TRUST_ANCHOR_ROOT_CRL: &[u8] =
-        include_bytes!("../data/tests/pkits/crls/TrustAnchorRootCRL.crl");
-    const GOOD_CA_CERT: &[u8] = include_bytes!("../data/tests/pkits/certs/GoodCACert.crt");
-    const GOOD_CA_CRL: &[u8] = include_bytes!("../data/tests/pkits/crls/GoodCACRL.crl");
+    #[test]
+    fn into_ordered_empty_chain() {
+        let ordered = UnverifiedCertChain::try_from_der([] as [&[u8]; 0])
+            .expect("failed to parse cert chain")
+            .into_ordered()
+            .expect("failed to order chain");
+        assert!(chain_ders(&ordered).is_empty());
+    }
 
     #[test]
-    fn trust_anchor_from_pem() {
-        assert!(TrustAnchor::try_from_pem(ROOT_CA).is_ok());
+    fn into_ordered_keeps_max_depth() {
+        let ordered = pem_chain(&[PROCESSOR_CA, ROOT_CA, LEAF_CERT])
+            .with_max_depth(3)
+            .into_ordered()
+            .expect("failed to order chain");
+        assert_eq!(ordered.max_depth, Some(3));
+        assert_eq!(ordered.len(), 3);
     }
 
     #[test]
-    fn trust_anchor_from_bad_pem_fails() {
+    fn into_ordered_too_deep() {
+        let chain = pem_chain(&[PROCESSOR_CA, ROOT_CA, LEAF_CERT]).with_max_depth(2);
+        assert_eq!(
+            chain.into_ordered().unwrap_err(),
+            Error::ChainTooDeep {
+                depth: 3,
+                max_depth: 2
+            }
+        );
+    }
+
+    #[test]
+    fn verify_too_deep_chain_fails() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let chain = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]).with_max_depth(2);
+
+        let (result, report) = chain.clone().verify_with_report(&trust_anchor, crl);
+        assert_eq!(
+            result.unwrap_err(),
+            Error::ChainTooDeep {
+                depth: 3,
+                max_depth: 2
+            }
+        );
+        assert!(report.certificates().is_empty());
+
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        assert!(chain.with_max_depth(3).verify(&trust_anchor, crl).is_ok());
+    }
+
+    #[test]
+    fn verifier_rejects_too_deep_chain() {
+        let chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor).with_max_chain_depth(2);
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), [], None),
+            Err(CertificateChainVerifierError::GeneralCertificateError)
+        );
+    }
+
+    #[test]
+    fn into_ordered_duplicate_certificate() {
+        let chain = pem_chain(&[LEAF_CERT, PROCESSOR_CA, LEAF_CERT]);
+        assert_eq!(
+            chain.into_ordered().unwrap_err(),
+            Error::DuplicateCertificate {
+                subject: pem_subject(LEAF_CERT)
+            }
+        );
+    }
+
+    #[test]
+    fn into_ordered_gap_in_chain() {
+        let chain = pem_chain(&[ROOT_CA, LEAF_CERT]);
+        assert_eq!(
+            chain.into_ordered().unwrap_err(),
+            Error::CertificateChainGap {
+                subject: pem_subject(LEAF_CERT),
+                issuer: pem_subject(PROCESSOR_CA),
+            }
+        );
+    }
+
+    #[test]
+    fn into_ordered_unrelated_certificate() {
+        let intel = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
+            .iter()
+            .map(|cert| {
+                Certificate::from_pem(cert)
+                    .and_then(|cert| cert.to_der())
+                    .expect("failed to parse cert")
+            })
+            .collect::<Vec<_>>();
+        let chain = UnverifiedCertChain::try_from_der(
+            intel.iter().map(Vec::as_slice).chain([GOOD_CA_CERT]),
+        )
+        .expect("failed to parse cert chain");
+        let good_ca = Certificate::from_der(GOOD_CA_CERT).expect("failed to parse cert");
+        assert_eq!(
+            chain.into_ordered().unwrap_err(),
+            Error::CertificateNotInPath {
+                subject: good_ca.tbs_certificate.subject.to_string()
+            }
+        );
+    }
+
+    // The below tests are from the
+    // [Public Key Infrastructure Test Suite)[https://csrc.nist.gov/projects/pki-testing]
+    // also known as PKITS. The numbers in the test names are the sections in
+    // the test description document,
+    // <https://csrc.nist.gov/CSRC/media/Projects/PKI-Testing/documents/PKITS.pdf>
+    //
+    // The point of the tests are to show that we've correctly hooked up CRL
+    // logic with mbedtls, not to test out mbedtls with respect to PKITS.
+    #[test]
+    fn missing_crl_4_4_1() {
+        const NO_CRL_CA_CERT: &[u8] = include_bytes!("../data/tests/pkits/certs/NoCRLCACert.crt");
+        const INVALID_MISSING_CRL_TEST1_EE: &[u8] =
+            include_bytes!("../data/tests/pkits/certs/InvalidMissingCRLTest1EE.crt");
+        let ders = [
+            INVALID_MISSING_CRL_TEST1_EE,
+            NO_CRL_CA_CERT,
+            TRUST_ANCHOR_ROOT_CERTIFICATE,
+        ];
+        let chain = ders
+            .iter()
+            .map(|der| Certificate::from_der(der).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let trust_anchor = TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE)
+            .expect("failed to parse root cert");
+        let crls = [TRUST_ANCHOR_ROOT_CRL]
+            .iter()
+            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .collect::<Vec<_>>();
+
+        // As the name suggests, this test should fail, however Mbedtls doesn't
+        // seem to conform to the RFC,
+        // <https://datatracker.ietf.org/doc/html/rfc5280#section-6.3.3>
+        // It's a bit hard to parse in the RFC, but when CRLs are the
+        // invalidation mechanism there should be a CRL for every CA.
+        //
+        // > After processing such CRLs, if the revocation status has
+        // > still not been determined, then return the cert_status
+        // > UNDETERMINED.
+        //
+        // It looks like mbedtls will ignore the missing CRL and continue,
+        // <https://github.com/mobilecoinfoundation/rust-mbedtls/blob/6d8fe323a3292f87a6bce4b35963d47139a583f9/mbedtls-sys/vendor/library/x509_crt.c#L2337>
+        //
+        // > Skip validation if no CRL for the given CA is present.
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+        assert!(verifier
+            .verify_certificate_chain(chain.iter(), crls.iter(), None)
+            .is_ok());
+    }
+
+    #[test]
+    fn invalid_revoked_ca_4_4_2() {
+        const REVOKED_SUB_CA_CERT: &[u8] =
+            include_bytes!("../data/tests/pkits/certs/RevokedsubCACert.crt");
+        const INVALID_REVOKED_CA_TEST2_EE: &[u8] =
+            include_bytes!("../data/tests/pkits/certs/InvalidRevokedCATest2EE.crt");
+        const REVOKED_SUB_CA_CRL: &[u8] =
+            include_bytes!("../data/tests/pkits/crls/RevokedsubCACRL.crl");
+        let ders = [
+            INVALID_REVOKED_CA_TEST2_EE,
+            REVOKED_SUB_CA_CERT,
+            GOOD_CA_CERT,
+            TRUST_ANCHOR_ROOT_CERTIFICATE,
+        ];
+        let chain = ders
+            .iter()
+            .map(|der| Certificate::from_der(der).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let trust_anchor = TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE)
+            .expect("failed to parse root cert");
+        let crls = [REVOKED_SUB_CA_CRL, GOOD_CA_CRL, TRUST_ANCHOR_ROOT_CRL]
+            .iter()
+            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .collect::<Vec<_>>();
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), crls.iter(), None),
+            Err(CertificateChainVerifierError::SignatureVerification)
+        );
+    }
+
+    #[test]
+    fn invalid_revoked_ee_4_4_3() {
+        const INVALID_REVOKED_EE_TEST3_EE: &[u8] =
+            include_bytes!("../data/tests/pkits/certs/InvalidRevokedEETest3EE.crt");
+        let ders = [
+            INVALID_REVOKED_EE_TEST3_EE,
+            GOOD_CA_CERT,
+            TRUST_ANCHOR_ROOT_CERTIFICATE,
+        ];
+        let chain = ders
+            .iter()
+            .map(|der| Certificate::from_der(der).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let trust_anchor = TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE)
+            .expect("failed to parse root cert");
+        let crls = [GOOD_CA_CRL, TRUST_ANCHOR_ROOT_CRL]
+            .iter()
+            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .collect::<Vec<_>>();
+        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), crls.iter(), None),
+            Err(CertificateChainVerifierError::SignatureVerification)
+        );
+    }
+
+    #[test]
+    fn invalid_bad_crl_signature_4_4_4() {
+        const BAD_CRL_SIGNATURE_CA_CRL: &[u8] =
+            include_bytes!("../data/tests/pkits/crls/BadCRLSignatureCACRL.crl");
+        // The CRL signature is invalid so parsing the CRL will fail
         assert!(matches!(
-            TrustAnchor::try_from_pem(&ROOT_CA[1..]),
+            CertificateRevocationList::try_from_der([
+                BAD_CRL_SIGNATURE_CA_CRL,
+                TRUST_ANCHOR_ROOT_CRL
+            ]),
             Err(Error::MbedTls(_))
         ));
     }
 
     #[test]
-    fn trust_anchor_from_der() {
-        assert!(TrustAnchor::try_from_der(&TRUST_ANCHOR_ROOT_CERTIFICATE).is_ok());
-    }
+    fn base_and_delta_crl_from_der() {
+        let crl = CertificateRevocationList::try_from_der([BASE_CRL, DELTA_CRL])
+            .expect("failed to parse CRLs");
+        assert_eq!(crl.bases.len(), 1);
+        assert_eq!(crl.deltas.len(), 1);
+        assert_eq!(
+            crl.delta_crl_uris(),
+            vec![String::from("http://example.com/delta.crl")]
+        );
+    }
+
+    #[test]
+    fn delta_crl_before_base_crl() {
+        let crl = CertificateRevocationList::try_from_der([DELTA_CRL, BASE_CRL])
+            .expect("failed to parse CRLs");
+        assert_eq!(crl.deltas.len(), 1);
+    }
+
+    #[test]
+    fn delta_crl_without_base_fails() {
+        assert_eq!(
+            CertificateRevocationList::try_from_der([DELTA_CRL]).unwrap_err(),
+            Error::DeltaCrlWithoutBase
+        );
+    }
+
+    #[test]
+    fn delta_crl_with_unknown_base_fails() {
+        assert_eq!(
+            CertificateRevocationList::try_from_der([BASE_CRL, DELTA_UNKNOWN_BASE_CRL])
+                .unwrap_err(),
+            Error::DeltaCrlWithoutBase
+        );
+    }
+
+    #[test]
+    fn most_recent_delta_crl_is_kept() {
+        let delta = CertificateList::from_der(DELTA_CRL).expect("failed to parse CRL");
+        let base = CertificateList::from_der(BASE_CRL).expect("failed to parse CRL");
+        let crl = CertificateRevocationList::try_from_crls([&delta, &base, &delta])
+            .expect("failed to parse CRLs");
+        assert_eq!(crl.deltas.len(), 1);
+        assert_eq!(
+            crl.deltas[0].number,
+            Uint::new(&[2]).expect("invalid number")
+        );
+    }
+
+    #[test]
+    fn extend_with_delta_for_existing_base() {
+        let mut crl =
+            CertificateRevocationList::try_from_der([BASE_CRL]).expect("failed to parse CRLs");
+        crl.try_extend_from_der([DELTA_CRL])
+            .expect("failed to extend CRLs");
+        assert_eq!(crl.bases.len(), 1);
+        assert_eq!(crl.deltas.len(), 1);
+    }
+
+    #[test]
+    fn extend_failure_leaves_crl_unchanged() {
+        let mut crl =
+            CertificateRevocationList::try_from_der([BASE_CRL]).expect("failed to parse CRLs");
+        assert_eq!(
+            crl.try_extend_from_der([TRUST_ANCHOR_ROOT_CRL, DELTA_UNKNOWN_BASE_CRL])
+                .unwrap_err(),
+            Error::DeltaCrlWithoutBase
+        );
+        assert_eq!(crl.bases.len(), 1);
+        assert_eq!(crl.base_ders, [BASE_CRL.to_vec()]);
+        assert!(crl.deltas.is_empty());
+    }
+
+    #[test]
+    fn merge_crls() {
+        let mut crl = CertificateRevocationList::try_from_der([TRUST_ANCHOR_ROOT_CRL])
+            .expect("failed to parse CRLs");
+        let other = CertificateRevocationList::try_from_der([BASE_CRL, DELTA_CRL])
+            .expect("failed to parse CRLs");
+        crl.merge(other).expect("failed to merge CRLs");
+        assert_eq!(crl.bases.len(), 2);
+        assert_eq!(crl.deltas.len(), 1);
+        assert_eq!(
+            crl.base_ders,
+            [TRUST_ANCHOR_ROOT_CRL.to_vec(), BASE_CRL.to_vec()]
+        );
+    }
+
+    #[test]
+    fn reuse_crl_across_verifications() {
+        let trust_anchor = TrustAnchor::try_from_der(DELTA_CA).expect("failed to parse root cert");
+        let mut crl = CertificateRevocationList::try_from_der([BASE_CRL, DELTA_CRL])
+            .expect("failed to parse CRLs");
+        for (leaf, expected) in [
+            (DELTA_GOOD_LEAF, Ok(())),
+            (DELTA_REVOKED_LEAF, Err(Error::CertificateRevoked)),
+            (DELTA_GOOD_LEAF, Ok(())),
+        ] {
+            let chain = UnverifiedCertChain::try_from_der([leaf, DELTA_CA])
+                .expect("failed to parse cert chain");
+            assert_eq!(chain.verify(&trust_anchor, &mut crl).map(|_| ()), expected);
+        }
+
+        let (result, _) = UnverifiedCertChain::try_from_der([DELTA_REVOKED_LEAF, DELTA_CA])
+            .expect("failed to parse cert chain")
+            .verify_with_report(&trust_anchor, crl.clone());
+        assert_eq!(result.map(|_| ()), Err(Error::CertificateRevoked));
+    }
+
+    #[test]
+    fn base_crl_without_freshest_crl_has_no_delta_uris() {
+        let crl = CertificateRevocationList::try_from_der([TRUST_ANCHOR_ROOT_CRL, GOOD_CA_CRL])
+            .expect("failed to parse CRLs");
+        assert!(crl.delta_crl_uris().is_empty());
+    }
+
+    #[parameterized(
+        good_leaf = { DELTA_GOOD_LEAF, Ok(()) },
+        revoked_by_base = { DELTA_BASE_REVOKED_LEAF, Err(Error::CertificateVerification(VerifyError::CERT_REVOKED.into())) },
+        revoked_by_delta = { DELTA_REVOKED_LEAF, Err(Error::CertificateRevoked) },
+    )]
+    fn verify_with_delta_crl(leaf: &[u8], expected: Result<()>) {
+        let trust_anchor = TrustAnchor::try_from_der(DELTA_CA).expect("failed to parse root cert");
+        let chain = UnverifiedCertChain::try_from_der([leaf, DELTA_CA])
+            .expect("failed to parse cert chain");
+        let crl = CertificateRevocationList::try_from_der([BASE_CRL, DELTA_CRL])
+            .expect("failed to parse CRLs");
+        assert_eq!(chain.verify(&trust_anchor, crl).map(|_| ()), expected);
+    }
+
+    #[parameterized(
+        before_this_update = { "2026-10-16T10:18:49Z", Err(Error::DeltaCrlNotYetValid) },
+        at_this_update = { "2026-10-16T10:18:50Z", Ok(()) },
+        before_next_update = { "2126-09-22T10:18:49Z", Ok(()) },
+        at_next_update = { "2126-09-22T10:18:50Z", Err(Error::DeltaCrlExpired) },
+    )]
+    fn delta_crl_validity(time: &str, expected: Result<()>) {
+        let delta = DeltaCrl::try_from_der(DELTA_CRL).expect("failed to parse delta CRL");
+        let time = time.parse::<DateTime>().expect("failed to parse time");
+        assert_eq!(delta.check_validity(time), expected);
+    }
+
+    #[test]
+    fn verify_with_expired_delta_crl_fails() {
+        let trust_anchor = TrustAnchor::try_from_der(DELTA_CA).expect("failed to parse root cert");
+        let chain = UnverifiedCertChain::try_from_der([DELTA_GOOD_LEAF, DELTA_CA])
+            .expect("failed to parse cert chain");
+        let crl = CertificateRevocationList::try_from_der([BASE_CRL, DELTA_CRL])
+            .expect("failed to parse CRLs");
+        let time = "2127-01-01T00:00:00Z"
+            .parse::<DateTime>()
+            .expect("failed to parse time");
+
+        assert_eq!(
+            chain.with_time(time).verify(&trust_anchor, crl).map(|_| ()),
+            Err(Error::DeltaCrlExpired)
+        );
+    }
+
+    #[parameterized(
+        good_leaf = { DELTA_GOOD_LEAF, RevocationStatus::NotRevoked },
+        revoked_by_base = { DELTA_BASE_REVOKED_LEAF, RevocationStatus::Revoked },
+        revoked_by_delta = { DELTA_REVOKED_LEAF, RevocationStatus::Revoked },
+    )]
+    fn report_with_delta_crl(leaf: &[u8], status: RevocationStatus) {
+        let trust_anchor = TrustAnchor::try_from_der(DELTA_CA).expect("failed to parse root cert");
+        let chain = UnverifiedCertChain::try_from_der([leaf, DELTA_CA])
+            .expect("failed to parse cert chain");
+        let crl = CertificateRevocationList::try_from_der([BASE_CRL, DELTA_CRL])
+            .expect("failed to parse CRLs");
+
+        let (result, report) = chain.verify_with_report(&trust_anchor, crl);
+
+        assert_eq!(result.is_ok(), report.failure().is_none());
+        assert_eq!(report.certificates().len(), 2);
+        let leaf_report = &report.certificates()[0];
+        assert_eq!(leaf_report.revocation_status(), status);
+        assert_eq!(
+            leaf_report.checks(),
+            [
+                CertificateCheck::IssuerSignature,
+                CertificateCheck::Revocation,
+                CertificateCheck::DeltaRevocation
+            ]
+        );
+        assert_eq!(
+            report.certificates()[1].revocation_status(),
+            RevocationStatus::NotRevoked
+        );
+    }
+
+    #[test]
+    fn report_for_valid_cert_chain() {
+        let chain = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]);
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+
+        let (result, report) = chain.verify_with_report(&trust_anchor, crl);
+
+        assert!(result.is_ok());
+        assert_eq!(report.failure(), None);
+        let subjects = report
+            .certificates()
+            .iter()
+            .map(|certificate| certificate.subject().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            subjects,
+            [LEAF_CERT, PROCESSOR_CA, ROOT_CA].map(pem_subject).to_vec()
+        );
+        for certificate in report.certificates() {
+            assert_eq!(
+                certificate.revocation_status(),
+                RevocationStatus::NotRevoked
+            );
+            assert_eq!(
+                certificate.checks(),
+                [
+                    CertificateCheck::IssuerSignature,
+                    CertificateCheck::Revocation
+                ]
+            );
+            assert!(certificate.not_before() < certificate.not_after());
+        }
+
+        let leaf = Certificate::from_pem(LEAF_CERT).expect("failed to parse cert");
+        assert_eq!(
+            report.certificates()[0].serial_number(),
+            hex::encode(leaf.tbs_certificate.serial_number.as_bytes())
+        );
+    }
+
+    #[test]
+    fn report_without_crls() {
+        let chain = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA]);
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crl =
+            CertificateRevocationList::try_from_der([ROOT_CRL]).expect("failed to parse CRLs");
 
-    #[test]
-    fn trust_anchor_from_bad_der_fails() {
-        assert!(matches!(
-            TrustAnchor::try_from_der(&TRUST_ANCHOR_ROOT_CERTIFICATE[1..]),
-            Err(Error::MbedTls(_))
-        ));
-    }
+        let (_, report) = chain.verify_with_report(&trust_anchor, crl);
 
-    #[test]
-    fn cert_chain_from_one_der_cert() {
-        let cert_chain = UnverifiedCertChain::try_from_der([TRUST_ANCHOR_ROOT_CERTIFICATE])
-            .expect("failed to parse cert chain");
-        let count = cert_chain.0.iter().count();
-        assert_eq!(count, 1);
+        let statuses = report
+            .certificates()
+            .iter()
+            .map(CertificateReport::revocation_status)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [
+                RevocationStatus::NoCrl,
+                RevocationStatus::NotRevoked,
+                RevocationStatus::NotRevoked
+            ]
+        );
+        assert_eq!(
+            report.certificates()[0].checks(),
+            [CertificateCheck::IssuerSignature]
+        );
     }
 
     #[test]
-    fn cert_chain_from_multiple_der_certs() {
-        let cert_chain =
-            UnverifiedCertChain::try_from_der([GOOD_CA_CERT, TRUST_ANCHOR_ROOT_CERTIFICATE])
-                .expect("failed to parse cert chain");
-        let count = cert_chain.0.iter().count();
-        assert_eq!(count, 2);
-    }
+    fn report_for_invalid_cert_chain() {
+        let chain = pem_chain(&[LEAF_CERT, ROOT_CA]);
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
 
-    #[test]
-    fn cert_chain_from_invalid_der_cert() {
-        assert!(matches!(
-            UnverifiedCertChain::try_from_der([&TRUST_ANCHOR_ROOT_CERTIFICATE[1..]]),
-            Err(Error::MbedTls(_))
-        ));
+        let (result, report) = chain.verify_with_report(&trust_anchor, crl);
+
+        let error = result.expect_err("chain should not verify");
+        let failure = report.failure().expect("report should have a failure");
+        assert!(failure.starts_with(&error.to_string()));
+        assert_eq!(report.certificates().len(), 2);
+        assert!(report
+            .to_string()
+            .ends_with(&alloc::format!("Verification failed: {failure}")));
     }
 
     #[test]
-    fn verify_valid_cert_chain() {
-        let chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
+    fn verify_revoked_by_delta_crl_through_verifier() {
+        let chain = [DELTA_REVOKED_LEAF, DELTA_CA]
             .iter()
-            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .map(|cert| Certificate::from_der(cert).expect("failed to parse cert"))
             .collect::<Vec<_>>();
-        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
-        let crls = [ROOT_CRL, PROCESSOR_CRL]
+        let crls = [BASE_CRL, DELTA_CRL]
             .iter()
             .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
             .collect::<Vec<_>>();
+        let trust_anchor = TrustAnchor::try_from_der(DELTA_CA).expect("failed to parse root cert");
         let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
-        assert!(verifier
-            .verify_certificate_chain(chain.iter(), crls.iter(), None)
-            .is_ok());
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), crls.iter(), None),
+            Err(CertificateChainVerifierError::CertificateRevoked)
+        );
     }
 
     #[test]
-    fn invalid_cert_chain() {
-        let chain = [LEAF_CERT, ROOT_CA]
+    fn verify_with_expired_delta_crl_through_verifier_fails() {
+        let chain = [DELTA_GOOD_LEAF, DELTA_CA]
             .iter()
-            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .map(|cert| Certificate::from_der(cert).expect("failed to parse cert"))
             .collect::<Vec<_>>();
-        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
-        let crls = [ROOT_CRL, PROCESSOR_CRL]
+        let crls = [BASE_CRL, DELTA_CRL]
             .iter()
             .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
             .collect::<Vec<_>>();
+        let trust_anchor = TrustAnchor::try_from_der(DELTA_CA).expect("failed to parse root cert");
         let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+        let time = "2127-01-01T00:00:00Z"
+            .parse::<DateTime>()
+            .expect("failed to parse time");
         assert_eq!(
-            verifier.verify_certificate_chain(chain.iter(), crls.iter(), None),
-            Err(CertificateChainVerifierError::SignatureVerification)
+            verifier.verify_certificate_chain(chain.iter(), crls.iter(), time),
+            Err(CertificateChainVerifierError::CrlExpired)
         );
     }
 
+    fn fetch_intel_crl(uri: &str) -> core::result::Result<Vec<u8>, &'static str> {
+        match uri {
+            "https://certificates.trustedservices.intel.com/IntelSGXRootCA.der" => {
+                Ok(ROOT_CRL.to_vec())
+            }
+            "https://api.trustedservices.intel.com/sgx/certification/v3/pckcrl?ca=processor&encoding=der" => {
+                Ok(include_bytes!("../data/tests/processor_crl.pem").to_vec())
+            }
+            _ => Err("unknown URI"),
+        }
+    }
+
     #[test]
-    fn unordered_cert_chain_succeeds() {
-        let chain = [PROCESSOR_CA, ROOT_CA, LEAF_CERT]
+    fn fetch_crls_for_cert_chain() {
+        let chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
             .iter()
             .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
             .collect::<Vec<_>>();
+        let mut fetched = Vec::new();
+        let crl = fetch_crls(&chain, |uri| {
+            fetched.push(String::from(uri));
+            fetch_intel_crl(uri)
+        })
+        .expect("failed to fetch CRLs");
+
+        // The processor CA and root CA share the same CRL
+        assert_eq!(fetched.len(), 2);
+        assert_eq!(crl.bases.len(), 2);
+
         let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let unverified =
+            UnverifiedCertChain::try_from_certificates(&chain).expect("failed to parse cert chain");
+        assert!(unverified.verify(&trust_anchor, crl).is_ok());
+    }
+
+    #[test]
+    fn fetch_crls_fails_to_fetch() {
+        let chain = [Certificate::from_pem(LEAF_CERT).expect("failed to parse cert")];
+        let error = fetch_crls(&chain, |_| Err("network is down")).unwrap_err();
+        assert_eq!(
+            error,
+            Error::CrlFetch {
+                uri: String::from("https://api.trustedservices.intel.com/sgx/certification/v3/pckcrl?ca=processor&encoding=der"),
+                error: String::from("network is down"),
+            }
+        );
+    }
+
+    const INTEL_ROOT_CA_PIN: [u8; 32] = [
+        0xa0, 0xaf, 0x03, 0x12, 0x89, 0xf5, 0xd5, 0xd4, 0x13, 0x2f, 0x91, 0x86, 0x06, 0x8a, 0x7f,
+        0xc1, 0x36, 0x28, 0x63, 0x3b, 0xa2, 0x35, 0x77, 0x74, 0x72, 0xe2, 0x9b, 0x6b, 0x6c, 0x67,
+        0xa4, 0x9e,
+    ];
+
+    #[test]
+    fn pinned_trust_anchor_verifies_chain() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let pinned = PinnedTrustAnchor::try_new(trust_anchor, INTEL_ROOT_CA_PIN)
+            .expect("failed to pin trust anchor");
+        assert_eq!(pinned.pin(), &INTEL_ROOT_CA_PIN);
+
+        let chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
         let crls = [ROOT_CRL, PROCESSOR_CRL]
             .iter()
             .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
             .collect::<Vec<_>>();
-        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+        let verifier = MbedTlsCertificateChainVerifier::new(pinned);
         assert!(verifier
             .verify_certificate_chain(chain.iter(), crls.iter(), None)
             .is_ok());
     }
 
-    // The below tests are from the
-    // [Public Key Infrastructure Test Suite)[https://csrc.nist.gov/projects/pki-testing]
-    // also known as PKITS. The numbers in the test names are the sections in
-    // the test description document,
-    // <https://csrc.nist.gov/CSRC/media/Projects/PKI-Testing/documents/PKITS.pdf>
-    //
-    // The point of the tests are to show that we've correctly hooked up CRL
-    // logic with mbedtls, not to test out mbedtls with respect to PKITS.
     #[test]
-    fn missing_crl_4_4_1() {
-        const NO_CRL_CA_CERT: &[u8] = include_bytes!("../data/tests/pkits/certs/NoCRLCACert.crt");
-        const INVALID_MISSING_CRL_TEST1_EE: &[u8] =
-            include_bytes!("../data/tests/pkits/certs/InvalidMissingCRLTest1EE.crt");
-        let ders = [
-            INVALID_MISSING_CRL_TEST1_EE,
-            NO_CRL_CA_CERT,
-            TRUST_ANCHOR_ROOT_CERTIFICATE,
-        ];
-        let chain = ders
-            .iter()
-            .map(|der| Certificate::from_der(der).expect("failed to parse cert"))
-            .collect::<Vec<_>>();
-        let trust_anchor = TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE)
-            .expect("failed to parse root cert");
-        let crls = [TRUST_ANCHOR_ROOT_CRL]
+    fn pinned_trust_anchor_mismatch() {
+        let trust_anchor =
+            TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE).expect("failed to parse root");
+        assert_eq!(
+            PinnedTrustAnchor::try_new(trust_anchor, INTEL_ROOT_CA_PIN).unwrap_err(),
+            Error::TrustAnchorPinMismatch
+        );
+    }
+
+    #[test]
+    fn leaf_public_key_of_verified_chain() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
             .iter()
-            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
             .collect::<Vec<_>>();
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let verified = UnverifiedCertChain::try_from_certificates(&chain)
+            .expect("failed to parse cert chain")
+            .verify(&trust_anchor, crl)
+            .expect("failed to verify cert chain");
 
-        // As the name suggests, this test should fail, however Mbedtls doesn't
-        // seem to conform to the RFC,
-        // <https://datatracker.ietf.org/doc/html/rfc5280#section-6.3.3>
-        // It's a bit hard to parse in the RFC, but when CRLs are the
-        // invalidation mechanism there should be a CRL for every CA.
-        //
-        // > After processing such CRLs, if the revocation status has
-        // > still not been determined, then return the cert_status
-        // > UNDETERMINED.
-        //
-        // It looks like mbedtls will ignore the missing CRL and continue,
-        // <https://github.com/mobilecoinfoundation/rust-mbedtls/blob/6d8fe323a3292f87a6bce4b35963d47139a583f9/mbedtls-sys/vendor/library/x509_crt.c#L2337>
-        //
-        // > Skip validation if no CRL for the given CA is present.
-        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
-        assert!(verifier
-            .verify_certificate_chain(chain.iter(), crls.iter(), None)
-            .is_ok());
+        let expected = VerifyingKey::from_sec1_bytes(
+            chain[0]
+                .tbs_certificate
+                .subject_public_key_info
+                .subject_public_key
+                .raw_bytes(),
+        )
+        .expect("failed to decode leaf key");
+        assert_eq!(verified.leaf_public_key(), Ok(expected));
+    }
+
+    /// The PCK chain embedded in the hardware quote, verified
+    fn verified_quote_pck_chain() -> VerifiedCertChain {
+        let start = QUOTE_BYTES
+            .windows(5)
+            .position(|window| window == b"-----")
+            .expect("no PEM in quote");
+        let bundle = core::str::from_utf8(&QUOTE_BYTES[start..])
+            .expect("PEM is not UTF-8")
+            .trim_end_matches('\0');
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        UnverifiedCertChain::try_from_pem_bundle(bundle)
+            .expect("failed to parse cert chain")
+            .verify(&TrustAnchor::intel_sgx_root(), crl)
+            .expect("failed to verify cert chain")
     }
 
     #[test]
-    fn invalid_revoked_ca_4_4_2() {
-        const REVOKED_SUB_CA_CERT: &[u8] =
-            include_bytes!("../data/tests/pkits/certs/RevokedsubCACert.crt");
-        const INVALID_REVOKED_CA_TEST2_EE: &[u8] =
-            include_bytes!("../data/tests/pkits/certs/InvalidRevokedCATest2EE.crt");
-        const REVOKED_SUB_CA_CRL: &[u8] =
-            include_bytes!("../data/tests/pkits/crls/RevokedsubCACRL.crl");
-        let ders = [
-            INVALID_REVOKED_CA_TEST2_EE,
-            REVOKED_SUB_CA_CERT,
-            GOOD_CA_CERT,
-            TRUST_ANCHOR_ROOT_CERTIFICATE,
-        ];
-        let chain = ders
+    fn pck_cert_chain_from_quote() {
+        let quote = Quote3::try_from(QUOTE_BYTES).expect("failed to parse quote");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let verified = quote
+            .pck_cert_chain()
+            .expect("failed to extract PCK cert chain")
+            .verify(&TrustAnchor::intel_sgx_root(), crl)
+            .expect("failed to verify cert chain");
+        assert_eq!(verified.to_der_vec().len(), 3);
+    }
+
+    #[test]
+    fn verify_qe_report_with_pck_leaf() {
+        let qe_report = &QUOTE_BYTES[QE_REPORT_OFFSET..QE_REPORT_OFFSET + 384];
+        let signature =
+            Signature::from_slice(&QUOTE_BYTES[QE_REPORT_OFFSET + 384..QE_REPORT_OFFSET + 448])
+                .expect("failed to decode signature");
+        let verified = verified_quote_pck_chain();
+        assert_eq!(verified.verify_qe_report(qe_report, &signature), Ok(()));
+
+        let mut modified = qe_report.to_vec();
+        modified[0] ^= 1;
+        assert_eq!(
+            verified.verify_qe_report(&modified, &signature),
+            Err(Error::QeReportSignature)
+        );
+    }
+
+    #[parameterized(
+        digital_signature = { KeyUsages::DigitalSignature.into(), Ok(()) },
+        signature_and_non_repudiation = { KeyUsages::DigitalSignature | KeyUsages::NonRepudiation, Ok(()) },
+        certificate_sign = { KeyUsages::KeyCertSign.into(), Err(Error::LeafKeyUsage) },
+        signature_and_key_agreement = { KeyUsages::DigitalSignature | KeyUsages::KeyAgreement, Err(Error::LeafKeyUsage) },
+    )]
+    fn leaf_with_usage(usage: FlagSet<KeyUsages>, expected: Result<()>) {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let verified = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA])
+            .verify(&trust_anchor, crl)
+            .expect("failed to verify cert chain");
+
+        let leaf = verified.leaf_with_usage(KeyUsage(usage));
+        assert_eq!(leaf.map(|_| ()), expected);
+    }
+
+    #[test]
+    fn der_of_verified_chain() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let verified = pem_chain(&[ROOT_CA, LEAF_CERT, PROCESSOR_CA])
+            .into_ordered()
+            .and_then(|chain| chain.verify(&trust_anchor, crl))
+            .expect("failed to verify cert chain");
+
+        let expected = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
             .iter()
-            .map(|der| Certificate::from_der(der).expect("failed to parse cert"))
+            .map(|pem| {
+                Certificate::from_pem(pem)
+                    .and_then(|cert| cert.to_der())
+                    .expect("failed to encode cert")
+            })
             .collect::<Vec<_>>();
+        assert_eq!(verified.to_der_vec(), expected);
+    }
+
+    #[test]
+    fn check_leaf_of_verified_chain() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let verified = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA])
+            .verify(&trust_anchor, crl)
+            .expect("failed to verify cert chain");
+
+        let matcher = LeafMatcher::new().common_name("Intel SGX PCK Certificate");
+        assert_eq!(verified.check_leaf(&matcher), Ok(()));
+        let matcher = LeafMatcher::new().common_name("Intel SGX PCK Processor CA");
+        assert_eq!(verified.check_leaf(&matcher), Err(Error::LeafMismatch));
+    }
+
+    #[test]
+    fn validity_window_of_verified_chain() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let verified = pem_chain(&[LEAF_CERT, PROCESSOR_CA, ROOT_CA])
+            .verify(&trust_anchor, crl)
+            .expect("failed to verify cert chain");
+
+        // The leaf's window is within the CAs'
+        let leaf = Certificate::from_pem(LEAF_CERT).expect("failed to parse cert");
+        let validity = leaf.tbs_certificate.validity;
+        assert_eq!(
+            verified.not_before(),
+            Ok(validity.not_before.to_date_time())
+        );
+        assert_eq!(verified.not_after(), Ok(validity.not_after.to_date_time()));
+    }
+
+    #[test]
+    fn validity_window_is_intersection() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL, PROCESSOR_CRL])
+            .expect("failed to parse CRLs");
+        let verified = pem_chain(&[PROCESSOR_CA, ROOT_CA])
+            .verify(&trust_anchor, crl)
+            .expect("failed to verify cert chain");
+
+        // The processor CA starts after the root, and ends before it
+        let processor_ca = Certificate::from_pem(PROCESSOR_CA).expect("failed to parse cert");
+        let root_ca = Certificate::from_pem(ROOT_CA).expect("failed to parse cert");
+        assert_eq!(
+            verified.not_before(),
+            Ok(processor_ca
+                .tbs_certificate
+                .validity
+                .not_before
+                .to_date_time())
+        );
+        assert_eq!(
+            verified.not_after(),
+            Ok(processor_ca
+                .tbs_certificate
+                .validity
+                .not_after
+                .to_date_time())
+        );
+        assert!(
+            root_ca.tbs_certificate.validity.not_after.to_date_time()
+                > processor_ca
+                    .tbs_certificate
+                    .validity
+                    .not_after
+                    .to_date_time()
+        );
+    }
+
+    #[test]
+    fn leaf_public_key_not_p256() {
+        // The PKITS certificates use RSA keys
         let trust_anchor = TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE)
             .expect("failed to parse root cert");
-        let crls = [REVOKED_SUB_CA_CRL, GOOD_CA_CRL, TRUST_ANCHOR_ROOT_CRL]
+        let crl = CertificateRevocationList::try_from_der([TRUST_ANCHOR_ROOT_CRL, GOOD_CA_CRL])
+            .expect("failed to parse CRLs");
+        let verified = UnverifiedCertChain::try_from_der([GOOD_CA_CERT])
+            .expect("failed to parse cert chain")
+            .verify(&trust_anchor, crl)
+            .expect("failed to verify cert chain");
+        assert_eq!(verified.leaf_public_key(), Err(Error::UnsupportedPublicKey));
+    }
+
+    fn verified_tcb_signer_chain() -> VerifiedCertChain {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let chain = [TCB_SIGNER, ROOT_CA]
             .iter()
-            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
             .collect::<Vec<_>>();
-        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+        let crl = CertificateRevocationList::try_from_der([ROOT_CRL]).expect("failed to parse CRL");
+        UnverifiedCertChain::try_from_certificates(&chain)
+            .expect("failed to parse cert chain")
+            .verify(&trust_anchor, crl)
+            .expect("failed to verify cert chain")
+    }
+
+    // The TCB info and its raw `r || s` signature, converted to DER
+    fn signed_tcb_info() -> (String, Vec<u8>) {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Signed<'a> {
+            #[serde(borrow)]
+            tcb_info: &'a serde_json::value::RawValue,
+            signature: String,
+        }
+        let signed: Signed = serde_json::from_str(TCB_INFO_JSON).expect("failed to parse TCB info");
+        let signature = hex::decode(signed.signature).expect("failed to decode signature");
+        let signature =
+            p256::ecdsa::Signature::from_slice(&signature).expect("failed to parse signature");
+        (
+            signed.tcb_info.get().to_string(),
+            signature.to_der().as_bytes().to_vec(),
+        )
+    }
 
+    #[test]
+    fn verify_ecdsa_signature_with_leaf() {
+        let verified = verified_tcb_signer_chain();
+        let (tcb_info, signature) = signed_tcb_info();
         assert_eq!(
-            verifier.verify_certificate_chain(chain.iter(), crls.iter(), None),
-            Err(CertificateChainVerifierError::SignatureVerification)
+            verified.verify_signature(
+                tcb_info.as_bytes(),
+                &signature,
+                SignatureScheme::EcdsaSha256
+            ),
+            Ok(())
         );
     }
 
     #[test]
-    fn invalid_revoked_ee_4_4_3() {
-        const INVALID_REVOKED_EE_TEST3_EE: &[u8] =
-            include_bytes!("../data/tests/pkits/certs/InvalidRevokedEETest3EE.crt");
-        let ders = [
-            INVALID_REVOKED_EE_TEST3_EE,
-            GOOD_CA_CERT,
-            TRUST_ANCHOR_ROOT_CERTIFICATE,
-        ];
-        let chain = ders
-            .iter()
-            .map(|der| Certificate::from_der(der).expect("failed to parse cert"))
-            .collect::<Vec<_>>();
+    fn verify_ecdsa_signature_of_modified_data() {
+        let verified = verified_tcb_signer_chain();
+        let (tcb_info, signature) = signed_tcb_info();
+        let tcb_info = tcb_info.replace("\"version\":3", "\"version\":4");
+        assert_eq!(
+            verified.verify_signature(
+                tcb_info.as_bytes(),
+                &signature,
+                SignatureScheme::EcdsaSha256
+            ),
+            Err(Error::SignatureVerification)
+        );
+    }
+
+    #[test]
+    fn verify_ecdsa_signature_with_rsa_scheme() {
+        let verified = verified_tcb_signer_chain();
+        let (tcb_info, signature) = signed_tcb_info();
+        assert_eq!(
+            verified.verify_signature(
+                tcb_info.as_bytes(),
+                &signature,
+                SignatureScheme::RsaPkcs1Sha256
+            ),
+            Err(Error::UnsupportedPublicKey)
+        );
+    }
+
+    #[test]
+    fn verify_rsa_signature_with_leaf() {
         let trust_anchor = TrustAnchor::try_from_der(TRUST_ANCHOR_ROOT_CERTIFICATE)
             .expect("failed to parse root cert");
-        let crls = [GOOD_CA_CRL, TRUST_ANCHOR_ROOT_CRL]
-            .iter()
-            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
-            .collect::<Vec<_>>();
-        let verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+        let crl = CertificateRevocationList::try_from_der([TRUST_ANCHOR_ROOT_CRL, GOOD_CA_CRL])
+            .expect("failed to parse CRLs");
+        let verified = UnverifiedCertChain::try_from_der([GOOD_CA_CERT])
+            .expect("failed to parse cert chain")
+            .verify(&trust_anchor, crl)
+            .expect("failed to verify cert chain");
 
+        // The good CA signs its CRL with SHA-256 and RSA
+        let good_ca_crl = CertificateList::from_der(GOOD_CA_CRL).expect("failed to parse CRL");
+        let tbs = good_ca_crl
+            .tbs_cert_list
+            .to_der()
+            .expect("failed to encode CRL");
+        let signature = good_ca_crl
+            .signature
+            .as_bytes()
+            .expect("failed to get signature");
         assert_eq!(
-            verifier.verify_certificate_chain(chain.iter(), crls.iter(), None),
-            Err(CertificateChainVerifierError::SignatureVerification)
+            verified.verify_signature(&tbs, signature, SignatureScheme::RsaPkcs1Sha256),
+            Ok(())
+        );
+        assert_eq!(
+            verified.verify_signature(&tbs, signature, SignatureScheme::RsaPkcs1Sha384),
+            Err(Error::SignatureVerification)
+        );
+        assert_eq!(
+            verified.verify_signature(&tbs, signature, SignatureScheme::EcdsaSha256),
+            Err(Error::UnsupportedPublicKey)
         );
     }
 
+    #[cfg(feature = "serde")]
     #[test]
-    fn invalid_bad_crl_signature_4_4_4() {
-        const BAD_CRL_SIGNATURE_CA_CRL: &[u8] =
-            include_bytes!("../data/tests/pkits/crls/BadCRLSignatureCACRL.crl");
-        // The CRL signature is invalid so parsing the CRL will fail
-        assert!(matches!(
-            CertificateRevocationList::try_from_der([
-                BAD_CRL_SIGNATURE_CA_CRL,
-                TRUST_ANCHOR_ROOT_CRL
-            ]),
-            Err(Error::MbedTls(_))
-        ));
+    fn serde_round_trip() {
+        let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("failed to parse root cert");
+        let chain = pem_chain(&[LEAF_CERT, PROCESSOR_CA]);
+        let crl = CertificateRevocationList::try_from_der([BASE_CRL, DELTA_CRL])
+            .expect("failed to parse CRLs");
+
+        let json = serde_json::to_string(&trust_anchor).expect("failed to serialize");
+        let trust_anchor: TrustAnchor = serde_json::from_str(&json).expect("failed to deserialize");
+        assert_eq!(
+            serde_json::to_string(&trust_anchor).expect("failed to serialize"),
+            json
+        );
+
+        let json = serde_json::to_string(&chain).expect("failed to serialize");
+        let deserialized: UnverifiedCertChain =
+            serde_json::from_str(&json).expect("failed to deserialize");
+        assert_eq!(chain_ders(&deserialized), chain_ders(&chain));
+
+        let json = serde_json::to_string(&crl).expect("failed to serialize");
+        let deserialized: CertificateRevocationList =
+            serde_json::from_str(&json).expect("failed to deserialize");
+        assert_eq!(deserialized.bases.len(), 1);
+        assert_eq!(deserialized.deltas.len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_invalid_der_fails() {
+        let json = alloc::format!(r#"["{}"]"#, hex::encode(&GOOD_CA_CERT[1..]));
+        assert!(serde_json::from_str::<UnverifiedCertChain>(&json).is_err());
+        assert!(serde_json::from_str::<UnverifiedCertChain>(r#"["not hex"]"#).is_err());
     }
 }
@@ -0,0 +1,189 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Options tuning how strictly evidence is verified
+
+use crate::CertificateChainVerifierError;
+use core::time::Duration;
+use der::DateTime;
+use x509_cert::{crl::CertificateList, Certificate};
+
+/// Options for verifying evidence.
+///
+/// The default options are the strictest, the verification time must be
+/// within every validity window.
+///
+/// ```
+/// # use core::time::Duration;
+/// # use mc_attestation_verifier::VerificationOptions;
+/// let options = VerificationOptions {
+///     max_clock_skew: Duration::from_secs(5 * 60),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct VerificationOptions {
+    /// How far the verification time may be outside of a validity window and
+    /// still be accepted.
+    ///
+    /// This tolerates a clock which has drifted from the issuer's, like a
+    /// freshly booted VM which has not yet synchronized its clock. The
+    /// tolerance applies on both ends of the window, so it should be kept to
+    /// seconds or minutes.
+    pub max_clock_skew: Duration,
+    /// The lowest `tcbEvaluationDataNumber` of the TCB info and QE identity
+    /// which will be accepted.
+    ///
+    /// Intel increments the number with each TCB recovery. Raising this
+    /// after a recovery forces collateral from before the recovery to be
+    /// re-fetched instead of being appraised against. The default of 0
+    /// accepts any number.
+    pub min_tcb_evaluation_data_number: u32,
+}
+
+impl VerificationOptions {
+    /// Whether `time` is before the start of a validity window, `not_before`,
+    /// by more than the clock skew.
+    pub(crate) fn is_not_yet_valid(&self, time: DateTime, not_before: DateTime) -> bool {
+        time.unix_duration().saturating_add(self.max_clock_skew) < not_before.unix_duration()
+    }
+
+    /// Whether `time` is at, or after, the end of a validity window,
+    /// `not_after`, by more than the clock skew.
+    pub(crate) fn is_expired(&self, time: DateTime, not_after: DateTime) -> bool {
+        time.unix_duration()
+            >= not_after
+                .unix_duration()
+                .saturating_add(self.max_clock_skew)
+    }
+
+    /// Check the validity periods of the `certificates` and `crls` at `time`.
+    ///
+    /// This is for [`CertificateChainVerifier`](crate::CertificateChainVerifier)
+    /// implementations whose backend does not check the time itself.
+    ///
+    /// A CRL without a `nextUpdate` does not expire.
+    ///
+    /// # Errors
+    /// * `CertificateChainVerifierError::CertificateNotYetValid` or
+    ///   `CertificateChainVerifierError::CertificateExpired` if a certificate
+    ///   is outside of its validity period.
+    /// * `CertificateChainVerifierError::CrlNotYetValid` or
+    ///   `CertificateChainVerifierError::CrlExpired` if a CRL is outside of
+    ///   its `thisUpdate` and `nextUpdate` times.
+    pub fn check_validity<'a, 'b>(
+        &self,
+        certificates: impl IntoIterator<Item = &'a Certificate>,
+        crls: impl IntoIterator<Item = &'b CertificateList>,
+        time: DateTime,
+    ) -> Result<(), CertificateChainVerifierError> {
+        for certificate in certificates {
+            let validity = &certificate.tbs_certificate.validity;
+            if self.is_not_yet_valid(time, validity.not_before.to_date_time()) {
+                return Err(CertificateChainVerifierError::CertificateNotYetValid);
+            }
+            if self.is_expired(time, validity.not_after.to_date_time()) {
+                return Err(CertificateChainVerifierError::CertificateExpired);
+            }
+        }
+        for crl in crls {
+            let tbs = &crl.tbs_cert_list;
+            if self.is_not_yet_valid(time, tbs.this_update.to_date_time()) {
+                return Err(CertificateChainVerifierError::CrlNotYetValid);
+            }
+            if let Some(next_update) = tbs.next_update {
+                if self.is_expired(time, next_update.to_date_time()) {
+                    return Err(CertificateChainVerifierError::CrlExpired);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use der::{Decode, DecodePem};
+    use yare::parameterized;
+
+    const ONE_MINUTE: Duration = Duration::from_secs(60);
+
+    fn time(time: &str) -> DateTime {
+        time.parse::<DateTime>().expect("Failed to parse time")
+    }
+
+    fn options(max_clock_skew: Duration) -> VerificationOptions {
+        VerificationOptions {
+            max_clock_skew,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn default_has_no_clock_skew() {
+        assert_eq!(
+            VerificationOptions::default().max_clock_skew,
+            Duration::ZERO
+        );
+        assert_eq!(
+            VerificationOptions::default().min_tcb_evaluation_data_number,
+            0
+        );
+    }
+
+    #[parameterized(
+        within = { "2023-07-12T20:00:00Z", Duration::ZERO, false },
+        just_before = { "2023-07-12T19:59:59Z", Duration::ZERO, true },
+        before_within_skew = { "2023-07-12T19:59:00Z", ONE_MINUTE, false },
+        before_past_skew = { "2023-07-12T19:58:59Z", ONE_MINUTE, true },
+    )]
+    fn not_yet_valid(at: &str, skew: Duration, expected: bool) {
+        assert_eq!(
+            options(skew).is_not_yet_valid(time(at), time("2023-07-12T20:00:00Z")),
+            expected
+        );
+    }
+
+    #[parameterized(
+        before = { "2023-07-12T19:59:59Z", Duration::ZERO, false },
+        at_end = { "2023-07-12T20:00:00Z", Duration::ZERO, true },
+        after_within_skew = { "2023-07-12T20:00:59Z", ONE_MINUTE, false },
+        after_past_skew = { "2023-07-12T20:01:00Z", ONE_MINUTE, true },
+    )]
+    fn expired(at: &str, skew: Duration, expected: bool) {
+        assert_eq!(
+            options(skew).is_expired(time(at), time("2023-07-12T20:00:00Z")),
+            expected
+        );
+    }
+
+    fn leaf_cert() -> Certificate {
+        Certificate::from_pem(include_str!("../data/tests/leaf_cert.pem"))
+            .expect("Failed to parse certificate")
+    }
+
+    fn processor_crl() -> CertificateList {
+        CertificateList::from_der(include_bytes!("../data/tests/processor_crl.der"))
+            .expect("Failed to parse CRL")
+    }
+
+    #[parameterized(
+        valid = { "2023-05-01T00:00:00Z", Duration::ZERO, Ok(()) },
+        cert_not_yet_valid = { "2022-06-13T21:46:33Z", Duration::ZERO, Err(CertificateChainVerifierError::CertificateNotYetValid) },
+        crl_not_yet_valid = { "2023-04-21T22:00:35Z", Duration::ZERO, Err(CertificateChainVerifierError::CrlNotYetValid) },
+        crl_not_yet_valid_within_skew = { "2023-04-21T22:00:35Z", ONE_MINUTE, Ok(()) },
+        crl_expired = { "2023-05-21T22:00:36Z", Duration::ZERO, Err(CertificateChainVerifierError::CrlExpired) },
+        crl_expired_within_skew = { "2023-05-21T22:00:36Z", ONE_MINUTE, Ok(()) },
+        cert_expired = { "2029-06-13T21:46:34Z", Duration::ZERO, Err(CertificateChainVerifierError::CertificateExpired) },
+    )]
+    fn check_validity_of_chain(
+        at: &str,
+        skew: Duration,
+        expected: Result<(), CertificateChainVerifierError>,
+    ) {
+        assert_eq!(
+            options(skew).check_validity([&leaf_cert()], [&processor_crl()], time(at)),
+            expected
+        );
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! The PCK CA which issued the PCK certificate of a platform.
+//!
+//! Single-package platforms get their PCK certificate from the Processor CA.
+//! Multi-package platforms register their platform manifest with the Intel
+//! registration service and get their PCK certificate from the Platform CA.
+//! The TCB in the SGX extensions of a Platform CA certificate is the platform
+//! TCB, covering every package of the platform, and the certificate carries
+//! the platform instance ID and the [`PlatformConfiguration`](crate::PlatformConfiguration).
+//!
+//! Each CA has its own PCK CRL, so the CRL in the collateral must be the one
+//! of the CA which issued the PCK certificate. See section 1.3 of
+//! <https://api.trustedservices.intel.com/documents/Intel_SGX_PCK_Certificate_CRL_Spec-1.5.pdf>
+
+use crate::{
+    certificate_chain::common_names, choice_to_status_message, Accessor, Error,
+    VerificationMessage, VerificationOutput, Verifier,
+};
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::Formatter;
+use serde::{Deserialize, Serialize};
+use x509_cert::{name::Name, Certificate};
+
+/// The common name of the PCK Processor CA
+const PROCESSOR_CA_COMMON_NAME: &str = "Intel SGX PCK Processor CA";
+
+/// The common name of the PCK Platform CA
+const PLATFORM_CA_COMMON_NAME: &str = "Intel SGX PCK Platform CA";
+
+/// The CA which issued a PCK certificate
+#[derive(displaydoc::Display, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PckCaType {
+    /// processor
+    Processor,
+    /// platform
+    Platform,
+}
+
+impl PckCaType {
+    /// The value of the `ca` parameter of PCS requests
+    pub fn value(&self) -> &'static str {
+        match self {
+            PckCaType::Processor => "processor",
+            PckCaType::Platform => "platform",
+        }
+    }
+
+    /// The path of the PCS, or PCCS, request for the PCK CRL of the CA,
+    /// relative to the base URL of the service
+    pub fn pck_crl_path(&self) -> String {
+        format!(
+            "/sgx/certification/v4/pckcrl?ca={}&encoding=der",
+            self.value()
+        )
+    }
+
+    fn try_from_name(name: &Name) -> Result<Self, Error> {
+        let names = common_names(name);
+        if names.iter().any(|name| name == PROCESSOR_CA_COMMON_NAME) {
+            Ok(PckCaType::Processor)
+        } else if names.iter().any(|name| name == PLATFORM_CA_COMMON_NAME) {
+            Ok(PckCaType::Platform)
+        } else {
+            Err(Error::PckCaType(format!("{name}")))
+        }
+    }
+}
+
+/// The CA which issued the PCK `certificate`, from its issuer name
+///
+/// # Errors
+/// `Error::PckCaType` if the issuer is neither of the PCK CAs.
+impl TryFrom<&Certificate> for PckCaType {
+    type Error = Error;
+
+    fn try_from(certificate: &Certificate) -> Result<Self, Self::Error> {
+        Self::try_from_name(&certificate.tbs_certificate.issuer)
+    }
+}
+
+/// Verifier for limiting which PCK CAs, and so which platform registration
+/// models, are accepted.
+///
+/// For example, `PckCaTypeVerifier::new([PckCaType::Processor])` only
+/// accepts single-package platforms.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PckCaTypeVerifier {
+    allowed: Vec<PckCaType>,
+}
+
+impl PckCaTypeVerifier {
+    /// Create a new instance which accepts PCK certificates from the
+    /// `allowed` CAs.
+    pub fn new(allowed: impl IntoIterator<Item = PckCaType>) -> Self {
+        Self {
+            allowed: allowed.into_iter().collect(),
+        }
+    }
+}
+
+impl<E: Accessor<PckCaType>> Verifier<E> for PckCaTypeVerifier {
+    type Value = PckCaType;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let actual = evidence.get();
+        let is_success = self.allowed.contains(&actual) as u8;
+        VerificationOutput::new(actual, is_success.into())
+    }
+}
+
+impl VerificationMessage<PckCaType> for PckCaTypeVerifier {
+    fn fmt_padded(
+        &self,
+        f: &mut Formatter<'_>,
+        pad: usize,
+        result: &VerificationOutput<PckCaType>,
+    ) -> core::fmt::Result {
+        let is_success = result.is_success();
+        let status = choice_to_status_message(is_success);
+        write!(
+            f,
+            "{:pad$}{status} The PCK certificate should be issued by the ",
+            ""
+        )?;
+        for (index, allowed) in self.allowed.iter().enumerate() {
+            if index > 0 {
+                write!(f, " or ")?;
+            }
+            write!(f, "{allowed}")?;
+        }
+        write!(f, " CA")?;
+        if is_success.unwrap_u8() == 0 {
+            write!(f, ", but it was issued by the {} CA", result.value())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::VerificationTreeDisplay;
+    use core::str::FromStr;
+    use der::DecodePem;
+    use yare::parameterized;
+
+    const LEAF_CERT: &str = include_str!("../data/tests/leaf_cert.pem");
+
+    #[test]
+    fn processor_ca_from_leaf() {
+        let leaf = Certificate::from_pem(LEAF_CERT).expect("Failed to parse certificate");
+        assert_eq!(PckCaType::try_from(&leaf), Ok(PckCaType::Processor));
+    }
+
+    #[parameterized(
+        processor = { "CN=Intel SGX PCK Processor CA,O=Intel Corporation", Ok(PckCaType::Processor) },
+        platform = { "CN=Intel SGX PCK Platform CA,O=Intel Corporation", Ok(PckCaType::Platform) },
+        other = { "CN=Intel SGX Root CA,O=Intel Corporation", Err(Error::PckCaType("CN=Intel SGX Root CA,O=Intel Corporation".into())) },
+    )]
+    fn ca_type_from_issuer(issuer: &str, expected: Result<PckCaType, Error>) {
+        let name = Name::from_str(issuer).expect("Failed to parse name");
+        assert_eq!(PckCaType::try_from_name(&name), expected);
+    }
+
+    #[test]
+    fn pck_crl_paths() {
+        assert_eq!(
+            PckCaType::Processor.pck_crl_path(),
+            "/sgx/certification/v4/pckcrl?ca=processor&encoding=der"
+        );
+        assert_eq!(
+            PckCaType::Platform.pck_crl_path(),
+            "/sgx/certification/v4/pckcrl?ca=platform&encoding=der"
+        );
+    }
+
+    #[test]
+    fn allowed_ca_type_succeeds() {
+        let verifier = PckCaTypeVerifier::new([PckCaType::Processor, PckCaType::Platform]);
+        let verification = verifier.verify(&PckCaType::Platform);
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [x] The PCK certificate should be issued by the processor or platform CA"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn multi_package_platform_fails_for_single_package_policy() {
+        let verifier = PckCaTypeVerifier::new([PckCaType::Processor]);
+        let verification = verifier.verify(&PckCaType::Platform);
+        assert_eq!(verification.is_success().unwrap_u8(), 0);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [ ] The PCK certificate should be issued by the processor CA, but it was issued by the platform CA"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+}
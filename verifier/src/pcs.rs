@@ -0,0 +1,941 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Client for the Intel Provisioning Certification Service (PCS).
+//!
+//! The PCS, and a PCCS caching it, serve the collateral for verifying
+//! quotes. See
+//! <https://api.portal.trustedservices.intel.com/content/documentation.html>
+
+use crate::{
+    crl_distribution_points,
+    intel_root_ca::INTEL_SGX_ROOT_CA_PEM,
+    mbedtls::{CertificateRevocationList, Error, Result, UnverifiedCertChain},
+    url_encoding::percent_decode,
+    CertificateChainVerifier, Collateral, CollateralProvider, Fmspc, PckCaType, QeIdentity,
+    RetryPolicy, SignedQeIdentity, SignedTcbInfo, TcbInfo, TeeType,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+use der::{DateTime, Decode, DecodePem};
+use std::{io::Read, thread};
+use x509_cert::{crl::CertificateList, Certificate};
+
+/// The base URL of the Intel PCS
+pub const INTEL_PCS_URL: &str = "https://api.trustedservices.intel.com";
+
+/// The version of the PCS API used unless another is configured
+pub const PCS_API_VERSION: u32 = 4;
+
+/// The response header with the URL encoded issuer chain of a PCK CRL
+pub(crate) const PCK_CRL_ISSUER_CHAIN_HEADER: &str = "SGX-PCK-CRL-Issuer-Chain";
+
+/// The response header with the URL encoded TCB signing chain of a TCB info
+const TCB_INFO_ISSUER_CHAIN_HEADER: &str = "TCB-Info-Issuer-Chain";
+
+/// The name of the TCB signing chain header before version 4 of the API
+const V3_TCB_INFO_ISSUER_CHAIN_HEADER: &str = "SGX-TCB-Info-Issuer-Chain";
+
+/// The response header with the URL encoded signing chain of a QE, or QvE,
+/// identity
+pub(crate) const ENCLAVE_IDENTITY_ISSUER_CHAIN_HEADER: &str = "SGX-Enclave-Identity-Issuer-Chain";
+
+/// An HTTP client for the Intel PCS, or a PCCS.
+///
+/// ```no_run
+/// use mc_attestation_verifier::{PckCaType, PcsClient};
+///
+/// let pck_crl = PcsClient::new().fetch_pck_crl(PckCaType::Processor).unwrap();
+/// let (crl, issuer_chain) = pck_crl.into_parts();
+/// ```
+///
+/// Where the Intel PCS is not reachable, the client can talk to a local PCCS,
+/// or a caching service of the cloud provider, instead.
+///
+/// ```no_run
+/// use mc_attestation_verifier::{PckCaType, PcsClient};
+///
+/// let client = PcsClient::with_base_url("https://pccs.internal:8081")
+///     .with_api_version(3)
+///     .with_header("Ocp-Apim-Subscription-Key", "0123456789abcdef");
+/// let pck_crl = client.fetch_pck_crl(PckCaType::Platform).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct PcsClient {
+    base_url: String,
+    api_version: u32,
+    headers: Vec<(String, String)>,
+    retry_policy: RetryPolicy,
+    root_ca_crl_url: Option<String>,
+    agent: ureq::Agent,
+}
+
+impl PcsClient {
+    /// Create a new instance for the Intel PCS
+    pub fn new() -> Self {
+        Self::with_base_url(INTEL_PCS_URL)
+    }
+
+    /// Create a new instance for the service at `base_url`, like a PCCS
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        while base_url.ends_with('/') {
+            base_url.pop();
+        }
+        Self {
+            base_url,
+            api_version: PCS_API_VERSION,
+            headers: Vec::new(),
+            retry_policy: RetryPolicy::new(),
+            root_ca_crl_url: None,
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    /// Use `version` of the PCS API, the `v{version}` of the request paths.
+    ///
+    /// Defaults to [`PCS_API_VERSION`]. Older versions are for services which
+    /// have yet to support the current one, the TCB info and QE identity they
+    /// serve may be in a format this crate does not support.
+    pub fn with_api_version(mut self, version: u32) -> Self {
+        self.api_version = version;
+        self
+    }
+
+    /// Send the header `name` with `value` in every request, like the API key
+    /// or the credentials of a caching service.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Retry requests which fail transiently with the `retry_policy`.
+    ///
+    /// Defaults to [`RetryPolicy::new()`].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Fetch the CRL of the Intel SGX root CA from `url`.
+    ///
+    /// Defaults to the CRL distribution point of the root CA for the Intel
+    /// PCS, and to the `rootcacrl` endpoint of the service otherwise, as a
+    /// PCCS serves it.
+    pub fn with_root_ca_crl_url(mut self, url: impl Into<String>) -> Self {
+        self.root_ca_crl_url = Some(url.into());
+        self
+    }
+
+    /// The base URL of the service
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The version of the PCS API in use
+    pub fn api_version(&self) -> u32 {
+        self.api_version
+    }
+
+    /// The extra headers sent in every request, as name and value pairs
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// The policy for retrying requests which fail transiently
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Fetch the PCK CRL of the `ca` and its issuer chain
+    ///
+    /// # Errors
+    /// `Error::PcsFetch` if the request fails.
+    /// `Error::MissingIssuerChain` if the response is missing the issuer
+    /// chain.
+    /// `Error::PcsUnavailable` or `Error::PcsRequest` if the service responds
+    /// with an error status, after any retries.
+    /// `Error::UrlEncoding` if the issuer chain is not validly URL encoded.
+    /// `Error::MbedTls` if the CRL or the issuer chain can not be decoded.
+    pub fn fetch_pck_crl(&self, ca: PckCaType) -> Result<PckCrl> {
+        let (crl, issuer_chain) =
+            self.get_with_issuer_chain(&self.pck_crl_url(ca), PCK_CRL_ISSUER_CHAIN_HEADER)?;
+        PckCrl::from_response(&crl, &issuer_chain)
+    }
+
+    /// Fetch the TCB info of the platforms with `fmspc` and verify its
+    /// signature.
+    ///
+    /// # Arguments
+    /// * `fmspc` - The FMSPC of the platform, from its PCK certificate.
+    /// * `tee_type` - Whether to fetch the SGX, or the TDX, TCB info.
+    /// * `certificate_verifier` - The verifier for the TCB signing chain of
+    ///   the response.
+    /// * `crls` - The CRLs to verify the TCB signing chain against, the CRL
+    ///   of the Intel SGX root CA.
+    /// * `time` - The current time, the TCB info and the TCB signing chain
+    ///   must be valid at this time.
+    ///
+    /// # Errors
+    /// `Error::PcsFetch` if the request fails.
+    /// `Error::MissingIssuerChain` if the response is missing the TCB signing
+    /// chain.
+    /// `Error::PcsUnavailable` or `Error::PcsRequest` if the service responds
+    /// with an error status, after any retries.
+    /// `Error::UrlEncoding` if the TCB signing chain is not validly URL
+    /// encoded.
+    /// `Error::Der` if the TCB signing chain can not be decoded.
+    /// `Error::Collateral` if the TCB info can not be parsed or verified.
+    pub fn fetch_tcb_info<C: CertificateChainVerifier>(
+        &self,
+        fmspc: &Fmspc,
+        tee_type: TeeType,
+        certificate_verifier: &C,
+        crls: &[CertificateList],
+        time: impl Into<Option<DateTime>>,
+    ) -> Result<TcbInfo> {
+        let (tcb_info, issuer_chain) = self.get_with_issuer_chain(
+            &self.tcb_info_url(fmspc, tee_type),
+            self.tcb_info_issuer_chain_header(),
+        )?;
+        verify_tcb_info(
+            &tcb_info,
+            &issuer_chain,
+            certificate_verifier,
+            crls,
+            time.into(),
+        )
+    }
+
+    /// Fetch the identity of the QE and verify its signature.
+    ///
+    /// The identity is what the QE report of a quote is verified against,
+    /// see [`QeReportBodyVerifier`](crate::QeReportBodyVerifier).
+    ///
+    /// # Arguments
+    /// * `tee_type` - Whether to fetch the identity of the SGX QE, or the TD
+    ///   QE.
+    /// * `certificate_verifier` - The verifier for the signing chain of the
+    ///   response.
+    /// * `crls` - The CRLs to verify the signing chain against, the CRL of
+    ///   the Intel SGX root CA.
+    /// * `time` - The current time, the identity and the signing chain must
+    ///   be valid at this time.
+    ///
+    /// # Errors
+    /// The errors of [`PcsClient::fetch_tcb_info()`], for the identity.
+    pub fn fetch_qe_identity<C: CertificateChainVerifier>(
+        &self,
+        tee_type: TeeType,
+        certificate_verifier: &C,
+        crls: &[CertificateList],
+        time: impl Into<Option<DateTime>>,
+    ) -> Result<QeIdentity> {
+        let (qe_identity, issuer_chain) = self.get_with_issuer_chain(
+            &self.qe_identity_url(tee_type),
+            ENCLAVE_IDENTITY_ISSUER_CHAIN_HEADER,
+        )?;
+        verify_qe_identity(
+            &qe_identity,
+            &issuer_chain,
+            certificate_verifier,
+            crls,
+            time.into(),
+        )
+    }
+
+    /// Fetch the identity of the quote verification enclave (QvE) and verify
+    /// its signature.
+    ///
+    /// See [`PcsClient::fetch_qe_identity()`] for the arguments and errors.
+    pub fn fetch_qve_identity<C: CertificateChainVerifier>(
+        &self,
+        certificate_verifier: &C,
+        crls: &[CertificateList],
+        time: impl Into<Option<DateTime>>,
+    ) -> Result<QeIdentity> {
+        let (qve_identity, issuer_chain) = self.get_with_issuer_chain(
+            &self.url("sgx", "qve/identity"),
+            ENCLAVE_IDENTITY_ISSUER_CHAIN_HEADER,
+        )?;
+        verify_qe_identity(
+            &qve_identity,
+            &issuer_chain,
+            certificate_verifier,
+            crls,
+            time.into(),
+        )
+    }
+
+    /// Fetch all of the collateral for verifying the quotes of the platforms
+    /// with `fmspc`.
+    ///
+    /// The root CA CRL is fetched as configured with
+    /// [`PcsClient::with_root_ca_crl_url()`]. The parts are checked to be
+    /// consistent, their signatures are verified when verifying a quote, see
+    /// [`Collateral`].
+    ///
+    /// # Arguments
+    /// * `ca` - The CA which issued the PCK certificate of the platform.
+    /// * `fmspc` - The FMSPC of the platform, from its PCK certificate.
+    /// * `tee_type` - Whether to fetch the SGX, or the TDX, TCB info and QE
+    ///   identity.
+    ///
+    /// # Errors
+    /// `Error::PcsFetch` if a request fails.
+    /// `Error::MissingIssuerChain` if a response is missing its issuer chain.
+    /// `Error::PcsUnavailable` or `Error::PcsRequest` if the service responds
+    /// with an error status, after any retries.
+    /// `Error::UrlEncoding` if an issuer chain is not validly URL encoded.
+    /// `Error::Der` if a CRL or an issuer chain can not be decoded.
+    /// `Error::Collateral` if the TCB info or QE identity can not be parsed,
+    /// or the parts do not fit together.
+    pub fn fetch_collateral(
+        &self,
+        ca: PckCaType,
+        fmspc: &Fmspc,
+        tee_type: TeeType,
+    ) -> Result<Collateral> {
+        self.collateral(ca, fmspc, tee_type)
+    }
+
+    /// Fetch `url`, returning the body of the response
+    fn get(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self.call(url)?;
+        read_body(url, response)
+    }
+
+    /// Fetch `url`, returning the body of the response and the issuer chain
+    /// in its `header`
+    pub(crate) fn get_with_issuer_chain(
+        &self,
+        url: &str,
+        header: &str,
+    ) -> Result<(Vec<u8>, String)> {
+        let response = self.call(url)?;
+        let issuer_chain = response
+            .header(header)
+            .ok_or_else(|| missing_issuer_chain(url, header))?
+            .to_string();
+        Ok((read_body(url, response)?, issuer_chain))
+    }
+
+    fn call(&self, url: &str) -> Result<ureq::Response> {
+        let mut retry = 0;
+        loop {
+            let error = match self
+                .headers
+                .iter()
+                .fold(self.agent.get(url), |request, (name, value)| {
+                    request.set(name, value)
+                })
+                .call()
+            {
+                Ok(response) => return Ok(response),
+                Err(ureq::Error::Status(status, _)) => status_error(url, status),
+                Err(e) => fetch_error(url, e),
+            };
+            match self.retry_policy.delay(retry) {
+                Some(delay) if error.is_transient() => thread::sleep(delay),
+                _ => return Err(error),
+            }
+            retry += 1;
+        }
+    }
+
+    /// The URL of the `endpoint` of the certification service for `tee`
+    pub(crate) fn url(&self, tee: &str, endpoint: &str) -> String {
+        format!(
+            "{}/{tee}/certification/v{}/{endpoint}",
+            self.base_url, self.api_version
+        )
+    }
+
+    pub(crate) fn pck_crl_url(&self, ca: PckCaType) -> String {
+        self.url("sgx", &format!("pckcrl?ca={}&encoding=der", ca.value()))
+    }
+
+    pub(crate) fn tcb_info_url(&self, fmspc: &Fmspc, tee_type: TeeType) -> String {
+        self.url(tee_path(tee_type), &format!("tcb?fmspc={fmspc}"))
+    }
+
+    pub(crate) fn qe_identity_url(&self, tee_type: TeeType) -> String {
+        self.url(tee_path(tee_type), "qe/identity")
+    }
+
+    /// The URL of the CRL of the Intel SGX root CA
+    ///
+    /// # Errors
+    /// `Error::Der` or `Error::Collateral` if the CRL distribution point of
+    /// the Intel SGX root CA can not be determined.
+    pub(crate) fn root_ca_crl_url(&self) -> Result<String> {
+        match &self.root_ca_crl_url {
+            Some(url) => Ok(url.clone()),
+            None if self.base_url == INTEL_PCS_URL => intel_root_ca_crl_url(),
+            None => Ok(self.url("sgx", "rootcacrl")),
+        }
+    }
+
+    pub(crate) fn tcb_info_issuer_chain_header(&self) -> &'static str {
+        if self.api_version < 4 {
+            V3_TCB_INFO_ISSUER_CHAIN_HEADER
+        } else {
+            TCB_INFO_ISSUER_CHAIN_HEADER
+        }
+    }
+}
+
+impl Default for PcsClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CollateralProvider for PcsClient {
+    type Error = Error;
+
+    fn pck_crl(&self, ca: PckCaType) -> Result<(CertificateList, Vec<Certificate>)> {
+        let (crl, issuer_chain) =
+            self.get_with_issuer_chain(&self.pck_crl_url(ca), PCK_CRL_ISSUER_CHAIN_HEADER)?;
+        Ok((CertificateList::from_der(&crl)?, pem_chain(&issuer_chain)?))
+    }
+
+    fn root_ca_crl(&self) -> Result<CertificateList> {
+        Ok(CertificateList::from_der(
+            &self.get(&self.root_ca_crl_url()?)?,
+        )?)
+    }
+
+    fn tcb_info(&self, fmspc: &Fmspc, tee_type: TeeType) -> Result<(String, Vec<Certificate>)> {
+        let (tcb_info, issuer_chain) = self.get_with_issuer_chain(
+            &self.tcb_info_url(fmspc, tee_type),
+            self.tcb_info_issuer_chain_header(),
+        )?;
+        Ok((
+            String::from_utf8(tcb_info).map_err(|e| invalid_utf8("TCB info", e))?,
+            pem_chain(&issuer_chain)?,
+        ))
+    }
+
+    fn qe_identity(&self, tee_type: TeeType) -> Result<(String, Vec<Certificate>)> {
+        let (qe_identity, issuer_chain) = self.get_with_issuer_chain(
+            &self.qe_identity_url(tee_type),
+            ENCLAVE_IDENTITY_ISSUER_CHAIN_HEADER,
+        )?;
+        Ok((
+            String::from_utf8(qe_identity).map_err(|e| invalid_utf8("QE identity", e))?,
+            pem_chain(&issuer_chain)?,
+        ))
+    }
+}
+
+/// The path segment of the PCS endpoints for the `tee_type`
+fn tee_path(tee_type: TeeType) -> &'static str {
+    match tee_type {
+        TeeType::Sgx => "sgx",
+        TeeType::Tdx => "tdx",
+    }
+}
+
+/// The URL of the CRL of the Intel SGX root CA, its CRL distribution point
+fn intel_root_ca_crl_url() -> Result<String> {
+    let root_ca = Certificate::from_pem(INTEL_SGX_ROOT_CA_PEM)?;
+    crl_distribution_points(&root_ca)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::Collateral("the root CA has no CRL distribution point".to_string()))
+}
+
+/// The error for a response with the HTTP `status`
+pub(crate) fn status_error(url: &str, status: u16) -> Error {
+    let url = url.to_string();
+    if status == 429 || status >= 500 {
+        Error::PcsUnavailable { url, status }
+    } else {
+        Error::PcsRequest { url, status }
+    }
+}
+
+pub(crate) fn fetch_error(url: &str, error: impl Display) -> Error {
+    Error::PcsFetch {
+        url: url.to_string(),
+        error: error.to_string(),
+    }
+}
+
+pub(crate) fn missing_issuer_chain(url: &str, header: &str) -> Error {
+    Error::MissingIssuerChain {
+        url: url.to_string(),
+        header: header.to_string(),
+    }
+}
+
+/// The error for a response body which is not the UTF-8 encoded JSON `what`
+pub(crate) fn invalid_utf8(what: &str, error: impl Display) -> Error {
+    Error::Collateral(format!("the {what} is not valid UTF-8: {error}"))
+}
+
+fn read_body(url: &str, response: ureq::Response) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut body)
+        .map_err(|e| fetch_error(url, e))?;
+    Ok(body)
+}
+
+/// Decode a URL encoded issuer chain header
+pub(crate) fn url_decode(issuer_chain: &str) -> Result<String> {
+    let issuer_chain = percent_decode(issuer_chain).map_err(|_| Error::UrlEncoding)?;
+    Ok(String::from_utf8_lossy(&issuer_chain).into_owned())
+}
+
+/// Decode a URL encoded PEM issuer chain header
+pub(crate) fn pem_chain(issuer_chain: &str) -> Result<Vec<Certificate>> {
+    Ok(Certificate::load_pem_chain(
+        url_decode(issuer_chain)?.as_bytes(),
+    )?)
+}
+
+/// Verify the TCB info of a response with its URL encoded TCB signing
+/// `issuer_chain`
+pub(crate) fn verify_tcb_info<C: CertificateChainVerifier>(
+    tcb_info: &[u8],
+    issuer_chain: &str,
+    certificate_verifier: &C,
+    crls: &[CertificateList],
+    time: Option<DateTime>,
+) -> Result<TcbInfo> {
+    let issuer_chain = pem_chain(issuer_chain)?;
+    let tcb_info = core::str::from_utf8(tcb_info).map_err(|e| invalid_utf8("TCB info", e))?;
+    SignedTcbInfo::try_from(tcb_info)
+        .and_then(|signed_tcb_info| {
+            signed_tcb_info.verify_with_certificate_chain(
+                certificate_verifier,
+                &issuer_chain,
+                crls,
+                time,
+            )
+        })
+        .map_err(Error::from)
+}
+
+/// Verify the QE, or QvE, identity of a response with its URL encoded
+/// signing `issuer_chain`
+pub(crate) fn verify_qe_identity<C: CertificateChainVerifier>(
+    qe_identity: &[u8],
+    issuer_chain: &str,
+    certificate_verifier: &C,
+    crls: &[CertificateList],
+    time: Option<DateTime>,
+) -> Result<QeIdentity> {
+    let issuer_chain = pem_chain(issuer_chain)?;
+    let qe_identity =
+        core::str::from_utf8(qe_identity).map_err(|e| invalid_utf8("QE identity", e))?;
+    SignedQeIdentity::try_from(qe_identity)
+        .and_then(|signed_qe_identity| {
+            signed_qe_identity.verify_with_certificate_chain(
+                certificate_verifier,
+                &issuer_chain,
+                crls,
+                time,
+            )
+        })
+        .map_err(Error::from)
+}
+
+/// A PCK CRL and the certificate chain of its issuer, the PCK CA.
+///
+/// The issuer chain is what the CRL is verified with, it is not trusted until
+/// verified against the Intel SGX root CA.
+#[derive(Clone, Debug)]
+pub struct PckCrl {
+    crl: CertificateRevocationList,
+    issuer_chain: UnverifiedCertChain,
+}
+
+impl PckCrl {
+    /// Create a new instance from a PCS, or PCCS, response.
+    ///
+    /// # Arguments
+    /// * `crl` - The body of the response, the DER or PEM encoded CRL.
+    /// * `issuer_chain` - The `SGX-PCK-CRL-Issuer-Chain` header of the
+    ///   response, the URL encoded PEM issuer chain.
+    ///
+    /// # Errors
+    /// `Error::UrlEncoding` if the issuer chain is not validly URL encoded.
+    /// `Error::MbedTls` if the CRL or the issuer chain can not be decoded.
+    pub fn from_response(crl: &[u8], issuer_chain: &str) -> Result<Self> {
+        let issuer_chain = url_decode(issuer_chain)?;
+        let crl = if crl.starts_with(b"-----BEGIN") {
+            CertificateRevocationList::try_from_pem_bundle(&String::from_utf8_lossy(crl))?
+        } else {
+            CertificateRevocationList::try_from_der([crl])?
+        };
+        let issuer_chain = UnverifiedCertChain::try_from_pem_bundle(&issuer_chain)?;
+        Ok(Self { crl, issuer_chain })
+    }
+
+    /// The CRL
+    pub fn crl(&self) -> &CertificateRevocationList {
+        &self.crl
+    }
+
+    /// The certificate chain of the CRL issuer
+    pub fn issuer_chain(&self) -> &UnverifiedCertChain {
+        &self.issuer_chain
+    }
+
+    /// The CRL and the certificate chain of its issuer
+    pub fn into_parts(self) -> (CertificateRevocationList, UnverifiedCertChain) {
+        (self.crl, self.issuer_chain)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::CertificateChainVerifierError;
+    use assert_matches::assert_matches;
+    use mc_sgx_core_types::IsvProductId;
+
+    /// Percent encode everything but letters and digits, as the PCS does
+    fn url_encode(text: &str) -> String {
+        text.bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => (byte as char).to_string(),
+                _ => format!("%{byte:02X}"),
+            })
+            .collect()
+    }
+
+    fn issuer_chain() -> String {
+        url_encode(&format!(
+            "{}{}",
+            include_str!("../data/tests/processor_ca.pem"),
+            include_str!("../data/tests/root_ca.pem")
+        ))
+    }
+
+    #[test]
+    fn pck_crl_url() {
+        let client = PcsClient::new();
+        assert_eq!(client.base_url(), INTEL_PCS_URL);
+        assert_eq!(
+            client.pck_crl_url(PckCaType::Processor),
+            "https://api.trustedservices.intel.com/sgx/certification/v4/pckcrl?ca=processor&encoding=der"
+        );
+    }
+
+    #[test]
+    fn pck_crl_url_of_pccs() {
+        let client = PcsClient::with_base_url("https://pccs.example.com:8081/");
+        assert_eq!(
+            client.pck_crl_url(PckCaType::Platform),
+            "https://pccs.example.com:8081/sgx/certification/v4/pckcrl?ca=platform&encoding=der"
+        );
+    }
+
+    #[test]
+    fn root_ca_crl_url_of_intel_root_ca() {
+        assert_eq!(
+            PcsClient::new().root_ca_crl_url(),
+            Ok("https://certificates.trustedservices.intel.com/IntelSGXRootCA.der".to_string())
+        );
+    }
+
+    #[test]
+    fn root_ca_crl_url_of_pccs() {
+        let client = PcsClient::with_base_url("https://pccs.example.com:8081").with_api_version(3);
+        assert_eq!(
+            client.root_ca_crl_url(),
+            Ok("https://pccs.example.com:8081/sgx/certification/v3/rootcacrl".to_string())
+        );
+    }
+
+    #[test]
+    fn root_ca_crl_url_is_configurable() {
+        let client = PcsClient::with_base_url("https://pccs.example.com")
+            .with_root_ca_crl_url("https://crl.example.com/root.der");
+        assert_eq!(
+            client.root_ca_crl_url(),
+            Ok("https://crl.example.com/root.der".to_string())
+        );
+    }
+
+    #[test]
+    fn status_errors() {
+        let url = "https://pccs.example.com";
+        for status in [429, 500, 503] {
+            let error = status_error(url, status);
+            assert_eq!(
+                error,
+                Error::PcsUnavailable {
+                    url: url.to_string(),
+                    status
+                }
+            );
+            assert!(error.is_transient());
+        }
+        for status in [400, 401, 404] {
+            let error = status_error(url, status);
+            assert_eq!(
+                error,
+                Error::PcsRequest {
+                    url: url.to_string(),
+                    status
+                }
+            );
+            assert!(!error.is_transient());
+        }
+    }
+
+    #[test]
+    fn missing_issuer_chain_is_not_transient() {
+        let error = missing_issuer_chain("https://pccs.example.com", TCB_INFO_ISSUER_CHAIN_HEADER);
+        assert!(!error.is_transient());
+        assert!(fetch_error("https://pccs.example.com", "connection reset").is_transient());
+    }
+
+    #[test]
+    fn retry_policy_is_configurable() {
+        assert_eq!(PcsClient::new().retry_policy(), &RetryPolicy::new());
+
+        let client = PcsClient::new().with_retry_policy(RetryPolicy::none());
+        assert_eq!(client.retry_policy().max_retries(), 0);
+    }
+
+    #[test]
+    fn urls_of_older_api_version() {
+        let client = PcsClient::with_base_url("https://pccs.example.com").with_api_version(3);
+        let fmspc = Fmspc::new([0x00, 0x90, 0x6E, 0xD5, 0x00, 0x00]);
+        assert_eq!(client.api_version(), 3);
+        assert_eq!(
+            client.pck_crl_url(PckCaType::Processor),
+            "https://pccs.example.com/sgx/certification/v3/pckcrl?ca=processor&encoding=der"
+        );
+        assert_eq!(
+            client.tcb_info_url(&fmspc, TeeType::Sgx),
+            "https://pccs.example.com/sgx/certification/v3/tcb?fmspc=00906ED50000"
+        );
+        assert_eq!(
+            client.qe_identity_url(TeeType::Sgx),
+            "https://pccs.example.com/sgx/certification/v3/qe/identity"
+        );
+        assert_eq!(
+            client.tcb_info_issuer_chain_header(),
+            V3_TCB_INFO_ISSUER_CHAIN_HEADER
+        );
+    }
+
+    #[test]
+    fn extra_headers() {
+        let client = PcsClient::new()
+            .with_header("Ocp-Apim-Subscription-Key", "key")
+            .with_header("Authorization", "Bearer token");
+        assert_eq!(client.api_version(), PCS_API_VERSION);
+        assert_eq!(
+            client.headers(),
+            [
+                ("Ocp-Apim-Subscription-Key".to_string(), "key".to_string()),
+                ("Authorization".to_string(), "Bearer token".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tcb_info_url() {
+        let client = PcsClient::new();
+        let fmspc = Fmspc::new([0x00, 0x90, 0x6E, 0xD5, 0x00, 0x00]);
+        assert_eq!(
+            client.tcb_info_url(&fmspc, TeeType::Sgx),
+            "https://api.trustedservices.intel.com/sgx/certification/v4/tcb?fmspc=00906ED50000"
+        );
+        assert_eq!(
+            client.tcb_info_url(&fmspc, TeeType::Tdx),
+            "https://api.trustedservices.intel.com/tdx/certification/v4/tcb?fmspc=00906ED50000"
+        );
+    }
+
+    struct TestChainVerifier(core::result::Result<(), CertificateChainVerifierError>);
+
+    impl CertificateChainVerifier for TestChainVerifier {
+        fn verify_certificate_chain<'a, 'b>(
+            &self,
+            _certificate_chain: impl IntoIterator<Item = &'a Certificate>,
+            _crls: impl IntoIterator<Item = &'b CertificateList>,
+            _time: impl Into<Option<DateTime>>,
+        ) -> core::result::Result<(), CertificateChainVerifierError> {
+            self.0.clone()
+        }
+    }
+
+    fn tcb_signing_chain() -> String {
+        url_encode(&format!(
+            "{}{}",
+            include_str!("../data/tests/tcb_signer.pem"),
+            include_str!("../data/tests/root_ca.pem")
+        ))
+    }
+
+    fn tcb_info_time() -> DateTime {
+        "2023-07-12T19:56:44Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time")
+    }
+
+    #[test]
+    fn verify_tcb_info_response() {
+        let json = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+
+        let tcb_info = verify_tcb_info(
+            json.as_bytes(),
+            &tcb_signing_chain(),
+            &TestChainVerifier(Ok(())),
+            &[],
+            Some(tcb_info_time()),
+        )
+        .expect("Failed to verify TCB info");
+
+        assert_eq!(tcb_info.fmspc(), [0x00, 0x90, 0x6E, 0xD5, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn tcb_info_response_with_bad_signing_chain_fails() {
+        let json = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+
+        let result = verify_tcb_info(
+            json.as_bytes(),
+            &tcb_signing_chain(),
+            &TestChainVerifier(Err(CertificateChainVerifierError::CertificateRevoked)),
+            &[],
+            Some(tcb_info_time()),
+        );
+
+        assert_matches!(result, Err(Error::Collateral(_)));
+    }
+
+    #[test]
+    fn tcb_info_response_with_modified_tcb_info_fails() {
+        let json = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json")
+            .replace("INTEL-SA-00334", "INTEL-SA-00335");
+
+        let result = verify_tcb_info(
+            json.as_bytes(),
+            &tcb_signing_chain(),
+            &TestChainVerifier(Ok(())),
+            &[],
+            Some(tcb_info_time()),
+        );
+
+        assert_matches!(result, Err(Error::Collateral(_)));
+    }
+
+    #[test]
+    fn tcb_info_response_with_invalid_utf8_fails() {
+        let mut json = include_bytes!("../data/tests/fmspc_00906ED50000_2023_07_12.json").to_vec();
+        json[1] = 0xFF;
+
+        let result = verify_tcb_info(
+            &json,
+            &tcb_signing_chain(),
+            &TestChainVerifier(Ok(())),
+            &[],
+            Some(tcb_info_time()),
+        );
+
+        assert_matches!(result, Err(Error::Collateral(reason)) if reason.contains("UTF-8"));
+    }
+
+    #[test]
+    fn qe_identity_url() {
+        let client = PcsClient::new();
+        assert_eq!(
+            client.qe_identity_url(TeeType::Sgx),
+            "https://api.trustedservices.intel.com/sgx/certification/v4/qe/identity"
+        );
+        assert_eq!(
+            client.qe_identity_url(TeeType::Tdx),
+            "https://api.trustedservices.intel.com/tdx/certification/v4/qe/identity"
+        );
+    }
+
+    fn qe_identity_time() -> DateTime {
+        "2023-07-12T20:48:25Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time")
+    }
+
+    #[test]
+    fn verify_qe_identity_response() {
+        let json = include_str!("../data/tests/qe_identity.json");
+
+        let qe_identity = verify_qe_identity(
+            json.as_bytes(),
+            &tcb_signing_chain(),
+            &TestChainVerifier(Ok(())),
+            &[],
+            Some(qe_identity_time()),
+        )
+        .expect("Failed to verify QE identity");
+
+        assert_eq!(qe_identity.isv_prod_id(), IsvProductId::from(1));
+    }
+
+    #[test]
+    fn qe_identity_response_with_bad_signing_chain_fails() {
+        let json = include_str!("../data/tests/qe_identity.json");
+
+        let result = verify_qe_identity(
+            json.as_bytes(),
+            &tcb_signing_chain(),
+            &TestChainVerifier(Err(CertificateChainVerifierError::CertificateRevoked)),
+            &[],
+            Some(qe_identity_time()),
+        );
+
+        assert_matches!(result, Err(Error::Collateral(_)));
+    }
+
+    #[test]
+    fn qe_identity_response_with_invalid_chain_encoding_fails() {
+        let json = include_str!("../data/tests/qe_identity.json");
+
+        let result = verify_qe_identity(
+            json.as_bytes(),
+            "%2",
+            &TestChainVerifier(Ok(())),
+            &[],
+            Some(qe_identity_time()),
+        );
+
+        assert_matches!(result, Err(Error::UrlEncoding));
+    }
+
+    #[test]
+    fn pck_crl_from_der_response() {
+        let crl = include_bytes!("../data/tests/processor_crl.der");
+
+        let pck_crl = PckCrl::from_response(crl, &issuer_chain()).expect("Failed to decode");
+
+        let (_crl, issuer_chain) = pck_crl.into_parts();
+        assert_eq!(issuer_chain.len(), 2);
+    }
+
+    #[test]
+    fn pck_crl_from_pem_response() {
+        let crl = include_str!("../data/tests/processor_crl.pem");
+
+        assert!(PckCrl::from_response(crl.as_bytes(), &issuer_chain()).is_ok());
+    }
+
+    #[test]
+    fn pck_crl_with_invalid_issuer_chain_encoding() {
+        let crl = include_bytes!("../data/tests/processor_crl.der");
+
+        assert_matches!(PckCrl::from_response(crl, "%2"), Err(Error::UrlEncoding));
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Certificates from degenerate PKCS#7, CMS, SignedData bundles.
+//!
+//! CA tooling often distributes certificate chains as a SignedData without
+//! any content or signers, see
+//! [RFC 5652 Section 5](https://datatracker.ietf.org/doc/html/rfc5652#section-5).
+//! Only the certificates are of interest, the rest of the SignedData is
+//! decoded as opaque values.
+
+use alloc::vec::Vec;
+use der::{
+    asn1::{Any, ObjectIdentifier},
+    Decode, Sequence, Tag, Tagged,
+};
+use x509_cert::Certificate;
+
+/// The `id-signedData` content type
+const SIGNED_DATA_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+
+/// The CMS `ContentInfo`
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct ContentInfo {
+    content_type: ObjectIdentifier,
+    #[asn1(context_specific = "0", tag_mode = "EXPLICIT")]
+    content: Any,
+}
+
+/// The CMS `SignedData`.
+///
+/// The `certificates` are decoded as a `SEQUENCE OF`, instead of the
+/// specified `SET OF`, to keep them in the order they were bundled.
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct SignedData {
+    version: u8,
+    digest_algorithms: Any,
+    encap_content_info: Any,
+    #[asn1(
+        context_specific = "0",
+        tag_mode = "IMPLICIT",
+        constructed = "true",
+        optional = "true"
+    )]
+    certificates: Option<Vec<Any>>,
+    #[asn1(
+        context_specific = "1",
+        tag_mode = "IMPLICIT",
+        constructed = "true",
+        optional = "true"
+    )]
+    crls: Option<Vec<Any>>,
+    signer_infos: Any,
+}
+
+/// The X.509 certificates of the DER encoded PKCS#7 `ContentInfo`, in the
+/// order they were bundled.
+///
+/// Other kinds of certificates, like attribute certificates, are skipped.
+///
+/// # Errors
+/// `der::Error` if the bytes are not a SignedData or a certificate can not
+/// be decoded. A content type other than SignedData is reported as
+/// `der::ErrorKind::OidUnknown`.
+pub(crate) fn certificates(der: &[u8]) -> der::Result<Vec<Certificate>> {
+    let content_info = ContentInfo::from_der(der)?;
+    if content_info.content_type != SIGNED_DATA_OID {
+        return Err(der::ErrorKind::OidUnknown {
+            oid: content_info.content_type,
+        }
+        .into());
+    }
+    let signed_data = content_info.content.decode_as::<SignedData>()?;
+    signed_data
+        .certificates
+        .into_iter()
+        .flatten()
+        .filter(|choice| choice.tag() == Tag::Sequence)
+        .map(|choice| choice.decode_as::<Certificate>())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_matches::assert_matches;
+    use der::{asn1::OctetString, DecodePem, Encode};
+
+    const CHAIN: &[u8] = include_bytes!("../data/tests/chain.p7b");
+
+    #[test]
+    fn certificates_in_bundle_order() {
+        let certificates = certificates(CHAIN).expect("Failed to decode bundle");
+        let expected = [
+            include_str!("../data/tests/leaf_cert.pem"),
+            include_str!("../data/tests/processor_ca.pem"),
+            include_str!("../data/tests/root_ca.pem"),
+        ]
+        .map(|pem| Certificate::from_pem(pem).expect("Failed to decode certificate"));
+        assert_eq!(certificates, expected);
+    }
+
+    #[test]
+    fn other_content_type_fails() {
+        let data = ContentInfo {
+            content_type: ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.1"),
+            content: Any::encode_from(&OctetString::new([1u8, 2]).expect("Failed to create"))
+                .expect("Failed to encode content"),
+        }
+        .to_der()
+        .expect("Failed to encode content info");
+        assert_matches!(
+            certificates(&data).map_err(|e| e.kind()),
+            Err(der::ErrorKind::OidUnknown { .. })
+        );
+    }
+
+    #[test]
+    fn truncated_bundle_fails() {
+        assert!(certificates(&CHAIN[..CHAIN.len() - 1]).is_err());
+    }
+}
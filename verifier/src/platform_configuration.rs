@@ -0,0 +1,434 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Verification of the platform configuration for TCB statuses which need
+//! additional configuration.
+//!
+//! The TCB statuses `ConfigurationNeeded`, `ConfigurationAndSWHardeningNeeded`
+//! and `OutOfDateConfigurationNeeded` indicate that the platform is only
+//! trustworthy when configured appropriately, for example with SMT disabled.
+//! Multi-package platforms report their configuration in the SGX extensions
+//! of the PCK certificate, see section 1.3.5 of
+//! <https://api.trustedservices.intel.com/documents/Intel_SGX_PCK_Certificate_CRL_Spec-1.5.pdf>
+
+use crate::{
+    advisories::AdvisoryStatus, choice_to_status_message, Accessor, Advisories, Error,
+    VerificationMessage, VerificationOutput, Verifier,
+};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use der::{asn1::ObjectIdentifier, Any, Decode, Sequence};
+use serde::{Deserialize, Serialize};
+use x509_cert::Certificate;
+
+/// The SGX extensions of a PCK certificate
+const SGX_EXTENSIONS_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1");
+/// The platform configuration within the SGX extensions
+const CONFIGURATION_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.7");
+const DYNAMIC_PLATFORM_OID: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.7.1");
+const CACHED_KEYS_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.7.2");
+const SMT_ENABLED_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.7.3");
+
+/// An entry of the SGX extensions, the value depends on the `id`.
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+pub(crate) struct SgxExtension {
+    pub(crate) id: ObjectIdentifier,
+    pub(crate) value: Any,
+}
+
+/// The entries of the SGX extensions of a PCK certificate.
+///
+/// Empty when the certificate has no SGX extensions.
+pub(crate) fn sgx_extensions(certificate: &Certificate) -> Result<Vec<SgxExtension>, Error> {
+    let Some(extension) = certificate
+        .tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .find(|extension| extension.extn_id == SGX_EXTENSIONS_OID)
+    else {
+        return Ok(Vec::new());
+    };
+    Ok(Vec::<SgxExtension>::from_der(
+        extension.extn_value.as_bytes(),
+    )?)
+}
+
+/// The configuration of an SGX platform.
+///
+/// Each setting is `None` when it is not known. When used as the required
+/// configuration, `None` means any value is acceptable.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PlatformConfiguration {
+    dynamic_platform: Option<bool>,
+    cached_keys: Option<bool>,
+    smt_enabled: Option<bool>,
+}
+
+impl PlatformConfiguration {
+    /// Create a new instance.
+    ///
+    /// # Arguments:
+    /// * `dynamic_platform` - Whether the platform can be extended with
+    ///   additional packages.
+    /// * `cached_keys` - Whether the platform root keys are cached by SGX
+    ///   registration backend services.
+    /// * `smt_enabled` - Whether simultaneous multithreading (hyper-threading)
+    ///   is enabled.
+    pub fn new(
+        dynamic_platform: Option<bool>,
+        cached_keys: Option<bool>,
+        smt_enabled: Option<bool>,
+    ) -> Self {
+        Self {
+            dynamic_platform,
+            cached_keys,
+            smt_enabled,
+        }
+    }
+
+    /// Whether the platform can be extended with additional packages
+    pub fn dynamic_platform(&self) -> Option<bool> {
+        self.dynamic_platform
+    }
+
+    /// Whether the platform root keys are cached by SGX registration backend
+    /// services
+    pub fn cached_keys(&self) -> Option<bool> {
+        self.cached_keys
+    }
+
+    /// Whether simultaneous multithreading is enabled
+    pub fn smt_enabled(&self) -> Option<bool> {
+        self.smt_enabled
+    }
+
+    /// Returns `true` if `self` has every setting of `required`.
+    ///
+    /// An unknown setting in `self` does not satisfy a setting in `required`.
+    fn satisfies(&self, required: &Self) -> bool {
+        let matches =
+            |actual: Option<bool>, required: Option<bool>| required.is_none() || actual == required;
+        matches(self.dynamic_platform, required.dynamic_platform)
+            && matches(self.cached_keys, required.cached_keys)
+            && matches(self.smt_enabled, required.smt_enabled)
+    }
+}
+
+impl TryFrom<&Certificate> for PlatformConfiguration {
+    type Error = Error;
+
+    /// Get the platform configuration from the SGX extensions of a PCK
+    /// certificate.
+    ///
+    /// Only PCK certificates issued by the Platform CA have a configuration.
+    /// Settings which aren't present are `None`.
+    fn try_from(certificate: &Certificate) -> Result<Self, Self::Error> {
+        let mut configuration = Self::default();
+        let sgx_extensions = sgx_extensions(certificate)?;
+        let Some(entry) = sgx_extensions
+            .iter()
+            .find(|entry| entry.id == CONFIGURATION_OID)
+        else {
+            return Ok(configuration);
+        };
+
+        for setting in entry.value.decode_as::<Vec<SgxExtension>>()? {
+            let value = Some(setting.value.decode_as::<bool>()?);
+            match setting.id {
+                DYNAMIC_PLATFORM_OID => configuration.dynamic_platform = value,
+                CACHED_KEYS_OID => configuration.cached_keys = value,
+                SMT_ENABLED_OID => configuration.smt_enabled = value,
+                _ => {}
+            }
+        }
+        Ok(configuration)
+    }
+}
+
+impl Display for PlatformConfiguration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let settings = [
+            ("dynamic platform", self.dynamic_platform),
+            ("cached keys", self.cached_keys),
+            ("SMT enabled", self.smt_enabled),
+        ];
+        let mut known = settings
+            .iter()
+            .filter_map(|(name, value)| value.map(|value| (name, value)))
+            .peekable();
+        if known.peek().is_none() {
+            return write!(f, "(none)");
+        }
+        write!(f, "{{")?;
+        for (index, (name, value)) in known.enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{name}: {value}")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Verifier for requiring a platform configuration when the TCB status
+/// indicates the platform needs additional configuration.
+///
+/// For any other TCB status the platform configuration is not checked. This
+/// allows accepting `ConfigurationNeeded` statuses, via the advisories of a
+/// [`TrustedIdentity`](crate::TrustedIdentity), only for platforms which have
+/// been configured appropriately.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PlatformConfigurationVerifier {
+    required: PlatformConfiguration,
+}
+
+impl PlatformConfigurationVerifier {
+    /// Create a new instance.
+    ///
+    /// # Arguments:
+    /// * `required` - The settings the platform must have when its TCB status
+    ///   needs configuration. For example, to require SMT to be disabled:
+    ///   `PlatformConfiguration::new(None, None, Some(false))`.
+    pub fn new(required: PlatformConfiguration) -> Self {
+        Self { required }
+    }
+}
+
+impl<E> Verifier<E> for PlatformConfigurationVerifier
+where
+    E: Accessor<Advisories> + Accessor<PlatformConfiguration>,
+{
+    type Value = (AdvisoryStatus, PlatformConfiguration);
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let advisories: Advisories = evidence.get();
+        let status = advisories.status();
+        let actual: PlatformConfiguration = evidence.get();
+
+        let is_success =
+            (!status.is_configuration_needed() || actual.satisfies(&self.required)) as u8;
+
+        VerificationOutput::new((status, actual), is_success.into())
+    }
+}
+
+impl VerificationMessage<(AdvisoryStatus, PlatformConfiguration)>
+    for PlatformConfigurationVerifier
+{
+    fn fmt_padded(
+        &self,
+        f: &mut Formatter<'_>,
+        pad: usize,
+        output: &VerificationOutput<(AdvisoryStatus, PlatformConfiguration)>,
+    ) -> core::fmt::Result {
+        let is_success = output.is_success();
+        let status = choice_to_status_message(is_success);
+        let (tcb_status, actual) = &output.value;
+        let required = &self.required;
+
+        if !tcb_status.is_configuration_needed() {
+            return write!(
+                f,
+                "{:pad$}{status} The platform configuration is not required for the TCB status {tcb_status:?}",
+                ""
+            );
+        }
+
+        write!(
+            f,
+            "{:pad$}{status} The platform configuration should be {required} for the TCB status {tcb_status:?}",
+            ""
+        )?;
+        if is_success.unwrap_u8() == 0 {
+            write!(f, ", but the actual platform configuration was {actual}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::VerificationTreeDisplay;
+    use alloc::{format, string::ToString, vec};
+    use der::{DecodePem, Encode};
+    use yare::parameterized;
+
+    const LEAF_CERT: &str = include_str!("../data/tests/leaf_cert.pem");
+
+    #[derive(Clone)]
+    struct Platform {
+        advisories: Advisories,
+        configuration: PlatformConfiguration,
+    }
+
+    impl Accessor<Advisories> for Platform {
+        fn get(&self) -> Advisories {
+            self.advisories.clone()
+        }
+    }
+
+    impl Accessor<PlatformConfiguration> for Platform {
+        fn get(&self) -> PlatformConfiguration {
+            self.configuration
+        }
+    }
+
+    fn platform(status: AdvisoryStatus, configuration: PlatformConfiguration) -> Platform {
+        Platform {
+            advisories: Advisories::new(["INTEL-SA-00161"], status),
+            configuration,
+        }
+    }
+
+    fn setting(id: ObjectIdentifier, value: bool) -> SgxExtension {
+        let value = Any::encode_from(&value).expect("failed to encode setting");
+        SgxExtension { id, value }
+    }
+
+    // The leaf certificate with the platform configuration added to its SGX
+    // extensions. The signature is no longer valid, which doesn't matter for
+    // parsing.
+    fn leaf_with_configuration(settings: Vec<SgxExtension>) -> Certificate {
+        let mut leaf = Certificate::from_pem(LEAF_CERT).expect("failed to parse cert");
+        let extension = leaf
+            .tbs_certificate
+            .extensions
+            .iter_mut()
+            .flatten()
+            .find(|extension| extension.extn_id == SGX_EXTENSIONS_OID)
+            .expect("missing SGX extensions");
+        let mut sgx_extensions = Vec::<SgxExtension>::from_der(extension.extn_value.as_bytes())
+            .expect("failed to decode SGX extensions");
+        let value = Any::encode_from(&settings).expect("failed to encode configuration");
+        sgx_extensions.push(SgxExtension {
+            id: CONFIGURATION_OID,
+            value,
+        });
+        let der = sgx_extensions
+            .to_der()
+            .expect("failed to encode SGX extensions");
+        extension.extn_value = der::asn1::OctetString::new(der).expect("failed to create octets");
+        leaf
+    }
+
+    #[test]
+    fn configuration_from_processor_pck_certificate() {
+        let leaf = Certificate::from_pem(LEAF_CERT).expect("failed to parse cert");
+        assert_eq!(
+            PlatformConfiguration::try_from(&leaf),
+            Ok(PlatformConfiguration::default())
+        );
+    }
+
+    #[test]
+    fn configuration_from_platform_pck_certificate() {
+        let leaf = leaf_with_configuration(vec![
+            setting(DYNAMIC_PLATFORM_OID, true),
+            setting(CACHED_KEYS_OID, false),
+            setting(SMT_ENABLED_OID, false),
+        ]);
+        assert_eq!(
+            PlatformConfiguration::try_from(&leaf),
+            Ok(PlatformConfiguration::new(
+                Some(true),
+                Some(false),
+                Some(false)
+            ))
+        );
+    }
+
+    #[test]
+    fn partial_configuration_from_pck_certificate() {
+        let leaf = leaf_with_configuration(vec![setting(SMT_ENABLED_OID, true)]);
+        assert_eq!(
+            PlatformConfiguration::try_from(&leaf),
+            Ok(PlatformConfiguration::new(None, None, Some(true)))
+        );
+    }
+
+    #[test]
+    fn display_configuration() {
+        let configuration = PlatformConfiguration::new(Some(true), None, Some(false));
+        assert_eq!(
+            configuration.to_string(),
+            "{dynamic platform: true, SMT enabled: false}"
+        );
+        assert_eq!(PlatformConfiguration::default().to_string(), "(none)");
+    }
+
+    #[parameterized(
+        configuration_needed = { AdvisoryStatus::ConfigurationNeeded },
+        configuration_and_sw_hardening_needed = { AdvisoryStatus::ConfigurationAndSWHardeningNeeded },
+        out_of_date_configuration_needed = { AdvisoryStatus::OutOfDateConfigurationNeeded },
+    )]
+    fn configuration_required(status: AdvisoryStatus) {
+        let required = PlatformConfiguration::new(None, None, Some(false));
+        let verifier = PlatformConfigurationVerifier::new(required);
+
+        let configured = platform(status, PlatformConfiguration::new(None, None, Some(false)));
+        assert_eq!(verifier.verify(&configured).is_success().unwrap_u8(), 1);
+
+        let smt_enabled = platform(status, PlatformConfiguration::new(None, None, Some(true)));
+        assert_eq!(verifier.verify(&smt_enabled).is_failure().unwrap_u8(), 1);
+
+        let unknown = platform(status, PlatformConfiguration::default());
+        assert_eq!(verifier.verify(&unknown).is_failure().unwrap_u8(), 1);
+    }
+
+    #[parameterized(
+        up_to_date = { AdvisoryStatus::UpToDate },
+        sw_hardening_needed = { AdvisoryStatus::SWHardeningNeeded },
+        out_of_date = { AdvisoryStatus::OutOfDate },
+    )]
+    fn configuration_not_required(status: AdvisoryStatus) {
+        let required = PlatformConfiguration::new(None, None, Some(false));
+        let verifier = PlatformConfigurationVerifier::new(required);
+        let smt_enabled = platform(status, PlatformConfiguration::new(None, None, Some(true)));
+        assert_eq!(verifier.verify(&smt_enabled).is_success().unwrap_u8(), 1);
+    }
+
+    #[test]
+    fn display_configuration_met() {
+        let required = PlatformConfiguration::new(None, None, Some(false));
+        let verifier = PlatformConfigurationVerifier::new(required);
+        let configured = platform(
+            AdvisoryStatus::ConfigurationNeeded,
+            PlatformConfiguration::new(Some(false), None, Some(false)),
+        );
+        let verification = verifier.verify(&configured);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [x] The platform configuration should be {SMT enabled: false} for the TCB status ConfigurationNeeded"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn display_configuration_not_met() {
+        let required = PlatformConfiguration::new(None, None, Some(false));
+        let verifier = PlatformConfigurationVerifier::new(required);
+        let smt_enabled = platform(
+            AdvisoryStatus::ConfigurationAndSWHardeningNeeded,
+            PlatformConfiguration::new(None, None, Some(true)),
+        );
+        let verification = verifier.verify(&smt_enabled);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [ ] The platform configuration should be {SMT enabled: false} for the TCB status ConfigurationAndSWHardeningNeeded, but the actual platform configuration was {SMT enabled: true}"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn display_configuration_not_required() {
+        let verifier = PlatformConfigurationVerifier::new(PlatformConfiguration::default());
+        let up_to_date = platform(AdvisoryStatus::UpToDate, PlatformConfiguration::default());
+        let verification = verifier.verify(&up_to_date);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [x] The platform configuration is not required for the TCB status UpToDate"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Identifiers of SGX platforms, as used in PCK certificates and when
+//! requesting collateral from the Intel PCS or a PCCS.
+//!
+//! Each identifier is its own type so they can not be transposed, for example
+//! passing a PCE ID where an FMSPC is expected. They are displayed and
+//! serialized as the upper case hex strings used by the PCS API, and parse
+//! from hex strings of either case.
+
+use crate::Error;
+use alloc::{format, string::String};
+use core::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+use mc_sgx_dcap_types::FMSPC_SIZE;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Size of a PCE ID in bytes
+pub const PCE_ID_SIZE: usize = 2;
+
+/// Size of a PPID in bytes
+pub const PPID_SIZE: usize = 16;
+
+macro_rules! platform_id {
+    ($(#[$meta:meta])* $name:ident, $size:expr, $kind:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name([u8; $size]);
+
+        impl $name {
+            /// Create a new instance from the raw bytes
+            pub const fn new(bytes: [u8; $size]) -> Self {
+                Self(bytes)
+            }
+
+            /// The raw bytes
+            pub const fn as_bytes(&self) -> &[u8; $size] {
+                &self.0
+            }
+
+            /// Try to parse from a hex string, of either case.
+            ///
+            /// # Errors
+            /// `Error::PlatformId` if `hex` is not valid hex of the correct
+            /// length.
+            pub fn try_from_hex(hex: &str) -> Result<Self, Error> {
+                let mut bytes = [0u8; $size];
+                hex::decode_to_slice(hex, &mut bytes).map_err(|e| Error::PlatformId {
+                    kind: $kind.into(),
+                    error: format!("{e}"),
+                })?;
+                Ok(Self(bytes))
+            }
+        }
+
+        impl From<[u8; $size]> for $name {
+            fn from(bytes: [u8; $size]) -> Self {
+                Self(bytes)
+            }
+        }
+
+        impl From<$name> for [u8; $size] {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl TryFrom<&[u8]> for $name {
+            type Error = Error;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                let bytes = bytes.try_into().map_err(|_| Error::PlatformId {
+                    kind: $kind.into(),
+                    error: format!("expected {} bytes got {}", $size, bytes.len()),
+                })?;
+                Ok(Self(bytes))
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl PartialEq<[u8; $size]> for $name {
+            fn eq(&self, other: &[u8; $size]) -> bool {
+                &self.0 == other
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(hex: &str) -> Result<Self, Self::Err> {
+                Self::try_from_hex(hex)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                for byte in self.0 {
+                    write!(f, "{byte:02X}")?;
+                }
+                Ok(())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let hex = String::deserialize(deserializer)?;
+                Self::try_from_hex(&hex).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+platform_id!(
+    /// The Family-Model-Stepping-Platform-CustomSKU of a platform.
+    ///
+    /// Selects the TCB info for the platform, see the `fmspc` query parameter
+    /// of <https://api.portal.trustedservices.intel.com/documentation#pcs-tcb-info-v4>.
+    Fmspc,
+    FMSPC_SIZE,
+    "FMSPC"
+);
+
+platform_id!(
+    /// The ID of the Provisioning Certification Enclave (PCE) of a platform.
+    PceId,
+    PCE_ID_SIZE,
+    "PCE ID"
+);
+
+platform_id!(
+    /// The Platform Provisioning ID (PPID) of a platform.
+    ///
+    /// Unique per platform, so it should be treated as personal data. The PCS
+    /// is only ever given the encrypted PPID.
+    Ppid,
+    PPID_SIZE,
+    "PPID"
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use assert_matches::assert_matches;
+
+    const FMSPC: Fmspc = Fmspc::new([0x00, 0x90, 0x6E, 0xD5, 0x00, 0x00]);
+
+    #[test]
+    fn fmspc_displays_as_upper_case_hex() {
+        assert_eq!(FMSPC.to_string(), "00906ED50000");
+    }
+
+    #[test]
+    fn fmspc_from_either_case_hex() {
+        assert_eq!("00906ED50000".parse::<Fmspc>(), Ok(FMSPC));
+        assert_eq!("00906ed50000".parse::<Fmspc>(), Ok(FMSPC));
+    }
+
+    #[test]
+    fn fmspc_from_short_hex_fails() {
+        assert_matches!(
+            "00906ED500".parse::<Fmspc>(),
+            Err(Error::PlatformId { kind, .. }) if kind == "FMSPC"
+        );
+    }
+
+    #[test]
+    fn pce_id_from_invalid_hex_fails() {
+        assert_matches!(
+            PceId::try_from_hex("00zz"),
+            Err(Error::PlatformId { kind, .. }) if kind == "PCE ID"
+        );
+    }
+
+    #[test]
+    fn ppid_from_slice() {
+        let bytes = [7u8; PPID_SIZE];
+        assert_eq!(Ppid::try_from(&bytes[..]), Ok(Ppid::new(bytes)));
+    }
+
+    #[test]
+    fn ppid_from_wrong_size_slice_fails() {
+        let bytes = [7u8; PPID_SIZE + 1];
+        assert_eq!(
+            Ppid::try_from(&bytes[..]),
+            Err(Error::PlatformId {
+                kind: "PPID".into(),
+                error: "expected 16 bytes got 17".into()
+            })
+        );
+    }
+
+    #[test]
+    fn fmspc_serde_round_trip() {
+        let json = serde_json::to_string(&FMSPC).expect("failed to serialize");
+        assert_eq!(json, r#""00906ED50000""#);
+        let fmspc = serde_json::from_str::<Fmspc>(&json).expect("failed to deserialize");
+        assert_eq!(fmspc, FMSPC);
+    }
+
+    #[test]
+    fn pce_id_deserialize_wrong_size_fails() {
+        assert!(serde_json::from_str::<PceId>(r#""000000""#).is_err());
+    }
+}
@@ -0,0 +1,448 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Adapter for deciding on evidence with external policy engines, like
+//! OPA/Rego or cedar.
+//!
+//! The facts of the evidence are provided to the engine as a [`PolicyInput`]
+//! and the engine's [`PolicyDecision`] is converted into a
+//! [`VerificationOutput`] so it can be composed with the other verifiers in
+//! this crate.
+
+use crate::{
+    choice_to_status_message, to_canonical_json, Accessor, Advisories, AdvisoryStatus, Error,
+    VerificationMessage, VerificationOutput, Verifier,
+};
+use alloc::{string::String, vec::Vec};
+use core::fmt::Formatter;
+use mc_sgx_core_types::{
+    AttributeFlags, Attributes, ConfigSvn, IsvProductId, IsvSvn, MiscellaneousSelect, MrEnclave,
+    MrSigner, ReportData,
+};
+use serde::{Deserialize, Serialize};
+
+/// The facts of the evidence provided as input to an external policy engine.
+///
+/// This serializes to a flat JSON object which can be used as the `input`
+/// document of OPA or as the context of a cedar request. Byte values, like the
+/// MRENCLAVE, are lower case hex strings. The attribute flags, XFRM, and
+/// MISCSELECT are the raw integers, with `debug` broken out of the flags so
+/// the common policy of denying debug enclaves needs no bit twiddling.
+///
+/// The facts are only as trustworthy as the evidence they came from. The
+/// evidence should be verified, for instance with an
+/// [`EvidenceVerifier`](crate::EvidenceVerifier), in addition to the policy
+/// engine's decision.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyInput {
+    mr_enclave: String,
+    mr_signer: String,
+    isv_product_id: u16,
+    isv_svn: u16,
+    config_svn: u16,
+    report_data: String,
+    attribute_flags: u64,
+    xfrm: u64,
+    debug: bool,
+    misc_select: u32,
+    tcb_status: AdvisoryStatus,
+    advisories: Advisories,
+}
+
+impl PolicyInput {
+    /// Create a new instance from the facts in `evidence`
+    pub fn new<E>(evidence: &E) -> Self
+    where
+        E: Accessor<MrEnclave>
+            + Accessor<MrSigner>
+            + Accessor<IsvProductId>
+            + Accessor<IsvSvn>
+            + Accessor<ConfigSvn>
+            + Accessor<ReportData>
+            + Accessor<Attributes>
+            + Accessor<MiscellaneousSelect>
+            + Accessor<Advisories>,
+    {
+        let mr_enclave: MrEnclave = evidence.get();
+        let mr_signer: MrSigner = evidence.get();
+        let isv_product_id: IsvProductId = evidence.get();
+        let isv_svn: IsvSvn = evidence.get();
+        let config_svn: ConfigSvn = evidence.get();
+        let report_data: ReportData = evidence.get();
+        let attributes: Attributes = evidence.get();
+        let misc_select: MiscellaneousSelect = evidence.get();
+        let advisories: Advisories = evidence.get();
+        let attribute_flags = attributes.flags();
+        Self {
+            mr_enclave: hex::encode(mr_enclave),
+            mr_signer: hex::encode(mr_signer),
+            isv_product_id: *isv_product_id.as_ref(),
+            isv_svn: *isv_svn.as_ref(),
+            config_svn: *config_svn.as_ref(),
+            report_data: hex::encode(report_data),
+            attribute_flags: attribute_flags.bits(),
+            xfrm: attributes.extended_features_mask().bits(),
+            debug: attribute_flags.contains(AttributeFlags::DEBUG),
+            misc_select: *misc_select.as_ref(),
+            tcb_status: advisories.status(),
+            advisories,
+        }
+    }
+
+    /// The JSON representation to provide to the policy engine
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// The canonical JSON representation, for signing, see
+    /// [`to_canonical_json()`](crate::to_canonical_json)
+    pub fn to_canonical_json(&self) -> Result<String, Error> {
+        to_canonical_json(self)
+    }
+}
+
+/// The decision of an external policy engine.
+///
+/// This deserializes from a JSON object of the form
+/// `{"allow": false, "reasons": ["..."]}`, where `reasons` is optional. This
+/// is the shape commonly returned by a Rego rule, for cedar the reasons are
+/// usually the IDs of the policies which determined the decision.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyDecision {
+    allow: bool,
+    #[serde(default)]
+    reasons: Vec<String>,
+}
+
+impl PolicyDecision {
+    /// Create a new instance
+    ///
+    /// # Arguments
+    /// * `allow` - Whether the policy engine allowed the evidence.
+    /// * `reasons` - The reasons the policy engine gave for the decision.
+    pub fn new<I, S>(allow: bool, reasons: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            allow,
+            reasons: reasons.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Parse the decision from the JSON output of a policy engine
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// The canonical JSON representation, for signing, see
+    /// [`to_canonical_json()`](crate::to_canonical_json)
+    pub fn to_canonical_json(&self) -> Result<String, Error> {
+        to_canonical_json(self)
+    }
+
+    /// Whether the policy engine allowed the evidence
+    pub fn is_allowed(&self) -> bool {
+        self.allow
+    }
+
+    /// The reasons the policy engine gave for the decision
+    pub fn reasons(&self) -> &[String] {
+        &self.reasons
+    }
+}
+
+/// An external policy engine, like OPA/Rego or cedar.
+///
+/// Implementations are responsible for running the engine, for instance an
+/// OPA policy compiled to wasm, with the [`PolicyInput`] and converting the
+/// result into a [`PolicyDecision`].
+pub trait PolicyEngine {
+    /// Evaluate the `input` with the policy engine.
+    ///
+    /// # Errors
+    /// `Error::PolicyEngine` if the engine failed to evaluate the `input`.
+    fn evaluate(&self, input: &PolicyInput) -> Result<PolicyDecision, Error>;
+}
+
+impl<F> PolicyEngine for F
+where
+    F: Fn(&PolicyInput) -> Result<PolicyDecision, Error>,
+{
+    fn evaluate(&self, input: &PolicyInput) -> Result<PolicyDecision, Error> {
+        self(input)
+    }
+}
+
+/// Verifier which delegates the decision to an external [`PolicyEngine`].
+///
+/// The verification succeeds only when the engine allows the evidence. An
+/// engine failing to evaluate the evidence is treated as a denial.
+#[derive(Debug, Clone)]
+pub struct PolicyEngineVerifier<P> {
+    name: String,
+    engine: P,
+}
+
+impl<P: PolicyEngine> PolicyEngineVerifier<P> {
+    /// Create a new instance
+    ///
+    /// # Arguments
+    /// * `name` - The name of the policy, used when displaying the
+    ///   verification results.
+    /// * `engine` - The policy engine to evaluate the evidence with.
+    pub fn new(name: impl Into<String>, engine: P) -> Self {
+        Self {
+            name: name.into(),
+            engine,
+        }
+    }
+}
+
+impl<P, E> Verifier<E> for PolicyEngineVerifier<P>
+where
+    P: PolicyEngine,
+    E: Accessor<MrEnclave>
+        + Accessor<MrSigner>
+        + Accessor<IsvProductId>
+        + Accessor<IsvSvn>
+        + Accessor<ConfigSvn>
+        + Accessor<ReportData>
+        + Accessor<Attributes>
+        + Accessor<MiscellaneousSelect>
+        + Accessor<Advisories>,
+{
+    type Value = Result<PolicyDecision, Error>;
+
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let input = PolicyInput::new(evidence);
+        let decision = self.engine.evaluate(&input);
+        let is_success = matches!(&decision, Ok(decision) if decision.is_allowed()) as u8;
+        VerificationOutput::new(decision, is_success.into())
+    }
+}
+
+impl<P> VerificationMessage<Result<PolicyDecision, Error>> for PolicyEngineVerifier<P> {
+    fn fmt_padded(
+        &self,
+        f: &mut Formatter<'_>,
+        pad: usize,
+        result: &VerificationOutput<Result<PolicyDecision, Error>>,
+    ) -> core::fmt::Result {
+        let status = choice_to_status_message(result.is_success());
+        let name = &self.name;
+        write!(f, "{:pad$}{status} The policy \"{name}\" ", "")?;
+        match &result.value {
+            Ok(decision) => {
+                if decision.is_allowed() {
+                    write!(f, "allowed the evidence")?;
+                } else {
+                    write!(f, "denied the evidence")?;
+                }
+                if !decision.reasons.is_empty() {
+                    write!(f, ": {}", decision.reasons.join(", "))?;
+                }
+                Ok(())
+            }
+            Err(error) => write!(f, "could not be evaluated: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::VerificationTreeDisplay;
+    use alloc::{format, vec};
+    use mc_sgx_core_types::ExtendedFeatureRequestMask;
+
+    #[derive(Clone)]
+    struct TestEvidence {
+        mr_enclave: MrEnclave,
+        attributes: Attributes,
+        advisories: Advisories,
+    }
+
+    impl TestEvidence {
+        fn new() -> Self {
+            Self {
+                mr_enclave: MrEnclave::from([0xAB; 32]),
+                attributes: Attributes::default()
+                    .set_flags(AttributeFlags::INITTED | AttributeFlags::MODE_64BIT)
+                    .set_extended_features_mask(ExtendedFeatureRequestMask::LEGACY),
+                advisories: Advisories::new(["INTEL-SA-00334"], AdvisoryStatus::SWHardeningNeeded),
+            }
+        }
+    }
+
+    impl Accessor<MrEnclave> for TestEvidence {
+        fn get(&self) -> MrEnclave {
+            self.mr_enclave
+        }
+    }
+
+    impl Accessor<MrSigner> for TestEvidence {
+        fn get(&self) -> MrSigner {
+            MrSigner::from([0x01; 32])
+        }
+    }
+
+    impl Accessor<IsvProductId> for TestEvidence {
+        fn get(&self) -> IsvProductId {
+            IsvProductId::from(2)
+        }
+    }
+
+    impl Accessor<IsvSvn> for TestEvidence {
+        fn get(&self) -> IsvSvn {
+            IsvSvn::from(3)
+        }
+    }
+
+    impl Accessor<ConfigSvn> for TestEvidence {
+        fn get(&self) -> ConfigSvn {
+            ConfigSvn::from(4)
+        }
+    }
+
+    impl Accessor<ReportData> for TestEvidence {
+        fn get(&self) -> ReportData {
+            ReportData::from([0x05; 64])
+        }
+    }
+
+    impl Accessor<Attributes> for TestEvidence {
+        fn get(&self) -> Attributes {
+            self.attributes
+        }
+    }
+
+    impl Accessor<MiscellaneousSelect> for TestEvidence {
+        fn get(&self) -> MiscellaneousSelect {
+            MiscellaneousSelect::from(6)
+        }
+    }
+
+    impl Accessor<Advisories> for TestEvidence {
+        fn get(&self) -> Advisories {
+            self.advisories.clone()
+        }
+    }
+
+    // A stand in for an engine, allows only the expected MRENCLAVE and
+    // denies any advisories
+    fn engine(input: &PolicyInput) -> Result<PolicyDecision, Error> {
+        let json: serde_json::Value = serde_json::from_str(&input.to_json()?)?;
+        let mut reasons = vec![];
+        if json["mr_enclave"] != "ab".repeat(32) {
+            reasons.push("unknown MRENCLAVE");
+        }
+        if json["advisories"]["status"] != "UpToDate" {
+            reasons.push("platform is not up to date");
+        }
+        Ok(PolicyDecision::new(reasons.is_empty(), reasons))
+    }
+
+    #[test]
+    fn policy_input_to_json() {
+        let input = PolicyInput::new(&TestEvidence::new());
+        let json = input.to_json().expect("Failed to serialize input");
+        let expected = format!(
+            concat!(
+                r#"{{"mr_enclave":"{}","mr_signer":"{}","isv_product_id":2,"isv_svn":3,"#,
+                r#""config_svn":4,"report_data":"{}","attribute_flags":5,"xfrm":3,"#,
+                r#""debug":false,"misc_select":6,"tcb_status":"SWHardeningNeeded","#,
+                r#""advisories":{{"ids":["INTEL-SA-00334"],"status":"SWHardeningNeeded"}}}}"#
+            ),
+            "ab".repeat(32),
+            "01".repeat(32),
+            "05".repeat(64),
+        );
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn policy_input_with_debug_enclave() {
+        let mut evidence = TestEvidence::new();
+        evidence.attributes = evidence
+            .attributes
+            .set_flags(AttributeFlags::INITTED | AttributeFlags::DEBUG);
+        let input = PolicyInput::new(&evidence);
+        let json: serde_json::Value =
+            serde_json::from_str(&input.to_json().expect("Failed to serialize input"))
+                .expect("Failed to parse input");
+        assert_eq!(json["debug"], true);
+        assert_eq!(json["attribute_flags"], 3);
+    }
+
+    #[test]
+    fn policy_decision_from_json() {
+        assert_eq!(
+            PolicyDecision::from_json(r#"{"allow": true}"#).expect("Failed to parse decision"),
+            PolicyDecision::new(true, [] as [&str; 0])
+        );
+        assert_eq!(
+            PolicyDecision::from_json(r#"{"allow": false, "reasons": ["debug enclave"]}"#)
+                .expect("Failed to parse decision"),
+            PolicyDecision::new(false, ["debug enclave"])
+        );
+        assert!(matches!(
+            PolicyDecision::from_json(r#"{"reasons": []}"#),
+            Err(Error::Serde(_))
+        ));
+    }
+
+    #[test]
+    fn policy_decision_to_canonical_json() {
+        let decision = PolicyDecision::new(false, ["debug enclave", "\u{1f600}"]);
+        assert_eq!(
+            decision.to_canonical_json(),
+            Ok("{\"allow\":false,\"reasons\":[\"debug enclave\",\"\u{1f600}\"]}".into())
+        );
+    }
+
+    #[test]
+    fn policy_engine_allows() {
+        let mut evidence = TestEvidence::new();
+        evidence.advisories = Advisories::default();
+        let verifier = PolicyEngineVerifier::new("enclave policy", engine);
+        let verification = verifier.verify(&evidence);
+
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [x] The policy "enclave policy" allowed the evidence"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn policy_engine_denies() {
+        let mut evidence = TestEvidence::new();
+        evidence.mr_enclave = MrEnclave::from([0xCD; 32]);
+        let verifier = PolicyEngineVerifier::new("enclave policy", engine);
+        let verification = verifier.verify(&evidence);
+
+        assert_eq!(verification.is_success().unwrap_u8(), 0);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [ ] The policy "enclave policy" denied the evidence: unknown MRENCLAVE, platform is not up to date"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn policy_engine_fails() {
+        let failing_engine =
+            |_: &PolicyInput| Err(Error::PolicyEngine(String::from("wasm module trapped")));
+        let verifier = PolicyEngineVerifier::new("enclave policy", failing_engine);
+        let verification = verifier.verify(&TestEvidence::new());
+
+        assert_eq!(verification.is_success().unwrap_u8(), 0);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [ ] The policy "enclave policy" could not be evaluated: The policy engine failed: wasm module trapped"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+}
@@ -0,0 +1,321 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Background prefetching of collateral for the FMSPCs a service has observed.
+//!
+//! The TCB info, QE identity, and CRLs of the collateral all expire. Fetching
+//! them from the PCS while verifying a quote adds a network round trip to the
+//! verification. The [`CollateralPrefetcher`] keeps the collateral of each
+//! observed FMSPC in memory and refreshes it ahead of its expiry, so in steady
+//! state the collateral is always available locally.
+
+use crate::{
+    worker::{Job, Step, Workers},
+    Collateral, Error, Fmspc,
+};
+use alloc::{
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::{fmt::Display, time::Duration};
+use der::DateTime;
+use std::{
+    collections::{btree_map, BTreeMap},
+    time::{Instant, SystemTime},
+};
+
+/// How long to wait before retrying a failed fetch. Also the minimum time
+/// between fetches of the same FMSPC, to avoid hammering the PCS with
+/// collateral that is already close to expiring.
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+
+type Fetch = dyn Fn(&Fmspc) -> Result<Collateral, String> + Send + Sync;
+
+#[derive(Debug)]
+struct Entry {
+    collateral: Option<Collateral>,
+    error: Option<String>,
+    refresh_at: Instant,
+    fetching: bool,
+}
+
+impl Entry {
+    fn new() -> Self {
+        Self {
+            collateral: None,
+            error: None,
+            refresh_at: Instant::now(),
+            fetching: false,
+        }
+    }
+}
+
+type Entries = BTreeMap<Fmspc, Entry>;
+
+/// Prefetches the collateral for observed FMSPCs on background threads.
+///
+/// FMSPCs are observed with [`CollateralPrefetcher::observe()`] or
+/// [`CollateralPrefetcher::collateral()`]. Each observed FMSPC is fetched
+/// right away and then refreshed `refresh_margin` before its collateral
+/// expires, see [`Collateral::expiration()`].
+///
+/// The worker threads are stopped when the prefetcher is dropped.
+#[derive(Debug)]
+pub struct CollateralPrefetcher {
+    workers: Workers<Entries>,
+}
+
+impl CollateralPrefetcher {
+    /// Spawn the worker threads of the prefetcher.
+    ///
+    /// # Arguments
+    /// * `fetch` - Fetches the collateral for an FMSPC, usually from the PCS
+    ///   or a PCCS. Called from the worker threads, never while holding a
+    ///   lock.
+    /// * `workers` - The number of worker threads, allowing multiple FMSPCs
+    ///   to be fetched concurrently. At least one worker is always spawned.
+    /// * `refresh_margin` - How long before the collateral expires to refresh
+    ///   it.
+    pub fn spawn<F, E>(fetch: F, workers: usize, refresh_margin: Duration) -> Self
+    where
+        F: Fn(&Fmspc) -> Result<Collateral, E> + Send + Sync + 'static,
+        E: Display,
+    {
+        let fetch: Arc<Fetch> =
+            Arc::new(move |fmspc: &Fmspc| fetch(fmspc).map_err(|e| e.to_string()));
+        let jobs = (0..workers.max(1)).map(|_| Prefetch {
+            fetch: fetch.clone(),
+            refresh_margin,
+        });
+        Self {
+            workers: Workers::spawn(Entries::new(), jobs),
+        }
+    }
+
+    /// Observe the `fmspc`, scheduling its collateral to be fetched if it has
+    /// not been observed before.
+    pub fn observe(&self, fmspc: Fmspc) {
+        let mut entries = self.workers.lock();
+        if let btree_map::Entry::Vacant(vacant) = entries.entry(fmspc) {
+            vacant.insert(Entry::new());
+            self.workers.wake();
+        }
+    }
+
+    /// The prefetched collateral for the `fmspc`.
+    ///
+    /// The `fmspc` is observed, see [`CollateralPrefetcher::observe()`], so
+    /// the first call for an FMSPC returns `None` while its collateral is
+    /// fetched in the background.
+    ///
+    /// The collateral is the most recently fetched. If refreshing failed it
+    /// may have expired, which the verification of the evidence will catch.
+    pub fn collateral(&self, fmspc: &Fmspc) -> Option<Collateral> {
+        self.observe(*fmspc);
+        let entries = self.workers.lock();
+        entries
+            .get(fmspc)
+            .and_then(|entry| entry.collateral.clone())
+    }
+
+    /// The error of the most recent fetch for the `fmspc`, if it failed.
+    pub fn error(&self, fmspc: &Fmspc) -> Option<String> {
+        let entries = self.workers.lock();
+        entries.get(fmspc).and_then(|entry| entry.error.clone())
+    }
+
+    /// The observed FMSPCs
+    pub fn fmspcs(&self) -> Vec<Fmspc> {
+        let entries = self.workers.lock();
+        entries.keys().copied().collect()
+    }
+}
+
+/// Fetches the entry which is due for a refresh the soonest
+struct Prefetch {
+    fetch: Arc<Fetch>,
+    refresh_margin: Duration,
+}
+
+impl Job for Prefetch {
+    type State = Entries;
+    type Work = Fmspc;
+    type Outcome = (Fmspc, Result<(Collateral, DateTime), String>);
+
+    fn next(&mut self, entries: &mut Entries) -> Step<Fmspc> {
+        let next = entries
+            .iter_mut()
+            .filter(|(_, entry)| !entry.fetching)
+            .min_by_key(|(_, entry)| entry.refresh_at);
+        let now = Instant::now();
+        match next {
+            Some((fmspc, entry)) if entry.refresh_at <= now => {
+                entry.fetching = true;
+                Step::Run(*fmspc)
+            }
+            Some((_, entry)) => Step::WaitFor(entry.refresh_at - now),
+            None => Step::Wait,
+        }
+    }
+
+    fn run(&mut self, fmspc: Fmspc) -> Self::Outcome {
+        let result = (self.fetch)(&fmspc).and_then(|collateral| {
+            let expiry = expiry(&collateral).map_err(|e| e.to_string())?;
+            Ok((collateral, expiry))
+        });
+        (fmspc, result)
+    }
+
+    fn finish(&mut self, entries: &mut Entries, (fmspc, result): Self::Outcome) {
+        let Some(entry) = entries.get_mut(&fmspc) else {
+            return;
+        };
+        entry.fetching = false;
+        let now = Instant::now();
+        match result {
+            Ok((collateral, expiry)) => {
+                entry.refresh_at = now + refresh_delay(expiry, self.refresh_margin);
+                entry.collateral = Some(collateral);
+                entry.error = None;
+            }
+            Err(error) => {
+                entry.refresh_at = now + RETRY_DELAY;
+                entry.error = Some(error);
+            }
+        }
+    }
+}
+
+/// How long from now to refresh collateral which expires at `expiry`
+fn refresh_delay(expiry: DateTime, refresh_margin: Duration) -> Duration {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    expiry
+        .unix_duration()
+        .saturating_sub(now)
+        .saturating_sub(refresh_margin)
+        .max(RETRY_DELAY)
+}
+
+/// The time the `collateral` expires, the earliest expiration of its parts.
+///
+/// # Errors
+/// `Error::Der` if a `nextUpdate` is not a valid time.
+fn expiry(collateral: &Collateral) -> Result<DateTime, Error> {
+    let (expiry, _) = collateral.expiration()?;
+    Ok(expiry)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::evidence::{
+        self,
+        test::{QE_IDENTITY_JSON, TCB_INFO_JSON},
+    };
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    const FMSPC: Fmspc = Fmspc::new([0x00, 0x90, 0x6E, 0xD5, 0x00, 0x00]);
+
+    fn collateral() -> Collateral {
+        evidence::test::collateral(TCB_INFO_JSON, QE_IDENTITY_JSON)
+    }
+
+    // Wait for the background fetch to finish
+    fn wait_for<T>(mut poll: impl FnMut() -> Option<T>) -> T {
+        let start = Instant::now();
+        loop {
+            if let Some(value) = poll() {
+                return value;
+            }
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "Timed out waiting for prefetch"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn expiry_is_earliest_next_update() {
+        // The processor CRL expires before the rest of the collateral
+        let expected = "2023-05-21T22:00:36Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+        assert_eq!(expiry(&collateral()), Ok(expected));
+    }
+
+    #[test]
+    fn refresh_delay_is_at_least_the_retry_delay() {
+        let expired = "2023-08-11T19:56:44Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+        assert_eq!(refresh_delay(expired, Duration::ZERO), RETRY_DELAY);
+    }
+
+    #[test]
+    fn refresh_delay_before_expiry() {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Time before epoch");
+        let expiry = DateTime::from_unix_duration(now + Duration::from_secs(3600))
+            .expect("Failed to create time");
+        let delay = refresh_delay(expiry, Duration::from_secs(600));
+        assert!(delay <= Duration::from_secs(3000));
+        assert!(delay > Duration::from_secs(2900));
+    }
+
+    #[test]
+    fn observed_fmspc_is_prefetched() {
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let counter = fetches.clone();
+        let prefetcher = CollateralPrefetcher::spawn(
+            move |fmspc: &Fmspc| {
+                assert_eq!(fmspc, &FMSPC);
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, String>(collateral())
+            },
+            2,
+            Duration::from_secs(3600),
+        );
+
+        prefetcher.observe(FMSPC);
+        prefetcher.observe(FMSPC);
+        let prefetched = wait_for(|| prefetcher.collateral(&FMSPC));
+
+        assert_eq!(prefetched.tcb_info().fmspc(), FMSPC);
+        assert_eq!(prefetcher.fmspcs(), [FMSPC]);
+        assert_eq!(prefetcher.error(&FMSPC), None);
+        // The test collateral has expired so it won't be refreshed before the
+        // retry delay
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn failed_fetch_is_recorded() {
+        let prefetcher = CollateralPrefetcher::spawn(
+            |_: &Fmspc| Err("PCS unavailable"),
+            1,
+            Duration::from_secs(3600),
+        );
+
+        assert!(prefetcher.collateral(&FMSPC).is_none());
+        let error = wait_for(|| prefetcher.error(&FMSPC));
+
+        assert_eq!(error, "PCS unavailable");
+        assert!(prefetcher.collateral(&FMSPC).is_none());
+    }
+
+    #[test]
+    fn drop_stops_workers() {
+        let prefetcher = CollateralPrefetcher::spawn(
+            |_: &Fmspc| Err("PCS unavailable"),
+            4,
+            Duration::from_secs(3600),
+        );
+        prefetcher.observe(FMSPC);
+        drop(prefetcher);
+    }
+}
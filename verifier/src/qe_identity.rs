@@ -38,8 +38,11 @@
 #![allow(dead_code)]
 
 use crate::{
-    advisories::AdvisoryStatus, Accessor, Advisories, Error, VerificationMessage,
-    VerificationOutput, Verifier,
+    advisories::AdvisoryStatus,
+    evidence::key_from_certificate,
+    report_body::{attributes_from_bytes, miscellaneous_select_from_bytes},
+    Accessor, Advisories, CertificateChainVerifier, Error, VerificationMessage,
+    VerificationOptions, VerificationOutput, Verifier,
 };
 use alloc::{
     boxed::Box,
@@ -48,13 +51,13 @@ use alloc::{
 };
 use core::fmt::Formatter;
 use der::DateTime;
-use mc_sgx_core_sys_types::sgx_attributes_t;
 use mc_sgx_core_types::{Attributes, IsvProductId, IsvSvn, MiscellaneousSelect, MrSigner};
 use p256::ecdsa::{signature::Verifier as SignatureVerifier, Signature, VerifyingKey};
 use serde::Deserialize;
 use serde_json::value::RawValue;
+use x509_cert::{crl::CertificateList, Certificate};
 
-const QE_IDENTITY_VERSION: u32 = 2;
+pub(crate) const QE_IDENTITY_VERSION: u32 = 2;
 const UNIX_TIME_STR: &str = "1970-01-01T00:00:00Z";
 
 /// QE(quoting enclave) identity information.
@@ -83,10 +86,53 @@ pub struct QeIdentity {
     mr_signer: [u8; 32],
     #[serde(rename = "isvprodid")]
     isv_prod_id: u16,
+    tcb_evaluation_data_number: u32,
     tcb_levels: Vec<TcbLevel>,
 }
 
 impl QeIdentity {
+    /// The enclave the identity is for, "QE", "QVE", or "TD_QE".
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The version of the identity structure.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The time the QE identity was issued, its `issueDate`.
+    ///
+    /// # Errors
+    /// `Error::Der` if the `issueDate` is not a valid time.
+    pub fn issue_date(&self) -> Result<DateTime, Error> {
+        Ok(self.issue_date.parse::<DateTime>()?)
+    }
+
+    /// The number of the TCB evaluation this QE identity was produced by,
+    /// incremented by Intel with each TCB recovery
+    pub fn tcb_evaluation_data_number(&self) -> u32 {
+        self.tcb_evaluation_data_number
+    }
+
+    /// The advisories of the TCB level the QE's `isv_svn` is at.
+    ///
+    /// This is step 4 of
+    /// <https://api.portal.trustedservices.intel.com/documentation#pcs-enclave-identity-v4>,
+    /// the highest TCB level whose ISV SVN is lower or equal to `isv_svn`.
+    ///
+    /// # Errors
+    /// `Error::UnsupportedTcbLevel` if `isv_svn` is below all of the TCB
+    /// levels.
+    pub fn advisories(&self, isv_svn: &IsvSvn) -> Result<Advisories, Error> {
+        self.tcb_levels
+            .iter()
+            .filter(|level| level.isv_svn().as_ref() <= isv_svn.as_ref())
+            .max_by_key(|level| *level.isv_svn().as_ref())
+            .map(TcbLevel::advisories)
+            .ok_or(Error::UnsupportedTcbLevel)
+    }
+
     /// The MRSIGNER key value of the QE.
     pub fn mr_signer(&self) -> MrSigner {
         MrSigner::from(self.mr_signer)
@@ -104,56 +150,69 @@ impl QeIdentity {
 
     /// The expected [`MiscellaneousSelect`] bits of the QE report body.
     pub fn miscellaneous_select(&self) -> MiscellaneousSelect {
-        let miscellaneous_select = u32::from_le_bytes(self.misc_select);
-        miscellaneous_select.into()
+        miscellaneous_select_from_bytes(self.misc_select)
     }
 
     /// The [`MiscellaneousSelect`] mask to use when comparing the
     /// [`MiscellaneousSelect`] bits of the QE report body.
     pub fn miscellaneous_select_mask(&self) -> MiscellaneousSelect {
-        let mask = u32::from_le_bytes(self.misc_select_mask);
-        mask.into()
+        miscellaneous_select_from_bytes(self.misc_select_mask)
     }
 
     /// The expected [`Attributes`] of the QE report body.
     pub fn attributes(&self) -> Attributes {
-        let mut bytes = [0u8; 8];
-        bytes.copy_from_slice(&self.attributes[..8]);
-        let flags = u64::from_le_bytes(bytes);
-        bytes.copy_from_slice(&self.attributes[8..]);
-        let xfrm = u64::from_le_bytes(bytes);
-        sgx_attributes_t { flags, xfrm }.into()
+        attributes_from_bytes(&self.attributes)
     }
 
     /// The [`Attributes`] mask to use when comparing the
     /// [`Attributes`] of the QE report body.
     pub fn attributes_mask(&self) -> Attributes {
-        let mut bytes = [0u8; 8];
-        bytes.copy_from_slice(&self.attributes_mask[..8]);
-        let flags = u64::from_le_bytes(bytes);
-        bytes.copy_from_slice(&self.attributes_mask[8..]);
-        let xfrm = u64::from_le_bytes(bytes);
-        sgx_attributes_t { flags, xfrm }.into()
+        attributes_from_bytes(&self.attributes_mask)
     }
 
-    fn verify(&self, time: Option<DateTime>) -> Result<(), Error> {
-        self.verify_version()?.verify_time(time)?;
+    /// The time the QE identity expires, its `nextUpdate`.
+    ///
+    /// # Errors
+    /// `Error::Der` if the `nextUpdate` is not a valid time.
+    pub fn next_update(&self) -> Result<DateTime, Error> {
+        Ok(self.next_update.parse::<DateTime>()?)
+    }
+
+    fn verify(&self, time: Option<DateTime>, options: &VerificationOptions) -> Result<(), Error> {
+        self.verify_version()?
+            .verify_time(time, options)?
+            .verify_evaluation_data_number(options)?;
         Ok(())
     }
 
-    fn verify_time(&self, time: Option<DateTime>) -> Result<&Self, Error> {
+    fn verify_time(
+        &self,
+        time: Option<DateTime>,
+        options: &VerificationOptions,
+    ) -> Result<&Self, Error> {
         let issue_date = self.issue_date.parse::<DateTime>()?;
         let next_update = self.next_update.parse::<DateTime>()?;
         if let Some(date_time) = time {
-            if date_time < issue_date {
+            if options.is_not_yet_valid(date_time, issue_date) {
                 return Err(Error::QeIdentityNotYetValid);
-            } else if date_time >= next_update {
+            } else if options.is_expired(date_time, next_update) {
                 return Err(Error::QeIdentityExpired);
             }
         }
         Ok(self)
     }
 
+    fn verify_evaluation_data_number(&self, options: &VerificationOptions) -> Result<&Self, Error> {
+        if self.tcb_evaluation_data_number < options.min_tcb_evaluation_data_number {
+            Err(Error::QeIdentityEvaluationDataNumber {
+                minimum: options.min_tcb_evaluation_data_number,
+                actual: self.tcb_evaluation_data_number,
+            })
+        } else {
+            Ok(self)
+        }
+    }
+
     fn verify_version(&self) -> Result<&Self, Error> {
         if self.version != QE_IDENTITY_VERSION {
             Err(Error::QeIdentityVersion {
@@ -207,6 +266,24 @@ impl TcbLevel {
     pub fn advisories(&self) -> Advisories {
         Advisories::new(&self.advisory_ids, self.tcb_status)
     }
+
+    /// The date of the TCB recovery this level is for, its `tcbDate`.
+    ///
+    /// # Errors
+    /// `Error::Der` if the `tcbDate` is not a valid time.
+    pub fn tcb_date(&self) -> Result<DateTime, Error> {
+        Ok(self.tcb_date.parse::<DateTime>()?)
+    }
+
+    /// The status of a QE at this level
+    pub fn tcb_status(&self) -> AdvisoryStatus {
+        self.tcb_status
+    }
+
+    /// The IDs of the Intel security advisories which apply at this level
+    pub fn advisory_ids(&self) -> &[String] {
+        &self.advisory_ids
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -253,13 +330,64 @@ impl SignedQeIdentity {
         self,
         key: Option<&VerifyingKey>,
         time: impl Into<Option<DateTime>>,
+    ) -> Result<(), Error> {
+        self.verify_with_options(key, time, &VerificationOptions::default())
+    }
+
+    /// Verify the `enclaveIdentity` signature and time are valid, with the
+    /// tolerances of `options`.
+    ///
+    /// See [`SignedQeIdentity::verify()`] for the other arguments.
+    pub fn verify_with_options(
+        self,
+        key: Option<&VerifyingKey>,
+        time: impl Into<Option<DateTime>>,
+        options: &VerificationOptions,
     ) -> Result<(), Error> {
         self.verify_signature(key)?;
         let qe_identity = QeIdentity::try_from(&self)?;
-        qe_identity.verify(time.into())?;
+        qe_identity.verify(time.into(), options)?;
         Ok(())
     }
 
+    /// Verify the `enclaveIdentity` was signed by the leaf of the signing
+    /// `certificate_chain`, and that its time is valid.
+    ///
+    /// # Arguments
+    /// - `certificate_verifier` - The verifier for the `certificate_chain`.
+    /// - `certificate_chain` - The signing chain, leaf first. This is the
+    ///   `SGX-Enclave-Identity-Issuer-Chain` header of the QE identity
+    ///   response.
+    /// - `crls` - The CRLs to verify the `certificate_chain` against.
+    /// - `time` - The current system time, see [`SignedQeIdentity::verify()`].
+    ///
+    /// # Returns
+    /// The verified QE identity, which the QE report of a quote can be
+    /// checked against with a [`QeReportBodyVerifier`](crate::QeReportBodyVerifier).
+    ///
+    /// # Errors
+    /// - `Error::SigningCertificateChain` if the `certificate_chain` fails
+    ///   verification.
+    /// - `Error::MissingPublicKey` if the leaf P-256 key can not be
+    ///   retrieved.
+    /// - The errors of [`SignedQeIdentity::verify()`].
+    pub fn verify_with_certificate_chain<C: CertificateChainVerifier>(
+        self,
+        certificate_verifier: &C,
+        certificate_chain: &[Certificate],
+        crls: &[CertificateList],
+        time: impl Into<Option<DateTime>>,
+    ) -> Result<QeIdentity, Error> {
+        let time = time.into();
+        certificate_verifier
+            .verify_certificate_chain(certificate_chain, crls, time)
+            .map_err(|e| Error::SigningCertificateChain(e.to_string()))?;
+        let key = certificate_chain.first().and_then(key_from_certificate);
+        let qe_identity = QeIdentity::try_from(&self)?;
+        self.verify(key.as_ref(), time)?;
+        Ok(qe_identity)
+    }
+
     fn verify_signature(&self, key: Option<&VerifyingKey>) -> Result<(), Error> {
         let key = key.ok_or(Error::MissingPublicKey)?;
         let qe_identity = self.enclave_identity.get();
@@ -286,6 +414,7 @@ impl TryFrom<&str> for SignedQeIdentity {
 pub struct SignedQeIdentityVerifier {
     key: Option<VerifyingKey>,
     time: Option<DateTime>,
+    options: VerificationOptions,
 }
 
 impl SignedQeIdentityVerifier {
@@ -312,15 +441,23 @@ impl SignedQeIdentityVerifier {
         Self {
             key,
             time: time.into(),
+            options: VerificationOptions::default(),
         }
     }
+
+    /// Verify the time with the tolerances of `options`
+    pub fn with_options(mut self, options: VerificationOptions) -> Self {
+        self.options = options;
+        self
+    }
 }
 
 impl<E: Accessor<SignedQeIdentity>> Verifier<E> for SignedQeIdentityVerifier {
     type Value = Option<Error>;
     fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
         let signed_qe_identity = evidence.get();
-        let result = signed_qe_identity.verify(self.key.as_ref(), self.time);
+        let result =
+            signed_qe_identity.verify_with_options(self.key.as_ref(), self.time, &self.options);
         let is_success = result.is_ok() as u8;
 
         VerificationOutput::new(result.err(), is_success.into())
@@ -356,7 +493,7 @@ impl VerificationMessage<Option<Error>> for SignedQeIdentityVerifier {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::VerificationTreeDisplay;
+    use crate::{CertificateChainVerifierError, VerificationTreeDisplay};
     use alloc::{format, vec};
     use assert_matches::assert_matches;
     use der::DateTime;
@@ -422,6 +559,21 @@ mod test {
         );
     }
 
+    #[test]
+    fn qe_identity_next_update() {
+        let json = include_str!("../data/tests/qe_identity.json");
+        let signed_qe_identity =
+            SignedQeIdentity::try_from(json).expect("Failed to parse signed identity");
+        let qe_identity =
+            QeIdentity::try_from(&signed_qe_identity).expect("Failed to parse identity");
+        assert_eq!(
+            qe_identity.next_update(),
+            Ok("2023-08-11T20:48:25Z"
+                .parse::<DateTime>()
+                .expect("Failed to parse time"))
+        );
+    }
+
     #[test]
     fn signed_id_two_signatures_errors() {
         let raw_identity = r#"{"enclaveIdentity":{"id":"QE","version":2,"miscselect":"00000000"},"signature":"abcd","signature":"should not be here"}"#;
@@ -452,6 +604,138 @@ mod test {
         );
     }
 
+    #[test]
+    fn typed_qe_identity_fields() {
+        let json = include_str!("../data/tests/qe_identity.json");
+        let signed_qe_identity =
+            SignedQeIdentity::try_from(json).expect("Failed to parse signed identity");
+        let qe_identity =
+            QeIdentity::try_from(&signed_qe_identity).expect("Failed to parse identity");
+        assert_eq!(qe_identity.id(), "QE");
+        assert_eq!(qe_identity.version(), 2);
+        assert_eq!(
+            qe_identity.issue_date(),
+            Ok("2023-07-12T20:48:25Z"
+                .parse::<DateTime>()
+                .expect("Failed to parse time"))
+        );
+        let second_level = &qe_identity.tcb_levels()[1];
+        assert_eq!(second_level.tcb_status(), AdvisoryStatus::OutOfDate);
+        assert_eq!(second_level.advisory_ids(), &["INTEL-SA-00615"]);
+        assert_eq!(
+            second_level.tcb_date(),
+            Ok("2021-11-10T00:00:00Z"
+                .parse::<DateTime>()
+                .expect("Failed to parse time"))
+        );
+    }
+
+    #[parameterized(
+        above_newest = { 9, &[], AdvisoryStatus::UpToDate },
+        at_newest = { 8, &[], AdvisoryStatus::UpToDate },
+        between_levels = { 7, &["INTEL-SA-00615"], AdvisoryStatus::OutOfDate },
+        at_oldest = { 1, &["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"], AdvisoryStatus::OutOfDate },
+    )]
+    fn qe_identity_advisories(isv_svn: u16, ids: &[&str], status: AdvisoryStatus) {
+        let json = include_str!("../data/tests/qe_identity.json");
+        let signed_qe_identity =
+            SignedQeIdentity::try_from(json).expect("Failed to parse signed identity");
+        let qe_identity =
+            QeIdentity::try_from(&signed_qe_identity).expect("Failed to parse identity");
+        assert_eq!(
+            qe_identity.advisories(&IsvSvn::from(isv_svn)),
+            Ok(Advisories::new(ids, status))
+        );
+    }
+
+    #[test]
+    fn qe_identity_advisories_below_oldest_level() {
+        let json = include_str!("../data/tests/qe_identity.json");
+        let signed_qe_identity =
+            SignedQeIdentity::try_from(json).expect("Failed to parse signed identity");
+        let qe_identity =
+            QeIdentity::try_from(&signed_qe_identity).expect("Failed to parse identity");
+        assert_eq!(
+            qe_identity.advisories(&IsvSvn::from(0)),
+            Err(Error::UnsupportedTcbLevel)
+        );
+    }
+
+    struct TestChainVerifier(Result<(), CertificateChainVerifierError>);
+
+    impl CertificateChainVerifier for TestChainVerifier {
+        fn verify_certificate_chain<'a, 'b>(
+            &self,
+            _certificate_chain: impl IntoIterator<Item = &'a Certificate>,
+            _crls: impl IntoIterator<Item = &'b CertificateList>,
+            _time: impl Into<Option<DateTime>>,
+        ) -> Result<(), CertificateChainVerifierError> {
+            self.0.clone()
+        }
+    }
+
+    fn qe_identity_signing_chain() -> Vec<Certificate> {
+        [
+            include_str!("../data/tests/tcb_signer.pem"),
+            include_str!("../data/tests/root_ca.pem"),
+        ]
+        .iter()
+        .map(|pem| Certificate::from_pem(pem).expect("failed to parse PEM"))
+        .collect()
+    }
+
+    #[test]
+    fn verify_with_certificate_chain() {
+        let json = include_str!("../data/tests/qe_identity.json");
+        let signed_qe_identity =
+            SignedQeIdentity::try_from(json).expect("Failed to parse signed identity");
+        let time = "2023-07-12T20:48:25Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+
+        let qe_identity = signed_qe_identity
+            .verify_with_certificate_chain(
+                &TestChainVerifier(Ok(())),
+                &qe_identity_signing_chain(),
+                &[],
+                time,
+            )
+            .expect("Failed to verify QE identity");
+        assert_eq!(qe_identity.isv_prod_id(), IsvProductId::from(1));
+    }
+
+    #[test]
+    fn verify_with_certificate_chain_fails_for_bad_chain() {
+        let json = include_str!("../data/tests/qe_identity.json");
+        let signed_qe_identity =
+            SignedQeIdentity::try_from(json).expect("Failed to parse signed identity");
+
+        let result = signed_qe_identity.verify_with_certificate_chain(
+            &TestChainVerifier(Err(CertificateChainVerifierError::CertificateExpired)),
+            &qe_identity_signing_chain(),
+            &[],
+            None,
+        );
+        assert_matches!(result, Err(Error::SigningCertificateChain(_)));
+    }
+
+    #[test]
+    fn verify_with_certificate_chain_fails_for_wrong_signer() {
+        let json = include_str!("../data/tests/qe_identity.json");
+        let signed_qe_identity =
+            SignedQeIdentity::try_from(json).expect("Failed to parse signed identity");
+        let mut chain = qe_identity_signing_chain();
+        chain.reverse();
+
+        let result = signed_qe_identity.verify_with_certificate_chain(
+            &TestChainVerifier(Ok(())),
+            &chain,
+            &[],
+            None,
+        );
+        assert_eq!(result.unwrap_err(), Error::SignatureVerification);
+    }
+
     #[test]
     fn parse_example_qe_identity() {
         let json = include_str!("../data/tests/example_qe_identity.json");
@@ -481,6 +765,7 @@ mod test {
             ]
         );
         assert_eq!(qe_identity.isv_prod_id, 1);
+        assert_eq!(qe_identity.tcb_evaluation_data_number(), 12);
         let first_level = &qe_identity.tcb_levels[0];
         assert_eq!(first_level.tcb.isv_svn, 6);
         assert_eq!(first_level.tcb_date, "2021-11-10T00:00:00Z");
@@ -527,6 +812,7 @@ mod test {
             ]
         );
         assert_eq!(qe_identity.isv_prod_id, 1);
+        assert_eq!(qe_identity.tcb_evaluation_data_number(), 15);
         let first_level = &qe_identity.tcb_levels[0];
         assert_eq!(first_level.tcb.isv_svn, 8);
         assert_eq!(first_level.tcb_date, "2023-02-15T00:00:00Z");
@@ -601,6 +887,56 @@ mod test {
         assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
     }
 
+    #[test]
+    fn qe_identity_verifier_tolerates_clock_skew() {
+        let json = include_str!("../data/tests/qe_identity.json");
+        let signed_qe_identity =
+            SignedQeIdentity::try_from(json).expect("Failed to parse signed identity");
+        let key = qe_verifying_key();
+        let time = "2023-08-11T20:48:25Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+        let options = VerificationOptions {
+            max_clock_skew: core::time::Duration::from_secs(1),
+            ..Default::default()
+        };
+        let verifier = SignedQeIdentityVerifier::new(Some(key), time).with_options(options);
+
+        let verification = verifier.verify(&signed_qe_identity);
+
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+    }
+
+    #[parameterized(
+        no_minimum = { 0, true },
+        at_minimum = { 15, true },
+        below_minimum = { 16, false },
+    )]
+    fn qe_identity_verifier_minimum_evaluation_data_number(minimum: u32, expected: bool) {
+        let json = include_str!("../data/tests/qe_identity.json");
+        let signed_qe_identity =
+            SignedQeIdentity::try_from(json).expect("Failed to parse signed identity");
+        let key = qe_verifying_key();
+        let options = VerificationOptions {
+            min_tcb_evaluation_data_number: minimum,
+            ..Default::default()
+        };
+        let verifier = SignedQeIdentityVerifier::new(Some(key), None).with_options(options);
+
+        let verification = verifier.verify(&signed_qe_identity);
+
+        assert_eq!(verification.is_success().unwrap_u8() == 1, expected);
+        if !expected {
+            assert_eq!(
+                verification.value,
+                Some(Error::QeIdentityEvaluationDataNumber {
+                    minimum,
+                    actual: 15
+                })
+            );
+        }
+    }
+
     #[test]
     fn qe_identity_verifier_fails_before_issue_date() {
         let json = include_str!("../data/tests/qe_identity.json");
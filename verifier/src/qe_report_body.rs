@@ -7,8 +7,8 @@ use crate::{
     qe_identity::{QeIdentity, TcbLevel},
     report_body::MrSignerKeyVerifier,
     struct_name::SpacedStructName,
-    Accessor, Advisories, AdvisoriesVerifier, AdvisoryStatus, AttributesVerifier,
-    IsvProductIdVerifier, MiscellaneousSelectVerifier, VerificationMessage, VerificationOutput,
+    Accessor, AdvisoryStatus, AttributesVerifier, IsvProductIdVerifier,
+    MiscellaneousSelectVerifier, Quote4SignatureData, VerificationMessage, VerificationOutput,
     Verifier, MESSAGE_INDENT,
 };
 use alloc::vec::Vec;
@@ -41,6 +41,13 @@ impl<T: AsRef<[u8]>> From<&Quote3<T>> for QeReportBody {
     }
 }
 
+/// The QE report body of a version 4 or 5 quote
+impl From<&Quote4SignatureData<'_>> for QeReportBody {
+    fn from(signature_data: &Quote4SignatureData<'_>) -> Self {
+        Self::new(signature_data.qe_report_body())
+    }
+}
+
 /// Verifier for ensuring a QE(quoting enclave) matches the provided identity
 ///
 ///
@@ -189,6 +196,10 @@ impl VerificationMessage<QeReportBodyValue> for QeReportBodyVerifier {
 /// >          from SGX Enclave Report.
 /// >       c. If a TCB level is found, read its status from tcbStatus field,
 /// >          otherwise your TCB Level is not supported.
+///
+/// Only a `Revoked` level fails the verification. A QE which is otherwise
+/// stale downgrades the TCB status of the platform instead, see
+/// [`Advisories::converge_with_qe()`].
 #[derive(Debug, Clone)]
 struct QeIsvSvnVerifier {
     tcb_levels: Vec<TcbLevel>,
@@ -224,16 +235,12 @@ impl<E: Accessor<IsvSvn>> Verifier<E> for QeIsvSvnVerifier {
             }
         });
 
-        let mut is_success = 0.into();
-        if let Some(tcb_level) = &tcb_level {
-            let up_to_date_with_no_advisories =
-                Advisories::new([] as [&str; 0], AdvisoryStatus::UpToDate);
-            let advisories_verifier = AdvisoriesVerifier::new(up_to_date_with_no_advisories);
-            let result = advisories_verifier.verify(&tcb_level.advisories());
-            is_success = result.is_success();
-        }
+        let is_success = matches!(
+            &tcb_level,
+            Some(level) if level.tcb_status() != AdvisoryStatus::Revoked
+        ) as u8;
 
-        VerificationOutput::new((isv_svn, tcb_level), is_success)
+        VerificationOutput::new((isv_svn, tcb_level), is_success.into())
     }
 }
 
@@ -241,7 +248,7 @@ impl Display for QeIsvSvnVerifier {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "The {} should correspond to an `UpToDate` level with no advisories, from: {:?}",
+            "The {} should correspond to a level which is not `Revoked`, from: {:?}",
             IsvSvn::spaced_struct_name(),
             self.tcb_levels
         )
@@ -277,7 +284,8 @@ mod test {
     use super::*;
     use crate::{
         qe_identity::{SignedQeIdentity, Tcb},
-        VerificationTreeDisplay,
+        quote4::test::tdx_quote,
+        Quote4, VerificationTreeDisplay,
     };
     use alloc::{format, string::ToString};
     use mc_sgx_core_sys_types::sgx_report_body_t;
@@ -293,7 +301,7 @@ mod test {
         let displayable = VerificationTreeDisplay::new(&verifier, verification);
         assert_eq!(
             displayable.to_string(),
-            r#"- [ ] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [], but the ISV SVN of 1 corresponds to None"#
+            r#"- [ ] The ISV SVN should correspond to a level which is not `Revoked`, from: [], but the ISV SVN of 1 corresponds to None"#
         );
     }
 
@@ -312,7 +320,7 @@ mod test {
         let displayable = VerificationTreeDisplay::new(&verifier, verification);
         assert_eq!(
             displayable.to_string(),
-            r#"- [x] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "1970-01-01T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }]"#
+            r#"- [x] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "1970-01-01T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }]"#
         );
     }
 
@@ -331,36 +339,30 @@ mod test {
         let displayable = VerificationTreeDisplay::new(&verifier, verification);
         assert_eq!(
             displayable.to_string(),
-            r#"- [ ] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "1970-01-01T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }], but the ISV SVN of 1 corresponds to None"#
+            r#"- [ ] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "1970-01-01T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }], but the ISV SVN of 1 corresponds to None"#
         );
     }
 
     #[test]
-    fn tcb_level_not_up_to_date_fails() {
+    fn tcb_level_out_of_date_succeeds() {
         let isv_svn = IsvSvn::from(1);
         let tcb_levels = [TcbLevel::new(
             Tcb::new(1),
-            AdvisoryStatus::SWHardeningNeeded,
-            [] as [&str; 0],
+            AdvisoryStatus::OutOfDate,
+            ["an id"],
         )];
         let verifier = QeIsvSvnVerifier::new(&tcb_levels);
         let verification = verifier.verify(&isv_svn);
-        assert_eq!(verification.is_failure().unwrap_u8(), 1);
-
-        let displayable = VerificationTreeDisplay::new(&verifier, verification);
-        assert_eq!(
-            displayable.to_string(),
-            r#"- [ ] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "1970-01-01T00:00:00Z", tcb_status: SWHardeningNeeded, advisory_ids: [] }], but the ISV SVN of 1 corresponds to Some(TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "1970-01-01T00:00:00Z", tcb_status: SWHardeningNeeded, advisory_ids: [] })"#
-        );
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
     }
 
     #[test]
-    fn tcb_level_with_advisories_fails() {
+    fn tcb_level_revoked_fails() {
         let isv_svn = IsvSvn::from(1);
         let tcb_levels = [TcbLevel::new(
             Tcb::new(1),
-            AdvisoryStatus::UpToDate,
-            ["an id"],
+            AdvisoryStatus::Revoked,
+            [] as [&str; 0],
         )];
         let verifier = QeIsvSvnVerifier::new(&tcb_levels);
         let verification = verifier.verify(&isv_svn);
@@ -369,7 +371,7 @@ mod test {
         let displayable = VerificationTreeDisplay::new(&verifier, verification);
         assert_eq!(
             displayable.to_string(),
-            r#"- [ ] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "1970-01-01T00:00:00Z", tcb_status: UpToDate, advisory_ids: ["an id"] }], but the ISV SVN of 1 corresponds to Some(TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "1970-01-01T00:00:00Z", tcb_status: UpToDate, advisory_ids: ["an id"] })"#
+            r#"- [ ] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "1970-01-01T00:00:00Z", tcb_status: Revoked, advisory_ids: [] }], but the ISV SVN of 1 corresponds to Some(TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "1970-01-01T00:00:00Z", tcb_status: Revoked, advisory_ids: [] })"#
         );
     }
 
@@ -379,11 +381,7 @@ mod test {
         let mut tcb_levels = [6, 5, 2, 1]
             .iter()
             .map(|isv_svn| {
-                TcbLevel::new(
-                    Tcb::new(*isv_svn),
-                    AdvisoryStatus::SWHardeningNeeded,
-                    [] as [&str; 0],
-                )
+                TcbLevel::new(Tcb::new(*isv_svn), AdvisoryStatus::Revoked, [] as [&str; 0])
             })
             .collect::<Vec<_>>();
         tcb_levels.insert(
@@ -424,6 +422,14 @@ mod test {
         );
     }
 
+    #[test]
+    fn qe_report_body_from_quote4_signature_data() {
+        let quote = Quote4::try_from(tdx_quote()).expect("Failed to parse quote");
+        let signature_data = quote.signature_data();
+        let qe_report_body = QeReportBody::from(&signature_data);
+        assert_eq!(qe_report_body.0, signature_data.qe_report_body());
+    }
+
     #[test]
     fn qe_report_body_succeeds() {
         let quote_bytes = include_bytes!("../data/tests/hw_quote.dat");
@@ -446,7 +452,7 @@ mod test {
               - [x] The ISV product ID should be 1
               - [x] The expected miscellaneous select is 0x0000_0000 with mask 0xFFFF_FFFF
               - [x] The expected attributes is Flags: INITTED | PROVISION_KEY Xfrm: (none) with mask Flags: 0xFFFF_FFFF_FFFF_FFFB Xfrm: (none)
-              - [x] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]"#;
+              - [x] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]"#;
         assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
     }
 
@@ -475,7 +481,7 @@ mod test {
               - [x] The ISV product ID should be 1
               - [x] The expected miscellaneous select is 0x0000_0000 with mask 0xFFFF_FFFF
               - [x] The expected attributes is Flags: INITTED | PROVISION_KEY Xfrm: (none) with mask Flags: 0xFFFF_FFFF_FFFF_FFFB Xfrm: (none)
-              - [x] The ISV SVN should correspond to an `UpToDate` level with no advisories, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]"#;
+              - [x] The ISV SVN should correspond to a level which is not `Revoked`, from: [TcbLevel { tcb: Tcb { isv_svn: 8 }, tcb_date: "2023-02-15T00:00:00Z", tcb_status: UpToDate, advisory_ids: [] }, TcbLevel { tcb: Tcb { isv_svn: 6 }, tcb_date: "2021-11-10T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 5 }, tcb_date: "2020-11-11T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 4 }, tcb_date: "2019-11-13T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 2 }, tcb_date: "2019-05-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }, TcbLevel { tcb: Tcb { isv_svn: 1 }, tcb_date: "2018-08-15T00:00:00Z", tcb_status: OutOfDate, advisory_ids: ["INTEL-SA-00202", "INTEL-SA-00219", "INTEL-SA-00293", "INTEL-SA-00334", "INTEL-SA-00477", "INTEL-SA-00615"] }]"#;
         assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
     }
 
@@ -510,8 +516,10 @@ mod test {
         let verifier = QeReportBodyVerifier::new(identity);
 
         let mut sgx_report_body: sgx_report_body_t = QeReportBody::from(&quote).0.into();
-        // The isv svn in the identity is 8, can be seen by looking at the json file
-        sgx_report_body.isv_svn = 7;
+        // The lowest isv svn in the identity is 1, can be seen by looking at the json
+        // file. A stale QE, with an isv svn between the levels, only downgrades the
+        // TCB status.
+        sgx_report_body.isv_svn = 0;
         let qe_report_body = QeReportBody::new(ReportBody::from(sgx_report_body));
 
         let verification = verifier.verify(&qe_report_body);
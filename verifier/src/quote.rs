@@ -1,8 +1,22 @@
 // Copyright (c) 2023-2024 The MobileCoin Foundation
 
-use crate::{Accessor, Error, VerificationMessage, VerificationOutput, Verifier};
+//! DCAP quotes.
+//!
+//! The version 3 ECDSA quote format is parsed by [`Quote3`], from
+//! `mc-sgx-dcap-types`, which bounds checks the header, the ISV report body,
+//! and the variable length signature data, QE report and certification data.
+//! It's re-exported so the quotes can be consumed without depending on
+//! `mc-sgx-dcap-types` directly.
+
+use crate::{
+    quote4::Reader, Accessor, Error, QuoteField, QuoteHeader, VerificationMessage,
+    VerificationOutput, Verifier,
+};
+use alloc::vec::Vec;
 use core::fmt::Formatter;
-use mc_sgx_dcap_types::Quote3;
+pub use mc_sgx_dcap_types::{
+    AuthenticationData, CertificationData, PckCertificateChain, Quote3, Quote3Error, SignatureData,
+};
 use p256::ecdsa::VerifyingKey;
 
 /// Verifier for ensuring a quote was signed with the provided key
@@ -16,7 +30,7 @@ impl<T> Quote3Verifier<T> {
     /// Create a new instance.
     ///
     /// The `key` should be retrieved from the leaf certificate of the quote's
-    /// [`mc-sgx-dcap-types::CertificationData`].
+    /// [`CertificationData`].
     pub fn new(key: Option<VerifyingKey>) -> Self {
         Self {
             key,
@@ -62,12 +76,54 @@ impl<T> VerificationMessage<()> for Quote3Verifier<T> {
     }
 }
 
+/// A [`Quote3`] borrowing the bytes it was parsed from.
+///
+/// Convert it with `Quote3::<Vec<u8>>::from()` to keep the quote past the
+/// lifetime of the bytes.
+pub type Quote3Ref<'a> = Quote3<&'a [u8]>;
+
+/// Size of the ISV report body of a version 3 quote
+const QUOTE3_REPORT_BODY_SIZE: usize = 384;
+
+/// Re-serialization of a [`Quote3`].
+///
+/// The quote is re-emitted as it was parsed, so a verified quote can be
+/// archived, forwarded, or hashed without keeping the original buffer.
+///
+/// ```
+/// # use mc_attestation_verifier::{Quote3, Quote3Bytes};
+/// # fn archive(quote: &Quote3<&[u8]>) -> Vec<u8> {
+/// let bytes = quote.to_bytes();
+/// # bytes
+/// # }
+/// ```
+pub trait Quote3Bytes {
+    /// The quote as it was parsed, the header, report body, and signature
+    /// data.
+    ///
+    /// Any bytes the input had after the signature data are not included.
+    fn to_bytes(&self) -> Vec<u8>;
+}
+
+impl<T: AsRef<[u8]>> Quote3Bytes for Quote3<T> {
+    fn to_bytes(&self) -> Vec<u8> {
+        let bytes = self.as_ref();
+        let mut reader = Reader::new(bytes);
+        let size = reader
+            .range(QuoteField::Header, QuoteHeader::SIZE)
+            .and_then(|_| reader.range(QuoteField::ReportBody, QUOTE3_REPORT_BODY_SIZE))
+            .and_then(|_| reader.u32(QuoteField::SignatureDataSize))
+            .expect("The quote was parsed so the signature data size is present");
+        let size = reader.offset() + size as usize;
+        bytes[..size].to_vec()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::VerificationTreeDisplay;
     use alloc::format;
-    use mc_sgx_dcap_types::CertificationData;
     use x509_cert::{der::DecodePem, Certificate};
 
     const QUOTE_BYTES: &[u8] = include_bytes!("../data/tests/hw_quote.dat");
@@ -110,6 +166,23 @@ mod test {
         assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
     }
 
+    #[test]
+    fn parse_quote3() {
+        let quote = Quote3::try_from(QUOTE_BYTES).expect("Failed to parse quote");
+        assert_eq!(quote.as_ref(), QUOTE_BYTES);
+        assert!(matches!(
+            quote.signature_data().certification_data(),
+            CertificationData::PckCertificateChain(_)
+        ));
+    }
+
+    #[test]
+    fn truncated_quote3_fails() {
+        let truncated = &QUOTE_BYTES[..QUOTE_BYTES.len() - 1];
+        let error = Quote3::try_from(truncated).expect_err("Parsed a truncated quote");
+        assert!(matches!(Error::from(error), Error::Quote3(_)));
+    }
+
     #[test]
     fn failure_to_verify_quote3() {
         let mut quote_bytes = QUOTE_BYTES.to_vec();
@@ -145,4 +218,14 @@ mod test {
             - [ ] The quote signature could not be verified: No public key available for signature verification"#;
         assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
     }
+
+    #[test]
+    fn quote3_to_bytes_round_trips() {
+        let quote = Quote3::try_from(QUOTE_BYTES).expect("Failed to parse quote");
+        let bytes = quote.to_bytes();
+
+        assert_eq!(bytes, QUOTE_BYTES);
+        let reparsed = Quote3::try_from(bytes.as_slice()).expect("Failed to parse quote");
+        assert_eq!(reparsed.app_report_body(), quote.app_report_body());
+    }
 }
@@ -0,0 +1,1441 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Version 4 DCAP quotes, as produced for TDX.
+//!
+//! The layout is documented in Appendix A of
+//! <https://download.01.org/intel-sgx/latest/dcap-latest/linux/docs/Intel_TDX_DCAP_Quoting_Library_API.pdf>.
+//! A quote is a [`QuoteHeader`], a [`TdReportBody`], and the variable length
+//! [`Quote4SignatureData`]. Every section is bounds checked when parsing, so
+//! the accessors never fail.
+
+use crate::{Error, PckCertificationData, QuoteInspector, TeeType};
+use alloc::vec::Vec;
+use core::{
+    fmt::{Display, Formatter},
+    ops::Range,
+};
+use mc_sgx_core_types::ReportBody;
+use p256::ecdsa::{signature::Verifier as _, Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// The attestation key type for ECDSA-256 with P-256
+pub(crate) const ECDSA_P256_KEY_TYPE: u16 = 2;
+
+/// The certification data type of the QE report certification data
+const QE_REPORT_CERTIFICATION_DATA_TYPE: u16 = 6;
+
+/// Size of an ECDSA P-256 signature or raw public key
+const ECDSA_P256_SIZE: usize = 64;
+
+/// The SEC1 tag of an uncompressed public key
+const SEC1_UNCOMPRESSED_TAG: u8 = 0x04;
+
+/// Offset of the report data in a SGX report body
+const REPORT_DATA_OFFSET: usize = 320;
+
+/// Size of a SGX report body, the QE report
+pub(crate) const QE_REPORT_SIZE: usize = 384;
+
+/// The ID of Intel's quoting enclaves, in [`QuoteHeader::qe_vendor_id()`]
+pub const INTEL_QE_VENDOR_ID: [u8; 16] = [
+    0x93, 0x9A, 0x72, 0x33, 0xF7, 0x9C, 0x4C, 0xA9, 0x94, 0x0A, 0x0D, 0xB3, 0x95, 0x7F, 0x06, 0x07,
+];
+
+/// Error parsing or validating a quote
+#[derive(displaydoc::Display, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuoteError {
+    /// Truncated {field} at offset {offset}, requires {size} bytes, {available} available
+    #[allow(missing_docs)]
+    Truncated {
+        field: QuoteField,
+        offset: usize,
+        size: usize,
+        available: usize,
+    },
+    /// Unsupported quote version {0}
+    Version(u16),
+    /// Unsupported attestation key type {0}
+    AttestationKeyType(u16),
+    /// Unsupported TEE type {0:#x}
+    TeeType(u32),
+    /// Unsupported quote body type {0}
+    BodyType(u16),
+    /// Quote body type {body_type} is not valid for {tee_type}
+    #[allow(missing_docs)]
+    BodyTypeTee { body_type: u16, tee_type: TeeType },
+    /// Quote version {version} is not valid for {tee_type}
+    #[allow(missing_docs)]
+    VersionTee { version: u16, tee_type: TeeType },
+    /// Unsupported QE vendor ID {0:02X?}
+    QeVendorId([u8; 16]),
+    /// Report body size mismatch, expecting {expected} bytes got {actual}
+    #[allow(missing_docs)]
+    BodySize { expected: usize, actual: usize },
+    /// Unsupported certification data type {0}
+    CertificationDataType(u16),
+    /// Certification data type {data_type} should be {expected} bytes, got {actual}
+    #[allow(missing_docs)]
+    CertificationDataSize {
+        data_type: u16,
+        expected: usize,
+        actual: usize,
+    },
+    /// Invalid ECDSA signature
+    Signature,
+    /// Invalid ECDSA attestation key
+    AttestationKey,
+    /// The QE report signature does not match the PCK key
+    QeReportSignature,
+    /// The attestation key is not bound to the QE report data
+    AttestationKeyBinding,
+    /// The quote signature does not match the attestation key
+    QuoteSignature,
+    /// Unsupported EPID signature type {0}
+    EpidSignatureType(u16),
+}
+
+/// A field, or variable length section, of a quote
+///
+/// Named by [`QuoteError::Truncated`] when the quote ends before the field
+/// does.
+#[derive(displaydoc::Display, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum QuoteField {
+    /// quote header
+    Header,
+    /// report body type
+    BodyType,
+    /// report body size
+    BodySize,
+    /// report body
+    ReportBody,
+    /// signature data size
+    SignatureDataSize,
+    /// signature data
+    SignatureData,
+    /// quote signature
+    QuoteSignature,
+    /// attestation key
+    AttestationKey,
+    /// QE report certification data type
+    QeCertificationDataType,
+    /// QE report certification data size
+    QeCertificationDataSize,
+    /// QE report certification data
+    QeCertificationData,
+    /// QE report
+    QeReport,
+    /// QE report signature
+    QeReportSignature,
+    /// QE authentication data size
+    QeAuthenticationDataSize,
+    /// QE authentication data
+    QeAuthenticationData,
+    /// certification data type
+    CertificationDataType,
+    /// certification data size
+    CertificationDataSize,
+    /// certification data
+    CertificationData,
+    /// PPID
+    Ppid,
+    /// CPUSVN
+    CpuSvn,
+    /// PCE SVN
+    PceSvn,
+    /// PCE ID
+    PceId,
+    /// EPID signature size
+    EpidSignatureSize,
+    /// EPID signature
+    EpidSignature,
+}
+
+impl From<QuoteError> for Error {
+    fn from(e: QuoteError) -> Self {
+        Error::Quote(e)
+    }
+}
+
+/// Bounds checked reads from the quote bytes.
+///
+/// Every read names the [`QuoteField`] being read, so a truncated quote is
+/// reported with the field and its offset. Reads never go past the end of
+/// the input, or of the section the reader was created for, regardless of
+/// the sizes in the quote.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    /// How far into the input has been read
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Advance over the `size` bytes of `field`, returning their range in the
+    /// input
+    pub(crate) fn range(
+        &mut self,
+        field: QuoteField,
+        size: usize,
+    ) -> Result<Range<usize>, QuoteError> {
+        let end = self
+            .offset
+            .checked_add(size)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(QuoteError::Truncated {
+                field,
+                offset: self.offset,
+                size,
+                available: self.bytes.len().saturating_sub(self.offset),
+            })?;
+        let range = self.offset..end;
+        self.offset = end;
+        Ok(range)
+    }
+
+    /// A reader over the next `size` bytes, the contents of `field`, with
+    /// offsets relative to the whole input
+    pub(crate) fn section(
+        &mut self,
+        field: QuoteField,
+        size: usize,
+    ) -> Result<Reader<'a>, QuoteError> {
+        let range = self.range(field, size)?;
+        Ok(Reader {
+            bytes: &self.bytes[..range.end],
+            offset: range.start,
+        })
+    }
+
+    pub(crate) fn take(&mut self, field: QuoteField, size: usize) -> Result<&'a [u8], QuoteError> {
+        let range = self.range(field, size)?;
+        Ok(&self.bytes[range])
+    }
+
+    pub(crate) fn array<const N: usize>(
+        &mut self,
+        field: QuoteField,
+    ) -> Result<[u8; N], QuoteError> {
+        let bytes = self.take(field, N)?;
+        Ok(bytes.try_into().expect("`take()` returns `N` bytes"))
+    }
+
+    pub(crate) fn u16(&mut self, field: QuoteField) -> Result<u16, QuoteError> {
+        self.array(field).map(u16::from_le_bytes)
+    }
+
+    pub(crate) fn u32(&mut self, field: QuoteField) -> Result<u32, QuoteError> {
+        self.array(field).map(u32::from_le_bytes)
+    }
+
+    pub(crate) fn u64(&mut self, field: QuoteField) -> Result<u64, QuoteError> {
+        self.array(field).map(u64::from_le_bytes)
+    }
+}
+
+/// The header of a DCAP quote.
+///
+/// The header layout is shared by the SGX and TDX quote versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QuoteHeader {
+    version: u16,
+    attestation_key_type: u16,
+    tee_type: u32,
+    qe_svn: u16,
+    pce_svn: u16,
+    qe_vendor_id: [u8; 16],
+    user_data: [u8; 20],
+}
+
+impl QuoteHeader {
+    /// Size of the header in bytes
+    pub const SIZE: usize = 48;
+
+    /// The quote versions supported by [`QuoteHeader::validate()`]
+    pub const SUPPORTED_VERSIONS: [u16; 3] = [3, 4, 5];
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, QuoteError> {
+        let mut reader = reader.section(QuoteField::Header, Self::SIZE)?;
+        let field = QuoteField::Header;
+        Ok(Self {
+            version: reader.u16(field)?,
+            attestation_key_type: reader.u16(field)?,
+            tee_type: reader.u32(field)?,
+            qe_svn: reader.u16(field)?,
+            pce_svn: reader.u16(field)?,
+            qe_vendor_id: reader.array(field)?,
+            user_data: reader.array(field)?,
+        })
+    }
+
+    /// The version of the quote format
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// The type of the attestation key, `2` for ECDSA-256 with P-256
+    pub fn attestation_key_type(&self) -> u16 {
+        self.attestation_key_type
+    }
+
+    /// The raw TEE type, see [`TeeType::value()`]
+    pub fn tee_type_value(&self) -> u32 {
+        self.tee_type
+    }
+
+    /// The TEE which produced the quote, `None` if it's unknown
+    pub fn tee_type(&self) -> Option<TeeType> {
+        [TeeType::Sgx, TeeType::Tdx]
+            .into_iter()
+            .find(|tee_type| tee_type.value() == self.tee_type)
+    }
+
+    /// The security version of the QE.
+    ///
+    /// Reserved in version 4 quotes.
+    pub fn qe_svn(&self) -> u16 {
+        self.qe_svn
+    }
+
+    /// The security version of the PCE.
+    ///
+    /// Reserved in version 4 quotes.
+    pub fn pce_svn(&self) -> u16 {
+        self.pce_svn
+    }
+
+    /// The ID of the QE vendor, [`INTEL_QE_VENDOR_ID`] for Intel's QE
+    pub fn qe_vendor_id(&self) -> &[u8; 16] {
+        &self.qe_vendor_id
+    }
+
+    /// Custom data provided by the QE
+    pub fn user_data(&self) -> &[u8; 20] {
+        &self.user_data
+    }
+
+    /// Validate the header, before any cryptographic work is done on the
+    /// quote.
+    ///
+    /// Ensures the version is supported and valid for the TEE type, the
+    /// attestation key is ECDSA-256 with P-256, and the quote comes from
+    /// Intel's QE. The fixed length of the user data is enforced when parsing
+    /// the header.
+    ///
+    /// # Errors
+    /// * `QuoteError::Version` if the version is unsupported
+    /// * `QuoteError::AttestationKeyType` if the key isn't ECDSA P-256
+    /// * `QuoteError::TeeType` if the TEE type is unknown
+    /// * `QuoteError::VersionTee` if the TEE can't produce this version,
+    ///   version 3 is only for SGX and version 4 only for TDX
+    /// * `QuoteError::QeVendorId` if the QE isn't from Intel
+    pub fn validate(&self) -> Result<(), QuoteError> {
+        if !Self::SUPPORTED_VERSIONS.contains(&self.version) {
+            return Err(QuoteError::Version(self.version));
+        }
+        if self.attestation_key_type != ECDSA_P256_KEY_TYPE {
+            return Err(QuoteError::AttestationKeyType(self.attestation_key_type));
+        }
+        let tee_type = self.tee_type().ok_or(QuoteError::TeeType(self.tee_type))?;
+        match (self.version, tee_type) {
+            (3, TeeType::Tdx) | (4, TeeType::Sgx) => {
+                return Err(QuoteError::VersionTee {
+                    version: self.version,
+                    tee_type,
+                })
+            }
+            _ => {}
+        }
+        if self.qe_vendor_id != INTEL_QE_VENDOR_ID {
+            return Err(QuoteError::QeVendorId(self.qe_vendor_id));
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for QuoteHeader {
+    type Error = QuoteError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::read(&mut Reader::new(bytes))
+    }
+}
+
+/// The TD report body of a TDX quote.
+///
+/// Describes the TDX module (SEAM) and the measurements of the trust domain.
+/// TDX 1.5 bodies, only found in version 5 quotes, extend the TDX 1.0 body
+/// with [`TdReportBody::tee_tcb_svn_2()`] and
+/// [`TdReportBody::mr_service_td()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TdReportBody {
+    tee_tcb_svn: [u8; 16],
+    mr_seam: [u8; 48],
+    mr_signer_seam: [u8; 48],
+    seam_attributes: [u8; 8],
+    td_attributes: [u8; 8],
+    xfam: [u8; 8],
+    mr_td: [u8; 48],
+    mr_config_id: [u8; 48],
+    mr_owner: [u8; 48],
+    mr_owner_config: [u8; 48],
+    rtmrs: [[u8; 48]; 4],
+    report_data: [u8; 64],
+    tee_tcb_svn_2: Option<[u8; 16]>,
+    mr_service_td: Option<[u8; 48]>,
+}
+
+impl TdReportBody {
+    /// Size of the TDX 1.0 report body in bytes
+    pub const SIZE: usize = 584;
+
+    /// Size of the TDX 1.5 report body in bytes
+    pub const TD15_SIZE: usize = 648;
+
+    pub(crate) fn read(reader: &mut Reader, td15: bool) -> Result<Self, QuoteError> {
+        let size = if td15 { Self::TD15_SIZE } else { Self::SIZE };
+        let mut reader = reader.section(QuoteField::ReportBody, size)?;
+        let field = QuoteField::ReportBody;
+        let mut body = Self {
+            tee_tcb_svn: reader.array(field)?,
+            mr_seam: reader.array(field)?,
+            mr_signer_seam: reader.array(field)?,
+            seam_attributes: reader.array(field)?,
+            td_attributes: reader.array(field)?,
+            xfam: reader.array(field)?,
+            mr_td: reader.array(field)?,
+            mr_config_id: reader.array(field)?,
+            mr_owner: reader.array(field)?,
+            mr_owner_config: reader.array(field)?,
+            rtmrs: [
+                reader.array(field)?,
+                reader.array(field)?,
+                reader.array(field)?,
+                reader.array(field)?,
+            ],
+            report_data: reader.array(field)?,
+            tee_tcb_svn_2: None,
+            mr_service_td: None,
+        };
+        if td15 {
+            body.tee_tcb_svn_2 = Some(reader.array(field)?);
+            body.mr_service_td = Some(reader.array(field)?);
+        }
+        Ok(body)
+    }
+
+    /// The size of this body in bytes, [`Self::SIZE`] or [`Self::TD15_SIZE`]
+    pub fn size(&self) -> usize {
+        match self.mr_service_td {
+            Some(_) => Self::TD15_SIZE,
+            None => Self::SIZE,
+        }
+    }
+
+    /// The security versions of the TDX module's TCB components
+    pub fn tee_tcb_svn(&self) -> &[u8; 16] {
+        &self.tee_tcb_svn
+    }
+
+    /// The measurement of the TDX module
+    pub fn mr_seam(&self) -> &[u8; 48] {
+        &self.mr_seam
+    }
+
+    /// The measurement of the TDX module's signer, zero for Intel's module
+    pub fn mr_signer_seam(&self) -> &[u8; 48] {
+        &self.mr_signer_seam
+    }
+
+    /// The attributes of the TDX module, currently zero
+    pub fn seam_attributes(&self) -> &[u8; 8] {
+        &self.seam_attributes
+    }
+
+    /// The attributes of the TD, including the debug bit
+    pub fn td_attributes(&self) -> &[u8; 8] {
+        &self.td_attributes
+    }
+
+    /// The extended features (XSAVE) available to the TD
+    pub fn xfam(&self) -> &[u8; 8] {
+        &self.xfam
+    }
+
+    /// The measurement of the initial contents of the TD
+    pub fn mr_td(&self) -> &[u8; 48] {
+        &self.mr_td
+    }
+
+    /// Software defined ID for the TD's configuration
+    pub fn mr_config_id(&self) -> &[u8; 48] {
+        &self.mr_config_id
+    }
+
+    /// Software defined ID for the TD's owner
+    pub fn mr_owner(&self) -> &[u8; 48] {
+        &self.mr_owner
+    }
+
+    /// Software defined ID for the owner defined configuration of the TD
+    pub fn mr_owner_config(&self) -> &[u8; 48] {
+        &self.mr_owner_config
+    }
+
+    /// The runtime extendable measurement registers, RTMR0 through RTMR3
+    pub fn rtmrs(&self) -> &[[u8; 48]; 4] {
+        &self.rtmrs
+    }
+
+    /// The runtime extendable measurement register at `index`, `None` if
+    /// `index` is greater than 3
+    pub fn rtmr(&self, index: usize) -> Option<&[u8; 48]> {
+        self.rtmrs.get(index)
+    }
+
+    /// Data provided by the TD, usually to bind a key or nonce to the quote
+    pub fn report_data(&self) -> &[u8; 64] {
+        &self.report_data
+    }
+
+    /// The security versions of the TDX module's TCB components for TDX 1.5,
+    /// `None` for a TDX 1.0 body
+    pub fn tee_tcb_svn_2(&self) -> Option<&[u8; 16]> {
+        self.tee_tcb_svn_2.as_ref()
+    }
+
+    /// The measurement of the service TD bound to this TD for TDX 1.5, `None`
+    /// for a TDX 1.0 body
+    pub fn mr_service_td(&self) -> Option<&[u8; 48]> {
+        self.mr_service_td.as_ref()
+    }
+}
+
+/// Parses a TDX 1.0 or TDX 1.5 body, depending on the length of the bytes
+impl TryFrom<&[u8]> for TdReportBody {
+    type Error = QuoteError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let td15 = match bytes.len() {
+            Self::SIZE => false,
+            Self::TD15_SIZE => true,
+            actual => {
+                return Err(QuoteError::BodySize {
+                    expected: Self::SIZE,
+                    actual,
+                })
+            }
+        };
+        Self::read(&mut Reader::new(bytes), td15)
+    }
+}
+
+/// Where the signature data sections are in the quote bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SignatureDataLayout {
+    signature: Signature,
+    attestation_key: VerifyingKey,
+    qe_report: Range<usize>,
+    qe_report_signature: Signature,
+    qe_authentication_data: Range<usize>,
+    certification_data_type: u16,
+    certification_data: Range<usize>,
+}
+
+impl SignatureDataLayout {
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, QuoteError> {
+        // The section sizes are redundant with the sizes of their contents,
+        // but they still bound them.
+        let size = reader.u32(QuoteField::SignatureDataSize)? as usize;
+        let mut reader = reader.section(QuoteField::SignatureData, size)?;
+
+        let signature = read_signature(&mut reader, QuoteField::QuoteSignature)?;
+        let attestation_key = read_attestation_key(&mut reader)?;
+
+        let certification_data_type = reader.u16(QuoteField::QeCertificationDataType)?;
+        if certification_data_type != QE_REPORT_CERTIFICATION_DATA_TYPE {
+            return Err(QuoteError::CertificationDataType(certification_data_type));
+        }
+        let size = reader.u32(QuoteField::QeCertificationDataSize)? as usize;
+        let mut reader = reader.section(QuoteField::QeCertificationData, size)?;
+
+        let qe_report = reader.range(QuoteField::QeReport, QE_REPORT_SIZE)?;
+        let qe_report_signature = read_signature(&mut reader, QuoteField::QeReportSignature)?;
+        let qe_authentication_data_size =
+            reader.u16(QuoteField::QeAuthenticationDataSize)? as usize;
+        let qe_authentication_data = reader.range(
+            QuoteField::QeAuthenticationData,
+            qe_authentication_data_size,
+        )?;
+        let certification_data_type = reader.u16(QuoteField::CertificationDataType)?;
+        let certification_data_size = reader.u32(QuoteField::CertificationDataSize)? as usize;
+        let certification_data =
+            reader.range(QuoteField::CertificationData, certification_data_size)?;
+
+        Ok(Self {
+            signature,
+            attestation_key,
+            qe_report,
+            qe_report_signature,
+            qe_authentication_data,
+            certification_data_type,
+            certification_data,
+        })
+    }
+}
+
+fn read_signature(reader: &mut Reader, field: QuoteField) -> Result<Signature, QuoteError> {
+    let bytes = reader.take(field, ECDSA_P256_SIZE)?;
+    Signature::from_slice(bytes).map_err(|_| QuoteError::Signature)
+}
+
+/// The attestation key is the raw x and y coordinates, without the SEC1 tag
+fn read_attestation_key(reader: &mut Reader) -> Result<VerifyingKey, QuoteError> {
+    let mut sec1 = [SEC1_UNCOMPRESSED_TAG; ECDSA_P256_SIZE + 1];
+    sec1[1..].copy_from_slice(reader.take(QuoteField::AttestationKey, ECDSA_P256_SIZE)?);
+    VerifyingKey::from_sec1_bytes(&sec1).map_err(|_| QuoteError::AttestationKey)
+}
+
+/// The signature data of a [`Quote4`] or a [`Quote5`](crate::Quote5).
+///
+/// The QE report certification data is flattened into this type, it's the
+/// only certification data supported at the top level of a version 4 quote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quote4SignatureData<'a> {
+    bytes: &'a [u8],
+    layout: &'a SignatureDataLayout,
+}
+
+impl<'a> Quote4SignatureData<'a> {
+    pub(crate) fn new(bytes: &'a [u8], layout: &'a SignatureDataLayout) -> Self {
+        Self { bytes, layout }
+    }
+
+    /// The signature of the quote header and report body by the attestation
+    /// key
+    pub fn signature(&self) -> Signature {
+        self.layout.signature
+    }
+
+    /// The attestation key generated by the QE
+    pub fn attestation_key(&self) -> VerifyingKey {
+        self.layout.attestation_key
+    }
+
+    /// The report of the QE, binding the attestation key to the QE
+    pub fn qe_report_body(&self) -> ReportBody {
+        ReportBody::try_from(self.qe_report_bytes()).expect("QE report size was checked")
+    }
+
+    /// The raw bytes of the QE report, as signed by the PCK
+    pub fn qe_report_bytes(&self) -> &'a [u8] {
+        &self.bytes[self.layout.qe_report.clone()]
+    }
+
+    /// The signature of the QE report by the PCK
+    pub fn qe_report_signature(&self) -> Signature {
+        self.layout.qe_report_signature
+    }
+
+    /// Verify the QE report signature with the `pck_key`.
+    ///
+    /// The `pck_key` must come from the leaf of a verified PCK certificate
+    /// chain, see
+    /// `VerifiedCertChain::verify_qe_report()` with the `mbedtls` feature.
+    ///
+    /// # Errors
+    /// `QuoteError::QeReportSignature` if the QE report was not signed by the
+    /// `pck_key`.
+    pub fn verify_qe_report_signature(&self, pck_key: &VerifyingKey) -> Result<(), QuoteError> {
+        pck_key
+            .verify(self.qe_report_bytes(), &self.layout.qe_report_signature)
+            .map_err(|_| QuoteError::QeReportSignature)
+    }
+
+    /// Verify the attestation key is bound to the QE report.
+    ///
+    /// The first 32 bytes of the QE report data must be the SHA-256 hash of
+    /// the raw attestation key followed by the QE authentication data. The
+    /// remaining 32 bytes must be zero. Only meaningful once the QE report
+    /// signature has been verified, see
+    /// [`Quote4SignatureData::verify_qe_report_signature()`].
+    ///
+    /// # Errors
+    /// `QuoteError::AttestationKeyBinding` if the hash doesn't match.
+    pub fn verify_attestation_key_binding(&self) -> Result<(), QuoteError> {
+        let encoded_key = self.layout.attestation_key.to_encoded_point(false);
+        let mut hasher = Sha256::new();
+        hasher.update(&encoded_key.as_bytes()[1..]);
+        hasher.update(self.qe_authentication_data());
+        let mut expected = [0u8; 64];
+        expected[..32].copy_from_slice(&hasher.finalize());
+
+        let report_data = &self.qe_report_bytes()[REPORT_DATA_OFFSET..];
+        if bool::from(report_data.ct_eq(&expected)) {
+            Ok(())
+        } else {
+            Err(QuoteError::AttestationKeyBinding)
+        }
+    }
+
+    /// Verify the `signed` bytes of the quote with the attestation key
+    pub(crate) fn verify_quote_signature(&self, signed: &[u8]) -> Result<(), QuoteError> {
+        self.layout
+            .attestation_key
+            .verify(signed, &self.layout.signature)
+            .map_err(|_| QuoteError::QuoteSignature)
+    }
+
+    /// Verify the QE report and the quote signatures, in order, see
+    /// [`VerifiedQuote`]
+    pub(crate) fn verify(&self, pck_key: &VerifyingKey, signed: &[u8]) -> Result<(), QuoteError> {
+        self.verify_qe_report_signature(pck_key)?;
+        self.verify_attestation_key_binding()?;
+        self.verify_quote_signature(signed)
+    }
+
+    /// The QE authentication data, hashed with the attestation key into the
+    /// QE report data
+    pub fn qe_authentication_data(&self) -> &'a [u8] {
+        &self.bytes[self.layout.qe_authentication_data.clone()]
+    }
+
+    /// The type of the nested certification data, `5` for a PEM PCK
+    /// certificate chain
+    pub fn certification_data_type(&self) -> u16 {
+        self.layout.certification_data_type
+    }
+
+    /// The raw nested certification data
+    pub fn certification_data(&self) -> &'a [u8] {
+        &self.bytes[self.layout.certification_data.clone()]
+    }
+
+    /// The parsed nested certification data
+    ///
+    /// # Errors
+    /// The errors of [`PckCertificationData::try_from_raw()`].
+    pub fn pck_certification_data(&self) -> Result<PckCertificationData<'a>, Error> {
+        PckCertificationData::try_from_raw(
+            self.certification_data_type(),
+            self.certification_data(),
+        )
+    }
+}
+
+/// A version 4 DCAP quote, produced by a TDX trust domain.
+///
+/// ```
+/// use mc_attestation_verifier::{Quote4, QuoteError, QuoteField};
+///
+/// let error = Quote4::try_from([4u8, 0].as_slice()).unwrap_err();
+/// assert_eq!(
+///     error,
+///     QuoteError::Truncated { field: QuoteField::Header, offset: 0, size: 48, available: 2 }
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quote4<T> {
+    bytes: T,
+    size: usize,
+    header: QuoteHeader,
+    report_body: TdReportBody,
+    signature_data: SignatureDataLayout,
+}
+
+impl<T: AsRef<[u8]>> Quote4<T> {
+    /// The quote version
+    pub const VERSION: u16 = 4;
+
+    fn parse(bytes: T) -> Result<Self, QuoteError> {
+        let mut reader = Reader::new(bytes.as_ref());
+        let header = QuoteHeader::read(&mut reader)?;
+        if header.version != Self::VERSION {
+            return Err(QuoteError::Version(header.version));
+        }
+        if header.attestation_key_type != ECDSA_P256_KEY_TYPE {
+            return Err(QuoteError::AttestationKeyType(header.attestation_key_type));
+        }
+        if header.tee_type() != Some(TeeType::Tdx) {
+            return Err(QuoteError::TeeType(header.tee_type));
+        }
+        let report_body = TdReportBody::read(&mut reader, false)?;
+        let signature_data = SignatureDataLayout::read(&mut reader)?;
+        let size = reader.offset();
+
+        Ok(Self {
+            bytes,
+            size,
+            header,
+            report_body,
+            signature_data,
+        })
+    }
+
+    /// The quote header
+    pub fn header(&self) -> &QuoteHeader {
+        &self.header
+    }
+
+    /// The TD report body
+    pub fn report_body(&self) -> &TdReportBody {
+        &self.report_body
+    }
+
+    /// The signature data
+    pub fn signature_data(&self) -> Quote4SignatureData<'_> {
+        Quote4SignatureData::new(self.bytes.as_ref(), &self.signature_data)
+    }
+
+    /// The bytes which are signed by the attestation key, the header and the
+    /// report body
+    pub fn signed_bytes(&self) -> &[u8] {
+        &self.bytes.as_ref()[..QuoteHeader::SIZE + TdReportBody::SIZE]
+    }
+
+    /// The quote as it was parsed, for archiving or forwarding the quote
+    /// without keeping the original buffer.
+    ///
+    /// Any bytes the input had after the signature data are not included.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bytes.as_ref()[..self.size].to_vec()
+    }
+
+    /// Verify the signature of the header and report body with the
+    /// attestation key.
+    ///
+    /// On its own this only shows the quote wasn't modified after it was
+    /// signed, the attestation key still needs to be tied to the platform,
+    /// see [`Quote4::verify()`].
+    ///
+    /// # Errors
+    /// `QuoteError::QuoteSignature` if the signature doesn't match.
+    pub fn verify_signature(&self) -> Result<(), QuoteError> {
+        self.signature_data()
+            .verify_quote_signature(self.signed_bytes())
+    }
+
+    /// Verify all of the quote's signatures, with the `pck_key` from the leaf
+    /// of the verified PCK certificate chain.
+    ///
+    /// In order: the QE report signature by the PCK, the binding of the
+    /// attestation key to the QE report, and the quote signature by the
+    /// attestation key.
+    ///
+    /// # Errors
+    /// `QuoteError::QeReportSignature`, `QuoteError::AttestationKeyBinding`,
+    /// or `QuoteError::QuoteSignature` for the first step which fails.
+    pub fn verify(self, pck_key: &VerifyingKey) -> Result<VerifiedQuote<Self>, QuoteError> {
+        self.signature_data().verify(pck_key, self.signed_bytes())?;
+        Ok(VerifiedQuote::new(self))
+    }
+}
+
+/// A [`Quote4`] borrowing the bytes it was parsed from.
+///
+/// Parsing and verifying the quote do not allocate, the header and report
+/// body are copied out of the bytes and the signature data is read in place.
+/// Use [`Quote4::to_owned()`] to keep the quote past the lifetime of the
+/// bytes.
+pub type Quote4Ref<'a> = Quote4<&'a [u8]>;
+
+impl Quote4<&[u8]> {
+    /// Copy the bytes of the quote so it no longer borrows them
+    ///
+    /// The quote is not parsed again.
+    pub fn to_owned(&self) -> Quote4<Vec<u8>> {
+        Quote4 {
+            bytes: self.bytes.to_vec(),
+            size: self.size,
+            header: self.header,
+            report_body: self.report_body.clone(),
+            signature_data: self.signature_data.clone(),
+        }
+    }
+}
+
+impl From<Quote4<&[u8]>> for Quote4<Vec<u8>> {
+    fn from(quote: Quote4<&[u8]>) -> Self {
+        quote.to_owned()
+    }
+}
+
+/// A quote whose signatures have been verified, back to the PCK.
+///
+/// Produced by [`Quote4::verify()`] or [`Quote5::verify()`](crate::Quote5::verify).
+/// The PCK certificate chain, TCB, and identity of the quote still need to be
+/// appraised.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedQuote<Q>(Q);
+
+impl<Q> VerifiedQuote<Q> {
+    pub(crate) fn new(quote: Q) -> Self {
+        Self(quote)
+    }
+
+    /// The verified quote
+    pub fn quote(&self) -> &Q {
+        &self.0
+    }
+
+    /// Unwrap the verified quote
+    pub fn into_inner(self) -> Q {
+        self.0
+    }
+}
+
+impl<Q> AsRef<Q> for VerifiedQuote<Q> {
+    fn as_ref(&self) -> &Q {
+        &self.0
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for Quote4<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes.as_ref()
+    }
+}
+
+/// Every field of the quote, see [`QuoteInspector`](crate::QuoteInspector)
+impl<T: AsRef<[u8]>> Display for Quote4<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        QuoteInspector::new(self.as_ref()).fmt(f)
+    }
+}
+
+impl<T> crate::Accessor<TdReportBody> for Quote4<T> {
+    fn get(&self) -> TdReportBody {
+        self.report_body.clone()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Quote4<&'a [u8]> {
+    type Error = QuoteError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::parse(bytes)
+    }
+}
+
+impl TryFrom<Vec<u8>> for Quote4<Vec<u8>> {
+    type Error = QuoteError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::parse(bytes)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use alloc::{string::ToString, vec, vec::Vec};
+    use assert_matches::assert_matches;
+    use p256::ecdsa::{signature::Signer, SigningKey};
+    use yare::parameterized;
+
+    const QUOTE_BYTES: &[u8] = include_bytes!("../data/tests/hw_quote.dat");
+
+    /// Offset of the signature data in a version 4 quote
+    const SIGNATURE_DATA_OFFSET: usize = QuoteHeader::SIZE + TdReportBody::SIZE;
+
+    pub(crate) fn attestation_signing_key() -> SigningKey {
+        SigningKey::from_slice(&[1u8; 32]).expect("Failed to create signing key")
+    }
+
+    /// A quote header for the `version` and `tee_type`
+    pub(crate) fn quote_header(version: u16, tee_type: TeeType) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend(version.to_le_bytes());
+        header.extend(ECDSA_P256_KEY_TYPE.to_le_bytes());
+        header.extend(tee_type.value().to_le_bytes());
+        header.extend([0u8; 4]);
+        header.extend(INTEL_QE_VENDOR_ID);
+        header.extend([0u8; 20]);
+        header
+    }
+
+    /// A TD report body where each field is filled with its index, to catch
+    /// offset errors
+    pub(crate) fn td_report_body(td15: bool) -> Vec<u8> {
+        let mut field_sizes = vec![16, 48, 48, 8, 8, 8, 48, 48, 48, 48, 48, 48, 48, 48, 64];
+        if td15 {
+            field_sizes.extend([16, 48]);
+        }
+        field_sizes
+            .into_iter()
+            .enumerate()
+            .flat_map(|(index, size)| vec![index as u8; size])
+            .collect()
+    }
+
+    pub(crate) fn pck_signing_key() -> SigningKey {
+        SigningKey::from_slice(&[2u8; 32]).expect("Failed to create signing key")
+    }
+
+    /// The size prefixed signature data for the `signed` bytes, the QE
+    /// report is signed by the [`pck_signing_key()`] and binds the attestation
+    /// key, its other contents are not meaningful
+    pub(crate) fn signature_data(signed: &[u8]) -> Vec<u8> {
+        let key = attestation_signing_key();
+        let signature: Signature = key.sign(signed);
+        let encoded_key = key.verifying_key().to_encoded_point(false);
+
+        let qe_authentication_data = [0xBBu8; 3];
+        let mut qe_report = [0xAAu8; QE_REPORT_SIZE];
+        let mut hasher = Sha256::new();
+        hasher.update(&encoded_key.as_bytes()[1..]);
+        hasher.update(qe_authentication_data);
+        qe_report[REPORT_DATA_OFFSET..REPORT_DATA_OFFSET + 32].copy_from_slice(&hasher.finalize());
+        qe_report[REPORT_DATA_OFFSET + 32..].fill(0);
+        let qe_report_signature: Signature = pck_signing_key().sign(&qe_report);
+        let mut qe_certification_data = vec![];
+        qe_certification_data.extend(qe_report);
+        qe_certification_data.extend(qe_report_signature.to_bytes());
+        qe_certification_data.extend(3u16.to_le_bytes());
+        qe_certification_data.extend(qe_authentication_data);
+        qe_certification_data.extend(5u16.to_le_bytes());
+        qe_certification_data.extend(5u32.to_le_bytes());
+        qe_certification_data.extend(b"chain");
+
+        let mut signature_data = vec![];
+        signature_data.extend(signature.to_bytes());
+        signature_data.extend(&encoded_key.as_bytes()[1..]);
+        signature_data.extend(QE_REPORT_CERTIFICATION_DATA_TYPE.to_le_bytes());
+        signature_data.extend((qe_certification_data.len() as u32).to_le_bytes());
+        signature_data.extend(qe_certification_data);
+
+        let mut sized = (signature_data.len() as u32).to_le_bytes().to_vec();
+        sized.extend(signature_data);
+        sized
+    }
+
+    /// A syntactically valid TDX quote, the signatures are not meaningful
+    pub(crate) fn tdx_quote() -> Vec<u8> {
+        let mut quote = quote_header(4, TeeType::Tdx);
+        quote.extend(td_report_body(false));
+        let signature_data = signature_data(&quote);
+        quote.extend(signature_data);
+        quote
+    }
+
+    #[test]
+    fn parse_tdx_quote() {
+        let bytes = tdx_quote();
+        let quote = Quote4::try_from(bytes.as_slice()).expect("Failed to parse quote");
+        assert_eq!(quote.as_ref(), bytes.as_slice());
+
+        let header = quote.header();
+        assert_eq!(header.version(), 4);
+        assert_eq!(header.attestation_key_type(), 2);
+        assert_eq!(header.tee_type(), Some(TeeType::Tdx));
+        assert_eq!(header.tee_type_value(), 0x81);
+        assert_eq!(header.qe_vendor_id(), &INTEL_QE_VENDOR_ID);
+        assert_eq!(header.user_data(), &[0; 20]);
+    }
+
+    #[test]
+    fn tdx_quote_to_bytes_round_trips() {
+        let bytes = tdx_quote();
+        let quote = Quote4::try_from(bytes.as_slice()).expect("Failed to parse quote");
+        let serialized = quote.to_bytes();
+
+        assert_eq!(serialized, bytes);
+        let reparsed = Quote4::try_from(serialized).expect("Failed to parse quote");
+        assert_eq!(reparsed.header(), quote.header());
+        assert_eq!(reparsed.report_body(), quote.report_body());
+        assert_eq!(reparsed.signature_data(), quote.signature_data());
+    }
+
+    #[test]
+    fn tdx_quote_to_bytes_drops_trailing_bytes() {
+        let bytes = tdx_quote();
+        let mut padded = bytes.clone();
+        padded.extend([0xFF; 8]);
+        let quote = Quote4::try_from(padded).expect("Failed to parse quote");
+
+        assert_eq!(quote.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn tdx_quote_to_owned() {
+        let owned = {
+            let bytes = tdx_quote();
+            let borrowed: Quote4Ref =
+                Quote4::try_from(bytes.as_slice()).expect("Failed to parse quote");
+            borrowed.to_owned()
+        };
+
+        let bytes = tdx_quote();
+        let parsed = Quote4::try_from(bytes.clone()).expect("Failed to parse quote");
+        assert_eq!(owned, parsed);
+        assert_eq!(owned.verify_signature(), Ok(()));
+    }
+
+    #[test]
+    fn valid_quote_headers() {
+        let tdx = QuoteHeader::try_from(tdx_quote().as_slice()).expect("Failed to parse header");
+        assert_eq!(tdx.validate(), Ok(()));
+
+        let sgx = QuoteHeader::try_from(QUOTE_BYTES).expect("Failed to parse header");
+        assert_eq!(sgx.validate(), Ok(()));
+
+        for tee_type in [TeeType::Sgx, TeeType::Tdx] {
+            let v5 = QuoteHeader::try_from(quote_header(5, tee_type).as_slice()).unwrap();
+            assert_eq!(v5.validate(), Ok(()));
+        }
+    }
+
+    #[parameterized(
+        version_2 = { 0, 2, QuoteError::Version(2) },
+        version_6 = { 0, 6, QuoteError::Version(6) },
+        sgx_version_4 = { 4, 0, QuoteError::VersionTee { version: 4, tee_type: TeeType::Sgx } },
+        key_type = { 2, 3, QuoteError::AttestationKeyType(3) },
+        tee_type = { 4, 0x82, QuoteError::TeeType(0x82) },
+        vendor_id = { 12, 0x94, {
+            let mut vendor_id = INTEL_QE_VENDOR_ID;
+            vendor_id[0] = 0x94;
+            QuoteError::QeVendorId(vendor_id)
+        } },
+    )]
+    fn invalid_quote_header(offset: usize, value: u8, expected: QuoteError) {
+        let mut bytes = quote_header(4, TeeType::Tdx);
+        bytes[offset] = value;
+        let header = QuoteHeader::try_from(bytes.as_slice()).expect("Failed to parse header");
+        assert_eq!(header.validate(), Err(expected));
+    }
+
+    #[test]
+    fn sgx_version_3_for_tdx_fails() {
+        let mut bytes = QUOTE_BYTES[..QuoteHeader::SIZE].to_vec();
+        bytes[4] = 0x81;
+        let header = QuoteHeader::try_from(bytes.as_slice()).expect("Failed to parse header");
+        assert_eq!(
+            header.validate(),
+            Err(QuoteError::VersionTee {
+                version: 3,
+                tee_type: TeeType::Tdx
+            })
+        );
+    }
+
+    #[test]
+    fn quote_header_user_data_too_short() {
+        let bytes = quote_header(4, TeeType::Tdx);
+        assert_eq!(
+            QuoteHeader::try_from(&bytes[..QuoteHeader::SIZE - 1]),
+            Err(QuoteError::Truncated {
+                field: QuoteField::Header,
+                offset: 0,
+                size: QuoteHeader::SIZE,
+                available: QuoteHeader::SIZE - 1
+            })
+        );
+    }
+
+    #[test]
+    fn tdx_report_body_fields() {
+        let quote = Quote4::try_from(tdx_quote()).expect("Failed to parse quote");
+        let body = quote.report_body();
+        assert_eq!(body.tee_tcb_svn(), &[0; 16]);
+        assert_eq!(body.mr_seam(), &[1; 48]);
+        assert_eq!(body.mr_signer_seam(), &[2; 48]);
+        assert_eq!(body.seam_attributes(), &[3; 8]);
+        assert_eq!(body.td_attributes(), &[4; 8]);
+        assert_eq!(body.xfam(), &[5; 8]);
+        assert_eq!(body.mr_td(), &[6; 48]);
+        assert_eq!(body.mr_config_id(), &[7; 48]);
+        assert_eq!(body.mr_owner(), &[8; 48]);
+        assert_eq!(body.mr_owner_config(), &[9; 48]);
+        assert_eq!(body.rtmrs(), &[[10; 48], [11; 48], [12; 48], [13; 48]]);
+        assert_eq!(body.rtmr(3), Some(&[13; 48]));
+        assert_eq!(body.rtmr(4), None);
+        assert_eq!(body.report_data(), &[14; 64]);
+        assert_eq!(body.tee_tcb_svn_2(), None);
+        assert_eq!(body.mr_service_td(), None);
+        assert_eq!(body.size(), TdReportBody::SIZE);
+    }
+
+    #[test]
+    fn td15_report_body() {
+        let bytes = td_report_body(true);
+        let body = TdReportBody::try_from(bytes.as_slice()).expect("Failed to parse body");
+        assert_eq!(body.report_data(), &[14; 64]);
+        assert_eq!(body.tee_tcb_svn_2(), Some(&[15; 16]));
+        assert_eq!(body.mr_service_td(), Some(&[16; 48]));
+        assert_eq!(body.size(), TdReportBody::TD15_SIZE);
+    }
+
+    #[test]
+    fn td_report_body_wrong_size() {
+        let bytes = td_report_body(true);
+        assert_eq!(
+            TdReportBody::try_from(&bytes[..TdReportBody::SIZE + 1]),
+            Err(QuoteError::BodySize {
+                expected: TdReportBody::SIZE,
+                actual: TdReportBody::SIZE + 1
+            })
+        );
+    }
+
+    #[test]
+    fn tdx_signature_data() {
+        let bytes = tdx_quote();
+        let quote = Quote4::try_from(bytes.as_slice()).expect("Failed to parse quote");
+        let signature_data = quote.signature_data();
+        let key = attestation_signing_key();
+
+        assert_eq!(&signature_data.attestation_key(), key.verifying_key());
+        assert_eq!(
+            signature_data.signature(),
+            key.sign(&bytes[..SIGNATURE_DATA_OFFSET])
+        );
+        assert_eq!(quote.signed_bytes(), &bytes[..SIGNATURE_DATA_OFFSET]);
+        assert_eq!(
+            signature_data.qe_report_bytes()[..REPORT_DATA_OFFSET],
+            [0xAA; REPORT_DATA_OFFSET]
+        );
+        assert_eq!(signature_data.qe_authentication_data(), &[0xBB; 3]);
+        assert_eq!(signature_data.certification_data_type(), 5);
+        assert_eq!(signature_data.certification_data(), b"chain");
+    }
+
+    #[test]
+    fn tdx_qe_report_signature() {
+        let quote = Quote4::try_from(tdx_quote()).expect("Failed to parse quote");
+        let signature_data = quote.signature_data();
+        assert_eq!(
+            signature_data.verify_qe_report_signature(pck_signing_key().verifying_key()),
+            Ok(())
+        );
+        assert_eq!(
+            signature_data.verify_qe_report_signature(attestation_signing_key().verifying_key()),
+            Err(QuoteError::QeReportSignature)
+        );
+    }
+
+    #[test]
+    fn modified_qe_report_fails_signature() {
+        let mut bytes = tdx_quote();
+        let qe_report_offset = SIGNATURE_DATA_OFFSET + 4 + 64 + 64 + 6;
+        bytes[qe_report_offset] = 0;
+        let quote = Quote4::try_from(bytes).expect("Failed to parse quote");
+        assert_eq!(
+            quote
+                .signature_data()
+                .verify_qe_report_signature(pck_signing_key().verifying_key()),
+            Err(QuoteError::QeReportSignature)
+        );
+    }
+
+    #[test]
+    fn tdx_attestation_key_binding() {
+        let quote = Quote4::try_from(tdx_quote()).expect("Failed to parse quote");
+        assert_eq!(
+            quote.signature_data().verify_attestation_key_binding(),
+            Ok(())
+        );
+    }
+
+    #[parameterized(
+        authentication_data = { SIGNATURE_DATA_OFFSET + 4 + 64 + 64 + 6 + QE_REPORT_SIZE + 64 + 2 },
+        report_data = { SIGNATURE_DATA_OFFSET + 4 + 64 + 64 + 6 + REPORT_DATA_OFFSET },
+        report_data_padding = { SIGNATURE_DATA_OFFSET + 4 + 64 + 64 + 6 + QE_REPORT_SIZE - 1 },
+    )]
+    fn unbound_attestation_key_fails(offset: usize) {
+        let mut bytes = tdx_quote();
+        bytes[offset] ^= 1;
+        let quote = Quote4::try_from(bytes).expect("Failed to parse quote");
+        assert_eq!(
+            quote.signature_data().verify_attestation_key_binding(),
+            Err(QuoteError::AttestationKeyBinding)
+        );
+    }
+
+    #[test]
+    fn tdx_quote_signature() {
+        let quote = Quote4::try_from(tdx_quote()).expect("Failed to parse quote");
+        assert_eq!(quote.verify_signature(), Ok(()));
+
+        let verified = quote
+            .clone()
+            .verify(pck_signing_key().verifying_key())
+            .expect("Failed to verify quote");
+        assert_eq!(verified.quote(), &quote);
+        assert_eq!(verified.into_inner(), quote);
+    }
+
+    #[parameterized(
+        header = { 10 },
+        body = { QuoteHeader::SIZE },
+        last_body_byte = { SIGNATURE_DATA_OFFSET - 1 },
+    )]
+    fn modified_tdx_quote_fails_signature(offset: usize) {
+        let mut bytes = tdx_quote();
+        bytes[offset] ^= 1;
+        let quote = Quote4::try_from(bytes).expect("Failed to parse quote");
+        assert_eq!(quote.verify_signature(), Err(QuoteError::QuoteSignature));
+        assert_eq!(
+            quote.verify(pck_signing_key().verifying_key()),
+            Err(QuoteError::QuoteSignature)
+        );
+    }
+
+    #[test]
+    fn verify_tdx_quote_checks_qe_report_first() {
+        let mut bytes = tdx_quote();
+        // Break both the quote signature and the attestation key binding
+        bytes[QuoteHeader::SIZE] ^= 1;
+        let binding_offset = SIGNATURE_DATA_OFFSET + 4 + 64 + 64 + 6 + REPORT_DATA_OFFSET;
+        bytes[binding_offset] ^= 1;
+        let quote = Quote4::try_from(bytes).expect("Failed to parse quote");
+
+        assert_eq!(
+            quote
+                .clone()
+                .verify(attestation_signing_key().verifying_key()),
+            Err(QuoteError::QeReportSignature)
+        );
+    }
+
+    #[test]
+    fn tdx_qe_report_body() {
+        let quote = Quote4::try_from(tdx_quote()).expect("Failed to parse quote");
+        let qe_report_body = quote.signature_data().qe_report_body();
+        assert_eq!(qe_report_body.isv_svn(), 0xAAAA.into());
+    }
+
+    #[parameterized(
+        header = { QuoteHeader::SIZE - 1, QuoteField::Header, 0, QuoteHeader::SIZE },
+        body = { SIGNATURE_DATA_OFFSET - 1, QuoteField::ReportBody, QuoteHeader::SIZE, TdReportBody::SIZE },
+        signature_data_size = { SIGNATURE_DATA_OFFSET + 1, QuoteField::SignatureDataSize, SIGNATURE_DATA_OFFSET, 4 },
+        signature_data = { SIGNATURE_DATA_OFFSET + 4 + 64, QuoteField::SignatureData, SIGNATURE_DATA_OFFSET + 4, tdx_quote().len() - SIGNATURE_DATA_OFFSET - 4 },
+        last_byte = { tdx_quote().len() - 1, QuoteField::SignatureData, SIGNATURE_DATA_OFFSET + 4, tdx_quote().len() - SIGNATURE_DATA_OFFSET - 4 },
+    )]
+    fn truncated_tdx_quote_fails(length: usize, field: QuoteField, offset: usize, size: usize) {
+        let bytes = tdx_quote();
+        assert_eq!(
+            Quote4::try_from(&bytes[..length]).unwrap_err(),
+            QuoteError::Truncated {
+                field,
+                offset,
+                size,
+                available: length - offset,
+            }
+        );
+    }
+
+    #[test]
+    fn every_truncation_of_a_tdx_quote_fails() {
+        let bytes = tdx_quote();
+        for length in 0..bytes.len() {
+            assert!(matches!(
+                Quote4::try_from(&bytes[..length]),
+                Err(QuoteError::Truncated { .. })
+            ));
+        }
+    }
+
+    // Offsets from the end of the quote, of the QE report certification
+    // data's variable length fields
+    #[parameterized(
+        qe_authentication_data = { 5 + 6 + 3 + 2, QuoteField::QeAuthenticationData },
+        certification_data = { 5 + 4, QuoteField::CertificationData },
+    )]
+    fn oversized_nested_fields_fail(offset_from_end: usize, field: QuoteField) {
+        let mut bytes = tdx_quote();
+        let offset = bytes.len() - offset_from_end;
+        // The sizes are little endian, the low bytes are the first
+        bytes[offset] = 0xFF;
+        bytes[offset + 1] = 0xFF;
+
+        assert_matches!(
+            Quote4::try_from(bytes.as_slice()),
+            Err(QuoteError::Truncated { field: f, .. }) if f == field
+        );
+    }
+
+    #[parameterized(
+        signature_data = { SIGNATURE_DATA_OFFSET, QuoteField::SignatureData },
+        qe_certification_data = { SIGNATURE_DATA_OFFSET + 4 + 128 + 2, QuoteField::QeCertificationData },
+    )]
+    fn maximum_section_sizes_fail(offset: usize, field: QuoteField) {
+        let mut bytes = tdx_quote();
+        bytes[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert_matches!(
+            Quote4::try_from(bytes.as_slice()),
+            Err(QuoteError::Truncated { field: f, size, .. }) if f == field && size == u32::MAX as usize
+        );
+    }
+
+    #[test]
+    fn nested_size_past_signature_data_fails() {
+        let mut bytes = tdx_quote();
+        // Grow the certification data size without growing the signature
+        // data, the quote has extra bytes so it's the enclosing section sizes
+        // which must catch it.
+        let size_offset = bytes.len() - 5 - 4;
+        bytes[size_offset] += 1;
+        bytes.push(0);
+
+        let error = Quote4::try_from(bytes.as_slice()).unwrap_err();
+        assert_eq!(
+            error,
+            QuoteError::Truncated {
+                field: QuoteField::CertificationData,
+                offset: bytes.len() - 6,
+                size: 6,
+                available: 5,
+            }
+        );
+    }
+
+    #[parameterized(
+        version = { 0, 3, QuoteError::Version(3) },
+        key_type = { 2, 3, QuoteError::AttestationKeyType(3) },
+        tee_type = { 4, 0, QuoteError::TeeType(0) },
+        certification_data_type = { SIGNATURE_DATA_OFFSET + 4 + 128, 5, QuoteError::CertificationDataType(5) },
+    )]
+    fn unsupported_tdx_quote_fields(offset: usize, value: u8, expected: QuoteError) {
+        let mut bytes = tdx_quote();
+        bytes[offset] = value;
+        assert_eq!(Quote4::try_from(bytes).unwrap_err(), expected);
+    }
+
+    #[test]
+    fn invalid_attestation_key_fails() {
+        let mut bytes = tdx_quote();
+        let key_offset = SIGNATURE_DATA_OFFSET + 4 + 64;
+        bytes[key_offset..key_offset + 64].fill(0);
+        assert_eq!(
+            Quote4::try_from(bytes).unwrap_err(),
+            QuoteError::AttestationKey
+        );
+    }
+
+    #[test]
+    fn invalid_signature_fails() {
+        let mut bytes = tdx_quote();
+        let signature_offset = SIGNATURE_DATA_OFFSET + 4;
+        bytes[signature_offset..signature_offset + 64].fill(0);
+        assert_eq!(Quote4::try_from(bytes).unwrap_err(), QuoteError::Signature);
+    }
+
+    #[test]
+    fn quote_error_into_error() {
+        let error = Error::from(QuoteError::Version(5));
+        assert_eq!(error, Error::Quote(QuoteError::Version(5)));
+        assert_eq!(
+            error.to_string(),
+            "Invalid quote: Unsupported quote version 5"
+        );
+    }
+}
@@ -0,0 +1,462 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Version 5 DCAP quotes.
+//!
+//! Version 5 quotes carry either a SGX report body or a TD report body, the
+//! type and size of the body precede it. The signature data is the same as
+//! for version 4 quotes. [`QuoteBody`] is the report body of any supported
+//! quote version, so code which only cares about the measurements doesn't
+//! need to branch on the version.
+
+use crate::{
+    quote4::{Reader, SignatureDataLayout, ECDSA_P256_KEY_TYPE},
+    Quote3, Quote4, Quote4SignatureData, QuoteError, QuoteField, QuoteHeader, QuoteInspector,
+    TdReportBody, TeeType, VerifiedQuote,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt::{Display, Formatter};
+use mc_sgx_core_types::ReportBody;
+use p256::ecdsa::VerifyingKey;
+
+/// Body type of a SGX report body
+const SGX_BODY_TYPE: u16 = 1;
+
+/// Body type of a TDX 1.0 report body
+const TD10_BODY_TYPE: u16 = 2;
+
+/// Body type of a TDX 1.5 report body
+const TD15_BODY_TYPE: u16 = 3;
+
+/// Size of a SGX report body
+const SGX_BODY_SIZE: usize = 384;
+
+/// Size of the body type and body size which precede the body
+const BODY_DESCRIPTOR_SIZE: usize = 6;
+
+/// The report body of a quote, regardless of the quote version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuoteBody {
+    /// The report body of a SGX enclave
+    Sgx(Box<ReportBody>),
+    /// The report body of a TDX trust domain, either TDX 1.0 or TDX 1.5
+    Td(Box<TdReportBody>),
+}
+
+impl QuoteBody {
+    /// The TEE which produced the report body
+    pub fn tee_type(&self) -> TeeType {
+        match self {
+            QuoteBody::Sgx(_) => TeeType::Sgx,
+            QuoteBody::Td(_) => TeeType::Tdx,
+        }
+    }
+
+    /// The data provided by the enclave, or trust domain, when requesting the
+    /// quote
+    pub fn report_data(&self) -> [u8; 64] {
+        match self {
+            QuoteBody::Sgx(body) => {
+                let mut report_data = [0u8; 64];
+                report_data.copy_from_slice(body.report_data().as_ref());
+                report_data
+            }
+            QuoteBody::Td(body) => *body.report_data(),
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> From<&Quote3<T>> for QuoteBody {
+    fn from(quote: &Quote3<T>) -> Self {
+        QuoteBody::Sgx(Box::new(quote.app_report_body().clone()))
+    }
+}
+
+impl<T: AsRef<[u8]>> From<&Quote4<T>> for QuoteBody {
+    fn from(quote: &Quote4<T>) -> Self {
+        QuoteBody::Td(Box::new(quote.report_body().clone()))
+    }
+}
+
+/// A version 5 DCAP quote, produced by a SGX enclave or a TDX trust domain.
+///
+/// ```
+/// use mc_attestation_verifier::{Quote5, QuoteError};
+///
+/// let mut bytes = [0u8; 48];
+/// bytes[0] = 4;
+/// let error = Quote5::try_from(bytes.as_slice()).unwrap_err();
+/// assert_eq!(error, QuoteError::Version(4));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quote5<T> {
+    bytes: T,
+    size: usize,
+    header: QuoteHeader,
+    body: QuoteBody,
+    signature_data: SignatureDataLayout,
+}
+
+impl<T: AsRef<[u8]>> Quote5<T> {
+    /// The quote version
+    pub const VERSION: u16 = 5;
+
+    fn parse(bytes: T) -> Result<Self, QuoteError> {
+        let mut reader = Reader::new(bytes.as_ref());
+        let header = QuoteHeader::read(&mut reader)?;
+        if header.version() != Self::VERSION {
+            return Err(QuoteError::Version(header.version()));
+        }
+        if header.attestation_key_type() != ECDSA_P256_KEY_TYPE {
+            return Err(QuoteError::AttestationKeyType(
+                header.attestation_key_type(),
+            ));
+        }
+        let tee_type = header
+            .tee_type()
+            .ok_or(QuoteError::TeeType(header.tee_type_value()))?;
+
+        let body_type = reader.u16(QuoteField::BodyType)?;
+        let (body_tee_type, expected) = match body_type {
+            SGX_BODY_TYPE => (TeeType::Sgx, SGX_BODY_SIZE),
+            TD10_BODY_TYPE => (TeeType::Tdx, TdReportBody::SIZE),
+            TD15_BODY_TYPE => (TeeType::Tdx, TdReportBody::TD15_SIZE),
+            _ => return Err(QuoteError::BodyType(body_type)),
+        };
+        if body_tee_type != tee_type {
+            return Err(QuoteError::BodyTypeTee {
+                body_type,
+                tee_type,
+            });
+        }
+        let actual = reader.u32(QuoteField::BodySize)? as usize;
+        if actual != expected {
+            return Err(QuoteError::BodySize { expected, actual });
+        }
+
+        let body = match body_type {
+            SGX_BODY_TYPE => QuoteBody::Sgx(Box::new(
+                ReportBody::try_from(reader.take(QuoteField::ReportBody, expected)?)
+                    .expect("body size was checked"),
+            )),
+            _ => QuoteBody::Td(Box::new(TdReportBody::read(
+                &mut reader,
+                body_type == TD15_BODY_TYPE,
+            )?)),
+        };
+        let signature_data = SignatureDataLayout::read(&mut reader)?;
+        let size = reader.offset();
+
+        Ok(Self {
+            bytes,
+            size,
+            header,
+            body,
+            signature_data,
+        })
+    }
+
+    /// The quote header
+    pub fn header(&self) -> &QuoteHeader {
+        &self.header
+    }
+
+    /// The report body
+    pub fn body(&self) -> &QuoteBody {
+        &self.body
+    }
+
+    /// The signature data
+    pub fn signature_data(&self) -> Quote4SignatureData<'_> {
+        Quote4SignatureData::new(self.bytes.as_ref(), &self.signature_data)
+    }
+
+    /// The bytes which are signed by the attestation key, the header, the
+    /// body type and size, and the body
+    pub fn signed_bytes(&self) -> &[u8] {
+        let body_size = match &self.body {
+            QuoteBody::Sgx(_) => SGX_BODY_SIZE,
+            QuoteBody::Td(body) => body.size(),
+        };
+        &self.bytes.as_ref()[..QuoteHeader::SIZE + BODY_DESCRIPTOR_SIZE + body_size]
+    }
+
+    /// The quote as it was parsed, see [`Quote4::to_bytes()`]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bytes.as_ref()[..self.size].to_vec()
+    }
+
+    /// Verify the signature of the header and body with the attestation key.
+    ///
+    /// # Errors
+    /// `QuoteError::QuoteSignature` if the signature doesn't match.
+    pub fn verify_signature(&self) -> Result<(), QuoteError> {
+        self.signature_data()
+            .verify_quote_signature(self.signed_bytes())
+    }
+
+    /// Verify all of the quote's signatures, with the `pck_key` from the leaf
+    /// of the verified PCK certificate chain, see [`Quote4::verify()`].
+    ///
+    /// # Errors
+    /// `QuoteError::QeReportSignature`, `QuoteError::AttestationKeyBinding`,
+    /// or `QuoteError::QuoteSignature` for the first step which fails.
+    pub fn verify(self, pck_key: &VerifyingKey) -> Result<VerifiedQuote<Self>, QuoteError> {
+        self.signature_data().verify(pck_key, self.signed_bytes())?;
+        Ok(VerifiedQuote::new(self))
+    }
+}
+
+/// A [`Quote5`] borrowing the bytes it was parsed from.
+///
+/// Only the report body is allocated when parsing. Use
+/// [`Quote5::to_owned()`] to keep the quote past the lifetime of the bytes.
+pub type Quote5Ref<'a> = Quote5<&'a [u8]>;
+
+impl Quote5<&[u8]> {
+    /// Copy the bytes of the quote so it no longer borrows them
+    ///
+    /// The quote is not parsed again.
+    pub fn to_owned(&self) -> Quote5<Vec<u8>> {
+        Quote5 {
+            bytes: self.bytes.to_vec(),
+            size: self.size,
+            header: self.header,
+            body: self.body.clone(),
+            signature_data: self.signature_data.clone(),
+        }
+    }
+}
+
+impl From<Quote5<&[u8]>> for Quote5<Vec<u8>> {
+    fn from(quote: Quote5<&[u8]>) -> Self {
+        quote.to_owned()
+    }
+}
+
+impl<T: AsRef<[u8]>> From<&Quote5<T>> for QuoteBody {
+    fn from(quote: &Quote5<T>) -> Self {
+        quote.body().clone()
+    }
+}
+
+/// Every field of the quote, see [`QuoteInspector`](crate::QuoteInspector)
+impl<T: AsRef<[u8]>> Display for Quote5<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        QuoteInspector::new(self.as_ref()).fmt(f)
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for Quote5<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes.as_ref()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Quote5<&'a [u8]> {
+    type Error = QuoteError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::parse(bytes)
+    }
+}
+
+impl TryFrom<Vec<u8>> for Quote5<Vec<u8>> {
+    type Error = QuoteError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::parse(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::quote4::test::{
+        attestation_signing_key, pck_signing_key, quote_header, signature_data, td_report_body,
+        tdx_quote,
+    };
+    use alloc::{vec, vec::Vec};
+    use yare::parameterized;
+
+    const QUOTE_BYTES: &[u8] = include_bytes!("../data/tests/hw_quote.dat");
+
+    fn quote5(tee_type: TeeType, body_type: u16, body: &[u8]) -> Vec<u8> {
+        let mut quote = quote_header(5, tee_type);
+        quote.extend(body_type.to_le_bytes());
+        quote.extend((body.len() as u32).to_le_bytes());
+        quote.extend(body);
+        let signature_data = signature_data(&quote);
+        quote.extend(signature_data);
+        quote
+    }
+
+    fn sgx_report_body() -> Vec<u8> {
+        // The SGX report body of the hardware version 3 quote
+        QUOTE_BYTES[QuoteHeader::SIZE..QuoteHeader::SIZE + SGX_BODY_SIZE].to_vec()
+    }
+
+    #[test]
+    fn sgx_quote5() {
+        let body = sgx_report_body();
+        let bytes = quote5(TeeType::Sgx, SGX_BODY_TYPE, &body);
+        let quote = Quote5::try_from(bytes.as_slice()).expect("Failed to parse quote");
+        let quote3 = Quote3::try_from(QUOTE_BYTES).expect("Failed to parse quote");
+
+        assert_eq!(quote.header().version(), 5);
+        assert_eq!(quote.body(), &QuoteBody::from(&quote3));
+        assert_eq!(quote.body().tee_type(), TeeType::Sgx);
+        assert_eq!(
+            quote.signed_bytes(),
+            &bytes[..QuoteHeader::SIZE + BODY_DESCRIPTOR_SIZE + SGX_BODY_SIZE]
+        );
+        assert_eq!(
+            &quote.signature_data().attestation_key(),
+            attestation_signing_key().verifying_key()
+        );
+    }
+
+    #[parameterized(
+        td10 = { TD10_BODY_TYPE, false },
+        td15 = { TD15_BODY_TYPE, true },
+    )]
+    fn tdx_quote5(body_type: u16, td15: bool) {
+        let body = td_report_body(td15);
+        let bytes = quote5(TeeType::Tdx, body_type, &body);
+        let quote = Quote5::try_from(bytes).expect("Failed to parse quote");
+
+        let QuoteBody::Td(td_body) = quote.body() else {
+            panic!("Expected a TD report body");
+        };
+        assert_eq!(
+            td_body.as_ref(),
+            &TdReportBody::try_from(body.as_slice()).unwrap()
+        );
+        assert_eq!(td_body.mr_service_td().is_some(), td15);
+        assert_eq!(quote.body().report_data(), [14; 64]);
+        assert_eq!(quote.signature_data().certification_data(), b"chain");
+        assert_eq!(quote.verify_signature(), Ok(()));
+    }
+
+    #[parameterized(
+        sgx = { TeeType::Sgx, SGX_BODY_TYPE, sgx_report_body() },
+        td10 = { TeeType::Tdx, TD10_BODY_TYPE, td_report_body(false) },
+        td15 = { TeeType::Tdx, TD15_BODY_TYPE, td_report_body(true) },
+    )]
+    fn quote5_to_bytes_round_trips(tee_type: TeeType, body_type: u16, body: Vec<u8>) {
+        let bytes = quote5(tee_type, body_type, &body);
+        let mut padded = bytes.clone();
+        padded.extend([0xFF; 8]);
+        let quote = Quote5::try_from(padded).expect("Failed to parse quote");
+        let serialized = quote.to_bytes();
+
+        assert_eq!(serialized, bytes);
+        let reparsed = Quote5::try_from(serialized).expect("Failed to parse quote");
+        assert_eq!(reparsed.header(), quote.header());
+        assert_eq!(reparsed.body(), quote.body());
+    }
+
+    #[test]
+    fn quote5_to_owned() {
+        let owned = {
+            let bytes = quote5(TeeType::Tdx, TD15_BODY_TYPE, &td_report_body(true));
+            let borrowed: Quote5Ref =
+                Quote5::try_from(bytes.as_slice()).expect("Failed to parse quote");
+            Quote5::<Vec<u8>>::from(borrowed)
+        };
+
+        let bytes = quote5(TeeType::Tdx, TD15_BODY_TYPE, &td_report_body(true));
+        let parsed = Quote5::try_from(bytes).expect("Failed to parse quote");
+        assert_eq!(owned, parsed);
+        assert_eq!(owned.verify_signature(), Ok(()));
+    }
+
+    #[test]
+    fn verify_quote5() {
+        let bytes = quote5(TeeType::Tdx, TD15_BODY_TYPE, &td_report_body(true));
+        let quote = Quote5::try_from(bytes.as_slice()).expect("Failed to parse quote");
+        let verified = quote
+            .verify(pck_signing_key().verifying_key())
+            .expect("Failed to verify quote");
+        assert_eq!(verified.quote().as_ref(), bytes.as_slice());
+    }
+
+    #[test]
+    fn modified_quote5_fails_signature() {
+        let mut bytes = quote5(TeeType::Tdx, TD15_BODY_TYPE, &td_report_body(true));
+        bytes[QuoteHeader::SIZE + BODY_DESCRIPTOR_SIZE] ^= 1;
+        let quote = Quote5::try_from(bytes).expect("Failed to parse quote");
+        assert_eq!(quote.verify_signature(), Err(QuoteError::QuoteSignature));
+        assert_eq!(
+            quote.verify(pck_signing_key().verifying_key()),
+            Err(QuoteError::QuoteSignature)
+        );
+    }
+
+    #[test]
+    fn quote4_and_quote5_bodies_match() {
+        let quote4 = Quote4::try_from(tdx_quote()).expect("Failed to parse quote");
+        let bytes = quote5(TeeType::Tdx, TD10_BODY_TYPE, &td_report_body(false));
+        let quote5 = Quote5::try_from(bytes).expect("Failed to parse quote");
+        assert_eq!(QuoteBody::from(&quote4), QuoteBody::from(&quote5));
+    }
+
+    #[test]
+    fn sgx_report_data_from_quote3() {
+        let quote3 = Quote3::try_from(QUOTE_BYTES).expect("Failed to parse quote");
+        let body = QuoteBody::from(&quote3);
+        assert_eq!(
+            body.report_data().as_slice(),
+            quote3.app_report_body().report_data().as_ref()
+        );
+    }
+
+    #[parameterized(
+        unknown_body_type = { TeeType::Tdx, 4, td_report_body(false), QuoteError::BodyType(4) },
+        sgx_body_for_tdx = { TeeType::Tdx, SGX_BODY_TYPE, sgx_report_body(), QuoteError::BodyTypeTee { body_type: SGX_BODY_TYPE, tee_type: TeeType::Tdx } },
+        td_body_for_sgx = { TeeType::Sgx, TD10_BODY_TYPE, td_report_body(false), QuoteError::BodyTypeTee { body_type: TD10_BODY_TYPE, tee_type: TeeType::Sgx } },
+        td15_size_for_td10 = { TeeType::Tdx, TD10_BODY_TYPE, td_report_body(true), QuoteError::BodySize { expected: TdReportBody::SIZE, actual: TdReportBody::TD15_SIZE } },
+        td10_size_for_td15 = { TeeType::Tdx, TD15_BODY_TYPE, td_report_body(false), QuoteError::BodySize { expected: TdReportBody::TD15_SIZE, actual: TdReportBody::SIZE } },
+    )]
+    fn invalid_quote5_body(tee_type: TeeType, body_type: u16, body: Vec<u8>, expected: QuoteError) {
+        let bytes = quote5(tee_type, body_type, &body);
+        assert_eq!(Quote5::try_from(bytes).unwrap_err(), expected);
+    }
+
+    #[test]
+    fn truncated_quote5_fails() {
+        let bytes = quote5(TeeType::Tdx, TD15_BODY_TYPE, &td_report_body(true));
+        let length = QuoteHeader::SIZE + BODY_DESCRIPTOR_SIZE + 10;
+        assert_eq!(
+            Quote5::try_from(&bytes[..length]).unwrap_err(),
+            QuoteError::Truncated {
+                field: QuoteField::ReportBody,
+                offset: QuoteHeader::SIZE + BODY_DESCRIPTOR_SIZE,
+                size: TdReportBody::TD15_SIZE,
+                available: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_tee_type_fails() {
+        let mut bytes = quote5(TeeType::Tdx, TD10_BODY_TYPE, &td_report_body(false));
+        bytes[4] = 0x82;
+        assert_eq!(
+            Quote5::try_from(bytes).unwrap_err(),
+            QuoteError::TeeType(0x82)
+        );
+    }
+
+    #[test]
+    fn empty_quote5_fails() {
+        assert_eq!(
+            Quote5::try_from(vec![]).unwrap_err(),
+            QuoteError::Truncated {
+                field: QuoteField::Header,
+                offset: 0,
+                size: QuoteHeader::SIZE,
+                available: 0,
+            }
+        );
+    }
+}
@@ -1,17 +1,27 @@
 // Copyright (c) 2018-2024 The MobileCoin Foundation
 
 //! Verifiers which operate on the [`ReportBody`]
+//!
+//! The [`ReportBody`] of a SGX enclave, from `mc-sgx-core-types`, is
+//! re-exported along with the newtypes of its fields. Each getter, like
+//! [`ReportBody::mr_enclave()`], returns a typed value which displays as hex,
+//! so policies can compare measurements without offsets into the quote
+//! bytes.
 
 use crate::{
-    choice_to_status_message, Accessor, EqualityVerifier, GreaterThanEqualVerifier, MaskedVerifier,
-    VerificationMessage, VerificationOutput, Verifier, MESSAGE_INDENT,
+    choice_to_status_message, struct_name::SpacedStructName, Accessor, EqualityVerifier, Error,
+    GreaterThanEqualVerifier, MaskedVerifier, VerificationMessage, VerificationOutput, Verifier,
+    MESSAGE_INDENT,
 };
-use core::fmt::{Debug, Formatter};
-use mc_sgx_core_types::{
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::{Debug, Display, Formatter};
+use mc_sgx_core_sys_types::sgx_attributes_t;
+use mc_sgx_core_types::AttributeFlags;
+pub use mc_sgx_core_types::{
     Attributes, ConfigId, ConfigSvn, CpuSvn, ExtendedProductId, FamilyId, IsvProductId, IsvSvn,
     MiscellaneousSelect, MrEnclave, MrSigner, ReportBody, ReportData,
 };
-use subtle::{ConstantTimeEq, ConstantTimeGreater};
+use subtle::{Choice, ConstantTimeEq, ConstantTimeGreater};
 
 /// Macro to generate boilerplate for implementing [`Accessor`] for a field of
 /// [`ReportBody`].
@@ -47,6 +57,30 @@ report_body_field_accessor! {
 /// Verifier for ensuring [`Attributes`] values are equivalent.
 pub type AttributesVerifier = MaskedVerifier<Attributes>;
 
+/// Verifier which fails when the DEBUG flag is set in the [`Attributes`].
+///
+/// A debug enclave can be inspected by the host, so none of its secrets are
+/// protected. Production verifiers should always include this check, it is
+/// applied by default in [`verify_quote()`](crate::verify_quote).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RejectDebug;
+
+impl<E: Accessor<Attributes>> Verifier<E> for RejectDebug {
+    type Value = Attributes;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let attributes = evidence.get();
+        let debug = attributes.flags().bits() & AttributeFlags::DEBUG.bits();
+        let is_success = debug.ct_eq(&0);
+        VerificationOutput::new(attributes, is_success)
+    }
+}
+
+impl Display for RejectDebug {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "The DEBUG attribute should not be set")
+    }
+}
+
 /// Verifier for ensuring [`ConfigId`] values are equivalent.
 pub type ConfigIdVerifier = EqualityVerifier<ConfigId>;
 
@@ -126,11 +160,159 @@ impl<E: Accessor<IsvSvn>> Verifier<E> for GreaterThanEqualVerifier<IsvSvn> {
 /// Verifier for ensuring [`MiscellaneousSelect`] values are equivalent.
 pub type MiscellaneousSelectVerifier = MaskedVerifier<MiscellaneousSelect>;
 
+impl MaskedVerifier<Attributes> {
+    /// Create a new instance from the hex encoded little endian bytes of the
+    /// `attributes` and `mask`, as in the `attributes` and `attributesMask`
+    /// of an enclave identity.
+    ///
+    /// ```
+    /// use mc_attestation_verifier::AttributesVerifier;
+    ///
+    /// // Requires INITTED and MODE64BIT, and that DEBUG is not set
+    /// let verifier = AttributesVerifier::try_from_hex(
+    ///     "05000000000000000000000000000000",
+    ///     "07000000000000000000000000000000",
+    /// );
+    /// assert!(verifier.is_ok());
+    /// ```
+    ///
+    /// # Errors
+    /// `Error::ReportBodyHex` if either is not 16 bytes of hex.
+    pub fn try_from_hex(attributes: &str, mask: &str) -> Result<Self, Error> {
+        let attributes = attributes_from_bytes(&decode_hex(attributes, "attributes")?);
+        let mask = attributes_from_bytes(&decode_hex(mask, "attributes mask")?);
+        Ok(Self::new(attributes, mask))
+    }
+}
+
+impl MaskedVerifier<MiscellaneousSelect> {
+    /// Create a new instance from the hex encoded little endian bytes of the
+    /// `miscellaneous_select` and `mask`, as in the `miscselect` and
+    /// `miscselectMask` of an enclave identity.
+    ///
+    /// # Errors
+    /// `Error::ReportBodyHex` if either is not 4 bytes of hex.
+    pub fn try_from_hex(miscellaneous_select: &str, mask: &str) -> Result<Self, Error> {
+        let miscellaneous_select =
+            miscellaneous_select_from_bytes(decode_hex(miscellaneous_select, "miscselect")?);
+        let mask = miscellaneous_select_from_bytes(decode_hex(mask, "miscselect mask")?);
+        Ok(Self::new(miscellaneous_select, mask))
+    }
+}
+
+/// The [`Attributes`] from their little endian bytes, flags then XFRM
+pub(crate) fn attributes_from_bytes(bytes: &[u8; 16]) -> Attributes {
+    let mut word = [0u8; 8];
+    word.copy_from_slice(&bytes[..8]);
+    let flags = u64::from_le_bytes(word);
+    word.copy_from_slice(&bytes[8..]);
+    let xfrm = u64::from_le_bytes(word);
+    sgx_attributes_t { flags, xfrm }.into()
+}
+
+/// The [`MiscellaneousSelect`] from its little endian bytes
+pub(crate) fn miscellaneous_select_from_bytes(bytes: [u8; 4]) -> MiscellaneousSelect {
+    u32::from_le_bytes(bytes).into()
+}
+
+fn decode_hex<const N: usize>(hex: &str, kind: &str) -> Result<[u8; N], Error> {
+    let mut bytes = [0u8; N];
+    hex::decode_to_slice(hex, &mut bytes).map_err(|e| Error::ReportBodyHex {
+        kind: kind.into(),
+        error: format!("{e}"),
+    })?;
+    Ok(bytes)
+}
+
 /// Verifier for ensuring [`MrEnclave`] values are equivalent.
 ///
 /// The Intel SDK docs refer to this as "Strict Enclave Modification Policy"
 pub type MrEnclaveVerifier = EqualityVerifier<MrEnclave>;
 
+/// Policy trusting any of an allowlist of enclave builds, by [`MrEnclave`].
+///
+/// The measurement is compared against every allowed value in constant time.
+/// The allowlist can be changed in place, so a fleet can allow the new build
+/// before an upgrade and remove the old one after it.
+///
+/// ```
+/// use mc_attestation_verifier::{MrEnclave, MrEnclavePolicy, Verifier};
+///
+/// let old_build = MrEnclave::from([1; 32]);
+/// let new_build = MrEnclave::from([2; 32]);
+/// let mut policy = MrEnclavePolicy::new([old_build]);
+/// policy.allow(new_build);
+/// assert_eq!(policy.verify(&new_build).is_success().unwrap_u8(), 1);
+///
+/// policy.remove(&old_build);
+/// assert_eq!(policy.verify(&old_build).is_failure().unwrap_u8(), 1);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MrEnclavePolicy {
+    allowed: Vec<MrEnclave>,
+}
+
+impl MrEnclavePolicy {
+    /// Create a new instance allowing the `allowed` measurements
+    pub fn new(allowed: impl IntoIterator<Item = MrEnclave>) -> Self {
+        let mut policy = Self::default();
+        for mr_enclave in allowed {
+            policy.allow(mr_enclave);
+        }
+        policy
+    }
+
+    /// Add `mr_enclave` to the allowed measurements
+    pub fn allow(&mut self, mr_enclave: MrEnclave) {
+        if !self.allowed.contains(&mr_enclave) {
+            self.allowed.push(mr_enclave);
+        }
+    }
+
+    /// Remove `mr_enclave` from the allowed measurements
+    pub fn remove(&mut self, mr_enclave: &MrEnclave) {
+        self.allowed.retain(|allowed| allowed != mr_enclave);
+    }
+
+    /// The allowed measurements
+    pub fn allowed(&self) -> &[MrEnclave] {
+        &self.allowed
+    }
+}
+
+impl<E: Accessor<MrEnclave>> Verifier<E> for MrEnclavePolicy {
+    type Value = MrEnclave;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let actual = evidence.get();
+        // Every allowed value is compared so the time doesn't reveal which
+        // one matched.
+        let is_success = self
+            .allowed
+            .iter()
+            .fold(Choice::from(0), |matched, allowed| {
+                matched | allowed.as_ref().ct_eq(actual.as_ref())
+            });
+        VerificationOutput::new(actual, is_success)
+    }
+}
+
+impl Display for MrEnclavePolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "The {} should be one of [",
+            MrEnclave::spaced_struct_name()
+        )?;
+        for (index, mr_enclave) in self.allowed.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{mr_enclave}")?;
+        }
+        write!(f, "]")
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct MrSignerValue {
     mr_signer_key: VerificationOutput<MrSigner>,
@@ -218,20 +400,360 @@ impl VerificationMessage<MrSignerValue> for MrSignerVerifier {
     }
 }
 
+/// Verifier for ensuring the [`MrSigner`] key hash is equivalent, without
+/// the product ID and ISV SVN of a [`MrSignerVerifier`].
 pub type MrSignerKeyVerifier = EqualityVerifier<MrSigner>;
 
+/// Policy trusting any enclave from a signer, rather than a specific build.
+///
+/// The enclave must be signed by `mr_signer`, be the product `isv_prod_id`,
+/// and have an ISV SVN of at least `min_isv_svn`. Verifies the same as
+/// [`MrSignerVerifier`], with the parts of the rule named.
+///
+/// ```
+/// use mc_attestation_verifier::{IsvProductId, IsvSvn, MrSigner, MrSignerPolicy};
+///
+/// let policy = MrSignerPolicy {
+///     mr_signer: MrSigner::from([0xAB; 32]),
+///     isv_prod_id: IsvProductId::from(1),
+///     min_isv_svn: IsvSvn::from(3),
+/// };
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MrSignerPolicy {
+    /// The MRSIGNER of the trusted signer
+    pub mr_signer: MrSigner,
+    /// The product ID the enclave must have
+    pub isv_prod_id: IsvProductId,
+    /// The lowest ISV SVN of the enclave to accept
+    pub min_isv_svn: IsvSvn,
+}
+
+impl From<&MrSignerPolicy> for MrSignerVerifier {
+    fn from(policy: &MrSignerPolicy) -> Self {
+        Self::new(policy.mr_signer, policy.isv_prod_id, policy.min_isv_svn)
+    }
+}
+
+impl<E: Accessor<MrSigner> + Accessor<IsvProductId> + Accessor<IsvSvn>> Verifier<E>
+    for MrSignerPolicy
+{
+    type Value = MrSignerValue;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        MrSignerVerifier::from(self).verify(evidence)
+    }
+}
+
+impl VerificationMessage<MrSignerValue> for MrSignerPolicy {
+    fn fmt_padded(
+        &self,
+        f: &mut Formatter<'_>,
+        pad: usize,
+        output: &VerificationOutput<MrSignerValue>,
+    ) -> core::fmt::Result {
+        MrSignerVerifier::from(self).fmt_padded(f, pad, output)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KssValue {
+    config_id: VerificationOutput<ConfigId>,
+    config_svn: VerificationOutput<ConfigSvn>,
+    extended_product_id: VerificationOutput<ExtendedProductId>,
+    family_id: VerificationOutput<FamilyId>,
+}
+
+/// Policy for the Key Separation and Sharing (KSS) identity of an enclave.
+///
+/// With KSS an enclave is further identified by the CONFIGID and CONFIGSVN
+/// it was loaded with, and the ISVEXTPRODID and ISVFAMILYID it was signed
+/// with. The CONFIGID, ISVEXTPRODID, and ISVFAMILYID must match exactly, the
+/// CONFIGSVN must be at least `min_config_svn`. This is usually combined
+/// with a [`MrEnclavePolicy`] or [`MrSignerPolicy`] using
+/// [`And`](crate::And).
+///
+/// ```
+/// use mc_attestation_verifier::{ConfigId, ConfigSvn, ExtendedProductId, FamilyId, KssPolicy};
+///
+/// let policy = KssPolicy {
+///     config_id: ConfigId::from([0x11; 64]),
+///     min_config_svn: ConfigSvn::from(2),
+///     isv_extended_product_id: ExtendedProductId::from([0x22; 16]),
+///     isv_family_id: FamilyId::from([0x33; 16]),
+/// };
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KssPolicy {
+    /// The CONFIGID the enclave must have been loaded with
+    pub config_id: ConfigId,
+    /// The lowest CONFIGSVN of the enclave to accept
+    pub min_config_svn: ConfigSvn,
+    /// The ISVEXTPRODID the enclave must have
+    pub isv_extended_product_id: ExtendedProductId,
+    /// The ISVFAMILYID the enclave must have
+    pub isv_family_id: FamilyId,
+}
+
+impl<E> Verifier<E> for KssPolicy
+where
+    E: Accessor<ConfigId> + Accessor<ConfigSvn> + Accessor<ExtendedProductId> + Accessor<FamilyId>,
+{
+    type Value = KssValue;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let config_id = ConfigIdVerifier::new(self.config_id.clone()).verify(evidence);
+        let config_svn = ConfigSvnVerifier::new(self.min_config_svn).verify(evidence);
+        let extended_product_id =
+            ExtendedProductIdVerifier::new(self.isv_extended_product_id.clone()).verify(evidence);
+        let family_id = FamilyIdVerifier::new(self.isv_family_id.clone()).verify(evidence);
+
+        let is_success = config_id.is_success()
+            & config_svn.is_success()
+            & extended_product_id.is_success()
+            & family_id.is_success();
+
+        VerificationOutput::new(
+            KssValue {
+                config_id,
+                config_svn,
+                extended_product_id,
+                family_id,
+            },
+            is_success,
+        )
+    }
+}
+
+impl VerificationMessage<KssValue> for KssPolicy {
+    fn fmt_padded(
+        &self,
+        f: &mut Formatter<'_>,
+        pad: usize,
+        output: &VerificationOutput<KssValue>,
+    ) -> core::fmt::Result {
+        let status = choice_to_status_message(output.is_success());
+
+        write!(
+            f,
+            "{:pad$}{status} KSS identity all of the following must be true:",
+            ""
+        )?;
+        let pad = pad + MESSAGE_INDENT;
+        writeln!(f)?;
+        ConfigIdVerifier::new(self.config_id.clone()).fmt_padded(
+            f,
+            pad,
+            &output.value.config_id,
+        )?;
+        writeln!(f)?;
+        ConfigSvnVerifier::new(self.min_config_svn).fmt_padded(f, pad, &output.value.config_svn)?;
+        writeln!(f)?;
+        ExtendedProductIdVerifier::new(self.isv_extended_product_id.clone()).fmt_padded(
+            f,
+            pad,
+            &output.value.extended_product_id,
+        )?;
+        writeln!(f)?;
+        FamilyIdVerifier::new(self.isv_family_id.clone()).fmt_padded(
+            f,
+            pad,
+            &output.value.family_id,
+        )
+    }
+}
+
 /// Verifier for ensuring [`ReportData`] values are equivalent.
+///
+/// The report data is how applications bind a nonce, or a channel, to the
+/// quote. Besides [`ReportDataVerifier::new()`] with an explicit mask, the
+/// report data can be checked against an exact value with
+/// [`ReportDataVerifier::exact()`], or only its leading bytes with
+/// [`ReportDataVerifier::prefix()`]. For values which need to be computed,
+/// like a hash of a nonce, see [`ReportDataFnVerifier`].
 pub type ReportDataVerifier = MaskedVerifier<ReportData>;
 
+impl MaskedVerifier<ReportData> {
+    /// Verify all 64 bytes of the report data are `expected`
+    pub fn exact(expected: ReportData) -> Self {
+        Self::new(expected, [0xFF; ReportData::SIZE].into())
+    }
+
+    /// Verify the report data starts with `prefix`, the remaining bytes are
+    /// ignored.
+    ///
+    /// # Errors
+    /// `Error::ReportDataPrefixLength` if `prefix` is longer than the report
+    /// data.
+    pub fn prefix(prefix: &[u8]) -> Result<Self, Error> {
+        if prefix.len() > ReportData::SIZE {
+            return Err(Error::ReportDataPrefixLength(prefix.len()));
+        }
+        let mut expected = [0u8; ReportData::SIZE];
+        expected[..prefix.len()].copy_from_slice(prefix);
+        let mut mask = [0u8; ReportData::SIZE];
+        mask[..prefix.len()].fill(0xFF);
+        Ok(Self::new(expected.into(), mask.into()))
+    }
+}
+
+/// Verifier for ensuring [`ReportData`] satisfies a caller provided
+/// function.
+///
+/// This is for report data which binds values the verifier has to compute,
+/// for example `SHA-256(nonce || tls_exporter)`.
+///
+/// ```
+/// use mc_attestation_verifier::{ReportData, ReportDataFnVerifier, Verifier};
+///
+/// let nonce = [7u8; 32];
+/// let verifier = ReportDataFnVerifier::new("bound to the nonce", move |report_data| {
+///     let bytes: &[u8] = report_data.as_ref();
+///     bytes[..32] == nonce
+/// });
+///
+/// let mut report_data = [0u8; ReportData::SIZE];
+/// report_data[..32].copy_from_slice(&nonce);
+/// let verification = verifier.verify(&ReportData::from(report_data));
+/// assert_eq!(verification.is_success().unwrap_u8(), 1);
+/// ```
+#[derive(Clone)]
+pub struct ReportDataFnVerifier<F> {
+    description: String,
+    predicate: F,
+}
+
+impl<F: Fn(&ReportData) -> bool> ReportDataFnVerifier<F> {
+    /// Create a new instance
+    ///
+    /// # Arguments
+    /// * `description` - What the `predicate` checks for, used when
+    ///   displaying the verification.
+    /// * `predicate` - Returns `true` when the report data is acceptable.
+    pub fn new(description: impl Into<String>, predicate: F) -> Self {
+        Self {
+            description: description.into(),
+            predicate,
+        }
+    }
+}
+
+impl<F> Debug for ReportDataFnVerifier<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ReportDataFnVerifier")
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F> Display for ReportDataFnVerifier<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "The report data should be {}", self.description)
+    }
+}
+
+impl<E: Accessor<ReportData>, F: Fn(&ReportData) -> bool> Verifier<E> for ReportDataFnVerifier<F> {
+    type Value = ReportData;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let actual = evidence.get();
+        let is_success = (self.predicate)(&actual) as u8;
+        VerificationOutput::new(actual, is_success.into())
+    }
+}
+
+/// Verifier for ensuring the [`ReportBody`] satisfies a caller provided
+/// check.
+///
+/// This is for application specific checks which the built in verifiers
+/// don't cover, for example inspecting the contents of the CONFIGID. The
+/// error returned by `check` is shown when displaying the verification.
+///
+/// ```
+/// use mc_attestation_verifier::{ReportBody, ReportBodyFnVerifier, Verifier};
+/// # use mc_sgx_core_sys_types::sgx_report_body_t;
+///
+/// let verifier = ReportBodyFnVerifier::new("configured for production", |report_body| {
+///     let config_id = report_body.config_id();
+///     let bytes: &[u8] = config_id.as_ref();
+///     match bytes[0] {
+///         1 => Ok(()),
+///         mode => Err(format!("the config mode was {mode}")),
+///     }
+/// });
+///
+/// # let report_body = ReportBody::from(&sgx_report_body_t::default());
+/// let verification = verifier.verify(&report_body);
+/// assert_eq!(verification.is_failure().unwrap_u8(), 1);
+/// ```
+#[derive(Clone)]
+pub struct ReportBodyFnVerifier<F> {
+    description: String,
+    check: F,
+}
+
+impl<F: Fn(&ReportBody) -> Result<(), String>> ReportBodyFnVerifier<F> {
+    /// Create a new instance
+    ///
+    /// # Arguments
+    /// * `description` - What the `check` is for, used when displaying the
+    ///   verification.
+    /// * `check` - Returns `Ok(())` when the report body is acceptable,
+    ///   otherwise an error describing why it isn't.
+    pub fn new(description: impl Into<String>, check: F) -> Self {
+        Self {
+            description: description.into(),
+            check,
+        }
+    }
+}
+
+impl<F> Debug for ReportBodyFnVerifier<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ReportBodyFnVerifier")
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E: Accessor<ReportBody>, F: Fn(&ReportBody) -> Result<(), String>> Verifier<E>
+    for ReportBodyFnVerifier<F>
+{
+    type Value = Option<String>;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let report_body = evidence.get();
+        let error = (self.check)(&report_body).err();
+        let is_success = error.is_none() as u8;
+        VerificationOutput::new(error, is_success.into())
+    }
+}
+
+impl<F> VerificationMessage<Option<String>> for ReportBodyFnVerifier<F> {
+    fn fmt_padded(
+        &self,
+        f: &mut Formatter<'_>,
+        pad: usize,
+        output: &VerificationOutput<Option<String>>,
+    ) -> core::fmt::Result {
+        let status = choice_to_status_message(output.is_success());
+        write!(
+            f,
+            "{:pad$}{status} The report body should be {}",
+            "", self.description
+        )?;
+        match output.value() {
+            Some(error) => write!(f, ", but {error}"),
+            None => Ok(()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{struct_name::SpacedStructName, And, VerificationTreeDisplay};
+    use crate::{struct_name::SpacedStructName, And, Or, VerificationTreeDisplay};
     use alloc::{format, string::ToString};
     use mc_sgx_core_sys_types::{
         sgx_attributes_t, sgx_cpu_svn_t, sgx_measurement_t, sgx_report_body_t, sgx_report_data_t,
     };
-    use mc_sgx_core_types::{AttributeFlags, ExtendedFeatureRequestMask};
+    use mc_sgx_core_types::ExtendedFeatureRequestMask;
     use yare::parameterized;
     const ALL_ATTRIBUTE_BITS: sgx_attributes_t = sgx_attributes_t {
         flags: 0xFFFF_FFFF_FFFF_FFFF,
@@ -286,6 +808,40 @@ mod test {
         },
     };
 
+    #[test]
+    fn typed_report_body_fields() {
+        let report_body = ReportBody::from(&REPORT_BODY_SRC);
+        assert_eq!(
+            report_body.mr_enclave(),
+            MrEnclave::from(REPORT_BODY_SRC.mr_enclave.m)
+        );
+        assert_eq!(
+            report_body.mr_signer(),
+            MrSigner::from(REPORT_BODY_SRC.mr_signer.m)
+        );
+        assert_eq!(report_body.isv_product_id(), IsvProductId::from(144));
+        assert_eq!(report_body.isv_svn(), IsvSvn::from(145));
+        assert_eq!(
+            report_body.cpu_svn(),
+            CpuSvn::from(REPORT_BODY_SRC.cpu_svn.svn)
+        );
+        assert_eq!(
+            report_body.report_data(),
+            ReportData::from(REPORT_BODY_SRC.report_data.d)
+        );
+    }
+
+    #[test]
+    fn report_body_fields_display_as_hex() {
+        let report_body = ReportBody::from(&REPORT_BODY_SRC);
+        let mr_enclave = report_body.mr_enclave().to_string();
+        let digits = mr_enclave
+            .trim_start_matches("0x")
+            .replace('_', "")
+            .to_lowercase();
+        assert_eq!(digits, hex::encode(REPORT_BODY_SRC.mr_enclave.m));
+    }
+
     #[test]
     fn report_body_succeeds() {
         let report_body = ReportBody::from(&REPORT_BODY_SRC);
@@ -349,6 +905,38 @@ mod test {
         assert_eq!(verifier.verify(&report_body).is_failure().unwrap_u8(), 1);
     }
 
+    #[test]
+    fn report_body_mr_enclave_or_mr_signer_policy_reports_every_branch() {
+        let report_body = ReportBody::from(&REPORT_BODY_SRC);
+        let other_enclave = MrEnclave::from([1; 32]);
+        let verifier = Or::new(
+            MrEnclavePolicy::new([other_enclave]),
+            And::new(
+                MrSignerKeyVerifier::new(report_body.mr_signer()),
+                IsvSvnVerifier::new(IsvSvn::from(3)),
+            ),
+        );
+
+        let verification = verifier.verify(&report_body);
+
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+
+        // The allowlist failing does not stop the MRSIGNER branch from being
+        // evaluated, and both are reported.
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = format!(
+            r#"
+            - [x] One of the following must be true:
+              - [ ] The MRENCLAVE should be one of [{other_enclave}], but the actual MRENCLAVE was {}
+              - [x] Both of the following must be true:
+                - [x] The MRSIGNER key hash should be {}
+                - [x] The ISV SVN should be at least 3"#,
+            report_body.mr_enclave(),
+            report_body.mr_signer(),
+        );
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(&expected));
+    }
+
     #[test]
     fn report_body_fails_due_to_mr_signer() {
         let report_body = ReportBody::from(&REPORT_BODY_SRC);
@@ -473,6 +1061,117 @@ mod test {
         assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
     }
 
+    #[test]
+    fn attributes_from_hex_ignores_masked_off_xfrm() {
+        // INITTED | MODE64BIT with DEBUG enforced to be clear, XFRM ignored
+        let verifier = AttributesVerifier::try_from_hex(
+            "05000000000000000000000000000000",
+            "07000000000000000000000000000000",
+        )
+        .expect("Failed to parse attributes");
+        let attributes = Attributes::from(sgx_attributes_t {
+            flags: 0x05,
+            xfrm: 0xE7,
+        });
+
+        assert_eq!(verifier.verify(&attributes).is_success().unwrap_u8(), 1);
+    }
+
+    #[test]
+    fn attributes_from_hex_enforces_debug_flag() {
+        let verifier = AttributesVerifier::try_from_hex(
+            "05000000000000000000000000000000",
+            "07000000000000000000000000000000",
+        )
+        .expect("Failed to parse attributes");
+        let attributes = Attributes::from(sgx_attributes_t {
+            flags: 0x07,
+            xfrm: 0x03,
+        });
+
+        assert_eq!(verifier.verify(&attributes).is_failure().unwrap_u8(), 1);
+    }
+
+    #[test]
+    fn attributes_from_hex_matches_qe_identity_byte_order() {
+        let verifier = AttributesVerifier::try_from_hex(
+            "11000000000000000700000000000000",
+            "FBFFFFFFFFFFFFFF0000000000000000",
+        )
+        .expect("Failed to parse attributes");
+        let expected = AttributesVerifier::new(
+            sgx_attributes_t {
+                flags: 0x11,
+                xfrm: 0x07,
+            }
+            .into(),
+            sgx_attributes_t {
+                flags: 0xFFFF_FFFF_FFFF_FFFB,
+                xfrm: 0,
+            }
+            .into(),
+        );
+
+        assert_eq!(verifier, expected);
+    }
+
+    #[parameterized(
+        attributes_too_short = { "0500000000000000", "07000000000000000000000000000000", "attributes" },
+        attributes_not_hex = { "0500000000000000000000000000000z", "07000000000000000000000000000000", "attributes" },
+        mask_too_long = { "05000000000000000000000000000000", "0700000000000000000000000000000000", "attributes mask" },
+    )]
+    fn attributes_from_hex_fails(attributes: &str, mask: &str, expected_kind: &str) {
+        let error = AttributesVerifier::try_from_hex(attributes, mask)
+            .expect_err("Expected invalid hex to fail");
+        assert!(matches!(error, Error::ReportBodyHex { kind, .. } if kind == expected_kind));
+    }
+
+    #[test]
+    fn reject_debug_succeeds_for_release_enclave() {
+        let attributes = Attributes::default().set_flags(AttributeFlags::INITTED);
+        let verifier = RejectDebug;
+
+        let verification = verifier.verify(&attributes);
+
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [x] The DEBUG attribute should not be set"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn reject_debug_fails_for_debug_enclave() {
+        let attributes = Attributes::default()
+            .set_flags(AttributeFlags::INITTED | AttributeFlags::DEBUG)
+            .set_extended_features_mask(ExtendedFeatureRequestMask::AVX_512);
+        let verifier = RejectDebug;
+
+        let verification = verifier.verify(&attributes);
+
+        assert_eq!(verification.is_failure().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [ ] The DEBUG attribute should not be set, but the actual attributes was Flags: INITTED | DEBUG Xfrm: AVX | AVX_512"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn reject_debug_from_report_body() {
+        let report_body = ReportBody::from(&REPORT_BODY_SRC);
+        let debug = report_body
+            .attributes()
+            .flags()
+            .contains(AttributeFlags::DEBUG);
+
+        assert_eq!(
+            RejectDebug.verify(&report_body).is_failure().unwrap_u8(),
+            u8::from(debug)
+        );
+    }
+
     #[test]
     fn config_id_success() {
         let config_id = ConfigId::from(REPORT_BODY_SRC.config_id);
@@ -566,6 +1265,43 @@ mod test {
         );
     }
 
+    #[test]
+    fn miscellaneous_select_from_hex_ignores_masked_off_bits() {
+        let verifier = MiscellaneousSelectVerifier::try_from_hex("01000000", "01000000")
+            .expect("Failed to parse miscselect");
+        let miscellaneous_select = MiscellaneousSelect::from(0xFFFF_0001);
+
+        assert_eq!(
+            verifier
+                .verify(&miscellaneous_select)
+                .is_success()
+                .unwrap_u8(),
+            1
+        );
+    }
+
+    #[test]
+    fn miscellaneous_select_from_hex_enforces_masked_bits() {
+        let verifier = MiscellaneousSelectVerifier::try_from_hex("00000000", "FFFFFFFF")
+            .expect("Failed to parse miscselect");
+        let miscellaneous_select = MiscellaneousSelect::from(0x0000_0100);
+
+        assert_eq!(
+            verifier
+                .verify(&miscellaneous_select)
+                .is_failure()
+                .unwrap_u8(),
+            1
+        );
+    }
+
+    #[test]
+    fn miscellaneous_select_from_hex_fails_for_bad_mask() {
+        let error = MiscellaneousSelectVerifier::try_from_hex("00000000", "FFFFFF")
+            .expect_err("Expected invalid hex to fail");
+        assert!(matches!(error, Error::ReportBodyHex { kind, .. } if kind == "miscselect mask"));
+    }
+
     #[test]
     fn mr_encalve_success() {
         let mr_enclave = MrEnclave::from(REPORT_BODY_SRC.mr_enclave);
@@ -592,6 +1328,75 @@ mod test {
         assert_eq!(verifier.verify(&mr_signer).is_success().unwrap_u8(), 1);
     }
 
+    #[test]
+    fn mr_signer_policy_matches_verifier() {
+        let report_body = ReportBody::from(&REPORT_BODY_SRC);
+        let policy = MrSignerPolicy {
+            mr_signer: MrSigner::from(REPORT_BODY_SRC.mr_signer),
+            isv_prod_id: IsvProductId::from(REPORT_BODY_SRC.isv_prod_id),
+            min_isv_svn: IsvSvn::from(REPORT_BODY_SRC.isv_svn),
+        };
+        assert_eq!(policy.verify(&report_body).is_success().unwrap_u8(), 1);
+
+        let policy = MrSignerPolicy {
+            min_isv_svn: IsvSvn::from(REPORT_BODY_SRC.isv_svn + 1),
+            ..policy
+        };
+        let verification = policy.verify(&report_body);
+        assert_eq!(verification.is_failure().unwrap_u8(), 1);
+
+        let expected = MrSignerVerifier::from(&policy);
+        assert_eq!(
+            VerificationTreeDisplay::new(&policy, verification.clone()).to_string(),
+            VerificationTreeDisplay::new(&expected, verification).to_string()
+        );
+    }
+
+    #[test]
+    fn mr_enclave_policy_allows_any_listed() {
+        let mr_enclave = MrEnclave::from(REPORT_BODY_SRC.mr_enclave);
+        let policy = MrEnclavePolicy::new([MrEnclave::from([1; 32]), mr_enclave]);
+        assert_eq!(policy.verify(&mr_enclave).is_success().unwrap_u8(), 1);
+        assert_eq!(policy.allowed().len(), 2);
+    }
+
+    #[test]
+    fn mr_enclave_policy_names_offending_measurement() {
+        let policy = MrEnclavePolicy::new([MrEnclave::from([1; 32])]);
+        let verification = policy.verify(&MrEnclave::from([2; 32]));
+        assert_eq!(verification.is_failure().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&policy, verification);
+        assert_eq!(
+            displayable.to_string(),
+            format!(
+                "- [ ] The MRENCLAVE should be one of [{}], but the actual MRENCLAVE was {}",
+                MrEnclave::from([1; 32]),
+                MrEnclave::from([2; 32])
+            )
+        );
+    }
+
+    #[test]
+    fn mr_enclave_policy_rotation() {
+        let old_build = MrEnclave::from([1; 32]);
+        let new_build = MrEnclave::from([2; 32]);
+        let mut policy = MrEnclavePolicy::new([old_build, old_build]);
+        assert_eq!(policy.allowed(), [old_build]);
+
+        policy.allow(new_build);
+        policy.remove(&old_build);
+        assert_eq!(policy.allowed(), [new_build]);
+        assert_eq!(policy.verify(&old_build).is_failure().unwrap_u8(), 1);
+    }
+
+    #[test]
+    fn empty_mr_enclave_policy_fails() {
+        let policy = MrEnclavePolicy::default();
+        let mr_enclave = MrEnclave::from(REPORT_BODY_SRC.mr_enclave);
+        assert_eq!(policy.verify(&mr_enclave).is_failure().unwrap_u8(), 1);
+    }
+
     #[test]
     fn mr_signer_key_fails() {
         let mut mr_signer = MrSigner::from(REPORT_BODY_SRC.mr_signer);
@@ -686,6 +1491,117 @@ mod test {
         assert_eq!(verifier.verify(&report_data).is_failure().unwrap_u8(), 1);
     }
 
+    #[test]
+    fn report_data_exact() {
+        let mut report_data = ReportData::from(REPORT_BODY_SRC.report_data);
+        let verifier = ReportDataVerifier::exact(report_data.clone());
+        assert_eq!(verifier.verify(&report_data).is_success().unwrap_u8(), 1);
+
+        let bytes: &mut [u8] = report_data.as_mut();
+        bytes[ReportData::SIZE - 1] = 0;
+        assert_eq!(verifier.verify(&report_data).is_failure().unwrap_u8(), 1);
+    }
+
+    #[test]
+    fn report_data_prefix() {
+        let mut report_data = ReportData::from(REPORT_BODY_SRC.report_data);
+        let verifier =
+            ReportDataVerifier::prefix(&REPORT_BODY_SRC.report_data.d[..32]).expect("Prefix fits");
+
+        let bytes: &mut [u8] = report_data.as_mut();
+        bytes[32] = 0;
+        assert_eq!(verifier.verify(&report_data).is_success().unwrap_u8(), 1);
+
+        let bytes: &mut [u8] = report_data.as_mut();
+        bytes[31] = 0;
+        assert_eq!(verifier.verify(&report_data).is_failure().unwrap_u8(), 1);
+    }
+
+    #[test]
+    fn report_data_prefix_too_long() {
+        assert_eq!(
+            ReportDataVerifier::prefix(&[0; ReportData::SIZE + 1]),
+            Err(Error::ReportDataPrefixLength(ReportData::SIZE + 1))
+        );
+    }
+
+    #[test]
+    fn report_data_fn_verifier() {
+        let report_data = ReportData::from(REPORT_BODY_SRC.report_data);
+        let verifier = ReportDataFnVerifier::new("all non zero", |report_data: &ReportData| {
+            let bytes: &[u8] = report_data.as_ref();
+            bytes.iter().all(|byte| *byte != 0)
+        });
+        let verification = verifier.verify(&report_data);
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        assert_eq!(
+            format!("{displayable}"),
+            "- [x] The report data should be all non zero"
+        );
+    }
+
+    #[test]
+    fn report_data_fn_verifier_fails() {
+        let report_data = ReportData::from([0; ReportData::SIZE]);
+        let verifier = ReportDataFnVerifier::new("all non zero", |report_data: &ReportData| {
+            let bytes: &[u8] = report_data.as_ref();
+            bytes.iter().all(|byte| *byte != 0)
+        });
+        assert_eq!(verifier.verify(&report_data).is_failure().unwrap_u8(), 1);
+    }
+
+    fn config_id_starts_with_80(report_body: &ReportBody) -> Result<(), String> {
+        let config_id = report_body.config_id();
+        let bytes: &[u8] = config_id.as_ref();
+        match bytes[0] {
+            80 => Ok(()),
+            other => Err(format!("the CONFIGID started with {other}")),
+        }
+    }
+
+    #[test]
+    fn report_body_fn_verifier() {
+        let report_body = ReportBody::from(&REPORT_BODY_SRC);
+        let verifier = ReportBodyFnVerifier::new("for config 80", config_id_starts_with_80);
+        let verification = verifier.verify(&report_body);
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        assert_eq!(
+            format!("{displayable}"),
+            "- [x] The report body should be for config 80"
+        );
+    }
+
+    #[test]
+    fn report_body_fn_verifier_fails() {
+        let mut report_body_src = REPORT_BODY_SRC;
+        report_body_src.config_id[0] = 2;
+        let report_body = ReportBody::from(&report_body_src);
+        let verifier = ReportBodyFnVerifier::new("for config 80", config_id_starts_with_80);
+        let verification = verifier.verify(&report_body);
+        assert_eq!(verification.is_failure().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        assert_eq!(
+            format!("{displayable}"),
+            "- [ ] The report body should be for config 80, but the CONFIGID started with 2"
+        );
+    }
+
+    #[test]
+    fn report_body_fn_verifier_composes_with_built_in_verifiers() {
+        let report_body = ReportBody::from(&REPORT_BODY_SRC);
+        let verifier = And::new(
+            MrEnclaveVerifier::new(report_body.mr_enclave()),
+            ReportBodyFnVerifier::new("for config 80", config_id_starts_with_80),
+        );
+        let verification = verifier.verify(&report_body);
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+    }
+
     #[test]
     fn cpu_svn_succeeds() {
         let cpu_svn = CpuSvn::from(REPORT_BODY_SRC.cpu_svn);
@@ -1016,4 +1932,80 @@ mod test {
 
         assert_eq!(verifier.to_string(), expected)
     }
+
+    fn kss_policy() -> KssPolicy {
+        KssPolicy {
+            config_id: ConfigId::from(REPORT_BODY_SRC.config_id),
+            min_config_svn: ConfigSvn::from(REPORT_BODY_SRC.config_svn),
+            isv_extended_product_id: ExtendedProductId::from(REPORT_BODY_SRC.isv_ext_prod_id),
+            isv_family_id: FamilyId::from(REPORT_BODY_SRC.isv_family_id),
+        }
+    }
+
+    #[test]
+    fn kss_policy_succeeds() {
+        let report_body = ReportBody::from(&REPORT_BODY_SRC);
+        let policy = kss_policy();
+
+        assert_eq!(policy.verify(&report_body).is_success().unwrap_u8(), 1);
+    }
+
+    #[test]
+    fn kss_policy_accepts_higher_config_svn() {
+        let mut report_body_src = REPORT_BODY_SRC;
+        report_body_src.config_svn += 1;
+        let report_body = ReportBody::from(&report_body_src);
+
+        assert_eq!(
+            kss_policy().verify(&report_body).is_success().unwrap_u8(),
+            1
+        );
+    }
+
+    #[test]
+    fn kss_policy_fails_for_lower_config_svn() {
+        let mut report_body_src = REPORT_BODY_SRC;
+        report_body_src.config_svn -= 1;
+        let report_body = ReportBody::from(&report_body_src);
+        let policy = kss_policy();
+        let verification = policy.verify(&report_body);
+
+        assert_eq!(verification.is_failure().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&policy, verification);
+        let lines = displayable.to_string();
+        let lines = lines.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[0],
+            "- [ ] KSS identity all of the following must be true:"
+        );
+        assert!(lines[1].starts_with("  - [x] The config ID should be"));
+        assert_eq!(
+            lines[2],
+            "  - [ ] The config SVN should be at least 146, but the actual config SVN was 145"
+        );
+        assert!(lines[3].starts_with("  - [x] The extended product ID should be"));
+        assert!(lines[4].starts_with("  - [x] The family ID should be"));
+    }
+
+    #[parameterized(
+        config_id = { 0 },
+        extended_product_id = { 1 },
+        family_id = { 2 },
+    )]
+    fn kss_policy_fails_for_different_id(field: usize) {
+        let mut report_body_src = REPORT_BODY_SRC;
+        match field {
+            0 => report_body_src.config_id[0] ^= 1,
+            1 => report_body_src.isv_ext_prod_id[0] ^= 1,
+            _ => report_body_src.isv_family_id[0] ^= 1,
+        }
+        let report_body = ReportBody::from(&report_body_src);
+
+        assert_eq!(
+            kss_policy().verify(&report_body).is_failure().unwrap_u8(),
+            1
+        );
+    }
 }
@@ -0,0 +1,160 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Retrying of collateral fetches which fail transiently.
+//!
+//! The PCS, and a PCCS, rate limit requests and have the occasional outage.
+//! Without retrying, a blip in the service fails the verification of every
+//! quote which needed collateral at the time. Requests which fail with a
+//! transport error, HTTP 429, or a 5xx status are retried with exponential
+//! backoff, other 4xx statuses are not since the request won't succeed.
+
+use core::time::Duration;
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// How the collateral clients retry requests which fail transiently.
+///
+/// The delay before a retry doubles with each retry, up to the maximum
+/// backoff. A random jitter of up to half of the delay is subtracted, so
+/// clients which failed together don't retry together.
+///
+/// ```
+/// use core::time::Duration;
+/// use mc_attestation_verifier::{PcsClient, RetryPolicy};
+///
+/// let client = PcsClient::new().with_retry_policy(
+///     RetryPolicy::new()
+///         .with_max_retries(5)
+///         .with_initial_backoff(Duration::from_millis(200)),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Create the default policy, 3 retries with a backoff starting at 1
+    /// second and capped at 30 seconds
+    pub fn new() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+
+    /// A policy which never retries
+    pub fn none() -> Self {
+        Self::new().with_max_retries(0)
+    }
+
+    /// Retry a request at most `max_retries` times
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Wait `initial_backoff` before the first retry
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Wait at most `max_backoff` between retries
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// The maximum number of retries of a request
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// The wait before the first retry, without jitter
+    pub fn initial_backoff(&self) -> Duration {
+        self.initial_backoff
+    }
+
+    /// The maximum wait between retries, without jitter
+    pub fn max_backoff(&self) -> Duration {
+        self.max_backoff
+    }
+
+    /// The delay before the `retry`, counting from 0, or `None` when there
+    /// are no retries left
+    pub(crate) fn delay(&self, retry: u32) -> Option<Duration> {
+        (retry < self.max_retries).then(|| self.backoff(retry, random()))
+    }
+
+    /// The delay before the `retry` with the `random` jitter
+    fn backoff(&self, retry: u32, random: u64) -> Duration {
+        let backoff = 2u32
+            .checked_pow(retry)
+            .and_then(|factor| self.initial_backoff.checked_mul(factor))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        let jitter = (backoff / 2).mul_f64(random as f64 / u64::MAX as f64);
+        backoff - jitter
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A random number for the jitter, it needn't be cryptographically secure
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles() {
+        let policy = RetryPolicy::new();
+
+        assert_eq!(policy.backoff(0, 0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(1, 0), Duration::from_secs(2));
+        assert_eq!(policy.backoff(2, 0), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let policy = RetryPolicy::new().with_max_backoff(Duration::from_secs(5));
+
+        assert_eq!(policy.backoff(3, 0), Duration::from_secs(5));
+        assert_eq!(policy.backoff(40, 0), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn jitter_is_up_to_half_the_backoff() {
+        let policy = RetryPolicy::new().with_initial_backoff(Duration::from_secs(4));
+
+        assert_eq!(policy.backoff(0, u64::MAX), Duration::from_secs(2));
+        assert_eq!(policy.backoff(0, u64::MAX / 2), Duration::from_secs(3));
+        for _ in 0..100 {
+            let delay = policy.delay(0).expect("Should retry");
+            assert!(delay >= Duration::from_secs(2));
+            assert!(delay <= Duration::from_secs(4));
+        }
+    }
+
+    #[test]
+    fn no_delay_after_the_last_retry() {
+        let policy = RetryPolicy::new().with_max_retries(2);
+
+        assert!(policy.delay(1).is_some());
+        assert_eq!(policy.delay(2), None);
+        assert_eq!(RetryPolicy::none().delay(0), None);
+    }
+}
@@ -0,0 +1,393 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Periodic re-verification of attested sessions.
+//!
+//! A session is established after verifying the evidence of a peer, but the
+//! collateral it was verified with keeps changing. A CRL update may revoke the
+//! platform's PCK certificate, or a TCB recovery may move the platform's TCB
+//! level to out of date. The [`ReverificationTask`] re-verifies the evidence
+//! of each session with fresh collateral and notifies when the outcome
+//! changes, so the session can be torn down.
+
+use crate::{
+    worker::{Job, Step, Workers},
+    Collateral, Fmspc,
+};
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::{fmt::Display, time::Duration};
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    time::Instant,
+};
+
+/// The outcome of the most recent verification of a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SessionState {
+    /// The session has not been verified yet
+    Pending,
+    /// The evidence of the session verified successfully
+    Trusted,
+    /// The evidence of the session failed to verify
+    Untrusted,
+}
+
+/// A change of the [`SessionState`] of a session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateChange<K> {
+    /// The ID of the session
+    pub session: K,
+    /// The state before the verification
+    pub previous: SessionState,
+    /// The state after the verification
+    pub current: SessionState,
+}
+
+#[derive(Debug)]
+struct Session<S> {
+    fmspc: Fmspc,
+    session: Arc<S>,
+    state: SessionState,
+}
+
+#[derive(Debug)]
+struct State<K, S> {
+    sessions: BTreeMap<K, Session<S>>,
+    // The most recent error fetching the collateral of each FMSPC
+    errors: BTreeMap<Fmspc, String>,
+    verify_now: bool,
+    next_round: Instant,
+}
+
+/// Re-verifies attested sessions on a background thread.
+///
+/// Sessions are added with [`ReverificationTask::add_session()`]. A new
+/// session is verified right away, after that all sessions are re-verified
+/// every `interval`. The collateral is fetched once per FMSPC for each round,
+/// so a [`CollateralPrefetcher`](crate::CollateralPrefetcher) can back the
+/// fetch to avoid going to the PCS.
+///
+/// When the collateral for an FMSPC can not be fetched, the state of its
+/// sessions is left as is until the next round. The error is available from
+/// [`ReverificationTask::error()`].
+///
+/// The background thread is stopped when the task is dropped.
+#[derive(Debug)]
+pub struct ReverificationTask<K, S> {
+    workers: Workers<State<K, S>>,
+}
+
+impl<K, S> ReverificationTask<K, S>
+where
+    K: Ord + Clone + Send + 'static,
+    S: Send + Sync + 'static,
+{
+    /// Spawn the background thread of the task.
+    ///
+    /// Returns the task and the receiver of the [`StateChange`]
+    /// notifications. Dropping the receiver does not stop the task.
+    ///
+    /// # Arguments
+    /// * `fetch` - Fetches the collateral for an FMSPC, once for each round
+    ///   with sessions on the platform.
+    /// * `verify` - Verifies the evidence of a session with the collateral,
+    ///   returning whether it succeeded. This is usually an
+    ///   [`EvidenceVerifier`](crate::EvidenceVerifier) for the current time.
+    /// * `interval` - How long to wait between rounds of re-verification.
+    pub fn spawn<F, E, V>(
+        fetch: F,
+        verify: V,
+        interval: Duration,
+    ) -> (Self, Receiver<StateChange<K>>)
+    where
+        F: Fn(&Fmspc) -> Result<Collateral, E> + Send + 'static,
+        E: Display,
+        V: Fn(&S, &Collateral) -> bool + Send + 'static,
+    {
+        let state = State {
+            sessions: BTreeMap::new(),
+            errors: BTreeMap::new(),
+            verify_now: false,
+            next_round: Instant::now() + interval,
+        };
+        let (notifications, receiver) = mpsc::channel();
+        let job = Reverify {
+            fetch: Box::new(move |fmspc: &Fmspc| fetch(fmspc).map_err(|e| e.to_string())),
+            verify: Box::new(verify),
+            interval,
+            notifications,
+        };
+        let task = Self {
+            workers: Workers::spawn(state, [job]),
+        };
+        (task, receiver)
+    }
+
+    /// Add the `session`, identified by `id`, for a platform with the
+    /// `fmspc`.
+    ///
+    /// The session starts out [`SessionState::Pending`] and is verified right
+    /// away. An existing session with the same `id` is replaced.
+    pub fn add_session(&self, id: K, fmspc: Fmspc, session: S) {
+        let mut state = self.workers.lock();
+        state.sessions.insert(
+            id,
+            Session {
+                fmspc,
+                session: Arc::new(session),
+                state: SessionState::Pending,
+            },
+        );
+        state.verify_now = true;
+        self.workers.wake();
+    }
+
+    /// Remove the session `id`, it will no longer be re-verified.
+    ///
+    /// Returns the state of the session, if it existed.
+    pub fn remove_session(&self, id: &K) -> Option<SessionState> {
+        let mut state = self.workers.lock();
+        state.sessions.remove(id).map(|session| session.state)
+    }
+
+    /// The current state of the session `id`
+    pub fn state(&self, id: &K) -> Option<SessionState> {
+        let state = self.workers.lock();
+        state.sessions.get(id).map(|session| session.state)
+    }
+
+    /// The IDs of the sessions
+    pub fn sessions(&self) -> Vec<K> {
+        let state = self.workers.lock();
+        state.sessions.keys().cloned().collect()
+    }
+
+    /// The error fetching the collateral for the `fmspc` in the latest
+    /// round, if it failed.
+    pub fn error(&self, fmspc: &Fmspc) -> Option<String> {
+        let state = self.workers.lock();
+        state.errors.get(fmspc).cloned()
+    }
+
+    /// Re-verify all of the sessions now, instead of waiting for the interval.
+    ///
+    /// Useful when a CRL update is known to have been published.
+    pub fn verify_now(&self) {
+        self.workers.lock().verify_now = true;
+        self.workers.wake();
+    }
+}
+
+type Fetch = dyn Fn(&Fmspc) -> Result<Collateral, String> + Send;
+type Verify<S> = dyn Fn(&S, &Collateral) -> bool + Send;
+
+/// Verifies all of the sessions, once every interval or when asked to
+struct Reverify<K, S> {
+    fetch: Box<Fetch>,
+    verify: Box<Verify<S>>,
+    interval: Duration,
+    notifications: Sender<StateChange<K>>,
+}
+
+type Round<K, S> = BTreeMap<Fmspc, Vec<(K, Arc<S>)>>;
+
+impl<K, S> Job for Reverify<K, S>
+where
+    K: Ord + Clone + Send + 'static,
+    S: Send + Sync + 'static,
+{
+    type State = State<K, S>;
+    type Work = Round<K, S>;
+    // The outcome of each verified session and the fetch error of each FMSPC
+    type Outcome = (Vec<(K, bool)>, Vec<(Fmspc, Option<String>)>);
+
+    fn next(&mut self, state: &mut State<K, S>) -> Step<Round<K, S>> {
+        let now = Instant::now();
+        if !state.verify_now && now < state.next_round {
+            return Step::WaitFor(state.next_round - now);
+        }
+        state.verify_now = false;
+        state.next_round = now + self.interval;
+
+        let mut round = Round::new();
+        for (id, session) in &state.sessions {
+            round
+                .entry(session.fmspc)
+                .or_default()
+                .push((id.clone(), session.session.clone()));
+        }
+        Step::Run(round)
+    }
+
+    fn run(&mut self, round: Round<K, S>) -> Self::Outcome {
+        let mut outcomes = Vec::new();
+        let mut errors = Vec::new();
+        for (fmspc, sessions) in round {
+            match (self.fetch)(&fmspc) {
+                Ok(collateral) => {
+                    for (id, session) in sessions {
+                        outcomes.push((id, (self.verify)(&session, &collateral)));
+                    }
+                    errors.push((fmspc, None));
+                }
+                Err(error) => errors.push((fmspc, Some(error))),
+            }
+        }
+        (outcomes, errors)
+    }
+
+    fn finish(&mut self, state: &mut State<K, S>, (outcomes, errors): Self::Outcome) {
+        for (fmspc, error) in errors {
+            match error {
+                Some(error) => state.errors.insert(fmspc, error),
+                None => state.errors.remove(&fmspc),
+            };
+        }
+        for (id, succeeded) in outcomes {
+            // The session may have been removed while it was verified
+            let Some(session) = state.sessions.get_mut(&id) else {
+                continue;
+            };
+            let current = if succeeded {
+                SessionState::Trusted
+            } else {
+                SessionState::Untrusted
+            };
+            if session.state != current {
+                let change = StateChange {
+                    session: id,
+                    previous: session.state,
+                    current,
+                };
+                session.state = current;
+                // Nobody listening is not a reason to stop re-verifying
+                let _ = self.notifications.send(change);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::evidence::{
+        self,
+        test::{QE_IDENTITY_JSON, TCB_INFO_JSON},
+    };
+    use core::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    const FMSPC: Fmspc = Fmspc::new([0x00, 0x90, 0x6E, 0xD5, 0x00, 0x00]);
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn collateral() -> Collateral {
+        evidence::test::collateral(TCB_INFO_JSON, QE_IDENTITY_JSON)
+    }
+
+    #[test]
+    fn new_session_is_verified() {
+        let (task, notifications) = ReverificationTask::spawn(
+            |_: &Fmspc| Ok::<_, String>(collateral()),
+            |trusted: &bool, _: &Collateral| *trusted,
+            Duration::from_secs(3600),
+        );
+        task.add_session(1, FMSPC, true);
+
+        let change = notifications
+            .recv_timeout(TIMEOUT)
+            .expect("No state change");
+
+        assert_eq!(
+            change,
+            StateChange {
+                session: 1,
+                previous: SessionState::Pending,
+                current: SessionState::Trusted,
+            }
+        );
+        assert_eq!(task.state(&1), Some(SessionState::Trusted));
+        assert_eq!(task.sessions(), [1]);
+    }
+
+    #[test]
+    fn revoked_platform_is_notified() {
+        let revoked = Arc::new(AtomicBool::new(false));
+        let crl_update = revoked.clone();
+        let (task, notifications) = ReverificationTask::spawn(
+            |_: &Fmspc| Ok::<_, String>(collateral()),
+            move |_: &(), _: &Collateral| !crl_update.load(Ordering::SeqCst),
+            Duration::from_millis(10),
+        );
+        task.add_session("peer", FMSPC, ());
+        let change = notifications
+            .recv_timeout(TIMEOUT)
+            .expect("No state change");
+        assert_eq!(change.current, SessionState::Trusted);
+
+        revoked.store(true, Ordering::SeqCst);
+        let change = notifications
+            .recv_timeout(TIMEOUT)
+            .expect("No state change");
+
+        assert_eq!(
+            change,
+            StateChange {
+                session: "peer",
+                previous: SessionState::Trusted,
+                current: SessionState::Untrusted,
+            }
+        );
+    }
+
+    #[test]
+    fn failed_fetch_leaves_state() {
+        let (task, notifications) = ReverificationTask::spawn(
+            |_: &Fmspc| Err("PCS unavailable"),
+            |_: &(), _: &Collateral| true,
+            Duration::from_secs(3600),
+        );
+        task.add_session(1, FMSPC, ());
+
+        let start = Instant::now();
+        while task.error(&FMSPC).is_none() {
+            assert!(start.elapsed() < TIMEOUT, "Timed out waiting for fetch");
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(task.error(&FMSPC).as_deref(), Some("PCS unavailable"));
+        assert_eq!(task.state(&1), Some(SessionState::Pending));
+        assert!(notifications.try_recv().is_err());
+    }
+
+    #[test]
+    fn removed_session_is_not_verified() {
+        let (task, _notifications) = ReverificationTask::spawn(
+            |_: &Fmspc| Ok::<_, String>(collateral()),
+            |_: &(), _: &Collateral| true,
+            Duration::from_secs(3600),
+        );
+        task.add_session(1, FMSPC, ());
+        task.remove_session(&1);
+
+        assert_eq!(task.state(&1), None);
+        assert!(task.sessions().is_empty());
+    }
+
+    #[test]
+    fn drop_stops_worker() {
+        let (task, notifications) = ReverificationTask::spawn(
+            |_: &Fmspc| Err("PCS unavailable"),
+            |_: &(), _: &Collateral| true,
+            Duration::from_millis(10),
+        );
+        task.add_session(1, FMSPC, ());
+        drop(task);
+        // The sender is dropped with the worker
+        assert!(notifications.recv_timeout(TIMEOUT).is_err());
+    }
+}
@@ -0,0 +1,488 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Supplemental data of verified evidence, in the shape of the
+//! `sgx_ql_qv_supplemental_t` of Intel's Quote Verification Library (QVL).
+//!
+//! Systems migrating from the QVL can compare the outputs of the two
+//! libraries, and keep providing the same fields to their consumers. See
+//! `sgx_qve_header.h` of
+//! <https://github.com/intel/SGXDataCenterAttestationPrimitives>
+
+use crate::{
+    platform_configuration::sgx_extensions, Accessor, Advisories, Error, Evidence, PceId,
+    PlatformConfiguration, Ppid, TcbInfo, TeeType,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use der::{asn1::ObjectIdentifier, DateTime, Decode, Tag, Tagged};
+use mc_sgx_core_types::CpuSvn;
+use mc_sgx_dcap_types::TcbInfo as PckTcb;
+use sha2::{Digest, Sha384};
+use x509_cert::{crl::CertificateList, Certificate};
+
+/// The major version of `sgx_ql_qv_supplemental_t` which
+/// [`SupplementalData`] corresponds to
+pub const SUPPLEMENTAL_DATA_VERSION: u16 = 3;
+
+/// Size of the root key ID, a SHA-384 digest
+pub const ROOT_KEY_ID_SIZE: usize = 48;
+
+/// Size of the platform instance ID
+pub const PLATFORM_INSTANCE_ID_SIZE: usize = 16;
+
+/// The CRL number extension of a CRL, see section 5.2.3 of RFC 5280
+const CRL_NUMBER_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.20");
+
+/// The entries of the SGX extensions of a PCK certificate, see section 1.3.5
+/// of
+/// <https://api.trustedservices.intel.com/documents/Intel_SGX_PCK_Certificate_CRL_Spec-1.5.pdf>
+const PPID_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.1");
+const PCE_ID_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.3");
+const SGX_TYPE_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.5");
+const PLATFORM_INSTANCE_ID_OID: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.6");
+
+/// The type of SGX of the platform, from the PCK certificate
+#[derive(displaydoc::Display, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SgxType {
+    /// Standard
+    Standard,
+    /// Scalable
+    Scalable,
+    /// Scalable with integrity
+    ScalableWithIntegrity,
+}
+
+impl SgxType {
+    /// The raw value of the SGX type, as in the PCK certificate and
+    /// `sgx_ql_qv_supplemental_t`
+    pub fn value(&self) -> u8 {
+        match self {
+            SgxType::Standard => 0,
+            SgxType::Scalable => 1,
+            SgxType::ScalableWithIntegrity => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for SgxType {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        [
+            SgxType::Standard,
+            SgxType::Scalable,
+            SgxType::ScalableWithIntegrity,
+        ]
+        .into_iter()
+        .find(|sgx_type| sgx_type.value() == value)
+        .ok_or_else(|| Error::SupplementalData(format!("unknown SGX type {value}")))
+    }
+}
+
+/// Supplemental data of verified [`Evidence`], mirroring
+/// `sgx_ql_qv_supplemental_t`.
+///
+/// This is derived from the evidence, it is only meaningful once the evidence
+/// has been verified.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SupplementalData {
+    earliest_issue_date: DateTime,
+    latest_issue_date: DateTime,
+    earliest_expiration_date: DateTime,
+    tcb_level_date_tag: DateTime,
+    pck_crl_num: u32,
+    root_ca_crl_num: u32,
+    tcb_eval_ref_num: u32,
+    root_key_id: [u8; ROOT_KEY_ID_SIZE],
+    pck_ppid: Ppid,
+    tcb_cpu_svn: CpuSvn,
+    tcb_pce_isv_svn: u16,
+    pce_id: PceId,
+    tee_type: TeeType,
+    sgx_type: SgxType,
+    platform_instance_id: Option<[u8; PLATFORM_INSTANCE_ID_SIZE]>,
+    platform_configuration: PlatformConfiguration,
+    advisory_ids: Vec<String>,
+}
+
+impl SupplementalData {
+    /// The major version of `sgx_ql_qv_supplemental_t` this corresponds to
+    pub fn version(&self) -> u16 {
+        SUPPLEMENTAL_DATA_VERSION
+    }
+
+    /// The earliest issue date of the collateral and certificates,
+    /// `earliest_issue_date`
+    pub fn earliest_issue_date(&self) -> DateTime {
+        self.earliest_issue_date
+    }
+
+    /// The latest issue date of the collateral and certificates,
+    /// `latest_issue_date`
+    pub fn latest_issue_date(&self) -> DateTime {
+        self.latest_issue_date
+    }
+
+    /// The earliest expiration date of the collateral and certificates,
+    /// `earliest_expiration_date`
+    pub fn earliest_expiration_date(&self) -> DateTime {
+        self.earliest_expiration_date
+    }
+
+    /// The date of the platform's TCB level in the TCB info,
+    /// `tcb_level_date_tag`
+    pub fn tcb_level_date_tag(&self) -> DateTime {
+        self.tcb_level_date_tag
+    }
+
+    /// The CRL number of the PCK CRL, `pck_crl_num`
+    pub fn pck_crl_num(&self) -> u32 {
+        self.pck_crl_num
+    }
+
+    /// The CRL number of the root CA CRL, `root_ca_crl_num`
+    pub fn root_ca_crl_num(&self) -> u32 {
+        self.root_ca_crl_num
+    }
+
+    /// The lower TCB evaluation data number of the TCB info and the QE
+    /// identity, `tcb_eval_ref_num`
+    pub fn tcb_eval_ref_num(&self) -> u32 {
+        self.tcb_eval_ref_num
+    }
+
+    /// The SHA-384 digest of the root CA's public key, `root_key_id`
+    pub fn root_key_id(&self) -> &[u8; ROOT_KEY_ID_SIZE] {
+        &self.root_key_id
+    }
+
+    /// The PPID from the PCK certificate, `pck_ppid`
+    pub fn pck_ppid(&self) -> Ppid {
+        self.pck_ppid
+    }
+
+    /// The CPU SVN from the PCK certificate, `tcb_cpusvn`
+    pub fn tcb_cpu_svn(&self) -> &CpuSvn {
+        &self.tcb_cpu_svn
+    }
+
+    /// The PCE SVN from the PCK certificate, `tcb_pce_isvsvn`
+    pub fn tcb_pce_isv_svn(&self) -> u16 {
+        self.tcb_pce_isv_svn
+    }
+
+    /// The PCE ID from the PCK certificate, `pce_id`
+    pub fn pce_id(&self) -> PceId {
+        self.pce_id
+    }
+
+    /// The TEE type of the quote, `tee_type`
+    pub fn tee_type(&self) -> TeeType {
+        self.tee_type
+    }
+
+    /// The SGX type from the PCK certificate, `sgx_type`
+    pub fn sgx_type(&self) -> SgxType {
+        self.sgx_type
+    }
+
+    /// The platform instance ID from the PCK certificate,
+    /// `platform_instance_id`.
+    ///
+    /// Only present for multi-package platforms.
+    pub fn platform_instance_id(&self) -> Option<&[u8; PLATFORM_INSTANCE_ID_SIZE]> {
+        self.platform_instance_id.as_ref()
+    }
+
+    /// The configuration of the platform from the PCK certificate,
+    /// `dynamic_platform`, `cached_keys`, and `smt_enabled`
+    pub fn platform_configuration(&self) -> PlatformConfiguration {
+        self.platform_configuration
+    }
+
+    /// The IDs of the advisories which apply to the platform and the QE
+    pub fn advisory_ids(&self) -> &[String] {
+        &self.advisory_ids
+    }
+
+    /// The advisory IDs as a comma separated list, `sa_list`
+    pub fn sa_list(&self) -> String {
+        self.advisory_ids.join(",")
+    }
+}
+
+impl<Q> TryFrom<&Evidence<Q>> for SupplementalData {
+    type Error = Error;
+
+    /// Derive the supplemental data of the `evidence`.
+    ///
+    /// # Errors
+    /// * `Error::SupplementalData` if the PCK certificate is missing any of
+    ///   the SGX extensions, or a CRL is missing its CRL number.
+    /// * `Error::Der` if any of the dates, or the SGX extensions, can not be
+    ///   decoded.
+    /// * The errors of [`TcbInfo::tcb_level()`].
+    fn try_from(evidence: &Evidence<Q>) -> Result<Self, Self::Error> {
+        let collateral = evidence.collateral();
+        let pck_certificate_chain = evidence.pck_certificate_chain();
+        let pck_leaf = pck_certificate_chain.first().ok_or_else(|| {
+            Error::SupplementalData("the PCK certificate chain is empty".to_string())
+        })?;
+
+        let tcb_info_issue_date = collateral.tcb_info().issue_date()?;
+        let mut issue_dates = Vec::from([
+            collateral.qe_identity().issue_date()?,
+            collateral
+                .root_ca_crl()
+                .tbs_cert_list
+                .this_update
+                .to_date_time(),
+            collateral
+                .pck_crl()
+                .tbs_cert_list
+                .this_update
+                .to_date_time(),
+        ]);
+        let certificates = collateral
+            .pck_crl_issuer_chain()
+            .iter()
+            .chain(collateral.tcb_info_issuer_chain())
+            .chain(collateral.qe_identity_issuer_chain())
+            .chain(pck_certificate_chain);
+        for certificate in certificates {
+            let validity = &certificate.tbs_certificate.validity;
+            issue_dates.push(validity.not_before.to_date_time());
+        }
+        let earliest_expiration_date = evidence.expiration()?.0;
+        let earliest_issue_date = issue_dates
+            .iter()
+            .copied()
+            .fold(tcb_info_issue_date, DateTime::min);
+        let latest_issue_date = issue_dates
+            .iter()
+            .copied()
+            .fold(tcb_info_issue_date, DateTime::max);
+
+        let pck_tcb = PckTcb::try_from(pck_leaf)?;
+        let tcb_info: &TcbInfo = collateral.tcb_info();
+        let tcb_level_date_tag = tcb_info.tcb_level(&pck_tcb)?.tcb_date()?;
+        let mut cpu_svn = [0u8; 16];
+        for (byte, svn) in cpu_svn.iter_mut().zip(pck_tcb.svns()) {
+            *byte = *svn as u8;
+        }
+
+        let root_ca = collateral
+            .pck_crl_issuer_chain()
+            .last()
+            .ok_or_else(|| Error::SupplementalData("the root CA is missing".to_string()))?;
+
+        let pck_extensions = PckExtensions::try_from(pck_leaf)?;
+        let advisories: Advisories = evidence.get();
+
+        Ok(Self {
+            earliest_issue_date,
+            latest_issue_date,
+            earliest_expiration_date,
+            tcb_level_date_tag,
+            pck_crl_num: crl_number(collateral.pck_crl(), "PCK CRL")?,
+            root_ca_crl_num: crl_number(collateral.root_ca_crl(), "root CA CRL")?,
+            tcb_eval_ref_num: tcb_info
+                .tcb_evaluation_data_number()
+                .min(collateral.qe_identity().tcb_evaluation_data_number()),
+            root_key_id: root_key_id(root_ca),
+            pck_ppid: pck_extensions.ppid,
+            tcb_cpu_svn: CpuSvn::from(cpu_svn),
+            tcb_pce_isv_svn: *pck_tcb.pce_svn() as u16,
+            pce_id: pck_extensions.pce_id,
+            // `Evidence` only holds SGX quotes
+            tee_type: TeeType::Sgx,
+            sgx_type: pck_extensions.sgx_type,
+            platform_instance_id: pck_extensions.platform_instance_id,
+            platform_configuration: evidence.get(),
+            advisory_ids: advisories.ids().map(ToString::to_string).collect(),
+        })
+    }
+}
+
+/// The values of the SGX extensions of a PCK certificate which are not
+/// covered by [`PckTcb`] or [`PlatformConfiguration`]
+#[derive(Debug)]
+struct PckExtensions {
+    ppid: Ppid,
+    pce_id: PceId,
+    sgx_type: SgxType,
+    platform_instance_id: Option<[u8; PLATFORM_INSTANCE_ID_SIZE]>,
+}
+
+impl TryFrom<&Certificate> for PckExtensions {
+    type Error = Error;
+
+    fn try_from(certificate: &Certificate) -> Result<Self, Self::Error> {
+        let mut ppid = None;
+        let mut pce_id = None;
+        let mut sgx_type = None;
+        let mut platform_instance_id = None;
+        for extension in sgx_extensions(certificate)? {
+            match extension.id {
+                PPID_OID => ppid = Some(Ppid::try_from(extension.value.value())?),
+                PCE_ID_OID => pce_id = Some(PceId::try_from(extension.value.value())?),
+                SGX_TYPE_OID => {
+                    extension.value.tag().assert_eq(Tag::Enumerated)?;
+                    let value = match extension.value.value() {
+                        [value] => *value,
+                        _ => return Err(missing("SGX type")),
+                    };
+                    sgx_type = Some(SgxType::try_from(value)?);
+                }
+                PLATFORM_INSTANCE_ID_OID => {
+                    let bytes = extension.value.value();
+                    platform_instance_id = Some(
+                        bytes
+                            .try_into()
+                            .map_err(|_| missing("platform instance ID"))?,
+                    );
+                }
+                _ => {}
+            }
+        }
+        Ok(Self {
+            ppid: ppid.ok_or_else(|| missing("PPID"))?,
+            pce_id: pce_id.ok_or_else(|| missing("PCE ID"))?,
+            sgx_type: sgx_type.ok_or_else(|| missing("SGX type"))?,
+            platform_instance_id,
+        })
+    }
+}
+
+fn missing(item: &str) -> Error {
+    Error::SupplementalData(format!("the PCK certificate has no valid {item}"))
+}
+
+/// The CRL number of `crl`, `name` is used for the error
+fn crl_number(crl: &CertificateList, name: &str) -> Result<u32, Error> {
+    let extension = crl
+        .tbs_cert_list
+        .crl_extensions
+        .iter()
+        .flatten()
+        .find(|extension| extension.extn_id == CRL_NUMBER_OID)
+        .ok_or_else(|| Error::SupplementalData(format!("the {name} has no CRL number")))?;
+    Ok(u32::from_der(extension.extn_value.as_bytes())?)
+}
+
+/// The SHA-384 digest of the SEC1 encoded public key of the `root_ca`
+fn root_key_id(root_ca: &Certificate) -> [u8; ROOT_KEY_ID_SIZE] {
+    let key = root_ca
+        .tbs_certificate
+        .subject_public_key_info
+        .subject_public_key
+        .raw_bytes();
+    Sha384::digest(key).into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_matches::assert_matches;
+    use der::DecodePem;
+    use yare::parameterized;
+
+    const LEAF_CERT: &str = include_str!("../data/tests/leaf_cert.pem");
+    const ROOT_CA: &str = include_str!("../data/tests/root_ca.pem");
+
+    #[parameterized(
+        standard = { 0, SgxType::Standard },
+        scalable = { 1, SgxType::Scalable },
+        scalable_with_integrity = { 2, SgxType::ScalableWithIntegrity },
+    )]
+    fn sgx_type_from_value(value: u8, expected: SgxType) {
+        assert_eq!(SgxType::try_from(value), Ok(expected));
+        assert_eq!(expected.value(), value);
+    }
+
+    #[test]
+    fn unknown_sgx_type() {
+        assert_matches!(SgxType::try_from(3), Err(Error::SupplementalData(_)));
+    }
+
+    #[test]
+    fn pck_extensions_from_leaf_cert() {
+        let certificate = Certificate::from_pem(LEAF_CERT).expect("Failed to parse certificate");
+        let extensions =
+            PckExtensions::try_from(&certificate).expect("Failed to get SGX extensions");
+
+        assert_eq!(
+            extensions.ppid,
+            Ppid::try_from_hex("dfa1509779ed52bdca5306c416f9911b").expect("Failed to parse PPID")
+        );
+        assert_eq!(extensions.pce_id, PceId::new([0, 0]));
+        assert_eq!(extensions.sgx_type, SgxType::Standard);
+        assert_eq!(extensions.platform_instance_id, None);
+    }
+
+    #[test]
+    fn pck_extensions_missing() {
+        let certificate = Certificate::from_pem(ROOT_CA).expect("Failed to parse certificate");
+        assert_matches!(
+            PckExtensions::try_from(&certificate),
+            Err(Error::SupplementalData(message)) if message.contains("PPID")
+        );
+    }
+
+    #[parameterized(
+        pck_crl = { include_bytes!("../data/tests/processor_crl.der") },
+        root_ca_crl = { include_bytes!("../data/tests/root_crl.der") },
+    )]
+    fn crl_numbers(der: &[u8]) {
+        let crl = CertificateList::from_der(der).expect("Failed to parse CRL");
+        assert_eq!(crl_number(&crl, "CRL"), Ok(1));
+    }
+
+    #[test]
+    fn root_key_id_is_sha384_of_public_key() {
+        let root_ca = Certificate::from_pem(ROOT_CA).expect("Failed to parse certificate");
+        let expected = hex::decode(
+            "46e403bd34f05a3f2817ab9badcaacc7ffc98e0f261008cd30dae936cace18d5dcf58eef31463613de1570d516200993",
+        )
+        .expect("Failed to decode hex");
+        assert_eq!(root_key_id(&root_ca).as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn sa_list_is_comma_separated() {
+        let mut supplemental = supplemental_data();
+        supplemental.advisory_ids =
+            Vec::from(["INTEL-SA-00334".to_string(), "INTEL-SA-00615".to_string()]);
+        assert_eq!(supplemental.sa_list(), "INTEL-SA-00334,INTEL-SA-00615");
+
+        supplemental.advisory_ids.clear();
+        assert_eq!(supplemental.sa_list(), "");
+    }
+
+    fn supplemental_data() -> SupplementalData {
+        let date = DateTime::new(2023, 7, 12, 0, 0, 0).expect("Failed to create time");
+        SupplementalData {
+            earliest_issue_date: date,
+            latest_issue_date: date,
+            earliest_expiration_date: date,
+            tcb_level_date_tag: date,
+            pck_crl_num: 1,
+            root_ca_crl_num: 1,
+            tcb_eval_ref_num: 15,
+            root_key_id: [0; ROOT_KEY_ID_SIZE],
+            pck_ppid: Ppid::new([0; 16]),
+            tcb_cpu_svn: CpuSvn::from([0; 16]),
+            tcb_pce_isv_svn: 13,
+            pce_id: PceId::new([0; 2]),
+            tee_type: TeeType::Sgx,
+            sgx_type: SgxType::Standard,
+            platform_instance_id: None,
+            platform_configuration: PlatformConfiguration::default(),
+            advisory_ids: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Building the [`TargetInfo`] of an enclave
+//!
+//! `EREPORT` MACs a report for the enclave described by a target info, see
+//! [`LocalReport`](crate::LocalReport). An attester needs the target info of
+//! the verifying enclave, which can be derived from one of its report bodies,
+//! or for the quoting enclave, from its QE identity.
+
+use crate::QeIdentity;
+use mc_sgx_core_sys_types::sgx_target_info_t;
+pub use mc_sgx_core_types::TargetInfo;
+use mc_sgx_core_types::{
+    Attributes, ConfigId, ConfigSvn, MiscellaneousSelect, MrEnclave, ReportBody,
+};
+
+/// Builder for the [`TargetInfo`] of an enclave
+///
+/// Anything not set is zero, which is what the SDK uses for an enclave without
+/// KSS.
+///
+/// ```
+/// use mc_attestation_verifier::{MrEnclave, TargetInfoBuilder};
+///
+/// let target_info = TargetInfoBuilder::new(MrEnclave::from([0xAB; 32])).build();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TargetInfoBuilder {
+    mr_enclave: MrEnclave,
+    attributes: Attributes,
+    config_svn: ConfigSvn,
+    miscellaneous_select: MiscellaneousSelect,
+    config_id: ConfigId,
+}
+
+impl TargetInfoBuilder {
+    /// Start a target info for the enclave measured as `mr_enclave`
+    pub fn new(mr_enclave: MrEnclave) -> Self {
+        Self {
+            mr_enclave,
+            attributes: Attributes::default(),
+            config_svn: ConfigSvn::default(),
+            miscellaneous_select: MiscellaneousSelect::default(),
+            config_id: ConfigId::default(),
+        }
+    }
+
+    /// Start a target info for the quoting enclave measured as `mr_enclave`
+    ///
+    /// The QE identity does not have the MRENCLAVE of the quoting enclave,
+    /// only its attributes and MISCSELECT. Those are the values expected
+    /// after applying the masks of the identity, so they must be the full
+    /// values of the quoting enclave for the report key to match.
+    pub fn from_qe_identity(qe_identity: &QeIdentity, mr_enclave: MrEnclave) -> Self {
+        Self::new(mr_enclave)
+            .attributes(qe_identity.attributes())
+            .miscellaneous_select(qe_identity.miscellaneous_select())
+    }
+
+    /// Set the attributes of the enclave
+    pub fn attributes(mut self, attributes: Attributes) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Set the CONFIGSVN of the enclave
+    pub fn config_svn(mut self, config_svn: ConfigSvn) -> Self {
+        self.config_svn = config_svn;
+        self
+    }
+
+    /// Set the MISCSELECT of the enclave
+    pub fn miscellaneous_select(mut self, miscellaneous_select: MiscellaneousSelect) -> Self {
+        self.miscellaneous_select = miscellaneous_select;
+        self
+    }
+
+    /// Set the CONFIGID of the enclave
+    pub fn config_id(mut self, config_id: ConfigId) -> Self {
+        self.config_id = config_id;
+        self
+    }
+
+    /// Build the target info
+    pub fn build(&self) -> TargetInfo {
+        TargetInfo::from(sgx_target_info_t {
+            mr_enclave: self.mr_enclave.into(),
+            attributes: self.attributes.into(),
+            reserved1: [0; 2],
+            config_svn: self.config_svn.into(),
+            misc_select: self.miscellaneous_select.clone().into(),
+            reserved2: [0; 8],
+            config_id: self.config_id.clone().into(),
+            reserved3: [0; 384],
+        })
+    }
+}
+
+/// The target info of the enclave which produced the report body
+impl From<&ReportBody> for TargetInfoBuilder {
+    fn from(report_body: &ReportBody) -> Self {
+        Self::new(report_body.mr_enclave())
+            .attributes(report_body.attributes())
+            .config_svn(report_body.config_svn())
+            .miscellaneous_select(report_body.miscellaneous_select())
+            .config_id(report_body.config_id())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use mc_sgx_core_sys_types::{sgx_attributes_t, sgx_report_body_t};
+
+    fn report_body() -> ReportBody {
+        let mut report_body = sgx_report_body_t::default();
+        report_body.mr_enclave.m = [1; 32];
+        report_body.attributes = sgx_attributes_t {
+            flags: 0x07,
+            xfrm: 0x03,
+        };
+        report_body.config_svn = 5;
+        report_body.misc_select = 6;
+        report_body.config_id = [7; 64];
+        ReportBody::from(&report_body)
+    }
+
+    #[test]
+    fn target_info_defaults_to_zero() {
+        let target_info: sgx_target_info_t = TargetInfoBuilder::new(MrEnclave::from([2; 32]))
+            .build()
+            .into();
+
+        assert_eq!(target_info.mr_enclave.m, [2; 32]);
+        assert_eq!(target_info.attributes, sgx_attributes_t::default());
+        assert_eq!(target_info.config_svn, 0);
+        assert_eq!(target_info.misc_select, 0);
+        assert_eq!(target_info.config_id, [0; 64]);
+    }
+
+    #[test]
+    fn target_info_from_report_body() {
+        let target_info: sgx_target_info_t = TargetInfoBuilder::from(&report_body()).build().into();
+
+        assert_eq!(target_info.mr_enclave.m, [1; 32]);
+        assert_eq!(
+            target_info.attributes,
+            sgx_attributes_t {
+                flags: 0x07,
+                xfrm: 0x03
+            }
+        );
+        assert_eq!(target_info.config_svn, 5);
+        assert_eq!(target_info.misc_select, 6);
+        assert_eq!(target_info.config_id, [7; 64]);
+        assert_eq!(target_info.reserved1, [0; 2]);
+        assert_eq!(target_info.reserved2, [0; 8]);
+        assert_eq!(target_info.reserved3, [0; 384]);
+    }
+
+    #[test]
+    fn target_info_overrides_report_body() {
+        let target_info: sgx_target_info_t = TargetInfoBuilder::from(&report_body())
+            .config_svn(ConfigSvn::from(9))
+            .build()
+            .into();
+
+        assert_eq!(target_info.config_svn, 9);
+        assert_eq!(target_info.mr_enclave.m, [1; 32]);
+    }
+
+    #[test]
+    fn target_info_from_qe_identity() {
+        let json = include_str!("../data/tests/qe_identity.json");
+        let signed_identity =
+            crate::SignedQeIdentity::try_from(json).expect("Failed to parse QE identity");
+        let qe_identity =
+            QeIdentity::try_from(&signed_identity).expect("Failed to parse QE identity");
+
+        let target_info: sgx_target_info_t =
+            TargetInfoBuilder::from_qe_identity(&qe_identity, MrEnclave::from([3; 32]))
+                .build()
+                .into();
+
+        assert_eq!(target_info.mr_enclave.m, [3; 32]);
+        assert_eq!(target_info.attributes, qe_identity.attributes().into());
+        assert_eq!(
+            target_info.misc_select,
+            u32::from(qe_identity.miscellaneous_select())
+        );
+        assert_eq!(target_info.config_svn, 0);
+    }
+}
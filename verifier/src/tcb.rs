@@ -26,38 +26,68 @@
 //!
 //! The TCB info is retrieved by using the fsmpc available in the report body
 //! and accessing <https://api.trustedservices.intel.com/sgx/certification/v4/tcb?fmspc={}>
+//!
+//! Both version 3 of the TCB info, from the v4 API, and version 2, from the v3
+//! API, are supported. Version 2 lists the SGX TCB component SVNs as the
+//! individual `sgxtcbcompXXsvn` members instead of the `sgxtcbcomponents`
+//! array.
 
 #![allow(dead_code)]
 
 use crate::{
     advisories::{Advisories, AdvisoryStatus},
-    Accessor, Error, VerificationMessage, VerificationOutput, Verifier,
+    evidence::key_from_certificate,
+    Accessor, CertificateChainVerifier, CpuSvnComponents, Error, Fmspc, PceId, TdxModule,
+    TdxModuleIdentity, VerificationMessage, VerificationOptions, VerificationOutput, Verifier,
+};
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
 };
-use alloc::{boxed::Box, string::String, vec::Vec};
 use core::fmt::Formatter;
 use der::DateTime;
-use mc_sgx_dcap_types::{TcbInfo as PckTcb, COMPONENT_SVN_COUNT, FMSPC_SIZE};
+use mc_sgx_core_types::CpuSvn;
+use mc_sgx_dcap_types::{TcbInfo as PckTcb, COMPONENT_SVN_COUNT};
 use p256::ecdsa::{signature::Verifier as SignatureVerifier, Signature, VerifyingKey};
 use serde::Deserialize;
 use serde_json::value::RawValue;
+use x509_cert::{crl::CertificateList, Certificate};
+
+pub(crate) const TCB_INFO_VERSION: u32 = 3;
+
+/// The status of a platform's TCB level, the `tcbStatus` of the level
+pub type TcbStatus = AdvisoryStatus;
+
+/// The TCB info versions which can be verified, the v3 API provides version 2
+pub(crate) const SUPPORTED_TCB_INFO_VERSIONS: [u32; 2] = [2, TCB_INFO_VERSION];
 
-const TCB_INFO_VERSION: u32 = 3;
+/// Version 2 of the TCB info predates the `id` member, it was only for SGX
+fn sgx_id() -> String {
+    "SGX".into()
+}
 
 /// The `tcbInfo` member of the TCB(Trusted Computing Base) data retrieved from
 /// <https://api.trustedservices.intel.com/sgx/certification/v4/tcb?fmspc={}>
 /// The schema is available at <https://api.portal.trustedservices.intel.com/documentation#pcs-tcb-info-model-v3>
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TcbInfo {
+    #[serde(default = "sgx_id")]
     id: String,
     version: u32,
     issue_date: String,
     next_update: String,
-    #[serde(with = "hex")]
-    fmspc: [u8; FMSPC_SIZE],
-    pce_id: String,
+    fmspc: Fmspc,
+    pce_id: PceId,
     tcb_type: u32,
     tcb_evaluation_data_number: u32,
+    #[serde(default)]
+    tdx_module: Option<TdxModule>,
+    #[serde(default)]
+    tdx_module_identities: Vec<TdxModuleIdentity>,
     tcb_levels: Vec<TcbLevel>,
 }
 
@@ -90,43 +120,175 @@ impl TcbInfo {
     /// - `Error::UnsupportedTcbLevel` if the TCB level reported is not found in
     ///   self.
     pub fn advisories(&self, pck_tcb: &PckTcb) -> Result<Advisories, Error> {
+        let level = self.tcb_level(pck_tcb)?;
+        Ok(Advisories::new(&level.advisory_ids, level.tcb_status))
+    }
+
+    /// Get the TCB level of the platform with the given `pck_tcb`
+    ///
+    /// The [`TcbStatus`] and advisory IDs of the platform are those of the
+    /// returned level. This is steps 3 and 4 of
+    /// <https://api.portal.trustedservices.intel.com/documentation#pcs-tcb-info-v4>,
+    /// see [`TcbInfo::advisories()`].
+    ///
+    /// # Errors
+    /// - `Error::FmspcMismatch` if the `fmspc` in `self` does not match the one
+    ///   in `pck_tcb`.
+    /// - `Error::UnsupportedTcbLevel` if the TCB level reported is not found in
+    ///   self.
+    pub fn tcb_level(&self, pck_tcb: &PckTcb) -> Result<&TcbLevel, Error> {
         // `self` should have been retrieved via
         // <https://api.trustedservices.intel.com/sgx/certification/v4/tcb?fmspc={}>
         // and the `pck_tcb.fmspc()`. Failure here should rarely happen, but we
         // still check to ensure the client didn't get mixed up.
-        if self.fmspc != *pck_tcb.fmspc() {
-            return Err(Error::FmspcMismatch);
+        let pck_fmspc = Fmspc::new(*pck_tcb.fmspc());
+        if self.fmspc != pck_fmspc {
+            return Err(Error::FmspcMismatch {
+                pck: pck_fmspc,
+                tcb_info: self.fmspc,
+            });
         }
 
-        for level in &self.tcb_levels {
-            if level.tcb.is_corresponding_level(pck_tcb) {
-                return Ok(Advisories::new(&level.advisory_ids, level.tcb_status));
-            }
+        self.tcb_level_for_svns(pck_tcb.svns(), *pck_tcb.pce_svn())
+    }
+
+    /// Get the TCB level of the platform with the given `cpu_svn` and
+    /// `pce_svn`
+    ///
+    /// This is for when the SGX TCB component SVNs from the PCK certificate
+    /// are not at hand. For TCB type 0, the only defined type, the SGX TCB
+    /// component SVNs are the bytes of the CPU SVN.
+    ///
+    /// # Errors
+    /// - `Error::TcbType` if the TCB type of `self` is not 0.
+    /// - `Error::UnsupportedTcbLevel` if the TCB level is not found in self.
+    pub fn tcb_level_for_cpu_svn(
+        &self,
+        cpu_svn: &CpuSvn,
+        pce_svn: u32,
+    ) -> Result<&TcbLevel, Error> {
+        if self.tcb_type != 0 {
+            return Err(Error::TcbType(self.tcb_type));
         }
-        Err(Error::UnsupportedTcbLevel)
+
+        let svns = CpuSvnComponents::from(cpu_svn).svns();
+        self.tcb_level_for_svns(&svns, pce_svn)
     }
 
-    fn verify(&self, time: Option<DateTime>) -> Result<(), Error> {
-        self.verify_version()?.verify_time(time)?;
+    fn tcb_level_for_svns(
+        &self,
+        svns: &[u32; COMPONENT_SVN_COUNT],
+        pce_svn: u32,
+    ) -> Result<&TcbLevel, Error> {
+        self.tcb_levels
+            .iter()
+            .find(|level| level.tcb.is_corresponding_svns(svns, pce_svn))
+            .ok_or(Error::UnsupportedTcbLevel)
+    }
+
+    /// The TEE the TCB info is for, "SGX" or "TDX"
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The version of the TCB info structure
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The FMSPC of the platforms this TCB info is for
+    pub fn fmspc(&self) -> Fmspc {
+        self.fmspc
+    }
+
+    /// The PCE ID of the platforms this TCB info is for
+    pub fn pce_id(&self) -> PceId {
+        self.pce_id
+    }
+
+    /// The time the TCB info expires, its `nextUpdate`.
+    ///
+    /// # Errors
+    /// `Error::Der` if the `nextUpdate` is not a valid time.
+    pub fn next_update(&self) -> Result<DateTime, Error> {
+        Ok(self.next_update.parse::<DateTime>()?)
+    }
+
+    /// The time the TCB info was issued, its `issueDate`.
+    ///
+    /// # Errors
+    /// `Error::Der` if the `issueDate` is not a valid time.
+    pub fn issue_date(&self) -> Result<DateTime, Error> {
+        Ok(self.issue_date.parse::<DateTime>()?)
+    }
+
+    /// The type of the TCB level comparison, 0 is the only defined type
+    pub fn tcb_type(&self) -> u32 {
+        self.tcb_type
+    }
+
+    /// The number of the TCB evaluation this TCB info was produced by,
+    /// incremented by Intel with each TCB recovery
+    pub fn tcb_evaluation_data_number(&self) -> u32 {
+        self.tcb_evaluation_data_number
+    }
+
+    /// The TCB levels, sorted from the newest to the oldest
+    pub fn tcb_levels(&self) -> &[TcbLevel] {
+        &self.tcb_levels
+    }
+
+    /// The expected TDX module, only present in the TCB info of TDX
+    /// platforms, see [`TcbInfo::tdx_module_advisories()`].
+    pub fn tdx_module(&self) -> Option<&TdxModule> {
+        self.tdx_module.as_ref()
+    }
+
+    /// The identities of the major versions of the TDX module, only present
+    /// in the TCB info of TDX 1.5 platforms, see
+    /// [`TcbInfo::tdx_module_advisories()`].
+    pub fn tdx_module_identities(&self) -> &[TdxModuleIdentity] {
+        &self.tdx_module_identities
+    }
+
+    fn verify(&self, time: Option<DateTime>, options: &VerificationOptions) -> Result<(), Error> {
+        self.verify_version()?
+            .verify_time(time, options)?
+            .verify_evaluation_data_number(options)?;
         Ok(())
     }
 
-    fn verify_time(&self, time: Option<DateTime>) -> Result<&Self, Error> {
+    fn verify_time(
+        &self,
+        time: Option<DateTime>,
+        options: &VerificationOptions,
+    ) -> Result<&Self, Error> {
         if let Some(date_time) = time {
             let issue_date = self.issue_date.parse::<DateTime>()?;
             let next_update = self.next_update.parse::<DateTime>()?;
-            if date_time < issue_date {
+            if options.is_not_yet_valid(date_time, issue_date) {
                 return Err(Error::TcbInfoNotYetValid);
-            } else if date_time >= next_update {
+            } else if options.is_expired(date_time, next_update) {
                 return Err(Error::TcbInfoExpired);
             }
         }
         Ok(self)
     }
+    fn verify_evaluation_data_number(&self, options: &VerificationOptions) -> Result<&Self, Error> {
+        if self.tcb_evaluation_data_number < options.min_tcb_evaluation_data_number {
+            Err(Error::TcbInfoEvaluationDataNumber {
+                minimum: options.min_tcb_evaluation_data_number,
+                actual: self.tcb_evaluation_data_number,
+            })
+        } else {
+            Ok(self)
+        }
+    }
+
     fn verify_version(&self) -> Result<&Self, Error> {
-        if self.version != TCB_INFO_VERSION {
+        if !SUPPORTED_TCB_INFO_VERSIONS.contains(&self.version) {
             Err(Error::TcbInfoVersion {
-                expected: TCB_INFO_VERSION,
+                supported: SUPPORTED_TCB_INFO_VERSIONS.to_vec(),
                 actual: self.version,
             })
         } else {
@@ -153,7 +315,7 @@ impl TryFrom<&SignedTcbInfo> for TcbInfo {
 }
 
 /// A single TCB level
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TcbLevel {
     tcb: Tcb,
@@ -163,15 +325,90 @@ pub struct TcbLevel {
     advisory_ids: Vec<String>,
 }
 
+impl TcbLevel {
+    /// The SVNs a platform needs to be at this level
+    pub fn tcb(&self) -> &Tcb {
+        &self.tcb
+    }
+
+    /// The date of the TCB recovery this level is for, its `tcbDate`.
+    ///
+    /// # Errors
+    /// `Error::Der` if the `tcbDate` is not a valid time.
+    pub fn tcb_date(&self) -> Result<DateTime, Error> {
+        Ok(self.tcb_date.parse::<DateTime>()?)
+    }
+
+    /// The status of a platform at this level
+    pub fn tcb_status(&self) -> TcbStatus {
+        self.tcb_status
+    }
+
+    /// The IDs of the Intel security advisories which apply at this level
+    pub fn advisory_ids(&self) -> &[String] {
+        &self.advisory_ids
+    }
+}
+
 /// A TCB
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "RawTcb")]
 pub struct Tcb {
-    #[serde(rename = "sgxtcbcomponents")]
     sgx_tcb_components: [TcbComponent; COMPONENT_SVN_COUNT],
-    #[serde(rename = "pcesvn")]
     pce_svn: u32,
 }
 
+/// The TCB as it appears in the TCB info JSON
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawTcb {
+    /// Version 3 of the TCB info
+    Components {
+        sgxtcbcomponents: Box<[TcbComponent; COMPONENT_SVN_COUNT]>,
+        pcesvn: u32,
+    },
+    /// Version 2 of the TCB info, `sgxtcbcomp01svn` through
+    /// `sgxtcbcomp16svn` and `pcesvn`
+    Svns(BTreeMap<String, u32>),
+}
+
+impl TryFrom<RawTcb> for Tcb {
+    type Error = Error;
+
+    fn try_from(raw: RawTcb) -> Result<Self, Self::Error> {
+        match raw {
+            RawTcb::Components {
+                sgxtcbcomponents,
+                pcesvn,
+            } => Ok(Self {
+                sgx_tcb_components: *sgxtcbcomponents,
+                pce_svn: pcesvn,
+            }),
+            RawTcb::Svns(svns) => {
+                let svn = |name: &str| {
+                    svns.get(name)
+                        .copied()
+                        .ok_or_else(|| Error::Serde(format!("missing field `{name}`")))
+                };
+                let mut sgx_tcb_components = Vec::with_capacity(COMPONENT_SVN_COUNT);
+                for index in 1..=COMPONENT_SVN_COUNT {
+                    sgx_tcb_components.push(TcbComponent {
+                        svn: svn(&format!("sgxtcbcomp{index:02}svn"))?,
+                        category: None,
+                        r#type: None,
+                    });
+                }
+                Ok(Self {
+                    sgx_tcb_components: sgx_tcb_components
+                        .try_into()
+                        .expect("Should have one component per SVN"),
+                    pce_svn: svn("pcesvn")?,
+                })
+            }
+        }
+    }
+}
+
 impl TryFrom<&str> for Tcb {
     type Error = Error;
 
@@ -182,6 +419,25 @@ impl TryFrom<&str> for Tcb {
 }
 
 impl Tcb {
+    /// The SGX TCB components, 01 through 16
+    pub fn sgx_tcb_components(&self) -> &[TcbComponent; COMPONENT_SVN_COUNT] {
+        &self.sgx_tcb_components
+    }
+
+    /// The SVNs of the SGX TCB components, 01 through 16
+    pub fn sgx_tcb_component_svns(&self) -> [u32; COMPONENT_SVN_COUNT] {
+        let mut svns = [0; COMPONENT_SVN_COUNT];
+        for (svn, component) in svns.iter_mut().zip(&self.sgx_tcb_components) {
+            *svn = component.svn;
+        }
+        svns
+    }
+
+    /// The SVN of the PCE
+    pub fn pce_svn(&self) -> u32 {
+        self.pce_svn
+    }
+
     /// Returns true if this [`Tcb`] instance can represent the provided
     /// `tcb_info`
     ///
@@ -201,25 +457,41 @@ impl Tcb {
     /// >      equal to the value in TCB Level, read status assigned to this
     /// >      TCB level. Otherwise, move to the next item on TCB Levels list.
     fn is_corresponding_level(&self, tcb_info: &PckTcb) -> bool {
-        let component_iter = self
-            .sgx_tcb_components
-            .iter()
-            .map(|c| c.svn)
-            .zip(tcb_info.svns());
-        let mut svn_iter =
-            component_iter.chain(core::iter::once((self.pce_svn, tcb_info.pce_svn())));
+        self.is_corresponding_svns(tcb_info.svns(), *tcb_info.pce_svn())
+    }
+
+    fn is_corresponding_svns(&self, svns: &[u32; COMPONENT_SVN_COUNT], pce_svn: u32) -> bool {
+        let component_iter = self.sgx_tcb_components.iter().map(|c| c.svn).zip(svns);
+        let mut svn_iter = component_iter.chain(core::iter::once((self.pce_svn, &pce_svn)));
         svn_iter.all(|(a, &b)| a <= b)
     }
 }
 
 /// A component of the TCB
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct TcbComponent {
     svn: u32,
     category: Option<String>,
     r#type: Option<String>,
 }
 
+impl TcbComponent {
+    /// The SVN of the component
+    pub fn svn(&self) -> u32 {
+        self.svn
+    }
+
+    /// The category of the component, for instance "BIOS"
+    pub fn category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    /// The type of the component, for instance "Early Microcode Update"
+    pub fn component_type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+}
+
 /// Signed TCB(Trusted Computing Base) info provided from
 /// <https://api.trustedservices.intel.com/sgx/certification/v4/tcb?fmspc={}>
 ///
@@ -245,12 +517,60 @@ impl SignedTcbInfo {
     ///     ```
     ///   or equivalent
     pub fn verify(self, key: Option<&VerifyingKey>, time: Option<DateTime>) -> Result<(), Error> {
+        self.verify_with_options(key, time, &VerificationOptions::default())
+    }
+
+    /// Verify the `tcbInfo` signature and time are valid, with the tolerances
+    /// of `options`.
+    ///
+    /// See [`SignedTcbInfo::verify()`] for the other arguments.
+    pub fn verify_with_options(
+        self,
+        key: Option<&VerifyingKey>,
+        time: Option<DateTime>,
+        options: &VerificationOptions,
+    ) -> Result<(), Error> {
         self.verify_signature(key)?;
         let tcb_info = TcbInfo::try_from(&self)?;
-        tcb_info.verify(time)?;
+        tcb_info.verify(time, options)?;
         Ok(())
     }
 
+    /// Verify the `tcbInfo` was signed by the leaf of the TCB signing
+    /// `certificate_chain`, and that its time is valid.
+    ///
+    /// # Arguments
+    /// - `certificate_verifier` - The verifier for the `certificate_chain`.
+    /// - `certificate_chain` - The TCB signing chain, leaf first. This is the
+    ///   `TCB-Info-Issuer-Chain` header of the TCB info response.
+    /// - `crls` - The CRLs to verify the `certificate_chain` against.
+    /// - `time` - The current system time, see [`SignedTcbInfo::verify()`].
+    ///
+    /// # Returns
+    /// The verified TCB info.
+    ///
+    /// # Errors
+    /// - `Error::SigningCertificateChain` if the `certificate_chain` fails
+    ///   verification.
+    /// - `Error::MissingPublicKey` if the leaf P-256 key can not be
+    ///   retrieved.
+    /// - The errors of [`SignedTcbInfo::verify()`].
+    pub fn verify_with_certificate_chain<C: CertificateChainVerifier>(
+        self,
+        certificate_verifier: &C,
+        certificate_chain: &[Certificate],
+        crls: &[CertificateList],
+        time: Option<DateTime>,
+    ) -> Result<TcbInfo, Error> {
+        certificate_verifier
+            .verify_certificate_chain(certificate_chain, crls, time)
+            .map_err(|e| Error::SigningCertificateChain(e.to_string()))?;
+        let key = certificate_chain.first().and_then(key_from_certificate);
+        let tcb_info = TcbInfo::try_from(&self)?;
+        self.verify(key.as_ref(), time)?;
+        Ok(tcb_info)
+    }
+
     fn verify_signature(&self, key: Option<&VerifyingKey>) -> Result<(), Error> {
         let key = key.ok_or(Error::MissingPublicKey)?;
         let tcb_info = self.tcb_info.as_ref().get();
@@ -276,6 +596,7 @@ impl TryFrom<&str> for SignedTcbInfo {
 pub struct SignedTcbInfoVerifier {
     key: Option<VerifyingKey>,
     time: Option<DateTime>,
+    options: VerificationOptions,
 }
 
 impl SignedTcbInfoVerifier {
@@ -298,7 +619,17 @@ impl SignedTcbInfoVerifier {
     ///     ```
     ///   or equivalent
     pub fn new(key: Option<VerifyingKey>, time: Option<DateTime>) -> Self {
-        Self { key, time }
+        Self {
+            key,
+            time,
+            options: VerificationOptions::default(),
+        }
+    }
+
+    /// Verify the time with the tolerances of `options`
+    pub fn with_options(mut self, options: VerificationOptions) -> Self {
+        self.options = options;
+        self
     }
 }
 
@@ -306,13 +637,80 @@ impl<E: Accessor<SignedTcbInfo>> Verifier<E> for SignedTcbInfoVerifier {
     type Value = Option<Error>;
     fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
         let signed_tcb_info = evidence.get();
-        let result = signed_tcb_info.verify(self.key.as_ref(), self.time);
+        let result =
+            signed_tcb_info.verify_with_options(self.key.as_ref(), self.time, &self.options);
         let is_success = result.is_ok() as u8;
 
         VerificationOutput::new(result.err(), is_success.into())
     }
 }
 
+/// Verifier for ensuring the FMSPC of the PCK certificate matches the FMSPC of
+/// the TCB info.
+///
+/// The TCB info is retrieved for an FMSPC. Appraising a platform with the TCB
+/// info of a different FMSPC would evaluate it against the TCB levels of
+/// another platform model.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FmspcVerifier {
+    tcb_info_fmspc: Fmspc,
+}
+
+impl FmspcVerifier {
+    /// Create a new instance.
+    ///
+    /// The [`Fmspc`] of the evidence is the one from the SGX extension of the
+    /// PCK certificate.
+    ///
+    /// # Arguments
+    /// - `tcb_info_fmspc` - The `fmspc` of the TCB info, see
+    ///   [`TcbInfo::fmspc()`].
+    pub fn new(tcb_info_fmspc: Fmspc) -> Self {
+        Self { tcb_info_fmspc }
+    }
+}
+
+impl<E: Accessor<Fmspc>> Verifier<E> for FmspcVerifier {
+    type Value = Option<Error>;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let pck_fmspc = evidence.get();
+        let is_success = (pck_fmspc == self.tcb_info_fmspc) as u8;
+        let error = (is_success == 0).then_some(Error::FmspcMismatch {
+            pck: pck_fmspc,
+            tcb_info: self.tcb_info_fmspc,
+        });
+
+        VerificationOutput::new(error, is_success.into())
+    }
+}
+
+impl VerificationMessage<Option<Error>> for FmspcVerifier {
+    fn fmt_padded(
+        &self,
+        f: &mut Formatter<'_>,
+        pad: usize,
+        result: &VerificationOutput<Option<Error>>,
+    ) -> core::fmt::Result {
+        let is_success = result.is_success();
+        let status = crate::choice_to_status_message(is_success);
+        write!(f, "{:pad$}{status} ", "")?;
+
+        if is_success.into() {
+            write!(
+                f,
+                "The FMSPC of the PCK certificate matches the TCB info: {}",
+                self.tcb_info_fmspc
+            )
+        } else {
+            let error = result
+                .value()
+                .as_ref()
+                .expect("Should have an error if not successful");
+            write!(f, "{error}")
+        }
+    }
+}
+
 impl VerificationMessage<Option<Error>> for SignedTcbInfoVerifier {
     fn fmt_padded(
         &self,
@@ -339,13 +737,24 @@ impl VerificationMessage<Option<Error>> for SignedTcbInfoVerifier {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::VerificationTreeDisplay;
+    use crate::{CertificateChainVerifierError, VerificationTreeDisplay};
     use alloc::{format, vec};
     use assert_matches::assert_matches;
     use p256::ecdsa::{signature::Signer, SigningKey};
     use x509_cert::{der::DecodePem, Certificate};
     use yare::parameterized;
 
+    const VERSION_2_TCB_INFO: &str = r#"{
+        "version":2,"issueDate":"2022-04-13T09:38:17Z","nextUpdate":"2022-05-13T09:38:17Z",
+        "fmspc":"00906ED50000","pceId":"0000","tcbType":0,"tcbEvaluationDataNumber":11,
+        "tcbLevels":[
+            {"tcb":{"sgxtcbcomp01svn":17,"sgxtcbcomp02svn":17,"sgxtcbcomp03svn":2,"sgxtcbcomp04svn":4,"sgxtcbcomp05svn":1,"sgxtcbcomp06svn":128,"sgxtcbcomp07svn":0,"sgxtcbcomp08svn":0,"sgxtcbcomp09svn":0,"sgxtcbcomp10svn":0,"sgxtcbcomp11svn":0,"sgxtcbcomp12svn":0,"sgxtcbcomp13svn":0,"sgxtcbcomp14svn":0,"sgxtcbcomp15svn":0,"sgxtcbcomp16svn":16,"pcesvn":11},
+             "tcbDate":"2021-06-09T00:00:00Z","tcbStatus":"UpToDate"},
+            {"tcb":{"sgxtcbcomp01svn":15,"sgxtcbcomp02svn":15,"sgxtcbcomp03svn":2,"sgxtcbcomp04svn":4,"sgxtcbcomp05svn":1,"sgxtcbcomp06svn":128,"sgxtcbcomp07svn":0,"sgxtcbcomp08svn":0,"sgxtcbcomp09svn":0,"sgxtcbcomp10svn":0,"sgxtcbcomp11svn":0,"sgxtcbcomp12svn":0,"sgxtcbcomp13svn":0,"sgxtcbcomp14svn":0,"sgxtcbcomp15svn":0,"sgxtcbcomp16svn":0,"pcesvn":10},
+             "tcbDate":"2020-11-11T00:00:00Z","tcbStatus":"OutOfDate","advisoryIDs":["INTEL-SA-00477"]}
+        ]
+    }"#;
+
     fn tcb_verifying_key() -> VerifyingKey {
         let pem = include_str!("../data/tests/tcb_signer.pem");
         let certificate = Certificate::from_pem(pem).expect("failed to parse PEM");
@@ -422,6 +831,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tcb_info_next_update() {
+        let json = include_str!("../data/tests/example_tcb.json");
+        let signed_tcb_info = SignedTcbInfo::try_from(json).expect("Failed to parse signed TCB");
+        let tcb_info = TcbInfo::try_from(&signed_tcb_info).expect("Failed to parse TCB info");
+        assert_eq!(
+            tcb_info.next_update(),
+            Ok("2022-05-13T09:38:17Z"
+                .parse::<DateTime>()
+                .expect("Failed to parse time"))
+        );
+    }
+
     #[test]
     fn parse_example_tcb_info() {
         let json = include_str!("../data/tests/example_tcb.json");
@@ -433,7 +855,7 @@ mod tests {
         assert_eq!(tcb_info.issue_date, "2022-04-13T09:38:17Z");
         assert_eq!(tcb_info.next_update, "2022-05-13T09:38:17Z");
         assert_eq!(tcb_info.fmspc, [80, 128, 111, 0, 0, 0]);
-        assert_eq!(tcb_info.pce_id, "0000");
+        assert_eq!(tcb_info.pce_id, PceId::new([0, 0]));
         assert_eq!(tcb_info.tcb_type, 0);
         assert_eq!(tcb_info.tcb_evaluation_data_number, 12);
         assert_eq!(tcb_info.tcb_levels.len(), 2);
@@ -470,7 +892,7 @@ mod tests {
         assert_eq!(tcb_info.issue_date, "2023-07-12T19:56:44Z");
         assert_eq!(tcb_info.next_update, "2023-08-11T19:56:44Z");
         assert_eq!(tcb_info.fmspc, [0, 144, 110, 213, 0, 0]);
-        assert_eq!(tcb_info.pce_id, "0000");
+        assert_eq!(tcb_info.pce_id, PceId::new([0, 0]));
         assert_eq!(tcb_info.tcb_type, 0);
         assert_eq!(tcb_info.tcb_evaluation_data_number, 15);
         assert_eq!(tcb_info.tcb_levels.len(), 17);
@@ -682,6 +1104,57 @@ mod tests {
         );
     }
 
+    #[parameterized(
+        within_skew = { "2023-08-11T19:57:43Z", true },
+        past_skew = { "2023-08-11T19:57:44Z", false },
+    )]
+    fn next_update_with_clock_skew(time: &str, expected: bool) {
+        let key = tcb_verifying_key();
+        let tcb_json = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+        let signed_tcb_info =
+            SignedTcbInfo::try_from(tcb_json).expect("Failed to parse signed TCB");
+        let time = Some(time.parse::<DateTime>().expect("Failed to parse time"));
+        let options = VerificationOptions {
+            max_clock_skew: core::time::Duration::from_secs(60),
+            ..Default::default()
+        };
+        let verifier = SignedTcbInfoVerifier::new(Some(key), time).with_options(options);
+
+        let verification = verifier.verify(&signed_tcb_info);
+
+        assert_eq!(verification.is_success().unwrap_u8() == 1, expected);
+    }
+
+    #[parameterized(
+        no_minimum = { 0, true },
+        at_minimum = { 15, true },
+        below_minimum = { 16, false },
+    )]
+    fn minimum_evaluation_data_number(minimum: u32, expected: bool) {
+        let key = tcb_verifying_key();
+        let tcb_json = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+        let signed_tcb_info =
+            SignedTcbInfo::try_from(tcb_json).expect("Failed to parse signed TCB");
+        let options = VerificationOptions {
+            min_tcb_evaluation_data_number: minimum,
+            ..Default::default()
+        };
+        let verifier = SignedTcbInfoVerifier::new(Some(key), None).with_options(options);
+
+        let verification = verifier.verify(&signed_tcb_info);
+
+        assert_eq!(verification.is_success().unwrap_u8() == 1, expected);
+        if !expected {
+            assert_eq!(
+                verification.value,
+                Some(Error::TcbInfoEvaluationDataNumber {
+                    minimum,
+                    actual: 15
+                })
+            );
+        }
+    }
+
     #[test]
     fn passes_ignoring_time() {
         let key = tcb_verifying_key();
@@ -793,7 +1266,7 @@ mod tests {
         let tcb = PckTcb::new(
             svns.try_into().expect("Not enough svns"),
             pce_svn,
-            tcb_info.fmspc.clone(),
+            tcb_info.fmspc.into(),
         );
         let expected_advisories = Advisories::new(ids, status);
 
@@ -814,7 +1287,7 @@ mod tests {
         let tcb = PckTcb::new(
             svns.try_into().expect("Not enough svns"),
             pce_svn,
-            tcb_info.fmspc.clone(),
+            tcb_info.fmspc.into(),
         );
 
         assert_matches!(tcb_info.advisories(&tcb), Err(Error::UnsupportedTcbLevel));
@@ -833,7 +1306,7 @@ mod tests {
             .map(|c| c.svn)
             .collect::<Vec<_>>();
 
-        let mut fmspc = tcb_info.fmspc.clone();
+        let mut fmspc: [u8; 6] = tcb_info.fmspc.into();
         fmspc[0] += 1;
 
         let tcb = PckTcb::new(
@@ -842,7 +1315,13 @@ mod tests {
             fmspc,
         );
 
-        assert_matches!(tcb_info.advisories(&tcb), Err(Error::FmspcMismatch));
+        assert_eq!(
+            tcb_info.advisories(&tcb),
+            Err(Error::FmspcMismatch {
+                pck: Fmspc::new([1, 144, 110, 213, 0, 0]),
+                tcb_info: tcb_info.fmspc(),
+            })
+        );
     }
 
     #[parameterized(
@@ -858,7 +1337,7 @@ mod tests {
         let tcb = PckTcb::new(
             svns.try_into().expect("Not enough svns"),
             pce_svn,
-            tcb_info.fmspc.clone(),
+            tcb_info.fmspc.into(),
         );
         let expected_advisories = Advisories::new::<[&str; 0], str>([], status);
 
@@ -950,8 +1429,8 @@ mod tests {
     }
 
     #[parameterized(
-        version_1 = { 2 },
-        version_3 = { 4 },
+        version_1 = { 1 },
+        version_4 = { 4 },
     )]
     fn signed_tcb_info_verifier_fails_for_different_version(version: u32) {
         let (signed_tcb_info, key) =
@@ -967,8 +1446,271 @@ mod tests {
         assert_eq!(verification.is_success().unwrap_u8(), 0);
         assert_matches!(
             verification.value.expect("Expecting error"),
-            Error::TcbInfoVersion{expected: TCB_INFO_VERSION, actual} if actual == version
+            Error::TcbInfoVersion{supported, actual} if supported == SUPPORTED_TCB_INFO_VERSIONS && actual == version
+        );
+    }
+
+    #[test]
+    fn fmspc_verifier_succeeds() {
+        let fmspc = Fmspc::new([0, 144, 110, 213, 0, 0]);
+        let verifier = FmspcVerifier::new(fmspc);
+        let verification = verifier.verify(&fmspc);
+
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [x] The FMSPC of the PCK certificate matches the TCB info: 00906ED50000"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn fmspc_verifier_fails_for_mismatch() {
+        let verifier = FmspcVerifier::new(Fmspc::new([0, 144, 110, 213, 0, 0]));
+        let pck_fmspc = Fmspc::new([0x50, 0x80, 0x6F, 0, 0, 0]);
+        let verification = verifier.verify(&pck_fmspc);
+
+        assert_eq!(verification.is_success().unwrap_u8(), 0);
+        assert_eq!(
+            verification.value,
+            Some(Error::FmspcMismatch {
+                pck: pck_fmspc,
+                tcb_info: Fmspc::new([0, 144, 110, 213, 0, 0]),
+            })
+        );
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [ ] The FMSPC of the PCK certificate, 50806F000000, does not match the FMSPC of the TCB info, 00906ED50000"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn signed_tcb_info_verifier_accepts_version_2() {
+        let (signed_tcb_info, key) = alter_signed_tcb_info("\"version\":3", "\"version\":2");
+        let time = "2023-07-12T19:56:44Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+
+        let verifier = SignedTcbInfoVerifier::new(Some(key), Some(time));
+        let verification = verifier.verify(&signed_tcb_info);
+
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+    }
+
+    #[test]
+    fn parse_version_2_tcb_info() {
+        let tcb_info = TcbInfo::try_from(VERSION_2_TCB_INFO).expect("Failed to parse TCB info");
+        assert_eq!(tcb_info.id(), "SGX");
+        assert_eq!(tcb_info.version(), 2);
+        assert_eq!(tcb_info.tcb_evaluation_data_number(), 11);
+        assert_eq!(tcb_info.tcb_levels().len(), 2);
+
+        let first_level = &tcb_info.tcb_levels()[0];
+        assert_eq!(first_level.tcb_status(), AdvisoryStatus::UpToDate);
+        assert_eq!(first_level.advisory_ids(), &[] as &[String]);
+        assert_eq!(
+            first_level.tcb_date(),
+            Ok("2021-06-09T00:00:00Z"
+                .parse::<DateTime>()
+                .expect("Failed to parse time"))
+        );
+        let tcb = first_level.tcb();
+        assert_eq!(
+            tcb.sgx_tcb_component_svns(),
+            [17, 17, 2, 4, 1, 128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 16]
+        );
+        assert_eq!(tcb.pce_svn(), 11);
+        assert_eq!(tcb.sgx_tcb_components()[0].category(), None);
+        assert_eq!(tcb.sgx_tcb_components()[0].component_type(), None);
+
+        let second_level = &tcb_info.tcb_levels()[1];
+        assert_eq!(second_level.tcb_status(), AdvisoryStatus::OutOfDate);
+        assert_eq!(second_level.advisory_ids(), &["INTEL-SA-00477"]);
+    }
+
+    #[test]
+    fn advisories_from_version_2_tcb_info() {
+        let tcb_info = TcbInfo::try_from(VERSION_2_TCB_INFO).expect("Failed to parse TCB info");
+        let tcb = PckTcb::new(
+            [17, 17, 2, 4, 1, 128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 15],
+            11,
+            tcb_info.fmspc().into(),
+        );
+
+        assert_eq!(
+            tcb_info.advisories(&tcb),
+            Ok(Advisories::new(
+                ["INTEL-SA-00477"],
+                AdvisoryStatus::OutOfDate
+            ))
+        );
+    }
+
+    #[test]
+    fn version_2_tcb_missing_svn_fails_to_parse() {
+        let json = VERSION_2_TCB_INFO.replacen("\"sgxtcbcomp05svn\":1,", "", 1);
+        assert_matches!(TcbInfo::try_from(json.as_str()), Err(Error::Serde(_)));
+    }
+
+    #[parameterized(
+        best = { &[20, 20, 2, 4, 1, 128, 14, 0, 0, 0, 0, 0, 0, 0, 0, 0], 13, "2023-02-15T00:00:00Z", TcbStatus::SWHardeningNeeded },
+        second_best = { &[20, 20, 2, 4, 1, 128, 13, 0, 0, 0, 0, 0, 0, 0, 0, 0], 13, "2023-02-15T00:00:00Z", TcbStatus::ConfigurationAndSWHardeningNeeded },
+        oldest = { &[2, 2, 2, 4, 1, 128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 6, "2018-08-15T00:00:00Z", TcbStatus::OutOfDate },
+    )]
+    fn tcb_level_from_pck_tcb(svns: &[u32], pce_svn: u32, tcb_date: &str, status: TcbStatus) {
+        let json = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+        let signed_tcb_info = SignedTcbInfo::try_from(json).expect("Failed to parse signed TCB");
+        let tcb_info = TcbInfo::try_from(&signed_tcb_info).expect("Failed to parse TCB info");
+        let tcb = PckTcb::new(
+            svns.try_into().expect("Not enough svns"),
+            pce_svn,
+            tcb_info.fmspc().into(),
+        );
+
+        let level = tcb_info.tcb_level(&tcb).expect("Failed to get TCB level");
+        assert_eq!(level.tcb_status(), status);
+        assert_eq!(level.tcb_date, tcb_date);
+    }
+
+    #[test]
+    fn tcb_level_from_cpu_svn() {
+        let json = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+        let signed_tcb_info = SignedTcbInfo::try_from(json).expect("Failed to parse signed TCB");
+        let tcb_info = TcbInfo::try_from(&signed_tcb_info).expect("Failed to parse TCB info");
+        let cpu_svn = CpuSvn::from([20, 20, 2, 4, 1, 128, 14, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let level = tcb_info
+            .tcb_level_for_cpu_svn(&cpu_svn, 13)
+            .expect("Failed to get TCB level");
+        assert_eq!(level.tcb_status(), TcbStatus::SWHardeningNeeded);
+        assert_eq!(level.advisory_ids(), &["INTEL-SA-00334", "INTEL-SA-00615"]);
+
+        assert_eq!(
+            tcb_info.tcb_level_for_cpu_svn(&cpu_svn, 5),
+            Err(Error::UnsupportedTcbLevel)
+        );
+    }
+
+    #[test]
+    fn tcb_level_from_cpu_svn_is_revoked() {
+        let json = VERSION_2_TCB_INFO.replace("\"OutOfDate\"", "\"Revoked\"");
+        let tcb_info = TcbInfo::try_from(json.as_str()).expect("Failed to parse TCB info");
+        let cpu_svn = CpuSvn::from([16, 16, 2, 4, 1, 128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let level = tcb_info
+            .tcb_level_for_cpu_svn(&cpu_svn, 11)
+            .expect("Failed to get TCB level");
+        assert_eq!(level.tcb_status(), TcbStatus::Revoked);
+        assert_eq!(level.advisory_ids(), &["INTEL-SA-00477"]);
+    }
+
+    #[test]
+    fn tcb_level_from_cpu_svn_fails_for_unknown_tcb_type() {
+        let json = VERSION_2_TCB_INFO.replace("\"tcbType\":0", "\"tcbType\":1");
+        let tcb_info = TcbInfo::try_from(json.as_str()).expect("Failed to parse TCB info");
+        let cpu_svn = CpuSvn::from([20; 16]);
+
+        assert_eq!(
+            tcb_info.tcb_level_for_cpu_svn(&cpu_svn, 11),
+            Err(Error::TcbType(1))
+        );
+    }
+
+    #[test]
+    fn typed_tcb_info_fields() {
+        let json = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+        let signed_tcb_info = SignedTcbInfo::try_from(json).expect("Failed to parse signed TCB");
+        let tcb_info = TcbInfo::try_from(&signed_tcb_info).expect("Failed to parse TCB info");
+
+        assert_eq!(tcb_info.id(), "SGX");
+        assert_eq!(tcb_info.version(), 3);
+        assert_eq!(tcb_info.tcb_type(), 0);
+        assert_eq!(tcb_info.tcb_evaluation_data_number(), 15);
+        assert_eq!(
+            tcb_info.issue_date(),
+            Ok("2023-07-12T19:56:44Z"
+                .parse::<DateTime>()
+                .expect("Failed to parse time"))
+        );
+        let first_level = &tcb_info.tcb_levels()[0];
+        assert_eq!(
+            first_level.advisory_ids(),
+            &["INTEL-SA-00334", "INTEL-SA-00615"]
+        );
+        assert_eq!(first_level.tcb().sgx_tcb_components()[0].svn(), 20);
+    }
+
+    struct TestChainVerifier(Result<(), CertificateChainVerifierError>);
+
+    impl CertificateChainVerifier for TestChainVerifier {
+        fn verify_certificate_chain<'a, 'b>(
+            &self,
+            _certificate_chain: impl IntoIterator<Item = &'a Certificate>,
+            _crls: impl IntoIterator<Item = &'b CertificateList>,
+            _time: impl Into<Option<DateTime>>,
+        ) -> Result<(), CertificateChainVerifierError> {
+            self.0.clone()
+        }
+    }
+
+    fn tcb_signing_chain() -> Vec<Certificate> {
+        [
+            include_str!("../data/tests/tcb_signer.pem"),
+            include_str!("../data/tests/root_ca.pem"),
+        ]
+        .iter()
+        .map(|pem| Certificate::from_pem(pem).expect("failed to parse PEM"))
+        .collect()
+    }
+
+    #[test]
+    fn verify_with_certificate_chain() {
+        let json = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+        let signed_tcb_info = SignedTcbInfo::try_from(json).expect("Failed to parse signed TCB");
+        let time = "2023-07-12T19:56:44Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+
+        let tcb_info = signed_tcb_info
+            .verify_with_certificate_chain(
+                &TestChainVerifier(Ok(())),
+                &tcb_signing_chain(),
+                &[],
+                Some(time),
+            )
+            .expect("Failed to verify TCB info");
+        assert_eq!(tcb_info.fmspc(), [0, 144, 110, 213, 0, 0]);
+    }
+
+    #[test]
+    fn verify_with_certificate_chain_fails_for_bad_chain() {
+        let json = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+        let signed_tcb_info = SignedTcbInfo::try_from(json).expect("Failed to parse signed TCB");
+
+        let result = signed_tcb_info.verify_with_certificate_chain(
+            &TestChainVerifier(Err(CertificateChainVerifierError::CertificateRevoked)),
+            &tcb_signing_chain(),
+            &[],
+            None,
+        );
+        assert_matches!(result, Err(Error::SigningCertificateChain(_)));
+    }
+
+    #[test]
+    fn verify_with_certificate_chain_fails_for_wrong_signer() {
+        let json = include_str!("../data/tests/fmspc_00906ED50000_2023_07_12.json");
+        let signed_tcb_info = SignedTcbInfo::try_from(json).expect("Failed to parse signed TCB");
+        let mut chain = tcb_signing_chain();
+        chain.reverse();
+
+        let result = signed_tcb_info.verify_with_certificate_chain(
+            &TestChainVerifier(Ok(())),
+            &chain,
+            &[],
+            None,
         );
+        assert_eq!(result.unwrap_err(), Error::SignatureVerification);
     }
 
     #[test]
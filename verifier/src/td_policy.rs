@@ -0,0 +1,400 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Appraisal policy for the identity of a TDX trust domain.
+//!
+//! [`TdPolicy`] is the TD counterpart of the report body policies for SGX
+//! enclaves: the MRTD takes the place of the MRENCLAVE, the TD attributes
+//! and XFAM the place of the enclave attributes, and the MROWNER,
+//! MRCONFIGID, and MROWNERCONFIG the place of the KSS identity.
+
+use crate::{
+    choice_to_status_message, td_report::TD_ATTRIBUTES_DEBUG, Accessor, MrConfigId, MrOwner,
+    MrOwnerConfig, MrTd, TdAttributes, TdReport, TdReportBody, VerificationMessage,
+    VerificationOutput, Verifier, Xfam, MESSAGE_INDENT,
+};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use subtle::{Choice, ConstantTimeEq};
+
+/// Policy for the identity of a TDX trust domain.
+///
+/// The MRTD must be one of the allowed measurements. The TD attributes and
+/// XFAM must have the expected bits under their masks. The MROWNER,
+/// MRCONFIGID, and MROWNERCONFIG must match when they are set.
+///
+/// A new policy rejects debug TDs, and doesn't constrain the XFAM.
+///
+/// ```
+/// use mc_attestation_verifier::{MrConfigId, MrTd, TdPolicy, Xfam};
+///
+/// let policy = TdPolicy::new([MrTd::new([0x11; 48])])
+///     .xfam(Xfam::new(0x0006_02E7), 0xFFFF_FFFF)
+///     .mr_config_id(MrConfigId::new([0x22; 48]));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TdPolicy {
+    mr_tds: Vec<MrTd>,
+    td_attributes: TdAttributes,
+    td_attributes_mask: u64,
+    xfam: Xfam,
+    xfam_mask: u64,
+    mr_owner: Option<MrOwner>,
+    mr_config_id: Option<MrConfigId>,
+    mr_owner_config: Option<MrOwnerConfig>,
+}
+
+impl TdPolicy {
+    /// Create a new instance allowing any of the `mr_tds` measurements
+    pub fn new(mr_tds: impl IntoIterator<Item = MrTd>) -> Self {
+        Self {
+            mr_tds: mr_tds.into_iter().collect(),
+            td_attributes: TdAttributes::new(0),
+            td_attributes_mask: TD_ATTRIBUTES_DEBUG,
+            xfam: Xfam::new(0),
+            xfam_mask: 0,
+            mr_owner: None,
+            mr_config_id: None,
+            mr_owner_config: None,
+        }
+    }
+
+    /// Require the bits of `mask` in the TD attributes to be those of
+    /// `expected`.
+    ///
+    /// This replaces the default of rejecting debug TDs, include the DEBUG
+    /// bit in `mask` to keep rejecting them.
+    pub fn td_attributes(mut self, expected: TdAttributes, mask: u64) -> Self {
+        self.td_attributes = expected;
+        self.td_attributes_mask = mask;
+        self
+    }
+
+    /// Require the bits of `mask` in the XFAM to be those of `expected`
+    pub fn xfam(mut self, expected: Xfam, mask: u64) -> Self {
+        self.xfam = expected;
+        self.xfam_mask = mask;
+        self
+    }
+
+    /// Require the MROWNER to be `mr_owner`
+    pub fn mr_owner(mut self, mr_owner: MrOwner) -> Self {
+        self.mr_owner = Some(mr_owner);
+        self
+    }
+
+    /// Require the MRCONFIGID to be `mr_config_id`
+    pub fn mr_config_id(mut self, mr_config_id: MrConfigId) -> Self {
+        self.mr_config_id = Some(mr_config_id);
+        self
+    }
+
+    /// Require the MROWNERCONFIG to be `mr_owner_config`
+    pub fn mr_owner_config(mut self, mr_owner_config: MrOwnerConfig) -> Self {
+        self.mr_owner_config = Some(mr_owner_config);
+        self
+    }
+
+    /// The allowed MRTD measurements
+    pub fn mr_tds(&self) -> &[MrTd] {
+        &self.mr_tds
+    }
+
+    fn mr_td_matches(&self, actual: &MrTd) -> Choice {
+        // Every allowed value is compared so the time doesn't reveal which
+        // one matched.
+        self.mr_tds
+            .iter()
+            .fold(Choice::from(0), |matched, allowed| {
+                matched | allowed.as_ref().ct_eq(actual.as_ref())
+            })
+    }
+}
+
+/// The outcome of each check of a [`TdPolicy`]
+#[derive(Clone, Debug)]
+pub struct TdPolicyValue {
+    report: TdReport,
+    mr_td: Choice,
+    td_attributes: Choice,
+    xfam: Choice,
+    mr_owner: Choice,
+    mr_config_id: Choice,
+    mr_owner_config: Choice,
+}
+
+impl TdPolicyValue {
+    /// The TD report which was verified
+    pub fn report(&self) -> &TdReport {
+        &self.report
+    }
+}
+
+fn masked_eq(actual: u64, expected: u64, mask: u64) -> Choice {
+    (actual & mask).ct_eq(&(expected & mask))
+}
+
+fn optional_eq<T: AsRef<[u8]>>(expected: &Option<T>, actual: &T) -> Choice {
+    expected.as_ref().map_or(Choice::from(1), |expected| {
+        expected.as_ref().ct_eq(actual.as_ref())
+    })
+}
+
+impl<E: Accessor<TdReportBody>> Verifier<E> for TdPolicy {
+    type Value = TdPolicyValue;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let body: TdReportBody = evidence.get();
+        let report = TdReport::from(&body);
+
+        let value = TdPolicyValue {
+            mr_td: self.mr_td_matches(&report.mr_td()),
+            td_attributes: masked_eq(
+                report.td_attributes().bits(),
+                self.td_attributes.bits(),
+                self.td_attributes_mask,
+            ),
+            xfam: masked_eq(report.xfam().bits(), self.xfam.bits(), self.xfam_mask),
+            mr_owner: optional_eq(&self.mr_owner, &report.mr_owner()),
+            mr_config_id: optional_eq(&self.mr_config_id, &report.mr_config_id()),
+            mr_owner_config: optional_eq(&self.mr_owner_config, &report.mr_owner_config()),
+            report,
+        };
+        let is_success = value.mr_td
+            & value.td_attributes
+            & value.xfam
+            & value.mr_owner
+            & value.mr_config_id
+            & value.mr_owner_config;
+
+        VerificationOutput::new(value, is_success)
+    }
+}
+
+/// Write one check of the policy, with the `actual` value on failure
+fn fmt_check(
+    f: &mut Formatter<'_>,
+    pad: usize,
+    is_success: Choice,
+    expectation: impl Display,
+    actual: impl Display,
+) -> core::fmt::Result {
+    writeln!(f)?;
+    let status = choice_to_status_message(is_success);
+    write!(f, "{:pad$}{status} {expectation}", "")?;
+    if is_success.unwrap_u8() == 0 {
+        write!(f, ", but the actual value was {actual}")?;
+    }
+    Ok(())
+}
+
+/// The allowed MRTDs, as a list
+struct MrTds<'a>(&'a [MrTd]);
+
+impl Display for MrTds<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[")?;
+        for (index, mr_td) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{mr_td}")?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl VerificationMessage<TdPolicyValue> for TdPolicy {
+    fn fmt_padded(
+        &self,
+        f: &mut Formatter<'_>,
+        pad: usize,
+        output: &VerificationOutput<TdPolicyValue>,
+    ) -> core::fmt::Result {
+        let status = choice_to_status_message(output.is_success());
+        write!(
+            f,
+            "{:pad$}{status} TD identity all of the following must be true:",
+            ""
+        )?;
+        let pad = pad + MESSAGE_INDENT;
+        let value = &output.value;
+        let report = &value.report;
+
+        fmt_check(
+            f,
+            pad,
+            value.mr_td,
+            format_args!("The MRTD should be one of {}", MrTds(&self.mr_tds)),
+            report.mr_td(),
+        )?;
+        fmt_check(
+            f,
+            pad,
+            value.td_attributes,
+            format_args!(
+                "The TD attributes should be {} with mask 0x{:016X}",
+                self.td_attributes, self.td_attributes_mask
+            ),
+            report.td_attributes(),
+        )?;
+        fmt_check(
+            f,
+            pad,
+            value.xfam,
+            format_args!(
+                "The XFAM should be {} with mask 0x{:016X}",
+                self.xfam, self.xfam_mask
+            ),
+            report.xfam(),
+        )?;
+        if let Some(mr_owner) = &self.mr_owner {
+            fmt_check(
+                f,
+                pad,
+                value.mr_owner,
+                format_args!("The MROWNER should be {mr_owner}"),
+                report.mr_owner(),
+            )?;
+        }
+        if let Some(mr_config_id) = &self.mr_config_id {
+            fmt_check(
+                f,
+                pad,
+                value.mr_config_id,
+                format_args!("The MRCONFIGID should be {mr_config_id}"),
+                report.mr_config_id(),
+            )?;
+        }
+        if let Some(mr_owner_config) = &self.mr_owner_config {
+            fmt_check(
+                f,
+                pad,
+                value.mr_owner_config,
+                format_args!("The MROWNERCONFIG should be {mr_owner_config}"),
+                report.mr_owner_config(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{quote4::test::tdx_quote, Quote4, VerificationTreeDisplay};
+    use alloc::format;
+
+    fn td_report_body() -> TdReportBody {
+        let quote = Quote4::try_from(tdx_quote()).expect("Failed to parse quote");
+        quote.report_body().clone()
+    }
+
+    fn hex(byte: &str, count: usize) -> alloc::string::String {
+        byte.repeat(count)
+    }
+
+    #[test]
+    fn td_policy_succeeds() {
+        let policy = TdPolicy::new([MrTd::new([1; 48]), MrTd::new([6; 48])])
+            .xfam(Xfam::new(0x0505_0505_0505_0505), u64::MAX)
+            .mr_owner(MrOwner::new([8; 48]))
+            .mr_config_id(MrConfigId::new([7; 48]))
+            .mr_owner_config(MrOwnerConfig::new([9; 48]));
+        let verification = policy.verify(&td_report_body());
+
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+        let displayable = VerificationTreeDisplay::new(&policy, verification);
+        let expected = format!(
+            r#"
+            - [x] TD identity all of the following must be true:
+              - [x] The MRTD should be one of [{}, {}]
+              - [x] The TD attributes should be 0x0000000000000000 with mask 0x0000000000000001
+              - [x] The XFAM should be 0x0505050505050505 with mask 0xFFFFFFFFFFFFFFFF
+              - [x] The MROWNER should be {}
+              - [x] The MRCONFIGID should be {}
+              - [x] The MROWNERCONFIG should be {}"#,
+            hex("01", 48),
+            hex("06", 48),
+            hex("08", 48),
+            hex("07", 48),
+            hex("09", 48),
+        );
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(&expected));
+    }
+
+    #[test]
+    fn unknown_mr_td_fails() {
+        let policy = TdPolicy::new([MrTd::new([1; 48])]);
+        let verification = policy.verify(&td_report_body());
+
+        assert_eq!(verification.is_success().unwrap_u8(), 0);
+        let displayable = VerificationTreeDisplay::new(&policy, verification);
+        let expected = format!(
+            r#"
+            - [ ] TD identity all of the following must be true:
+              - [ ] The MRTD should be one of [{}], but the actual value was {}
+              - [x] The TD attributes should be 0x0000000000000000 with mask 0x0000000000000001
+              - [x] The XFAM should be 0x0000000000000000 with mask 0x0000000000000000"#,
+            hex("01", 48),
+            hex("06", 48),
+        );
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(&expected));
+    }
+
+    #[test]
+    fn debug_td_fails_by_default() {
+        let mut body = tdx_quote();
+        // Set the DEBUG bit of the TD attributes, which are 0x04 bytes
+        body[crate::QuoteHeader::SIZE + 120] = 0x05;
+        let quote = Quote4::try_from(body).expect("Failed to parse quote");
+        let policy = TdPolicy::new([MrTd::new([6; 48])]);
+        let verification = policy.verify(quote.report_body());
+
+        assert_eq!(verification.is_success().unwrap_u8(), 0);
+        let displayable = VerificationTreeDisplay::new(&policy, verification);
+        let expected = r#"
+            - [ ] The TD attributes should be 0x0000000000000000 with mask 0x0000000000000001, but the actual value was 0x0404040404040405"#;
+        assert!(format!("\n{displayable}").contains(&textwrap::dedent(expected)[1..]));
+    }
+
+    #[test]
+    fn td_attributes_replace_debug_default() {
+        let policy = TdPolicy::new([MrTd::new([6; 48])]).td_attributes(
+            TdAttributes::new(0x0400_0000_0000_0000),
+            0xFF00_0000_0000_0000,
+        );
+        let verification = policy.verify(&td_report_body());
+
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+    }
+
+    #[test]
+    fn xfam_mismatch_fails() {
+        let policy = TdPolicy::new([MrTd::new([6; 48])]).xfam(Xfam::new(0), 0x01);
+        let verification = policy.verify(&td_report_body());
+
+        assert_eq!(verification.is_success().unwrap_u8(), 0);
+    }
+
+    #[test]
+    fn mr_owner_mismatch_fails() {
+        let policy = TdPolicy::new([MrTd::new([6; 48])]).mr_owner(MrOwner::new([0; 48]));
+        let verification = policy.verify(&td_report_body());
+
+        assert_eq!(verification.is_success().unwrap_u8(), 0);
+        let displayable = VerificationTreeDisplay::new(&policy, verification);
+        let expected = format!(
+            "- [ ] The MROWNER should be {}, but the actual value was {}",
+            hex("00", 48),
+            hex("08", 48),
+        );
+        assert!(format!("{displayable}").contains(&expected));
+    }
+
+    #[test]
+    fn mr_config_id_mismatch_fails() {
+        let policy = TdPolicy::new([MrTd::new([6; 48])]).mr_config_id(MrConfigId::new([0; 48]));
+        let verification = policy.verify(&td_report_body());
+
+        assert_eq!(verification.is_success().unwrap_u8(), 0);
+    }
+}
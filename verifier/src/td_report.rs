@@ -0,0 +1,378 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Typed view of the measurements of a TDX trust domain.
+//!
+//! [`TdReport`] exposes the fields of a [`TdReportBody`] that identify the
+//! trust domain, each as its own type so they can not be transposed, for
+//! example comparing an RTMR against an expected MRTD. The measurements are
+//! displayed and serialized as upper case hex strings, and parse from hex
+//! strings of either case.
+
+use crate::{Error, QuoteBody, TdReportBody};
+use alloc::{format, string::String};
+use core::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The debug bit of [`TdAttributes`]
+pub(crate) const TD_ATTRIBUTES_DEBUG: u64 = 1;
+
+macro_rules! td_measurement {
+    ($(#[$meta:meta])* $name:ident, $size:expr, $kind:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name([u8; $size]);
+
+        impl $name {
+            /// Create a new instance from the raw bytes
+            pub const fn new(bytes: [u8; $size]) -> Self {
+                Self(bytes)
+            }
+
+            /// The raw bytes
+            pub const fn as_bytes(&self) -> &[u8; $size] {
+                &self.0
+            }
+
+            /// Try to parse from a hex string, of either case.
+            ///
+            /// # Errors
+            /// `Error::TdMeasurement` if `hex` is not valid hex of the
+            /// correct length.
+            pub fn try_from_hex(hex: &str) -> Result<Self, Error> {
+                let mut bytes = [0u8; $size];
+                hex::decode_to_slice(hex, &mut bytes).map_err(|e| Error::TdMeasurement {
+                    kind: $kind.into(),
+                    error: format!("{e}"),
+                })?;
+                Ok(Self(bytes))
+            }
+        }
+
+        impl From<[u8; $size]> for $name {
+            fn from(bytes: [u8; $size]) -> Self {
+                Self(bytes)
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl PartialEq<[u8; $size]> for $name {
+            fn eq(&self, other: &[u8; $size]) -> bool {
+                &self.0 == other
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(hex: &str) -> Result<Self, Self::Err> {
+                Self::try_from_hex(hex)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                for byte in self.0 {
+                    write!(f, "{byte:02X}")?;
+                }
+                Ok(())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let hex = String::deserialize(deserializer)?;
+                Self::try_from_hex(&hex).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+td_measurement!(
+    /// The measurement of the initial contents of a TD, MRTD
+    MrTd,
+    48,
+    "MRTD"
+);
+
+td_measurement!(
+    /// A runtime extendable measurement register of a TD, RTMR
+    Rtmr,
+    48,
+    "RTMR"
+);
+
+td_measurement!(
+    /// Software defined ID for the owner of a TD, MROWNER
+    MrOwner,
+    48,
+    "MROWNER"
+);
+
+td_measurement!(
+    /// Software defined ID for the configuration of a TD, MRCONFIGID
+    MrConfigId,
+    48,
+    "MRCONFIGID"
+);
+
+td_measurement!(
+    /// Software defined ID for the owner defined configuration of a TD,
+    /// MROWNERCONFIG
+    MrOwnerConfig,
+    48,
+    "MROWNERCONFIG"
+);
+
+td_measurement!(
+    /// Data provided by a TD when requesting the quote, REPORTDATA
+    TdReportData,
+    64,
+    "REPORTDATA"
+);
+
+macro_rules! td_flags {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        pub struct $name(u64);
+
+        impl $name {
+            /// Create a new instance from the flag bits
+            pub const fn new(bits: u64) -> Self {
+                Self(bits)
+            }
+
+            /// The flag bits
+            pub const fn bits(&self) -> u64 {
+                self.0
+            }
+
+            /// Whether all of the bits of `mask` are set
+            pub const fn contains(&self, mask: u64) -> bool {
+                self.0 & mask == mask
+            }
+        }
+
+        /// The little endian bytes from the TD report body
+        impl From<[u8; 8]> for $name {
+            fn from(bytes: [u8; 8]) -> Self {
+                Self(u64::from_le_bytes(bytes))
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                write!(f, "0x{:016X}", self.0)
+            }
+        }
+    };
+}
+
+td_flags!(
+    /// The attributes of a TD, TDATTRIBUTES
+    TdAttributes
+);
+
+td_flags!(
+    /// The extended features (XSAVE) available to a TD, XFAM
+    Xfam
+);
+
+impl TdAttributes {
+    /// Whether the TD is a debug TD, whose memory and state can be inspected
+    /// by the host
+    pub const fn is_debug(&self) -> bool {
+        self.contains(TD_ATTRIBUTES_DEBUG)
+    }
+}
+
+/// The identifying fields of a TDX trust domain's report body, as typed
+/// values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TdReport {
+    mr_td: MrTd,
+    rtmrs: [Rtmr; 4],
+    mr_owner: MrOwner,
+    mr_config_id: MrConfigId,
+    mr_owner_config: MrOwnerConfig,
+    td_attributes: TdAttributes,
+    xfam: Xfam,
+    report_data: TdReportData,
+}
+
+impl TdReport {
+    /// The measurement of the initial contents of the TD
+    pub fn mr_td(&self) -> MrTd {
+        self.mr_td
+    }
+
+    /// The runtime extendable measurement registers, RTMR0 through RTMR3
+    pub fn rtmrs(&self) -> [Rtmr; 4] {
+        self.rtmrs
+    }
+
+    /// The runtime extendable measurement register at `index`, `None` if
+    /// `index` is greater than 3
+    pub fn rtmr(&self, index: usize) -> Option<Rtmr> {
+        self.rtmrs.get(index).copied()
+    }
+
+    /// Software defined ID for the TD's owner
+    pub fn mr_owner(&self) -> MrOwner {
+        self.mr_owner
+    }
+
+    /// Software defined ID for the TD's configuration
+    pub fn mr_config_id(&self) -> MrConfigId {
+        self.mr_config_id
+    }
+
+    /// Software defined ID for the owner defined configuration of the TD
+    pub fn mr_owner_config(&self) -> MrOwnerConfig {
+        self.mr_owner_config
+    }
+
+    /// The attributes of the TD
+    pub fn td_attributes(&self) -> TdAttributes {
+        self.td_attributes
+    }
+
+    /// The extended features available to the TD
+    pub fn xfam(&self) -> Xfam {
+        self.xfam
+    }
+
+    /// The data provided by the TD when requesting the quote
+    pub fn report_data(&self) -> TdReportData {
+        self.report_data
+    }
+}
+
+impl From<&TdReportBody> for TdReport {
+    fn from(body: &TdReportBody) -> Self {
+        let rtmrs = body.rtmrs();
+        Self {
+            mr_td: MrTd::new(*body.mr_td()),
+            rtmrs: [
+                Rtmr::new(rtmrs[0]),
+                Rtmr::new(rtmrs[1]),
+                Rtmr::new(rtmrs[2]),
+                Rtmr::new(rtmrs[3]),
+            ],
+            mr_owner: MrOwner::new(*body.mr_owner()),
+            mr_config_id: MrConfigId::new(*body.mr_config_id()),
+            mr_owner_config: MrOwnerConfig::new(*body.mr_owner_config()),
+            td_attributes: TdAttributes::from(*body.td_attributes()),
+            xfam: Xfam::from(*body.xfam()),
+            report_data: TdReportData::new(*body.report_data()),
+        }
+    }
+}
+
+impl QuoteBody {
+    /// The typed view of a TD report body, `None` for a SGX report body
+    pub fn td_report(&self) -> Option<TdReport> {
+        match self {
+            QuoteBody::Td(body) => Some(TdReport::from(body.as_ref())),
+            QuoteBody::Sgx(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{quote4::test::tdx_quote, Quote4};
+    use alloc::string::ToString;
+    use assert_matches::assert_matches;
+
+    fn td_report() -> TdReport {
+        let quote = Quote4::try_from(tdx_quote()).expect("Failed to parse quote");
+        TdReport::from(quote.report_body())
+    }
+
+    #[test]
+    fn td_report_fields() {
+        let report = td_report();
+        assert_eq!(report.mr_td(), [6; 48]);
+        assert_eq!(report.mr_config_id(), [7; 48]);
+        assert_eq!(report.mr_owner(), [8; 48]);
+        assert_eq!(report.mr_owner_config(), [9; 48]);
+        assert_eq!(
+            report.rtmrs(),
+            [
+                Rtmr::new([10; 48]),
+                Rtmr::new([11; 48]),
+                Rtmr::new([12; 48]),
+                Rtmr::new([13; 48])
+            ]
+        );
+        assert_eq!(report.rtmr(0), Some(Rtmr::new([10; 48])));
+        assert_eq!(report.rtmr(4), None);
+        assert_eq!(report.report_data(), [14; 64]);
+        assert_eq!(report.td_attributes().bits(), 0x0404_0404_0404_0404);
+        assert_eq!(report.xfam(), Xfam::new(0x0505_0505_0505_0505));
+    }
+
+    #[test]
+    fn td_report_from_quote_body() {
+        let quote = Quote4::try_from(tdx_quote()).expect("Failed to parse quote");
+        let body = QuoteBody::from(&quote);
+        assert_eq!(body.td_report(), Some(td_report()));
+    }
+
+    #[test]
+    fn debug_td_attributes() {
+        assert!(TdAttributes::from([1, 0, 0, 0, 0, 0, 0, 0]).is_debug());
+        assert!(!TdAttributes::from([0, 0, 0, 0, 0, 0, 0, 0x10]).is_debug());
+        assert!(!td_report().td_attributes().is_debug());
+    }
+
+    #[test]
+    fn td_flags_display() {
+        assert_eq!(
+            TdAttributes::new(0x1000_0001).to_string(),
+            "0x0000000010000001"
+        );
+    }
+
+    #[test]
+    fn td_measurement_hex_round_trip() {
+        let rtmr = td_report().rtmr(1).expect("Missing RTMR1");
+        let hex = rtmr.to_string();
+        assert_eq!(hex, "0B".repeat(48));
+        assert_eq!(Rtmr::try_from_hex(&hex.to_lowercase()), Ok(rtmr));
+        assert_eq!(hex.parse::<Rtmr>(), Ok(rtmr));
+    }
+
+    #[test]
+    fn td_measurement_serde() {
+        let mr_td = td_report().mr_td();
+        let json = serde_json::to_string(&mr_td).expect("Failed to serialize");
+        assert_eq!(json, format!("\"{}\"", "06".repeat(48)));
+        let decoded: MrTd = serde_json::from_str(&json).expect("Failed to deserialize");
+        assert_eq!(decoded, mr_td);
+    }
+
+    #[test]
+    fn td_measurement_wrong_length() {
+        assert_matches!(
+            MrTd::try_from_hex("0102"),
+            Err(Error::TdMeasurement { kind, .. }) if kind == "MRTD"
+        );
+    }
+}
@@ -0,0 +1,474 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Verification of the TDX module, the SEAM, of a TDX quote.
+//!
+//! The TD report body describes the TDX module which the trust domain ran on.
+//! Version 3 of the TDX TCB info describes the expected TDX module in
+//! `tdxModule`, and, since TDX 1.5, the identities and TCB levels of each
+//! major version of the TDX module in `tdxModuleIdentities`. See
+//! <https://api.portal.trustedservices.intel.com/documentation#pcs-tcb-info-model-v3>
+//!
+//! The major version of the TDX module is the second byte of the TEE TCB SVN,
+//! and the SVN of the TDX module is the first byte. Major version 0 predates
+//! the TDX module identities, only `tdxModule` applies.
+
+use crate::{
+    advisories::{Advisories, AdvisoryStatus},
+    choice_to_status_message, Accessor, Error, TcbInfo, TdReportBody, VerificationMessage,
+    VerificationOutput, Verifier,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Formatter;
+use der::DateTime;
+use serde::Deserialize;
+
+/// The index of the TDX module SVN in the TEE TCB SVN
+const TDX_MODULE_SVN_INDEX: usize = 0;
+/// The index of the TDX module major version in the TEE TCB SVN
+const TDX_MODULE_VERSION_INDEX: usize = 1;
+
+/// The expected TDX module, the `tdxModule` member of a TDX TCB info
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TdxModule {
+    #[serde(with = "hex", rename = "mrsigner")]
+    mr_signer: [u8; 48],
+    #[serde(with = "hex")]
+    attributes: [u8; 8],
+    #[serde(with = "hex")]
+    attributes_mask: [u8; 8],
+}
+
+impl TdxModule {
+    /// The expected MRSIGNERSEAM of the TDX module
+    pub fn mr_signer(&self) -> &[u8; 48] {
+        &self.mr_signer
+    }
+
+    /// The expected SEAM attributes of the TDX module
+    pub fn attributes(&self) -> &[u8; 8] {
+        &self.attributes
+    }
+
+    /// The mask to apply to the SEAM attributes before comparing them
+    pub fn attributes_mask(&self) -> &[u8; 8] {
+        &self.attributes_mask
+    }
+
+    fn verify(&self, report_body: &TdReportBody) -> Result<(), Error> {
+        verify_seam(
+            &self.mr_signer,
+            &self.attributes,
+            &self.attributes_mask,
+            report_body,
+        )
+    }
+}
+
+/// The identity of a major version of the TDX module, an entry of the
+/// `tdxModuleIdentities` member of a TDX TCB info
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TdxModuleIdentity {
+    id: String,
+    #[serde(with = "hex", rename = "mrsigner")]
+    mr_signer: [u8; 48],
+    #[serde(with = "hex")]
+    attributes: [u8; 8],
+    #[serde(with = "hex")]
+    attributes_mask: [u8; 8],
+    tcb_levels: Vec<TdxModuleTcbLevel>,
+}
+
+impl TdxModuleIdentity {
+    /// The ID of the identity, "TDX_" followed by the two digit major version
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The expected MRSIGNERSEAM of the TDX module
+    pub fn mr_signer(&self) -> &[u8; 48] {
+        &self.mr_signer
+    }
+
+    /// The expected SEAM attributes of the TDX module
+    pub fn attributes(&self) -> &[u8; 8] {
+        &self.attributes
+    }
+
+    /// The mask to apply to the SEAM attributes before comparing them
+    pub fn attributes_mask(&self) -> &[u8; 8] {
+        &self.attributes_mask
+    }
+
+    /// The TCB levels of the TDX module, highest first
+    pub fn tcb_levels(&self) -> &[TdxModuleTcbLevel] {
+        &self.tcb_levels
+    }
+
+    /// Get the TCB level of a TDX module with the `isv_svn`.
+    ///
+    /// The level is the first one whose SVN is less than or equal to
+    /// `isv_svn`.
+    ///
+    /// # Errors
+    /// `Error::UnsupportedTdxModuleTcbLevel` if `isv_svn` is below all of the
+    /// levels.
+    pub fn tcb_level(&self, isv_svn: u8) -> Result<&TdxModuleTcbLevel, Error> {
+        self.tcb_levels
+            .iter()
+            .find(|level| level.tcb.isv_svn <= isv_svn)
+            .ok_or(Error::UnsupportedTdxModuleTcbLevel(isv_svn))
+    }
+
+    fn verify(&self, report_body: &TdReportBody) -> Result<(), Error> {
+        verify_seam(
+            &self.mr_signer,
+            &self.attributes,
+            &self.attributes_mask,
+            report_body,
+        )
+    }
+}
+
+/// A TCB level of a [`TdxModuleIdentity`]
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TdxModuleTcbLevel {
+    tcb: TdxModuleTcb,
+    tcb_date: String,
+    tcb_status: AdvisoryStatus,
+    #[serde(rename = "advisoryIDs", default)]
+    advisory_ids: Vec<String>,
+}
+
+impl TdxModuleTcbLevel {
+    /// The minimum SVN of the TDX module for this level
+    pub fn isv_svn(&self) -> u8 {
+        self.tcb.isv_svn
+    }
+
+    /// The date of the TCB recovery this level is for, its `tcbDate`.
+    ///
+    /// # Errors
+    /// `Error::Der` if the `tcbDate` is not a valid time.
+    pub fn tcb_date(&self) -> Result<DateTime, Error> {
+        Ok(self.tcb_date.parse::<DateTime>()?)
+    }
+
+    /// The status of a TDX module at this level
+    pub fn tcb_status(&self) -> AdvisoryStatus {
+        self.tcb_status
+    }
+
+    /// The advisories of a TDX module at this level
+    pub fn advisories(&self) -> Advisories {
+        Advisories::new(&self.advisory_ids, self.tcb_status)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+struct TdxModuleTcb {
+    #[serde(rename = "isvsvn")]
+    isv_svn: u8,
+}
+
+/// Compare the MRSIGNERSEAM and the masked SEAM attributes of the
+/// `report_body` with the expected values
+fn verify_seam(
+    mr_signer: &[u8; 48],
+    attributes: &[u8; 8],
+    attributes_mask: &[u8; 8],
+    report_body: &TdReportBody,
+) -> Result<(), Error> {
+    if report_body.mr_signer_seam() != mr_signer {
+        return Err(Error::TdxModuleMismatch("MRSIGNERSEAM".to_string()));
+    }
+    let masked_equal = report_body
+        .seam_attributes()
+        .iter()
+        .zip(attributes)
+        .zip(attributes_mask)
+        .all(|((actual, expected), mask)| actual & mask == expected & mask);
+    if !masked_equal {
+        return Err(Error::TdxModuleMismatch("SEAM attributes".to_string()));
+    }
+    Ok(())
+}
+
+impl TcbInfo {
+    /// Appraise the TDX module of the `report_body` against the TDX module
+    /// identity of `self`.
+    ///
+    /// The MRSIGNERSEAM and the masked SEAM attributes must match the TDX
+    /// module identity for the TDX module's major version. For major version
+    /// 0 these are compared with `tdxModule`.
+    ///
+    /// # Returns
+    /// The advisories of the TDX module's TCB level. These converge with the
+    /// platform's advisories the same way as the QE's, see
+    /// [`Advisories::converge_with_qe()`]. `None` for major version 0, as it
+    /// has no TCB levels of its own.
+    ///
+    /// # Errors
+    /// * `Error::TdxModuleIdentityNotFound` if `self` has no identity for the
+    ///   TDX module.
+    /// * `Error::TdxModuleMismatch` if the MRSIGNERSEAM or SEAM attributes
+    ///   don't match the identity.
+    /// * `Error::UnsupportedTdxModuleTcbLevel` if the TDX module SVN is below
+    ///   all the TCB levels of the identity.
+    pub fn tdx_module_advisories(
+        &self,
+        report_body: &TdReportBody,
+    ) -> Result<Option<Advisories>, Error> {
+        let tee_tcb_svn = report_body.tee_tcb_svn();
+        let version = tee_tcb_svn[TDX_MODULE_VERSION_INDEX];
+        if version == 0 {
+            let tdx_module = self
+                .tdx_module()
+                .ok_or_else(|| Error::TdxModuleIdentityNotFound("tdxModule".to_string()))?;
+            tdx_module.verify(report_body)?;
+            return Ok(None);
+        }
+
+        let id = format!("TDX_{version:02X}");
+        let identity = self
+            .tdx_module_identities()
+            .iter()
+            .find(|identity| identity.id == id)
+            .ok_or(Error::TdxModuleIdentityNotFound(id))?;
+        identity.verify(report_body)?;
+        let level = identity.tcb_level(tee_tcb_svn[TDX_MODULE_SVN_INDEX])?;
+        Ok(Some(level.advisories()))
+    }
+}
+
+/// Verifier for the TDX module of a TD report body.
+///
+/// Succeeds when the TDX module matches the TDX module identity of the TCB
+/// info and its TCB level is not `Revoked`, see
+/// [`TcbInfo::tdx_module_advisories()`].
+#[derive(Clone, Debug)]
+pub struct TdxModuleVerifier {
+    tcb_info: TcbInfo,
+}
+
+impl TdxModuleVerifier {
+    /// Create a new instance.
+    ///
+    /// # Arguments
+    /// * `tcb_info` - The TDX TCB info for the platform, it should already
+    ///   have been verified.
+    pub fn new(tcb_info: TcbInfo) -> Self {
+        Self { tcb_info }
+    }
+}
+
+impl<E: Accessor<TdReportBody>> Verifier<E> for TdxModuleVerifier {
+    type Value = Option<Error>;
+    fn verify(&self, evidence: &E) -> VerificationOutput<Self::Value> {
+        let report_body = evidence.get();
+        let error = match self.tcb_info.tdx_module_advisories(&report_body) {
+            Ok(Some(advisories)) if advisories.status() == AdvisoryStatus::Revoked => Some(
+                Error::TdxModuleRevoked(report_body.tee_tcb_svn()[TDX_MODULE_SVN_INDEX]),
+            ),
+            Ok(_) => None,
+            Err(error) => Some(error),
+        };
+        let is_success = error.is_none() as u8;
+        VerificationOutput::new(error, is_success.into())
+    }
+}
+
+impl VerificationMessage<Option<Error>> for TdxModuleVerifier {
+    fn fmt_padded(
+        &self,
+        f: &mut Formatter<'_>,
+        pad: usize,
+        result: &VerificationOutput<Option<Error>>,
+    ) -> core::fmt::Result {
+        let is_success = result.is_success();
+        let status = choice_to_status_message(is_success);
+        write!(f, "{:pad$}{status} ", "")?;
+
+        match result.value() {
+            None => write!(
+                f,
+                "The TDX module matches the TDX module identity of the TCB info"
+            ),
+            Some(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{quote4::Reader, SignedTcbInfo, VerificationTreeDisplay};
+    use yare::parameterized;
+
+    const TDX_TCB_INFO: &str = r#"{
+        "id":"TDX","version":3,"issueDate":"2024-05-01T00:00:00Z",
+        "nextUpdate":"2024-05-31T00:00:00Z","fmspc":"00806F050000","pceId":"0000",
+        "tcbType":0,"tcbEvaluationDataNumber":16,
+        "tdxModule":{
+            "mrsigner":"000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+            "attributes":"0000000000000000","attributesMask":"FFFFFFFFFFFFFFFF"
+        },
+        "tdxModuleIdentities":[{
+            "id":"TDX_01",
+            "mrsigner":"000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+            "attributes":"0000000000000000","attributesMask":"FFFFFFFFFFFFFFFF",
+            "tcbLevels":[
+                {"tcb":{"isvsvn":4},"tcbDate":"2024-03-13T00:00:00Z","tcbStatus":"UpToDate"},
+                {"tcb":{"isvsvn":2},"tcbDate":"2023-08-09T00:00:00Z","tcbStatus":"OutOfDate","advisoryIDs":["INTEL-SA-00960"]},
+                {"tcb":{"isvsvn":1},"tcbDate":"2023-02-15T00:00:00Z","tcbStatus":"Revoked"}
+            ]
+        }],
+        "tcbLevels":[]
+    }"#;
+
+    fn tcb_info() -> TcbInfo {
+        TcbInfo::try_from(TDX_TCB_INFO).expect("Failed to parse TCB info")
+    }
+
+    /// A TD report body for a TDX module of `version` and `svn`
+    fn report_body(version: u8, svn: u8, mr_signer_seam: u8, seam_attributes: u8) -> TdReportBody {
+        let mut bytes = [0u8; TdReportBody::SIZE];
+        bytes[TDX_MODULE_SVN_INDEX] = svn;
+        bytes[TDX_MODULE_VERSION_INDEX] = version;
+        // The tee_tcb_svn, then mr_seam, precede mr_signer_seam
+        bytes[16 + 48..16 + 48 + 48].fill(mr_signer_seam);
+        bytes[16 + 48 + 48] = seam_attributes;
+        TdReportBody::read(&mut Reader::new(&bytes), false).expect("Failed to read report body")
+    }
+
+    #[test]
+    fn tcb_info_without_tdx_module() {
+        let signed_tcb_info = SignedTcbInfo::try_from(include_str!(
+            "../data/tests/fmspc_00906ED50000_2023_07_12.json"
+        ))
+        .expect("Failed to parse signed TCB info");
+        let tcb_info = TcbInfo::try_from(&signed_tcb_info).expect("Failed to parse TCB info");
+        assert_eq!(tcb_info.tdx_module(), None);
+        assert!(tcb_info.tdx_module_identities().is_empty());
+        assert_eq!(
+            tcb_info.tdx_module_advisories(&report_body(0, 0, 0, 0)),
+            Err(Error::TdxModuleIdentityNotFound("tdxModule".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_tdx_module_identities() {
+        let tcb_info = tcb_info();
+        let tdx_module = tcb_info.tdx_module().expect("Missing TDX module");
+        assert_eq!(tdx_module.mr_signer(), &[0; 48]);
+        assert_eq!(tdx_module.attributes_mask(), &[0xFF; 8]);
+
+        let identities = tcb_info.tdx_module_identities();
+        assert_eq!(identities.len(), 1);
+        assert_eq!(identities[0].id(), "TDX_01");
+        assert_eq!(identities[0].tcb_levels().len(), 3);
+        assert_eq!(identities[0].tcb_levels()[1].isv_svn(), 2);
+    }
+
+    #[test]
+    fn major_version_0_uses_tdx_module() {
+        assert_eq!(
+            tcb_info().tdx_module_advisories(&report_body(0, 0, 0, 0)),
+            Ok(None)
+        );
+    }
+
+    #[parameterized(
+        up_to_date = { 4, AdvisoryStatus::UpToDate },
+        above_highest = { 9, AdvisoryStatus::UpToDate },
+        out_of_date = { 3, AdvisoryStatus::OutOfDate },
+        out_of_date_at_threshold = { 2, AdvisoryStatus::OutOfDate },
+        revoked = { 1, AdvisoryStatus::Revoked },
+    )]
+    fn seam_svn_threshold(svn: u8, status: AdvisoryStatus) {
+        let advisories = tcb_info()
+            .tdx_module_advisories(&report_body(1, svn, 0, 0))
+            .expect("Failed to appraise TDX module")
+            .expect("Missing TDX module advisories");
+        assert_eq!(advisories.status(), status);
+    }
+
+    #[test]
+    fn seam_svn_below_all_levels() {
+        assert_eq!(
+            tcb_info().tdx_module_advisories(&report_body(1, 0, 0, 0)),
+            Err(Error::UnsupportedTdxModuleTcbLevel(0))
+        );
+    }
+
+    #[test]
+    fn unknown_major_version() {
+        assert_eq!(
+            tcb_info().tdx_module_advisories(&report_body(2, 4, 0, 0)),
+            Err(Error::TdxModuleIdentityNotFound("TDX_02".to_string()))
+        );
+    }
+
+    #[parameterized(
+        mr_signer_seam = { 1, 0, "MRSIGNERSEAM" },
+        seam_attributes = { 0, 1, "SEAM attributes" },
+    )]
+    fn tdx_module_mismatch(mr_signer_seam: u8, seam_attributes: u8, kind: &str) {
+        for version in [0, 1] {
+            assert_eq!(
+                tcb_info().tdx_module_advisories(&report_body(
+                    version,
+                    4,
+                    mr_signer_seam,
+                    seam_attributes
+                )),
+                Err(Error::TdxModuleMismatch(kind.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn verifier_succeeds() {
+        let verifier = TdxModuleVerifier::new(tcb_info());
+        let verification = verifier.verify(&report_body(1, 3, 0, 0));
+
+        assert_eq!(verification.is_success().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [x] The TDX module matches the TDX module identity of the TCB info"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn verifier_fails_for_revoked_tdx_module() {
+        let verifier = TdxModuleVerifier::new(tcb_info());
+        let verification = verifier.verify(&report_body(1, 1, 0, 0));
+
+        assert_eq!(verification.is_failure().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [ ] The TDX module SVN 1 is at a revoked TCB level"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+
+    #[test]
+    fn verifier_fails_for_mismatched_tdx_module() {
+        let verifier = TdxModuleVerifier::new(tcb_info());
+        let verification = verifier.verify(&report_body(1, 4, 2, 0));
+
+        assert_eq!(verification.is_failure().unwrap_u8(), 1);
+
+        let displayable = VerificationTreeDisplay::new(&verifier, verification);
+        let expected = r#"
+            - [ ] The MRSIGNERSEAM of the TDX module does not match the TCB info"#;
+        assert_eq!(format!("\n{displayable}"), textwrap::dedent(expected));
+    }
+}
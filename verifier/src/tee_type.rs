@@ -0,0 +1,126 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! The type of Trusted Execution Environment (TEE) which produced a quote
+
+use crate::Error;
+use serde::{Deserialize, Serialize};
+
+/// Offset of the TEE type in the quote header. See the `tee_type` field in
+/// Table 3 of
+/// <https://download.01.org/intel-sgx/latest/dcap-latest/linux/docs/Intel_TDX_DCAP_Quoting_Library_API.pdf>
+const TEE_TYPE_OFFSET: usize = 4;
+
+/// The type of Trusted Execution Environment (TEE) which produced a quote
+#[derive(displaydoc::Display, Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum TeeType {
+    /// SGX
+    Sgx,
+    /// TDX
+    Tdx,
+}
+
+impl TeeType {
+    /// The raw value of the TEE type in a quote header
+    pub fn value(&self) -> u32 {
+        match self {
+            TeeType::Sgx => 0x0000_0000,
+            TeeType::Tdx => 0x0000_0081,
+        }
+    }
+
+    /// Get the TEE type from the header of the `quote` bytes.
+    ///
+    /// Returns `None` if the `quote` is too short or the TEE type is unknown.
+    pub fn from_quote(quote: &[u8]) -> Option<Self> {
+        let bytes = quote.get(TEE_TYPE_OFFSET..TEE_TYPE_OFFSET + 4)?;
+        let value = u32::from_le_bytes(bytes.try_into().ok()?);
+        [TeeType::Sgx, TeeType::Tdx]
+            .into_iter()
+            .find(|tee_type| tee_type.value() == value)
+    }
+
+    /// Ensure the `quote` bytes were produced by this TEE type.
+    ///
+    /// Quotes which are too short, or have an unknown TEE type, are left to
+    /// be rejected when parsing the quote.
+    ///
+    /// # Errors
+    /// `Error::TeeTypeMismatch` if the quote is from a different TEE type.
+    pub fn verify_quote(&self, quote: &[u8]) -> Result<(), Error> {
+        match Self::from_quote(quote) {
+            Some(actual) if actual != *self => Err(Error::TeeTypeMismatch {
+                expected: *self,
+                actual,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use yare::parameterized;
+
+    const QUOTE_BYTES: &[u8] = include_bytes!("../data/tests/hw_quote.dat");
+
+    fn tdx_quote() -> [u8; 48] {
+        let mut header = [0u8; 48];
+        header[..2].copy_from_slice(&4u16.to_le_bytes());
+        header[2..4].copy_from_slice(&2u16.to_le_bytes());
+        header[4..8].copy_from_slice(&0x81u32.to_le_bytes());
+        header
+    }
+
+    #[test]
+    fn sgx_tee_type_from_quote() {
+        assert_eq!(TeeType::from_quote(QUOTE_BYTES), Some(TeeType::Sgx));
+    }
+
+    #[test]
+    fn tdx_tee_type_from_quote() {
+        assert_eq!(TeeType::from_quote(&tdx_quote()), Some(TeeType::Tdx));
+    }
+
+    #[parameterized(
+        too_short = { &QUOTE_BYTES[..7] },
+        unknown = { &[3, 0, 2, 0, 1, 0, 0, 0] },
+    )]
+    fn no_tee_type_from_quote(quote: &[u8]) {
+        assert_eq!(TeeType::from_quote(quote), None);
+        assert_eq!(TeeType::Sgx.verify_quote(quote), Ok(()));
+    }
+
+    #[test]
+    fn sgx_quote_for_sgx() {
+        assert_eq!(TeeType::Sgx.verify_quote(QUOTE_BYTES), Ok(()));
+    }
+
+    #[test]
+    fn sgx_quote_for_tdx() {
+        assert_eq!(
+            TeeType::Tdx.verify_quote(QUOTE_BYTES),
+            Err(Error::TeeTypeMismatch {
+                expected: TeeType::Tdx,
+                actual: TeeType::Sgx
+            })
+        );
+    }
+
+    #[test]
+    fn tdx_quote_for_sgx() {
+        let error = TeeType::Sgx.verify_quote(&tdx_quote()).unwrap_err();
+        assert_eq!(
+            error,
+            Error::TeeTypeMismatch {
+                expected: TeeType::Sgx,
+                actual: TeeType::Tdx
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "TEE type mismatch, expecting SGX got TDX"
+        );
+    }
+}
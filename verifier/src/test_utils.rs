@@ -0,0 +1,807 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Building quotes and collateral for unit tests, without SGX hardware
+//!
+//! This module is only available with the `test-utils` feature. The quotes
+//! from [`QuoteBuilder`] are syntactically valid DCAP v3 quotes, every
+//! signature in them is valid, but the PCK certificate chain is rooted in a
+//! self-signed test root instead of the Intel SGX Root CA. Use
+//! [`TestQuote::root_certificate()`] as the trust anchor when verifying them.
+//!
+//! The keys are fixed, anyone can sign with them. Never trust the test root
+//! outside of tests.
+//!
+//! [`collateral()`] builds the DCAP collateral from the raw responses of a
+//! PCS or PCCS, for tests which fetch their own collateral.
+
+use crate::{
+    ffi, platform_configuration::SgxExtension, Attributes, CpuSvn, Error, Fmspc, IsvProductId,
+    IsvSvn, MrEnclave, MrSigner, PceId, Ppid, ReportData, INTEL_QE_VENDOR_ID,
+};
+use alloc::{string::String, vec, vec::Vec};
+use der::{
+    asn1::{BitString, ObjectIdentifier, OctetString, UintRef, UtcTime},
+    oid::AssociatedOid,
+    pem::LineEnding,
+    Any, DateTime, Encode, EncodePem, EncodeValue, Sequence, Tag, Tagged,
+};
+use mc_sgx_core_sys_types::sgx_attributes_t;
+use mc_sgx_dcap_types::Collateral;
+use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+use sha2::{Digest, Sha256};
+use x509_cert::{
+    ext::{pkix::BasicConstraints, Extension},
+    name::Name,
+    serial_number::SerialNumber,
+    spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned},
+    time::{Time, Validity},
+    Certificate, TbsCertificate, Version,
+};
+
+const ECDSA_WITH_SHA_256_OID: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+const EC_PUBLIC_KEY_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+const PRIME_256_V1_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+
+const SGX_EXTENSIONS_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1");
+const PPID_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.1");
+const TCB_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.2");
+const PCE_SVN_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.2.17");
+const CPU_SVN_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.2.18");
+const PCE_ID_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.3");
+const FMSPC_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.4");
+const SGX_TYPE_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113741.1.13.1.5");
+
+/// The private keys, the chain is root -> platform CA -> PCK leaf, the leaf
+/// signs the QE report, and the attestation key signs the quote.
+const ROOT_KEY: [u8; 32] = [0x11; 32];
+const PLATFORM_CA_KEY: [u8; 32] = [0x22; 32];
+const PCK_KEY: [u8; 32] = [0x33; 32];
+const ATTESTATION_KEY: [u8; 32] = [0x44; 32];
+
+const ROOT_SUBJECT: &str = "CN=Intel SGX Root CA,O=mc-attestation-verifier test-utils";
+const PLATFORM_CA_SUBJECT: &str =
+    "CN=Intel SGX PCK Platform CA,O=mc-attestation-verifier test-utils";
+const PCK_SUBJECT: &str = "CN=Intel SGX PCK Certificate,O=mc-attestation-verifier test-utils";
+
+/// The MRSIGNER of Intel's quoting enclave
+const QE_MR_SIGNER: [u8; 32] = [
+    0x8C, 0x4F, 0x57, 0x75, 0xD7, 0x96, 0x50, 0x3E, 0x96, 0x13, 0x7F, 0x77, 0xC6, 0x8A, 0x82, 0x9A,
+    0x00, 0x56, 0xAC, 0x8D, 0xED, 0x70, 0x14, 0x0B, 0x08, 0x1B, 0x09, 0x44, 0x90, 0xC5, 0x7B, 0xFF,
+];
+
+/// `INIT` and `MODE64BIT`, with x87 and SSE enabled
+const ENCLAVE_ATTRIBUTES: sgx_attributes_t = sgx_attributes_t {
+    flags: 0x05,
+    xfrm: 0x03,
+};
+
+/// `INIT` and `PROVISIONKEY`, as in Intel's QE identity
+const QE_ATTRIBUTES: sgx_attributes_t = sgx_attributes_t {
+    flags: 0x11,
+    xfrm: 0x00,
+};
+
+const QUOTE_VERSION: u16 = 3;
+const ECDSA_P256_KEY_TYPE: u16 = 2;
+const REPORT_BODY_SIZE: usize = 384;
+const PCK_CERTIFICATE_CHAIN_TYPE: u16 = 5;
+
+/// Builder for a [`TestQuote`]
+///
+/// Anything not set is zero, except the attributes, which default to a
+/// production enclave, and the FMSPC and PPID, which are non zero test
+/// values.
+///
+/// ```
+/// use mc_attestation_verifier::{
+///     test_utils::QuoteBuilder, CpuSvn, IsvSvn, MrEnclave, MrSigner,
+/// };
+///
+/// let quote = QuoteBuilder::new()
+///     .mr_enclave(MrEnclave::from([1; 32]))
+///     .mr_signer(MrSigner::from([2; 32]))
+///     .isv_svn(IsvSvn::from(3))
+///     .cpu_svn(CpuSvn::from([4; 16]))
+///     .pce_svn(13)
+///     .build();
+///
+/// assert_eq!(quote.pck_certificate_chain().len(), 3);
+/// assert!(!quote.as_ref().is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteBuilder {
+    mr_enclave: MrEnclave,
+    mr_signer: MrSigner,
+    isv_product_id: IsvProductId,
+    isv_svn: IsvSvn,
+    attributes: Attributes,
+    report_data: ReportData,
+    cpu_svn: CpuSvn,
+    pce_svn: u16,
+    qe_svn: u16,
+    fmspc: Fmspc,
+    pce_id: PceId,
+    ppid: Ppid,
+}
+
+impl Default for QuoteBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuoteBuilder {
+    /// Start a quote with the default values
+    pub fn new() -> Self {
+        Self {
+            mr_enclave: MrEnclave::from([0; 32]),
+            mr_signer: MrSigner::from([0; 32]),
+            isv_product_id: IsvProductId::from(0),
+            isv_svn: IsvSvn::from(0),
+            attributes: Attributes::from(ENCLAVE_ATTRIBUTES),
+            report_data: ReportData::from([0; 64]),
+            cpu_svn: CpuSvn::from([0; 16]),
+            pce_svn: 0,
+            qe_svn: 0,
+            fmspc: Fmspc::new([0x00, 0x90, 0x6E, 0xD5, 0x00, 0x00]),
+            pce_id: PceId::new([0; 2]),
+            ppid: Ppid::new([0xAB; 16]),
+        }
+    }
+
+    /// Set the MRENCLAVE of the application enclave
+    pub fn mr_enclave(mut self, mr_enclave: MrEnclave) -> Self {
+        self.mr_enclave = mr_enclave;
+        self
+    }
+
+    /// Set the MRSIGNER of the application enclave
+    pub fn mr_signer(mut self, mr_signer: MrSigner) -> Self {
+        self.mr_signer = mr_signer;
+        self
+    }
+
+    /// Set the ISV product ID of the application enclave
+    pub fn isv_product_id(mut self, isv_product_id: IsvProductId) -> Self {
+        self.isv_product_id = isv_product_id;
+        self
+    }
+
+    /// Set the ISV SVN of the application enclave
+    pub fn isv_svn(mut self, isv_svn: IsvSvn) -> Self {
+        self.isv_svn = isv_svn;
+        self
+    }
+
+    /// Set the attributes of the application enclave
+    pub fn attributes(mut self, attributes: Attributes) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Set the report data of the application enclave
+    pub fn report_data(mut self, report_data: ReportData) -> Self {
+        self.report_data = report_data;
+        self
+    }
+
+    /// Set the CPU SVN of the platform
+    ///
+    /// This is the CPU SVN of the report body and the SGX TCB components of
+    /// the PCK certificate.
+    pub fn cpu_svn(mut self, cpu_svn: CpuSvn) -> Self {
+        self.cpu_svn = cpu_svn;
+        self
+    }
+
+    /// Set the PCE SVN of the platform
+    ///
+    /// This is the PCE SVN of the quote header and of the PCK certificate.
+    pub fn pce_svn(mut self, pce_svn: u16) -> Self {
+        self.pce_svn = pce_svn;
+        self
+    }
+
+    /// Set the ISV SVN of the quoting enclave
+    ///
+    /// This is the QE SVN of the quote header and the ISV SVN of the QE
+    /// report.
+    pub fn qe_svn(mut self, qe_svn: u16) -> Self {
+        self.qe_svn = qe_svn;
+        self
+    }
+
+    /// Set the FMSPC of the PCK certificate
+    pub fn fmspc(mut self, fmspc: Fmspc) -> Self {
+        self.fmspc = fmspc;
+        self
+    }
+
+    /// Set the PCE ID of the PCK certificate
+    pub fn pce_id(mut self, pce_id: PceId) -> Self {
+        self.pce_id = pce_id;
+        self
+    }
+
+    /// Set the PPID of the PCK certificate
+    pub fn ppid(mut self, ppid: Ppid) -> Self {
+        self.ppid = ppid;
+        self
+    }
+
+    /// Build the quote and its PCK certificate chain
+    pub fn build(&self) -> TestQuote {
+        let root_key = signing_key(&ROOT_KEY);
+        let platform_ca_key = signing_key(&PLATFORM_CA_KEY);
+        let pck_key = signing_key(&PCK_KEY);
+
+        let root = certificate(
+            1,
+            ROOT_SUBJECT,
+            ROOT_SUBJECT,
+            &root_key,
+            &root_key,
+            true,
+            None,
+        );
+        let platform_ca = certificate(
+            2,
+            PLATFORM_CA_SUBJECT,
+            ROOT_SUBJECT,
+            &platform_ca_key,
+            &root_key,
+            true,
+            None,
+        );
+        let pck = certificate(
+            3,
+            PCK_SUBJECT,
+            PLATFORM_CA_SUBJECT,
+            &pck_key,
+            &platform_ca_key,
+            false,
+            Some(self.sgx_extensions()),
+        );
+        let pck_certificate_chain = vec![pck, platform_ca, root];
+
+        let mut bytes = self.header();
+        bytes.extend(report_body(
+            &self.cpu_svn,
+            &self.attributes,
+            &self.mr_enclave,
+            &self.mr_signer,
+            self.isv_product_id,
+            self.isv_svn,
+            &self.report_data,
+        ));
+        let signature_data = self.signature_data(&bytes, &pck_key, &pck_certificate_chain);
+        bytes.extend((signature_data.len() as u32).to_le_bytes());
+        bytes.extend(signature_data);
+
+        TestQuote {
+            bytes,
+            pck_certificate_chain,
+        }
+    }
+
+    fn header(&self) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend(QUOTE_VERSION.to_le_bytes());
+        header.extend(ECDSA_P256_KEY_TYPE.to_le_bytes());
+        header.extend([0u8; 4]);
+        header.extend(self.qe_svn.to_le_bytes());
+        header.extend(self.pce_svn.to_le_bytes());
+        header.extend(INTEL_QE_VENDOR_ID);
+        header.extend([0u8; 20]);
+        header
+    }
+
+    /// The signature data of a v3 quote, `signed` is the header and report
+    /// body
+    fn signature_data(
+        &self,
+        signed: &[u8],
+        pck_key: &SigningKey,
+        pck_certificate_chain: &[Certificate],
+    ) -> Vec<u8> {
+        let attestation_key = signing_key(&ATTESTATION_KEY);
+        let quote_signature: Signature = attestation_key.sign(signed);
+        let encoded_key = attestation_key.verifying_key().to_encoded_point(false);
+        let raw_key = &encoded_key.as_bytes()[1..];
+
+        let authentication_data = [0u8; 32];
+        let mut qe_report_data = [0u8; 64];
+        let mut hasher = Sha256::new();
+        hasher.update(raw_key);
+        hasher.update(authentication_data);
+        qe_report_data[..32].copy_from_slice(&hasher.finalize());
+        let qe_report = report_body(
+            &self.cpu_svn,
+            &Attributes::from(QE_ATTRIBUTES),
+            &MrEnclave::from([0; 32]),
+            &MrSigner::from(QE_MR_SIGNER),
+            IsvProductId::from(1),
+            IsvSvn::from(self.qe_svn),
+            &ReportData::from(qe_report_data),
+        );
+        let qe_report_signature: Signature = pck_key.sign(&qe_report);
+
+        let pem = pck_certificate_chain
+            .iter()
+            .map(|certificate| {
+                certificate
+                    .to_pem(LineEnding::LF)
+                    .expect("Test certificate should encode as PEM")
+            })
+            .collect::<String>();
+
+        let mut signature_data = vec![];
+        signature_data.extend(quote_signature.to_bytes());
+        signature_data.extend(raw_key);
+        signature_data.extend(qe_report);
+        signature_data.extend(qe_report_signature.to_bytes());
+        signature_data.extend((authentication_data.len() as u16).to_le_bytes());
+        signature_data.extend(authentication_data);
+        signature_data.extend(PCK_CERTIFICATE_CHAIN_TYPE.to_le_bytes());
+        signature_data.extend((pem.len() as u32).to_le_bytes());
+        signature_data.extend(pem.as_bytes());
+        signature_data
+    }
+
+    /// The DER of the SGX extensions of the PCK certificate
+    fn sgx_extensions(&self) -> Vec<u8> {
+        let mut tcb = self
+            .cpu_svn
+            .as_ref()
+            .iter()
+            .enumerate()
+            .map(|(index, svn)| SgxExtension {
+                id: TCB_OID
+                    .push_arc(index as u32 + 1)
+                    .expect("TCB component OID should be valid"),
+                value: any(svn),
+            })
+            .collect::<Vec<_>>();
+        tcb.push(SgxExtension {
+            id: PCE_SVN_OID,
+            value: any(&self.pce_svn),
+        });
+        tcb.push(SgxExtension {
+            id: CPU_SVN_OID,
+            value: octet_string(self.cpu_svn.as_ref()),
+        });
+
+        let extensions = vec![
+            SgxExtension {
+                id: PPID_OID,
+                value: octet_string(self.ppid.as_bytes()),
+            },
+            SgxExtension {
+                id: TCB_OID,
+                value: any(&tcb),
+            },
+            SgxExtension {
+                id: PCE_ID_OID,
+                value: octet_string(self.pce_id.as_bytes()),
+            },
+            SgxExtension {
+                id: FMSPC_OID,
+                value: octet_string(self.fmspc.as_bytes()),
+            },
+            SgxExtension {
+                id: SGX_TYPE_OID,
+                // Standard
+                value: Any::new(Tag::Enumerated, [0u8]).expect("SGX type should be valid"),
+            },
+        ];
+        extensions
+            .to_der()
+            .expect("SGX extensions should encode as DER")
+    }
+}
+
+/// A quote from a [`QuoteBuilder`]
+///
+/// The bytes are available with `as_ref()`, for parsing them as a
+/// [`Quote3`](crate::Quote3).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestQuote {
+    bytes: Vec<u8>,
+    pck_certificate_chain: Vec<Certificate>,
+}
+
+impl TestQuote {
+    /// The PCK certificate chain embedded in the quote
+    ///
+    /// The leaf PCK certificate is first, the self-signed root is last.
+    pub fn pck_certificate_chain(&self) -> &[Certificate] {
+        &self.pck_certificate_chain
+    }
+
+    /// The self-signed root of the PCK certificate chain, the trust anchor
+    /// for verifying the quote
+    pub fn root_certificate(&self) -> &Certificate {
+        self.pck_certificate_chain
+            .last()
+            .expect("Chain should have a root")
+    }
+
+    /// The bytes of the quote
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl AsRef<[u8]> for TestQuote {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// The collateral from the raw parts, as they are provided by the PCS
+///
+/// The issuer chains are concatenated PEM certificates, leaf first. The CRLs
+/// are DER encoded.
+///
+/// # Errors
+/// `Error::Collateral` if a part can not be decoded.
+pub fn collateral(
+    pck_crl_issuer_chain: &str,
+    root_ca_crl: &[u8],
+    pck_crl: &[u8],
+    tcb_info_issuer_chain: &str,
+    tcb_info: &str,
+    qe_identity_issuer_chain: &str,
+    qe_identity: &str,
+) -> Result<Collateral, Error> {
+    ffi::dcap_collateral(
+        pck_crl_issuer_chain,
+        root_ca_crl,
+        pck_crl,
+        tcb_info_issuer_chain,
+        tcb_info,
+        qe_identity_issuer_chain,
+        qe_identity,
+    )
+}
+
+/// The DER encoded form of an ECDSA signature
+#[derive(Sequence)]
+struct EcdsaSignature<'a> {
+    r: UintRef<'a>,
+    s: UintRef<'a>,
+}
+
+fn signing_key(bytes: &[u8; 32]) -> SigningKey {
+    SigningKey::from_slice(bytes).expect("Test key should be a valid P-256 key")
+}
+
+fn any(value: &(impl EncodeValue + Tagged)) -> Any {
+    Any::encode_from(value).expect("Value should encode as DER")
+}
+
+fn octet_string(bytes: &[u8]) -> Any {
+    Any::new(Tag::OctetString, bytes).expect("Bytes should be a valid octet string")
+}
+
+fn time(year: u16) -> Time {
+    let date_time = DateTime::new(year, 1, 1, 0, 0, 0).expect("Date should be valid");
+    Time::UtcTime(UtcTime::from_date_time(date_time).expect("Date should be a valid UTC time"))
+}
+
+/// A certificate for the P-256 `key`, signed by `issuer_key`
+fn certificate(
+    serial_number: u8,
+    subject: &str,
+    issuer: &str,
+    key: &SigningKey,
+    issuer_key: &SigningKey,
+    is_ca: bool,
+    sgx_extensions: Option<Vec<u8>>,
+) -> Certificate {
+    let subject = subject.parse::<Name>().expect("Subject should be valid");
+    let issuer = issuer.parse::<Name>().expect("Issuer should be valid");
+    let algorithm = AlgorithmIdentifierOwned {
+        oid: ECDSA_WITH_SHA_256_OID,
+        parameters: None,
+    };
+    let public_key = key.verifying_key().to_encoded_point(false);
+    let subject_public_key_info = SubjectPublicKeyInfoOwned {
+        algorithm: AlgorithmIdentifierOwned {
+            oid: EC_PUBLIC_KEY_OID,
+            parameters: Some(any(&PRIME_256_V1_OID)),
+        },
+        subject_public_key: BitString::from_bytes(public_key.as_bytes())
+            .expect("Public key should be a valid bit string"),
+    };
+
+    let mut extensions = vec![];
+    if is_ca {
+        let basic_constraints = BasicConstraints {
+            ca: true,
+            path_len_constraint: None,
+        };
+        extensions.push(Extension {
+            extn_id: BasicConstraints::OID,
+            critical: true,
+            extn_value: OctetString::new(
+                basic_constraints
+                    .to_der()
+                    .expect("Basic constraints should encode as DER"),
+            )
+            .expect("Basic constraints should be valid"),
+        });
+    }
+    if let Some(sgx_extensions) = sgx_extensions {
+        extensions.push(Extension {
+            extn_id: SGX_EXTENSIONS_OID,
+            critical: false,
+            extn_value: OctetString::new(sgx_extensions).expect("SGX extensions should be valid"),
+        });
+    }
+
+    let tbs_certificate = TbsCertificate {
+        version: Version::V3,
+        serial_number: SerialNumber::new(&[serial_number]).expect("Serial number should be valid"),
+        signature: algorithm.clone(),
+        issuer,
+        validity: Validity {
+            not_before: time(2020),
+            not_after: time(2049),
+        },
+        subject,
+        subject_public_key_info,
+        issuer_unique_id: None,
+        subject_unique_id: None,
+        extensions: Some(extensions),
+    };
+    let tbs = tbs_certificate
+        .to_der()
+        .expect("TBS certificate should encode as DER");
+    let signature: Signature = issuer_key.sign(&tbs);
+    let (r, s) = signature.split_bytes();
+    let signature = EcdsaSignature {
+        r: UintRef::new(&r).expect("r should be a valid integer"),
+        s: UintRef::new(&s).expect("s should be a valid integer"),
+    }
+    .to_der()
+    .expect("Signature should encode as DER");
+
+    Certificate {
+        tbs_certificate,
+        signature_algorithm: algorithm,
+        signature: BitString::from_bytes(&signature)
+            .expect("Signature should be a valid bit string"),
+    }
+}
+
+/// A report body with the fields a quote's report bodies vary by, the rest
+/// are zero
+fn report_body(
+    cpu_svn: &CpuSvn,
+    attributes: &Attributes,
+    mr_enclave: &MrEnclave,
+    mr_signer: &MrSigner,
+    isv_product_id: IsvProductId,
+    isv_svn: IsvSvn,
+    report_data: &ReportData,
+) -> [u8; REPORT_BODY_SIZE] {
+    let attributes = sgx_attributes_t::from(*attributes);
+    let mut body = [0u8; REPORT_BODY_SIZE];
+    body[..16].copy_from_slice(cpu_svn.as_ref());
+    body[48..56].copy_from_slice(&attributes.flags.to_le_bytes());
+    body[56..64].copy_from_slice(&attributes.xfrm.to_le_bytes());
+    body[64..96].copy_from_slice(mr_enclave.as_ref());
+    body[128..160].copy_from_slice(mr_signer.as_ref());
+    body[256..258].copy_from_slice(&u16::from(isv_product_id).to_le_bytes());
+    body[258..260].copy_from_slice(&u16::from(isv_svn).to_le_bytes());
+    body[320..].copy_from_slice(report_data.as_ref());
+    body
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::platform_configuration::sgx_extensions;
+    use der::Decode;
+    use p256::ecdsa::{signature::Verifier as _, VerifyingKey};
+
+    const SIGNATURE_DATA_OFFSET: usize = 48 + REPORT_BODY_SIZE + 4;
+    const QE_REPORT_OFFSET: usize = SIGNATURE_DATA_OFFSET + 128;
+
+    fn verifying_key(certificate: &Certificate) -> VerifyingKey {
+        crate::evidence::key_from_certificate(certificate).expect("Failed to get public key")
+    }
+
+    fn verify_certificate(certificate: &Certificate, issuer: &Certificate) {
+        let tbs = certificate
+            .tbs_certificate
+            .to_der()
+            .expect("Failed to encode TBS certificate");
+        let signature = EcdsaSignature::from_der(
+            certificate
+                .signature
+                .as_bytes()
+                .expect("Failed to get signature"),
+        )
+        .expect("Failed to decode signature");
+        let scalar = |uint: UintRef<'_>| {
+            let mut bytes = [0u8; 32];
+            bytes[32 - uint.as_bytes().len()..].copy_from_slice(uint.as_bytes());
+            bytes
+        };
+        let signature = Signature::from_scalars(scalar(signature.r), scalar(signature.s))
+            .expect("Failed to decode signature");
+        verifying_key(issuer)
+            .verify(&tbs, &signature)
+            .expect("Certificate signature should be valid");
+    }
+
+    #[test]
+    fn quote_fields() {
+        let quote = QuoteBuilder::new()
+            .mr_enclave(MrEnclave::from([1; 32]))
+            .mr_signer(MrSigner::from([2; 32]))
+            .isv_product_id(IsvProductId::from(3))
+            .isv_svn(IsvSvn::from(4))
+            .report_data(ReportData::from([5; 64]))
+            .cpu_svn(CpuSvn::from([6; 16]))
+            .pce_svn(7)
+            .qe_svn(8)
+            .build();
+        let bytes = quote.as_ref();
+
+        assert_eq!(&bytes[..2], &3u16.to_le_bytes());
+        assert_eq!(&bytes[8..10], &8u16.to_le_bytes());
+        assert_eq!(&bytes[10..12], &7u16.to_le_bytes());
+        assert_eq!(&bytes[12..28], &INTEL_QE_VENDOR_ID);
+
+        let body = &bytes[48..48 + REPORT_BODY_SIZE];
+        assert_eq!(&body[..16], &[6; 16]);
+        assert_eq!(&body[48..56], &5u64.to_le_bytes());
+        assert_eq!(&body[64..96], &[1; 32]);
+        assert_eq!(&body[128..160], &[2; 32]);
+        assert_eq!(&body[256..258], &3u16.to_le_bytes());
+        assert_eq!(&body[258..260], &4u16.to_le_bytes());
+        assert_eq!(&body[320..], &[5; 64]);
+
+        let signature_data_size = u32::from_le_bytes(
+            bytes[SIGNATURE_DATA_OFFSET - 4..SIGNATURE_DATA_OFFSET]
+                .try_into()
+                .expect("Size should be 4 bytes"),
+        );
+        assert_eq!(
+            bytes.len(),
+            SIGNATURE_DATA_OFFSET + signature_data_size as usize
+        );
+    }
+
+    #[test]
+    fn quote_signatures_are_valid() {
+        let quote = QuoteBuilder::new().build();
+        let bytes = quote.as_ref();
+
+        let signature = Signature::from_slice(&bytes[SIGNATURE_DATA_OFFSET..][..64])
+            .expect("Failed to decode quote signature");
+        let mut attestation_key = vec![0x04];
+        attestation_key.extend(&bytes[SIGNATURE_DATA_OFFSET + 64..][..64]);
+        let attestation_key =
+            VerifyingKey::from_sec1_bytes(&attestation_key).expect("Failed to decode key");
+        attestation_key
+            .verify(&bytes[..SIGNATURE_DATA_OFFSET - 4], &signature)
+            .expect("Quote signature should be valid");
+
+        let qe_report = &bytes[QE_REPORT_OFFSET..][..REPORT_BODY_SIZE];
+        assert_eq!(&qe_report[128..160], &QE_MR_SIGNER);
+        let qe_report_signature =
+            Signature::from_slice(&bytes[QE_REPORT_OFFSET + REPORT_BODY_SIZE..][..64])
+                .expect("Failed to decode QE report signature");
+        verifying_key(&quote.pck_certificate_chain()[0])
+            .verify(qe_report, &qe_report_signature)
+            .expect("QE report signature should be valid");
+
+        let authentication_data = &bytes[QE_REPORT_OFFSET + REPORT_BODY_SIZE + 64 + 2..][..32];
+        let mut hasher = Sha256::new();
+        hasher.update(&bytes[SIGNATURE_DATA_OFFSET + 64..][..64]);
+        hasher.update(authentication_data);
+        assert_eq!(&qe_report[320..352], &hasher.finalize()[..]);
+    }
+
+    #[test]
+    fn embedded_chain_matches() {
+        let quote = QuoteBuilder::new().build();
+        let pem = quote
+            .pck_certificate_chain()
+            .iter()
+            .map(|certificate| {
+                certificate
+                    .to_pem(LineEnding::LF)
+                    .expect("Failed to encode")
+            })
+            .collect::<String>();
+
+        assert!(quote.as_ref().ends_with(pem.as_bytes()));
+        let chain = Certificate::load_pem_chain(pem.as_bytes()).expect("Failed to parse chain");
+        assert_eq!(chain.as_slice(), quote.pck_certificate_chain());
+    }
+
+    #[test]
+    fn pck_chain_is_signed_by_root() {
+        let quote = QuoteBuilder::new().build();
+        let chain = quote.pck_certificate_chain();
+
+        verify_certificate(&chain[0], &chain[1]);
+        verify_certificate(&chain[1], &chain[2]);
+        verify_certificate(&chain[2], &chain[2]);
+        assert_eq!(quote.root_certificate(), &chain[2]);
+
+        let der = chain[0].to_der().expect("Failed to encode");
+        assert_eq!(
+            Certificate::from_der(&der).expect("Failed to decode"),
+            chain[0]
+        );
+    }
+
+    #[test]
+    fn pck_certificate_has_sgx_extensions() {
+        let fmspc = Fmspc::new([1, 2, 3, 4, 5, 6]);
+        let pce_id = PceId::new([7, 8]);
+        let ppid = Ppid::new([9; 16]);
+        let quote = QuoteBuilder::new()
+            .fmspc(fmspc)
+            .pce_id(pce_id)
+            .ppid(ppid)
+            .cpu_svn(CpuSvn::from([
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+            ]))
+            .pce_svn(300)
+            .build();
+
+        let extensions = sgx_extensions(&quote.pck_certificate_chain()[0])
+            .expect("Failed to parse SGX extensions");
+        let value = |id| {
+            extensions
+                .iter()
+                .find(|extension| extension.id == id)
+                .expect("Missing SGX extension")
+                .value
+                .clone()
+        };
+        assert_eq!(value(FMSPC_OID).value(), fmspc.as_bytes());
+        assert_eq!(value(PCE_ID_OID).value(), pce_id.as_bytes());
+        assert_eq!(value(PPID_OID).value(), ppid.as_bytes());
+        assert_eq!(value(SGX_TYPE_OID).tag(), Tag::Enumerated);
+
+        let tcb = value(TCB_OID)
+            .decode_as::<Vec<SgxExtension>>()
+            .expect("Failed to parse TCB");
+        assert_eq!(tcb.len(), 18);
+        assert_eq!(tcb[0].value.decode_as::<u8>(), Ok(1));
+        assert_eq!(tcb[15].value.decode_as::<u8>(), Ok(16));
+        assert_eq!(tcb[16].id, PCE_SVN_OID);
+        assert_eq!(tcb[16].value.decode_as::<u16>(), Ok(300));
+        assert_eq!(
+            tcb[17].value.value(),
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]
+        );
+    }
+
+    #[test]
+    fn ca_certificates_have_basic_constraints() {
+        let quote = QuoteBuilder::new().build();
+        let chain = quote.pck_certificate_chain();
+        let has_basic_constraints = |certificate: &Certificate| {
+            certificate
+                .tbs_certificate
+                .extensions
+                .iter()
+                .flatten()
+                .any(|extension| extension.extn_id == BasicConstraints::OID)
+        };
+
+        assert!(!has_basic_constraints(&chain[0]));
+        assert!(has_basic_constraints(&chain[1]));
+        assert!(has_basic_constraints(&chain[2]));
+    }
+}
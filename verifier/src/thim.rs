@@ -0,0 +1,317 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Client for the Azure Trusted Hardware Identity Management (THIM) service.
+//!
+//! Azure confidential computing platforms are provisioned by Azure, their
+//! collateral is served by THIM rather than the Intel PCS. THIM implements
+//! the PCS API, so the requests are those of a [`PcsClient`], with the
+//! addition of fetching the PCK certificate of a platform from its QE ID.
+//! See
+//! <https://learn.microsoft.com/en-us/azure/security/fundamentals/trusted-hardware-identity-management>
+
+use crate::{
+    mbedtls::{Error, Result},
+    CertificateChainVerifier, Collateral, CollateralProvider, CpuSvn, Fmspc, PceId, PckCaType,
+    PckCrl, PcsClient, QeIdentity, RetryPolicy, TcbInfo, TeeType,
+};
+use alloc::{format, string::String, vec::Vec};
+use der::DateTime;
+use x509_cert::{crl::CertificateList, Certificate};
+
+/// The base URL of the global THIM cache
+pub const AZURE_THIM_URL: &str = "https://global.acccache.azure.net";
+
+/// The base URL of THIM on an Azure VM, through the instance metadata service
+pub const AZURE_INSTANCE_METADATA_THIM_URL: &str = "http://169.254.169.254/metadata/THIM";
+
+/// The response header with the URL encoded issuer chain of a PCK certificate
+const PCK_CERTIFICATE_ISSUER_CHAIN_HEADER: &str = "SGX-PCK-Certificate-Issuer-Chain";
+
+/// The size of a QE ID, the platform specific ID of the QE
+pub const QE_ID_SIZE: usize = 16;
+
+/// An HTTP client for Azure THIM.
+///
+/// ```no_run
+/// use mc_attestation_verifier::{Fmspc, PckCaType, TeeType, ThimClient};
+///
+/// let fmspc = "00906ED50000".parse::<Fmspc>().unwrap();
+/// let collateral = ThimClient::new()
+///     .fetch_collateral(PckCaType::Platform, &fmspc, TeeType::Sgx)
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct ThimClient {
+    pcs: PcsClient,
+}
+
+impl ThimClient {
+    /// Create a new instance for the global THIM cache
+    pub fn new() -> Self {
+        Self::with_base_url(AZURE_THIM_URL)
+    }
+
+    /// Create a new instance for THIM through the instance metadata service.
+    ///
+    /// This is only reachable from within an Azure VM, but doesn't need
+    /// access to the internet.
+    pub fn from_instance_metadata() -> Self {
+        Self::with_base_url(AZURE_INSTANCE_METADATA_THIM_URL).with_header("Metadata", "true")
+    }
+
+    /// Create a new instance for the THIM service at `base_url`
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            pcs: PcsClient::with_base_url(base_url),
+        }
+    }
+
+    /// Send the header `name` with `value` in every request.
+    ///
+    /// See [`PcsClient::with_header()`].
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.pcs = self.pcs.with_header(name, value);
+        self
+    }
+
+    /// Retry requests which fail transiently with the `retry_policy`.
+    ///
+    /// See [`PcsClient::with_retry_policy()`].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.pcs = self.pcs.with_retry_policy(retry_policy);
+        self
+    }
+
+    /// The client for the PCS API of the service
+    pub fn pcs_client(&self) -> &PcsClient {
+        &self.pcs
+    }
+
+    /// Fetch the PCK certificate of a platform and its issuer chain.
+    ///
+    /// The values identifying the platform and its TCB are those of the QE
+    /// report and the quote header, of the quote being verified.
+    ///
+    /// # Arguments
+    /// * `qe_id` - The QE ID of the platform, the first 16 bytes of the user
+    ///   data of the quote header.
+    /// * `cpu_svn` - The CPU SVN of the platform.
+    /// * `pce_svn` - The SVN of the PCE of the platform.
+    /// * `pce_id` - The ID of the PCE of the platform.
+    ///
+    /// # Returns
+    /// The PCK certificate, followed by its issuer chain. The chain is not
+    /// trusted until verified against the Intel SGX root CA.
+    ///
+    /// # Errors
+    /// `Error::PcsFetch` if the request fails.
+    /// `Error::MissingIssuerChain` if the response is missing the issuer
+    /// chain.
+    /// `Error::PcsUnavailable` or `Error::PcsRequest` if the service responds
+    /// with an error status, after any retries.
+    /// `Error::UrlEncoding` if the issuer chain is not validly URL encoded.
+    /// `Error::Der` if the certificate or the issuer chain can not be
+    /// decoded.
+    pub fn fetch_pck_certificate_chain(
+        &self,
+        qe_id: &[u8; QE_ID_SIZE],
+        cpu_svn: &CpuSvn,
+        pce_svn: u16,
+        pce_id: &PceId,
+    ) -> Result<Vec<Certificate>> {
+        let url = self.pcs.url(
+            "sgx",
+            &format!(
+                "pckcert?qeid={}&cpusvn={}&pcesvn={}&pceid={pce_id}",
+                hex::encode_upper(qe_id),
+                hex::encode_upper(cpu_svn),
+                hex::encode_upper(pce_svn.to_le_bytes()),
+            ),
+        );
+        let (certificate, issuer_chain) = self
+            .pcs
+            .get_with_issuer_chain(&url, PCK_CERTIFICATE_ISSUER_CHAIN_HEADER)?;
+        pck_certificate_chain(&certificate, &issuer_chain)
+    }
+
+    /// Fetch the PCK CRL of the `ca` and its issuer chain.
+    ///
+    /// See [`PcsClient::fetch_pck_crl()`].
+    pub fn fetch_pck_crl(&self, ca: PckCaType) -> Result<PckCrl> {
+        self.pcs.fetch_pck_crl(ca)
+    }
+
+    /// Fetch the TCB info of the platforms with `fmspc` and verify its
+    /// signature.
+    ///
+    /// See [`PcsClient::fetch_tcb_info()`].
+    pub fn fetch_tcb_info<C: CertificateChainVerifier>(
+        &self,
+        fmspc: &Fmspc,
+        tee_type: TeeType,
+        certificate_verifier: &C,
+        crls: &[CertificateList],
+        time: impl Into<Option<DateTime>>,
+    ) -> Result<TcbInfo> {
+        self.pcs
+            .fetch_tcb_info(fmspc, tee_type, certificate_verifier, crls, time)
+    }
+
+    /// Fetch the identity of the QE and verify its signature.
+    ///
+    /// See [`PcsClient::fetch_qe_identity()`].
+    pub fn fetch_qe_identity<C: CertificateChainVerifier>(
+        &self,
+        tee_type: TeeType,
+        certificate_verifier: &C,
+        crls: &[CertificateList],
+        time: impl Into<Option<DateTime>>,
+    ) -> Result<QeIdentity> {
+        self.pcs
+            .fetch_qe_identity(tee_type, certificate_verifier, crls, time)
+    }
+
+    /// Fetch all of the collateral for verifying the quotes of the platforms
+    /// with `fmspc`.
+    ///
+    /// See [`PcsClient::fetch_collateral()`].
+    pub fn fetch_collateral(
+        &self,
+        ca: PckCaType,
+        fmspc: &Fmspc,
+        tee_type: TeeType,
+    ) -> Result<Collateral> {
+        self.pcs.fetch_collateral(ca, fmspc, tee_type)
+    }
+}
+
+impl Default for ThimClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CollateralProvider for ThimClient {
+    type Error = Error;
+
+    fn pck_crl(&self, ca: PckCaType) -> Result<(CertificateList, Vec<Certificate>)> {
+        self.pcs.pck_crl(ca)
+    }
+
+    fn root_ca_crl(&self) -> Result<CertificateList> {
+        self.pcs.root_ca_crl()
+    }
+
+    fn tcb_info(&self, fmspc: &Fmspc, tee_type: TeeType) -> Result<(String, Vec<Certificate>)> {
+        self.pcs.tcb_info(fmspc, tee_type)
+    }
+
+    fn qe_identity(&self, tee_type: TeeType) -> Result<(String, Vec<Certificate>)> {
+        self.pcs.qe_identity(tee_type)
+    }
+}
+
+/// The PCK certificate of a response, followed by its URL encoded
+/// `issuer_chain`
+fn pck_certificate_chain(certificate: &[u8], issuer_chain: &str) -> Result<Vec<Certificate>> {
+    let mut chain = Certificate::load_pem_chain(certificate)?;
+    if chain.len() != 1 {
+        return Err(Error::Collateral(format!(
+            "expected a single PCK certificate, got {}",
+            chain.len()
+        )));
+    }
+    chain.extend(Certificate::load_pem_chain(
+        crate::pcs::url_decode(issuer_chain)?.as_bytes(),
+    )?);
+    Ok(chain)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use assert_matches::assert_matches;
+
+    /// Percent encode everything but letters and digits, as THIM does
+    fn url_encode(text: &str) -> String {
+        text.bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => (byte as char).to_string(),
+                _ => format!("%{byte:02X}"),
+            })
+            .collect()
+    }
+
+    fn issuer_chain() -> String {
+        url_encode(&format!(
+            "{}{}",
+            include_str!("../data/tests/processor_ca.pem"),
+            include_str!("../data/tests/root_ca.pem")
+        ))
+    }
+
+    #[test]
+    fn global_cache_urls() {
+        let client = ThimClient::new();
+        assert_eq!(client.pcs_client().base_url(), AZURE_THIM_URL);
+        assert!(client.pcs_client().headers().is_empty());
+        assert_eq!(
+            client.pcs_client().url("sgx", "qe/identity"),
+            "https://global.acccache.azure.net/sgx/certification/v4/qe/identity"
+        );
+    }
+
+    #[test]
+    fn instance_metadata_sends_metadata_header() {
+        let client = ThimClient::from_instance_metadata();
+        assert_eq!(
+            client.pcs_client().base_url(),
+            AZURE_INSTANCE_METADATA_THIM_URL
+        );
+        assert_eq!(
+            client.pcs_client().headers(),
+            [("Metadata".to_string(), "true".to_string())]
+        );
+    }
+
+    #[test]
+    fn retry_policy_is_forwarded() {
+        let client = ThimClient::new().with_retry_policy(RetryPolicy::none());
+        assert_eq!(client.pcs_client().retry_policy(), &RetryPolicy::none());
+    }
+
+    #[test]
+    fn pck_certificate_with_issuer_chain() {
+        let certificate = include_str!("../data/tests/leaf_cert.pem");
+
+        let chain = pck_certificate_chain(certificate.as_bytes(), &issuer_chain())
+            .expect("Failed to decode chain");
+
+        assert_eq!(chain.len(), 3);
+    }
+
+    #[test]
+    fn multiple_pck_certificates_fails() {
+        let certificates = format!(
+            "{}{}",
+            include_str!("../data/tests/leaf_cert.pem"),
+            include_str!("../data/tests/leaf_cert.pem")
+        );
+
+        assert_matches!(
+            pck_certificate_chain(certificates.as_bytes(), &issuer_chain()),
+            Err(Error::Collateral(_))
+        );
+    }
+
+    #[test]
+    fn pck_certificate_with_invalid_issuer_chain_encoding_fails() {
+        let certificate = include_str!("../data/tests/leaf_cert.pem");
+
+        assert_matches!(
+            pck_certificate_chain(certificate.as_bytes(), "%2"),
+            Err(Error::UrlEncoding)
+        );
+    }
+}
@@ -0,0 +1,10 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Experimental support for TEEs other than SGX and TDX.
+//!
+//! This module is only available with the `unstable` feature. Its APIs are
+//! exempt from semantic versioning and may change, or be removed, in any
+//! release. They are meant for early adopters to experiment with, while the
+//! SGX and TDX APIs stay stable.
+
+pub mod cove;
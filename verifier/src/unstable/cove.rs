@@ -0,0 +1,247 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Evidence from a RISC-V Confidential VM Extension (CoVE) TEE.
+//!
+//! The TEE Security Manager (TSM) provides the evidence of a TEE VM (TVM) as
+//! an attestation certificate. The measurements of each layer, the TSM, the
+//! initial TVM, and the TVM runtime registers, are carried in the TCG DICE
+//! `TcbInfo` and `MultiTcbInfo` extensions of the certificate, see section
+//! 6.1 of
+//! <https://trustedcomputinggroup.org/wp-content/uploads/DICE-Attestation-Architecture-Version-1.1-Revision-18_pub.pdf>
+//!
+//! Only parsing is provided. The certificate chain up to the platform's
+//! DICE root needs to be verified separately.
+
+use crate::Error;
+use alloc::{string::String, vec::Vec};
+use der::{
+    asn1::{BitString, ObjectIdentifier, OctetString},
+    Decode, Sequence,
+};
+use x509_cert::Certificate;
+
+/// The TCG DICE `TcbInfo` extension, `tcg-dice-TcbInfo`
+const TCB_INFO_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.23.133.5.4.1");
+/// The TCG DICE `MultiTcbInfo` extension, `tcg-dice-MultiTcbInfo`
+const MULTI_TCB_INFO_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.23.133.5.4.5");
+
+/// The evidence of a CoVE TVM.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoveEvidence {
+    certificate: Certificate,
+    tcb_infos: Vec<DiceTcbInfo>,
+}
+
+impl CoveEvidence {
+    /// Parse the evidence from the DER encoded attestation certificate.
+    ///
+    /// A certificate without any TCB info is accepted, the verifier needs to
+    /// require the layers it expects.
+    ///
+    /// # Errors
+    /// `Error::Der` if the certificate or its TCB info can not be decoded.
+    pub fn try_from_der(der: &[u8]) -> Result<Self, Error> {
+        let certificate = Certificate::from_der(der)?;
+        let mut tcb_infos = Vec::new();
+        for extension in certificate.tbs_certificate.extensions.iter().flatten() {
+            let value = extension.extn_value.as_bytes();
+            if extension.extn_id == TCB_INFO_OID {
+                tcb_infos.push(DiceTcbInfo::from_der(value)?);
+            } else if extension.extn_id == MULTI_TCB_INFO_OID {
+                tcb_infos.extend(Vec::<DiceTcbInfo>::from_der(value)?);
+            }
+        }
+        Ok(Self {
+            certificate,
+            tcb_infos,
+        })
+    }
+
+    /// The attestation certificate
+    pub fn certificate(&self) -> &Certificate {
+        &self.certificate
+    }
+
+    /// The TCB info of each layer, in the order of the extensions
+    pub fn tcb_infos(&self) -> &[DiceTcbInfo] {
+        &self.tcb_infos
+    }
+
+    /// The TCB info of the `layer`, if present
+    pub fn layer(&self, layer: u64) -> Option<&DiceTcbInfo> {
+        self.tcb_infos
+            .iter()
+            .find(|tcb_info| tcb_info.layer == Some(layer))
+    }
+}
+
+/// The TCG DICE `TcbInfo` of a single layer.
+///
+/// All of the fields are optional in the specification, so each one is
+/// `None` when it is not present.
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+pub struct DiceTcbInfo {
+    #[asn1(context_specific = "0", tag_mode = "IMPLICIT", optional = "true")]
+    vendor: Option<String>,
+    #[asn1(context_specific = "1", tag_mode = "IMPLICIT", optional = "true")]
+    model: Option<String>,
+    #[asn1(context_specific = "2", tag_mode = "IMPLICIT", optional = "true")]
+    version: Option<String>,
+    #[asn1(context_specific = "3", tag_mode = "IMPLICIT", optional = "true")]
+    svn: Option<u64>,
+    #[asn1(context_specific = "4", tag_mode = "IMPLICIT", optional = "true")]
+    layer: Option<u64>,
+    #[asn1(context_specific = "5", tag_mode = "IMPLICIT", optional = "true")]
+    index: Option<u64>,
+    #[asn1(context_specific = "6", tag_mode = "IMPLICIT", optional = "true")]
+    fwids: Option<Vec<Fwid>>,
+    #[asn1(context_specific = "7", tag_mode = "IMPLICIT", optional = "true")]
+    flags: Option<BitString>,
+    #[asn1(context_specific = "8", tag_mode = "IMPLICIT", optional = "true")]
+    vendor_info: Option<OctetString>,
+    #[asn1(context_specific = "9", tag_mode = "IMPLICIT", optional = "true")]
+    tcb_type: Option<OctetString>,
+}
+
+impl DiceTcbInfo {
+    /// The vendor of the layer's component
+    pub fn vendor(&self) -> Option<&str> {
+        self.vendor.as_deref()
+    }
+
+    /// The model of the layer's component
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// The version of the layer's component
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// The security version number of the layer's component
+    pub fn svn(&self) -> Option<u64> {
+        self.svn
+    }
+
+    /// The layer the TCB info is for
+    pub fn layer(&self) -> Option<u64> {
+        self.layer
+    }
+
+    /// The index of the component within the layer
+    pub fn index(&self) -> Option<u64> {
+        self.index
+    }
+
+    /// The measurements of the layer, empty when there are none
+    pub fn fwids(&self) -> &[Fwid] {
+        self.fwids.as_deref().unwrap_or_default()
+    }
+
+    /// The operational flags, like debug or not configured, of the layer
+    pub fn flags(&self) -> Option<&[u8]> {
+        self.flags.as_ref().and_then(BitString::as_bytes)
+    }
+
+    /// Vendor specific information
+    pub fn vendor_info(&self) -> Option<&[u8]> {
+        self.vendor_info.as_ref().map(OctetString::as_bytes)
+    }
+
+    /// The type of the TCB info
+    pub fn tcb_type(&self) -> Option<&[u8]> {
+        self.tcb_type.as_ref().map(OctetString::as_bytes)
+    }
+}
+
+/// A measurement, firmware ID, of a layer.
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+pub struct Fwid {
+    hash_algorithm: ObjectIdentifier,
+    digest: OctetString,
+}
+
+impl Fwid {
+    /// The hash algorithm used for the `digest`
+    pub fn hash_algorithm(&self) -> ObjectIdentifier {
+        self.hash_algorithm
+    }
+
+    /// The measurement
+    pub fn digest(&self) -> &[u8] {
+        self.digest.as_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use der::oid::db::rfc5912::ID_SHA_384;
+
+    const EVIDENCE: &[u8] = include_bytes!("../../data/tests/cove/evidence.der");
+
+    #[test]
+    fn tcb_info_and_multi_tcb_info() {
+        let evidence = CoveEvidence::try_from_der(EVIDENCE).expect("Failed to parse evidence");
+        let layers = evidence
+            .tcb_infos()
+            .iter()
+            .map(|tcb_info| (tcb_info.layer(), tcb_info.model()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            layers,
+            vec![
+                (Some(0), Some("CoVE TSM")),
+                (Some(1), Some("CoVE TVM")),
+                (Some(2), Some("CoVE TVM runtime")),
+            ]
+        );
+    }
+
+    #[test]
+    fn tsm_layer() {
+        let evidence = CoveEvidence::try_from_der(EVIDENCE).expect("Failed to parse evidence");
+        let tsm = evidence.layer(0).expect("Missing TSM layer");
+        assert_eq!(tsm.vendor(), Some("RISC-V"));
+        assert_eq!(tsm.svn(), Some(3));
+        assert_eq!(tsm.version(), None);
+        assert_eq!(tsm.index(), None);
+        assert_eq!(tsm.flags(), None);
+
+        let [fwid] = tsm.fwids() else {
+            panic!("Expected one FWID, got {:?}", tsm.fwids());
+        };
+        assert_eq!(fwid.hash_algorithm(), ID_SHA_384);
+        assert_eq!(
+            fwid.digest(),
+            hex::decode("56200f17dda0aaa97d2866a244fa8010340ef5b930facb7bc5d2d977e083cdc93fff1af222c8fb9f4e9a9af86e2874a5")
+                .expect("Invalid hex")
+        );
+    }
+
+    #[test]
+    fn runtime_layer_has_multiple_measurements() {
+        let evidence = CoveEvidence::try_from_der(EVIDENCE).expect("Failed to parse evidence");
+        let runtime = evidence.layer(2).expect("Missing runtime layer");
+        assert_eq!(runtime.fwids().len(), 2);
+        assert_eq!(evidence.layer(3), None);
+    }
+
+    #[test]
+    fn evidence_without_tcb_info() {
+        let evidence =
+            CoveEvidence::try_from_der(include_bytes!("../../data/tests/cove/no_tcb_info.der"))
+                .expect("Failed to parse evidence");
+        assert!(evidence.tcb_infos().is_empty());
+    }
+
+    #[test]
+    fn invalid_evidence_fails() {
+        assert!(matches!(
+            CoveEvidence::try_from_der(&EVIDENCE[1..]),
+            Err(Error::Der(_))
+        ));
+    }
+}
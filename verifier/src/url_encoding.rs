@@ -0,0 +1,57 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Decoding of the URL encoded certificate chains in the headers of the
+//! Intel services' responses.
+
+use crate::Error;
+use alloc::vec::Vec;
+
+/// Decode the percent encoded `encoded`, `+` is decoded as a space
+///
+/// # Errors
+/// `Error::UrlEncoding` if a `%` is not followed by two hex digits.
+pub(crate) fn percent_decode(encoded: &str) -> Result<Vec<u8>, Error> {
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut bytes = encoded.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'%' => {
+                let hex = [
+                    bytes.next().ok_or(Error::UrlEncoding)?,
+                    bytes.next().ok_or(Error::UrlEncoding)?,
+                ];
+                let mut value = [0u8];
+                hex::decode_to_slice(hex, &mut value).map_err(|_| Error::UrlEncoding)?;
+                decoded.push(value[0]);
+            }
+            b'+' => decoded.push(b' '),
+            _ => decoded.push(byte),
+        }
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        plain = { "abc", b"abc" },
+        escaped = { "a%20b%2Fc%0a", b"a b/c\n" },
+        plus = { "a+b", b"a b" },
+        empty = { "", b"" },
+    )]
+    fn decode(encoded: &str, expected: &[u8]) {
+        assert_eq!(percent_decode(encoded), Ok(expected.to_vec()));
+    }
+
+    #[parameterized(
+        truncated = { "abc%2" },
+        missing = { "abc%" },
+        not_hex = { "%zz" },
+    )]
+    fn invalid(encoded: &str) {
+        assert_eq!(percent_decode(encoded), Err(Error::UrlEncoding));
+    }
+}
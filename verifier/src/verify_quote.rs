@@ -0,0 +1,469 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Verification of a quote in one call.
+//!
+//! The [`Verifier`]s of this crate can be composed to verify any subset of
+//! the evidence. [`verify_quote()`] composes all of them, in the order
+//! required by the DCAP specification, so none of the steps can be skipped
+//! by accident.
+
+use crate::{
+    Accessor, Advisories, AdvisoryPolicy, And, CertificateChainVerifier, Collateral,
+    CollateralItem, Error, Evidence, EvidenceVerifier, QuoteError, QuoteHeader, RejectDebug,
+    ReportBody, SupplementalData, TcbStatus, TrustedIdentity, VerificationMessage,
+    VerificationOptions, VerificationTreeDisplay, Verifier,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use der::DateTime;
+use mc_sgx_dcap_types::Quote3;
+
+/// The quote versions supported by [`verify_quote()`]
+pub(crate) const VERIFIABLE_QUOTE_VERSION: u16 = 3;
+
+/// What a quote must satisfy to be accepted by [`verify_quote()`].
+#[derive(Debug)]
+pub struct QuotePolicy<C> {
+    certificate_verifier: C,
+    trusted_identities: Vec<TrustedIdentity>,
+    options: VerificationOptions,
+    allow_debug: bool,
+    advisory_policy: Option<AdvisoryPolicy>,
+}
+
+impl<C: CertificateChainVerifier> QuotePolicy<C> {
+    /// Create a new instance
+    ///
+    /// # Arguments
+    /// * `certificate_verifier` - The verifier for the PCK, TCB info, and QE
+    ///   identity certificate chains. It is responsible for knowing the trust
+    ///   root.
+    /// * `trusted_identities` - The identities of the enclaves to trust. Only
+    ///   one of the identities needs to match.
+    pub fn new<I, ID>(certificate_verifier: C, trusted_identities: I) -> Self
+    where
+        I: IntoIterator<Item = ID>,
+        ID: Into<TrustedIdentity>,
+    {
+        Self {
+            certificate_verifier,
+            trusted_identities: trusted_identities.into_iter().map(Into::into).collect(),
+            options: VerificationOptions::default(),
+            allow_debug: false,
+            advisory_policy: None,
+        }
+    }
+
+    /// Verify the times of the TCB info and QE identity with the tolerances
+    /// of `options`, see [`EvidenceVerifier::with_options()`].
+    pub fn with_options(mut self, options: VerificationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Accept enclaves with the DEBUG attribute set.
+    ///
+    /// By default [`verify_quote()`] rejects debug enclaves with
+    /// [`RejectDebug`], as their secrets are visible to the host. This is only
+    /// intended for development.
+    pub fn allow_debug_enclaves(mut self) -> Self {
+        self.allow_debug = true;
+        self
+    }
+
+    /// Only accept a TCB which needs SW hardening or configuration when its
+    /// advisories are approved by `advisory_policy`.
+    ///
+    /// This applies on top of the advisories allowed by the trusted
+    /// identities.
+    pub fn with_advisory_policy(mut self, advisory_policy: AdvisoryPolicy) -> Self {
+        self.advisory_policy = Some(advisory_policy);
+        self
+    }
+
+    /// The identities of the enclaves to trust
+    pub fn trusted_identities(&self) -> &[TrustedIdentity] {
+        &self.trusted_identities
+    }
+}
+
+/// The outcome of a successful [`verify_quote()`].
+///
+/// The quote satisfied the policy. The TCB status and advisories are provided
+/// so relying parties can apply their own risk decisions on top of the
+/// policy, and the collateral expiration so the result can be cached until
+/// the collateral needs to be refreshed.
+#[derive(Debug, Clone)]
+pub struct QuoteVerificationResult {
+    tcb_status: TcbStatus,
+    advisory_ids: Vec<String>,
+    report_body: ReportBody,
+    collateral_expiration: DateTime,
+    earliest_expiring_collateral: CollateralItem,
+    evidence: Evidence<Vec<u8>>,
+    transcript: String,
+}
+
+impl QuoteVerificationResult {
+    /// The TCB status of the platform
+    pub fn tcb_status(&self) -> TcbStatus {
+        self.tcb_status
+    }
+
+    /// The IDs of the advisories which apply to the platform and the QE, in
+    /// sorted order
+    pub fn advisory_ids(&self) -> &[String] {
+        &self.advisory_ids
+    }
+
+    /// The verified report body of the application enclave
+    pub fn report_body(&self) -> &ReportBody {
+        &self.report_body
+    }
+
+    /// The time the collateral, or the PCK certificate chain of the quote,
+    /// expires, the result should not be relied on after this time
+    pub fn collateral_expiration(&self) -> DateTime {
+        self.collateral_expiration
+    }
+
+    /// The item of the collateral, or the PCK certificate chain, which
+    /// expires first, the one to refresh
+    pub fn earliest_expiring_collateral(&self) -> CollateralItem {
+        self.earliest_expiring_collateral
+    }
+
+    /// The verified evidence
+    pub fn evidence(&self) -> &Evidence<Vec<u8>> {
+        &self.evidence
+    }
+
+    /// The tree of every check which was performed, as rendered by
+    /// [`VerificationTreeDisplay`].
+    ///
+    /// This is the same transcript `Error::QuoteVerification` provides on
+    /// failure, for logging why a quote was accepted.
+    pub fn transcript(&self) -> &str {
+        &self.transcript
+    }
+
+    /// The supplemental data of the verified evidence, matching what Intel's
+    /// QVL provides in `sgx_ql_qv_supplemental_t`.
+    ///
+    /// # Errors
+    /// The errors of [`SupplementalData::try_from()`].
+    pub fn supplemental_data(&self) -> Result<SupplementalData, Error> {
+        SupplementalData::try_from(&self.evidence)
+    }
+}
+
+/// Verify a quote, and its collateral, against the `policy`.
+///
+/// The steps are performed in order:
+/// 1. The quote header is validated, see [`QuoteHeader::validate()`], and the
+///    parts of the `collateral` are checked to be consistent, see
+///    [`Collateral`](crate::Collateral).
+/// 2. The PCK certificate chain of the quote, and the signing chains of the
+///    TCB info and QE identity, are verified against the CRLs of the
+///    `collateral`.
+/// 3. The QE report signature is verified with the PCK leaf key, the
+///    attestation key binding is checked, and the quote signature is
+///    verified with the attestation key.
+/// 4. The QE report is verified against the QE identity.
+/// 5. The TCB info is verified and the platform's TCB level is evaluated.
+/// 6. The application enclave is verified against the trusted identities,
+///    including the advisories of the TCB level.
+/// 7. The application enclave is checked to not be a debug enclave, see
+///    [`RejectDebug`], unless allowed with
+///    [`QuotePolicy::allow_debug_enclaves()`].
+/// 8. The advisories are checked against the [`AdvisoryPolicy`], when one is
+///    provided with [`QuotePolicy::with_advisory_policy()`].
+///
+/// Only version 3 SGX quotes can be verified.
+///
+/// # Arguments
+/// * `quote` - The quote bytes.
+/// * `collateral` - The collateral for the `quote`.
+/// * `policy` - The policy the `quote` must satisfy.
+/// * `time` - The time to verify at, see [`EvidenceVerifier::new()`].
+///
+/// # Returns
+/// The [`QuoteVerificationResult`] with the TCB status, advisories, report
+/// body, and collateral expiration.
+///
+/// # Errors
+/// * `Error::Quote` if the quote header is invalid or the quote version is
+///   not supported.
+/// * `Error::Der` if the `nextUpdate` of the TCB info or QE identity is not a
+///   valid time.
+/// * `Error::Quote3`, and the other errors of [`Evidence::new()`], if the
+///   `quote` can not be parsed or does not fit the `collateral`.
+/// * `Error::QuoteVerification` with the transcript of the verification if
+///   any of the verification steps fail.
+pub fn verify_quote<C: CertificateChainVerifier>(
+    quote: &[u8],
+    collateral: &Collateral,
+    policy: &QuotePolicy<C>,
+    time: impl Into<Option<DateTime>>,
+) -> Result<QuoteVerificationResult, Error> {
+    let header = QuoteHeader::try_from(quote)?;
+    header.validate()?;
+    if header.version() != VERIFIABLE_QUOTE_VERSION {
+        return Err(QuoteError::Version(header.version()).into());
+    }
+
+    let evidence = Evidence::try_from_quote_bytes(quote, collateral.clone())?;
+    let (collateral_expiration, earliest_expiring_collateral) = evidence.expiration()?;
+    let verifier = EvidenceVerifier::new(
+        &policy.certificate_verifier,
+        &policy.trusted_identities,
+        time,
+    )
+    .with_options(policy.options);
+    let mut transcript = if policy.allow_debug {
+        run_verifier(&verifier, &evidence)?
+    } else {
+        run_verifier(&And::new(verifier, RejectDebug), &evidence)?
+    };
+    if let Some(advisory_policy) = &policy.advisory_policy {
+        transcript.push('\n');
+        transcript.push_str(&run_verifier(advisory_policy, &evidence)?);
+    }
+
+    let advisories: Advisories = evidence.get();
+    let quote: Quote3<Vec<u8>> = evidence.get();
+    Ok(QuoteVerificationResult {
+        tcb_status: advisories.status(),
+        advisory_ids: advisories.ids().map(ToString::to_string).collect(),
+        report_body: quote.app_report_body().clone(),
+        collateral_expiration,
+        earliest_expiring_collateral,
+        evidence,
+        transcript,
+    })
+}
+
+/// Run the `verifier`, providing the transcript on success, or as the error
+/// on failure
+fn run_verifier<V>(verifier: &V, evidence: &Evidence<Vec<u8>>) -> Result<String, Error>
+where
+    V: Verifier<Evidence<Vec<u8>>>,
+    V: VerificationMessage<V::Value>,
+{
+    let output = verifier.verify(evidence);
+    let is_success = output.is_success();
+    let transcript = VerificationTreeDisplay::new(verifier, output).to_string();
+    if is_success.into() {
+        Ok(transcript)
+    } else {
+        Err(Error::QuoteVerification(transcript))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::{
+        evidence::{
+            self,
+            test::{QE_IDENTITY_JSON, TCB_INFO_JSON},
+        },
+        quote4::test::tdx_quote,
+        CertificateChainVerifierError, SgxType, TeeType, TrustedMrEnclaveIdentity,
+    };
+    use assert_matches::assert_matches;
+    use core::time::Duration;
+    use mc_sgx_core_types::MrEnclave;
+    use x509_cert::{crl::CertificateList, Certificate};
+
+    const QUOTE: &[u8] = include_bytes!("../data/tests/hw_quote.dat");
+
+    // Accepts all certificate chains, the chains aren't the focus of these tests
+    pub(crate) struct AcceptingChainVerifier;
+
+    impl CertificateChainVerifier for AcceptingChainVerifier {
+        fn verify_certificate_chain<'a, 'b>(
+            &self,
+            _certificate_chain: impl IntoIterator<Item = &'a Certificate>,
+            _crls: impl IntoIterator<Item = &'b CertificateList>,
+            _time: impl Into<Option<DateTime>>,
+        ) -> Result<(), CertificateChainVerifierError> {
+            Ok(())
+        }
+    }
+
+    fn mr_enclave() -> MrEnclave {
+        MrEnclave::from([
+            0x84, 0x0d, 0x61, 0xb0, 0x58, 0x5d, 0xc8, 0xb4, 0xdc, 0x90, 0xf5, 0x3a, 0xf2, 0x93,
+            0xc7, 0x60, 0xfd, 0xa0, 0x6b, 0xee, 0x75, 0x97, 0x8a, 0x6a, 0x86, 0x26, 0x3f, 0xfb,
+            0x29, 0x64, 0x23, 0xf4,
+        ])
+    }
+
+    fn collateral() -> Collateral {
+        evidence::test::collateral(TCB_INFO_JSON, QE_IDENTITY_JSON)
+    }
+
+    fn policy(mr_enclave: MrEnclave) -> QuotePolicy<AcceptingChainVerifier> {
+        let identity = TrustedMrEnclaveIdentity::new(
+            mr_enclave,
+            [] as [&str; 0],
+            ["INTEL-SA-00334", "INTEL-SA-00615"],
+        );
+        QuotePolicy::new(AcceptingChainVerifier, [identity])
+    }
+
+    fn time() -> DateTime {
+        "2023-07-12T20:48:25Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time")
+    }
+
+    #[test]
+    fn verify_quote_succeeds() {
+        let result = verify_quote(QUOTE, &collateral(), &policy(mr_enclave()), time())
+            .expect("Failed to verify quote");
+        assert_eq!(result.report_body().mr_enclave(), mr_enclave());
+        let verified_mr_enclave: MrEnclave = result.evidence().get();
+        assert_eq!(verified_mr_enclave, mr_enclave());
+    }
+
+    #[test]
+    fn verify_quote_result_has_tcb_status_and_expiration() {
+        let result = verify_quote(QUOTE, &collateral(), &policy(mr_enclave()), time())
+            .expect("Failed to verify quote");
+        assert_eq!(result.tcb_status(), TcbStatus::SWHardeningNeeded);
+        assert_eq!(result.advisory_ids(), ["INTEL-SA-00334", "INTEL-SA-00615"]);
+        let expected_expiration = "2023-05-21T22:00:36Z"
+            .parse::<DateTime>()
+            .expect("Failed to parse time");
+        assert_eq!(result.collateral_expiration(), expected_expiration);
+        assert_eq!(
+            result.earliest_expiring_collateral(),
+            CollateralItem::PckCrl
+        );
+    }
+
+    #[test]
+    fn verify_quote_result_has_transcript() {
+        let result = verify_quote(QUOTE, &collateral(), &policy(mr_enclave()), time())
+            .expect("Failed to verify quote");
+        let transcript = result.transcript();
+        assert!(transcript.starts_with("- [x] Both of the following must be true:"));
+        assert!(transcript.contains("- [x] The Quote issuer chain was verified."));
+        assert!(transcript.contains("- [x] The DEBUG attribute should not be set"));
+        assert!(!transcript.contains("- [ ]"));
+    }
+
+    #[test]
+    fn verify_quote_supplemental_data() {
+        let result = verify_quote(QUOTE, &collateral(), &policy(mr_enclave()), time())
+            .expect("Failed to verify quote");
+        let supplemental = result
+            .supplemental_data()
+            .expect("Failed to get supplemental data");
+        assert_eq!(supplemental.version(), 3);
+        assert_eq!(supplemental.tee_type(), TeeType::Sgx);
+        assert_eq!(supplemental.sgx_type(), SgxType::Standard);
+        assert_eq!(supplemental.pck_crl_num(), 1);
+        assert_eq!(supplemental.root_ca_crl_num(), 1);
+        assert_eq!(supplemental.tcb_eval_ref_num(), 15);
+        assert_eq!(
+            supplemental.earliest_expiration_date(),
+            result.collateral_expiration()
+        );
+        assert!(supplemental.earliest_issue_date() <= supplemental.latest_issue_date());
+        assert_eq!(supplemental.sa_list(), "INTEL-SA-00334,INTEL-SA-00615");
+    }
+
+    #[test]
+    fn verify_quote_rejects_debug_enclave() {
+        let mut quote = QUOTE.to_vec();
+        // The flags of the application report body attributes
+        quote[96] |= 0x02;
+        let error = verify_quote(&quote, &collateral(), &policy(mr_enclave()), time()).unwrap_err();
+        assert_matches!(
+            error,
+            Error::QuoteVerification(transcript)
+                if transcript.contains("- [ ] The DEBUG attribute should not be set")
+        );
+    }
+
+    #[test]
+    fn verify_quote_debug_enclave_fails_signature_when_allowed() {
+        // Flipping the DEBUG bit invalidates the quote signature, so this
+        // shows the debug check is skipped rather than the quote accepted.
+        let mut quote = QUOTE.to_vec();
+        quote[96] |= 0x02;
+        let policy = policy(mr_enclave()).allow_debug_enclaves();
+        let error = verify_quote(&quote, &collateral(), &policy, time()).unwrap_err();
+        assert_matches!(
+            error,
+            Error::QuoteVerification(transcript)
+                if !transcript.contains("DEBUG attribute")
+        );
+    }
+
+    #[test]
+    fn verify_quote_approved_advisories() {
+        let policy = policy(mr_enclave())
+            .with_advisory_policy(AdvisoryPolicy::new(["INTEL-SA-00334", "INTEL-SA-00615"]));
+        let result =
+            verify_quote(QUOTE, &collateral(), &policy, time()).expect("Failed to verify quote");
+        assert_eq!(result.tcb_status(), TcbStatus::SWHardeningNeeded);
+    }
+
+    #[test]
+    fn verify_quote_unapproved_advisory() {
+        let policy =
+            policy(mr_enclave()).with_advisory_policy(AdvisoryPolicy::new(["INTEL-SA-00615"]));
+        let error = verify_quote(QUOTE, &collateral(), &policy, time()).unwrap_err();
+        assert_matches!(
+            error,
+            Error::QuoteVerification(transcript)
+                if transcript.contains("INTEL-SA-00334 are not approved")
+        );
+    }
+
+    #[test]
+    fn verify_quote_untrusted_identity() {
+        let policy = policy(MrEnclave::from([0xAB; 32]));
+        let error = verify_quote(QUOTE, &collateral(), &policy, time()).unwrap_err();
+        assert_matches!(error, Error::QuoteVerification(transcript) if transcript.contains("- [ ]"));
+    }
+
+    #[test]
+    fn verify_quote_expired_collateral() {
+        let later = DateTime::from_unix_duration(
+            time().unix_duration() + Duration::from_secs(60 * 60 * 24 * 365),
+        )
+        .expect("Failed to create time");
+        let error = verify_quote(QUOTE, &collateral(), &policy(mr_enclave()), later).unwrap_err();
+        assert_matches!(error, Error::QuoteVerification(_));
+    }
+
+    #[test]
+    fn verify_quote_invalid_header() {
+        let mut quote = QUOTE.to_vec();
+        quote[0] = 2;
+        let error = verify_quote(&quote, &collateral(), &policy(mr_enclave()), time()).unwrap_err();
+        assert_eq!(error, Error::Quote(QuoteError::Version(2)));
+    }
+
+    #[test]
+    fn verify_quote_truncated_header() {
+        assert_matches!(
+            verify_quote(&QUOTE[..10], &collateral(), &policy(mr_enclave()), time()),
+            Err(Error::Quote(QuoteError::Truncated { .. }))
+        );
+    }
+
+    #[test]
+    fn verify_quote_tdx_not_supported() {
+        let error =
+            verify_quote(&tdx_quote(), &collateral(), &policy(mr_enclave()), time()).unwrap_err();
+        assert_eq!(error, Error::Quote(QuoteError::Version(4)));
+    }
+}
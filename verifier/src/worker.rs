@@ -0,0 +1,152 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! Background worker threads driven by state shared with their owner.
+//!
+//! A worker waits on the shared state until a [`Job`] has work for it, does
+//! the work without holding the lock, and then stores the outcome back in the
+//! state. The owner changes the state under the same lock and wakes the
+//! workers when there is new work.
+
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use std::{
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
+};
+
+/// What a worker does next
+pub(crate) enum Step<W> {
+    /// Do the work
+    Run(W),
+    /// Wait at most the duration for new work
+    WaitFor(Duration),
+    /// Wait until woken
+    Wait,
+}
+
+/// The work of a background thread
+pub(crate) trait Job: Send + 'static {
+    /// The state shared with the owner
+    type State: Send + 'static;
+    /// A unit of work, taken from the state
+    type Work;
+    /// The outcome of a unit of work
+    type Outcome;
+
+    /// Take the next unit of work from the `state`, or how long to wait for
+    /// one.
+    fn next(&mut self, state: &mut Self::State) -> Step<Self::Work>;
+
+    /// Do the `work`, called without holding the lock.
+    fn run(&mut self, work: Self::Work) -> Self::Outcome;
+
+    /// Store the `outcome` of a unit of work in the `state`.
+    fn finish(&mut self, state: &mut Self::State, outcome: Self::Outcome);
+}
+
+#[derive(Debug)]
+struct Shared<S> {
+    state: Mutex<S>,
+    wakeup: Condvar,
+    shutdown: AtomicBool,
+}
+
+impl<S> Shared<S> {
+    fn lock(&self) -> MutexGuard<'_, S> {
+        // The work happens outside of the lock so the state is always
+        // consistent.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Background threads sharing a state with their owner.
+///
+/// The threads are stopped when this is dropped.
+#[derive(Debug)]
+pub(crate) struct Workers<S> {
+    shared: Arc<Shared<S>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl<S: Send + 'static> Workers<S> {
+    /// Spawn one thread for each of the `jobs`, sharing the `state`.
+    pub(crate) fn spawn<J>(state: S, jobs: impl IntoIterator<Item = J>) -> Self
+    where
+        J: Job<State = S>,
+    {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(state),
+            wakeup: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+        });
+        let threads = jobs
+            .into_iter()
+            .map(|job| {
+                let shared = shared.clone();
+                thread::spawn(move || run(&shared, job))
+            })
+            .collect();
+        Self { shared, threads }
+    }
+}
+
+impl<S> Workers<S> {
+    /// Lock the shared state
+    pub(crate) fn lock(&self) -> MutexGuard<'_, S> {
+        self.shared.lock()
+    }
+
+    /// Wake one of the threads, after there is new work in the state
+    pub(crate) fn wake(&self) {
+        self.shared.wakeup.notify_one();
+    }
+}
+
+impl<S> Drop for Workers<S> {
+    fn drop(&mut self) {
+        {
+            // Set under the lock so a thread can't miss the wakeup between
+            // checking for the shutdown and waiting.
+            let _state = self.shared.lock();
+            self.shared.shutdown.store(true, Ordering::Relaxed);
+        }
+        self.shared.wakeup.notify_all();
+        for thread in self.threads.drain(..) {
+            // A thread which panicked has nothing left to clean up
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run<J: Job>(shared: &Shared<J::State>, mut job: J) {
+    let mut state = shared.lock();
+    loop {
+        if shared.shutdown.load(Ordering::Relaxed) {
+            return;
+        }
+        match job.next(&mut state) {
+            Step::Run(work) => {
+                drop(state);
+                let outcome = job.run(work);
+                state = shared.lock();
+                job.finish(&mut state, outcome);
+            }
+            Step::WaitFor(timeout) => {
+                state = shared
+                    .wakeup
+                    .wait_timeout(state, timeout)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0;
+            }
+            Step::Wait => {
+                state = shared
+                    .wakeup
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        }
+    }
+}
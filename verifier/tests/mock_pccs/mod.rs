@@ -0,0 +1,272 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! An in-process mock of a Provisioning Certificate Caching Service (PCCS).
+//!
+//! The mock serves responses recorded from the Intel PCS, see
+//! `data/tests/README.md`, over HTTP on a local port. Like the PCS, the issuer
+//! chains are provided URL encoded in the response headers. See
+//! <https://api.portal.trustedservices.intel.com/documentation> for the API.
+
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// The FMSPC of the recorded TCB info
+pub const FMSPC: &str = "00906ED50000";
+
+const ROOT_CA: &str = include_str!("../../data/tests/root_ca.pem");
+const PROCESSOR_CA: &str = include_str!("../../data/tests/processor_ca.pem");
+const TCB_SIGNER: &str = include_str!("../../data/tests/tcb_signer.pem");
+const TCB_INFO_JSON: &str = include_str!("../../data/tests/fmspc_00906ED50000_2023_07_12.json");
+const QE_IDENTITY_JSON: &str = include_str!("../../data/tests/qe_identity.json");
+const PROCESSOR_CRL: &[u8] = include_bytes!("../../data/tests/processor_crl.der");
+const ROOT_CRL: &[u8] = include_bytes!("../../data/tests/root_crl.der");
+
+/// A fault the mock answers a request with, instead of the recorded response
+#[derive(Clone, Copy, Debug)]
+pub enum Fault {
+    /// Respond with the HTTP status, and an empty body
+    Status(u16),
+    /// Respond without the issuer chain header
+    MissingIssuerChain,
+}
+
+/// A running mock PCCS, stopped when dropped
+#[derive(Debug)]
+pub struct MockPccs {
+    address: SocketAddr,
+    requests: Arc<Mutex<Vec<String>>>,
+    shutdown: Arc<AtomicBool>,
+    server: Option<JoinHandle<()>>,
+}
+
+impl MockPccs {
+    /// Start the mock on an ephemeral local port
+    pub fn start() -> Self {
+        Self::start_with_faults([])
+    }
+
+    /// Start the mock, answering the first requests with the `faults`, in
+    /// order, before serving the recorded responses
+    pub fn start_with_faults(faults: impl IntoIterator<Item = Fault>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock PCCS");
+        let address = listener
+            .local_addr()
+            .expect("Failed to get mock PCCS address");
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let faults = Mutex::new(faults.into_iter().collect());
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let server = {
+            let requests = requests.clone();
+            let shutdown = shutdown.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    if let Ok(stream) = stream {
+                        serve(stream, &requests, &faults);
+                    }
+                }
+            })
+        };
+
+        Self {
+            address,
+            requests,
+            shutdown,
+            server: Some(server),
+        }
+    }
+
+    /// The base URL of the service, the equivalent of
+    /// `https://api.trustedservices.intel.com`
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// The paths, with the query, of the requests received so far
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().expect("Mock PCCS panicked").clone()
+    }
+}
+
+impl Drop for MockPccs {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the listener so it sees the shutdown
+        let _ = TcpStream::connect(self.address);
+        if let Some(server) = self.server.take() {
+            let _ = server.join();
+        }
+    }
+}
+
+fn serve(stream: TcpStream, requests: &Mutex<Vec<String>>, faults: &Mutex<VecDeque<Fault>>) {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // Skip the request headers
+    let mut line = String::new();
+    while reader.read_line(&mut line).is_ok() && !matches!(line.as_str(), "\r\n" | "\n" | "") {
+        line.clear();
+    }
+
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+    requests
+        .lock()
+        .expect("Mock PCCS panicked")
+        .push(path.clone());
+
+    let fault = faults.lock().expect("Mock PCCS panicked").pop_front();
+    let response = match fault {
+        None => respond(&path),
+        Some(Fault::Status(status)) => Response::error(status),
+        Some(Fault::MissingIssuerChain) => Response {
+            headers: Vec::new(),
+            ..respond(&path)
+        },
+    };
+    let mut writer = &stream;
+    let _ = write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        if response.status == 200 {
+            "OK"
+        } else {
+            "Error"
+        },
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        let _ = write!(writer, "{name}: {value}\r\n");
+    }
+    let _ = writer.write_all(b"\r\n");
+    let _ = writer.write_all(&response.body);
+}
+
+fn respond(path: &str) -> Response {
+    let Some(path) = path.strip_prefix("/sgx/certification/v4/") else {
+        return Response::not_found();
+    };
+    let tcb_chain = [TCB_SIGNER, ROOT_CA].join("\n");
+    match path {
+        "pckcrl?ca=processor&encoding=der" => Response::ok(PROCESSOR_CRL).with_chain(
+            "SGX-PCK-CRL-Issuer-Chain",
+            &[PROCESSOR_CA, ROOT_CA].join("\n"),
+        ),
+        "rootcacrl" => Response::ok(ROOT_CRL),
+        "qe/identity" => Response::ok(QE_IDENTITY_JSON.as_bytes())
+            .with_chain("SGX-Enclave-Identity-Issuer-Chain", &tcb_chain),
+        _ if path == format!("tcb?fmspc={FMSPC}") => {
+            Response::ok(TCB_INFO_JSON.as_bytes()).with_chain("TCB-Info-Issuer-Chain", &tcb_chain)
+        }
+        _ => Response::not_found(),
+    }
+}
+
+/// An HTTP response
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    fn ok(body: &[u8]) -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body: body.to_vec(),
+        }
+    }
+
+    fn not_found() -> Self {
+        Self::error(404)
+    }
+
+    fn error(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    fn with_chain(mut self, name: &str, pem_chain: &str) -> Self {
+        self.headers
+            .push((name.to_string(), percent_encode(pem_chain)));
+        self
+    }
+}
+
+/// A minimal HTTP/1.1 GET, enough to talk to the mock
+pub fn get(url: &str) -> std::io::Result<Response> {
+    let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidInput, url);
+    let rest = url.strip_prefix("http://").ok_or_else(invalid)?;
+    let (host, path) = rest.split_at(rest.find('/').ok_or_else(invalid)?);
+
+    let mut stream = TcpStream::connect(host)?;
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n\r\n"
+    )?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(invalid)?;
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let mut body = Vec::new();
+    reader.read_to_end(&mut body)?;
+    Ok(Response {
+        status,
+        headers,
+        body,
+    })
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
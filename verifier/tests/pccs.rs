@@ -0,0 +1,320 @@
+// Copyright (c) 2023-2024 The MobileCoin Foundation
+
+//! End to end tests of fetching collateral from a PCCS and verifying evidence
+//! with it, against the in-process [`MockPccs`].
+
+#![cfg(feature = "pcs")]
+
+mod mock_pccs;
+
+use assert_matches::assert_matches;
+use der::DecodePem;
+use mc_attestation_verifier::{
+    CollateralPrefetcher, CollateralProvider, Evidence, EvidenceVerifier, Fmspc,
+    MbedTlsCertificateChainVerifier, MbedTlsError, PckCaType, PcsClient, RetryPolicy, TeeType,
+    TrustAnchor, TrustedMrEnclaveIdentity, Verifier,
+};
+use mc_sgx_core_types::MrEnclave;
+use mc_sgx_dcap_types::{CertificationData, Quote3, TcbInfo as PckTcb};
+use mock_pccs::{get, Fault, MockPccs, FMSPC};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use x509_cert::Certificate;
+
+const QUOTE: &[u8] = include_bytes!("../data/tests/hw_quote.dat");
+const ROOT_CA: &str = include_str!("../data/tests/root_ca.pem");
+const PCK_CRL_PATH: &str = "/sgx/certification/v4/pckcrl?ca=processor&encoding=der";
+
+/// The certificate chain in the quote, starting with the PCK certificate
+fn quote_certificate_chain(quote: &Quote3<&[u8]>) -> Vec<Certificate> {
+    let signature_data = quote.signature_data();
+    let CertificationData::PckCertificateChain(pem_chain) = signature_data.certification_data()
+    else {
+        panic!("Quote is missing the PCK certificate chain");
+    };
+    pem_chain
+        .into_iter()
+        .map(|pem| Certificate::from_pem(pem).expect("Failed to parse certificate"))
+        .collect()
+}
+
+fn quote_fmspc(quote: &Quote3<&[u8]>) -> Fmspc {
+    let chain = quote_certificate_chain(quote);
+    let pck_tcb = PckTcb::try_from(&chain[0]).expect("Failed to get the PCK TCB");
+    Fmspc::new(*pck_tcb.fmspc())
+}
+
+/// A client for the `pccs` which retries twice, without waiting long
+fn retrying_client(pccs: &MockPccs) -> PcsClient {
+    let retry_policy = RetryPolicy::new()
+        .with_max_retries(2)
+        .with_initial_backoff(Duration::from_millis(1))
+        .with_max_backoff(Duration::from_millis(1));
+    PcsClient::with_base_url(pccs.base_url()).with_retry_policy(retry_policy)
+}
+
+fn wait_for<T>(mut poll: impl FnMut() -> Option<T>) -> T {
+    let start = Instant::now();
+    loop {
+        if let Some(value) = poll() {
+            return value;
+        }
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "Timed out waiting for the mock PCCS"
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn unknown_fmspc_is_not_found() {
+    let pccs = MockPccs::start();
+    let response = get(&format!(
+        "{}/sgx/certification/v4/tcb?fmspc=000000000000",
+        pccs.base_url()
+    ))
+    .expect("Failed to GET TCB info");
+    assert_eq!(response.status, 404);
+}
+
+#[test]
+fn fetch_collateral_for_quote() {
+    let pccs = MockPccs::start();
+    let quote = Quote3::try_from(QUOTE).expect("Failed to parse quote");
+    let fmspc = quote_fmspc(&quote);
+    assert_eq!(fmspc.to_string(), FMSPC);
+
+    let collateral = PcsClient::with_base_url(pccs.base_url())
+        .fetch_collateral(PckCaType::Processor, &fmspc, TeeType::Sgx)
+        .expect("Failed to fetch");
+
+    assert_eq!(collateral.tcb_info_issuer_chain().len(), 2);
+    assert_eq!(collateral.qe_identity_issuer_chain().len(), 2);
+    assert_eq!(collateral.pck_crl_issuer_chain().len(), 2);
+    assert!(Evidence::new(quote, collateral).is_ok());
+    assert_eq!(
+        pccs.requests(),
+        [
+            PCK_CRL_PATH.to_string(),
+            "/sgx/certification/v4/rootcacrl".to_string(),
+            format!("/sgx/certification/v4/tcb?fmspc={FMSPC}"),
+            "/sgx/certification/v4/qe/identity".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn prefetch_collateral_from_pccs() {
+    let pccs = MockPccs::start();
+    let client = PcsClient::with_base_url(pccs.base_url());
+    let prefetcher = CollateralPrefetcher::spawn(
+        move |fmspc: &Fmspc| client.fetch_collateral(PckCaType::Processor, fmspc, TeeType::Sgx),
+        1,
+        Duration::from_secs(60),
+    );
+    let quote = Quote3::try_from(QUOTE).expect("Failed to parse quote");
+    let fmspc = quote_fmspc(&quote);
+
+    let collateral = wait_for(|| prefetcher.collateral(&fmspc));
+
+    assert_eq!(prefetcher.error(&fmspc), None);
+    assert!(Evidence::new(quote, collateral).is_ok());
+}
+
+#[test]
+fn prefetch_unknown_fmspc_reports_error() {
+    let pccs = MockPccs::start();
+    let client = PcsClient::with_base_url(pccs.base_url());
+    let prefetcher = CollateralPrefetcher::spawn(
+        move |fmspc: &Fmspc| client.fetch_collateral(PckCaType::Processor, fmspc, TeeType::Sgx),
+        1,
+        Duration::from_secs(60),
+    );
+    let fmspc = Fmspc::new([0; 6]);
+    prefetcher.observe(fmspc);
+
+    let error = wait_for(|| prefetcher.error(&fmspc));
+
+    assert!(
+        error.ends_with("HTTP status 404"),
+        "unexpected error: {error}"
+    );
+    assert!(prefetcher.collateral(&fmspc).is_none());
+}
+
+#[test]
+fn pcs_client_fetches_root_ca_crl_from_base_url() {
+    use der::Encode;
+
+    let pccs = MockPccs::start();
+    let client = PcsClient::with_base_url(pccs.base_url());
+
+    let crl = client.root_ca_crl().expect("Failed to fetch root CA CRL");
+
+    assert_eq!(
+        crl.to_der().expect("Failed to encode CRL"),
+        include_bytes!("../data/tests/root_crl.der")
+    );
+    assert_eq!(pccs.requests(), ["/sgx/certification/v4/rootcacrl"]);
+}
+
+#[test]
+fn fetch_and_verify_evidence() {
+    let pccs = MockPccs::start();
+    let quote = Quote3::try_from(QUOTE).expect("Failed to parse quote");
+    let collateral = PcsClient::with_base_url(pccs.base_url())
+        .fetch_collateral(PckCaType::Processor, &quote_fmspc(&quote), TeeType::Sgx)
+        .expect("Failed to fetch");
+    let evidence: Evidence<Vec<u8>> = Evidence::new(quote, collateral)
+        .expect("Failed to create evidence")
+        .into();
+
+    let trust_anchor = TrustAnchor::try_from_pem(ROOT_CA).expect("Failed to parse root CA");
+    let certificate_verifier = MbedTlsCertificateChainVerifier::new(trust_anchor);
+    let mr_enclave = MrEnclave::from([
+        0x84, 0x0d, 0x61, 0xb0, 0x58, 0x5d, 0xc8, 0xb4, 0xdc, 0x90, 0xf5, 0x3a, 0xf2, 0x93, 0xc7,
+        0x60, 0xfd, 0xa0, 0x6b, 0xee, 0x75, 0x97, 0x8a, 0x6a, 0x86, 0x26, 0x3f, 0xfb, 0x29, 0x64,
+        0x23, 0xf4,
+    ]);
+    let identity = TrustedMrEnclaveIdentity::new(
+        mr_enclave,
+        [] as [&str; 0],
+        ["INTEL-SA-00334", "INTEL-SA-00615"],
+    );
+    let time = "2023-07-12T20:48:25Z"
+        .parse::<der::DateTime>()
+        .expect("Failed to parse time");
+    let verifier = EvidenceVerifier::new(certificate_verifier, [identity], time);
+
+    let verification = verifier.verify(&evidence);
+
+    assert_eq!(verification.is_success().unwrap_u8(), 1);
+}
+
+#[test]
+fn fetch_pck_crl_from_pccs() {
+    let pccs = MockPccs::start();
+    let client = PcsClient::with_base_url(pccs.base_url());
+
+    let pck_crl = client
+        .fetch_pck_crl(PckCaType::Processor)
+        .expect("Failed to fetch PCK CRL");
+
+    assert!(pck_crl.crl().delta_crl_uris().is_empty());
+    assert_eq!(pccs.requests(), [PCK_CRL_PATH]);
+}
+
+#[test]
+fn transient_errors_are_retried() {
+    for status in [429, 500, 503] {
+        let pccs = MockPccs::start_with_faults([Fault::Status(status); 2]);
+
+        let pck_crl = retrying_client(&pccs).fetch_pck_crl(PckCaType::Processor);
+
+        assert!(pck_crl.is_ok(), "status {status} was not retried");
+        assert_eq!(pccs.requests(), [PCK_CRL_PATH; 3]);
+    }
+}
+
+#[test]
+fn transient_errors_are_retried_up_to_max_retries() {
+    let pccs = MockPccs::start_with_faults([Fault::Status(503); 3]);
+
+    let pck_crl = retrying_client(&pccs).fetch_pck_crl(PckCaType::Processor);
+
+    assert_matches!(
+        pck_crl,
+        Err(MbedTlsError::PcsUnavailable { status: 503, .. })
+    );
+    assert_eq!(pccs.requests(), [PCK_CRL_PATH; 3]);
+}
+
+#[test]
+fn client_errors_are_not_retried() {
+    for status in [400, 401, 404] {
+        let pccs = MockPccs::start_with_faults([Fault::Status(status)]);
+
+        let pck_crl = retrying_client(&pccs).fetch_pck_crl(PckCaType::Processor);
+
+        assert_matches!(pck_crl, Err(MbedTlsError::PcsRequest { status: s, .. }) if s == status);
+        assert_eq!(pccs.requests(), [PCK_CRL_PATH]);
+    }
+}
+
+#[test]
+fn missing_issuer_chain_is_not_retried() {
+    let pccs = MockPccs::start_with_faults([Fault::MissingIssuerChain]);
+
+    let pck_crl = retrying_client(&pccs).fetch_pck_crl(PckCaType::Processor);
+
+    assert_matches!(pck_crl, Err(MbedTlsError::MissingIssuerChain { .. }));
+    assert_eq!(pccs.requests(), [PCK_CRL_PATH]);
+}
+
+#[cfg(feature = "tokio")]
+mod async_pcs {
+    use super::*;
+    use mc_attestation_verifier::AsyncPcsClient;
+
+    fn retrying_client(pccs: &MockPccs) -> AsyncPcsClient {
+        AsyncPcsClient::from(super::retrying_client(pccs))
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried() {
+        for status in [429, 500, 503] {
+            let pccs = MockPccs::start_with_faults([Fault::Status(status); 2]);
+
+            let pck_crl = retrying_client(&pccs)
+                .fetch_pck_crl(PckCaType::Processor)
+                .await;
+
+            assert!(pck_crl.is_ok(), "status {status} was not retried");
+            assert_eq!(pccs.requests(), [PCK_CRL_PATH; 3]);
+        }
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried_up_to_max_retries() {
+        let pccs = MockPccs::start_with_faults([Fault::Status(503); 3]);
+
+        let pck_crl = retrying_client(&pccs)
+            .fetch_pck_crl(PckCaType::Processor)
+            .await;
+
+        assert_matches!(
+            pck_crl,
+            Err(MbedTlsError::PcsUnavailable { status: 503, .. })
+        );
+        assert_eq!(pccs.requests(), [PCK_CRL_PATH; 3]);
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        for status in [400, 401, 404] {
+            let pccs = MockPccs::start_with_faults([Fault::Status(status)]);
+
+            let pck_crl = retrying_client(&pccs)
+                .fetch_pck_crl(PckCaType::Processor)
+                .await;
+
+            assert_matches!(pck_crl, Err(MbedTlsError::PcsRequest { status: s, .. }) if s == status);
+            assert_eq!(pccs.requests(), [PCK_CRL_PATH]);
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_issuer_chain_is_not_retried() {
+        let pccs = MockPccs::start_with_faults([Fault::MissingIssuerChain]);
+
+        let pck_crl = retrying_client(&pccs)
+            .fetch_pck_crl(PckCaType::Processor)
+            .await;
+
+        assert_matches!(pck_crl, Err(MbedTlsError::MissingIssuerChain { .. }));
+        assert_eq!(pccs.requests(), [PCK_CRL_PATH]);
+    }
+}